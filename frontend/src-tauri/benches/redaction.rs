@@ -0,0 +1,27 @@
+//! Benchmarks for log redaction, which runs on every line of service output
+
+use app_lib::secrets::redact_env_vars;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+const CLEAN_LINE: &str =
+    "info: Microsoft.Hosting.Lifetime[14] Now listening on: http://localhost:5001";
+
+const SECRET_LINE: &str = "warn: Request failed with Authorization: Bearer \
+    eyJhbGciOiJIUzI1NiJ9.eyJzdWIiOiIxIn0.abc OPENAI_API_KEY=sk-proj-abcdefghijklmnopqrstuvwxyz0123456789";
+
+fn bench_redaction(c: &mut Criterion) {
+    let long_line = CLEAN_LINE.repeat(50);
+
+    c.bench_function("redact clean line", |b| {
+        b.iter(|| redact_env_vars(black_box(CLEAN_LINE)))
+    });
+    c.bench_function("redact line with secrets", |b| {
+        b.iter(|| redact_env_vars(black_box(SECRET_LINE)))
+    });
+    c.bench_function("redact long clean line", |b| {
+        b.iter(|| redact_env_vars(black_box(&long_line)))
+    });
+}
+
+criterion_group!(benches, bench_redaction);
+criterion_main!(benches);
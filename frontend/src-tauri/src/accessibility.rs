@@ -0,0 +1,249 @@
+//! Screen reader announcements for service state.
+//!
+//! This module provides:
+//! - An announcement when services start, become ready, stop answering or
+//!   fail, so screen reader users aren't left waiting on the splash screen
+//! - Native delivery: `NSAccessibilityAnnouncementRequestedNotification` for
+//!   VoiceOver on macOS and a UI Automation notification event for
+//!   Narrator and NVDA on Windows
+//!
+//! Each state is announced once when it's entered. Other platforms have no
+//! announcement API, so state changes are only logged there.
+
+use parking_lot::Mutex;
+use tauri::{AppHandle, Manager};
+
+use crate::health::HealthMonitor;
+use crate::services::{ServiceManager, ServicePhase, ServiceState};
+
+/// A service state worth announcing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Announcement {
+    Starting,
+    Ready,
+    /// Running but failing health checks
+    Degraded,
+    DatabaseFailed,
+    BackendFailed,
+}
+
+impl Announcement {
+    /// The announcement for a service state, if it has one
+    pub fn for_state(state: &ServiceState, healthy: bool) -> Option<Self> {
+        match (state.postgres, state.backend) {
+            (ServicePhase::Failed, _) => Some(Self::DatabaseFailed),
+            (_, ServicePhase::Failed) => Some(Self::BackendFailed),
+            (ServicePhase::Stopping, _) | (_, ServicePhase::Stopping) => None,
+            (ServicePhase::Running, ServicePhase::Running) if healthy => Some(Self::Ready),
+            (ServicePhase::Running, ServicePhase::Running) => Some(Self::Degraded),
+            (ServicePhase::Stopped, ServicePhase::Stopped) => None,
+            _ => Some(Self::Starting),
+        }
+    }
+
+    pub fn message(self) -> &'static str {
+        match self {
+            Self::Starting => "Second Brain is starting",
+            Self::Ready => "Second Brain is ready",
+            Self::Degraded => "Second Brain is not responding. Trying to reconnect",
+            Self::DatabaseFailed => "Second Brain could not start its database",
+            Self::BackendFailed => "Second Brain stopped unexpectedly",
+        }
+    }
+
+    /// Failures interrupt whatever the screen reader is saying
+    fn is_urgent(self) -> bool {
+        matches!(self, Self::DatabaseFailed | Self::BackendFailed)
+    }
+}
+
+/// The last state seen, kept in Tauri state
+#[derive(Default)]
+pub struct Announcer {
+    last: Mutex<Option<Announcement>>,
+}
+
+impl Announcer {
+    /// Record the current announcement; returns it if it changed
+    fn update(&self, current: Option<Announcement>) -> Option<Announcement> {
+        let previous = std::mem::replace(&mut *self.last.lock(), current);
+        current.filter(|_| current != previous)
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn post(_app: &AppHandle, announcement: Announcement) {
+    use objc2::runtime::AnyObject;
+    use objc2::MainThreadMarker;
+    use objc2_app_kit::{
+        NSAccessibilityAnnouncementKey, NSAccessibilityAnnouncementRequestedNotification,
+        NSAccessibilityPostNotificationWithUserInfo, NSAccessibilityPriorityKey,
+        NSAccessibilityPriorityLevel, NSApplication,
+    };
+    use objc2_foundation::{NSDictionary, NSNumber, NSString};
+
+    // Only called from the main thread, see `announce`
+    let mtm = unsafe { MainThreadMarker::new_unchecked() };
+    let app = NSApplication::sharedApplication(mtm);
+    let text = NSString::from_str(announcement.message());
+    let priority = NSNumber::new_isize(if announcement.is_urgent() {
+        NSAccessibilityPriorityLevel::High.0
+    } else {
+        NSAccessibilityPriorityLevel::Medium.0
+    });
+    let (text, priority): (&AnyObject, &AnyObject) = (&text, &priority);
+    unsafe {
+        let info = NSDictionary::from_slices(
+            &[NSAccessibilityAnnouncementKey, NSAccessibilityPriorityKey],
+            &[text, priority],
+        );
+        NSAccessibilityPostNotificationWithUserInfo(
+            &app,
+            NSAccessibilityAnnouncementRequestedNotification,
+            Some(&info),
+        );
+    }
+}
+
+#[cfg(windows)]
+fn post(app: &AppHandle, announcement: Announcement) {
+    use windows::core::BSTR;
+    use windows::Win32::UI::Accessibility::{
+        NotificationKind_Other, NotificationProcessing_ImportantMostRecent,
+        NotificationProcessing_MostRecent, UiaHostProviderFromHwnd, UiaRaiseNotificationEvent,
+    };
+
+    // Notifications are raised from a window, so there's nothing to say
+    // before the main window exists
+    let Some(window) = app.get_webview_window("main") else {
+        return;
+    };
+    let hwnd = match window.hwnd() {
+        Ok(hwnd) => hwnd,
+        Err(e) => {
+            tracing::warn!("Failed to get main window handle: {}", e);
+            return;
+        }
+    };
+    let processing = if announcement.is_urgent() {
+        NotificationProcessing_ImportantMostRecent
+    } else {
+        NotificationProcessing_MostRecent
+    };
+    let result = unsafe {
+        UiaHostProviderFromHwnd(hwnd).and_then(|provider| {
+            UiaRaiseNotificationEvent(
+                &provider,
+                NotificationKind_Other,
+                processing,
+                &BSTR::from(announcement.message()),
+                // Newer announcements replace older ones still queued
+                &BSTR::from("second-brain-service-state"),
+            )
+        })
+    };
+    if let Err(e) = result {
+        tracing::warn!("Failed to raise accessibility notification: {}", e);
+    }
+}
+
+#[cfg(not(any(target_os = "macos", windows)))]
+fn post(_app: &AppHandle, _announcement: Announcement) {}
+
+/// Announce to the screen reader from the main thread
+fn announce(app: &AppHandle, announcement: Announcement) {
+    tracing::info!("Announcing: {}", announcement.message());
+    let handle = app.clone();
+    if let Err(e) = app.run_on_main_thread(move || post(&handle, announcement)) {
+        tracing::warn!("Failed to schedule accessibility announcement: {}", e);
+    }
+}
+
+/// Announce the current service state if it changed
+pub fn refresh(app: &AppHandle) {
+    let (Some(services), Some(announcer)) = (
+        app.try_state::<ServiceManager>(),
+        app.try_state::<Announcer>(),
+    ) else {
+        return;
+    };
+    let healthy = app
+        .try_state::<HealthMonitor>()
+        .map_or(true, |monitor| monitor.is_healthy());
+    let current = Announcement::for_state(&services.subscribe().borrow(), healthy);
+    if let Some(announcement) = announcer.update(current) {
+        announce(app, announcement);
+    }
+}
+
+/// Announce service state changes for as long as the app runs
+pub fn start(app: &AppHandle) {
+    app.manage(Announcer::default());
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut services = app.state::<ServiceManager>().subscribe();
+        loop {
+            refresh(&app);
+            if services.changed().await.is_err() {
+                break;
+            }
+        }
+    });
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(postgres: ServicePhase, backend: ServicePhase) -> ServiceState {
+        ServiceState {
+            postgres,
+            backend,
+            ..ServiceState::default()
+        }
+    }
+
+    #[test]
+    fn test_for_state() {
+        use ServicePhase::*;
+        let cases = [
+            (Running, Starting, true, Some(Announcement::Starting)),
+            (Running, Running, true, Some(Announcement::Ready)),
+            (Running, Running, false, Some(Announcement::Degraded)),
+            (Failed, Stopped, true, Some(Announcement::DatabaseFailed)),
+            (Running, Failed, true, Some(Announcement::BackendFailed)),
+            (Running, Stopping, true, None),
+            (Stopped, Stopped, true, None),
+        ];
+        for (postgres, backend, healthy, expected) in cases {
+            assert_eq!(
+                Announcement::for_state(&state(postgres, backend), healthy),
+                expected,
+                "{:?} {:?} {}",
+                postgres,
+                backend,
+                healthy
+            );
+        }
+    }
+
+    #[test]
+    fn test_announces_each_state_once() {
+        let announcer = Announcer::default();
+        let starting = Some(Announcement::Starting);
+        let ready = Some(Announcement::Ready);
+
+        assert_eq!(announcer.update(starting), starting);
+        assert_eq!(announcer.update(starting), None);
+        assert_eq!(announcer.update(ready), ready);
+        assert_eq!(announcer.update(ready), None);
+
+        // A restart is announced again
+        assert_eq!(announcer.update(None), None);
+        assert_eq!(announcer.update(starting), starting);
+    }
+}
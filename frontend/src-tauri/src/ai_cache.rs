@@ -0,0 +1,337 @@
+//! Content-addressed response cache for idempotent AI operations.
+//!
+//! This module provides:
+//! - Request hashing (method + path + canonical JSON body)
+//! - On-disk response storage under the app data directory
+//! - TTL expiry and a total size cap with oldest-first eviction
+//! - Hit/miss statistics for diagnostics
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime};
+
+/// Default time-to-live for cached responses (7 days)
+pub const DEFAULT_TTL_SECS: u64 = 7 * 24 * 60 * 60;
+
+/// Default total size cap for the cache (256 MB)
+pub const DEFAULT_MAX_BYTES: u64 = 256 * 1024 * 1024;
+
+/// Backend API paths whose responses are deterministic for a given request
+const CACHEABLE_PATH_MARKERS: &[&str] = &["/embeddings", "/summarize", "/summary"];
+
+/// Cache statistics surfaced in diagnostics
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AiCacheStats {
+    /// Number of lookups served from the cache
+    pub hits: u64,
+    /// Number of lookups that missed
+    pub misses: u64,
+    /// Number of entries currently on disk
+    pub entries: usize,
+    /// Total size of cached responses in bytes
+    pub total_bytes: u64,
+    /// Configured size cap in bytes
+    pub max_bytes: u64,
+    /// Configured TTL in seconds
+    pub ttl_secs: u64,
+}
+
+/// Disk-backed response cache keyed by request hash
+pub struct AiCache {
+    dir: PathBuf,
+    ttl: Duration,
+    max_bytes: u64,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl AiCache {
+    /// Create a cache rooted at `<app_data_dir>/ai-cache` with default limits
+    pub fn new(app_data_dir: &Path) -> Self {
+        Self::with_limits(
+            app_data_dir.join("ai-cache"),
+            Duration::from_secs(DEFAULT_TTL_SECS),
+            DEFAULT_MAX_BYTES,
+        )
+    }
+
+    /// Create a cache with explicit limits
+    pub fn with_limits(dir: PathBuf, ttl: Duration, max_bytes: u64) -> Self {
+        Self {
+            dir,
+            ttl,
+            max_bytes,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Whether a request to `path` is safe to cache
+    pub fn is_cacheable(path: &str) -> bool {
+        let path = path.to_ascii_lowercase();
+        CACHEABLE_PATH_MARKERS
+            .iter()
+            .any(|marker| path.contains(marker))
+    }
+
+    /// Compute the content address for a request
+    pub fn request_key(method: &str, path: &str, body: Option<&serde_json::Value>) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(method.to_ascii_uppercase().as_bytes());
+        hasher.update(b"\n");
+        hasher.update(path.as_bytes());
+        hasher.update(b"\n");
+        if let Some(body) = body {
+            // serde_json::Value maps are sorted, so this is canonical
+            hasher.update(body.to_string().as_bytes());
+        }
+        hasher
+            .finalize()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect()
+    }
+
+    fn entry_path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", key))
+    }
+
+    /// Look up a cached response, counting the hit or miss
+    pub fn get(&self, key: &str) -> Option<serde_json::Value> {
+        let path = self.entry_path(key);
+
+        let fresh = fs::metadata(&path)
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+            .map(|age| age <= self.ttl)
+            .unwrap_or(false);
+
+        if !fresh {
+            if path.exists() {
+                let _ = fs::remove_file(&path);
+            }
+            self.misses.fetch_add(1, Ordering::Relaxed);
+            return None;
+        }
+
+        match fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+        {
+            Some(value) => {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                Some(value)
+            }
+            None => {
+                let _ = fs::remove_file(&path);
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+        }
+    }
+
+    /// Store a response and enforce the size cap
+    pub fn put(&self, key: &str, value: &serde_json::Value) -> Result<(), String> {
+        fs::create_dir_all(&self.dir)
+            .map_err(|e| format!("Failed to create AI cache directory: {}", e))?;
+
+        let path = self.entry_path(key);
+        let temp_path = self.dir.join(format!(".{}.tmp", key));
+
+        fs::write(&temp_path, value.to_string())
+            .map_err(|e| format!("Failed to write AI cache entry: {}", e))?;
+        fs::rename(&temp_path, &path)
+            .map_err(|e| format!("Failed to rename AI cache entry: {}", e))?;
+
+        self.evict_to_fit();
+        Ok(())
+    }
+
+    /// Cached entries with their size and modification time
+    fn entries(&self) -> Vec<(PathBuf, u64, SystemTime)> {
+        let Ok(read_dir) = fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+
+        read_dir
+            .filter_map(|e| e.ok())
+            .filter(|e| {
+                e.path()
+                    .extension()
+                    .map(|ext| ext == "json")
+                    .unwrap_or(false)
+            })
+            .filter_map(|e| {
+                let metadata = e.metadata().ok()?;
+                let modified = metadata.modified().ok()?;
+                Some((e.path(), metadata.len(), modified))
+            })
+            .collect()
+    }
+
+    /// Remove expired entries, then the oldest entries until under the size cap
+    fn evict_to_fit(&self) {
+        let now = SystemTime::now();
+        let mut entries: Vec<_> = self
+            .entries()
+            .into_iter()
+            .filter(|(path, _, modified)| {
+                let expired = now
+                    .duration_since(*modified)
+                    .map(|age| age > self.ttl)
+                    .unwrap_or(false);
+                if expired {
+                    let _ = fs::remove_file(path);
+                }
+                !expired
+            })
+            .collect();
+
+        let mut total: u64 = entries.iter().map(|(_, size, _)| size).sum();
+        if total <= self.max_bytes {
+            return;
+        }
+
+        // Oldest first
+        entries.sort_by_key(|(_, _, modified)| *modified);
+        for (path, size, _) in entries {
+            if total <= self.max_bytes {
+                break;
+            }
+            if fs::remove_file(&path).is_ok() {
+                total = total.saturating_sub(size);
+            }
+        }
+    }
+
+    /// Remove every cached entry, returning how many were removed
+    pub fn clear(&self) -> Result<usize, String> {
+        let entries = self.entries();
+        let count = entries.len();
+        for (path, _, _) in entries {
+            fs::remove_file(&path)
+                .map_err(|e| format!("Failed to remove AI cache entry: {}", e))?;
+        }
+        self.hits.store(0, Ordering::Relaxed);
+        self.misses.store(0, Ordering::Relaxed);
+        tracing::info!("Cleared {} AI cache entries", count);
+        Ok(count)
+    }
+
+    /// Current cache statistics
+    pub fn stats(&self) -> AiCacheStats {
+        let entries = self.entries();
+        AiCacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            entries: entries.len(),
+            total_bytes: entries.iter().map(|(_, size, _)| size).sum(),
+            max_bytes: self.max_bytes,
+            ttl_secs: self.ttl.as_secs(),
+        }
+    }
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    fn test_cache(dir: &TempDir, max_bytes: u64) -> AiCache {
+        AiCache::with_limits(
+            dir.path().join("ai-cache"),
+            Duration::from_secs(60),
+            max_bytes,
+        )
+    }
+
+    #[test]
+    fn test_request_key_is_stable() {
+        let body = json!({"b": 1, "a": "text"});
+        let key1 = AiCache::request_key("post", "/embeddings", Some(&body));
+        let key2 = AiCache::request_key("POST", "/embeddings", Some(&body));
+        assert_eq!(key1, key2);
+        assert_eq!(key1.len(), 64);
+    }
+
+    #[test]
+    fn test_request_key_differs_by_body() {
+        let key1 = AiCache::request_key("POST", "/embeddings", Some(&json!({"input": "a"})));
+        let key2 = AiCache::request_key("POST", "/embeddings", Some(&json!({"input": "b"})));
+        assert_ne!(key1, key2);
+    }
+
+    #[test]
+    fn test_is_cacheable() {
+        assert!(AiCache::is_cacheable("/ai/embeddings"));
+        assert!(AiCache::is_cacheable("/notes/123/summarize"));
+        assert!(!AiCache::is_cacheable("/notes"));
+    }
+
+    #[test]
+    fn test_put_and_get_counts_hits_and_misses() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache = test_cache(&temp_dir, DEFAULT_MAX_BYTES);
+
+        assert!(cache.get("missing").is_none());
+
+        cache.put("abc", &json!({"vector": [1, 2, 3]})).unwrap();
+        assert_eq!(cache.get("abc"), Some(json!({"vector": [1, 2, 3]})));
+
+        let stats = cache.stats();
+        assert_eq!(stats.hits, 1);
+        assert_eq!(stats.misses, 1);
+        assert_eq!(stats.entries, 1);
+    }
+
+    #[test]
+    fn test_expired_entry_is_a_miss() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache = AiCache::with_limits(
+            temp_dir.path().join("ai-cache"),
+            Duration::from_secs(0),
+            DEFAULT_MAX_BYTES,
+        );
+
+        cache.put("abc", &json!("value")).unwrap();
+        std::thread::sleep(Duration::from_millis(1100));
+
+        assert!(cache.get("abc").is_none());
+        assert_eq!(cache.stats().entries, 0);
+    }
+
+    #[test]
+    fn test_size_cap_evicts_oldest() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache = test_cache(&temp_dir, 20);
+
+        cache.put("first", &json!("0123456789")).unwrap();
+        std::thread::sleep(Duration::from_millis(20));
+        cache.put("second", &json!("0123456789")).unwrap();
+
+        let stats = cache.stats();
+        assert_eq!(stats.entries, 1);
+        assert!(cache.get("second").is_some());
+    }
+
+    #[test]
+    fn test_clear_removes_entries() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache = test_cache(&temp_dir, DEFAULT_MAX_BYTES);
+
+        cache.put("a", &json!(1)).unwrap();
+        cache.put("b", &json!(2)).unwrap();
+
+        assert_eq!(cache.clear().unwrap(), 2);
+        assert_eq!(cache.stats().entries, 0);
+    }
+}
@@ -0,0 +1,170 @@
+//! Backend API URL announcements for external tools.
+//!
+//! This module provides:
+//! - The backend API URL for a port, shared by everything that hands it out
+//! - `api-endpoint.json` in the app data directory, rewritten whenever the
+//!   backend becomes ready, so tools that aren't running can find it later
+//! - An `api-url-changed` event with the old and new URLs when a port
+//!   conflict moved the backend, relayed to event bridge clients and pushed
+//!   to the browser extension by the native messaging host
+//!
+//! The previous URL survives restarts through the discovery file, so a move
+//! between launches is announced just like one after a crash.
+
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+
+use crate::config::{load_json, save_json_atomic};
+use crate::notifications::{Notice, Severity};
+
+/// Event emitted when the backend API URL changes
+pub const EVENT: &str = "api-url-changed";
+
+/// Discovery file in the app data directory
+const FILE: &str = "api-endpoint.json";
+
+/// Backend API URL for a port
+pub fn api_url(port: u16) -> String {
+    format!("http://localhost:{}/api", port)
+}
+
+/// Contents of the discovery file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApiEndpoint {
+    pub api_url: String,
+    pub port: u16,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Payload of `api-url-changed`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApiUrlChanged {
+    pub old_url: String,
+    pub new_url: String,
+    pub old_port: u16,
+    pub new_port: u16,
+}
+
+/// Port last announced, kept in Tauri state
+#[derive(Default)]
+pub struct Announced {
+    port: Mutex<Option<u16>>,
+}
+
+fn path(app_data_dir: &Path) -> PathBuf {
+    app_data_dir.join(FILE)
+}
+
+/// Endpoint last written by the app, if any
+pub fn read(app_data_dir: &Path) -> Option<ApiEndpoint> {
+    load_json(&path(app_data_dir))
+}
+
+/// Change from the previously announced port, if there was one and it differs
+pub(crate) fn change(previous: Option<u16>, port: u16) -> Option<ApiUrlChanged> {
+    let old_port = previous.filter(|old| *old != port)?;
+    Some(ApiUrlChanged {
+        old_url: api_url(old_port),
+        new_url: api_url(port),
+        old_port,
+        new_port: port,
+    })
+}
+
+/// Seed the announced port from the endpoint the last run wrote
+pub fn init(app: &AppHandle) {
+    let previous = app
+        .path()
+        .app_data_dir()
+        .ok()
+        .and_then(|dir| read(&dir))
+        .map(|endpoint| endpoint.port);
+    app.manage(Announced {
+        port: Mutex::new(previous),
+    });
+}
+
+/// Record the port the backend is serving on, announcing it if it moved
+pub fn backend_ready<R: Runtime>(app: &AppHandle<R>, port: u16) {
+    let Some(announced) = app.try_state::<Announced>() else {
+        return;
+    };
+    let changed = change(announced.port.lock().replace(port), port);
+
+    if let Ok(app_data_dir) = app.path().app_data_dir() {
+        let endpoint = ApiEndpoint {
+            api_url: api_url(port),
+            port,
+            updated_at: Utc::now(),
+        };
+        if let Err(e) = save_json_atomic(&path(&app_data_dir), &endpoint) {
+            tracing::warn!("Failed to write {}: {}", FILE, e);
+        }
+    }
+
+    let Some(changed) = changed else {
+        return;
+    };
+    tracing::warn!(
+        "Backend API moved from {} to {}",
+        changed.old_url,
+        changed.new_url
+    );
+    if let Err(e) = app.emit(EVENT, &changed) {
+        tracing::warn!("Failed to emit {}: {}", EVENT, e);
+    }
+    crate::notifications::notify(
+        app,
+        Notice::new(
+            "api_endpoint",
+            Severity::Warning,
+            "Backend moved to a new port",
+            format!(
+                "Port {} was in use, so the API is now at {}. Tools configured with the old \
+                 address need updating.",
+                changed.old_port, changed.new_url
+            ),
+        ),
+    );
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_change() {
+        assert_eq!(change(None, 5001), None);
+        assert_eq!(change(Some(5001), 5001), None);
+        assert_eq!(
+            change(Some(5001), 5002),
+            Some(ApiUrlChanged {
+                old_url: "http://localhost:5001/api".to_string(),
+                new_url: "http://localhost:5002/api".to_string(),
+                old_port: 5001,
+                new_port: 5002,
+            })
+        );
+    }
+
+    #[test]
+    fn test_read_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(read(dir.path()).is_none());
+
+        let endpoint = ApiEndpoint {
+            api_url: api_url(5003),
+            port: 5003,
+            updated_at: Utc::now(),
+        };
+        save_json_atomic(&path(dir.path()), &endpoint).unwrap();
+        assert_eq!(read(dir.path()), Some(endpoint));
+    }
+}
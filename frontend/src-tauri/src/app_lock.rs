@@ -0,0 +1,714 @@
+//! Application lock with idle timeout.
+//!
+//! This module provides:
+//! - Locking after a configurable idle period, on demand (app menu shortcut
+//!   or tray item) and at launch when the lock is enabled
+//! - Unlocking with Touch ID, Windows Hello or a passphrase; the passphrase
+//!   is stored as a salted PBKDF2 hash in the keychain
+//! - An invoke guard that refuses every command not needed to draw the lock
+//!   screen while locked, so hiding the overlay in the webview doesn't reveal
+//!   any data the shell holds
+//! - A plugin wrapper applying the same guard to plugins that read files or
+//!   the clipboard, or run programs, whose commands bypass the invoke handler
+//! - An `app-lock-changed` event the frontend blanks its windows on
+//!
+//! Idle time is measured from the last `report_app_activity` call, which the
+//! frontend makes (throttled) on keyboard and pointer input.
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+use std::time::Duration;
+use tauri::ipc::Invoke;
+use tauri::plugin::Plugin;
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::config::{load_json, save_json_atomic};
+use crate::error::AppError;
+use crate::keychain;
+
+/// Keychain account holding the passphrase hash
+const PASSPHRASE_ACCOUNT: &str = "app-lock-passphrase";
+
+/// PBKDF2 rounds for new passphrase hashes
+const PBKDF2_ITERATIONS: u32 = 210_000;
+
+/// Shortest accepted passphrase
+const MIN_PASSPHRASE_LEN: usize = 6;
+
+/// How often the idle timeout is checked
+const IDLE_CHECK: Duration = Duration::from_secs(15);
+
+/// Longest wait imposed after repeated wrong passphrases
+const MAX_FAILURE_DELAY: Duration = Duration::from_secs(30);
+
+/// Commands that keep working while locked: the lock itself, the service
+/// status the lock screen shows and hung-window recovery
+const ALLOWED_WHILE_LOCKED: &[&str] = &[
+    "get_app_lock",
+    "lock_app",
+    "unlock_app",
+    "unlock_app_with_biometrics",
+    "report_app_activity",
+    "invoke_batch",
+    "get_backend_url",
+    "is_backend_ready",
+    "get_database_status",
+    "get_service_state",
+    "get_backend_health",
+    "get_startup_metrics",
+    "get_app_version",
+    "get_profile",
+    "webview_heartbeat",
+    "reload_webview",
+    "recreate_window",
+    "report_window_route",
+];
+
+/// App lock settings
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppLockSettings {
+    pub enabled: bool,
+    /// Minutes without activity before locking; 0 locks only on demand
+    pub idle_timeout_minutes: u32,
+    /// Offer Touch ID or Windows Hello before the passphrase
+    pub biometrics: bool,
+}
+
+impl Default for AppLockSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            idle_timeout_minutes: 10,
+            biometrics: true,
+        }
+    }
+}
+
+impl AppLockSettings {
+    fn path(app_data_dir: &Path) -> PathBuf {
+        app_data_dir.join("app-lock.json")
+    }
+
+    pub fn load(app_data_dir: &Path) -> Self {
+        load_json(&Self::path(app_data_dir)).unwrap_or_default()
+    }
+
+    pub fn save(&self, app_data_dir: &Path) -> Result<(), String> {
+        save_json_atomic(&Self::path(app_data_dir), self)
+    }
+
+    fn idle_timeout(&self) -> Option<Duration> {
+        (self.enabled && self.idle_timeout_minutes > 0)
+            .then(|| Duration::from_secs(u64::from(self.idle_timeout_minutes) * 60))
+    }
+}
+
+/// Lock state, managed from launch so no command runs before the guard can
+/// see it
+#[derive(Default)]
+pub struct AppLock {
+    locked: AtomicBool,
+    /// Unix seconds of the last reported activity
+    last_activity: AtomicI64,
+    settings: Mutex<AppLockSettings>,
+    failures: Mutex<u32>,
+}
+
+impl AppLock {
+    pub fn is_locked(&self) -> bool {
+        self.locked.load(Ordering::SeqCst)
+    }
+
+    fn touch(&self) {
+        self.last_activity
+            .store(chrono::Utc::now().timestamp(), Ordering::SeqCst);
+    }
+
+    fn idle_for(&self) -> Duration {
+        let idle = chrono::Utc::now().timestamp() - self.last_activity.load(Ordering::SeqCst);
+        Duration::from_secs(idle.max(0) as u64)
+    }
+}
+
+/// Payload of `app-lock-changed`
+#[derive(Debug, Clone, Serialize)]
+struct LockChanged {
+    locked: bool,
+    /// What locked the app: `idle`, `manual` or `launch`
+    reason: Option<&'static str>,
+}
+
+fn set_locked(app: &AppHandle, locked: bool, reason: Option<&'static str>) {
+    let lock = app.state::<AppLock>();
+    if lock.locked.swap(locked, Ordering::SeqCst) == locked {
+        return;
+    }
+    if locked {
+        tracing::info!("App locked ({})", reason.unwrap_or("manual"));
+        // Slides would otherwise stay on screen over the lock
+        crate::presentation::end(app);
+    } else {
+        lock.touch();
+        *lock.failures.lock() = 0;
+        tracing::info!("App unlocked");
+    }
+    crate::events::emit_critical(app, "app-lock-changed", &LockChanged { locked, reason });
+}
+
+/// Whether a command may run while the app is locked
+fn is_allowed_while_locked(command: &str) -> bool {
+    ALLOWED_WHILE_LOCKED.contains(&command)
+}
+
+fn locked_error() -> AppError {
+    AppError::Permission("Second Brain is locked".to_string())
+}
+
+/// Whether the app is locked
+pub fn is_locked(app: &AppHandle) -> bool {
+    app.try_state::<AppLock>()
+        .is_some_and(|lock| lock.is_locked())
+}
+
+/// Refuse commands while locked, unless needed for the lock screen
+pub fn check(app: &AppHandle, command: &str) -> Result<(), AppError> {
+    if is_locked(app) && !is_allowed_while_locked(command) {
+        return Err(locked_error());
+    }
+    Ok(())
+}
+
+/// Refuse while locked, for requests that reach note content from outside
+/// the webview (control API, native messaging)
+pub fn ensure_unlocked(app: &AppHandle) -> Result<(), AppError> {
+    if is_locked(app) {
+        return Err(locked_error());
+    }
+    Ok(())
+}
+
+/// Reject an invoke while locked unless `allowed`; hands it back otherwise
+fn refuse_while_locked<R: Runtime>(
+    invoke: Invoke<R>,
+    allowed: impl Fn(&str) -> bool,
+) -> Option<Invoke<R>> {
+    let refused = invoke
+        .message
+        .webview()
+        .try_state::<AppLock>()
+        .is_some_and(|lock| lock.is_locked())
+        && !allowed(invoke.message.command());
+    if refused {
+        tracing::debug!("Refused {} while locked", invoke.message.command());
+        invoke.resolver.reject(locked_error());
+        return None;
+    }
+    Some(invoke)
+}
+
+/// Wrap the invoke handler so commands are refused while locked
+pub fn guard<R: Runtime>(
+    handler: impl Fn(Invoke<R>) -> bool + Send + Sync + 'static,
+) -> impl Fn(Invoke<R>) -> bool + Send + Sync + 'static {
+    move |invoke| match refuse_while_locked(invoke, is_allowed_while_locked) {
+        Some(invoke) => handler(invoke),
+        None => true,
+    }
+}
+
+/// A plugin whose commands are all refused while locked
+pub struct Guarded<P>(P);
+
+/// Wrap a plugin so its commands are refused while locked
+///
+/// Plugin commands are dispatched by their plugin, never reaching the invoke
+/// handler's guard.
+pub fn guard_plugin<P>(plugin: P) -> Guarded<P> {
+    Guarded(plugin)
+}
+
+impl<R: Runtime, P: Plugin<R>> Plugin<R> for Guarded<P> {
+    fn name(&self) -> &'static str {
+        self.0.name()
+    }
+
+    fn initialize(
+        &mut self,
+        app: &AppHandle<R>,
+        config: serde_json::Value,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.0.initialize(app, config)
+    }
+
+    fn initialization_script(&self) -> Option<String> {
+        self.0.initialization_script()
+    }
+
+    fn window_created(&mut self, window: tauri::Window<R>) {
+        self.0.window_created(window)
+    }
+
+    fn webview_created(&mut self, webview: tauri::Webview<R>) {
+        self.0.webview_created(webview)
+    }
+
+    fn on_navigation(&mut self, webview: &tauri::Webview<R>, url: &tauri::Url) -> bool {
+        self.0.on_navigation(webview, url)
+    }
+
+    fn on_page_load(
+        &mut self,
+        webview: &tauri::Webview<R>,
+        payload: &tauri::webview::PageLoadPayload<'_>,
+    ) {
+        self.0.on_page_load(webview, payload)
+    }
+
+    fn on_event(&mut self, app: &AppHandle<R>, event: &tauri::RunEvent) {
+        self.0.on_event(app, event)
+    }
+
+    fn extend_api(&mut self, invoke: Invoke<R>) -> bool {
+        match refuse_while_locked(invoke, |_| false) {
+            Some(invoke) => self.0.extend_api(invoke),
+            None => true,
+        }
+    }
+}
+
+// ============================================================
+// Passphrase
+// ============================================================
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if hex.len() % 2 != 0 || !hex.is_ascii() {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+        .collect()
+}
+
+/// PBKDF2-HMAC-SHA256 with a single output block
+fn pbkdf2(passphrase: &[u8], salt: &[u8], iterations: u32) -> [u8; 32] {
+    pbkdf2::pbkdf2_hmac_array::<Sha256, 32>(passphrase, salt, iterations)
+}
+
+/// Stored form: `pbkdf2-sha256$<iterations>$<salt hex>$<hash hex>`
+fn hash_passphrase(passphrase: &str, salt: &[u8], iterations: u32) -> String {
+    format!(
+        "pbkdf2-sha256${}${}${}",
+        iterations,
+        encode_hex(salt),
+        encode_hex(&pbkdf2(passphrase.as_bytes(), salt, iterations))
+    )
+}
+
+fn verify_passphrase(passphrase: &str, stored: &str) -> bool {
+    let parts: Vec<&str> = stored.split('$').collect();
+    let [scheme, iterations, salt, hash] = parts.as_slice() else {
+        return false;
+    };
+    let (Ok(iterations), Some(salt), Some(hash)) = (
+        iterations.parse::<u32>(),
+        decode_hex(salt),
+        decode_hex(hash),
+    ) else {
+        return false;
+    };
+    *scheme == "pbkdf2-sha256"
+        && iterations > 0
+        && crate::control_api::constant_time_eq(
+            &pbkdf2(passphrase.as_bytes(), &salt, iterations),
+            &hash,
+        )
+}
+
+fn stored_hash(app_data_dir: &Path) -> Result<Option<String>, AppError> {
+    Ok(keychain::get_secret(app_data_dir, PASSPHRASE_ACCOUNT)?)
+}
+
+// ============================================================
+// Biometrics
+// ============================================================
+
+/// Prompt for Touch ID; Ok(false) when declined, Err when unavailable
+#[cfg(target_os = "macos")]
+fn authenticate_biometric(reason: &str) -> Result<bool, AppError> {
+    // evaluatePolicy replies on another queue, so spin the run loop until it does
+    const SCRIPT: &str = r#"
+ObjC.import('LocalAuthentication');
+function run(argv) {
+  const context = $.LAContext.alloc.init;
+  if (!context.canEvaluatePolicyError(1, null)) return 'unavailable';
+  let result = null;
+  context.evaluatePolicyLocalizedReasonReply(1, argv[0], function (ok, error) {
+    result = ok ? 'verified' : 'declined';
+  });
+  while (result === null) {
+    $.NSRunLoop.currentRunLoop.runUntilDate($.NSDate.dateWithTimeIntervalSinceNow(0.1));
+  }
+  return result;
+}
+"#;
+    match crate::osascript::run_jxa(SCRIPT, &[reason])?.as_str() {
+        "verified" => Ok(true),
+        "declined" => Ok(false),
+        _ => Err(AppError::NotFound(
+            "Touch ID isn't available on this Mac".to_string(),
+        )),
+    }
+}
+
+/// Prompt for Windows Hello; Ok(false) when declined, Err when unavailable
+#[cfg(windows)]
+fn authenticate_biometric(reason: &str) -> Result<bool, AppError> {
+    const SCRIPT: &str = r#"
+param($reason)
+Add-Type -AssemblyName System.Runtime.WindowsRuntime
+$verifier = [Windows.Security.Credentials.UI.UserConsentVerifier, Windows.Security.Credentials.UI, ContentType = WindowsRuntime]
+$asTask = [System.WindowsRuntimeSystemExtensions].GetMethods() | Where-Object {
+  $_.Name -eq 'AsTask' -and $_.GetParameters().Count -eq 1 -and
+  $_.GetParameters()[0].ParameterType.Name -eq 'IAsyncOperation`1'
+} | Select-Object -First 1
+function Await($operation, $type) {
+  $task = $asTask.MakeGenericMethod($type).Invoke($null, @($operation))
+  $task.Wait() | Out-Null
+  $task.Result
+}
+$availability = Await ($verifier::CheckAvailabilityAsync()) ([Windows.Security.Credentials.UI.UserConsentVerifierAvailability])
+if ("$availability" -ne 'Available') { return 'unavailable' }
+Await ($verifier::RequestVerificationAsync($reason)) ([Windows.Security.Credentials.UI.UserConsentVerificationResult])
+"#;
+    // -Command joins trailing arguments into the command text, so the reason
+    // is passed to the script block as a quoted literal instead
+    let command = format!("& {{{}}} {}", SCRIPT, powershell_quote(reason));
+    let output = std::process::Command::new("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-Command", &command])
+        .output()
+        .map_err(|e| AppError::Internal(format!("Failed to run PowerShell: {}", e)))?;
+    match String::from_utf8_lossy(&output.stdout).trim() {
+        "Verified" => Ok(true),
+        "unavailable" | "" => Err(AppError::NotFound(
+            "Windows Hello isn't set up on this PC".to_string(),
+        )),
+        _ => Ok(false),
+    }
+}
+
+/// A PowerShell single-quoted string literal
+///
+/// PowerShell also treats typographic single quotes as quote characters, so
+/// those are doubled too.
+#[cfg(any(windows, test))]
+fn powershell_quote(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('\'');
+    for c in text.chars() {
+        if matches!(c, '\'' | '\u{2018}' | '\u{2019}' | '\u{201A}' | '\u{201B}') {
+            quoted.push(c);
+        }
+        quoted.push(c);
+    }
+    quoted.push('\'');
+    quoted
+}
+
+#[cfg(not(any(target_os = "macos", windows)))]
+fn authenticate_biometric(_reason: &str) -> Result<bool, AppError> {
+    Err(AppError::NotFound(
+        "Biometric unlock isn't available on this platform".to_string(),
+    ))
+}
+
+fn biometrics_supported() -> bool {
+    cfg!(any(target_os = "macos", windows))
+}
+
+// ============================================================
+// Lifecycle
+// ============================================================
+
+/// Lock the app now, if the lock is enabled
+pub fn lock_now(app: &AppHandle) {
+    if app.state::<AppLock>().settings.lock().enabled {
+        set_locked(app, true, Some("manual"));
+    }
+}
+
+/// Load settings, lock at launch if enabled and watch for idleness
+pub fn start(app: &AppHandle) {
+    let lock = app.state::<AppLock>();
+    lock.touch();
+    if let Ok(app_data_dir) = app.path().app_data_dir() {
+        *lock.settings.lock() = AppLockSettings::load(&app_data_dir);
+    }
+    let enabled = lock.settings.lock().enabled;
+    if enabled && !crate::cli::options(app).headless {
+        set_locked(app, true, Some("launch"));
+    }
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(IDLE_CHECK);
+        loop {
+            interval.tick().await;
+            let lock = app.state::<AppLock>();
+            // Standing on one slide for a while isn't leaving the app idle
+            if crate::presentation::is_active(&app) {
+                lock.touch();
+                continue;
+            }
+            let timeout = lock.settings.lock().idle_timeout();
+            if let Some(timeout) = timeout {
+                if !lock.is_locked() && lock.idle_for() >= timeout {
+                    set_locked(&app, true, Some("idle"));
+                }
+            }
+        }
+    });
+}
+
+// ============================================================
+// Commands
+// ============================================================
+
+/// Lock state and settings
+#[derive(Debug, Clone, Serialize)]
+pub struct AppLockStatus {
+    pub locked: bool,
+    pub settings: AppLockSettings,
+    pub has_passphrase: bool,
+    pub biometrics_supported: bool,
+}
+
+fn status(app: &AppHandle) -> Result<AppLockStatus, AppError> {
+    let app_data_dir = app.path().app_data_dir()?;
+    let lock = app.state::<AppLock>();
+    let settings = lock.settings.lock().clone();
+    Ok(AppLockStatus {
+        locked: lock.is_locked(),
+        settings,
+        has_passphrase: stored_hash(&app_data_dir)?.is_some(),
+        biometrics_supported: biometrics_supported(),
+    })
+}
+
+/// Get lock state and settings
+#[tauri::command]
+pub async fn get_app_lock(app: AppHandle) -> Result<AppLockStatus, AppError> {
+    status(&app)
+}
+
+/// Save lock settings
+///
+/// Enabling the lock requires a passphrase, which is also the fallback when
+/// biometrics fail.
+#[tauri::command]
+pub async fn set_app_lock_settings(
+    app: AppHandle,
+    settings: AppLockSettings,
+) -> Result<AppLockStatus, AppError> {
+    let app_data_dir = app.path().app_data_dir()?;
+    if settings.enabled && stored_hash(&app_data_dir)?.is_none() {
+        return Err(AppError::InvalidInput(
+            "Set a passphrase before turning on the app lock".to_string(),
+        ));
+    }
+    settings.save(&app_data_dir)?;
+    let lock = app.state::<AppLock>();
+    *lock.settings.lock() = settings;
+    lock.touch();
+    status(&app)
+}
+
+/// Set or change the unlock passphrase; changing it requires the current one
+#[tauri::command]
+pub async fn set_app_lock_passphrase(
+    app: AppHandle,
+    passphrase: String,
+    current: Option<String>,
+) -> Result<AppLockStatus, AppError> {
+    if passphrase.chars().count() < MIN_PASSPHRASE_LEN {
+        return Err(AppError::InvalidInput(format!(
+            "Use a passphrase of at least {} characters",
+            MIN_PASSPHRASE_LEN
+        )));
+    }
+    let app_data_dir = app.path().app_data_dir()?;
+    if let Some(stored) = stored_hash(&app_data_dir)? {
+        let current = current.unwrap_or_default();
+        let matches =
+            tokio::task::spawn_blocking(move || verify_passphrase(&current, &stored)).await?;
+        if !matches {
+            return Err(AppError::Permission(
+                "The current passphrase is incorrect".to_string(),
+            ));
+        }
+    }
+    let mut salt = [0u8; 16];
+    getrandom::fill(&mut salt)
+        .map_err(|e| AppError::Internal(format!("Failed to generate salt: {}", e)))?;
+    let hash =
+        tokio::task::spawn_blocking(move || hash_passphrase(&passphrase, &salt, PBKDF2_ITERATIONS))
+            .await?;
+    keychain::set_secret(&app_data_dir, PASSPHRASE_ACCOUNT, &hash)?;
+    status(&app)
+}
+
+/// Lock the app now
+#[tauri::command]
+pub async fn lock_app(app: AppHandle) -> Result<AppLockStatus, AppError> {
+    if !app.state::<AppLock>().settings.lock().enabled {
+        return Err(AppError::InvalidInput(
+            "The app lock is turned off".to_string(),
+        ));
+    }
+    set_locked(&app, true, Some("manual"));
+    status(&app)
+}
+
+/// Unlock with the passphrase
+#[tauri::command]
+pub async fn unlock_app(app: AppHandle, passphrase: String) -> Result<AppLockStatus, AppError> {
+    let app_data_dir = app.path().app_data_dir()?;
+    let stored = stored_hash(&app_data_dir)?;
+    let matches = match stored {
+        Some(stored) => {
+            tokio::task::spawn_blocking(move || verify_passphrase(&passphrase, &stored)).await?
+        }
+        None => false,
+    };
+    if !matches {
+        // Slow down guessing: one more second per consecutive failure
+        let failures = {
+            let lock = app.state::<AppLock>();
+            let mut failures = lock.failures.lock();
+            *failures += 1;
+            *failures
+        };
+        tokio::time::sleep(Duration::from_secs(u64::from(failures)).min(MAX_FAILURE_DELAY)).await;
+        return Err(AppError::Permission("Incorrect passphrase".to_string()));
+    }
+    set_locked(&app, false, None);
+    status(&app)
+}
+
+/// Unlock with Touch ID or Windows Hello
+#[tauri::command]
+pub async fn unlock_app_with_biometrics(app: AppHandle) -> Result<AppLockStatus, AppError> {
+    if !app.state::<AppLock>().settings.lock().biometrics {
+        return Err(AppError::InvalidInput(
+            "Biometric unlock is turned off".to_string(),
+        ));
+    }
+    let verified =
+        tokio::task::spawn_blocking(|| authenticate_biometric("unlock Second Brain")).await??;
+    if !verified {
+        return Err(AppError::Cancelled(
+            "Authentication was cancelled".to_string(),
+        ));
+    }
+    set_locked(&app, false, None);
+    status(&app)
+}
+
+/// Record user activity, postponing the idle lock
+#[tauri::command]
+pub async fn report_app_activity(app: AppHandle) -> Result<(), AppError> {
+    let lock = app.state::<AppLock>();
+    if !lock.is_locked() {
+        lock.touch();
+    }
+    Ok(())
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_pbkdf2_vector() {
+        // RFC 7914 section 11
+        let key = pbkdf2(b"passwd", b"salt", 1);
+        assert_eq!(
+            encode_hex(&key),
+            "55ac046e56e3089fec1691c22544b605f94185216dde0465e68b9d57c20dacbc"
+        );
+    }
+
+    #[test]
+    fn test_verify_passphrase() {
+        let stored = hash_passphrase("correct horse", b"0123456789abcdef", 1000);
+        assert!(stored.starts_with("pbkdf2-sha256$1000$"));
+        assert!(verify_passphrase("correct horse", &stored));
+        assert!(!verify_passphrase("wrong horse", &stored));
+        assert!(!verify_passphrase("correct horse", "garbage"));
+        assert!(!verify_passphrase("correct horse", "pbkdf2-sha256$0$00$00"));
+    }
+
+    #[test]
+    fn test_allowed_while_locked() {
+        assert!(is_allowed_while_locked("unlock_app"));
+        assert!(is_allowed_while_locked("get_service_state"));
+        assert!(!is_allowed_while_locked("read_text_file"));
+        assert!(!is_allowed_while_locked("get_secrets"));
+        assert!(!is_allowed_while_locked("proxy_request"));
+    }
+
+    #[test]
+    fn test_powershell_quote() {
+        assert_eq!(
+            powershell_quote("Unlock Second Brain"),
+            "'Unlock Second Brain'"
+        );
+        assert_eq!(powershell_quote("it's; exit"), "'it''s; exit'");
+        assert_eq!(powershell_quote("a\u{2019}b"), "'a\u{2019}\u{2019}b'");
+    }
+
+    #[test]
+    fn test_idle_timeout() {
+        let settings = AppLockSettings {
+            enabled: true,
+            idle_timeout_minutes: 5,
+            biometrics: false,
+        };
+        assert_eq!(settings.idle_timeout(), Some(Duration::from_secs(300)));
+        let on_demand = AppLockSettings {
+            idle_timeout_minutes: 0,
+            ..settings.clone()
+        };
+        assert_eq!(on_demand.idle_timeout(), None);
+        assert_eq!(AppLockSettings::default().idle_timeout(), None);
+    }
+
+    #[test]
+    fn test_settings_round_trip() {
+        let dir = TempDir::new().unwrap();
+        assert_eq!(
+            AppLockSettings::load(dir.path()),
+            AppLockSettings::default()
+        );
+        let settings = AppLockSettings {
+            enabled: true,
+            idle_timeout_minutes: 1,
+            biometrics: false,
+        };
+        settings.save(dir.path()).unwrap();
+        assert_eq!(AppLockSettings::load(dir.path()), settings);
+    }
+}
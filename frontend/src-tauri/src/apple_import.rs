@@ -0,0 +1,547 @@
+//! Apple Notes and Reminders importers.
+//!
+//! This module provides:
+//! - Reading Apple Notes via Apple Events (osascript) and Reminders via EventKit
+//! - Duplicate detection against a local ledger of previously imported items
+//! - A dry-run preview of what an import would send
+//! - Batched upload to the backend import endpoint with progress events
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::config::{load_json, save_json_atomic};
+use crate::error::AppError;
+use crate::jobs::JobContext;
+use crate::osascript::{run_jxa, PermissionStatus};
+
+/// Backend endpoint that ingests external notes
+const IMPORT_NOTES_PATH: &str = "/import/notes";
+
+/// Notes sent per backend request
+const UPLOAD_BATCH_SIZE: usize = 50;
+
+/// Reads every note with its folder via the Notes scripting dictionary
+const NOTES_SCRIPT: &str = r#"
+function run(argv) {
+    const Notes = Application('Notes');
+    const out = [];
+    Notes.folders().forEach(function (folder) {
+        const folderName = folder.name();
+        const ids = folder.notes.id();
+        const names = folder.notes.name();
+        const bodies = folder.notes.plaintext();
+        const created = folder.notes.creationDate();
+        const modified = folder.notes.modificationDate();
+        for (let i = 0; i < ids.length; i++) {
+            out.push({
+                external_id: ids[i],
+                title: names[i] || 'Untitled',
+                body: bodies[i] || '',
+                folder: folderName,
+                created_at: created[i].toISOString(),
+                updated_at: modified[i].toISOString(),
+                completed: false,
+            });
+        }
+    });
+    return JSON.stringify(out);
+}
+"#;
+
+/// EventKit reminders bridge: `status`, `request`, or `fetch`
+const REMINDERS_SCRIPT: &str = r#"
+ObjC.import('EventKit');
+ObjC.import('Foundation');
+
+function pump(isDone) {
+    const deadline = Date.now() + 120000;
+    while (!isDone() && Date.now() < deadline) {
+        $.NSRunLoop.currentRunLoop.runUntilDate($.NSDate.dateWithTimeIntervalSinceNow(0.2));
+    }
+}
+
+function iso(date) {
+    return date.isNil() ? null : new Date(date.timeIntervalSince1970 * 1000).toISOString();
+}
+
+function run(argv) {
+    const store = $.EKEventStore.alloc.init;
+    const mode = argv[0];
+
+    if (mode === 'status') {
+        return JSON.stringify({ status: $.EKEventStore.authorizationStatusForEntityType(1) });
+    }
+
+    if (mode === 'request') {
+        let done = false;
+        const handler = function (granted, error) { done = true; };
+        if (store.respondsToSelector('requestFullAccessToRemindersWithCompletion:')) {
+            store.requestFullAccessToRemindersWithCompletion(handler);
+        } else {
+            store.requestAccessToEntityTypeCompletion(1, handler);
+        }
+        pump(function () { return done; });
+        return JSON.stringify({ status: $.EKEventStore.authorizationStatusForEntityType(1) });
+    }
+
+    let reminders = null;
+    store.fetchRemindersMatchingPredicateCompletion(
+        store.predicateForRemindersInCalendars($()),
+        function (result) { reminders = result; }
+    );
+    pump(function () { return reminders !== null; });
+
+    const out = [];
+    for (let i = 0; reminders && i < reminders.count; i++) {
+        const r = reminders.objectAtIndex(i);
+        out.push({
+            external_id: ObjC.unwrap(r.calendarItemIdentifier),
+            title: ObjC.unwrap(r.title) || 'Untitled',
+            body: ObjC.unwrap(r.notes) || '',
+            folder: ObjC.unwrap(r.calendar.title) || null,
+            created_at: iso(r.creationDate),
+            updated_at: iso(r.lastModifiedDate),
+            completed: r.completed,
+        });
+    }
+    return JSON.stringify(out);
+}
+"#;
+
+/// Where imported items come from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportSource {
+    AppleNotes,
+    Reminders,
+}
+
+impl ImportSource {
+    /// Source label stored on imported notes
+    pub fn label(&self) -> &'static str {
+        match self {
+            ImportSource::AppleNotes => "apple_notes",
+            ImportSource::Reminders => "apple_reminders",
+        }
+    }
+}
+
+/// A note or reminder read from the system
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImportItem {
+    pub external_id: String,
+    pub title: String,
+    pub body: String,
+    pub folder: Option<String>,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+    #[serde(default)]
+    pub completed: bool,
+}
+
+impl ImportItem {
+    /// Hash of the imported content, used to detect changes since the last import
+    pub fn content_hash(&self) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(self.title.as_bytes());
+        hasher.update(b"\n");
+        hasher.update(self.folder.as_deref().unwrap_or("").as_bytes());
+        hasher.update(b"\n");
+        hasher.update(self.body.as_bytes());
+        hasher.update(if self.completed { b"\n1" } else { b"\n0" });
+        hasher
+            .finalize()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect()
+    }
+
+    /// Payload accepted by the backend import endpoint
+    fn to_import_note(&self, source: ImportSource) -> serde_json::Value {
+        let mut tags = Vec::new();
+        if source == ImportSource::Reminders {
+            tags.push("reminder");
+            if self.completed {
+                tags.push("completed");
+            }
+        }
+
+        serde_json::json!({
+            "id": self.external_id,
+            "title": self.title,
+            "body": self.body,
+            "folder": self.folder,
+            "source": source.label(),
+            "created_at": self.created_at,
+            "updated_at": self.updated_at,
+            "tags": tags,
+        })
+    }
+}
+
+/// Whether an item differs from what was last imported
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ItemStatus {
+    New,
+    Changed,
+    Unchanged,
+}
+
+/// Content hashes of previously imported items, keyed by source and external ID
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImportLedger {
+    entries: HashMap<String, String>,
+}
+
+impl ImportLedger {
+    fn path(app_data_dir: &Path) -> PathBuf {
+        app_data_dir.join("apple-import-ledger.json")
+    }
+
+    pub fn load(app_data_dir: &Path) -> Self {
+        load_json(&Self::path(app_data_dir)).unwrap_or_default()
+    }
+
+    pub fn save(&self, app_data_dir: &Path) -> Result<(), String> {
+        save_json_atomic(&Self::path(app_data_dir), self)
+    }
+
+    fn key(source: ImportSource, external_id: &str) -> String {
+        format!("{}:{}", source.label(), external_id)
+    }
+
+    pub fn status(&self, source: ImportSource, item: &ImportItem) -> ItemStatus {
+        match self.entries.get(&Self::key(source, &item.external_id)) {
+            None => ItemStatus::New,
+            Some(hash) if *hash == item.content_hash() => ItemStatus::Unchanged,
+            Some(_) => ItemStatus::Changed,
+        }
+    }
+
+    pub fn record(&mut self, source: ImportSource, item: &ImportItem) {
+        self.entries
+            .insert(Self::key(source, &item.external_id), item.content_hash());
+    }
+}
+
+/// Preview entry for a single item
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportPreviewItem {
+    pub external_id: String,
+    pub title: String,
+    pub folder: Option<String>,
+    pub status: ItemStatus,
+}
+
+/// Result of a dry run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportPreview {
+    pub source: ImportSource,
+    pub total: usize,
+    pub new: usize,
+    pub changed: usize,
+    pub unchanged: usize,
+    pub items: Vec<ImportPreviewItem>,
+}
+
+impl ImportPreview {
+    fn build(source: ImportSource, items: &[ImportItem], ledger: &ImportLedger) -> Self {
+        let items: Vec<ImportPreviewItem> = items
+            .iter()
+            .map(|item| ImportPreviewItem {
+                external_id: item.external_id.clone(),
+                title: item.title.clone(),
+                folder: item.folder.clone(),
+                status: ledger.status(source, item),
+            })
+            .collect();
+
+        let count = |status| items.iter().filter(|i| i.status == status).count();
+        Self {
+            source,
+            total: items.len(),
+            new: count(ItemStatus::New),
+            changed: count(ItemStatus::Changed),
+            unchanged: count(ItemStatus::Unchanged),
+            items,
+        }
+    }
+}
+
+/// Result of a completed import
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImportSummary {
+    /// Items read from the system
+    pub total: usize,
+    /// Items skipped locally because they were unchanged since the last import
+    pub unchanged: usize,
+    /// Counts reported by the backend
+    pub imported: u64,
+    pub updated: u64,
+    pub skipped: u64,
+}
+
+/// Progress event payload
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportProgress {
+    pub source: ImportSource,
+    /// "reading", "uploading", or "complete"
+    pub phase: &'static str,
+    pub processed: usize,
+    pub total: usize,
+}
+
+fn parse_items(json: &str) -> Result<Vec<ImportItem>, String> {
+    serde_json::from_str(json).map_err(|e| format!("Invalid import output: {}", e))
+}
+
+/// Current Reminders authorization status
+fn reminders_permission() -> PermissionStatus {
+    if !cfg!(target_os = "macos") {
+        return PermissionStatus::Unavailable;
+    }
+    run_jxa(REMINDERS_SCRIPT, &["status"])
+        .ok()
+        .and_then(|out| serde_json::from_str::<serde_json::Value>(&out).ok())
+        .and_then(|v| v["status"].as_i64())
+        .map(PermissionStatus::from_code)
+        .unwrap_or(PermissionStatus::Unavailable)
+}
+
+/// Read every item from `source`, prompting for Reminders access if needed
+fn read_items(source: ImportSource) -> Result<Vec<ImportItem>, String> {
+    match source {
+        ImportSource::AppleNotes => parse_items(&run_jxa(NOTES_SCRIPT, &[])?),
+        ImportSource::Reminders => {
+            if reminders_permission() == PermissionStatus::NotDetermined {
+                run_jxa(REMINDERS_SCRIPT, &["request"])?;
+            }
+            if !reminders_permission().is_authorized() {
+                return Err(
+                    "Reminders access not granted. Enable Second Brain in System \
+                     Settings > Privacy & Security > Reminders."
+                        .to_string(),
+                );
+            }
+            parse_items(&run_jxa(REMINDERS_SCRIPT, &["fetch"])?)
+        }
+    }
+}
+
+async fn read_items_async(source: ImportSource) -> Result<Vec<ImportItem>, String> {
+    tokio::task::spawn_blocking(move || read_items(source))
+        .await
+        .map_err(|e| format!("Task panicked: {}", e))?
+}
+
+fn emit_progress(
+    app: &AppHandle,
+    source: ImportSource,
+    phase: &'static str,
+    processed: usize,
+    total: usize,
+) {
+    let _ = app.emit(
+        "apple-import-progress",
+        ImportProgress {
+            source,
+            phase,
+            processed,
+            total,
+        },
+    );
+}
+
+/// Preview an import without sending anything to the backend
+#[tauri::command]
+pub async fn preview_apple_import(
+    app: AppHandle,
+    source: ImportSource,
+) -> Result<ImportPreview, AppError> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let items = read_items_async(source).await?;
+    let ledger = ImportLedger::load(&app_data_dir);
+    Ok(ImportPreview::build(source, &items, &ledger))
+}
+
+/// Import new and changed items from Apple Notes or Reminders
+pub async fn import_items(
+    job: &JobContext,
+    source: ImportSource,
+) -> Result<ImportSummary, AppError> {
+    let app = job.app().clone();
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+
+    emit_progress(&app, source, "reading", 0, 0);
+    job.progress("reading", 0, None);
+    let items = read_items_async(source).await?;
+    let mut ledger = ImportLedger::load(&app_data_dir);
+
+    let pending: Vec<ImportItem> = items
+        .iter()
+        .filter(|item| ledger.status(source, item) != ItemStatus::Unchanged)
+        .cloned()
+        .collect();
+
+    let mut summary = ImportSummary {
+        total: items.len(),
+        unchanged: items.len() - pending.len(),
+        ..Default::default()
+    };
+
+    tracing::info!(
+        "Importing {} of {} items from {}",
+        pending.len(),
+        items.len(),
+        source.label()
+    );
+
+    let mut processed = 0;
+    emit_progress(&app, source, "uploading", processed, pending.len());
+
+    for batch in pending.chunks(UPLOAD_BATCH_SIZE) {
+        // Stop between batches so the ledger matches what was sent
+        job.check()?;
+        job.progress("uploading", processed as u64, Some(pending.len() as u64));
+        let notes: Vec<serde_json::Value> = batch
+            .iter()
+            .map(|item| item.to_import_note(source))
+            .collect();
+        let body = serde_json::json!({ "notes": notes });
+
+        let response =
+            crate::proxy::send_backend_request(&app, "POST", IMPORT_NOTES_PATH, Some(&body), None)
+                .await?;
+
+        summary.imported += response["importedCount"].as_u64().unwrap_or(0);
+        summary.updated += response["updatedCount"].as_u64().unwrap_or(0);
+        summary.skipped += response["skippedCount"].as_u64().unwrap_or(0);
+
+        // Record progress per batch so an interrupted import resumes where it left off
+        for item in batch {
+            ledger.record(source, item);
+        }
+        ledger.save(&app_data_dir)?;
+
+        processed += batch.len();
+        emit_progress(&app, source, "uploading", processed, pending.len());
+    }
+
+    emit_progress(&app, source, "complete", processed, pending.len());
+    Ok(summary)
+}
+
+/// Import new and changed items from Apple Notes or Reminders
+///
+/// Use `start_job` with kind `apple_import` for cancellation.
+#[tauri::command]
+pub async fn import_from_apple(
+    app: AppHandle,
+    source: ImportSource,
+) -> Result<ImportSummary, AppError> {
+    import_items(&JobContext::untracked(&app), source).await
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn item(id: &str, body: &str) -> ImportItem {
+        ImportItem {
+            external_id: id.to_string(),
+            title: format!("Note {}", id),
+            body: body.to_string(),
+            folder: Some("Notes".to_string()),
+            created_at: Some("2025-03-10T09:00:00.000Z".to_string()),
+            updated_at: None,
+            completed: false,
+        }
+    }
+
+    #[test]
+    fn test_parse_items() {
+        let json = r#"[{"external_id":"x-coredata://1","title":"Groceries","body":"Milk","folder":"Notes","created_at":"2025-03-10T09:00:00.000Z","updated_at":"2025-03-11T09:00:00.000Z","completed":false}]"#;
+        let items = parse_items(json).unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].title, "Groceries");
+    }
+
+    #[test]
+    fn test_ledger_detects_new_changed_unchanged() {
+        let mut ledger = ImportLedger::default();
+        let original = item("1", "first");
+
+        assert_eq!(
+            ledger.status(ImportSource::AppleNotes, &original),
+            ItemStatus::New
+        );
+
+        ledger.record(ImportSource::AppleNotes, &original);
+        assert_eq!(
+            ledger.status(ImportSource::AppleNotes, &original),
+            ItemStatus::Unchanged
+        );
+        assert_eq!(
+            ledger.status(ImportSource::AppleNotes, &item("1", "edited")),
+            ItemStatus::Changed
+        );
+
+        // Same ID from another source is unrelated
+        assert_eq!(
+            ledger.status(ImportSource::Reminders, &original),
+            ItemStatus::New
+        );
+    }
+
+    #[test]
+    fn test_ledger_persists() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut ledger = ImportLedger::default();
+        ledger.record(ImportSource::Reminders, &item("r1", ""));
+        ledger.save(temp_dir.path()).unwrap();
+
+        let loaded = ImportLedger::load(temp_dir.path());
+        assert_eq!(
+            loaded.status(ImportSource::Reminders, &item("r1", "")),
+            ItemStatus::Unchanged
+        );
+    }
+
+    #[test]
+    fn test_preview_counts() {
+        let mut ledger = ImportLedger::default();
+        ledger.record(ImportSource::AppleNotes, &item("1", "a"));
+        ledger.record(ImportSource::AppleNotes, &item("2", "b"));
+
+        let items = vec![item("1", "a"), item("2", "changed"), item("3", "c")];
+        let preview = ImportPreview::build(ImportSource::AppleNotes, &items, &ledger);
+
+        assert_eq!(preview.total, 3);
+        assert_eq!(preview.unchanged, 1);
+        assert_eq!(preview.changed, 1);
+        assert_eq!(preview.new, 1);
+    }
+
+    #[test]
+    fn test_reminder_payload_tags() {
+        let mut reminder = item("r1", "Call the bank");
+        reminder.completed = true;
+
+        let payload = reminder.to_import_note(ImportSource::Reminders);
+        assert_eq!(payload["source"], "apple_reminders");
+        assert_eq!(payload["id"], "r1");
+        assert_eq!(
+            payload["tags"],
+            serde_json::json!(["reminder", "completed"])
+        );
+    }
+}
@@ -0,0 +1,433 @@
+//! Compaction of old logs, crash dumps and backups into archives.
+//!
+//! This module provides:
+//! - `archive_old_data`, which moves files older than a threshold into a
+//!   dated `.tar.zst` archive under the app data directory
+//! - A manifest per archive listing its entries
+//! - Listing and extraction of archives
+//!
+//! Originals are deleted only after the archive has been fully written.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tauri::{AppHandle, Manager};
+
+use crate::backup::EncryptedBackupSettings;
+use crate::config::{load_json, save_json_atomic};
+use crate::error::AppError;
+
+/// Default age after which files are archived
+const DEFAULT_THRESHOLD_DAYS: u32 = 30;
+
+/// zstd compression level; archives are written rarely and read even less
+const COMPRESSION_LEVEL: i32 = 19;
+
+/// Archive file extension
+const ARCHIVE_EXTENSION: &str = ".tar.zst";
+
+/// One archived file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchiveEntry {
+    /// Path inside the archive, e.g. `logs/backend-20240101.log`
+    pub path: String,
+    pub size_bytes: u64,
+}
+
+/// Metadata written next to each archive
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchiveManifest {
+    /// Archive file name
+    pub name: String,
+    /// Creation time (RFC 3339)
+    pub created_at: String,
+    /// Compressed size
+    pub size_bytes: u64,
+    /// Total size of the archived files
+    pub original_bytes: u64,
+    pub entries: Vec<ArchiveEntry>,
+}
+
+impl ArchiveManifest {
+    fn path(archive_dir: &Path, name: &str) -> PathBuf {
+        archive_dir.join(format!("{}.json", name))
+    }
+}
+
+/// Result of an archiving pass
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ArchiveReport {
+    /// The new archive, None when nothing was old enough
+    pub archive: Option<ArchiveManifest>,
+    pub reclaimed_bytes: u64,
+}
+
+/// A directory whose old files are archived under a prefix
+struct ArchiveSource {
+    prefix: &'static str,
+    dir: PathBuf,
+    include: fn(&str) -> bool,
+}
+
+/// A file selected for archiving
+#[derive(Debug, Clone)]
+struct Candidate {
+    entry: String,
+    path: PathBuf,
+    size: u64,
+}
+
+fn archive_dir(app_data_dir: &Path) -> PathBuf {
+    app_data_dir.join("archives")
+}
+
+fn sources(app_data_dir: &Path) -> Vec<ArchiveSource> {
+    let mut sources = vec![
+        ArchiveSource {
+            prefix: "logs",
+            dir: app_data_dir.join("logs"),
+            include: |_| true,
+        },
+        ArchiveSource {
+            prefix: "crashes",
+            dir: app_data_dir.join("crashes"),
+            include: |_| true,
+        },
+    ];
+    // Only touch our own files in the user's backup folder
+    if let Some(destination) = EncryptedBackupSettings::load(app_data_dir).destination {
+        sources.push(ArchiveSource {
+            prefix: "backups",
+            dir: destination,
+            include: |name| {
+                name.starts_with("secondbrain-")
+                    && (name.ends_with(".sbbackup") || name.ends_with(".json"))
+            },
+        });
+    }
+    sources
+}
+
+/// Top-level files in each source last modified before `cutoff`
+fn collect_candidates(sources: &[ArchiveSource], cutoff: SystemTime) -> Vec<Candidate> {
+    let mut candidates = Vec::new();
+    for source in sources {
+        let Ok(entries) = fs::read_dir(&source.dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let Ok(meta) = entry.metadata() else {
+                continue;
+            };
+            let old = meta.modified().map(|m| m < cutoff).unwrap_or(false);
+            if meta.is_file() && old && !name.starts_with('.') && (source.include)(&name) {
+                candidates.push(Candidate {
+                    entry: format!("{}/{}", source.prefix, name),
+                    path: entry.path(),
+                    size: meta.len(),
+                });
+            }
+        }
+    }
+    candidates.sort_by(|a, b| a.entry.cmp(&b.entry));
+    candidates
+}
+
+/// First unused `archive-<date>[-n].tar.zst` name
+fn next_archive_name(archive_dir: &Path, date: &str) -> String {
+    let mut name = format!("archive-{}{}", date, ARCHIVE_EXTENSION);
+    let mut n = 2;
+    while archive_dir.join(&name).exists() {
+        name = format!("archive-{}-{}{}", date, n, ARCHIVE_EXTENSION);
+        n += 1;
+    }
+    name
+}
+
+fn validate_archive_name(name: &str) -> Result<(), String> {
+    if !name.ends_with(ARCHIVE_EXTENSION) || name.contains(['/', '\\']) || name.starts_with('.') {
+        return Err(format!("Invalid archive name: {}", name));
+    }
+    Ok(())
+}
+
+/// Write candidates into a new archive, then delete the originals
+fn archive_files(
+    archive_dir: &Path,
+    candidates: &[Candidate],
+    date: &str,
+) -> Result<ArchiveReport, String> {
+    if candidates.is_empty() {
+        return Ok(ArchiveReport::default());
+    }
+    fs::create_dir_all(archive_dir)
+        .map_err(|e| format!("Failed to create {}: {}", archive_dir.display(), e))?;
+
+    let name = next_archive_name(archive_dir, date);
+    let path = archive_dir.join(&name);
+    let partial = archive_dir.join(format!("{}.partial", name));
+
+    let write = || -> std::io::Result<()> {
+        let file = fs::File::create(&partial)?;
+        let encoder = zstd::Encoder::new(file, COMPRESSION_LEVEL)?;
+        let mut builder = tar::Builder::new(encoder);
+        for candidate in candidates {
+            builder.append_path_with_name(&candidate.path, &candidate.entry)?;
+        }
+        builder.into_inner()?.finish()?.sync_all()
+    };
+    if let Err(e) = write().and_then(|_| fs::rename(&partial, &path)) {
+        let _ = fs::remove_file(&partial);
+        return Err(format!("Failed to write archive: {}", e));
+    }
+
+    let manifest = ArchiveManifest {
+        size_bytes: fs::metadata(&path).map(|m| m.len()).unwrap_or(0),
+        original_bytes: candidates.iter().map(|c| c.size).sum(),
+        entries: candidates
+            .iter()
+            .map(|c| ArchiveEntry {
+                path: c.entry.clone(),
+                size_bytes: c.size,
+            })
+            .collect(),
+        created_at: chrono::Local::now().to_rfc3339(),
+        name,
+    };
+    save_json_atomic(
+        &ArchiveManifest::path(archive_dir, &manifest.name),
+        &manifest,
+    )?;
+
+    let mut reclaimed_bytes = 0;
+    for candidate in candidates {
+        match fs::remove_file(&candidate.path) {
+            Ok(()) => reclaimed_bytes += candidate.size,
+            Err(e) => tracing::warn!(
+                "Archived {:?} but could not remove it: {}",
+                candidate.path,
+                e
+            ),
+        }
+    }
+    Ok(ArchiveReport {
+        reclaimed_bytes: reclaimed_bytes.saturating_sub(manifest.size_bytes),
+        archive: Some(manifest),
+    })
+}
+
+/// Manifests of all archives, newest first
+fn list_manifests(archive_dir: &Path) -> Vec<ArchiveManifest> {
+    let Ok(entries) = fs::read_dir(archive_dir) else {
+        return Vec::new();
+    };
+    let mut manifests: Vec<ArchiveManifest> = entries
+        .flatten()
+        .filter(|e| {
+            e.file_name()
+                .to_string_lossy()
+                .ends_with(&format!("{}.json", ARCHIVE_EXTENSION))
+        })
+        .filter_map(|e| load_json(&e.path()))
+        .filter(|m: &ArchiveManifest| archive_dir.join(&m.name).is_file())
+        .collect();
+    manifests.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    manifests
+}
+
+/// Unpack an archive into `destination`
+///
+/// Entries with absolute paths or `..` components are skipped by `tar`.
+fn extract_to(archive: &Path, destination: &Path) -> Result<(), String> {
+    let file = fs::File::open(archive)
+        .map_err(|e| format!("Failed to open {}: {}", archive.display(), e))?;
+    let decoder = zstd::Decoder::new(file).map_err(|e| format!("Invalid archive: {}", e))?;
+    fs::create_dir_all(destination)
+        .map_err(|e| format!("Failed to create {}: {}", destination.display(), e))?;
+    tar::Archive::new(decoder)
+        .unpack(destination)
+        .map_err(|e| format!("Failed to extract archive: {}", e))
+}
+
+// ============================================================
+// Commands
+// ============================================================
+
+/// Archive logs, crash dumps and backups older than `older_than_days`
+#[tauri::command]
+pub async fn archive_old_data(
+    app: AppHandle,
+    older_than_days: Option<u32>,
+) -> Result<ArchiveReport, AppError> {
+    let days = older_than_days.unwrap_or(DEFAULT_THRESHOLD_DAYS);
+    if days == 0 {
+        return Err(AppError::InvalidInput(
+            "Threshold must be at least 1 day".to_string(),
+        ));
+    }
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+
+    let report = tokio::task::spawn_blocking(move || {
+        let cutoff = SystemTime::now() - Duration::from_secs(days as u64 * 24 * 60 * 60);
+        let candidates = collect_candidates(&sources(&app_data_dir), cutoff);
+        let date = chrono::Local::now().format("%Y-%m-%d").to_string();
+        archive_files(&archive_dir(&app_data_dir), &candidates, &date)
+    })
+    .await
+    .map_err(|e| format!("Task panicked: {}", e))??;
+
+    if let Some(ref archive) = report.archive {
+        tracing::info!(
+            "Archived {} file(s) into {}, reclaimed {} bytes",
+            archive.entries.len(),
+            archive.name,
+            report.reclaimed_bytes
+        );
+    }
+    Ok(report)
+}
+
+/// List archives, newest first
+#[tauri::command]
+pub async fn list_archives(app: AppHandle) -> Result<Vec<ArchiveManifest>, AppError> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    Ok(list_manifests(&archive_dir(&app_data_dir)))
+}
+
+/// Extract an archive, returning the folder it was extracted to
+///
+/// Defaults to `archives/extracted/<archive name>` in the app data directory.
+#[tauri::command]
+pub async fn extract_archive(
+    app: AppHandle,
+    name: String,
+    destination: Option<String>,
+) -> Result<String, AppError> {
+    validate_archive_name(&name)?;
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let archive_dir = archive_dir(&app_data_dir);
+    let archive = archive_dir.join(&name);
+    if !archive.is_file() {
+        return Err(AppError::NotFound(format!("Archive {} not found", name)));
+    }
+    let destination = destination.map(PathBuf::from).unwrap_or_else(|| {
+        archive_dir
+            .join("extracted")
+            .join(name.trim_end_matches(ARCHIVE_EXTENSION))
+    });
+
+    let target = destination.clone();
+    tokio::task::spawn_blocking(move || extract_to(&archive, &target))
+        .await
+        .map_err(|e| format!("Task panicked: {}", e))??;
+    Ok(destination.to_string_lossy().to_string())
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+    fn write_aged(path: &Path, content: &[u8], age: Duration) {
+        fs::write(path, content).unwrap();
+        let file = fs::File::options().write(true).open(path).unwrap();
+        file.set_modified(SystemTime::now() - age).unwrap();
+    }
+
+    fn test_sources(root: &Path) -> Vec<ArchiveSource> {
+        vec![
+            ArchiveSource {
+                prefix: "logs",
+                dir: root.join("logs"),
+                include: |_| true,
+            },
+            ArchiveSource {
+                prefix: "backups",
+                dir: root.join("backups"),
+                include: |name| name.ends_with(".sbbackup"),
+            },
+        ]
+    }
+
+    #[test]
+    fn test_collect_candidates_filters_by_age_and_source() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("logs")).unwrap();
+        fs::create_dir_all(root.join("backups")).unwrap();
+        write_aged(&root.join("logs/old.log"), b"old", 40 * DAY);
+        write_aged(&root.join("logs/new.log"), b"new", DAY);
+        write_aged(&root.join("backups/a.sbbackup"), b"a", 40 * DAY);
+        write_aged(&root.join("backups/notes.txt"), b"user file", 40 * DAY);
+
+        let candidates = collect_candidates(&test_sources(root), SystemTime::now() - 30 * DAY);
+        let entries: Vec<&str> = candidates.iter().map(|c| c.entry.as_str()).collect();
+        assert_eq!(entries, vec!["backups/a.sbbackup", "logs/old.log"]);
+    }
+
+    #[test]
+    fn test_archive_and_extract_roundtrip() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("logs")).unwrap();
+        let log = "line\n".repeat(1000);
+        write_aged(&root.join("logs/backend.log"), log.as_bytes(), 40 * DAY);
+
+        let candidates = collect_candidates(&test_sources(root), SystemTime::now() - 30 * DAY);
+        let archive_dir = root.join("archives");
+        let report = archive_files(&archive_dir, &candidates, "2024-05-01").unwrap();
+
+        let manifest = report.archive.unwrap();
+        assert_eq!(manifest.name, "archive-2024-05-01.tar.zst");
+        assert_eq!(manifest.original_bytes, log.len() as u64);
+        assert!(manifest.size_bytes < manifest.original_bytes);
+        assert!(report.reclaimed_bytes > 0);
+        assert!(!root.join("logs/backend.log").exists());
+        assert_eq!(list_manifests(&archive_dir), vec![manifest.clone()]);
+
+        let out = root.join("out");
+        extract_to(&archive_dir.join(&manifest.name), &out).unwrap();
+        assert_eq!(
+            fs::read_to_string(out.join("logs/backend.log")).unwrap(),
+            log
+        );
+    }
+
+    #[test]
+    fn test_archive_names_are_unique_per_day() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        assert_eq!(
+            next_archive_name(dir, "2024-05-01"),
+            "archive-2024-05-01.tar.zst"
+        );
+        fs::write(dir.join("archive-2024-05-01.tar.zst"), b"").unwrap();
+        assert_eq!(
+            next_archive_name(dir, "2024-05-01"),
+            "archive-2024-05-01-2.tar.zst"
+        );
+    }
+
+    #[test]
+    fn test_nothing_to_archive() {
+        let temp_dir = TempDir::new().unwrap();
+        let report = archive_files(temp_dir.path(), &[], "2024-05-01").unwrap();
+        assert!(report.archive.is_none());
+        assert!(list_manifests(temp_dir.path()).is_empty());
+    }
+
+    #[test]
+    fn test_validate_archive_name() {
+        assert!(validate_archive_name("archive-2024-05-01.tar.zst").is_ok());
+        assert!(validate_archive_name("../secrets.tar.zst").is_err());
+        assert!(validate_archive_name("archive.zip").is_err());
+    }
+}
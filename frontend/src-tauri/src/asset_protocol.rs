@@ -0,0 +1,440 @@
+//! `sb-asset://` protocol for attachment files.
+//!
+//! This module provides:
+//! - A custom URI scheme that serves files from the attachment store
+//!   straight to the webview, so images and media never pass through IPC
+//!   as base64 or through the backend's HTTP port
+//! - Single byte-range requests, so audio and video can seek
+//! - Bounded reads: no response carries more than `MAX_RANGE_BYTES`, so a
+//!   large video is never loaded into memory at once
+//! - Immutable caching: the URL names the content hash, so it never changes
+//!
+//! URLs take the form `sb-asset://localhost/<hash>` (`http://sb-asset.localhost/<hash>`
+//! on Windows). An optional `?name=<file name>` sets the content type when
+//! it can't be recognised from the file itself.
+
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+use tauri::http::{header, Method, Request, Response, StatusCode};
+use tauri::{Manager, Runtime, UriSchemeContext, UriSchemeResponder};
+
+use crate::attachments::AttachmentStore;
+
+/// URI scheme the webview loads attachments from
+pub const SCHEME: &str = "sb-asset";
+
+/// Largest slice returned for one range request; players ask for the rest
+const MAX_RANGE_BYTES: u64 = 4 * 1024 * 1024;
+
+/// Bytes read to recognise a file's type
+const SNIFF_BYTES: usize = 512;
+
+/// Protocol handler; file reads run off the webview's thread
+pub fn handle<R: Runtime>(
+    ctx: UriSchemeContext<'_, R>,
+    request: Request<Vec<u8>>,
+    responder: UriSchemeResponder,
+) {
+    let app_data_dir = match ctx.app_handle().path().app_data_dir() {
+        Ok(dir) => dir,
+        Err(e) => {
+            tracing::error!("Asset request without app data directory: {}", e);
+            responder.respond(status(StatusCode::INTERNAL_SERVER_ERROR));
+            return;
+        }
+    };
+    tauri::async_runtime::spawn_blocking(move || {
+        let store = AttachmentStore::new(&app_data_dir);
+        responder.respond(respond(&store, &request));
+    });
+}
+
+fn status(code: StatusCode) -> Response<Vec<u8>> {
+    Response::builder()
+        .status(code)
+        .body(Vec::new())
+        .unwrap_or_default()
+}
+
+/// Byte range to serve, inclusive; `Ok(None)` serves the whole file
+///
+/// Only single ranges are supported. Multi-range requests are answered
+/// with the whole file, which the spec allows.
+fn parse_range(header: &str, len: u64) -> Result<Option<(u64, u64)>, ()> {
+    let Some(spec) = header.trim().strip_prefix("bytes=") else {
+        return Ok(None);
+    };
+    if spec.contains(',') {
+        return Ok(None);
+    }
+    let (start, end) = spec.split_once('-').ok_or(())?;
+    let (start, end) = match (start.trim(), end.trim()) {
+        ("", "") => return Err(()),
+        // Suffix range: the last N bytes
+        ("", suffix) => {
+            let suffix: u64 = suffix.parse().map_err(|_| ())?;
+            if suffix == 0 {
+                return Err(());
+            }
+            (len.saturating_sub(suffix), len.saturating_sub(1))
+        }
+        (start, "") => (start.parse().map_err(|_| ())?, len.saturating_sub(1)),
+        (start, end) => {
+            let start: u64 = start.parse().map_err(|_| ())?;
+            let end: u64 = end.parse().map_err(|_| ())?;
+            (start, end.min(len.saturating_sub(1)))
+        }
+    };
+    if len == 0 || start >= len || start > end {
+        return Err(());
+    }
+    Ok(Some((start, end.min(start + MAX_RANGE_BYTES - 1))))
+}
+
+/// Content type from a file's leading bytes
+fn sniff_mime_type(head: &[u8]) -> Option<&'static str> {
+    let starts = |magic: &[u8]| head.starts_with(magic);
+    if starts(b"\x89PNG\r\n\x1a\n") {
+        Some("image/png")
+    } else if starts(b"\xff\xd8\xff") {
+        Some("image/jpeg")
+    } else if starts(b"GIF87a") || starts(b"GIF89a") {
+        Some("image/gif")
+    } else if starts(b"RIFF") && head.get(8..12) == Some(b"WEBP") {
+        Some("image/webp")
+    } else if starts(b"RIFF") && head.get(8..12) == Some(b"WAVE") {
+        Some("audio/wav")
+    } else if starts(b"%PDF-") {
+        Some("application/pdf")
+    } else if starts(b"ID3") || starts(b"\xff\xfb") {
+        Some("audio/mpeg")
+    } else if starts(b"OggS") {
+        Some("audio/ogg")
+    } else if starts(b"\x1a\x45\xdf\xa3") {
+        Some("video/webm")
+    } else if head.get(4..8) == Some(b"ftyp") {
+        match head.get(8..11) {
+            Some(b"M4A") => Some("audio/mp4"),
+            Some(b"qt ") => Some("video/quicktime"),
+            _ => Some("video/mp4"),
+        }
+    } else {
+        None
+    }
+}
+
+/// `name` query parameter, if present
+fn name_hint(query: Option<&str>) -> Option<String> {
+    query?
+        .split('&')
+        .find_map(|pair| percent_decode(pair.strip_prefix("name=")?))
+        .filter(|name| !name.is_empty())
+}
+
+/// Decode a percent-encoded query value
+pub(crate) fn percent_decode(value: &str) -> Option<String> {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' => {
+                let hex = std::str::from_utf8(bytes.get(i + 1..i + 3)?).ok()?;
+                out.push(u8::from_str_radix(hex, 16).ok()?);
+                i += 3;
+            }
+            b'+' => {
+                out.push(b' ');
+                i += 1;
+            }
+            b => {
+                out.push(b);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8(out).ok()
+}
+
+/// Answer one request from the store
+fn respond(store: &AttachmentStore, request: &Request<Vec<u8>>) -> Response<Vec<u8>> {
+    let head_only = request.method() == Method::HEAD;
+    if request.method() != Method::GET && !head_only {
+        return status(StatusCode::METHOD_NOT_ALLOWED);
+    }
+    let hash = request.uri().path().trim_matches('/').to_ascii_lowercase();
+    let Some(path) = store.path_for(&hash) else {
+        return status(StatusCode::NOT_FOUND);
+    };
+    let header_value = |name| {
+        request
+            .headers()
+            .get(name)
+            .and_then(|value: &header::HeaderValue| value.to_str().ok())
+    };
+
+    let etag = format!("\"{}\"", hash);
+    let mut response =
+        if header_value(header::IF_NONE_MATCH).is_some_and(|tags| tags.contains(&etag)) {
+            status(StatusCode::NOT_MODIFIED)
+        } else {
+            let content_type = name_hint(request.uri().query())
+                .map(|name| crate::attachments::guess_mime_type(&name));
+            match serve_file(&path, header_value(header::RANGE), content_type, head_only) {
+                Ok(response) => response,
+                Err(e) => {
+                    tracing::warn!("Failed to serve attachment {}: {}", hash, e);
+                    status(StatusCode::INTERNAL_SERVER_ERROR)
+                }
+            }
+        };
+
+    // Errors must not be cached, or a file stored later would stay missing
+    let cacheable = response.status().is_success() || response.status() == StatusCode::NOT_MODIFIED;
+    if !cacheable {
+        return response;
+    }
+    let headers = response.headers_mut();
+    if let Ok(etag) = header::HeaderValue::from_str(&etag) {
+        headers.insert(header::ETAG, etag);
+    }
+    for (name, value) in [
+        (header::CACHE_CONTROL, "public, max-age=31536000, immutable"),
+        (header::ACCEPT_RANGES, "bytes"),
+        (header::X_CONTENT_TYPE_OPTIONS, "nosniff"),
+        // Scripts in an SVG or HTML attachment must never run
+        (header::CONTENT_SECURITY_POLICY, "sandbox"),
+    ] {
+        headers.insert(name, header::HeaderValue::from_static(value));
+    }
+    response
+}
+
+/// Read the requested part of a stored file
+///
+/// No response reads more than `MAX_RANGE_BYTES`: an unranged request for a
+/// larger file gets its first slice as partial content, and the player asks
+/// for the rest. HEAD requests read only enough to sniff the type.
+fn serve_file(
+    path: &Path,
+    range: Option<&str>,
+    content_type: Option<&'static str>,
+    head_only: bool,
+) -> std::io::Result<Response<Vec<u8>>> {
+    let mut file = File::open(path)?;
+    let len = file.metadata()?.len();
+
+    let content_type = match content_type.filter(|t| *t != "application/octet-stream") {
+        Some(content_type) => content_type,
+        None => {
+            let mut head = Vec::with_capacity(SNIFF_BYTES);
+            (&mut file)
+                .take(SNIFF_BYTES as u64)
+                .read_to_end(&mut head)?;
+            sniff_mime_type(&head).unwrap_or("application/octet-stream")
+        }
+    };
+
+    let range = match range.map(|header| parse_range(header, len)) {
+        None | Some(Ok(None)) if len > MAX_RANGE_BYTES => Some((0, MAX_RANGE_BYTES - 1)),
+        None | Some(Ok(None)) => None,
+        Some(Ok(Some(range))) => Some(range),
+        Some(Err(())) => {
+            return Ok(Response::builder()
+                .status(StatusCode::RANGE_NOT_SATISFIABLE)
+                .header(header::CONTENT_RANGE, format!("bytes */{}", len))
+                .body(Vec::new())
+                .unwrap_or_default());
+        }
+    };
+    let (start, end) = range.unwrap_or((0, len.saturating_sub(1)));
+    let count = if len == 0 { 0 } else { end - start + 1 };
+
+    let mut body = Vec::new();
+    if !head_only && count > 0 {
+        body.reserve_exact(count as usize);
+        file.seek(SeekFrom::Start(start))?;
+        file.take(count).read_to_end(&mut body)?;
+    }
+
+    let mut builder = Response::builder()
+        .header(header::CONTENT_TYPE, content_type)
+        .header(header::CONTENT_LENGTH, count);
+    builder = match range {
+        Some(_) => builder.status(StatusCode::PARTIAL_CONTENT).header(
+            header::CONTENT_RANGE,
+            format!("bytes {}-{}/{}", start, end, len),
+        ),
+        None => builder.status(StatusCode::OK),
+    };
+    builder
+        .body(body)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const PNG: &[u8] = b"\x89PNG\r\n\x1a\nrest-of-image";
+
+    fn request(
+        method: Method,
+        uri: &str,
+        headers: &[(header::HeaderName, &str)],
+    ) -> Request<Vec<u8>> {
+        let mut builder = Request::builder().method(method).uri(uri);
+        for (name, value) in headers {
+            builder = builder.header(name, *value);
+        }
+        builder.body(Vec::new()).unwrap()
+    }
+
+    fn store_with(data: &[u8], name: &str) -> (TempDir, AttachmentStore, String) {
+        let temp_dir = TempDir::new().unwrap();
+        let store = AttachmentStore::with_dir(temp_dir.path().join("attachments"));
+        let hash = store.store_bytes(data, name).unwrap().hash;
+        (temp_dir, store, hash)
+    }
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(parse_range("bytes=0-99", 1000), Ok(Some((0, 99))));
+        assert_eq!(parse_range("bytes=900-", 1000), Ok(Some((900, 999))));
+        assert_eq!(parse_range("bytes=-100", 1000), Ok(Some((900, 999))));
+        assert_eq!(parse_range("bytes=500-5000", 1000), Ok(Some((500, 999))));
+        assert_eq!(parse_range("bytes=0-1,5-9", 1000), Ok(None));
+        assert_eq!(parse_range("items=0-1", 1000), Ok(None));
+        assert_eq!(parse_range("bytes=1000-", 1000), Err(()));
+        assert_eq!(parse_range("bytes=9-5", 1000), Err(()));
+        assert_eq!(parse_range("bytes=-0", 1000), Err(()));
+        assert_eq!(parse_range("bytes=a-b", 1000), Err(()));
+        // Open-ended ranges are capped
+        let big = 100 * MAX_RANGE_BYTES;
+        assert_eq!(
+            parse_range("bytes=0-", big),
+            Ok(Some((0, MAX_RANGE_BYTES - 1)))
+        );
+    }
+
+    #[test]
+    fn test_sniff_mime_type() {
+        assert_eq!(sniff_mime_type(PNG), Some("image/png"));
+        assert_eq!(sniff_mime_type(b"\xff\xd8\xff\xe0"), Some("image/jpeg"));
+        assert_eq!(sniff_mime_type(b"RIFF\0\0\0\0WEBPVP8 "), Some("image/webp"));
+        assert_eq!(sniff_mime_type(b"\0\0\0\x20ftypisom"), Some("video/mp4"));
+        assert_eq!(sniff_mime_type(b"\0\0\0\x20ftypM4A "), Some("audio/mp4"));
+        assert_eq!(sniff_mime_type(b"plain text"), None);
+    }
+
+    #[test]
+    fn test_name_hint() {
+        assert_eq!(
+            name_hint(Some("name=voice%20memo.m4a")),
+            Some("voice memo.m4a".to_string())
+        );
+        assert_eq!(name_hint(Some("v=1&name=a.png")), Some("a.png".to_string()));
+        assert_eq!(name_hint(Some("name=")), None);
+        assert_eq!(name_hint(None), None);
+    }
+
+    #[test]
+    fn test_serves_whole_file() {
+        let (_dir, store, hash) = store_with(PNG, "image.png");
+        let response = respond(
+            &store,
+            &request(Method::GET, &format!("sb-asset://localhost/{}", hash), &[]),
+        );
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.body(), PNG);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "image/png");
+        assert_eq!(
+            response.headers()[header::ETAG],
+            format!("\"{}\"", hash).as_str()
+        );
+        assert_eq!(response.headers()[header::ACCEPT_RANGES], "bytes");
+    }
+
+    #[test]
+    fn test_serves_ranges() {
+        let (_dir, store, hash) = store_with(b"0123456789", "digits.txt");
+        let uri = format!("sb-asset://localhost/{}?name=digits.txt", hash);
+
+        let response = respond(
+            &store,
+            &request(Method::GET, &uri, &[(header::RANGE, "bytes=2-5")]),
+        );
+        assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(response.body(), b"2345");
+        assert_eq!(response.headers()[header::CONTENT_RANGE], "bytes 2-5/10");
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "text/plain");
+
+        let response = respond(
+            &store,
+            &request(Method::GET, &uri, &[(header::RANGE, "bytes=20-")]),
+        );
+        assert_eq!(response.status(), StatusCode::RANGE_NOT_SATISFIABLE);
+        assert_eq!(response.headers()[header::CONTENT_RANGE], "bytes */10");
+    }
+
+    #[test]
+    fn test_caps_unranged_large_files() {
+        let contents = vec![7u8; MAX_RANGE_BYTES as usize + 10];
+        let (_dir, store, hash) = store_with(&contents, "video.mp4");
+        let response = respond(
+            &store,
+            &request(Method::GET, &format!("sb-asset://localhost/{}", hash), &[]),
+        );
+        assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(response.body().len() as u64, MAX_RANGE_BYTES);
+        assert_eq!(
+            response.headers()[header::CONTENT_RANGE],
+            format!("bytes 0-{}/{}", MAX_RANGE_BYTES - 1, contents.len()).as_str()
+        );
+    }
+
+    #[test]
+    fn test_head_and_conditional_requests() {
+        let (_dir, store, hash) = store_with(PNG, "image.png");
+        let uri = format!("http://sb-asset.localhost/{}", hash);
+
+        let response = respond(&store, &request(Method::HEAD, &uri, &[]));
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.body().is_empty());
+        assert_eq!(
+            response.headers()[header::CONTENT_LENGTH],
+            PNG.len().to_string().as_str()
+        );
+
+        let etag = format!("\"{}\"", hash);
+        let response = respond(
+            &store,
+            &request(Method::GET, &uri, &[(header::IF_NONE_MATCH, &etag)]),
+        );
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert!(response.body().is_empty());
+    }
+
+    #[test]
+    fn test_rejects_unknown_and_invalid_requests() {
+        let (_dir, store, hash) = store_with(PNG, "image.png");
+        let missing = "a".repeat(64);
+        for (method, path, expected) in [
+            (Method::GET, missing.as_str(), StatusCode::NOT_FOUND),
+            (Method::GET, "../secrets.json", StatusCode::NOT_FOUND),
+            (Method::POST, hash.as_str(), StatusCode::METHOD_NOT_ALLOWED),
+        ] {
+            let response = respond(
+                &store,
+                &request(method, &format!("sb-asset://localhost/{}", path), &[]),
+            );
+            assert_eq!(response.status(), expected);
+        }
+    }
+}
@@ -0,0 +1,819 @@
+//! Content-addressed attachment store.
+//!
+//! This module provides:
+//! - Storage of uploaded files by SHA-256 under the app data directory
+//! - Deduplication of identical files
+//! - Reference tracking from note content (`sb-attachment://<hash>` links)
+//! - Garbage collection of unreferenced files and storage statistics
+//! - An integrity audit of orphaned and missing files, with repairs
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tauri::{AppHandle, Manager};
+
+use crate::error::AppError;
+
+/// URI scheme notes use to link attachments
+pub const ATTACHMENT_URI_PREFIX: &str = "sb-attachment://";
+
+/// Unreferenced files younger than this are kept, since a note
+/// referencing a fresh upload may not be saved yet
+pub const DEFAULT_GC_GRACE_SECS: u64 = 24 * 60 * 60;
+
+/// Backend endpoint returning all notes with full content
+const EXPORT_NOTES_PATH: &str = "/notes/for-export";
+
+/// Length of a hex-encoded SHA-256 hash
+const HASH_LEN: usize = 64;
+
+/// A stored file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Attachment {
+    pub hash: String,
+    pub size_bytes: u64,
+    /// Original file name
+    pub name: String,
+    pub mime_type: String,
+    /// Link to embed in note content
+    pub uri: String,
+    /// Whether an identical file was already stored
+    pub deduplicated: bool,
+}
+
+/// Store statistics surfaced in the storage breakdown
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AttachmentStats {
+    pub files: usize,
+    pub total_bytes: u64,
+    /// Files no note links to (None when references could not be loaded)
+    pub unreferenced_files: Option<usize>,
+    pub unreferenced_bytes: Option<u64>,
+}
+
+/// Result of a garbage-collection pass
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GcReport {
+    pub removed_files: usize,
+    pub reclaimed_bytes: u64,
+    /// Unreferenced files kept because they are within the grace period
+    pub kept_recent: usize,
+}
+
+/// A blob on disk
+#[derive(Debug, Clone)]
+struct StoredBlob {
+    hash: String,
+    path: PathBuf,
+    size: u64,
+    modified: SystemTime,
+}
+
+/// Disk-backed store keyed by content hash
+pub struct AttachmentStore {
+    dir: PathBuf,
+}
+
+impl AttachmentStore {
+    /// Create a store rooted at `<app_data_dir>/attachments`
+    pub fn new(app_data_dir: &Path) -> Self {
+        Self::with_dir(app_data_dir.join("attachments"))
+    }
+
+    pub fn with_dir(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// Blob path, sharded by the first two hex digits
+    fn blob_path(&self, hash: &str) -> PathBuf {
+        self.dir.join(&hash[..2]).join(hash)
+    }
+
+    fn tmp_dir(&self) -> PathBuf {
+        self.dir.join("tmp")
+    }
+
+    /// Path of a stored file, if present
+    pub fn path_for(&self, hash: &str) -> Option<PathBuf> {
+        if !is_valid_hash(hash) {
+            return None;
+        }
+        let path = self.blob_path(hash);
+        path.is_file().then_some(path)
+    }
+
+    /// Copy a file into the store
+    pub fn store_file(&self, source: &Path) -> Result<Attachment, String> {
+        let name = source
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| "attachment".to_string());
+        let file = fs::File::open(source)
+            .map_err(|e| format!("Failed to open {}: {}", source.display(), e))?;
+        self.store_reader(file, &name)
+    }
+
+    /// Store in-memory data
+    pub fn store_bytes(&self, data: &[u8], name: &str) -> Result<Attachment, String> {
+        self.store_reader(data, name)
+    }
+
+    /// Hash while copying to a temp file, then move it into place
+    fn store_reader(&self, mut reader: impl Read, name: &str) -> Result<Attachment, String> {
+        let tmp_dir = self.tmp_dir();
+        fs::create_dir_all(&tmp_dir)
+            .map_err(|e| format!("Failed to create {}: {}", tmp_dir.display(), e))?;
+        let mut suffix = [0u8; 8];
+        getrandom::fill(&mut suffix).map_err(|e| format!("Failed to generate name: {}", e))?;
+        let tmp_path = tmp_dir.join(
+            suffix
+                .iter()
+                .map(|b| format!("{:02x}", b))
+                .collect::<String>(),
+        );
+
+        let result = (|| {
+            let mut tmp = fs::File::create(&tmp_path)
+                .map_err(|e| format!("Failed to create temp file: {}", e))?;
+            let mut hasher = Sha256::new();
+            let mut size = 0u64;
+            let mut buf = vec![0u8; 64 * 1024];
+            loop {
+                let n = reader
+                    .read(&mut buf)
+                    .map_err(|e| format!("Failed to read attachment: {}", e))?;
+                if n == 0 {
+                    break;
+                }
+                hasher.update(&buf[..n]);
+                tmp.write_all(&buf[..n])
+                    .map_err(|e| format!("Failed to write attachment: {}", e))?;
+                size += n as u64;
+            }
+            tmp.sync_all()
+                .map_err(|e| format!("Failed to write attachment: {}", e))?;
+            let hash: String = hasher
+                .finalize()
+                .iter()
+                .map(|b| format!("{:02x}", b))
+                .collect();
+
+            let blob = self.blob_path(&hash);
+            let deduplicated = blob.is_file();
+            if deduplicated {
+                // Restart the grace period so a pending GC keeps it
+                if let Ok(file) = fs::File::options().write(true).open(&blob) {
+                    let _ = file.set_modified(SystemTime::now());
+                }
+            } else {
+                if let Some(parent) = blob.parent() {
+                    fs::create_dir_all(parent)
+                        .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+                }
+                fs::rename(&tmp_path, &blob)
+                    .map_err(|e| format!("Failed to store attachment: {}", e))?;
+            }
+
+            Ok(Attachment {
+                uri: format!("{}{}", ATTACHMENT_URI_PREFIX, hash),
+                hash,
+                size_bytes: size,
+                name: name.to_string(),
+                mime_type: guess_mime_type(name).to_string(),
+                deduplicated,
+            })
+        })();
+
+        let _ = fs::remove_file(&tmp_path);
+        result
+    }
+
+    fn blobs(&self) -> Vec<StoredBlob> {
+        let mut blobs = Vec::new();
+        let Ok(shards) = fs::read_dir(&self.dir) else {
+            return blobs;
+        };
+        for shard in shards.flatten() {
+            let shard_name = shard.file_name().to_string_lossy().to_string();
+            if shard_name.len() != 2 || !shard.path().is_dir() {
+                continue;
+            }
+            let Ok(entries) = fs::read_dir(shard.path()) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let hash = entry.file_name().to_string_lossy().to_string();
+                let Ok(meta) = entry.metadata() else {
+                    continue;
+                };
+                if is_valid_hash(&hash) && hash.starts_with(&shard_name) && meta.is_file() {
+                    blobs.push(StoredBlob {
+                        hash,
+                        path: entry.path(),
+                        size: meta.len(),
+                        modified: meta.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+                    });
+                }
+            }
+        }
+        blobs
+    }
+
+    pub fn stats(&self, referenced: Option<&HashSet<String>>) -> AttachmentStats {
+        let blobs = self.blobs();
+        let unreferenced: Option<Vec<&StoredBlob>> =
+            referenced.map(|refs| blobs.iter().filter(|b| !refs.contains(&b.hash)).collect());
+        AttachmentStats {
+            files: blobs.len(),
+            total_bytes: blobs.iter().map(|b| b.size).sum(),
+            unreferenced_files: unreferenced.as_ref().map(|u| u.len()),
+            unreferenced_bytes: unreferenced.map(|u| u.iter().map(|b| b.size).sum()),
+        }
+    }
+
+    /// Delete unreferenced files older than `grace` and leftover temp files
+    pub fn collect_garbage(
+        &self,
+        referenced: &HashSet<String>,
+        grace: Duration,
+        now: SystemTime,
+    ) -> GcReport {
+        let mut report = GcReport::default();
+        let is_old = |modified: SystemTime| {
+            now.duration_since(modified)
+                .map(|age| age >= grace)
+                .unwrap_or(false)
+        };
+
+        for blob in self.blobs() {
+            if referenced.contains(&blob.hash) {
+                continue;
+            }
+            if !is_old(blob.modified) {
+                report.kept_recent += 1;
+                continue;
+            }
+            match fs::remove_file(&blob.path) {
+                Ok(()) => {
+                    report.removed_files += 1;
+                    report.reclaimed_bytes += blob.size;
+                }
+                Err(e) => tracing::warn!("Failed to remove attachment {}: {}", blob.hash, e),
+            }
+        }
+
+        // Temp files from interrupted uploads
+        if let Ok(entries) = fs::read_dir(self.tmp_dir()) {
+            for entry in entries.flatten() {
+                let Ok(meta) = entry.metadata() else {
+                    continue;
+                };
+                if is_old(meta.modified().unwrap_or(SystemTime::UNIX_EPOCH))
+                    && fs::remove_file(entry.path()).is_ok()
+                {
+                    report.reclaimed_bytes += meta.len();
+                }
+            }
+        }
+
+        report
+    }
+}
+
+fn is_valid_hash(hash: &str) -> bool {
+    hash.len() == HASH_LEN
+        && hash
+            .chars()
+            .all(|c| c.is_ascii_digit() || ('a'..='f').contains(&c))
+}
+
+/// Attachment hashes linked from a piece of text
+pub fn extract_references(text: &str) -> HashSet<String> {
+    text.match_indices(ATTACHMENT_URI_PREFIX)
+        .filter_map(|(index, prefix)| {
+            let rest = &text[index + prefix.len()..];
+            let hash = rest.get(..HASH_LEN)?.to_ascii_lowercase();
+            is_valid_hash(&hash).then_some(hash)
+        })
+        .collect()
+}
+
+pub(crate) fn guess_mime_type(name: &str) -> &'static str {
+    let extension = Path::new(name)
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    match extension.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        "pdf" => "application/pdf",
+        "txt" => "text/plain",
+        "md" => "text/markdown",
+        "csv" => "text/csv",
+        "json" => "application/json",
+        "mp3" => "audio/mpeg",
+        "m4a" => "audio/mp4",
+        "wav" => "audio/wav",
+        "mp4" => "video/mp4",
+        "zip" => "application/zip",
+        _ => "application/octet-stream",
+    }
+}
+
+/// A note as returned by the export endpoint
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ExportedNote {
+    id: String,
+    title: String,
+    content: String,
+    #[serde(default)]
+    tags: Vec<String>,
+    folder: Option<String>,
+}
+
+async fn fetch_notes(app: &AppHandle) -> Result<Vec<ExportedNote>, String> {
+    let response =
+        crate::proxy::send_backend_request(app, "GET", EXPORT_NOTES_PATH, None, None).await?;
+    serde_json::from_value(response).map_err(|e| format!("Invalid notes response: {}", e))
+}
+
+/// Attachment hashes mapped to the IDs of the notes linking them
+fn note_references(notes: &[ExportedNote]) -> HashMap<String, Vec<String>> {
+    let mut references: HashMap<String, Vec<String>> = HashMap::new();
+    for note in notes {
+        for hash in extract_references(&note.content) {
+            references.entry(hash).or_default().push(note.id.clone());
+        }
+    }
+    references
+}
+
+/// Collect attachment references from every note, archived ones included
+pub async fn referenced_hashes(app: &AppHandle) -> Result<HashSet<String>, String> {
+    let notes = fetch_notes(app).await?;
+    Ok(note_references(&notes).into_keys().collect())
+}
+
+// ============================================================
+// Integrity Audit
+// ============================================================
+
+/// A stored file no note links to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OrphanFile {
+    pub hash: String,
+    pub size_bytes: u64,
+}
+
+/// A link whose file is not in the store
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MissingAttachment {
+    pub hash: String,
+    /// Notes linking the missing file
+    pub note_ids: Vec<String>,
+}
+
+/// Result of cross-checking the store against note references
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AttachmentAudit {
+    pub checked_files: usize,
+    pub checked_references: usize,
+    pub orphans: Vec<OrphanFile>,
+    pub missing: Vec<MissingAttachment>,
+}
+
+/// Audit counts surfaced in diagnostics
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AttachmentAuditSummary {
+    pub orphan_files: usize,
+    pub orphan_bytes: u64,
+    pub missing_attachments: usize,
+}
+
+impl AttachmentAudit {
+    pub fn summary(&self) -> AttachmentAuditSummary {
+        AttachmentAuditSummary {
+            orphan_files: self.orphans.len(),
+            orphan_bytes: self.orphans.iter().map(|o| o.size_bytes).sum(),
+            missing_attachments: self.missing.len(),
+        }
+    }
+}
+
+/// A fix for an audit finding
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RepairAction {
+    /// Delete a stored file if it is still unreferenced
+    DeleteOrphan { hash: String },
+    /// Point links to a missing file at another stored file
+    Relink {
+        missing: String,
+        replacement: String,
+    },
+    /// Store a recovered copy (e.g. from an extracted backup); its content
+    /// must hash to the missing attachment
+    RestoreFromFile { hash: String, path: String },
+}
+
+/// Outcome of one repair action
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepairResult {
+    pub action: RepairAction,
+    pub error: Option<String>,
+}
+
+impl AttachmentStore {
+    /// Compare stored files against references
+    pub fn audit(&self, references: &HashMap<String, Vec<String>>) -> AttachmentAudit {
+        let blobs = self.blobs();
+        let stored: HashSet<&str> = blobs.iter().map(|b| b.hash.as_str()).collect();
+
+        let mut orphans: Vec<OrphanFile> = blobs
+            .iter()
+            .filter(|b| !references.contains_key(&b.hash))
+            .map(|b| OrphanFile {
+                hash: b.hash.clone(),
+                size_bytes: b.size,
+            })
+            .collect();
+        orphans.sort_by(|a, b| a.hash.cmp(&b.hash));
+
+        let mut missing: Vec<MissingAttachment> = references
+            .iter()
+            .filter(|(hash, _)| !stored.contains(hash.as_str()))
+            .map(|(hash, note_ids)| {
+                let mut note_ids = note_ids.clone();
+                note_ids.sort();
+                MissingAttachment {
+                    hash: hash.clone(),
+                    note_ids,
+                }
+            })
+            .collect();
+        missing.sort_by(|a, b| a.hash.cmp(&b.hash));
+
+        AttachmentAudit {
+            checked_files: blobs.len(),
+            checked_references: references.len(),
+            orphans,
+            missing,
+        }
+    }
+
+    /// Store a recovered file, checking it is the expected content
+    fn restore_file(&self, hash: &str, source: &Path) -> Result<(), String> {
+        let attachment = self.store_file(source)?;
+        if attachment.hash != hash {
+            // Keep the store clean if the wrong file was picked
+            if !attachment.deduplicated {
+                let _ = fs::remove_file(self.blob_path(&attachment.hash));
+            }
+            return Err(format!(
+                "{} does not match attachment {}",
+                source.display(),
+                hash
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Replace links to one attachment with another in a note's content
+fn relink_content(content: &str, missing: &str, replacement: &str) -> String {
+    let pattern = regex_lite::Regex::new(&format!(
+        "(?i){}{}",
+        regex_lite::escape(ATTACHMENT_URI_PREFIX),
+        missing
+    ))
+    .expect("escaped prefix and hex hash form a valid pattern");
+    pattern
+        .replace_all(
+            content,
+            format!("{}{}", ATTACHMENT_URI_PREFIX, replacement).as_str(),
+        )
+        .into_owned()
+}
+
+async fn apply_repair(
+    app: &AppHandle,
+    store: &AttachmentStore,
+    notes: &[ExportedNote],
+    action: &RepairAction,
+) -> Result<(), String> {
+    match action {
+        RepairAction::DeleteOrphan { hash } => {
+            let path = store
+                .path_for(hash)
+                .ok_or_else(|| format!("Attachment {} not found", hash))?;
+            if note_references(notes).contains_key(hash) {
+                return Err(format!("Attachment {} is referenced by a note", hash));
+            }
+            fs::remove_file(path).map_err(|e| format!("Failed to delete {}: {}", hash, e))
+        }
+        RepairAction::Relink {
+            missing,
+            replacement,
+        } => {
+            if !is_valid_hash(missing) {
+                return Err(format!("Invalid attachment hash: {}", missing));
+            }
+            if store.path_for(replacement).is_none() {
+                return Err(format!("Replacement {} is not stored", replacement));
+            }
+            for note in notes {
+                let content = relink_content(&note.content, missing, replacement);
+                if content == note.content {
+                    continue;
+                }
+                crate::proxy::send_backend_request(
+                    app,
+                    "PUT",
+                    &format!("/notes/{}", note.id),
+                    Some(&serde_json::json!({
+                        "title": note.title,
+                        "content": content,
+                        "tags": note.tags,
+                        "folder": note.folder,
+                        "updateContentJson": true,
+                    })),
+                    None,
+                )
+                .await?;
+            }
+            Ok(())
+        }
+        RepairAction::RestoreFromFile { hash, path } => {
+            if !is_valid_hash(hash) {
+                return Err(format!("Invalid attachment hash: {}", hash));
+            }
+            store.restore_file(hash, Path::new(path))
+        }
+    }
+}
+
+// ============================================================
+// Commands
+// ============================================================
+
+/// Copy a file into the attachment store
+#[tauri::command]
+pub async fn store_attachment(app: AppHandle, path: String) -> Result<Attachment, AppError> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    tokio::task::spawn_blocking(move || {
+        AttachmentStore::new(&app_data_dir).store_file(Path::new(&path))
+    })
+    .await?
+    .map_err(AppError::from)
+}
+
+/// Resolve an attachment hash to its file path
+#[tauri::command]
+pub async fn get_attachment_path(app: AppHandle, hash: String) -> Result<String, AppError> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    AttachmentStore::new(&app_data_dir)
+        .path_for(&hash.to_ascii_lowercase())
+        .map(|p| p.to_string_lossy().to_string())
+        .ok_or_else(|| AppError::NotFound(format!("Attachment {} not found", hash)))
+}
+
+/// Delete attachments no note links to
+///
+/// Fails rather than guessing when references can't be loaded, since an
+/// empty reference set would delete everything.
+#[tauri::command]
+pub async fn reclaim_space(app: AppHandle) -> Result<GcReport, AppError> {
+    let referenced = referenced_hashes(&app).await?;
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let report = tokio::task::spawn_blocking(move || {
+        AttachmentStore::new(&app_data_dir).collect_garbage(
+            &referenced,
+            Duration::from_secs(DEFAULT_GC_GRACE_SECS),
+            SystemTime::now(),
+        )
+    })
+    .await
+    .map_err(|e| format!("Task panicked: {}", e))?;
+
+    tracing::info!(
+        "Attachment GC removed {} file(s), reclaimed {} bytes",
+        report.removed_files,
+        report.reclaimed_bytes
+    );
+    Ok(report)
+}
+
+/// Cross-check stored files against note references
+#[tauri::command]
+pub async fn audit_attachments(app: AppHandle) -> Result<AttachmentAudit, AppError> {
+    let references = note_references(&fetch_notes(&app).await?);
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    tokio::task::spawn_blocking(move || AttachmentStore::new(&app_data_dir).audit(&references))
+        .await
+        .map_err(AppError::from)
+}
+
+/// Apply fixes for audit findings, reporting the outcome of each
+#[tauri::command]
+pub async fn repair_attachments(
+    app: AppHandle,
+    actions: Vec<RepairAction>,
+) -> Result<Vec<RepairResult>, AppError> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let store = AttachmentStore::new(&app_data_dir);
+
+    // Fetched once up front; references are re-checked against it before deleting
+    let notes = fetch_notes(&app).await?;
+    let mut results = Vec::with_capacity(actions.len());
+    for action in actions {
+        let error = apply_repair(&app, &store, &notes, &action).await.err();
+        if let Some(ref e) = error {
+            tracing::warn!("Attachment repair {:?} failed: {}", action, e);
+        }
+        results.push(RepairResult { action, error });
+    }
+    Ok(results)
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const HOUR: Duration = Duration::from_secs(3600);
+
+    #[test]
+    fn test_store_dedupes_identical_content() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = AttachmentStore::with_dir(temp_dir.path().join("attachments"));
+
+        let first = store.store_bytes(b"hello", "a.png").unwrap();
+        let second = store.store_bytes(b"hello", "b.txt").unwrap();
+        let other = store.store_bytes(b"world", "c.pdf").unwrap();
+
+        assert!(!first.deduplicated);
+        assert!(second.deduplicated);
+        assert_eq!(first.hash, second.hash);
+        assert_ne!(first.hash, other.hash);
+        assert_eq!(first.mime_type, "image/png");
+        assert_eq!(first.uri, format!("sb-attachment://{}", first.hash));
+        assert_eq!(
+            fs::read(store.path_for(&first.hash).unwrap()).unwrap(),
+            b"hello"
+        );
+
+        let stats = store.stats(None);
+        assert_eq!(stats.files, 2);
+        assert_eq!(stats.total_bytes, 10);
+        assert!(fs::read_dir(store.tmp_dir()).unwrap().next().is_none());
+    }
+
+    #[test]
+    fn test_store_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = temp_dir.path().join("photo.JPG");
+        fs::write(&source, b"jpeg").unwrap();
+        let store = AttachmentStore::with_dir(temp_dir.path().join("attachments"));
+
+        let attachment = store.store_file(&source).unwrap();
+        assert_eq!(attachment.name, "photo.JPG");
+        assert_eq!(attachment.mime_type, "image/jpeg");
+        assert_eq!(attachment.size_bytes, 4);
+    }
+
+    #[test]
+    fn test_extract_references() {
+        let hash = "a".repeat(64);
+        let text = format!(
+            "![img](sb-attachment://{}) and [doc](sb-attachment://{}) sb-attachment://short",
+            hash,
+            "B".repeat(64)
+        );
+        let refs = extract_references(&text);
+        assert_eq!(refs.len(), 2);
+        assert!(refs.contains(&hash));
+        assert!(refs.contains(&"b".repeat(64)));
+    }
+
+    #[test]
+    fn test_gc_removes_only_old_unreferenced_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = AttachmentStore::with_dir(temp_dir.path().join("attachments"));
+        let kept = store.store_bytes(b"linked", "a").unwrap();
+        let orphan = store.store_bytes(b"orphan", "b").unwrap();
+        let referenced: HashSet<String> = [kept.hash.clone()].into();
+
+        let stats = store.stats(Some(&referenced));
+        assert_eq!(stats.unreferenced_files, Some(1));
+        assert_eq!(stats.unreferenced_bytes, Some(6));
+
+        // Within the grace period nothing is removed
+        let report = store.collect_garbage(&referenced, HOUR, SystemTime::now());
+        assert_eq!(report.removed_files, 0);
+        assert_eq!(report.kept_recent, 1);
+
+        let later = SystemTime::now() + 2 * HOUR;
+        let report = store.collect_garbage(&referenced, HOUR, later);
+        assert_eq!(report.removed_files, 1);
+        assert_eq!(report.reclaimed_bytes, 6);
+        assert!(store.path_for(&orphan.hash).is_none());
+        assert!(store.path_for(&kept.hash).is_some());
+    }
+
+    #[test]
+    fn test_path_for_rejects_invalid_hashes() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = AttachmentStore::with_dir(temp_dir.path().to_path_buf());
+        assert!(store.path_for("../../etc/passwd").is_none());
+        assert!(store.path_for(&"g".repeat(64)).is_none());
+    }
+
+    #[test]
+    fn test_audit_reports_orphans_and_missing() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = AttachmentStore::with_dir(temp_dir.path().join("attachments"));
+        let linked = store.store_bytes(b"linked", "a").unwrap();
+        let orphan = store.store_bytes(b"orphan", "b").unwrap();
+        let gone = "c".repeat(64);
+
+        let notes = vec![
+            ExportedNote {
+                id: "n2".to_string(),
+                title: String::new(),
+                content: format!(
+                    "{} {}",
+                    linked.uri,
+                    ATTACHMENT_URI_PREFIX.to_string() + &gone
+                ),
+                tags: vec![],
+                folder: None,
+            },
+            ExportedNote {
+                id: "n1".to_string(),
+                title: String::new(),
+                content: format!("{}{}", ATTACHMENT_URI_PREFIX, gone),
+                tags: vec![],
+                folder: None,
+            },
+        ];
+        let audit = store.audit(&note_references(&notes));
+
+        assert_eq!(audit.checked_files, 2);
+        assert_eq!(audit.checked_references, 2);
+        assert_eq!(
+            audit.orphans,
+            vec![OrphanFile {
+                hash: orphan.hash,
+                size_bytes: 6
+            }]
+        );
+        assert_eq!(
+            audit.missing,
+            vec![MissingAttachment {
+                hash: gone,
+                note_ids: vec!["n1".to_string(), "n2".to_string()]
+            }]
+        );
+        assert_eq!(audit.summary().orphan_bytes, 6);
+    }
+
+    #[test]
+    fn test_restore_file_checks_hash() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = AttachmentStore::with_dir(temp_dir.path().join("attachments"));
+        let recovered = temp_dir.path().join("recovered.png");
+        fs::write(&recovered, b"picture").unwrap();
+        let hash = format!("{:x}", Sha256::digest(b"picture"));
+
+        assert!(store.restore_file(&"d".repeat(64), &recovered).is_err());
+        assert_eq!(store.stats(None).files, 0);
+        store.restore_file(&hash, &recovered).unwrap();
+        assert!(store.path_for(&hash).is_some());
+    }
+
+    #[test]
+    fn test_relink_content() {
+        let (old, new) = ("a".repeat(64), "b".repeat(64));
+        let content = format!(
+            "![x](sb-attachment://{}) sb-attachment://{}",
+            old.to_uppercase(),
+            old
+        );
+        assert_eq!(
+            relink_content(&content, &old, &new),
+            format!("![x](sb-attachment://{}) sb-attachment://{}", new, new)
+        );
+    }
+}
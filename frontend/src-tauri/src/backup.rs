@@ -0,0 +1,1673 @@
+//! Encrypted database backups.
+//!
+//! This module provides:
+//! - `pg_dump` archives encrypted with AES-256-GCM under a keychain-stored key
+//! - Backups written to any folder, including cloud-synced ones
+//!   (iCloud Drive, Dropbox, OneDrive)
+//! - Scheduled backups with retention of the newest N archives
+//! - Integrity verification (checksum, authenticated decryption, dump listing)
+//! - Restore previews comparing a backup with the live database
+//! - Restore into the embedded database
+//! - Local backups: plain compressed `pg_dump` archives kept under the app
+//!   data dir's `backups` folder, for quick copies without a configured
+//!   destination
+//! - Restore of a local backup with the backend stopped, after checking the
+//!   dump and taking a pre-restore backup of the live database
+//! - Daily or weekly local backups keeping the newest N scheduled ones, with
+//!   the last backup time recorded in `ServiceConfig` and shown in the tray
+//!
+//! Archive format: an 8-byte magic and a 7-byte random nonce prefix, followed
+//! by length-prefixed AES-GCM chunks. Each chunk nonce is the prefix, a 32-bit
+//! chunk counter, and a final-chunk flag, so reordered or truncated archives
+//! fail to decrypt.
+
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Arc;
+use tauri::{AppHandle, Manager};
+
+use crate::config::{load_json, save_json_atomic, ServiceConfig};
+use crate::database::PostgresManager;
+use crate::error::{database_not_running, AppError};
+use crate::jobs::JobContext;
+use crate::keychain;
+use crate::scheduler::{
+    JobAction, Schedule, ScheduledJob, Scheduler, ENCRYPTED_BACKUP_JOB_ID, LOCAL_BACKUP_JOB_ID,
+};
+use crate::services::{ServiceCommand, ServiceManager, ServicePhase};
+use crate::AppState;
+
+/// Identifies an encrypted backup archive (format version 1)
+const MAGIC: &[u8; 8] = b"SBBKUP01";
+
+/// Plaintext bytes per encrypted chunk
+const CHUNK_SIZE: usize = 1024 * 1024;
+
+/// AES-GCM authentication tag length
+const TAG_SIZE: usize = 16;
+
+/// Keychain account holding the backup encryption key
+const KEY_ACCOUNT: &str = "backup-encryption-key";
+
+/// Encrypted archive file extension
+const ARCHIVE_EXTENSION: &str = "sbbackup";
+
+/// Default number of archives kept in the destination
+const DEFAULT_KEEP: usize = 7;
+
+/// Folder under the app data dir holding local backups
+const LOCAL_BACKUP_DIR: &str = "backups";
+
+/// Local backup file extension (`pg_dump` custom format)
+const LOCAL_EXTENSION: &str = "dump";
+
+/// ID suffix of scheduled local backups; only these are pruned
+const SCHEDULED_LABEL: &str = "auto";
+
+/// Stages a local restore reports as job progress
+const RESTORE_STAGES: u64 = 5;
+
+/// Scratch database backups are restored into for previews
+const PREVIEW_DATABASE: &str = "secondbrain_restore_preview";
+
+/// Per-database statistics compared by restore previews
+const STATS_SQL: &str = "SELECT count(*) FILTER (WHERE NOT is_deleted), \
+     count(*) FILTER (WHERE is_deleted), \
+     (SELECT count(*) FROM chat_conversations WHERE NOT is_deleted), \
+     min(updated_at) FILTER (WHERE NOT is_deleted), \
+     max(updated_at) FILTER (WHERE NOT is_deleted) \
+     FROM notes";
+
+/// Latest applied migration and the number applied
+const SCHEMA_SQL: &str = "SELECT max(\"MigrationId\"), count(*) FROM \"__EFMigrationsHistory\"";
+
+/// Live note IDs with their last-modified times
+const NOTE_VERSIONS_SQL: &str = "SELECT id, updated_at FROM notes WHERE NOT is_deleted";
+
+/// Encrypted backup settings
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EncryptedBackupSettings {
+    /// Folder archives are written to
+    pub destination: Option<PathBuf>,
+    /// When to back up automatically, disabled when None
+    pub schedule: Option<Schedule>,
+    /// Number of archives to keep; older ones are deleted
+    pub keep: usize,
+}
+
+impl Default for EncryptedBackupSettings {
+    fn default() -> Self {
+        Self {
+            destination: None,
+            schedule: None,
+            keep: DEFAULT_KEEP,
+        }
+    }
+}
+
+impl EncryptedBackupSettings {
+    fn path(app_data_dir: &Path) -> PathBuf {
+        app_data_dir.join("encrypted-backup-settings.json")
+    }
+
+    /// Load settings, returning defaults if missing or invalid
+    pub fn load(app_data_dir: &Path) -> Self {
+        load_json(&Self::path(app_data_dir)).unwrap_or_default()
+    }
+
+    /// Save settings atomically
+    pub fn save(&self, app_data_dir: &Path) -> Result<(), String> {
+        save_json_atomic(&Self::path(app_data_dir), self)
+    }
+
+    /// Validate settings
+    pub fn validate(&self) -> Result<(), String> {
+        if self.keep == 0 {
+            return Err("At least one backup must be kept".to_string());
+        }
+        if let Some(ref schedule) = self.schedule {
+            schedule.validate()?;
+            if self.destination.is_none() {
+                return Err("Choose a backup folder before enabling a schedule".to_string());
+            }
+        }
+        if let Some(ref destination) = self.destination {
+            if !destination.is_absolute() {
+                return Err("Backup folder must be an absolute path".to_string());
+            }
+        }
+        Ok(())
+    }
+
+    fn destination(&self) -> Result<&Path, String> {
+        self.destination
+            .as_deref()
+            .ok_or_else(|| "No backup folder configured".to_string())
+    }
+}
+
+/// Local backup schedule
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackupSchedule {
+    /// When to back up automatically (daily or weekly), disabled when None
+    pub schedule: Option<Schedule>,
+    /// Number of scheduled backups to keep; manual and pre-restore backups
+    /// are never deleted
+    pub keep: usize,
+}
+
+impl Default for BackupSchedule {
+    fn default() -> Self {
+        Self {
+            schedule: None,
+            keep: DEFAULT_KEEP,
+        }
+    }
+}
+
+impl BackupSchedule {
+    fn path(app_data_dir: &Path) -> PathBuf {
+        app_data_dir.join("backup-schedule.json")
+    }
+
+    /// Load the schedule, returning defaults if missing or invalid
+    pub fn load(app_data_dir: &Path) -> Self {
+        load_json(&Self::path(app_data_dir)).unwrap_or_default()
+    }
+
+    /// Save the schedule atomically
+    pub fn save(&self, app_data_dir: &Path) -> Result<(), String> {
+        save_json_atomic(&Self::path(app_data_dir), self)
+    }
+
+    /// Validate the schedule
+    pub fn validate(&self) -> Result<(), String> {
+        if self.keep == 0 {
+            return Err("At least one backup must be kept".to_string());
+        }
+        match self.schedule {
+            None => Ok(()),
+            Some(ref schedule @ (Schedule::Daily { .. } | Schedule::Weekly { .. })) => {
+                schedule.validate()
+            }
+            Some(_) => Err("Automatic backups run daily or weekly".to_string()),
+        }
+    }
+}
+
+/// Metadata written next to each archive
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackupManifest {
+    /// Archive file stem
+    pub id: String,
+    pub file_name: String,
+    /// Creation time (RFC 3339)
+    pub created_at: String,
+    pub size_bytes: u64,
+    /// SHA-256 of the encrypted archive
+    pub sha256: String,
+    pub app_version: String,
+}
+
+impl BackupManifest {
+    fn path(destination: &Path, id: &str) -> PathBuf {
+        destination.join(format!("{}.json", id))
+    }
+}
+
+/// A local backup
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LocalBackup {
+    /// File stem, e.g. `secondbrain-20250101-120000`
+    pub id: String,
+    pub path: PathBuf,
+    /// Creation time (RFC 3339)
+    pub created_at: String,
+    pub size_bytes: u64,
+}
+
+/// Result of verifying an archive
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BackupVerification {
+    pub id: String,
+    /// The archive matches the checksum in its manifest
+    pub checksum_ok: bool,
+    /// Every chunk decrypted and authenticated
+    pub decrypt_ok: bool,
+    /// `pg_restore` could read the dump; None if PostgreSQL is unavailable
+    pub dump_ok: Option<bool>,
+    pub error: Option<String>,
+}
+
+impl BackupVerification {
+    pub fn is_valid(&self) -> bool {
+        self.checksum_ok && self.decrypt_ok && self.dump_ok != Some(false)
+    }
+}
+
+/// Contents of one database, as compared by a restore preview
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DatabaseSummary {
+    pub notes: u64,
+    /// Notes in the trash
+    pub deleted_notes: u64,
+    pub conversations: u64,
+    pub oldest_update: Option<String>,
+    pub newest_update: Option<String>,
+    /// Latest applied schema migration
+    pub schema_version: Option<String>,
+    pub migration_count: u64,
+}
+
+/// What restoring a backup would change in the live database
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RestorePreview {
+    pub backup_id: String,
+    pub backup_created_at: String,
+    pub backup: DatabaseSummary,
+    /// None if the live database could not be read
+    pub live: Option<DatabaseSummary>,
+    /// Live notes missing from the backup, which a restore would remove
+    pub notes_removed: u64,
+    /// Backup notes missing from the live database, which a restore would bring back
+    pub notes_added: u64,
+    /// Notes whose live version differs from the backup's
+    pub notes_changed: u64,
+    pub notes_unchanged: u64,
+    pub warnings: Vec<String>,
+}
+
+// ============================================================
+// Encryption
+// ============================================================
+
+fn chunk_nonce(prefix: &[u8; 7], counter: u32, last: bool) -> [u8; 12] {
+    let mut nonce = [0u8; 12];
+    nonce[..7].copy_from_slice(prefix);
+    nonce[7..11].copy_from_slice(&counter.to_be_bytes());
+    nonce[11] = last as u8;
+    nonce
+}
+
+/// Read until `buf` is full or EOF, returning the bytes read
+fn read_full(reader: &mut impl Read, buf: &mut [u8]) -> std::io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..])? {
+            0 => break,
+            n => filled += n,
+        }
+    }
+    Ok(filled)
+}
+
+/// Encrypt `reader` into `writer`
+pub fn encrypt_stream(
+    mut reader: impl Read,
+    mut writer: impl Write,
+    key: &[u8; 32],
+) -> Result<(), String> {
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+
+    let mut prefix = [0u8; 7];
+    getrandom::fill(&mut prefix).map_err(|e| format!("Failed to generate nonce: {}", e))?;
+
+    let mut header = Vec::with_capacity(MAGIC.len() + prefix.len());
+    header.extend_from_slice(MAGIC);
+    header.extend_from_slice(&prefix);
+    writer
+        .write_all(&header)
+        .map_err(|e| format!("Failed to write backup: {}", e))?;
+
+    let read_err = |e: std::io::Error| format!("Failed to read dump: {}", e);
+    let mut current = vec![0u8; CHUNK_SIZE];
+    let mut next = vec![0u8; CHUNK_SIZE];
+    let mut current_len = read_full(&mut reader, &mut current).map_err(read_err)?;
+    let mut counter: u32 = 0;
+
+    loop {
+        // Look ahead one chunk to know whether this one is the last
+        let next_len = if current_len == CHUNK_SIZE {
+            read_full(&mut reader, &mut next).map_err(read_err)?
+        } else {
+            0
+        };
+        let last = next_len == 0;
+
+        let nonce = chunk_nonce(&prefix, counter, last);
+        let ciphertext = cipher
+            .encrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: &current[..current_len],
+                    aad: &header,
+                },
+            )
+            .map_err(|_| "Encryption failed".to_string())?;
+
+        writer
+            .write_all(&(ciphertext.len() as u32).to_be_bytes())
+            .and_then(|_| writer.write_all(&ciphertext))
+            .map_err(|e| format!("Failed to write backup: {}", e))?;
+
+        if last {
+            break;
+        }
+        counter = counter
+            .checked_add(1)
+            .ok_or_else(|| "Backup too large".to_string())?;
+        std::mem::swap(&mut current, &mut next);
+        current_len = next_len;
+    }
+
+    writer
+        .flush()
+        .map_err(|e| format!("Failed to write backup: {}", e))
+}
+
+/// Read a chunk length prefix, returning None at EOF
+fn read_chunk_len(reader: &mut impl Read) -> Result<Option<usize>, String> {
+    let mut len = [0u8; 4];
+    match read_full(reader, &mut len).map_err(|e| format!("Failed to read backup: {}", e))? {
+        0 => Ok(None),
+        4 => {
+            let len = u32::from_be_bytes(len) as usize;
+            if !(TAG_SIZE..=CHUNK_SIZE + TAG_SIZE).contains(&len) {
+                return Err("Backup is corrupted (invalid chunk length)".to_string());
+            }
+            Ok(Some(len))
+        }
+        _ => Err("Backup is truncated".to_string()),
+    }
+}
+
+/// Decrypt `reader` into `writer`, failing on tampering, truncation, or a wrong key
+pub fn decrypt_stream(
+    mut reader: impl Read,
+    mut writer: impl Write,
+    key: &[u8; 32],
+) -> Result<(), String> {
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+
+    let mut header = [0u8; 15];
+    if read_full(&mut reader, &mut header).map_err(|e| format!("Failed to read backup: {}", e))?
+        != header.len()
+        || &header[..8] != MAGIC
+    {
+        return Err("Not an encrypted Second Brain backup".to_string());
+    }
+    let mut prefix = [0u8; 7];
+    prefix.copy_from_slice(&header[8..]);
+
+    let mut next_len = read_chunk_len(&mut reader)?;
+    let mut counter: u32 = 0;
+
+    while let Some(len) = next_len {
+        let mut ciphertext = vec![0u8; len];
+        if read_full(&mut reader, &mut ciphertext)
+            .map_err(|e| format!("Failed to read backup: {}", e))?
+            != len
+        {
+            return Err("Backup is truncated".to_string());
+        }
+
+        next_len = read_chunk_len(&mut reader)?;
+        let nonce = chunk_nonce(&prefix, counter, next_len.is_none());
+        let plaintext = cipher
+            .decrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: &ciphertext,
+                    aad: &header,
+                },
+            )
+            .map_err(|_| {
+                "Backup failed authentication (wrong key, corrupted, or truncated)".to_string()
+            })?;
+
+        writer
+            .write_all(&plaintext)
+            .map_err(|e| format!("Failed to write dump: {}", e))?;
+        counter = counter.wrapping_add(1);
+    }
+
+    if counter == 0 {
+        return Err("Backup is truncated".to_string());
+    }
+    writer
+        .flush()
+        .map_err(|e| format!("Failed to write dump: {}", e))
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn parse_key(hex: &str) -> Result<[u8; 32], String> {
+    let hex = hex.trim();
+    if hex.len() != 64 || !hex.is_ascii() {
+        return Err("Backup key must be 64 hex characters".to_string());
+    }
+    let mut key = [0u8; 32];
+    for (i, byte) in key.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16)
+            .map_err(|_| "Backup key must be 64 hex characters".to_string())?;
+    }
+    Ok(key)
+}
+
+/// Load the backup key from the keychain, generating one on first use
+fn load_or_create_key(app_data_dir: &Path) -> Result<[u8; 32], String> {
+    if let Some(hex) = keychain::get_secret(app_data_dir, KEY_ACCOUNT)? {
+        return parse_key(&hex);
+    }
+
+    let mut key = [0u8; 32];
+    getrandom::fill(&mut key).map_err(|e| format!("Failed to generate backup key: {}", e))?;
+    keychain::set_secret(app_data_dir, KEY_ACCOUNT, &encode_hex(&key))?;
+    tracing::info!("Generated new backup encryption key");
+    Ok(key)
+}
+
+fn sha256_file(path: &Path) -> Result<String, String> {
+    let mut file = BufReader::new(
+        File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?,
+    );
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let n = file
+            .read(&mut buf)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(encode_hex(&hasher.finalize()))
+}
+
+// ============================================================
+// Archives
+// ============================================================
+
+/// Archives in `destination`, newest first
+fn list_manifests(destination: &Path) -> Vec<BackupManifest> {
+    let Ok(entries) = std::fs::read_dir(destination) else {
+        return Vec::new();
+    };
+
+    let mut manifests: Vec<BackupManifest> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|p| load_json::<BackupManifest>(&p))
+        .filter(|m| destination.join(&m.file_name).exists())
+        .collect();
+    manifests.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    manifests
+}
+
+fn find_manifest(destination: &Path, id: &str) -> Result<BackupManifest, String> {
+    list_manifests(destination)
+        .into_iter()
+        .find(|m| m.id == id)
+        .ok_or_else(|| format!("Backup not found: {}", id))
+}
+
+/// Delete all but the newest `keep` archives
+fn apply_retention(destination: &Path, keep: usize) {
+    for manifest in list_manifests(destination).into_iter().skip(keep) {
+        tracing::info!("Removing old backup {}", manifest.id);
+        let _ = std::fs::remove_file(destination.join(&manifest.file_name));
+        let _ = std::fs::remove_file(BackupManifest::path(destination, &manifest.id));
+    }
+}
+
+/// The embedded database manager, if PostgreSQL has been started
+pub(crate) fn postgres_manager(app: &AppHandle) -> Option<Arc<PostgresManager>> {
+    app.state::<AppState>().postgres_manager.read().clone()
+}
+
+/// Run a PostgreSQL client tool against the embedded database
+pub(crate) fn pg_command(manager: &PostgresManager, tool: &str) -> Result<Command, String> {
+    let path = manager.get_bin_dir().join(tool);
+    if !path.exists() {
+        return Err(format!("{} not found at {:?}", tool, path));
+    }
+    let mut command = Command::new(path);
+    command
+        .arg("-h")
+        .arg("localhost")
+        .arg("-p")
+        .arg(manager.get_port().to_string())
+        .arg("-U")
+        .arg("secondbrain");
+    Ok(command)
+}
+
+pub(crate) fn run(mut command: Command, what: &str) -> Result<String, String> {
+    let output = command
+        .output()
+        .map_err(|e| format!("Failed to run {}: {}", what, e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "{} failed: {}",
+            what,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Temporary plaintext dump under the app data dir, removed on drop
+pub(crate) struct TempDump(pub PathBuf);
+
+impl TempDump {
+    pub(crate) fn new(app_data_dir: &Path) -> Result<Self, String> {
+        let dir = app_data_dir.join("tmp");
+        std::fs::create_dir_all(&dir)
+            .map_err(|e| format!("Failed to create temp directory: {}", e))?;
+        Ok(Self(dir.join(format!(
+            "backup-{}-{}.dump",
+            std::process::id(),
+            Local::now().timestamp_nanos_opt().unwrap_or_default()
+        ))))
+    }
+}
+
+impl Drop for TempDump {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+fn decrypt_to(archive: &Path, out: &Path, key: &[u8; 32]) -> Result<(), String> {
+    let reader =
+        BufReader::new(File::open(archive).map_err(|e| format!("Failed to open backup: {}", e))?);
+    let writer = BufWriter::new(
+        File::create(out).map_err(|e| format!("Failed to create temp dump: {}", e))?,
+    );
+    decrypt_stream(reader, writer, key)
+}
+
+fn create_backup_blocking(
+    job: &JobContext,
+    manager: &PostgresManager,
+    app_data_dir: &Path,
+    destination: &Path,
+    keep: usize,
+    app_version: String,
+) -> Result<BackupManifest, String> {
+    std::fs::create_dir_all(destination)
+        .map_err(|e| format!("Failed to create backup folder: {}", e))?;
+    let key = load_or_create_key(app_data_dir)?;
+
+    job.check()?;
+    job.progress("dumping", 0, Some(3));
+    let dump = TempDump::new(app_data_dir)?;
+    let mut pg_dump = pg_command(manager, "pg_dump")?;
+    pg_dump
+        .arg("-d")
+        .arg("secondbrain")
+        .arg("-Fc")
+        .arg("-f")
+        .arg(&dump.0);
+    run(pg_dump, "pg_dump")?;
+
+    let id = format!("secondbrain-{}", Local::now().format("%Y%m%d-%H%M%S"));
+    let file_name = format!("{}.{}", id, ARCHIVE_EXTENSION);
+    let final_path = destination.join(&file_name);
+    // Write under a temporary name so sync clients never upload a partial archive
+    let partial_path = destination.join(format!(".{}.partial", file_name));
+
+    let result = (|| {
+        job.check()?;
+        job.progress("encrypting", 1, Some(3));
+        let reader =
+            BufReader::new(File::open(&dump.0).map_err(|e| format!("Failed to open dump: {}", e))?);
+        let writer = BufWriter::new(
+            File::create(&partial_path).map_err(|e| format!("Failed to create backup: {}", e))?,
+        );
+        encrypt_stream(reader, writer, &key)?;
+        job.check()?;
+        std::fs::rename(&partial_path, &final_path)
+            .map_err(|e| format!("Failed to save backup: {}", e))
+    })();
+    if let Err(e) = result {
+        let _ = std::fs::remove_file(&partial_path);
+        return Err(e);
+    }
+
+    let manifest = BackupManifest {
+        id: id.clone(),
+        file_name,
+        created_at: Local::now().to_rfc3339(),
+        size_bytes: std::fs::metadata(&final_path)
+            .map(|m| m.len())
+            .unwrap_or_default(),
+        sha256: sha256_file(&final_path)?,
+        app_version,
+    };
+    save_json_atomic(&BackupManifest::path(destination, &id), &manifest)?;
+
+    apply_retention(destination, keep);
+    tracing::info!(
+        "Created encrypted backup {} ({} bytes)",
+        id,
+        manifest.size_bytes
+    );
+    Ok(manifest)
+}
+
+fn verify_backup_blocking(
+    manager: Option<&PostgresManager>,
+    app_data_dir: &Path,
+    destination: &Path,
+    id: &str,
+) -> Result<BackupVerification, String> {
+    let manifest = find_manifest(destination, id)?;
+    let archive = destination.join(&manifest.file_name);
+    let mut report = BackupVerification {
+        id: id.to_string(),
+        ..Default::default()
+    };
+
+    report.checksum_ok = sha256_file(&archive)? == manifest.sha256;
+    if !report.checksum_ok {
+        report.error = Some("Checksum does not match manifest".to_string());
+        return Ok(report);
+    }
+
+    let key = load_or_create_key(app_data_dir)?;
+    let dump = TempDump::new(app_data_dir)?;
+    if let Err(e) = decrypt_to(&archive, &dump.0, &key) {
+        report.error = Some(e);
+        return Ok(report);
+    }
+    report.decrypt_ok = true;
+
+    if let Some(manager) = manager {
+        let mut pg_restore = pg_command(manager, "pg_restore")?;
+        // --list only reads the archive's table of contents
+        pg_restore.arg("--list").arg(&dump.0);
+        match run(pg_restore, "pg_restore") {
+            Ok(_) => report.dump_ok = Some(true),
+            Err(e) => {
+                report.dump_ok = Some(false);
+                report.error = Some(e);
+            }
+        }
+    }
+    Ok(report)
+}
+
+/// Decrypt an archive into a temporary dump for restoring
+fn decrypt_backup_blocking(
+    app_data_dir: &Path,
+    archive: &Path,
+    key: &[u8; 32],
+) -> Result<TempDump, String> {
+    let dump = TempDump::new(app_data_dir)?;
+    decrypt_to(archive, &dump.0, key)?;
+    Ok(dump)
+}
+
+/// Replace the live database's contents with a custom-format dump
+fn restore_dump(manager: &PostgresManager, dump: &Path) -> Result<(), String> {
+    let mut pg_restore = pg_command(manager, "pg_restore")?;
+    pg_restore
+        .arg("-d")
+        .arg("secondbrain")
+        .arg("--clean")
+        .arg("--if-exists")
+        .arg("--no-owner")
+        .arg("--single-transaction")
+        .arg(dump);
+    run(pg_restore, "pg_restore").map(|_| ())
+}
+
+/// Check that `pg_restore` can read a dump's table of contents
+fn verify_dump(manager: &PostgresManager, dump: &Path) -> Result<(), String> {
+    let mut pg_restore = pg_command(manager, "pg_restore")?;
+    pg_restore.arg("--list").arg(dump);
+    run(pg_restore, "pg_restore")
+        .map(|_| ())
+        .map_err(|e| format!("Not a valid backup: {}", e))
+}
+
+/// Run a query with psql, returning tuples-only, `|`-separated output
+pub(crate) fn query(
+    manager: &PostgresManager,
+    database: &str,
+    sql: &str,
+) -> Result<String, String> {
+    let mut psql = pg_command(manager, "psql")?;
+    psql.arg("-d")
+        .arg(database)
+        .arg("-X")
+        .arg("-q")
+        .arg("-tA")
+        .arg("-v")
+        .arg("ON_ERROR_STOP=1")
+        .arg("-c")
+        .arg(sql);
+    run(psql, "psql")
+}
+
+/// Scratch database for working on a copy of the data, dropped on drop
+pub(crate) struct ScratchDatabase<'a> {
+    manager: &'a PostgresManager,
+    pub name: &'static str,
+}
+
+impl<'a> ScratchDatabase<'a> {
+    pub(crate) fn create(manager: &'a PostgresManager, name: &'static str) -> Result<Self, String> {
+        // A previous run may have been interrupted before cleanup
+        query(
+            manager,
+            "postgres",
+            &format!("DROP DATABASE IF EXISTS {} WITH (FORCE)", name),
+        )?;
+        query(manager, "postgres", &format!("CREATE DATABASE {}", name))?;
+        Ok(Self { manager, name })
+    }
+
+    /// Load a custom-format dump into the database
+    pub(crate) fn restore(&self, dump: &Path) -> Result<(), String> {
+        let mut pg_restore = pg_command(self.manager, "pg_restore")?;
+        pg_restore
+            .arg("-d")
+            .arg(self.name)
+            .arg("--no-owner")
+            .arg(dump);
+        run(pg_restore, "pg_restore").map(|_| ())
+    }
+}
+
+impl Drop for ScratchDatabase<'_> {
+    fn drop(&mut self) {
+        if let Err(e) = query(
+            self.manager,
+            "postgres",
+            &format!("DROP DATABASE IF EXISTS {} WITH (FORCE)", self.name),
+        ) {
+            tracing::warn!("Failed to drop scratch database {}: {}", self.name, e);
+        }
+    }
+}
+
+fn non_empty(field: Option<&str>) -> Option<String> {
+    field.filter(|f| !f.is_empty()).map(str::to_string)
+}
+
+/// Parse the output of `STATS_SQL` and, if available, `SCHEMA_SQL`
+fn parse_summary(stats: &str, schema: Option<&str>) -> Result<DatabaseSummary, String> {
+    let fields: Vec<&str> = stats.trim().split('|').collect();
+    let count = |i: usize| -> Result<u64, String> {
+        fields
+            .get(i)
+            .and_then(|f| f.parse().ok())
+            .ok_or_else(|| format!("Unexpected statistics output: {}", stats.trim()))
+    };
+    let mut summary = DatabaseSummary {
+        notes: count(0)?,
+        deleted_notes: count(1)?,
+        conversations: count(2)?,
+        oldest_update: non_empty(fields.get(3).copied()),
+        newest_update: non_empty(fields.get(4).copied()),
+        ..Default::default()
+    };
+    if let Some(schema) = schema {
+        let fields: Vec<&str> = schema.trim().split('|').collect();
+        summary.schema_version = non_empty(fields.first().copied());
+        summary.migration_count = fields.get(1).and_then(|f| f.parse().ok()).unwrap_or(0);
+    }
+    Ok(summary)
+}
+
+fn summarize(manager: &PostgresManager, database: &str) -> Result<DatabaseSummary, String> {
+    let stats = query(manager, database, STATS_SQL)?;
+    // Databases created before migrations were tracked have no history table
+    let schema = query(manager, database, SCHEMA_SQL).ok();
+    parse_summary(&stats, schema.as_deref())
+}
+
+fn note_versions(
+    manager: &PostgresManager,
+    database: &str,
+) -> Result<std::collections::HashMap<String, String>, String> {
+    Ok(query(manager, database, NOTE_VERSIONS_SQL)?
+        .lines()
+        .filter_map(|line| line.split_once('|'))
+        .map(|(id, updated)| (id.to_string(), updated.to_string()))
+        .collect())
+}
+
+/// Count removed, added, changed and unchanged notes going from `live` to `backup`
+fn diff_notes(
+    live: &std::collections::HashMap<String, String>,
+    backup: &std::collections::HashMap<String, String>,
+) -> (u64, u64, u64, u64) {
+    let removed = live.keys().filter(|id| !backup.contains_key(*id)).count() as u64;
+    let (mut added, mut changed, mut unchanged) = (0, 0, 0);
+    for (id, updated) in backup {
+        match live.get(id) {
+            None => added += 1,
+            Some(live_updated) if live_updated != updated => changed += 1,
+            Some(_) => unchanged += 1,
+        }
+    }
+    (removed, added, changed, unchanged)
+}
+
+fn preview_restore_blocking(
+    manager: &PostgresManager,
+    app_data_dir: &Path,
+    destination: &Path,
+    id: &str,
+) -> Result<RestorePreview, String> {
+    let manifest = find_manifest(destination, id)?;
+    let key = load_or_create_key(app_data_dir)?;
+    let dump = TempDump::new(app_data_dir)?;
+    decrypt_to(&destination.join(&manifest.file_name), &dump.0, &key)?;
+
+    let preview_db = ScratchDatabase::create(manager, PREVIEW_DATABASE)?;
+    preview_db.restore(&dump.0)?;
+    let backup = summarize(manager, PREVIEW_DATABASE)?;
+    let backup_notes = note_versions(manager, PREVIEW_DATABASE)?;
+    drop(preview_db);
+
+    let mut preview = RestorePreview {
+        backup_id: manifest.id,
+        backup_created_at: manifest.created_at,
+        backup,
+        ..Default::default()
+    };
+    match summarize(manager, "secondbrain")
+        .and_then(|live| Ok((live, note_versions(manager, "secondbrain")?)))
+    {
+        Ok((live, live_notes)) => {
+            (
+                preview.notes_removed,
+                preview.notes_added,
+                preview.notes_changed,
+                preview.notes_unchanged,
+            ) = diff_notes(&live_notes, &backup_notes);
+            if live.migration_count > preview.backup.migration_count {
+                preview.warnings.push(
+                    "The backup uses an older schema; it will be upgraded when the backend starts"
+                        .to_string(),
+                );
+            } else if live.migration_count < preview.backup.migration_count {
+                preview
+                    .warnings
+                    .push("The backup was made by a newer version of Second Brain".to_string());
+            }
+            if preview.notes_removed > 0 {
+                preview.warnings.push(format!(
+                    "{} note(s) created since the backup will be lost",
+                    preview.notes_removed
+                ));
+            }
+            preview.live = Some(live);
+        }
+        Err(e) => preview
+            .warnings
+            .push(format!("Could not read the live database: {}", e)),
+    }
+    Ok(preview)
+}
+
+// ============================================================
+// Local backups
+// ============================================================
+
+fn local_backup_dir(app_data_dir: &Path) -> PathBuf {
+    app_data_dir.join(LOCAL_BACKUP_DIR)
+}
+
+/// Reject IDs that aren't a plain file stem from `list_local_backups`
+fn validate_local_id(id: &str) -> Result<(), String> {
+    let valid = id.starts_with("secondbrain-")
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("Invalid backup id: {}", id))
+    }
+}
+
+fn local_backup(path: PathBuf) -> Option<LocalBackup> {
+    let id = path.file_stem()?.to_string_lossy().to_string();
+    validate_local_id(&id).ok()?;
+    let metadata = std::fs::metadata(&path).ok()?;
+    let created_at = metadata
+        .modified()
+        .map(|time| chrono::DateTime::<Local>::from(time).to_rfc3339())
+        .unwrap_or_default();
+    Some(LocalBackup {
+        id,
+        path,
+        created_at,
+        size_bytes: metadata.len(),
+    })
+}
+
+/// Local backups, newest first
+fn list_local_backups(app_data_dir: &Path) -> Vec<LocalBackup> {
+    let Ok(entries) = std::fs::read_dir(local_backup_dir(app_data_dir)) else {
+        return Vec::new();
+    };
+    let mut backups: Vec<LocalBackup> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|ext| ext == LOCAL_EXTENSION))
+        .filter_map(local_backup)
+        .collect();
+    // IDs embed the creation time, so they sort chronologically
+    backups.sort_by(|a, b| b.id.cmp(&a.id));
+    backups
+}
+
+/// Dump the database into the local backup folder; `label` is appended to
+/// the ID, e.g. `pre-restore`
+fn create_local_backup_blocking(
+    job: &JobContext,
+    manager: &PostgresManager,
+    app_data_dir: &Path,
+    label: Option<&str>,
+) -> Result<LocalBackup, String> {
+    let dir = local_backup_dir(app_data_dir);
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create backup folder: {}", e))?;
+
+    let mut id = format!("secondbrain-{}", Local::now().format("%Y%m%d-%H%M%S"));
+    if let Some(label) = label {
+        id = format!("{}-{}", id, label);
+    }
+    let final_path = dir.join(format!("{}.{}", id, LOCAL_EXTENSION));
+    if final_path.exists() {
+        return Err(format!("Backup {} already exists", id));
+    }
+    let partial_path = dir.join(format!(".{}.partial", id));
+
+    job.check()?;
+    job.progress("dumping", 0, Some(2));
+    let mut pg_dump = pg_command(manager, "pg_dump")?;
+    pg_dump
+        .arg("-d")
+        .arg("secondbrain")
+        .arg("-Fc")
+        .arg("-Z")
+        .arg("6")
+        .arg("-f")
+        .arg(&partial_path);
+    let result = run(pg_dump, "pg_dump").and_then(|_| {
+        job.check()?;
+        job.progress("saving", 1, Some(2));
+        std::fs::rename(&partial_path, &final_path)
+            .map_err(|e| format!("Failed to save backup: {}", e))
+    });
+    if let Err(e) = result {
+        let _ = std::fs::remove_file(&partial_path);
+        return Err(e);
+    }
+
+    let backup = local_backup(final_path).ok_or_else(|| "Backup disappeared".to_string())?;
+    tracing::info!(
+        "Created local backup {} ({} bytes)",
+        backup.id,
+        backup.size_bytes
+    );
+    Ok(backup)
+}
+
+/// Record a backup's time in the service config and the tray
+fn record_backup_time(app: &AppHandle, app_data_dir: &Path) {
+    let now = chrono::Utc::now().timestamp() as u64;
+    let mut config = ServiceConfig::load(app_data_dir);
+    config.last_backup_at = Some(now);
+    if let Err(e) = config.save(app_data_dir) {
+        tracing::warn!("Failed to record backup time: {}", e);
+    }
+    crate::tray::set_last_backup(app, Some(now));
+}
+
+/// Delete scheduled backups beyond the newest `keep`, returning their IDs
+fn prune_scheduled_backups(app_data_dir: &Path, keep: usize) -> Vec<String> {
+    let suffix = format!("-{}", SCHEDULED_LABEL);
+    list_local_backups(app_data_dir)
+        .into_iter()
+        .filter(|backup| backup.id.ends_with(&suffix))
+        .skip(keep)
+        .filter_map(|backup| match std::fs::remove_file(&backup.path) {
+            Ok(()) => Some(backup.id),
+            Err(e) => {
+                tracing::warn!("Failed to delete old backup {}: {}", backup.id, e);
+                None
+            }
+        })
+        .collect()
+}
+
+async fn backup_locally(job: &JobContext, label: Option<&str>) -> Result<LocalBackup, String> {
+    let app = job.app();
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let manager = postgres_manager(app).ok_or_else(|| "Database is not running".to_string())?;
+    let worker = job.clone();
+    let dir = app_data_dir.clone();
+    let label = label.map(str::to_string);
+    let backup = tokio::task::spawn_blocking(move || {
+        create_local_backup_blocking(&worker, &manager, &dir, label.as_deref())
+    })
+    .await
+    .map_err(|e| format!("Task panicked: {}", e))??;
+    record_backup_time(app, &app_data_dir);
+    Ok(backup)
+}
+
+/// Dump the database into the local backup folder
+pub async fn run_local_backup(job: &JobContext) -> Result<LocalBackup, String> {
+    backup_locally(job, None).await
+}
+
+/// Take a scheduled local backup and prune old scheduled ones
+pub async fn run_scheduled_local_backup(app: &AppHandle) -> Result<(), String> {
+    let backup = backup_locally(&JobContext::untracked(app), Some(SCHEDULED_LABEL)).await?;
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let keep = BackupSchedule::load(&app_data_dir).keep;
+    let pruned = prune_scheduled_backups(&app_data_dir, keep);
+    if !pruned.is_empty() {
+        tracing::info!(
+            "Deleted {} old scheduled backups after {}",
+            pruned.len(),
+            backup.id
+        );
+    }
+    Ok(())
+}
+
+/// Refuse a restore while services are starting or restarting
+pub(crate) fn check_restorable(services: &ServiceManager) -> Result<(), AppError> {
+    let state = services.state();
+    if state.busy.is_some()
+        || state.postgres == ServicePhase::Starting
+        || state.backend == ServicePhase::Starting
+    {
+        return Err(AppError::Conflict(
+            "Services are starting; restore once they are running".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Replace the database's contents with a custom-format dump
+///
+/// The dump is verified and the live database backed up first. The backend
+/// is stopped for the restore and started again even if it fails. Returns
+/// the backup taken beforehand.
+async fn restore_with_backend_stopped(
+    job: &JobContext,
+    dump: &Path,
+) -> Result<LocalBackup, AppError> {
+    let app = job.app().clone();
+    let services = app.state::<ServiceManager>();
+    check_restorable(&services)?;
+    let manager = postgres_manager(&app).ok_or_else(database_not_running)?;
+    let app_data_dir = app.path().app_data_dir()?;
+
+    job.progress("verifying", 0, Some(RESTORE_STAGES));
+    let (verifier, path) = (manager.clone(), dump.to_path_buf());
+    tokio::task::spawn_blocking(move || verify_dump(&verifier, &path)).await??;
+    job.check()?;
+
+    job.progress("backing_up", 1, Some(RESTORE_STAGES));
+    let (dumper, untracked) = (manager.clone(), JobContext::untracked(&app));
+    let safety = tokio::task::spawn_blocking(move || {
+        create_local_backup_blocking(&untracked, &dumper, &app_data_dir, Some("pre-restore"))
+    })
+    .await??;
+    // Last point a cancel can stop the restore
+    job.check()?;
+
+    job.progress("stopping_backend", 2, Some(RESTORE_STAGES));
+    services.send(ServiceCommand::StopBackend).await?;
+
+    job.progress("restoring", 3, Some(RESTORE_STAGES));
+    let path = dump.to_path_buf();
+    let restored = tokio::task::spawn_blocking(move || restore_dump(&manager, &path)).await;
+
+    job.progress("starting_backend", 4, Some(RESTORE_STAGES));
+    let restarted = services.send(ServiceCommand::RestartBackend).await;
+    if let Err(ref e) = restarted {
+        tracing::error!("Backend failed to start after restore: {}", e);
+    }
+    restored??;
+    restarted?;
+    Ok(safety)
+}
+
+/// Restore a local backup, or any custom-format dump, into the database
+///
+/// Refuses while services are starting or restarting. The backend is
+/// stopped for the restore and started again even if it fails. Returns the
+/// backup of the live database taken beforehand.
+pub async fn run_local_restore(job: &JobContext, path: PathBuf) -> Result<LocalBackup, AppError> {
+    if !path.is_file() {
+        return Err(AppError::NotFound(format!(
+            "Backup not found: {}",
+            path.display()
+        )));
+    }
+    let safety = restore_with_backend_stopped(job, &path).await?;
+
+    tracing::info!(
+        "Restored local backup {:?}; previous data saved as {}",
+        path,
+        safety.id
+    );
+    Ok(safety)
+}
+
+/// Create an encrypted backup in the configured folder
+pub async fn run_encrypted_backup(job: &JobContext) -> Result<BackupManifest, String> {
+    let app = job.app();
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let settings = EncryptedBackupSettings::load(&app_data_dir);
+    let destination = settings.destination()?.to_path_buf();
+    let manager = postgres_manager(app).ok_or_else(|| "Database is not running".to_string())?;
+    let app_version = app.package_info().version.to_string();
+
+    let target = destination.clone();
+    let worker = job.clone();
+    let manifest = tokio::task::spawn_blocking(move || {
+        create_backup_blocking(
+            &worker,
+            &manager,
+            &app_data_dir,
+            &target,
+            settings.keep,
+            app_version,
+        )
+    })
+    .await
+    .map_err(|e| format!("Task panicked: {}", e))??;
+
+    job.progress("uploading", 2, Some(3));
+    crate::cloud_backup::upload_if_enabled(app, &destination, &manifest).await;
+    Ok(manifest)
+}
+
+/// The configured backup folder and the manifest of a backup in it
+pub(crate) fn find_local_backup(
+    app: &AppHandle,
+    id: &str,
+) -> Result<(PathBuf, BackupManifest), String> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let destination = EncryptedBackupSettings::load(&app_data_dir)
+        .destination()?
+        .to_path_buf();
+    let manifest = find_manifest(&destination, id)?;
+    Ok((destination, manifest))
+}
+
+/// Register or remove the scheduled backup job
+pub fn apply_schedule(app: &AppHandle, settings: &EncryptedBackupSettings) {
+    let scheduler = app.state::<Scheduler>();
+    scheduler.remove_job(ENCRYPTED_BACKUP_JOB_ID);
+
+    if let (Some(schedule), Some(_)) = (&settings.schedule, &settings.destination) {
+        scheduler.upsert_job(
+            ScheduledJob {
+                id: ENCRYPTED_BACKUP_JOB_ID.to_string(),
+                name: "Encrypted backup".to_string(),
+                schedule: schedule.clone(),
+                skip_on_battery: true,
+                wait_for_idle: true,
+                // Spread backups so several devices sharing a folder don't write at once
+                jitter_secs: 300,
+                action: JobAction::EncryptedBackup,
+            },
+            Local::now(),
+        );
+    }
+}
+
+/// Register or remove the scheduled local backup job
+pub fn apply_local_schedule(app: &AppHandle, settings: &BackupSchedule) {
+    let scheduler = app.state::<Scheduler>();
+    scheduler.remove_job(LOCAL_BACKUP_JOB_ID);
+
+    if let Some(schedule) = &settings.schedule {
+        scheduler.upsert_job(
+            ScheduledJob {
+                id: LOCAL_BACKUP_JOB_ID.to_string(),
+                name: "Local backup".to_string(),
+                schedule: schedule.clone(),
+                skip_on_battery: true,
+                wait_for_idle: true,
+                jitter_secs: 60,
+                action: JobAction::LocalBackup,
+            },
+            Local::now(),
+        );
+    }
+}
+
+/// Load persisted settings and schedule backups
+pub fn start(app: &AppHandle) {
+    if let Ok(app_data_dir) = app.path().app_data_dir() {
+        apply_schedule(app, &EncryptedBackupSettings::load(&app_data_dir));
+        apply_local_schedule(app, &BackupSchedule::load(&app_data_dir));
+    }
+}
+
+/// Get the encrypted backup settings
+#[tauri::command]
+pub async fn get_encrypted_backup_settings(
+    app: AppHandle,
+) -> Result<EncryptedBackupSettings, AppError> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    Ok(EncryptedBackupSettings::load(&app_data_dir))
+}
+
+/// Update the encrypted backup settings and reschedule
+#[tauri::command]
+pub async fn set_encrypted_backup_settings(
+    app: AppHandle,
+    settings: EncryptedBackupSettings,
+) -> Result<(), AppError> {
+    settings.validate()?;
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    settings.save(&app_data_dir)?;
+    apply_schedule(&app, &settings);
+    Ok(())
+}
+
+/// Create an encrypted backup now
+#[tauri::command]
+pub async fn create_encrypted_backup(app: AppHandle) -> Result<BackupManifest, AppError> {
+    run_encrypted_backup(&JobContext::untracked(&app))
+        .await
+        .map_err(AppError::from)
+}
+
+/// List encrypted backups in the configured folder, newest first
+#[tauri::command]
+pub async fn list_encrypted_backups(app: AppHandle) -> Result<Vec<BackupManifest>, AppError> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let settings = EncryptedBackupSettings::load(&app_data_dir);
+    Ok(list_manifests(settings.destination()?))
+}
+
+/// Verify an encrypted backup's checksum, encryption, and dump contents
+#[tauri::command]
+pub async fn verify_encrypted_backup(
+    app: AppHandle,
+    id: String,
+) -> Result<BackupVerification, AppError> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let destination = EncryptedBackupSettings::load(&app_data_dir)
+        .destination()?
+        .to_path_buf();
+    let manager = postgres_manager(&app);
+
+    tokio::task::spawn_blocking(move || {
+        verify_backup_blocking(manager.as_deref(), &app_data_dir, &destination, &id)
+    })
+    .await?
+    .map_err(AppError::from)
+}
+
+/// Report what restoring a backup would change, without touching live data
+#[tauri::command]
+pub async fn preview_restore(
+    app: AppHandle,
+    backup_id: String,
+) -> Result<RestorePreview, AppError> {
+    let (destination, _) = find_local_backup(&app, &backup_id)?;
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let manager = postgres_manager(&app).ok_or_else(database_not_running)?;
+
+    tokio::task::spawn_blocking(move || {
+        preview_restore_blocking(&manager, &app_data_dir, &destination, &backup_id)
+    })
+    .await?
+    .map_err(AppError::from)
+}
+
+/// Restore an encrypted backup into the database, replacing its contents
+///
+/// `path` may point at any archive (e.g. copied from another machine); `key`
+/// is the hex key exported from that machine and defaults to this one's.
+/// Restores the same way as `restore_backup`, with the backend stopped and
+/// the live database backed up first.
+#[tauri::command]
+pub async fn restore_encrypted_backup(
+    app: AppHandle,
+    path: String,
+    key: Option<String>,
+) -> Result<(), AppError> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let archive = PathBuf::from(path);
+    if !archive.is_file() {
+        return Err(AppError::NotFound(format!(
+            "Backup not found: {}",
+            archive.display()
+        )));
+    }
+    check_restorable(&app.state::<ServiceManager>())?;
+    crate::snapshots::snapshot_before(&app, "backup-restore").await?;
+
+    let source = archive.clone();
+    let dump = tokio::task::spawn_blocking(move || {
+        let key = match key {
+            Some(hex) => parse_key(&hex)?,
+            None => load_or_create_key(&app_data_dir)?,
+        };
+        decrypt_backup_blocking(&app_data_dir, &source, &key)
+    })
+    .await??;
+    let safety = restore_with_backend_stopped(&JobContext::untracked(&app), &dump.0).await?;
+
+    tracing::info!(
+        "Restored encrypted backup {:?}; previous data saved as {}",
+        archive,
+        safety.id
+    );
+    Ok(())
+}
+
+/// Create a local backup now
+///
+/// Start a `local_backup` job instead to follow its progress.
+#[tauri::command]
+pub async fn create_backup(app: AppHandle) -> Result<LocalBackup, AppError> {
+    run_local_backup(&JobContext::untracked(&app))
+        .await
+        .map_err(AppError::from)
+}
+
+/// List local backups, newest first
+#[tauri::command]
+pub async fn list_backups(app: AppHandle) -> Result<Vec<LocalBackup>, AppError> {
+    let app_data_dir = app.path().app_data_dir()?;
+    Ok(list_local_backups(&app_data_dir))
+}
+
+/// Restore a local backup, replacing the database's contents
+///
+/// Returns the backup of the previous contents. Start a `restore_backup`
+/// job instead to follow its progress.
+#[tauri::command]
+pub async fn restore_backup(app: AppHandle, path: String) -> Result<LocalBackup, AppError> {
+    run_local_restore(&JobContext::untracked(&app), PathBuf::from(path)).await
+}
+
+/// Delete a local backup
+#[tauri::command]
+pub async fn delete_backup(app: AppHandle, id: String) -> Result<(), AppError> {
+    validate_local_id(&id).map_err(AppError::InvalidInput)?;
+    let app_data_dir = app.path().app_data_dir()?;
+    let path = local_backup_dir(&app_data_dir).join(format!("{}.{}", id, LOCAL_EXTENSION));
+    if !path.is_file() {
+        return Err(AppError::NotFound(format!("Backup not found: {}", id)));
+    }
+    std::fs::remove_file(&path)?;
+    tracing::info!("Deleted local backup {}", id);
+    Ok(())
+}
+
+/// Get the local backup schedule
+#[tauri::command]
+pub async fn get_backup_schedule(app: AppHandle) -> Result<BackupSchedule, AppError> {
+    let app_data_dir = app.path().app_data_dir()?;
+    Ok(BackupSchedule::load(&app_data_dir))
+}
+
+/// Update the local backup schedule and reschedule
+#[tauri::command]
+pub async fn set_backup_schedule(app: AppHandle, schedule: BackupSchedule) -> Result<(), AppError> {
+    schedule.validate().map_err(AppError::InvalidInput)?;
+    let app_data_dir = app.path().app_data_dir()?;
+    schedule.save(&app_data_dir)?;
+    apply_local_schedule(&app, &schedule);
+    Ok(())
+}
+
+/// Export the backup key so archives can be restored on another machine
+#[tauri::command]
+pub async fn export_backup_key(app: AppHandle) -> Result<String, AppError> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    load_or_create_key(&app_data_dir)
+        .map(|key| encode_hex(&key))
+        .map_err(AppError::from)
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const KEY: [u8; 32] = [7u8; 32];
+
+    fn encrypt(data: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        encrypt_stream(data, &mut out, &KEY).unwrap();
+        out
+    }
+
+    fn decrypt(data: &[u8], key: &[u8; 32]) -> Result<Vec<u8>, String> {
+        let mut out = Vec::new();
+        decrypt_stream(data, &mut out, key).map(|_| out)
+    }
+
+    #[test]
+    fn test_roundtrip_small_and_multi_chunk() {
+        for len in [0, 10, CHUNK_SIZE, CHUNK_SIZE * 2 + 123] {
+            let data: Vec<u8> = (0..len).map(|i| (i % 251) as u8).collect();
+            let encrypted = encrypt(&data);
+            assert!(encrypted.starts_with(MAGIC));
+            assert_eq!(decrypt(&encrypted, &KEY).unwrap(), data, "len {}", len);
+        }
+    }
+
+    #[test]
+    fn test_wrong_key_fails() {
+        let encrypted = encrypt(b"secret notes");
+        assert!(decrypt(&encrypted, &[8u8; 32]).is_err());
+    }
+
+    #[test]
+    fn test_tampering_detected() {
+        let mut encrypted = encrypt(b"secret notes");
+        let last = encrypted.len() - 1;
+        encrypted[last] ^= 1;
+        assert!(decrypt(&encrypted, &KEY).is_err());
+    }
+
+    #[test]
+    fn test_truncation_detected() {
+        let data = vec![1u8; CHUNK_SIZE + 10];
+        let encrypted = encrypt(&data);
+
+        // Drop the final chunk entirely: the new last chunk lacks the final flag
+        let first_chunk_end = 15 + 4 + CHUNK_SIZE + TAG_SIZE;
+        assert!(decrypt(&encrypted[..first_chunk_end], &KEY).is_err());
+        assert!(decrypt(&encrypted[..encrypted.len() - 3], &KEY).is_err());
+        assert!(decrypt(&encrypted[..15], &KEY).is_err());
+    }
+
+    #[test]
+    fn test_parse_key() {
+        let hex = encode_hex(&KEY);
+        assert_eq!(parse_key(&hex).unwrap(), KEY);
+        assert!(parse_key("abcd").is_err());
+        assert!(parse_key(&"zz".repeat(32)).is_err());
+    }
+
+    #[test]
+    fn test_settings_validation() {
+        assert!(EncryptedBackupSettings::default().validate().is_ok());
+
+        let no_destination = EncryptedBackupSettings {
+            schedule: Some(Schedule::Daily { hour: 2, minute: 0 }),
+            ..Default::default()
+        };
+        assert!(no_destination.validate().is_err());
+
+        let keep_none = EncryptedBackupSettings {
+            keep: 0,
+            ..Default::default()
+        };
+        assert!(keep_none.validate().is_err());
+    }
+
+    #[test]
+    fn test_parse_summary() {
+        let summary = parse_summary(
+            "120|4|9|2023-01-02 03:04:05+00|2024-05-06 07:08:09+00\n",
+            Some("20240101000000_AddTags|42\n"),
+        )
+        .unwrap();
+        assert_eq!(summary.notes, 120);
+        assert_eq!(summary.deleted_notes, 4);
+        assert_eq!(summary.conversations, 9);
+        assert_eq!(
+            summary.newest_update.as_deref(),
+            Some("2024-05-06 07:08:09+00")
+        );
+        assert_eq!(
+            summary.schema_version.as_deref(),
+            Some("20240101000000_AddTags")
+        );
+        assert_eq!(summary.migration_count, 42);
+
+        let empty = parse_summary("0|0|0||\n", None).unwrap();
+        assert_eq!(empty.oldest_update, None);
+        assert_eq!(empty.schema_version, None);
+        assert!(parse_summary("oops", None).is_err());
+    }
+
+    #[test]
+    fn test_diff_notes() {
+        let map = |pairs: &[(&str, &str)]| -> std::collections::HashMap<String, String> {
+            pairs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect()
+        };
+        let live = map(&[("a", "1"), ("b", "2"), ("c", "3")]);
+        let backup = map(&[("a", "1"), ("b", "1"), ("d", "1")]);
+        // c removed, d added, b changed, a unchanged
+        assert_eq!(diff_notes(&live, &backup), (1, 1, 1, 1));
+    }
+
+    #[test]
+    fn test_retention_keeps_newest() {
+        let temp_dir = TempDir::new().unwrap();
+        let destination = temp_dir.path();
+
+        for day in 1..=4 {
+            let id = format!("secondbrain-2025010{}", day);
+            let file_name = format!("{}.{}", id, ARCHIVE_EXTENSION);
+            std::fs::write(destination.join(&file_name), b"x").unwrap();
+            let manifest = BackupManifest {
+                id: id.clone(),
+                file_name,
+                created_at: format!("2025-01-0{}T00:00:00+00:00", day),
+                size_bytes: 1,
+                sha256: String::new(),
+                app_version: "1.0.0".to_string(),
+            };
+            save_json_atomic(&BackupManifest::path(destination, &id), &manifest).unwrap();
+        }
+
+        apply_retention(destination, 2);
+
+        let ids: Vec<String> = list_manifests(destination)
+            .into_iter()
+            .map(|m| m.id)
+            .collect();
+        assert_eq!(ids, vec!["secondbrain-20250104", "secondbrain-20250103"]);
+        assert!(!destination
+            .join(format!("secondbrain-20250101.{}", ARCHIVE_EXTENSION))
+            .exists());
+    }
+
+    #[test]
+    fn test_local_backups() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = local_backup_dir(temp_dir.path());
+        std::fs::create_dir_all(&dir).unwrap();
+        for name in [
+            "secondbrain-20250101-090000.dump",
+            "secondbrain-20250102-090000.dump",
+            ".secondbrain-20250103-090000.partial",
+            "notes.dump",
+        ] {
+            std::fs::write(dir.join(name), b"x").unwrap();
+        }
+
+        let ids: Vec<String> = list_local_backups(temp_dir.path())
+            .into_iter()
+            .map(|b| b.id)
+            .collect();
+        assert_eq!(
+            ids,
+            vec!["secondbrain-20250102-090000", "secondbrain-20250101-090000"]
+        );
+
+        assert!(validate_local_id("secondbrain-20250101-090000").is_ok());
+        assert!(validate_local_id("secondbrain-../../secrets").is_err());
+        assert!(validate_local_id("notes").is_err());
+    }
+
+    #[test]
+    fn test_prune_scheduled_backups() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = local_backup_dir(temp_dir.path());
+        std::fs::create_dir_all(&dir).unwrap();
+        for name in [
+            "secondbrain-20250101-090000-auto.dump",
+            "secondbrain-20250102-090000-auto.dump",
+            "secondbrain-20250103-090000-auto.dump",
+            "secondbrain-20250101-120000.dump",
+            "secondbrain-20250101-130000-pre-restore.dump",
+        ] {
+            std::fs::write(dir.join(name), b"x").unwrap();
+        }
+
+        let pruned = prune_scheduled_backups(temp_dir.path(), 2);
+        assert_eq!(pruned, vec!["secondbrain-20250101-090000-auto"]);
+        assert_eq!(list_local_backups(temp_dir.path()).len(), 4);
+
+        let weekly = BackupSchedule {
+            schedule: Some(Schedule::Weekly {
+                weekday: 6,
+                hour: 3,
+                minute: 0,
+            }),
+            keep: 4,
+        };
+        assert!(weekly.validate().is_ok());
+        let hourly = BackupSchedule {
+            schedule: Some(Schedule::Interval { every_secs: 3600 }),
+            ..weekly
+        };
+        assert!(hourly.validate().is_err());
+    }
+}
@@ -0,0 +1,179 @@
+//! Batched invocation of read-only commands.
+//!
+//! This module provides:
+//! - `invoke_batch`, running several status and settings getters in one IPC
+//!   round trip and returning each result keyed by its call id
+//!
+//! Only commands without side effects are accepted, so a batch can never
+//! change state. Calls run concurrently and fail independently.
+
+use futures_util::future::join_all;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use tauri::{AppHandle, Manager};
+
+use crate::error::AppError;
+
+/// Most calls accepted in one batch
+const MAX_BATCH_CALLS: usize = 64;
+
+/// One command in a batch
+#[derive(Debug, Clone, Deserialize)]
+pub struct BatchCall {
+    /// Caller-chosen id the result is keyed by
+    pub id: String,
+    pub command: String,
+}
+
+/// Outcome of one call, serialized as `{ "ok": value }` or `{ "error": AppError }`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchResult {
+    Ok(serde_json::Value),
+    Error(AppError),
+}
+
+fn to_value<T: Serialize>(result: Result<T, AppError>) -> Result<serde_json::Value, AppError> {
+    result.and_then(|value| {
+        serde_json::to_value(value).map_err(|e| AppError::Internal(e.to_string()))
+    })
+}
+
+/// Run one batchable command
+async fn call(app: &AppHandle, command: &str) -> Result<serde_json::Value, AppError> {
+    crate::app_lock::check(app, command)?;
+    let app = app.clone();
+    match command {
+        "get_backend_url" => to_value(crate::get_backend_url(app.state()).await),
+        "is_backend_ready" => to_value(crate::is_backend_ready(app.state()).await),
+        "get_database_status" => to_value(crate::get_database_status(app.state()).await),
+        "get_startup_metrics" => to_value(crate::get_startup_metrics(app.state()).await),
+        "get_startup_stats" => to_value(crate::get_startup_stats(app).await),
+        "get_resource_usage" => to_value(crate::resource_monitor::get_resource_usage(app).await),
+        "get_port_config" => to_value(crate::get_port_config(app.state()).await),
+        "get_app_version" => to_value(crate::commands::get_app_version(app).await),
+        "get_service_state" => to_value(crate::services::get_service_state(app).await),
+        "get_backend_health" => to_value(crate::health::get_backend_health(app).await),
+        "get_http_settings" => to_value(crate::http::get_http_settings(app).await),
+        "get_ai_cache_stats" => to_value(crate::proxy::get_ai_cache_stats(app).await),
+        "get_schedule_settings" => to_value(crate::scheduler::get_schedule_settings(app).await),
+        "list_scheduled_jobs" => to_value(crate::scheduler::list_scheduled_jobs(app).await),
+        "get_trash_settings" => to_value(crate::trash::get_trash_settings(app).await),
+        "get_snapshot_settings" => to_value(crate::snapshots::get_snapshot_settings(app).await),
+        "get_encrypted_backup_settings" => {
+            to_value(crate::backup::get_encrypted_backup_settings(app).await)
+        }
+        "get_cloud_backup_settings" => {
+            to_value(crate::cloud_backup::get_cloud_backup_settings(app).await)
+        }
+        "get_note_history_settings" => {
+            to_value(crate::note_history::get_note_history_settings(app).await)
+        }
+        "get_note_history_status" => {
+            to_value(crate::note_history::get_note_history_status(app).await)
+        }
+        "get_obsidian_settings" => to_value(crate::obsidian::get_obsidian_settings(app).await),
+        "get_obsidian_sync_status" => {
+            to_value(crate::obsidian::get_obsidian_sync_status(app).await)
+        }
+        "list_sync_conflicts" => to_value(crate::obsidian::list_sync_conflicts(app).await),
+        "get_peer_sync_settings" => to_value(crate::peer_sync::get_peer_sync_settings(app).await),
+        "get_peer_sync_status" => to_value(crate::peer_sync::get_peer_sync_status(app).await),
+        "get_email_watcher_config" => {
+            to_value(crate::email_watcher::get_email_watcher_config(app).await)
+        }
+        "get_email_watcher_status" => {
+            to_value(crate::email_watcher::get_email_watcher_status(app).await)
+        }
+        "get_contacts_status" => to_value(crate::contacts::get_contacts_status(app).await),
+        "list_feeds" => to_value(crate::feeds::list_feeds(app).await),
+        "list_uploads" => to_value(crate::uploads::list_uploads(app).await),
+        "list_queued_writes" => to_value(crate::write_queue::list_queued_writes(app).await),
+        "list_jobs" => to_value(crate::jobs::list_jobs(app).await),
+        _ => Err(AppError::InvalidInput(format!(
+            "{} can't be called in a batch",
+            command
+        ))),
+    }
+}
+
+fn validate(calls: &[BatchCall]) -> Result<(), AppError> {
+    if calls.len() > MAX_BATCH_CALLS {
+        return Err(AppError::InvalidInput(format!(
+            "A batch can hold at most {} calls",
+            MAX_BATCH_CALLS
+        )));
+    }
+    let mut ids = HashSet::new();
+    for call in calls {
+        if !ids.insert(call.id.as_str()) {
+            return Err(AppError::InvalidInput(format!(
+                "Duplicate call id in batch: {}",
+                call.id
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Run several read-only commands in one round trip
+#[tauri::command]
+pub async fn invoke_batch(
+    app: AppHandle,
+    calls: Vec<BatchCall>,
+) -> Result<HashMap<String, BatchResult>, AppError> {
+    validate(&calls)?;
+    let results = join_all(calls.iter().map(|c| call(&app, &c.command))).await;
+    Ok(calls
+        .into_iter()
+        .zip(results)
+        .map(|(c, result)| {
+            let result = match result {
+                Ok(value) => BatchResult::Ok(value),
+                Err(e) => BatchResult::Error(e),
+            };
+            (c.id, result)
+        })
+        .collect())
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn batch_call(id: &str) -> BatchCall {
+        BatchCall {
+            id: id.to_string(),
+            command: "get_port_config".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_validate_rejects_duplicates_and_oversized_batches() {
+        assert!(validate(&[batch_call("a"), batch_call("b")]).is_ok());
+        assert_eq!(
+            validate(&[batch_call("a"), batch_call("a")])
+                .unwrap_err()
+                .kind(),
+            "invalid_input"
+        );
+        let many: Vec<BatchCall> = (0..=MAX_BATCH_CALLS)
+            .map(|i| batch_call(&i.to_string()))
+            .collect();
+        assert!(validate(&many).is_err());
+    }
+
+    #[test]
+    fn test_result_serialization() {
+        let ok = serde_json::to_value(BatchResult::Ok(serde_json::json!([5433, 5001]))).unwrap();
+        assert_eq!(ok, serde_json::json!({ "ok": [5433, 5001] }));
+
+        let error = serde_json::to_value(BatchResult::Error(AppError::NotReady("Starting".into())))
+            .unwrap();
+        assert_eq!(error["error"]["kind"], "not_ready");
+    }
+}
@@ -0,0 +1,320 @@
+//! Calendar integration backed by EventKit.
+//!
+//! This module provides:
+//! - Calendar permission status and access prompts
+//! - Event queries for a time range
+//! - Pushing today's events into the backend's daily note
+
+use chrono::{DateTime, Duration as ChronoDuration, Local, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::error::AppError;
+use crate::osascript::{run_jxa, PermissionStatus};
+
+/// Backend endpoint that merges calendar events into the daily note
+const DAILY_NOTE_EVENTS_PATH: &str = "/daily-notes/events";
+
+/// Longest range a single query may span
+const MAX_RANGE_DAYS: i64 = 366;
+
+/// EventKit bridge: `status`, `request`, or `events <start> <end>` (epoch seconds)
+///
+/// Access requests complete asynchronously, so the run loop is pumped until
+/// the completion handler fires or the user leaves the prompt open too long.
+const EVENTKIT_SCRIPT: &str = r#"
+ObjC.import('EventKit');
+ObjC.import('Foundation');
+
+function run(argv) {
+    const store = $.EKEventStore.alloc.init;
+    const mode = argv[0];
+
+    if (mode === 'status') {
+        return JSON.stringify({ status: $.EKEventStore.authorizationStatusForEntityType(0) });
+    }
+
+    if (mode === 'request') {
+        let done = false;
+        const handler = function (granted, error) { done = true; };
+        if (store.respondsToSelector('requestFullAccessToEventsWithCompletion:')) {
+            store.requestFullAccessToEventsWithCompletion(handler);
+        } else {
+            store.requestAccessToEntityTypeCompletion(0, handler);
+        }
+        const deadline = Date.now() + 120000;
+        while (!done && Date.now() < deadline) {
+            $.NSRunLoop.currentRunLoop.runUntilDate($.NSDate.dateWithTimeIntervalSinceNow(0.2));
+        }
+        return JSON.stringify({ status: $.EKEventStore.authorizationStatusForEntityType(0) });
+    }
+
+    const start = $.NSDate.dateWithTimeIntervalSince1970(Number(argv[1]));
+    const end = $.NSDate.dateWithTimeIntervalSince1970(Number(argv[2]));
+    const predicate = store.predicateForEventsWithStartDateEndDateCalendars(start, end, $());
+    const events = store.eventsMatchingPredicate(predicate);
+    const out = [];
+    for (let i = 0; i < events.count; i++) {
+        const e = events.objectAtIndex(i);
+        out.push({
+            id: ObjC.unwrap(e.eventIdentifier) || '',
+            title: ObjC.unwrap(e.title) || '',
+            start: e.startDate.timeIntervalSince1970,
+            end: e.endDate.timeIntervalSince1970,
+            all_day: e.allDay,
+            location: ObjC.unwrap(e.location) || null,
+            calendar: ObjC.unwrap(e.calendar.title) || '',
+        });
+    }
+    return JSON.stringify(out);
+}
+"#;
+
+/// Time range for an event query
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventRange {
+    pub start: DateTime<Local>,
+    pub end: DateTime<Local>,
+}
+
+impl EventRange {
+    /// Local midnight to midnight for the day containing `now`
+    pub fn day_of(now: DateTime<Local>) -> Self {
+        let midnight = now.date_naive().and_hms_opt(0, 0, 0).unwrap_or_default();
+        let start = Local
+            .from_local_datetime(&midnight)
+            .earliest()
+            .unwrap_or(now);
+        Self {
+            start,
+            end: start + ChronoDuration::days(1),
+        }
+    }
+
+    /// Reject empty, inverted, or oversized ranges
+    pub fn validate(&self) -> Result<(), String> {
+        if self.end <= self.start {
+            return Err("Event range end must be after start".to_string());
+        }
+        if self.end - self.start > ChronoDuration::days(MAX_RANGE_DAYS) {
+            return Err(format!("Event range cannot exceed {} days", MAX_RANGE_DAYS));
+        }
+        Ok(())
+    }
+}
+
+/// A calendar event
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CalendarEvent {
+    pub id: String,
+    pub title: String,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub all_day: bool,
+    pub location: Option<String>,
+    /// Name of the calendar the event belongs to
+    pub calendar: String,
+}
+
+/// Event shape emitted by the EventKit script
+#[derive(Deserialize)]
+struct RawEvent {
+    id: String,
+    title: String,
+    start: f64,
+    end: f64,
+    all_day: bool,
+    location: Option<String>,
+    calendar: String,
+}
+
+#[derive(Deserialize)]
+struct RawStatus {
+    status: i64,
+}
+
+/// Parse the EventKit script's event list, sorted by start time
+fn parse_events(json: &str) -> Result<Vec<CalendarEvent>, String> {
+    let raw: Vec<RawEvent> =
+        serde_json::from_str(json).map_err(|e| format!("Invalid EventKit output: {}", e))?;
+
+    let mut events: Vec<CalendarEvent> = raw
+        .into_iter()
+        .filter_map(|e| {
+            Some(CalendarEvent {
+                id: e.id,
+                title: e.title,
+                start: Utc.timestamp_opt(e.start as i64, 0).single()?,
+                end: Utc.timestamp_opt(e.end as i64, 0).single()?,
+                all_day: e.all_day,
+                location: e.location.filter(|l| !l.is_empty()),
+                calendar: e.calendar,
+            })
+        })
+        .collect();
+
+    events.sort_by_key(|e| e.start);
+    Ok(events)
+}
+
+fn parse_status(json: &str) -> Result<PermissionStatus, String> {
+    let raw: RawStatus =
+        serde_json::from_str(json).map_err(|e| format!("Invalid EventKit output: {}", e))?;
+    Ok(PermissionStatus::from_code(raw.status))
+}
+
+/// Current calendar authorization status
+pub fn permission_status() -> PermissionStatus {
+    if !cfg!(target_os = "macos") {
+        return PermissionStatus::Unavailable;
+    }
+    run_jxa(EVENTKIT_SCRIPT, &["status"])
+        .and_then(|out| parse_status(&out))
+        .unwrap_or(PermissionStatus::Unavailable)
+}
+
+/// Fetch events in `range`, prompting for access if not yet determined
+pub fn fetch_events(range: &EventRange, prompt: bool) -> Result<Vec<CalendarEvent>, String> {
+    range.validate()?;
+
+    let mut status = permission_status();
+    if status == PermissionStatus::NotDetermined && prompt {
+        status = parse_status(&run_jxa(EVENTKIT_SCRIPT, &["request"])?)?;
+    }
+
+    match status {
+        PermissionStatus::Authorized => {}
+        PermissionStatus::Unavailable => {
+            return Err("Calendar integration is only available on macOS".to_string())
+        }
+        _ => {
+            return Err(
+                "Calendar access not granted. Enable Second Brain in System Settings > \
+                 Privacy & Security > Calendars."
+                    .to_string(),
+            )
+        }
+    }
+
+    let start = range.start.timestamp().to_string();
+    let end = range.end.timestamp().to_string();
+    parse_events(&run_jxa(EVENTKIT_SCRIPT, &["events", &start, &end])?)
+}
+
+/// Send today's events to the backend daily note
+///
+/// Runs unattended from the scheduler, so it never shows a permission prompt.
+pub async fn push_today_events(app: &AppHandle) -> Result<(), String> {
+    let range = EventRange::day_of(Local::now());
+    let query = range.clone();
+    let events = tokio::task::spawn_blocking(move || fetch_events(&query, false))
+        .await
+        .map_err(|e| format!("Task panicked: {}", e))??;
+
+    tracing::info!("Pushing {} calendar events to the daily note", events.len());
+
+    let body = serde_json::json!({
+        "date": range.start.format("%Y-%m-%d").to_string(),
+        "events": events,
+    });
+    crate::proxy::send_backend_request(app, "POST", DAILY_NOTE_EVENTS_PATH, Some(&body), None)
+        .await
+        .map(|_| ())
+        .map_err(String::from)
+}
+
+/// Get the calendar permission status
+#[tauri::command]
+pub async fn get_calendar_permission() -> Result<PermissionStatus, AppError> {
+    tokio::task::spawn_blocking(permission_status)
+        .await
+        .map_err(AppError::from)
+}
+
+/// Prompt for calendar access if the user has not decided yet
+#[tauri::command]
+pub async fn request_calendar_access() -> Result<PermissionStatus, AppError> {
+    tokio::task::spawn_blocking(|| match permission_status() {
+        PermissionStatus::NotDetermined => parse_status(&run_jxa(EVENTKIT_SCRIPT, &["request"])?),
+        status => Ok(status),
+    })
+    .await?
+    .map_err(AppError::from)
+}
+
+/// Get calendar events within a time range
+#[tauri::command]
+pub async fn get_events(range: EventRange) -> Result<Vec<CalendarEvent>, AppError> {
+    tokio::task::spawn_blocking(move || fetch_events(&range, true))
+        .await?
+        .map_err(AppError::from)
+}
+
+/// Push today's events into the daily note immediately
+#[tauri::command]
+pub async fn push_events_to_daily_note(app: AppHandle) -> Result<(), AppError> {
+    push_today_events(&app).await.map_err(AppError::from)
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_events_sorted() {
+        let json = r#"[
+            {"id":"b","title":"Standup","start":1741600800,"end":1741601700,"all_day":false,"location":"","calendar":"Work"},
+            {"id":"a","title":"Breakfast","start":1741590000,"end":1741593600,"all_day":false,"location":"Cafe","calendar":"Home"}
+        ]"#;
+
+        let events = parse_events(json).unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].title, "Breakfast");
+        assert_eq!(events[0].location.as_deref(), Some("Cafe"));
+        assert_eq!(events[1].location, None);
+    }
+
+    #[test]
+    fn test_parse_events_invalid() {
+        assert!(parse_events("not json").is_err());
+    }
+
+    #[test]
+    fn test_parse_status() {
+        assert_eq!(
+            parse_status(r#"{"status":3}"#).unwrap(),
+            PermissionStatus::Authorized
+        );
+    }
+
+    #[test]
+    fn test_day_range_spans_one_day() {
+        let now = Local.with_ymd_and_hms(2025, 3, 10, 15, 30, 0).unwrap();
+        let range = EventRange::day_of(now);
+
+        assert_eq!(range.start.format("%H:%M").to_string(), "00:00");
+        assert!(range.start <= now && now < range.end);
+        assert!(range.validate().is_ok());
+    }
+
+    #[test]
+    fn test_range_validation() {
+        let start = Local.with_ymd_and_hms(2025, 3, 10, 0, 0, 0).unwrap();
+
+        let inverted = EventRange {
+            start,
+            end: start - ChronoDuration::hours(1),
+        };
+        assert!(inverted.validate().is_err());
+
+        let huge = EventRange {
+            start,
+            end: start + ChronoDuration::days(400),
+        };
+        assert!(huge.validate().is_err());
+    }
+}
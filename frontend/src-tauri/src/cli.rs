@@ -0,0 +1,254 @@
+//! Command-line launch options.
+//!
+//! This module provides:
+//! - Parsing of the flags the shell accepts at launch, plus files,
+//!   `secondbrain://` links and `--capture` text to act on (see `launch`)
+//! - The parsed options, kept in Tauri state for modules that behave
+//!   differently depending on how the app was started
+//! - The `query` subcommand for launchers (see `query_cli`):
+//!   `second-brain query <text> [--capture] [--limit <n>] [--json]`
+//!
+//! Unknown flags are ignored, since macOS may pass its own (e.g. `-psn_…`).
+//! Other bare arguments are taken as files and only acted on if they exist.
+
+use tauri::{AppHandle, Manager};
+
+/// Arguments of the `query` subcommand
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct QueryArgs {
+    /// Text to search for, or to save with `--capture`; words are joined
+    pub text: String,
+    /// Save the text as a note instead of searching
+    pub capture: bool,
+    /// Most notes to return (`--limit <n>` or `--limit=<n>`)
+    pub limit: Option<u32>,
+    /// Print JSON instead of plain text
+    pub json: bool,
+}
+
+/// Options given on the command line
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LaunchOptions {
+    /// Run services without any window or tray (`--headless`)
+    pub headless: bool,
+    /// Serve a browser extension over stdio instead of starting the app
+    /// (`--native-messaging`)
+    pub native_messaging: bool,
+    /// Profile to run as (`--profile <name>` or `--profile=<name>`)
+    pub profile: Option<String>,
+    /// Run a throwaway demo with sample notes (`--demo`)
+    pub demo: bool,
+    /// Serve fixture data from the shell instead of starting PostgreSQL and
+    /// the .NET backend (`--mock-backend`), for frontend development
+    pub mock_backend: bool,
+    /// Allow `inject_fault` in release builds (`--fault-injection`), for QA
+    pub fault_injection: bool,
+    /// Text to save as a note (`--capture <text>` or `--capture=<text>`)
+    pub captures: Vec<String>,
+    /// `secondbrain://` URLs to open
+    pub links: Vec<String>,
+    /// Files to open, as given (possibly relative to the working directory)
+    pub files: Vec<String>,
+    /// Query the running app and print the result instead of starting it
+    /// (`query <text>`)
+    pub query: Option<QueryArgs>,
+}
+
+impl LaunchOptions {
+    /// Parse arguments, excluding the program name
+    pub fn parse<I, S>(args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut args = args.into_iter().peekable();
+        if args.peek().is_some_and(|arg| arg.as_ref() == "query") {
+            args.next();
+            return Self::parse_query(args);
+        }
+        let mut options = Self::default();
+        while let Some(arg) = args.next() {
+            match arg.as_ref() {
+                "--headless" => options.headless = true,
+                "--native-messaging" => options.native_messaging = true,
+                "--demo" => options.demo = true,
+                "--mock-backend" => options.mock_backend = true,
+                "--fault-injection" => options.fault_injection = true,
+                "--profile" => options.profile = args.next().map(|name| name.as_ref().to_string()),
+                "--capture" => options
+                    .captures
+                    .extend(args.next().map(|text| text.as_ref().to_string())),
+                other => {
+                    if let Some(name) = other.strip_prefix("--profile=") {
+                        options.profile = Some(name.to_string());
+                    } else if let Some(text) = other.strip_prefix("--capture=") {
+                        options.captures.push(text.to_string());
+                    } else if is_deep_link(other) {
+                        options.links.push(other.to_string());
+                    } else if !other.starts_with('-') && !other.contains("://") {
+                        options.files.push(other.to_string());
+                    }
+                }
+            }
+        }
+        options
+    }
+
+    /// Parse the arguments after `query`
+    fn parse_query<I, S>(args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut options = Self::default();
+        let mut query = QueryArgs::default();
+        let mut words = Vec::new();
+        let mut args = args.into_iter().map(|arg| arg.as_ref().to_string());
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--json" => query.json = true,
+                "--capture" => query.capture = true,
+                "--limit" => query.limit = args.next().and_then(|n| n.parse().ok()),
+                "--profile" => options.profile = args.next(),
+                // Everything after `--` is text, even if it looks like a flag
+                "--" => words.extend(args.by_ref()),
+                other => {
+                    if let Some(n) = other.strip_prefix("--limit=") {
+                        query.limit = n.parse().ok();
+                    } else if let Some(name) = other.strip_prefix("--profile=") {
+                        options.profile = Some(name.to_string());
+                    } else {
+                        words.push(arg);
+                    }
+                }
+            }
+        }
+        query.text = words.join(" ");
+        options.query = Some(query);
+        options
+    }
+
+    /// Options of the current process
+    pub fn from_env() -> Self {
+        Self::parse(std::env::args().skip(1))
+    }
+
+    /// Whether there is anything to open or capture
+    pub fn has_requests(&self) -> bool {
+        !self.captures.is_empty() || !self.links.is_empty() || !self.files.is_empty()
+    }
+}
+
+/// Whether an argument is a `secondbrain://` URL
+pub fn is_deep_link(arg: &str) -> bool {
+    arg.split_once("://")
+        .is_some_and(|(scheme, _)| scheme.eq_ignore_ascii_case("secondbrain"))
+}
+
+/// Options the app was launched with
+pub fn options(app: &AppHandle) -> LaunchOptions {
+    app.try_state::<LaunchOptions>()
+        .map(|options| options.inner().clone())
+        .unwrap_or_default()
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            LaunchOptions::parse(Vec::<String>::new()),
+            LaunchOptions::default()
+        );
+        assert!(LaunchOptions::parse(["--headless"]).headless);
+        assert!(LaunchOptions::parse(["-psn_0_12345", "--headless"]).headless);
+        assert!(!LaunchOptions::parse(["--headless=no", "headless"]).headless);
+        assert!(LaunchOptions::parse(["--demo"]).demo);
+        assert!(LaunchOptions::parse(["--mock-backend"]).mock_backend);
+        assert!(LaunchOptions::parse(["--fault-injection"]).fault_injection);
+
+        // Chrome appends the calling extension's origin
+        let native = LaunchOptions::parse(["--native-messaging", "chrome-extension://abcdefgh/"]);
+        assert!(native.native_messaging);
+        assert!(!native.headless);
+        assert!(!native.has_requests());
+    }
+
+    #[test]
+    fn test_parse_requests() {
+        let options = LaunchOptions::parse([
+            "--capture",
+            "Call the dentist",
+            "notes/todo.md",
+            "SecondBrain://note/42",
+            "--capture=buy milk",
+            "https://example.com",
+            "-psn_0_12345",
+        ]);
+        assert_eq!(options.captures, vec!["Call the dentist", "buy milk"]);
+        assert_eq!(options.links, vec!["SecondBrain://note/42"]);
+        assert_eq!(options.files, vec!["notes/todo.md"]);
+        assert!(options.has_requests());
+
+        // The value after --profile isn't a file
+        assert!(!LaunchOptions::parse(["--profile", "work"]).has_requests());
+    }
+
+    #[test]
+    fn test_parse_query() {
+        let options = LaunchOptions::parse([
+            "query",
+            "meeting",
+            "notes",
+            "--json",
+            "--limit=5",
+            "--profile",
+            "work",
+        ]);
+        assert_eq!(
+            options.query,
+            Some(QueryArgs {
+                text: "meeting notes".to_string(),
+                capture: false,
+                limit: Some(5),
+                json: true,
+            })
+        );
+        assert_eq!(options.profile.as_deref(), Some("work"));
+        assert!(!options.has_requests());
+
+        let capture = LaunchOptions::parse(["query", "--capture", "--", "--not a flag"])
+            .query
+            .unwrap();
+        assert!(capture.capture);
+        assert_eq!(capture.text, "--not a flag");
+
+        // Only a leading `query` is the subcommand
+        let file = LaunchOptions::parse(["notes.md", "query"]);
+        assert_eq!(file.query, None);
+        assert_eq!(file.files, vec!["notes.md", "query"]);
+    }
+
+    #[test]
+    fn test_parse_profile() {
+        assert_eq!(LaunchOptions::parse(["--headless"]).profile, None);
+        assert_eq!(
+            LaunchOptions::parse(["--profile", "work", "--headless"])
+                .profile
+                .as_deref(),
+            Some("work")
+        );
+        assert_eq!(
+            LaunchOptions::parse(["--profile=work"]).profile.as_deref(),
+            Some("work")
+        );
+        // A trailing flag without a value leaves the default profile
+        assert_eq!(LaunchOptions::parse(["--profile"]).profile, None);
+    }
+}
@@ -0,0 +1,213 @@
+//! System clock drift detection.
+//!
+//! This module provides:
+//! - A check comparing the system clock with the `Date` header of HTTPS
+//!   responses from the AI providers, at launch and every few hours
+//! - A `clock-skew-detected` warning with the offset when the clock is off
+//!   by more than cloud providers tolerate
+//! - The latest result for the diagnostic report
+//!
+//! `Date` headers only have one-second resolution, which is plenty: request
+//! signing and token checks start failing at minutes of skew, not seconds.
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+use crate::error::AppError;
+use crate::notifications::{Notice, Severity};
+
+/// Servers whose clocks are compared against, tried in order
+const REFERENCE_URLS: &[&str] = &[
+    "https://api.openai.com/",
+    "https://api.anthropic.com/",
+    "https://generativelanguage.googleapis.com/",
+];
+
+/// Offset beyond which the clock counts as skewed
+const SKEW_THRESHOLD: Duration = Duration::from_secs(60);
+
+/// Time between checks
+const CHECK_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// Timeout for each reference request
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Result of a clock check
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClockCheck {
+    /// Reference time minus system time; positive when the system clock is
+    /// behind
+    pub offset_ms: i64,
+    /// Host whose `Date` header was used
+    pub source: String,
+    /// When the check ran (Unix epoch seconds)
+    pub checked_at: i64,
+    pub skewed: bool,
+}
+
+/// Latest clock check, kept in Tauri state
+#[derive(Default)]
+pub struct ClockMonitor {
+    last: Mutex<Option<ClockCheck>>,
+}
+
+/// Offset of a `Date` header from the midpoint of the request
+fn offset_ms(
+    date: &str,
+    sent: chrono::DateTime<chrono::Utc>,
+    received: chrono::DateTime<chrono::Utc>,
+) -> Option<i64> {
+    let server = chrono::DateTime::parse_from_rfc2822(date.trim()).ok()?;
+    // The header truncates to the second, so its time is half a second late
+    // on average
+    let server_ms = server.timestamp_millis() + 500;
+    let local_ms = sent.timestamp_millis() + (received - sent).num_milliseconds() / 2;
+    Some(server_ms - local_ms)
+}
+
+fn is_skewed(offset_ms: i64) -> bool {
+    offset_ms.unsigned_abs() > SKEW_THRESHOLD.as_millis() as u64
+}
+
+/// Compare the system clock with the first reference that answers
+async fn measure(app: &AppHandle) -> Result<ClockCheck, AppError> {
+    let client = crate::http::external(app);
+    let mut last_error = None;
+    for url in REFERENCE_URLS {
+        let sent = chrono::Utc::now();
+        let response = match client.head(*url).timeout(REQUEST_TIMEOUT).send().await {
+            Ok(response) => response,
+            Err(e) => {
+                last_error = Some(e.to_string());
+                continue;
+            }
+        };
+        let received = chrono::Utc::now();
+        let Some(offset_ms) = response
+            .headers()
+            .get(reqwest::header::DATE)
+            .and_then(|date| date.to_str().ok())
+            .and_then(|date| offset_ms(date, sent, received))
+        else {
+            last_error = Some(format!("{} sent no usable Date header", url));
+            continue;
+        };
+        return Ok(ClockCheck {
+            offset_ms,
+            source: response.url().host_str().unwrap_or(url).to_string(),
+            checked_at: received.timestamp(),
+            skewed: is_skewed(offset_ms),
+        });
+    }
+    Err(AppError::Network(format!(
+        "Couldn't reach a time reference: {}",
+        last_error.unwrap_or_default()
+    )))
+}
+
+/// Check the clock, record the result and warn if it's skewed
+pub async fn check(app: &AppHandle) -> Result<ClockCheck, AppError> {
+    let result = measure(app).await?;
+    *app.state::<ClockMonitor>().last.lock() = Some(result.clone());
+    if result.skewed {
+        tracing::warn!(
+            offset_ms = result.offset_ms,
+            source = %result.source,
+            "System clock is skewed"
+        );
+        crate::events::emit_critical(app, "clock-skew-detected", &result);
+        crate::notifications::notify(
+            app,
+            Notice::new(
+                "clock",
+                Severity::Warning,
+                "Your system clock is off",
+                format!(
+                    "It's {} seconds {}. AI providers and sync may reject requests until it's \
+                     corrected.",
+                    result.offset_ms.unsigned_abs() / 1000,
+                    if result.offset_ms > 0 {
+                        "behind"
+                    } else {
+                        "ahead"
+                    }
+                ),
+            ),
+        );
+    } else {
+        tracing::debug!(offset_ms = result.offset_ms, "System clock is in sync");
+    }
+    Ok(result)
+}
+
+/// Latest clock check, if any has succeeded
+pub fn last_check(app: &AppHandle) -> Option<ClockCheck> {
+    app.try_state::<ClockMonitor>()
+        .and_then(|monitor| monitor.last.lock().clone())
+}
+
+/// Check the clock at launch and periodically after that
+pub fn start(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+            if let Err(e) = check(&app).await {
+                tracing::debug!("Clock check skipped: {}", e);
+            }
+        }
+    });
+}
+
+// ============================================================
+// Commands
+// ============================================================
+
+/// Get the latest clock check
+#[tauri::command]
+pub async fn get_clock_status(app: AppHandle) -> Result<Option<ClockCheck>, AppError> {
+    Ok(last_check(&app))
+}
+
+/// Compare the system clock with a time reference now
+#[tauri::command]
+pub async fn check_clock(app: AppHandle) -> Result<ClockCheck, AppError> {
+    check(&app).await
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_offset_ms() {
+        let sent = chrono::Utc.with_ymd_and_hms(2026, 3, 1, 12, 0, 0).unwrap();
+        let received = sent + chrono::Duration::milliseconds(200);
+
+        // In sync: the server's second plus half a second lands near the
+        // midpoint of the request
+        let offset = offset_ms("Sun, 01 Mar 2026 12:00:00 GMT", sent, received).unwrap();
+        assert_eq!(offset, 400);
+        assert!(!is_skewed(offset));
+
+        // System clock five minutes behind
+        let offset = offset_ms("Sun, 01 Mar 2026 12:05:00 GMT", sent, received).unwrap();
+        assert_eq!(offset, 300_400);
+        assert!(is_skewed(offset));
+
+        // System clock ahead
+        let offset = offset_ms("Sun, 01 Mar 2026 11:58:00 GMT", sent, received).unwrap();
+        assert!(offset < 0);
+        assert!(is_skewed(offset));
+
+        assert_eq!(offset_ms("yesterday", sent, received), None);
+    }
+}
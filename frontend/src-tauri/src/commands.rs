@@ -1,9 +1,11 @@
 use std::process::Command;
 use tauri::{AppHandle, Manager};
 
+use crate::error::AppError;
+
 /// Open the app data directory in Finder
 #[tauri::command]
-pub async fn open_data_directory(app: AppHandle) -> Result<(), String> {
+pub async fn open_data_directory(app: AppHandle) -> Result<(), AppError> {
     let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
 
     Command::new("open")
@@ -16,7 +18,7 @@ pub async fn open_data_directory(app: AppHandle) -> Result<(), String> {
 
 /// Open the log directory in Finder
 #[tauri::command]
-pub async fn open_log_directory(app: AppHandle) -> Result<(), String> {
+pub async fn open_log_directory(app: AppHandle) -> Result<(), AppError> {
     let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
 
     let log_dir = app_data_dir.join("logs");
@@ -34,7 +36,7 @@ pub async fn open_log_directory(app: AppHandle) -> Result<(), String> {
 
 /// Get the app version
 #[tauri::command]
-pub async fn get_app_version(app: AppHandle) -> Result<String, String> {
+pub async fn get_app_version(app: AppHandle) -> Result<String, AppError> {
     let version = app
         .config()
         .version
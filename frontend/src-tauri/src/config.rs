@@ -2,13 +2,353 @@
 //!
 //! This module provides:
 //! - Persistent storage of last-known good configuration
+//! - User-editable service settings (port ranges, health-check timing,
+//!   backend log level, PostgreSQL memory, restart policy, shutdown grace,
+//!   CORS origins) kept in the same file and changed through
+//!   `get_settings`/`update_settings`
+//! - `set_allowed_origins` for adding CORS origins (LAN clients, the web
+//!   clipper, dev servers) on top of the webview's own
 //! - Atomic file writes with temp file + rename
-//! - Schema validation for configuration
+//! - Schema validation and migration of older config files
 
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::fs;
 use std::io::Write;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+use crate::error::AppError;
+use crate::services::{ServiceCommand, ServiceManager, ServicePhase};
+use crate::startup::StartupConfig;
+use crate::AppState;
+
+/// Current `service-config.json` schema version
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// Inclusive range of ports a service may move to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PortRange {
+    pub start: u16,
+    pub end: u16,
+}
+
+impl PortRange {
+    pub fn contains(&self, port: u16) -> bool {
+        (self.start..=self.end).contains(&port)
+    }
+
+    /// Number of ports in the range
+    pub fn count(&self) -> u16 {
+        self.end.saturating_sub(self.start).saturating_add(1)
+    }
+
+    /// `port` if it's in the range, otherwise the start of the range
+    pub fn preferred(&self, port: u16) -> u16 {
+        if self.contains(port) {
+            port
+        } else {
+            self.start
+        }
+    }
+
+    fn validate(&self, name: &str) -> Result<(), String> {
+        if self.start < 1024 {
+            return Err(format!("{} must start at 1024 or above", name));
+        }
+        if self.end < self.start {
+            return Err(format!("{} ends before it starts", name));
+        }
+        Ok(())
+    }
+}
+
+/// Minimum level the backend logs at, in ASP.NET Core's terms
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackendLogLevel {
+    Trace,
+    Debug,
+    Information,
+    #[default]
+    Warning,
+    Error,
+    Critical,
+}
+
+impl BackendLogLevel {
+    /// Value for `Logging__LogLevel__Default`
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Trace => "Trace",
+            Self::Debug => "Debug",
+            Self::Information => "Information",
+            Self::Warning => "Warning",
+            Self::Error => "Error",
+            Self::Critical => "Critical",
+        }
+    }
+}
+
+/// PostgreSQL memory settings written to postgresql.conf
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PostgresMemory {
+    pub shared_buffers_mb: u32,
+    pub work_mem_mb: u32,
+    pub maintenance_work_mem_mb: u32,
+    pub effective_cache_size_mb: u32,
+    pub max_connections: u32,
+}
+
+impl Default for PostgresMemory {
+    fn default() -> Self {
+        Self {
+            shared_buffers_mb: 128,
+            work_mem_mb: 4,
+            maintenance_work_mem_mb: 64,
+            effective_cache_size_mb: 256,
+            max_connections: 20,
+        }
+    }
+}
+
+impl PostgresMemory {
+    /// postgresql.conf lines for these settings
+    pub fn conf_values(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("max_connections", self.max_connections.to_string()),
+            ("shared_buffers", format!("{}MB", self.shared_buffers_mb)),
+            ("work_mem", format!("{}MB", self.work_mem_mb)),
+            (
+                "maintenance_work_mem",
+                format!("{}MB", self.maintenance_work_mem_mb),
+            ),
+            (
+                "effective_cache_size",
+                format!("{}MB", self.effective_cache_size_mb),
+            ),
+        ]
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if !(16..=16 * 1024).contains(&self.shared_buffers_mb) {
+            return Err("shared_buffers_mb must be between 16 and 16384".to_string());
+        }
+        if !(1..=2048).contains(&self.work_mem_mb) {
+            return Err("work_mem_mb must be between 1 and 2048".to_string());
+        }
+        if !(1..=16 * 1024).contains(&self.maintenance_work_mem_mb) {
+            return Err("maintenance_work_mem_mb must be between 1 and 16384".to_string());
+        }
+        if self.effective_cache_size_mb < self.shared_buffers_mb {
+            return Err("effective_cache_size_mb can't be below shared_buffers_mb".to_string());
+        }
+        if !(5..=500).contains(&self.max_connections) {
+            return Err("max_connections must be between 5 and 500".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Origins the webview loads the app from, always allowed by the backend
+pub const BUILTIN_ORIGINS: &[&str] = &[
+    "tauri://localhost",
+    "https://tauri.localhost",
+    "http://localhost",
+    "http://127.0.0.1",
+];
+
+/// Most extra CORS origins that can be configured
+const MAX_ALLOWED_ORIGINS: usize = 32;
+
+/// Check a CORS origin and put it in the form browsers send, e.g.
+/// `HTTP://Example.com:80/` becomes `http://example.com`
+///
+/// Origins are a scheme, host and optional port: http, https or a browser
+/// extension scheme such as `chrome-extension`, with no path, query or
+/// wildcard.
+pub fn normalize_origin(origin: &str) -> Result<String, String> {
+    let origin = origin.trim();
+    if origin.contains('*') {
+        return Err(format!(
+            "Wildcard origins aren't allowed, list each one: {}",
+            origin
+        ));
+    }
+    let url =
+        reqwest::Url::parse(origin).map_err(|e| format!("Invalid origin '{}': {}", origin, e))?;
+    let scheme = url.scheme();
+    if !matches!(scheme, "http" | "https") && !scheme.ends_with("-extension") {
+        return Err(format!(
+            "Origin '{}' must use http, https or a browser extension scheme",
+            origin
+        ));
+    }
+    let host = url
+        .host_str()
+        .filter(|host| !host.is_empty())
+        .ok_or_else(|| format!("Origin '{}' has no host", origin))?;
+    if !matches!(url.path(), "" | "/")
+        || url.query().is_some()
+        || url.fragment().is_some()
+        || !url.username().is_empty()
+        || url.password().is_some()
+    {
+        return Err(format!(
+            "Origin '{}' can only have a scheme, host and port",
+            origin
+        ));
+    }
+    Ok(match url.port() {
+        Some(port) => format!("{}://{}:{}", scheme, host, port),
+        None => format!("{}://{}", scheme, host),
+    })
+}
+
+/// When a crashed backend is restarted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RestartPolicy {
+    pub enabled: bool,
+    /// Crashes within `window_secs` after which the backend is left down
+    pub max_crashes: u32,
+    /// Window in which crashes count towards a crash loop (seconds)
+    pub window_secs: u64,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_crashes: 5,
+            window_secs: 300,
+        }
+    }
+}
+
+impl RestartPolicy {
+    pub fn window(&self) -> Duration {
+        Duration::from_secs(self.window_secs)
+    }
+}
+
+/// Service settings power users can edit, stored in `service-config.json`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ServiceSettings {
+    /// Ports the backend may use; None tries the next few after the last one
+    pub backend_ports: Option<PortRange>,
+    /// Ports PostgreSQL may use; None tries the next few after the last one
+    pub postgres_ports: Option<PortRange>,
+    /// How long to wait for the backend's first successful health check
+    /// (seconds)
+    pub health_check_timeout_secs: u64,
+    /// Health-check interval once the backend is healthy (seconds)
+    pub health_check_interval_secs: u64,
+    pub backend_log_level: BackendLogLevel,
+    pub postgres_memory: PostgresMemory,
+    pub auto_restart: RestartPolicy,
+    /// Relaunch the app on its own when services fail and can't recover,
+    /// rather than only offering to
+    pub auto_relaunch: bool,
+    /// How long the backend gets to exit after being asked to stop before
+    /// it's killed (seconds)
+    pub shutdown_grace_secs: u64,
+    /// Start the app when the user logs in
+    pub launch_at_login: bool,
+    /// CORS origins the backend allows besides `BUILTIN_ORIGINS`
+    pub allowed_origins: Vec<String>,
+}
+
+impl Default for ServiceSettings {
+    fn default() -> Self {
+        let startup = StartupConfig::default();
+        Self {
+            backend_ports: None,
+            postgres_ports: None,
+            health_check_timeout_secs: startup.health_max_wait_secs,
+            health_check_interval_secs: startup.health_steady_interval_secs,
+            backend_log_level: BackendLogLevel::default(),
+            postgres_memory: PostgresMemory::default(),
+            auto_restart: RestartPolicy::default(),
+            auto_relaunch: false,
+            shutdown_grace_secs: 10,
+            launch_at_login: false,
+            allowed_origins: Vec::new(),
+        }
+    }
+}
+
+impl ServiceSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(range) = &self.backend_ports {
+            range.validate("backend_ports")?;
+        }
+        if let Some(range) = &self.postgres_ports {
+            range.validate("postgres_ports")?;
+        }
+        if let (Some(backend), Some(postgres)) = (&self.backend_ports, &self.postgres_ports) {
+            if backend.start <= postgres.end && postgres.start <= backend.end {
+                return Err("backend_ports and postgres_ports overlap".to_string());
+            }
+        }
+        if !(5..=1800).contains(&self.health_check_timeout_secs) {
+            return Err("health_check_timeout_secs must be between 5 and 1800".to_string());
+        }
+        if !(5..=3600).contains(&self.health_check_interval_secs) {
+            return Err("health_check_interval_secs must be between 5 and 3600".to_string());
+        }
+        self.postgres_memory.validate()?;
+        if self.auto_restart.max_crashes == 0 {
+            return Err("auto_restart.max_crashes must be at least 1".to_string());
+        }
+        if !(10..=86_400).contains(&self.auto_restart.window_secs) {
+            return Err("auto_restart.window_secs must be between 10 and 86400".to_string());
+        }
+        if self.shutdown_grace_secs > 300 {
+            return Err("shutdown_grace_secs can be at most 300".to_string());
+        }
+        if self.allowed_origins.len() > MAX_ALLOWED_ORIGINS {
+            return Err(format!(
+                "allowed_origins can have at most {} entries",
+                MAX_ALLOWED_ORIGINS
+            ));
+        }
+        for origin in &self.allowed_origins {
+            if normalize_origin(origin)? != *origin {
+                return Err(format!("Origin '{}' isn't in normalized form", origin));
+            }
+        }
+        Ok(())
+    }
+
+    /// Origins passed to the backend: the built-in ones, then the
+    /// configured ones
+    pub fn effective_origins(&self) -> Vec<String> {
+        let mut origins: Vec<String> = BUILTIN_ORIGINS.iter().map(|o| o.to_string()).collect();
+        for origin in &self.allowed_origins {
+            if !origins.contains(origin) {
+                origins.push(origin.clone());
+            }
+        }
+        origins
+    }
+
+    /// `config` with the health-check timing from these settings
+    pub fn apply_to(&self, config: StartupConfig) -> StartupConfig {
+        StartupConfig {
+            health_max_wait_secs: self.health_check_timeout_secs,
+            health_steady_interval_secs: self.health_check_interval_secs,
+            ..config
+        }
+    }
+
+    /// Grace period before a stopping backend is killed
+    pub fn shutdown_grace(&self) -> Duration {
+        Duration::from_secs(self.shutdown_grace_secs)
+    }
+}
 
 /// Cached service configuration that persists across restarts
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -19,6 +359,12 @@ pub struct ServiceConfig {
     pub backend_port: u16,
     /// Timestamp of last successful startup (Unix epoch seconds)
     pub last_successful_startup: Option<u64>,
+    /// Timestamp of the last successful local backup (Unix epoch seconds)
+    #[serde(default)]
+    pub last_backup_at: Option<u64>,
+    /// Settings edited by the user rather than recorded by the app
+    #[serde(default)]
+    pub settings: ServiceSettings,
     /// Schema version for migration purposes
     pub schema_version: u32,
 }
@@ -29,85 +375,106 @@ impl Default for ServiceConfig {
             postgres_port: 5433,
             backend_port: 5001,
             last_successful_startup: None,
-            schema_version: 1,
+            last_backup_at: None,
+            settings: ServiceSettings::default(),
+            schema_version: SCHEMA_VERSION,
         }
     }
 }
 
+/// Bring a config file written by an older version up to `SCHEMA_VERSION`
+fn migrate(value: &mut serde_json::Value) -> Result<(), ParseError> {
+    let object = value
+        .as_object_mut()
+        .ok_or_else(|| ParseError::Invalid("Config is not a JSON object".to_string()))?;
+    let version = object
+        .get("schema_version")
+        .and_then(|v| v.as_u64())
+        .ok_or_else(|| ParseError::Invalid("Config has no schema_version".to_string()))?;
+    if version > u64::from(SCHEMA_VERSION) {
+        return Err(ParseError::Unsupported(format!(
+            "Config schema version {} is newer than this app supports ({})",
+            version, SCHEMA_VERSION
+        )));
+    }
+
+    if version < 2 {
+        // 1 -> 2: the shutdown grace period moved into the settings
+        let mut settings = serde_json::Map::new();
+        if let Some(grace) = object.remove("shutdown_grace_secs") {
+            settings.insert("shutdown_grace_secs".to_string(), grace);
+        }
+        object.insert("settings".to_string(), settings.into());
+    }
+
+    object.insert("schema_version".to_string(), SCHEMA_VERSION.into());
+    Ok(())
+}
+
+/// Parse a config file, migrating it from older schema versions
+fn parse(contents: &str) -> Result<ServiceConfig, ParseError> {
+    let mut value: serde_json::Value =
+        serde_json::from_str(contents).map_err(|e| ParseError::Invalid(e.to_string()))?;
+    migrate(&mut value)?;
+    serde_json::from_value(value).map_err(|e| ParseError::Invalid(e.to_string()))
+}
+
+/// Why a config file couldn't be used
+#[derive(Debug)]
+enum ParseError {
+    /// Not valid JSON or not a config
+    Invalid(String),
+    /// A schema version this app can't read
+    Unsupported(String),
+}
+
 impl ServiceConfig {
     /// Load configuration from file, returning default if file doesn't exist or is invalid
     pub fn load(config_dir: &Path) -> Self {
+        Self::load_or(config_dir, Self::default())
+    }
+
+    /// Load configuration from file, returning `defaults` if file doesn't exist or is invalid
+    pub fn load_or(config_dir: &Path, defaults: Self) -> Self {
         let config_path = config_dir.join("service-config.json");
 
         if !config_path.exists() {
-            log::info!("No service config found, using defaults");
-            return Self::default();
+            tracing::info!("No service config found, using defaults");
+            return defaults;
         }
 
         match fs::read_to_string(&config_path) {
-            Ok(contents) => match serde_json::from_str::<ServiceConfig>(&contents) {
-                Ok(config) => {
-                    // Validate schema version
-                    if config.schema_version != 1 {
-                        log::warn!(
-                            "Config schema version mismatch (found {}, expected 1), using defaults",
-                            config.schema_version
-                        );
-                        return Self::default();
+            Ok(contents) => match parse(&contents) {
+                Ok(mut config) => {
+                    if let Err(e) = config.settings.validate() {
+                        tracing::warn!("Invalid service settings ({}), using defaults", e);
+                        config.settings = ServiceSettings::default();
                     }
-                    log::info!("Loaded service config from {:?}", config_path);
+                    tracing::info!("Loaded service config from {:?}", config_path);
                     config
                 }
-                Err(e) => {
-                    log::warn!("Failed to parse service config: {}, using defaults", e);
-                    Self::default()
+                Err(ParseError::Unsupported(e)) => {
+                    tracing::warn!("{}, using defaults", e);
+                    defaults
+                }
+                Err(ParseError::Invalid(e)) => {
+                    crate::config_recovery::recover(&config_path, &contents, &e, defaults)
                 }
             },
             Err(e) => {
-                log::warn!("Failed to read service config: {}, using defaults", e);
-                Self::default()
+                tracing::warn!("Failed to read service config: {}, using defaults", e);
+                defaults
             }
         }
     }
 
-    /// Save configuration to file atomically (temp file + rename)
+    /// Save configuration through the state journal
     pub fn save(&self, config_dir: &Path) -> Result<(), String> {
-        // Ensure directory exists
-        fs::create_dir_all(config_dir)
-            .map_err(|e| format!("Failed to create config directory: {}", e))?;
-
-        let config_path = config_dir.join("service-config.json");
-        let temp_path = config_dir.join(".service-config.json.tmp");
-
-        // Write to temp file
-        let json = serde_json::to_string_pretty(self)
-            .map_err(|e| format!("Failed to serialize config: {}", e))?;
-
-        {
-            let mut file = fs::File::create(&temp_path)
-                .map_err(|e| format!("Failed to create temp config file: {}", e))?;
-
-            file.write_all(json.as_bytes())
-                .map_err(|e| format!("Failed to write config: {}", e))?;
-
-            file.sync_all()
-                .map_err(|e| format!("Failed to sync config file: {}", e))?;
-        }
-
-        // Set restrictive permissions (Unix only)
-        #[cfg(unix)]
-        {
-            use std::os::unix::fs::PermissionsExt;
-            let permissions = fs::Permissions::from_mode(0o600);
-            fs::set_permissions(&temp_path, permissions)
-                .map_err(|e| format!("Failed to set config permissions: {}", e))?;
-        }
-
-        // Atomic rename
-        fs::rename(&temp_path, &config_path)
-            .map_err(|e| format!("Failed to rename config file: {}", e))?;
-
-        log::info!("Saved service config to {:?}", config_path);
+        crate::journal::write(config_dir, "service-config.json", self)?;
+        tracing::info!(
+            "Saved service config to {:?}",
+            Self::config_path(config_dir)
+        );
         Ok(())
     }
 
@@ -123,18 +490,87 @@ impl ServiceConfig {
         );
     }
 
+    /// Grace period before a stopping backend is killed
+    pub fn shutdown_grace(&self) -> Duration {
+        self.settings.shutdown_grace()
+    }
+
     /// Get config file path for a given directory
     pub fn config_path(config_dir: &Path) -> PathBuf {
         config_dir.join("service-config.json")
     }
 }
 
+/// Load a JSON file, returning None if it doesn't exist or is invalid
+pub fn load_json<T: DeserializeOwned>(path: &Path) -> Option<T> {
+    if !path.exists() {
+        return None;
+    }
+
+    match fs::read_to_string(path) {
+        Ok(contents) => match serde_json::from_str::<T>(&contents) {
+            Ok(value) => Some(value),
+            Err(e) => {
+                tracing::warn!("Failed to parse {:?}: {}", path, e);
+                None
+            }
+        },
+        Err(e) => {
+            tracing::warn!("Failed to read {:?}: {}", path, e);
+            None
+        }
+    }
+}
+
+/// Save a value as JSON atomically (temp file + rename) with restrictive permissions
+pub fn save_json_atomic<T: Serialize>(path: &Path, value: &T) -> Result<(), String> {
+    let dir = path
+        .parent()
+        .ok_or_else(|| format!("Invalid path: {:?}", path))?;
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| format!("Invalid path: {:?}", path))?
+        .to_string_lossy();
+
+    fs::create_dir_all(dir).map_err(|e| format!("Failed to create directory: {}", e))?;
+
+    let temp_path = dir.join(format!(".{}.tmp", file_name));
+
+    let json =
+        serde_json::to_string_pretty(value).map_err(|e| format!("Failed to serialize: {}", e))?;
+
+    {
+        let mut file = fs::File::create(&temp_path)
+            .map_err(|e| format!("Failed to create temp file: {}", e))?;
+
+        file.write_all(json.as_bytes())
+            .map_err(|e| format!("Failed to write {}: {}", file_name, e))?;
+
+        file.sync_all()
+            .map_err(|e| format!("Failed to sync {}: {}", file_name, e))?;
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let permissions = fs::Permissions::from_mode(0o600);
+        fs::set_permissions(&temp_path, permissions)
+            .map_err(|e| format!("Failed to set permissions: {}", e))?;
+    }
+
+    fs::rename(&temp_path, path).map_err(|e| format!("Failed to rename {}: {}", file_name, e))?;
+
+    Ok(())
+}
+
 /// Validate that a configuration file is well-formed
 pub fn validate_config_file(path: &Path) -> Result<ServiceConfig, String> {
     let contents = fs::read_to_string(path).map_err(|e| format!("Failed to read file: {}", e))?;
 
-    let config: ServiceConfig =
-        serde_json::from_str(&contents).map_err(|e| format!("Invalid JSON: {}", e))?;
+    let config = parse(&contents).map_err(|e| match e {
+        ParseError::Invalid(e) => format!("Invalid JSON: {}", e),
+        ParseError::Unsupported(e) => e,
+    })?;
 
     // Validate port ranges
     if config.postgres_port < 1024 {
@@ -151,9 +587,97 @@ pub fn validate_config_file(path: &Path) -> Result<ServiceConfig, String> {
         ));
     }
 
+    config.settings.validate()?;
     Ok(config)
 }
 
+/// Settings currently in effect, or the defaults before the config is loaded
+pub(crate) fn current_settings(state: &AppState) -> ServiceSettings {
+    state
+        .service_config
+        .read()
+        .as_ref()
+        .map(|config| config.settings.clone())
+        .unwrap_or_default()
+}
+
+// ============================================================
+// Commands
+// ============================================================
+
+/// Service settings in effect
+#[tauri::command]
+pub async fn get_settings(app: AppHandle) -> Result<ServiceSettings, AppError> {
+    Ok(current_settings(&app.state::<AppState>()))
+}
+
+/// Save service settings to disk and put them in effect
+pub(crate) fn save_settings(app: &AppHandle, settings: ServiceSettings) -> Result<(), AppError> {
+    let app_data_dir = app.path().app_data_dir()?;
+    let state = app.state::<AppState>();
+
+    let mut config = ServiceConfig::load_or(
+        &app_data_dir,
+        state.service_config.read().clone().unwrap_or_default(),
+    );
+    config.settings = settings;
+    config.save(&app_data_dir)?;
+    *state.service_config.write() = Some(config);
+    Ok(())
+}
+
+/// Validate and save service settings
+///
+/// Health-check timing, the restart policy, the shutdown grace period and
+/// launching at login apply at once; ports, the log level, PostgreSQL
+/// memory and allowed origins apply the next time the services start.
+#[tauri::command]
+pub async fn update_settings(
+    app: AppHandle,
+    settings: ServiceSettings,
+) -> Result<ServiceSettings, AppError> {
+    settings.validate().map_err(AppError::InvalidInput)?;
+    if settings.launch_at_login != crate::login_item::is_enabled(&app) {
+        crate::login_item::apply(&app, settings.launch_at_login)?;
+    }
+    save_settings(&app, settings.clone())?;
+
+    tracing::info!("Service settings updated");
+    Ok(settings)
+}
+
+/// Replace the extra CORS origins the backend allows
+///
+/// Origins are normalized and deduplicated, and a running backend is
+/// restarted to pick them up. Returns the effective list, built-in origins
+/// first.
+#[tauri::command]
+pub async fn set_allowed_origins(
+    app: AppHandle,
+    origins: Vec<String>,
+) -> Result<Vec<String>, AppError> {
+    let mut normalized: Vec<String> = Vec::new();
+    for origin in &origins {
+        let origin = normalize_origin(origin).map_err(AppError::InvalidInput)?;
+        if !BUILTIN_ORIGINS.contains(&origin.as_str()) && !normalized.contains(&origin) {
+            normalized.push(origin);
+        }
+    }
+    let mut settings = current_settings(&app.state::<AppState>());
+    settings.allowed_origins = normalized;
+    settings.validate().map_err(AppError::InvalidInput)?;
+    let effective = settings.effective_origins();
+    save_settings(&app, settings)?;
+    tracing::info!("Allowed origins updated: {}", effective.join(", "));
+
+    let services = app.state::<ServiceManager>();
+    let backend = services.state().backend;
+    if matches!(backend, ServicePhase::Running | ServicePhase::Starting) {
+        services.send(ServiceCommand::RestartBackend).await?;
+    }
+    Ok(effective)
+}
+
 // ============================================================
 // Unit Tests
 // ============================================================
@@ -168,8 +692,156 @@ mod tests {
         let config = ServiceConfig::default();
         assert_eq!(config.postgres_port, 5433);
         assert_eq!(config.backend_port, 5001);
-        assert_eq!(config.schema_version, 1);
+        assert_eq!(config.schema_version, SCHEMA_VERSION);
         assert!(config.last_successful_startup.is_none());
+        assert_eq!(config.settings.shutdown_grace_secs, 10);
+        assert!(config.settings.validate().is_ok());
+    }
+
+    #[test]
+    fn test_migrate_from_v1() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(
+            ServiceConfig::config_path(temp_dir.path()),
+            r#"{"postgres_port":5440,"backend_port":5010,"last_successful_startup":null,"shutdown_grace_secs":30,"schema_version":1}"#,
+        )
+        .unwrap();
+
+        let config = ServiceConfig::load(temp_dir.path());
+        assert_eq!(config.schema_version, SCHEMA_VERSION);
+        assert_eq!(config.backend_port, 5010);
+        assert_eq!(config.settings.shutdown_grace_secs, 30);
+        assert_eq!(config.settings.postgres_memory, PostgresMemory::default());
+    }
+
+    #[test]
+    fn test_invalid_settings_fall_back_to_defaults() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(
+            ServiceConfig::config_path(temp_dir.path()),
+            r#"{"postgres_port":5440,"backend_port":5010,"last_successful_startup":null,"settings":{"health_check_timeout_secs":0},"schema_version":2}"#,
+        )
+        .unwrap();
+
+        let config = ServiceConfig::load(temp_dir.path());
+        assert_eq!(config.backend_port, 5010);
+        assert_eq!(config.settings, ServiceSettings::default());
+    }
+
+    #[test]
+    fn test_settings_validation() {
+        let valid = ServiceSettings {
+            backend_ports: Some(PortRange {
+                start: 5001,
+                end: 5020,
+            }),
+            postgres_ports: Some(PortRange {
+                start: 5433,
+                end: 5440,
+            }),
+            ..Default::default()
+        };
+        assert!(valid.validate().is_ok());
+
+        let overlapping = ServiceSettings {
+            postgres_ports: Some(PortRange {
+                start: 5010,
+                end: 5030,
+            }),
+            ..valid.clone()
+        };
+        assert!(overlapping.validate().unwrap_err().contains("overlap"));
+
+        let privileged = ServiceSettings {
+            backend_ports: Some(PortRange { start: 80, end: 90 }),
+            ..Default::default()
+        };
+        assert!(privileged.validate().is_err());
+
+        let mut tiny_cache = ServiceSettings::default();
+        tiny_cache.postgres_memory.effective_cache_size_mb = 64;
+        assert!(tiny_cache.validate().is_err());
+
+        let mut no_restarts = ServiceSettings::default();
+        no_restarts.auto_restart.max_crashes = 0;
+        assert!(no_restarts.validate().is_err());
+    }
+
+    #[test]
+    fn test_normalize_origin() {
+        assert_eq!(
+            normalize_origin(" HTTP://Example.com:80/ ").unwrap(),
+            "http://example.com"
+        );
+        assert_eq!(
+            normalize_origin("http://192.168.1.20:5173").unwrap(),
+            "http://192.168.1.20:5173"
+        );
+        assert_eq!(
+            normalize_origin("chrome-extension://abcdefghijklmnop").unwrap(),
+            "chrome-extension://abcdefghijklmnop"
+        );
+        assert!(normalize_origin("*").is_err());
+        assert!(normalize_origin("https://*.example.com").is_err());
+        assert!(normalize_origin("https://example.com/app").is_err());
+        assert!(normalize_origin("ftp://example.com").is_err());
+        assert!(normalize_origin("example.com").is_err());
+    }
+
+    #[test]
+    fn test_effective_origins() {
+        let mut settings = ServiceSettings::default();
+        assert_eq!(settings.effective_origins(), BUILTIN_ORIGINS);
+
+        settings.allowed_origins = vec![
+            "http://localhost".to_string(),
+            "http://192.168.1.20:5173".to_string(),
+        ];
+        assert!(settings.validate().is_ok());
+        let effective = settings.effective_origins();
+        assert_eq!(effective.len(), BUILTIN_ORIGINS.len() + 1);
+        assert_eq!(effective.last().unwrap(), "http://192.168.1.20:5173");
+
+        settings.allowed_origins = vec!["HTTP://Example.com".to_string()];
+        assert!(settings.validate().is_err());
+    }
+
+    #[test]
+    fn test_port_range() {
+        let range = PortRange {
+            start: 5001,
+            end: 5010,
+        };
+        assert_eq!(range.count(), 10);
+        assert_eq!(range.preferred(5005), 5005);
+        assert_eq!(range.preferred(6000), 5001);
+    }
+
+    #[test]
+    fn test_apply_to_startup_config() {
+        let settings = ServiceSettings {
+            health_check_timeout_secs: 300,
+            health_check_interval_secs: 60,
+            ..Default::default()
+        };
+        let config = settings.apply_to(StartupConfig::default());
+        assert_eq!(config.health_max_wait_secs, 300);
+        assert_eq!(config.health_steady_interval_secs, 60);
+        assert_eq!(config.max_attempts, StartupConfig::default().max_attempts);
+    }
+
+    #[test]
+    fn test_service_config_without_shutdown_grace() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(
+            ServiceConfig::config_path(temp_dir.path()),
+            r#"{"postgres_port":5440,"backend_port":5010,"last_successful_startup":null,"schema_version":1}"#,
+        )
+        .unwrap();
+
+        let config = ServiceConfig::load(temp_dir.path());
+        assert_eq!(config.postgres_port, 5440);
+        assert_eq!(config.shutdown_grace(), std::time::Duration::from_secs(10));
     }
 
     #[test]
@@ -289,6 +961,29 @@ mod tests {
         assert_eq!(mode & 0o777, 0o600);
     }
 
+    #[test]
+    fn test_save_json_atomic_and_load_json() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("nested").join("settings.json");
+
+        save_json_atomic(&path, &ServiceConfig::default()).unwrap();
+
+        let loaded: ServiceConfig = load_json(&path).unwrap();
+        assert_eq!(loaded.postgres_port, 5433);
+        assert!(!temp_dir.path().join("nested/.settings.json.tmp").exists());
+    }
+
+    #[test]
+    fn test_load_json_missing_or_invalid() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("settings.json");
+
+        assert!(load_json::<ServiceConfig>(&path).is_none());
+
+        fs::write(&path, "not json").unwrap();
+        assert!(load_json::<ServiceConfig>(&path).is_none());
+    }
+
     #[test]
     fn test_schema_version_mismatch() {
         let temp_dir = TempDir::new().unwrap();
@@ -0,0 +1,280 @@
+//! Recovery from corrupted secrets and service config files.
+//!
+//! This module provides:
+//! - Quarantine of a file that fails to parse, renamed to
+//!   `<name>.corrupt-<timestamp>` so a later save can't overwrite it
+//! - Best-effort recovery of the fields that can still be read from it,
+//!   used in place of plain defaults
+//! - A `config-corrupted` event per quarantined file, batched as
+//!   `{ items, dropped }` since files are loaded before any window exists
+//! - `recover_config`, which saves what can be recovered from the newest
+//!   quarantined copy of a file back under its own name
+//!
+//! Quarantined copies are never deleted here, so a user can always go back
+//! to them by hand.
+
+use chrono::Local;
+use regex_lite::Regex;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+
+use crate::config::{save_json_atomic, ServiceConfig};
+use crate::error::AppError;
+use crate::Secrets;
+
+/// Files `recover_config` knows how to recover
+const RECOVERABLE: &[&str] = &["secrets.json", "service-config.json"];
+
+/// A corrupt file and what was recovered from it
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CorruptConfig {
+    pub file: String,
+    /// Name of the quarantined copy, None if it couldn't be moved aside
+    pub quarantined_as: Option<String>,
+    pub error: String,
+    /// Fields read back from the broken file
+    pub recovered_fields: Vec<String>,
+}
+
+fn quarantine(path: &Path) -> std::io::Result<PathBuf> {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let target = path.with_file_name(format!(
+        "{}.corrupt-{}",
+        name,
+        Local::now().format("%Y%m%d-%H%M%S")
+    ));
+    std::fs::rename(path, &target)?;
+    Ok(target)
+}
+
+/// Value of `key` in text that may not be valid JSON as a whole
+fn scan_field(contents: &str, key: &str) -> Option<Value> {
+    let pattern = format!(
+        r#""{}"\s*:\s*("(?:[^"\\]|\\.)*"|-?\d+(?:\.\d+)?|true|false)"#,
+        regex_lite::escape(key)
+    );
+    let captures = Regex::new(&pattern).ok()?.captures(contents)?;
+    serde_json::from_str(captures.get(1)?.as_str()).ok()
+}
+
+/// Read whatever fields of `T` survive in `contents`, taking the rest from
+/// `defaults`; also returns the names of the fields read, sorted
+///
+/// Each field is kept only if `T` still deserializes with it, so a field
+/// with the wrong type falls back to its default on its own.
+pub fn recover_fields<T>(contents: &str, defaults: T) -> (T, Vec<String>)
+where
+    T: Serialize + DeserializeOwned,
+{
+    let Ok(Value::Object(fields)) = serde_json::to_value(&defaults) else {
+        return (defaults, Vec::new());
+    };
+    let parsed = match serde_json::from_str::<Value>(contents) {
+        Ok(Value::Object(object)) => Some(object),
+        _ => None,
+    };
+
+    let mut merged: Map<String, Value> = fields.clone();
+    let mut recovered = Vec::new();
+    for key in fields.keys() {
+        let value = match &parsed {
+            Some(object) => object.get(key).cloned(),
+            None => scan_field(contents, key),
+        };
+        let Some(value) = value.filter(|value| !value.is_null()) else {
+            continue;
+        };
+        let mut trial = merged.clone();
+        trial.insert(key.clone(), value);
+        if serde_json::from_value::<T>(Value::Object(trial.clone())).is_ok() {
+            merged = trial;
+            recovered.push(key.clone());
+        }
+    }
+
+    recovered.sort();
+    let value = serde_json::from_value(Value::Object(merged)).unwrap_or(defaults);
+    (value, recovered)
+}
+
+/// Quarantine a file that failed to parse and recover what it can
+///
+/// Called by loaders in place of falling back to `defaults`.
+pub fn recover<T>(path: &Path, contents: &str, error: &str, defaults: T) -> T
+where
+    T: Serialize + DeserializeOwned,
+{
+    let file = path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+    let quarantined_as = match quarantine(path) {
+        Ok(target) => Some(
+            target
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string(),
+        ),
+        Err(e) => {
+            tracing::error!("Failed to quarantine corrupt {}: {}", file, e);
+            None
+        }
+    };
+    let (value, recovered_fields) = recover_fields(contents, defaults);
+    tracing::warn!(
+        "{} is corrupt ({}); moved to {:?}, recovered {} fields",
+        file,
+        error,
+        quarantined_as,
+        recovered_fields.len()
+    );
+
+    crate::events::emit_batched(
+        "config-corrupted",
+        &CorruptConfig {
+            file,
+            quarantined_as,
+            error: error.to_string(),
+            recovered_fields,
+        },
+    );
+    value
+}
+
+/// Newest quarantined copy of `file` in `dir`
+fn latest_quarantined(dir: &Path, file: &str) -> Option<PathBuf> {
+    let prefix = format!("{}.corrupt-", file);
+    std::fs::read_dir(dir)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .is_some_and(|name| name.to_string_lossy().starts_with(&prefix))
+        })
+        // Timestamps sort in name order
+        .max()
+}
+
+fn restore<T>(dir: &Path, file: &str) -> Result<CorruptConfig, AppError>
+where
+    T: Serialize + DeserializeOwned + Default,
+{
+    let source = latest_quarantined(dir, file)
+        .ok_or_else(|| AppError::NotFound(format!("No quarantined copy of {}", file)))?;
+    let contents = std::fs::read_to_string(&source)?;
+    let error = serde_json::from_str::<T>(&contents)
+        .err()
+        .map(|e| e.to_string())
+        .unwrap_or_default();
+    let (value, recovered_fields) = recover_fields(&contents, T::default());
+    save_json_atomic(&dir.join(file), &value)?;
+    tracing::info!(
+        "Recovered {} fields of {} from {:?}",
+        recovered_fields.len(),
+        file,
+        source
+    );
+    Ok(CorruptConfig {
+        file: file.to_string(),
+        quarantined_as: source
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string()),
+        error,
+        recovered_fields,
+    })
+}
+
+// ============================================================
+// Commands
+// ============================================================
+
+/// Save what can be recovered from the newest quarantined copy of a file
+///
+/// The file's current contents are replaced; restart the backend afterwards
+/// for recovered secrets to take effect.
+#[tauri::command]
+pub async fn recover_config(app: AppHandle, file: String) -> Result<CorruptConfig, AppError> {
+    if !RECOVERABLE.contains(&file.as_str()) {
+        return Err(AppError::InvalidInput(format!(
+            "{} can't be recovered; expected one of {}",
+            file,
+            RECOVERABLE.join(", ")
+        )));
+    }
+    let app_data_dir = app.path().app_data_dir()?;
+    tokio::task::spawn_blocking(move || match file.as_str() {
+        "secrets.json" => restore::<Secrets>(&app_data_dir, &file),
+        _ => restore::<ServiceConfig>(&app_data_dir, &file),
+    })
+    .await?
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_recover_fields_from_truncated_file() {
+        let contents = r#"{
+  "openai_api_key": "sk-abc\"123",
+  "anthropic_api_key": null,
+  "git_require_user_scoped_root": true,
+  "xai_api_key": "xai-"#;
+        let (secrets, fields) = recover_fields(contents, Secrets::default());
+        assert_eq!(secrets.openai_api_key.as_deref(), Some("sk-abc\"123"));
+        assert_eq!(secrets.git_require_user_scoped_root, Some(true));
+        assert!(secrets.xai_api_key.is_none());
+        assert_eq!(
+            fields,
+            vec!["git_require_user_scoped_root", "openai_api_key"]
+        );
+    }
+
+    #[test]
+    fn test_recover_fields_skips_wrong_types() {
+        let contents = r#"{"postgres_port": "oops", "backend_port": 5002}"#;
+        let (config, fields) = recover_fields(contents, ServiceConfig::default());
+        assert_eq!(config.postgres_port, 5433);
+        assert_eq!(config.backend_port, 5002);
+        assert_eq!(fields, vec!["backend_port"]);
+    }
+
+    #[test]
+    fn test_recover_quarantines_and_restores() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("service-config.json");
+        let contents = r#"{"postgres_port": 5440, "backend_port": 50"#;
+        std::fs::write(&path, contents).unwrap();
+
+        let config = recover(
+            &path,
+            contents,
+            "EOF while parsing",
+            ServiceConfig::default(),
+        );
+        assert_eq!(config.postgres_port, 5440);
+        assert!(!path.exists());
+        let quarantined = latest_quarantined(temp_dir.path(), "service-config.json").unwrap();
+        assert_eq!(std::fs::read_to_string(&quarantined).unwrap(), contents);
+
+        let report = restore::<ServiceConfig>(temp_dir.path(), "service-config.json").unwrap();
+        assert_eq!(
+            report.recovered_fields,
+            vec!["backend_port", "postgres_port"]
+        );
+        assert_eq!(ServiceConfig::load(temp_dir.path()).postgres_port, 5440);
+        // The quarantined copy is kept
+        assert!(quarantined.exists());
+    }
+}
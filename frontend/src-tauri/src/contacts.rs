@@ -0,0 +1,340 @@
+//! Opt-in Contacts integration for people enrichment.
+//!
+//! This module provides:
+//! - A persisted opt-in switch that requests Contacts access when enabled
+//! - Contact lookup by name or email address
+//! - Canonical names, emails, and avatars for the backend to enrich notes
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+
+use crate::config::{load_json, save_json_atomic};
+use crate::error::AppError;
+use crate::osascript::{run_jxa, PermissionStatus};
+
+/// Maximum contacts returned for a single lookup
+const MAX_RESULTS: usize = 10;
+
+/// Contacts framework bridge: `status`, `request`, `name <query>`, or `email <query>`
+const CONTACTS_SCRIPT: &str = r#"
+ObjC.import('Contacts');
+ObjC.import('Foundation');
+
+function run(argv) {
+    const store = $.CNContactStore.alloc.init;
+    const mode = argv[0];
+
+    if (mode === 'status') {
+        return JSON.stringify({ status: $.CNContactStore.authorizationStatusForEntityType(0) });
+    }
+
+    if (mode === 'request') {
+        let done = false;
+        store.requestAccessForEntityTypeCompletionHandler(0, function (granted, error) { done = true; });
+        const deadline = Date.now() + 120000;
+        while (!done && Date.now() < deadline) {
+            $.NSRunLoop.currentRunLoop.runUntilDate($.NSDate.dateWithTimeIntervalSinceNow(0.2));
+        }
+        return JSON.stringify({ status: $.CNContactStore.authorizationStatusForEntityType(0) });
+    }
+
+    const predicate = mode === 'email'
+        ? $.CNContact.predicateForContactsMatchingEmailAddress(argv[1])
+        : $.CNContact.predicateForContactsMatchingName(argv[1]);
+    const keys = $([
+        $.CNContactIdentifierKey, $.CNContactGivenNameKey, $.CNContactFamilyNameKey,
+        $.CNContactOrganizationNameKey, $.CNContactEmailAddressesKey,
+        $.CNContactThumbnailImageDataKey,
+    ]);
+    const contacts = store.unifiedContactsMatchingPredicateKeysToFetchError(predicate, keys, $());
+    const out = [];
+    for (let i = 0; i < contacts.count; i++) {
+        const c = contacts.objectAtIndex(i);
+        const emails = [];
+        for (let j = 0; j < c.emailAddresses.count; j++) {
+            emails.push(ObjC.unwrap(c.emailAddresses.objectAtIndex(j).value));
+        }
+        const thumb = c.thumbnailImageData;
+        out.push({
+            identifier: ObjC.unwrap(c.identifier),
+            given_name: ObjC.unwrap(c.givenName) || '',
+            family_name: ObjC.unwrap(c.familyName) || '',
+            organization: ObjC.unwrap(c.organizationName) || '',
+            emails: emails,
+            avatar_base64: thumb.isNil() ? null : ObjC.unwrap(thumb.base64EncodedStringWithOptions(0)),
+        });
+    }
+    return JSON.stringify(out);
+}
+"#;
+
+/// Persisted opt-in state
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContactsSettings {
+    /// Whether contact lookups are allowed
+    pub enabled: bool,
+}
+
+impl ContactsSettings {
+    fn path(app_data_dir: &Path) -> PathBuf {
+        app_data_dir.join("contacts-settings.json")
+    }
+
+    /// Load settings, defaulting to disabled
+    pub fn load(app_data_dir: &Path) -> Self {
+        load_json(&Self::path(app_data_dir)).unwrap_or_default()
+    }
+
+    /// Save settings atomically
+    pub fn save(&self, app_data_dir: &Path) -> Result<(), String> {
+        save_json_atomic(&Self::path(app_data_dir), self)
+    }
+}
+
+/// Opt-in state and current permission
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContactsStatus {
+    pub enabled: bool,
+    pub permission: PermissionStatus,
+}
+
+/// Lookup key for a contact
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ContactQuery {
+    Name(String),
+    Email(String),
+}
+
+impl ContactQuery {
+    /// Script mode and trimmed search term
+    fn to_args(&self) -> Result<(&'static str, String), String> {
+        let (mode, term) = match self {
+            ContactQuery::Name(name) => ("name", name.trim()),
+            ContactQuery::Email(email) => ("email", email.trim()),
+        };
+
+        if term.is_empty() {
+            return Err("Contact query cannot be empty".to_string());
+        }
+        if mode == "email" && !term.contains('@') {
+            return Err(format!("Invalid email address: {}", term));
+        }
+
+        Ok((mode, term.to_string()))
+    }
+}
+
+/// A contact card reduced to the fields used for enrichment
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Contact {
+    pub identifier: String,
+    /// Display name built from the given and family names
+    pub full_name: String,
+    pub given_name: String,
+    pub family_name: String,
+    pub organization: String,
+    pub emails: Vec<String>,
+    /// Thumbnail image data, base64-encoded
+    pub avatar_base64: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RawContact {
+    identifier: String,
+    given_name: String,
+    family_name: String,
+    organization: String,
+    emails: Vec<String>,
+    avatar_base64: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RawStatus {
+    status: i64,
+}
+
+fn parse_contacts(json: &str) -> Result<Vec<Contact>, String> {
+    let raw: Vec<RawContact> =
+        serde_json::from_str(json).map_err(|e| format!("Invalid Contacts output: {}", e))?;
+
+    Ok(raw
+        .into_iter()
+        .take(MAX_RESULTS)
+        .map(|c| {
+            let full_name = match (c.given_name.is_empty(), c.family_name.is_empty()) {
+                (false, false) => format!("{} {}", c.given_name, c.family_name),
+                (false, true) => c.given_name.clone(),
+                (true, false) => c.family_name.clone(),
+                (true, true) => c.organization.clone(),
+            };
+            Contact {
+                identifier: c.identifier,
+                full_name,
+                given_name: c.given_name,
+                family_name: c.family_name,
+                organization: c.organization,
+                emails: c.emails,
+                avatar_base64: c.avatar_base64.filter(|a| !a.is_empty()),
+            }
+        })
+        .collect())
+}
+
+fn parse_status(json: &str) -> Result<PermissionStatus, String> {
+    let raw: RawStatus =
+        serde_json::from_str(json).map_err(|e| format!("Invalid Contacts output: {}", e))?;
+    Ok(PermissionStatus::from_code(raw.status))
+}
+
+/// Current Contacts authorization status
+pub fn permission_status() -> PermissionStatus {
+    if !cfg!(target_os = "macos") {
+        return PermissionStatus::Unavailable;
+    }
+    run_jxa(CONTACTS_SCRIPT, &["status"])
+        .and_then(|out| parse_status(&out))
+        .unwrap_or(PermissionStatus::Unavailable)
+}
+
+/// Prompt for Contacts access if the user has not decided yet
+fn request_access() -> Result<PermissionStatus, String> {
+    match permission_status() {
+        PermissionStatus::NotDetermined => parse_status(&run_jxa(CONTACTS_SCRIPT, &["request"])?),
+        status => Ok(status),
+    }
+}
+
+/// Get the Contacts opt-in state and permission status
+#[tauri::command]
+pub async fn get_contacts_status(app: AppHandle) -> Result<ContactsStatus, AppError> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let enabled = ContactsSettings::load(&app_data_dir).enabled;
+    let permission = tokio::task::spawn_blocking(permission_status)
+        .await
+        .map_err(|e| format!("Task panicked: {}", e))?;
+    Ok(ContactsStatus {
+        enabled,
+        permission,
+    })
+}
+
+/// Opt in to or out of the Contacts integration
+///
+/// Enabling prompts for access; the integration stays disabled unless access
+/// is granted. Returns the resulting permission status.
+#[tauri::command]
+pub async fn set_contacts_enabled(
+    app: AppHandle,
+    enabled: bool,
+) -> Result<PermissionStatus, AppError> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+
+    let permission = if enabled {
+        tokio::task::spawn_blocking(request_access)
+            .await
+            .map_err(|e| format!("Task panicked: {}", e))??
+    } else {
+        tokio::task::spawn_blocking(permission_status)
+            .await
+            .map_err(|e| format!("Task panicked: {}", e))?
+    };
+
+    let settings = ContactsSettings {
+        enabled: enabled && permission.is_authorized(),
+    };
+    settings.save(&app_data_dir)?;
+
+    tracing::info!(
+        "Contacts integration {} (permission: {:?})",
+        if settings.enabled {
+            "enabled"
+        } else {
+            "disabled"
+        },
+        permission
+    );
+    Ok(permission)
+}
+
+/// Look up contacts by name or email address
+#[tauri::command]
+pub async fn lookup_contact(app: AppHandle, query: ContactQuery) -> Result<Vec<Contact>, AppError> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    if !ContactsSettings::load(&app_data_dir).enabled {
+        return Err(AppError::NotReady(
+            "Contacts integration is disabled. Enable it in Settings first.".to_string(),
+        ));
+    }
+
+    let (mode, term) = query.to_args()?;
+
+    tokio::task::spawn_blocking(move || {
+        if !permission_status().is_authorized() {
+            return Err(
+                "Contacts access not granted. Enable Second Brain in System Settings > \
+                 Privacy & Security > Contacts."
+                    .to_string(),
+            );
+        }
+        parse_contacts(&run_jxa(CONTACTS_SCRIPT, &[mode, &term])?)
+    })
+    .await?
+    .map_err(AppError::from)
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_settings_default_disabled() {
+        let temp_dir = TempDir::new().unwrap();
+        assert!(!ContactsSettings::load(temp_dir.path()).enabled);
+
+        ContactsSettings { enabled: true }
+            .save(temp_dir.path())
+            .unwrap();
+        assert!(ContactsSettings::load(temp_dir.path()).enabled);
+    }
+
+    #[test]
+    fn test_query_validation() {
+        assert!(ContactQuery::Name("  ".to_string()).to_args().is_err());
+        assert!(ContactQuery::Email("not-an-email".to_string())
+            .to_args()
+            .is_err());
+
+        let (mode, term) = ContactQuery::Email(" ada@example.com ".to_string())
+            .to_args()
+            .unwrap();
+        assert_eq!(mode, "email");
+        assert_eq!(term, "ada@example.com");
+    }
+
+    #[test]
+    fn test_query_deserializes_from_tagged_object() {
+        let query: ContactQuery = serde_json::from_str(r#"{"name":"Ada"}"#).unwrap();
+        assert!(matches!(query, ContactQuery::Name(ref n) if n == "Ada"));
+    }
+
+    #[test]
+    fn test_parse_contacts_builds_full_name() {
+        let json = r#"[
+            {"identifier":"1","given_name":"Ada","family_name":"Lovelace","organization":"","emails":["ada@example.com"],"avatar_base64":null},
+            {"identifier":"2","given_name":"","family_name":"","organization":"Acme","emails":[],"avatar_base64":""}
+        ]"#;
+
+        let contacts = parse_contacts(json).unwrap();
+        assert_eq!(contacts[0].full_name, "Ada Lovelace");
+        assert_eq!(contacts[0].emails, vec!["ada@example.com"]);
+        assert_eq!(contacts[1].full_name, "Acme");
+        assert_eq!(contacts[1].avatar_base64, None);
+    }
+}
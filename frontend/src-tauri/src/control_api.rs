@@ -0,0 +1,808 @@
+//! Local control API for scripting the shell.
+//!
+//! This module provides:
+//! - A small HTTP/1.1 server on 127.0.0.1, off by default
+//! - Bearer-token authentication, with the token kept in the keychain
+//! - Routes for status, service restarts, backups and quick capture, so
+//!   launchers such as Raycast or Alfred can drive the app without the webview
+//!
+//! Routes (all JSON):
+//! - `GET  /v1/status`
+//! - `POST /v1/services/restart` with `{"target": "all" | "backend" | "database"}`
+//! - `POST /v1/backup`, answered with the job id to follow
+//! - `POST /v1/capture` with `{"content", "title"?, "tags"?, "folder"?}`
+//! - `POST /v1/notes/search` with `{"query", "limit"?}`
+//! - `POST /v1/reminders` with `{"note_id", "at", "message"?}`, `at` in
+//!   RFC 3339
+//!
+//! Requests carrying an `Origin` header or a non-local `Host` are refused,
+//! so web pages can't reach the API through the browser. Search and capture
+//! are refused while the app is locked.
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::config::{load_json, save_json_atomic};
+use crate::error::AppError;
+use crate::jobs::JobRequest;
+use crate::keychain;
+use crate::services::{ServiceCommand, ServiceManager, ServiceState};
+use crate::AppState;
+
+/// Default port for the control API
+const DEFAULT_PORT: u16 = 47822;
+
+/// Keychain account holding the bearer token
+const TOKEN_ACCOUNT: &str = "control-api-token";
+
+/// Largest accepted request head (request line and headers)
+const MAX_HEAD_BYTES: usize = 16 * 1024;
+
+/// Largest accepted request body
+const MAX_BODY_BYTES: usize = 1024 * 1024;
+
+/// Most notes returned by a search
+const MAX_SEARCH_RESULTS: u32 = 50;
+
+/// Timeout for reading a request or writing a response
+const IO_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Control API settings
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ControlApiSettings {
+    pub enabled: bool,
+    pub port: u16,
+}
+
+impl Default for ControlApiSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: DEFAULT_PORT,
+        }
+    }
+}
+
+impl ControlApiSettings {
+    fn path(app_data_dir: &Path) -> PathBuf {
+        app_data_dir.join("control-api.json")
+    }
+
+    pub fn load(app_data_dir: &Path) -> Self {
+        load_json(&Self::path(app_data_dir)).unwrap_or_default()
+    }
+
+    pub fn save(&self, app_data_dir: &Path) -> Result<(), String> {
+        save_json_atomic(&Self::path(app_data_dir), self)
+    }
+
+    fn validate(&self) -> Result<(), AppError> {
+        if self.port < 1024 {
+            return Err(AppError::InvalidInput(format!(
+                "Port {} is reserved; choose 1024 or above",
+                self.port
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Running listener, kept in Tauri state
+#[derive(Default)]
+pub struct ControlApi {
+    /// Port and accept loop of the open listener
+    listener: Mutex<Option<(u16, tauri::async_runtime::JoinHandle<()>)>>,
+    token: Mutex<Option<String>>,
+}
+
+/// What a script needs to call the API
+#[derive(Debug, Clone, Serialize)]
+pub struct ControlApiInfo {
+    pub enabled: bool,
+    pub port: u16,
+    pub url: String,
+    pub token: String,
+}
+
+// ============================================================
+// HTTP
+// ============================================================
+
+/// A parsed request
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct HttpRequest {
+    pub method: String,
+    pub path: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl HttpRequest {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Parse the request line and headers; the body is read separately
+fn parse_head(head: &str) -> Result<HttpRequest, AppError> {
+    let mut lines = head.lines();
+    let request_line = lines.next().unwrap_or_default();
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(path), Some(version)) = (parts.next(), parts.next(), parts.next())
+    else {
+        return Err(AppError::InvalidInput("Malformed request line".to_string()));
+    };
+    if !version.starts_with("HTTP/1.") {
+        return Err(AppError::InvalidInput(format!(
+            "Unsupported protocol {}",
+            version
+        )));
+    }
+    let headers = lines
+        .take_while(|line| !line.is_empty())
+        .filter_map(|line| {
+            let (name, value) = line.split_once(':')?;
+            Some((name.trim().to_string(), value.trim().to_string()))
+        })
+        .collect();
+    Ok(HttpRequest {
+        method: method.to_ascii_uppercase(),
+        path: path.to_string(),
+        headers,
+        body: Vec::new(),
+    })
+}
+
+pub(crate) async fn read_request(stream: &mut TcpStream) -> Result<HttpRequest, AppError> {
+    let mut reader = BufReader::new(stream);
+    let mut head = String::new();
+    {
+        // Bounds a single endless line as well as many lines
+        let mut limited = (&mut reader).take(MAX_HEAD_BYTES as u64 + 1);
+        loop {
+            let read = limited.read_line(&mut head).await?;
+            if read == 0 || head.ends_with("\r\n\r\n") || head.ends_with("\n\n") {
+                break;
+            }
+        }
+    }
+    if head.len() > MAX_HEAD_BYTES {
+        return Err(AppError::InvalidInput("Request head too large".to_string()));
+    }
+    let mut request = parse_head(&head)?;
+    let length = request
+        .header("content-length")
+        .map(|value| value.parse::<usize>())
+        .transpose()
+        .map_err(|_| AppError::InvalidInput("Invalid Content-Length".to_string()))?
+        .unwrap_or(0);
+    if length > MAX_BODY_BYTES {
+        return Err(AppError::InvalidInput("Request body too large".to_string()));
+    }
+    request.body.resize(length, 0);
+    reader.read_exact(&mut request.body).await?;
+    Ok(request)
+}
+
+fn http_status(error: &AppError) -> (u16, &'static str) {
+    match error {
+        AppError::InvalidInput(_) => (400, "Bad Request"),
+        AppError::Permission(_) => (403, "Forbidden"),
+        AppError::NotFound(_) => (404, "Not Found"),
+        AppError::Conflict(_) | AppError::Cancelled(_) => (409, "Conflict"),
+        AppError::NotReady(_) => (503, "Service Unavailable"),
+        _ => (500, "Internal Server Error"),
+    }
+}
+
+fn reason(code: u16) -> &'static str {
+    match code {
+        201 => "Created",
+        202 => "Accepted",
+        _ => "OK",
+    }
+}
+
+async fn write_response(
+    stream: &mut TcpStream,
+    status: (u16, &str),
+    body: &serde_json::Value,
+) -> std::io::Result<()> {
+    let body = body.to_string();
+    let response = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n{}",
+        status.0,
+        status.1,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+// ============================================================
+// Authentication
+// ============================================================
+
+pub(crate) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+pub(crate) fn is_local_host(host: &str) -> bool {
+    let name = match host.rsplit_once(':') {
+        Some((name, port)) if port.chars().all(|c| c.is_ascii_digit()) => name,
+        _ => host,
+    };
+    matches!(name, "127.0.0.1" | "localhost" | "[::1]")
+}
+
+/// Refuse browser requests and requests without the token
+fn authorize(request: &HttpRequest, token: &str) -> Result<(), AppError> {
+    if request.header("origin").is_some() {
+        return Err(AppError::Permission(
+            "Browser requests are not accepted".to_string(),
+        ));
+    }
+    if !request.header("host").is_some_and(is_local_host) {
+        return Err(AppError::Permission("Host must be localhost".to_string()));
+    }
+    let presented = request
+        .header("authorization")
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::trim)
+        .unwrap_or_default();
+    if token.is_empty() || !constant_time_eq(presented.as_bytes(), token.as_bytes()) {
+        return Err(AppError::Permission(
+            "Missing or invalid bearer token".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn new_token() -> Result<String, AppError> {
+    let mut random = [0u8; 32];
+    getrandom::fill(&mut random)
+        .map_err(|e| AppError::Internal(format!("Failed to generate token: {}", e)))?;
+    Ok(random.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// Token from the keychain, if one was ever created
+pub(crate) fn stored_token(app_data_dir: &Path) -> Result<Option<String>, AppError> {
+    Ok(keychain::get_secret(app_data_dir, TOKEN_ACCOUNT)?)
+}
+
+/// Token from the keychain, created on first use
+pub(crate) fn load_or_create_token(app_data_dir: &Path) -> Result<String, AppError> {
+    if let Some(token) = stored_token(app_data_dir)? {
+        return Ok(token);
+    }
+    let token = new_token()?;
+    keychain::set_secret(app_data_dir, TOKEN_ACCOUNT, &token)?;
+    Ok(token)
+}
+
+/// Token currently accepted, shared with the event bridge so a rotated token
+/// applies to the next connection
+pub(crate) fn current_token(app: &AppHandle) -> Result<String, AppError> {
+    let api = app.state::<ControlApi>();
+    let mut current = api.token.lock();
+    if let Some(token) = current.as_ref() {
+        return Ok(token.clone());
+    }
+    let token = load_or_create_token(&app.path().app_data_dir()?)?;
+    *current = Some(token.clone());
+    Ok(token)
+}
+
+// ============================================================
+// Routes
+// ============================================================
+
+/// Service to restart
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum RestartTarget {
+    #[default]
+    All,
+    Backend,
+    Database,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct RestartRequest {
+    target: RestartTarget,
+}
+
+/// A note captured from a script
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub(crate) struct CaptureRequest {
+    content: String,
+    title: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+    folder: Option<String>,
+}
+
+impl CaptureRequest {
+    /// Capture of plain text, titled from its first line
+    pub(crate) fn text(content: impl Into<String>) -> Self {
+        Self {
+            content: content.into(),
+            title: None,
+            tags: Vec::new(),
+            folder: None,
+        }
+    }
+}
+
+/// A search over note titles and content
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+struct SearchRequest {
+    query: String,
+    limit: Option<u32>,
+}
+
+/// A reminder registered by a script or the backend
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+struct ReminderRequest {
+    note_id: String,
+    at: String,
+    #[serde(default)]
+    message: String,
+}
+
+/// Backend path for a search, with the limit clamped
+fn search_path(request: &SearchRequest) -> String {
+    let limit = request
+        .limit
+        .unwrap_or(10)
+        .clamp(1, MAX_SEARCH_RESULTS)
+        .to_string();
+    let mut url = reqwest::Url::parse("http://localhost/notes/paged").expect("static URL");
+    url.query_pairs_mut()
+        .append_pair("search", request.query.trim())
+        .append_pair("page", "1")
+        .append_pair("pageSize", &limit);
+    format!("{}?{}", url.path(), url.query().unwrap_or_default())
+}
+
+#[derive(Debug, Serialize)]
+struct StatusResponse {
+    version: String,
+    api_url: String,
+    backend_ready: bool,
+    services: ServiceState,
+}
+
+/// Title for a capture: the given one, else the content's first line
+fn capture_title(request: &CaptureRequest) -> String {
+    let given = request.title.as_deref().map(str::trim).unwrap_or_default();
+    if !given.is_empty() {
+        return given.to_string();
+    }
+    let first_line = request
+        .content
+        .lines()
+        .map(|line| line.trim().trim_start_matches('#').trim())
+        .find(|line| !line.is_empty())
+        .unwrap_or_default();
+    if first_line.is_empty() {
+        format!(
+            "Quick capture {}",
+            chrono::Local::now().format("%Y-%m-%d %H:%M")
+        )
+    } else {
+        first_line.chars().take(80).collect()
+    }
+}
+
+/// Create a note from a capture
+///
+/// Queued while the backend is down, like edits from the webview.
+pub(crate) async fn capture_note(
+    app: &AppHandle,
+    capture: &CaptureRequest,
+) -> Result<serde_json::Value, AppError> {
+    if capture.content.trim().is_empty() {
+        return Err(AppError::InvalidInput("Content is empty".to_string()));
+    }
+    let body = serde_json::json!({
+        "title": capture_title(capture),
+        "content": capture.content,
+        "tags": capture.tags,
+        "folder": capture.folder,
+    });
+    crate::write_queue::send_or_queue(app, "POST", "/notes", Some(body), None).await
+}
+
+fn parse_body<T: serde::de::DeserializeOwned + Default>(body: &[u8]) -> Result<T, AppError> {
+    if body.iter().all(u8::is_ascii_whitespace) {
+        return Ok(T::default());
+    }
+    Ok(serde_json::from_slice(body)?)
+}
+
+async fn route(
+    app: &AppHandle,
+    request: &HttpRequest,
+) -> Result<(u16, serde_json::Value), AppError> {
+    let path = request.path.split('?').next().unwrap_or_default();
+    match (request.method.as_str(), path) {
+        ("GET", "/v1/status") => {
+            let state = app.state::<AppState>();
+            let port = *state.backend_port.read();
+            let status = StatusResponse {
+                version: app.package_info().version.to_string(),
+                api_url: crate::api_endpoint::api_url(port),
+                backend_ready: *state.is_backend_ready.read(),
+                services: app.state::<ServiceManager>().state(),
+            };
+            Ok((200, serde_json::to_value(status)?))
+        }
+        ("POST", "/v1/services/restart") => {
+            let restart: RestartRequest = parse_body(&request.body)?;
+            let command = match restart.target {
+                // Restarting the database restarts the backend after it
+                RestartTarget::All | RestartTarget::Database => ServiceCommand::RestartDatabase,
+                RestartTarget::Backend => ServiceCommand::RestartBackend,
+            };
+            app.state::<ServiceManager>().send(command).await?;
+            Ok((
+                200,
+                serde_json::to_value(app.state::<ServiceManager>().state())?,
+            ))
+        }
+        ("POST", "/v1/backup") => {
+            let id = crate::jobs::spawn(app, JobRequest::Backup)?;
+            Ok((202, serde_json::json!({ "job_id": id })))
+        }
+        ("POST", "/v1/capture") => {
+            crate::app_lock::ensure_unlocked(app)?;
+            let capture: CaptureRequest = serde_json::from_slice(&request.body)?;
+            Ok((201, capture_note(app, &capture).await?))
+        }
+        ("POST", "/v1/notes/search") => {
+            crate::app_lock::ensure_unlocked(app)?;
+            let search: SearchRequest = serde_json::from_slice(&request.body)?;
+            if search.query.trim().is_empty() {
+                return Err(AppError::InvalidInput("Query is empty".to_string()));
+            }
+            let response =
+                crate::proxy::send_backend_request(app, "GET", &search_path(&search), None, None)
+                    .await?;
+            Ok((200, response))
+        }
+        ("POST", "/v1/reminders") => {
+            let request: ReminderRequest = serde_json::from_slice(&request.body)?;
+            let reminder =
+                crate::reminders::schedule(app, &request.note_id, &request.at, &request.message)?;
+            Ok((201, serde_json::to_value(reminder)?))
+        }
+        (
+            _,
+            "/v1/status"
+            | "/v1/services/restart"
+            | "/v1/backup"
+            | "/v1/capture"
+            | "/v1/notes/search"
+            | "/v1/reminders",
+        ) => Err(AppError::InvalidInput(format!(
+            "{} is not allowed on {}",
+            request.method, path
+        ))),
+        _ => Err(AppError::NotFound(format!("No route for {}", path))),
+    }
+}
+
+async fn handle_connection(app: AppHandle, mut stream: TcpStream) -> std::io::Result<()> {
+    let request = match tokio::time::timeout(IO_TIMEOUT, read_request(&mut stream)).await {
+        Ok(Ok(request)) => request,
+        Ok(Err(e)) => {
+            return write_response(&mut stream, http_status(&e), &serde_json::to_value(&e)?).await
+        }
+        Err(_) => return Ok(()),
+    };
+    let token = app
+        .state::<ControlApi>()
+        .token
+        .lock()
+        .clone()
+        .unwrap_or_default();
+    let result = match authorize(&request, &token) {
+        Ok(()) => route(&app, &request).await,
+        Err(e) => Err(e),
+    };
+    let (status, body) = match result {
+        Ok((code, body)) => ((code, reason(code)), body),
+        Err(e) => {
+            if !matches!(e, AppError::Permission(_)) {
+                tracing::warn!(
+                    "Control API {} {} failed: {}",
+                    request.method,
+                    request.path,
+                    e
+                );
+            }
+            (http_status(&e), serde_json::to_value(&e)?)
+        }
+    };
+    tracing::debug!(
+        "Control API {} {} -> {}",
+        request.method,
+        request.path,
+        status.0
+    );
+    tokio::time::timeout(IO_TIMEOUT, write_response(&mut stream, status, &body))
+        .await
+        .unwrap_or(Ok(()))
+}
+
+/// Start or stop the listener to match settings
+///
+/// The port is bound before this returns, so a port that is already taken
+/// is reported to the caller. A listener already open on the same port is
+/// kept, since the aborted one may not have released it yet.
+pub fn apply_settings(app: &AppHandle, settings: &ControlApiSettings) -> Result<(), AppError> {
+    let api = app.state::<ControlApi>();
+    let mut current = api.listener.lock();
+    if !settings.enabled {
+        if let Some((_, task)) = current.take() {
+            task.abort();
+        }
+        return Ok(());
+    }
+
+    let app_data_dir = app.path().app_data_dir()?;
+    *api.token.lock() = Some(load_or_create_token(&app_data_dir)?);
+    if current
+        .as_ref()
+        .is_some_and(|(port, task)| *port == settings.port && !task.inner().is_finished())
+    {
+        return Ok(());
+    }
+    if let Some((_, task)) = current.take() {
+        task.abort();
+    }
+
+    let port = settings.port;
+    let listener = std::net::TcpListener::bind(("127.0.0.1", port))
+        .and_then(|listener| listener.set_nonblocking(true).map(|()| listener))
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::AddrInUse => {
+                AppError::Conflict(format!("Port {} is already in use", port))
+            }
+            _ => AppError::Io(format!(
+                "Control API could not listen on port {}: {}",
+                port, e
+            )),
+        })?;
+    tracing::info!("Control API listening on 127.0.0.1:{}", port);
+
+    let app_for_task = app.clone();
+    let task = tauri::async_runtime::spawn(async move {
+        let listener = match TcpListener::from_std(listener) {
+            Ok(listener) => listener,
+            Err(e) => {
+                tracing::warn!("Control API could not listen on port {}: {}", port, e);
+                return;
+            }
+        };
+        while let Ok((stream, _)) = listener.accept().await {
+            let app = app_for_task.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = handle_connection(app, stream).await {
+                    tracing::debug!("Control API connection failed: {}", e);
+                }
+            });
+        }
+    });
+    *current = Some((port, task));
+    Ok(())
+}
+
+/// Load persisted settings and start listening if enabled
+pub fn start(app: &AppHandle) {
+    app.manage(ControlApi::default());
+    if let Ok(app_data_dir) = app.path().app_data_dir() {
+        if let Err(e) = apply_settings(app, &ControlApiSettings::load(&app_data_dir)) {
+            tracing::warn!("Control API not started: {}", e);
+        }
+    }
+}
+
+// ============================================================
+// Commands
+// ============================================================
+
+fn info(app_data_dir: &Path, settings: ControlApiSettings) -> Result<ControlApiInfo, AppError> {
+    Ok(ControlApiInfo {
+        url: format!("http://127.0.0.1:{}/v1", settings.port),
+        token: load_or_create_token(app_data_dir)?,
+        enabled: settings.enabled,
+        port: settings.port,
+    })
+}
+
+/// Get the control API settings and token
+#[tauri::command]
+pub async fn get_control_api(app: AppHandle) -> Result<ControlApiInfo, AppError> {
+    let app_data_dir = app.path().app_data_dir()?;
+    info(&app_data_dir, ControlApiSettings::load(&app_data_dir))
+}
+
+/// Enable, disable or move the control API
+#[tauri::command]
+pub async fn set_control_api_settings(
+    app: AppHandle,
+    settings: ControlApiSettings,
+) -> Result<ControlApiInfo, AppError> {
+    settings.validate()?;
+    let app_data_dir = app.path().app_data_dir()?;
+    if let Err(e) = apply_settings(&app, &settings) {
+        // Go back to what was running before
+        let _ = apply_settings(&app, &ControlApiSettings::load(&app_data_dir));
+        return Err(e);
+    }
+    settings.save(&app_data_dir)?;
+    info(&app_data_dir, settings)
+}
+
+/// Replace the bearer token; scripts using the old one stop working
+#[tauri::command]
+pub async fn rotate_control_api_token(app: AppHandle) -> Result<ControlApiInfo, AppError> {
+    let app_data_dir = app.path().app_data_dir()?;
+    let token = new_token()?;
+    keychain::set_secret(&app_data_dir, TOKEN_ACCOUNT, &token)?;
+    *app.state::<ControlApi>().token.lock() = Some(token);
+    info(&app_data_dir, ControlApiSettings::load(&app_data_dir))
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn request(headers: &[(&str, &str)]) -> HttpRequest {
+        HttpRequest {
+            method: "GET".to_string(),
+            path: "/v1/status".to_string(),
+            headers: headers
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            body: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_parse_head() {
+        let request = parse_head(
+            "post /v1/capture HTTP/1.1\r\nHost: 127.0.0.1:47822\r\nContent-Length: 12\r\n\r\n",
+        )
+        .unwrap();
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.path, "/v1/capture");
+        assert_eq!(request.headers.len(), 2);
+        assert_eq!(request.header("content-length"), Some("12"));
+
+        assert!(parse_head("GET /\r\n\r\n").is_err());
+        assert!(parse_head("GET / SPDY/3\r\n\r\n").is_err());
+    }
+
+    #[test]
+    fn test_authorize() {
+        let token = "secret-token";
+        let ok = request(&[
+            ("Host", "localhost:47822"),
+            ("Authorization", "Bearer secret-token"),
+        ]);
+        assert!(authorize(&ok, token).is_ok());
+
+        let cases = [
+            request(&[("Host", "localhost:47822")]),
+            request(&[("Host", "localhost"), ("Authorization", "Bearer wrong")]),
+            request(&[
+                ("Host", "evil.example:47822"),
+                ("Authorization", "Bearer secret-token"),
+            ]),
+            request(&[
+                ("Host", "127.0.0.1"),
+                ("Origin", "https://evil.example"),
+                ("Authorization", "Bearer secret-token"),
+            ]),
+        ];
+        for case in cases {
+            assert_eq!(
+                authorize(&case, token).err().map(|e| e.kind()),
+                Some("permission")
+            );
+        }
+        // An unset token never matches an empty bearer
+        let empty = request(&[("Host", "localhost"), ("Authorization", "Bearer ")]);
+        assert!(authorize(&empty, "").is_err());
+    }
+
+    #[test]
+    fn test_is_local_host() {
+        assert!(is_local_host("127.0.0.1:47822"));
+        assert!(is_local_host("localhost"));
+        assert!(is_local_host("[::1]:47822"));
+        assert!(!is_local_host("localhost.evil.example"));
+        assert!(!is_local_host("192.168.1.5:47822"));
+    }
+
+    #[test]
+    fn test_capture_title() {
+        let capture = |title: Option<&str>, content: &str| CaptureRequest {
+            content: content.to_string(),
+            title: title.map(str::to_string),
+            tags: Vec::new(),
+            folder: None,
+        };
+        assert_eq!(capture_title(&capture(Some(" Idea "), "body")), "Idea");
+        assert_eq!(
+            capture_title(&capture(None, "\n# Groceries\nmilk")),
+            "Groceries"
+        );
+        assert!(capture_title(&capture(None, "  \n")).starts_with("Quick capture "));
+        assert_eq!(capture_title(&capture(None, &"x".repeat(200))).len(), 80);
+    }
+
+    #[test]
+    fn test_search_path() {
+        let search = |query: &str, limit| SearchRequest {
+            query: query.to_string(),
+            limit,
+        };
+        assert_eq!(
+            search_path(&search(" rust & tauri ", None)),
+            "/notes/paged?search=rust+%26+tauri&page=1&pageSize=10"
+        );
+        assert!(search_path(&search("x", Some(500))).ends_with("pageSize=50"));
+        assert!(search_path(&search("x", Some(0))).ends_with("pageSize=1"));
+    }
+
+    #[test]
+    fn test_restart_body_defaults_to_all() {
+        let parsed: RestartRequest = parse_body(b"").unwrap();
+        assert_eq!(parsed.target, RestartTarget::All);
+        let parsed: RestartRequest = parse_body(br#"{"target":"backend"}"#).unwrap();
+        assert_eq!(parsed.target, RestartTarget::Backend);
+        assert!(parse_body::<RestartRequest>(br#"{"target":"kernel"}"#).is_err());
+    }
+
+    #[test]
+    fn test_settings_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        assert_eq!(
+            ControlApiSettings::load(temp_dir.path()),
+            ControlApiSettings::default()
+        );
+        let settings = ControlApiSettings {
+            enabled: true,
+            port: 50000,
+        };
+        settings.save(temp_dir.path()).unwrap();
+        assert_eq!(ControlApiSettings::load(temp_dir.path()), settings);
+        assert!(ControlApiSettings {
+            enabled: true,
+            port: 80
+        }
+        .validate()
+        .is_err());
+    }
+}
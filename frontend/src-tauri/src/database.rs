@@ -1,12 +1,386 @@
-use std::io::{BufRead, BufReader};
-use std::path::PathBuf;
-use std::process::{Child, Command, Stdio};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
 use std::sync::Mutex;
 use std::time::Duration;
+use tokio::process::Child;
 
+use crate::config::PostgresMemory;
 use crate::port_utils::{find_available_port, validate_port, PortStatus};
 use crate::startup::{ExponentialBackoff, StartupConfig, StartupTimer};
 
+/// Folder in the resource dir holding bundled PostgreSQL builds, either one
+/// per platform (`postgresql/macos-aarch64/bin`) or a single one
+/// (`postgresql/bin`)
+const BUNDLED_DIR: &str = "postgresql";
+
+/// System PostgreSQL 18 installations, tried in order
+const SYSTEM_BIN_DIRS: &[&str] = &[
+    // Homebrew (Apple Silicon)
+    "/opt/homebrew/opt/postgresql@18/bin",
+    // Homebrew (Intel)
+    "/usr/local/opt/postgresql@18/bin",
+];
+
+/// Folders holding one installation per major version, with the prefix
+/// before the version in each installation's name
+const VERSIONED_INSTALL_DIRS: &[(&str, &str)] = &[
+    // Debian and Ubuntu packages (`/usr/lib/postgresql/18/bin`)
+    ("/usr/lib/postgresql", ""),
+    // PGDG packages for RHEL and Fedora (`/usr/pgsql-18/bin`)
+    ("/usr", "pgsql-"),
+];
+
+/// Homebrew folders holding one formula per major version
+/// (`/opt/homebrew/opt/postgresql@16/bin`), searched for the binaries of an
+/// older data directory
+const HOMEBREW_OPT_DIRS: &[(&str, &str)] = &[
+    ("/opt/homebrew/opt", "postgresql@"),
+    ("/usr/local/opt", "postgresql@"),
+];
+
+/// Oldest major version the app's database works with
+pub const MIN_POSTGRES_VERSION: u32 = 18;
+
+/// Database superuser created by initdb
+const SUPERUSER: &str = "secondbrain";
+
+/// pg_hba.conf lines letting local connections take base backups
+const REPLICATION_HBA: &str = "\
+host    replication     all             127.0.0.1/32            trust
+host    replication     all             ::1/128                 trust
+";
+
+/// Longest a segment stays unarchived on a quiet database (seconds)
+const ARCHIVE_TIMEOUT_SECS: u32 = 300;
+
+/// Longest replaying archived WAL may take during a point-in-time restore
+const RECOVERY_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Where the PostgreSQL binaries in use come from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PostgresSource {
+    /// Shipped in the app's resource directory
+    Bundled,
+    /// Installed on the system, e.g. with Homebrew
+    System,
+}
+
+/// Bundled build folder for this platform, e.g. `macos-aarch64`
+fn bundled_platform() -> String {
+    format!("{}-{}", std::env::consts::OS, std::env::consts::ARCH)
+}
+
+fn has_server_binaries(bin_dir: &Path) -> bool {
+    bin_dir.join("initdb").exists() && bin_dir.join("postgres").exists()
+}
+
+/// Whether `postgres` in `bin_dir` runs here, which a build for another
+/// architecture or with missing libraries doesn't
+fn runs_here(bin_dir: &Path) -> bool {
+    Command::new(bin_dir.join("postgres"))
+        .arg("--version")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+/// Major version in `postgres --version` output (`postgres (PostgreSQL)
+/// 18.1 (Debian 18.1-1)`) or a version folder name (`18`, `9.6`)
+pub fn parse_major_version(version: &str) -> Option<u32> {
+    version
+        .split_whitespace()
+        .find(|word| word.starts_with(|c: char| c.is_ascii_digit()))?
+        .split(|c: char| !c.is_ascii_digit())
+        .next()?
+        .parse()
+        .ok()
+}
+
+/// Major version of the `postgres` binary in `bin_dir`
+fn binary_major_version(bin_dir: &Path) -> Option<u32> {
+    let output = Command::new(bin_dir.join("postgres"))
+        .arg("--version")
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    parse_major_version(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Installations with server binaries under `parent` whose folder is named
+/// `<prefix><version>`
+fn versioned_bin_dirs(parent: &Path, prefix: &str) -> Vec<(u32, PathBuf)> {
+    let Ok(entries) = std::fs::read_dir(parent) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name();
+            let version = parse_major_version(name.to_str()?.strip_prefix(prefix)?)?;
+            Some((version, entry.path().join("bin")))
+        })
+        .filter(|(_, bin_dir)| has_server_binaries(bin_dir))
+        .collect()
+}
+
+/// A PostgreSQL installation at `$PGHOME` or in the versioned install
+/// folders, whichever is new enough; `$PGHOME` wins when it is, otherwise
+/// the newest version is used
+fn find_versioned_bin_dir(
+    pg_home: Option<&Path>,
+    install_dirs: &[(PathBuf, &str)],
+) -> Option<(u32, PathBuf)> {
+    if let Some(bin_dir) = pg_home
+        .map(|home| home.join("bin"))
+        .filter(|bin_dir| has_server_binaries(bin_dir))
+    {
+        match binary_major_version(&bin_dir) {
+            Some(version) if version >= MIN_POSTGRES_VERSION => return Some((version, bin_dir)),
+            version => tracing::warn!(
+                "Ignoring PGHOME PostgreSQL at {:?} (version {:?}, need {}+)",
+                bin_dir,
+                version,
+                MIN_POSTGRES_VERSION
+            ),
+        }
+    }
+    install_dirs
+        .iter()
+        .flat_map(|(parent, prefix)| versioned_bin_dirs(parent, prefix))
+        .filter(|(version, _)| *version >= MIN_POSTGRES_VERSION)
+        .max_by_key(|(version, _)| *version)
+}
+
+/// Folders where older versions may be installed, for upgrades
+fn old_install_dirs() -> Vec<(PathBuf, &'static str)> {
+    HOMEBREW_OPT_DIRS
+        .iter()
+        .chain(VERSIONED_INSTALL_DIRS)
+        .map(|(parent, prefix)| (PathBuf::from(parent), *prefix))
+        .collect()
+}
+
+/// An installation of exactly `version`, at `$PGHOME` or in the versioned
+/// install folders
+fn find_bin_dir_for_version(
+    version: u32,
+    pg_home: Option<&Path>,
+    install_dirs: &[(PathBuf, &str)],
+) -> Option<PathBuf> {
+    pg_home
+        .map(|home| home.join("bin"))
+        .filter(|bin_dir| {
+            has_server_binaries(bin_dir) && binary_major_version(bin_dir) == Some(version)
+        })
+        .or_else(|| {
+            install_dirs
+                .iter()
+                .flat_map(|(parent, prefix)| versioned_bin_dirs(parent, prefix))
+                .find(|(found, _)| *found == version)
+                .map(|(_, bin_dir)| bin_dir)
+        })
+}
+
+/// Quote a value as a postgresql.conf string
+fn conf_string(value: &str) -> String {
+    format!("'{}'", value.replace('\\', "\\\\").replace('\'', "''"))
+}
+
+/// postgresql.conf settings archiving completed WAL segments into
+/// `archive_dir`, or turning archiving off with None
+fn archive_conf_values(archive_dir: Option<&Path>) -> Vec<(&'static str, String)> {
+    let Some(dir) = archive_dir else {
+        return vec![
+            ("archive_mode", "off".to_string()),
+            ("archive_command", "''".to_string()),
+        ];
+    };
+    // PostgreSQL expands %p to the segment's path and %f to its name
+    let target = dir.join("%f").to_string_lossy().to_string();
+    // Never overwrite an archived segment
+    let command = if cfg!(windows) {
+        format!("if not exist \"{0}\" copy \"%p\" \"{0}\"", target)
+    } else {
+        format!("test ! -f \"{0}\" && cp \"%p\" \"{0}\"", target)
+    };
+    vec![
+        ("wal_level", "replica".to_string()),
+        ("archive_mode", "on".to_string()),
+        ("archive_command", conf_string(&command)),
+        ("archive_timeout", ARCHIVE_TIMEOUT_SECS.to_string()),
+    ]
+}
+
+/// postgresql.conf settings replaying WAL from `restore_dir` up to `target`
+/// (a timestamp PostgreSQL understands) and then opening for writes; None
+/// clears them once recovery is done
+fn recovery_conf_values(recovery: Option<(&Path, &str)>) -> Vec<(&'static str, String)> {
+    let Some((restore_dir, target)) = recovery else {
+        return vec![
+            ("restore_command", "''".to_string()),
+            ("recovery_target_time", "''".to_string()),
+            ("recovery_target_action", "'pause'".to_string()),
+        ];
+    };
+    let source = restore_dir.join("%f").to_string_lossy().to_string();
+    let command = if cfg!(windows) {
+        format!("copy \"{}\" \"%p\"", source)
+    } else {
+        format!("cp \"{}\" \"%p\"", source)
+    };
+    vec![
+        ("restore_command", conf_string(&command)),
+        ("recovery_target_time", conf_string(target)),
+        ("recovery_target_action", "'promote'".to_string()),
+    ]
+}
+
+/// Copy a directory tree
+fn copy_dir(from: &Path, to: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(to)?;
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            std::fs::copy(entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
+
+/// postgresql.conf contents with `values` set, replacing existing lines and
+/// appending missing ones
+fn set_conf_values(content: &str, values: &[(&str, String)]) -> String {
+    let mut remaining: Vec<&(&str, String)> = values.iter().collect();
+    let mut lines: Vec<String> = content
+        .lines()
+        .map(|line| {
+            let key = line.split('=').next().unwrap_or_default().trim();
+            match remaining.iter().position(|(name, _)| *name == key) {
+                Some(index) => {
+                    let (name, value) = remaining.remove(index);
+                    format!("{} = {}", name, value)
+                }
+                None => line.to_string(),
+            }
+        })
+        .collect();
+    lines.extend(
+        remaining
+            .into_iter()
+            .map(|(name, value)| format!("{} = {}", name, value)),
+    );
+    let mut updated = lines.join("\n");
+    updated.push('\n');
+    updated
+}
+
+/// Run a PostgreSQL tool, turning a failure into an error with its output
+fn run_tool(command: &mut Command, name: &str) -> Result<(), String> {
+    let output = command
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| format!("Failed to run {}: {}", name, e))?;
+    if output.status.success() {
+        return Ok(());
+    }
+    Err(format!(
+        "{} failed.\nstdout: {}\nstderr: {}",
+        name,
+        String::from_utf8_lossy(&output.stdout).trim(),
+        String::from_utf8_lossy(&output.stderr).trim()
+    ))
+}
+
+/// Arguments connecting a client tool to the server on `port`
+fn connection_args(port: u16) -> [String; 6] {
+    [
+        "-h".to_string(),
+        "localhost".to_string(),
+        "-p".to_string(),
+        port.to_string(),
+        "-U".to_string(),
+        SUPERUSER.to_string(),
+    ]
+}
+
+/// Start a server with `pg_ctl`, waiting until it accepts connections
+fn pg_ctl_start(bin_dir: &Path, data_dir: &Path, port: u16, log: &Path) -> Result<(), String> {
+    run_tool(
+        Command::new(bin_dir.join("pg_ctl"))
+            .arg("start")
+            .arg("-D")
+            .arg(data_dir)
+            .arg("-o")
+            .arg(format!("-p {} -c listen_addresses=localhost", port))
+            .arg("-l")
+            .arg(log)
+            .arg("-w")
+            .env("LC_ALL", "C")
+            .env("LANG", "C"),
+        "pg_ctl start",
+    )
+}
+
+fn pg_ctl_stop(bin_dir: &Path, data_dir: &Path) -> Result<(), String> {
+    run_tool(
+        Command::new(bin_dir.join("pg_ctl"))
+            .arg("stop")
+            .arg("-D")
+            .arg(data_dir)
+            .arg("-m")
+            .arg("fast")
+            .arg("-w"),
+        "pg_ctl stop",
+    )
+}
+
+/// A data directory from an older major version than the binaries, which
+/// they can't open until it's upgraded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PendingUpgrade {
+    pub from_version: u32,
+    pub to_version: u32,
+}
+
+/// Stage of a major-version upgrade
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UpgradeStep {
+    FindingOldBinaries,
+    CreatingDataDir,
+    Upgrading,
+    /// pg_upgrade failed and the data is being dumped and restored instead
+    DumpingAndRestoring,
+    Finishing,
+}
+
+impl UpgradeStep {
+    /// Rough progress through the upgrade when this step starts, in percent
+    pub fn percent(self) -> u8 {
+        match self {
+            UpgradeStep::FindingOldBinaries => 0,
+            UpgradeStep::CreatingDataDir => 10,
+            UpgradeStep::Upgrading => 25,
+            UpgradeStep::DumpingAndRestoring => 50,
+            UpgradeStep::Finishing => 90,
+        }
+    }
+}
+
+/// How the data was carried over to the new version
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UpgradeMethod {
+    PgUpgrade,
+    DumpRestore,
+}
+
 /// Error types for PostgreSQL operations
 #[derive(Debug)]
 pub enum PostgresError {
@@ -17,6 +391,7 @@ pub enum PostgresError {
     PortConflict { port: u16, message: String },
     Timeout(String),
     ConfigError(String),
+    UpgradeFailed(String),
 }
 
 impl std::fmt::Display for PostgresError {
@@ -26,7 +401,7 @@ impl std::fmt::Display for PostgresError {
                 write!(f, "Database not initialized. Call init_database() first.")
             }
             PostgresError::BinaryNotFound(path) => {
-                write!(f, "PostgreSQL binary not found at {}. Please install PostgreSQL 18: brew install postgresql@18", path)
+                write!(f, "PostgreSQL binary not found at {}. This build has no bundled PostgreSQL for {}; please install PostgreSQL 18: brew install postgresql@18", path, bundled_platform())
             }
             PostgresError::InitFailed(msg) => write!(f, "Database initialization failed: {}", msg),
             PostgresError::StartFailed(msg) => write!(f, "Failed to start PostgreSQL: {}", msg),
@@ -35,6 +410,7 @@ impl std::fmt::Display for PostgresError {
             }
             PostgresError::Timeout(msg) => write!(f, "Timeout: {}", msg),
             PostgresError::ConfigError(msg) => write!(f, "Configuration error: {}", msg),
+            PostgresError::UpgradeFailed(msg) => write!(f, "Database upgrade failed: {}", msg),
         }
     }
 }
@@ -52,9 +428,12 @@ pub struct PostgresManager {
     process: Mutex<Option<Child>>,
     data_dir: PathBuf,
     bin_dir: PathBuf,
+    bin_source: PostgresSource,
     port: Mutex<u16>,
     initialized: Mutex<bool>,
     startup_config: StartupConfig,
+    /// Retries the last successful start needed
+    retries: Mutex<u32>,
 }
 
 impl PostgresManager {
@@ -72,16 +451,22 @@ impl PostgresManager {
         startup_config: StartupConfig,
     ) -> Self {
         // Try bundled PostgreSQL first, then fall back to system installations
-        let bin_dir = Self::find_postgres_bin_dir(&resource_dir);
+        let (bin_dir, bin_source) = Self::find_postgres_bin_dir(&resource_dir);
 
-        log::info!("Using PostgreSQL bin directory: {:?}", bin_dir);
+        tracing::info!(
+            "Using {:?} PostgreSQL bin directory: {:?}",
+            bin_source,
+            bin_dir
+        );
 
         Self {
             process: Mutex::new(None),
             data_dir: app_data_dir.join("postgresql"),
             bin_dir,
+            bin_source,
             port: Mutex::new(port),
             initialized: Mutex::new(false),
+            retries: Mutex::new(0),
             startup_config,
         }
     }
@@ -92,7 +477,7 @@ impl PostgresManager {
 
         match validate_port(current_port) {
             PortStatus::Available => {
-                log::info!("Port {} is available for PostgreSQL", current_port);
+                tracing::info!("Port {} is available for PostgreSQL", current_port);
                 Ok(current_port)
             }
             PortStatus::InUse { process } => {
@@ -100,7 +485,7 @@ impl PostgresManager {
                     .map(|p| format!(" (PID: {}, name: {})", p.pid, p.name.unwrap_or_default()))
                     .unwrap_or_default();
 
-                log::warn!(
+                tracing::warn!(
                     "Port {} is in use{}, searching for alternative...",
                     current_port,
                     process_info
@@ -108,7 +493,7 @@ impl PostgresManager {
 
                 // Try to find an alternative port
                 if let Some(new_port) = find_available_port(current_port + 1, 10) {
-                    log::info!("Found alternative port: {}", new_port);
+                    tracing::info!("Found alternative port: {}", new_port);
                     *self.port.lock().unwrap() = new_port;
                     Ok(new_port)
                 } else {
@@ -143,46 +528,93 @@ impl PostgresManager {
         *self.port.lock().unwrap() = port;
     }
 
-    /// Find PostgreSQL 18 bin directory from system installations
-    /// Requires PostgreSQL 18 with pgvector extension
-    fn find_postgres_bin_dir(_resource_dir: &PathBuf) -> PathBuf {
-        let possible_paths = vec![
-            // Homebrew PostgreSQL 18 (Apple Silicon)
-            PathBuf::from("/opt/homebrew/opt/postgresql@18/bin"),
-            // Homebrew PostgreSQL 18 (Intel)
-            PathBuf::from("/usr/local/opt/postgresql@18/bin"),
-        ];
-
-        for path in &possible_paths {
-            let initdb = path.join("initdb");
-            let postgres = path.join("postgres");
+    /// Find the PostgreSQL 18 bin directory, preferring binaries bundled
+    /// for this platform over system installations
+    /// Requires PostgreSQL 18 or newer with pgvector extension
+    fn find_postgres_bin_dir(resource_dir: &Path) -> (PathBuf, PostgresSource) {
+        let bundled = resource_dir.join(BUNDLED_DIR);
+        for path in [
+            bundled.join(bundled_platform()).join("bin"),
+            bundled.join("bin"),
+        ] {
+            if !has_server_binaries(&path) {
+                continue;
+            }
+            if runs_here(&path) {
+                tracing::info!("Found bundled PostgreSQL at {:?}", path);
+                return (path, PostgresSource::Bundled);
+            }
+            tracing::warn!(
+                "Bundled PostgreSQL at {:?} doesn't run on {}, skipping",
+                path,
+                bundled_platform()
+            );
+        }
 
-            if initdb.exists() && postgres.exists() {
-                log::info!("Found PostgreSQL 18 at {:?}", path);
-                return path.clone();
+        for path in SYSTEM_BIN_DIRS.iter().map(PathBuf::from) {
+            if has_server_binaries(&path) {
+                tracing::info!("Found PostgreSQL 18 at {:?}", path);
+                return (path, PostgresSource::System);
             }
         }
 
-        // Return the first path as fallback (will fail later with helpful error)
-        log::warn!("PostgreSQL 18 not found. Please install: brew install postgresql@18 pgvector");
-        possible_paths
-            .first()
-            .cloned()
-            .unwrap_or_else(|| PathBuf::from("/opt/homebrew/opt/postgresql@18/bin"))
+        let pg_home = std::env::var_os("PGHOME").map(PathBuf::from);
+        let install_dirs: Vec<(PathBuf, &str)> = VERSIONED_INSTALL_DIRS
+            .iter()
+            .map(|(parent, prefix)| (PathBuf::from(parent), *prefix))
+            .collect();
+        if let Some((version, path)) = find_versioned_bin_dir(pg_home.as_deref(), &install_dirs) {
+            tracing::info!("Found PostgreSQL {} at {:?}", version, path);
+            return (path, PostgresSource::System);
+        }
+
+        // Return the usual path as fallback (will fail later with helpful error)
+        if cfg!(target_os = "linux") {
+            tracing::warn!(
+                "PostgreSQL {}+ not found. Please install postgresql-{} with pgvector, \
+                 or set PGHOME",
+                MIN_POSTGRES_VERSION,
+                MIN_POSTGRES_VERSION
+            );
+            let (parent, _) = VERSIONED_INSTALL_DIRS[0];
+            return (
+                Path::new(parent)
+                    .join(MIN_POSTGRES_VERSION.to_string())
+                    .join("bin"),
+                PostgresSource::System,
+            );
+        }
+        tracing::warn!(
+            "PostgreSQL 18 not found. Please install: brew install postgresql@18 pgvector"
+        );
+        (PathBuf::from(SYSTEM_BIN_DIRS[0]), PostgresSource::System)
     }
 
     /// Initialize the database directory if it doesn't exist
+    #[tracing::instrument(skip(self))]
     pub fn init_database(&self) -> Result<(), String> {
         if self.data_dir.exists() && self.data_dir.join("PG_VERSION").exists() {
-            log::info!("PostgreSQL data directory already exists");
+            tracing::info!("PostgreSQL data directory already exists");
             *self.initialized.lock().unwrap() = true;
             return Ok(());
         }
 
-        log::info!("Initializing PostgreSQL database at {:?}", self.data_dir);
+        tracing::info!("Initializing PostgreSQL database at {:?}", self.data_dir);
+        self.run_initdb(&self.data_dir)?;
+
+        tracing::info!("PostgreSQL database initialized successfully");
+
+        // Configure PostgreSQL for localhost-only connections
+        self.configure_postgresql()?;
+
+        *self.initialized.lock().unwrap() = true;
+        Ok(())
+    }
 
+    /// Create a cluster in `data_dir` with the app's user and locale
+    fn run_initdb(&self, data_dir: &Path) -> Result<(), String> {
         // Create data directory
-        std::fs::create_dir_all(&self.data_dir)
+        std::fs::create_dir_all(data_dir)
             .map_err(|e| format!("Failed to create data directory: {}", e))?;
 
         // Get initdb path
@@ -192,16 +624,16 @@ impl PostgresManager {
             return Err(format!("initdb not found at {:?}. Please install PostgreSQL 18: brew install postgresql@18", initdb_path));
         }
 
-        log::info!("Running initdb from {:?}", initdb_path);
+        tracing::info!("Running initdb from {:?}", initdb_path);
 
         // Initialize PostgreSQL database
         // Use C.UTF-8 locale to support Unicode characters (emojis, etc.)
         // while maintaining C collation for performance
         let output = Command::new(&initdb_path)
             .arg("-D")
-            .arg(&self.data_dir)
+            .arg(data_dir)
             .arg("-U")
-            .arg("secondbrain")
+            .arg(SUPERUSER)
             .arg("--encoding=UTF8")
             .arg("--locale=C")
             .arg("--lc-ctype=C.UTF-8")
@@ -219,13 +651,6 @@ impl PostgresManager {
                 stdout, stderr
             ));
         }
-
-        log::info!("PostgreSQL database initialized successfully");
-
-        // Configure PostgreSQL for localhost-only connections
-        self.configure_postgresql()?;
-
-        *self.initialized.lock().unwrap() = true;
         Ok(())
     }
 
@@ -272,6 +697,7 @@ local   all             all                                     trust
 host    all             all             127.0.0.1/32            trust
 host    all             all             ::1/128                 trust
 "#;
+        let hba_content = format!("{}{}", hba_content, REPLICATION_HBA);
 
         std::fs::write(&hba_file, hba_content)
             .map_err(|e| format!("Failed to write pg_hba.conf: {}", e))?;
@@ -279,6 +705,207 @@ host    all             all             ::1/128                 trust
         Ok(())
     }
 
+    /// Major version the data directory was created with, from PG_VERSION
+    pub fn data_dir_version(&self) -> Option<u32> {
+        std::fs::read_to_string(self.data_dir.join("PG_VERSION"))
+            .ok()
+            .as_deref()
+            .and_then(parse_major_version)
+    }
+
+    /// The upgrade the data directory needs before these binaries can open it
+    pub fn pending_upgrade(&self) -> Result<Option<PendingUpgrade>, PostgresError> {
+        let (Some(from_version), Some(to_version)) =
+            (self.data_dir_version(), binary_major_version(&self.bin_dir))
+        else {
+            return Ok(None);
+        };
+        if from_version > to_version {
+            return Err(PostgresError::UpgradeFailed(format!(
+                "The database was created with PostgreSQL {} but only {} is installed; \
+                 install PostgreSQL {} or newer",
+                from_version, to_version, from_version
+            )));
+        }
+        Ok((from_version < to_version).then_some(PendingUpgrade {
+            from_version,
+            to_version,
+        }))
+    }
+
+    /// Folder next to the data directory used for upgrade files
+    fn work_dir(&self) -> &Path {
+        self.data_dir.parent().unwrap_or(&self.data_dir)
+    }
+
+    /// Upgrade the data directory to the binaries' major version
+    ///
+    /// The new cluster is built next to the old one with pg_upgrade, or by
+    /// dumping and restoring if that fails, and swapped in when complete. The
+    /// old data directory is kept as `postgresql-<version>-backup`.
+    #[tracing::instrument(skip(self, on_step))]
+    pub fn upgrade_data_dir(
+        &self,
+        upgrade: PendingUpgrade,
+        on_step: impl Fn(UpgradeStep),
+    ) -> Result<UpgradeMethod, PostgresError> {
+        let backup_dir = self
+            .work_dir()
+            .join(format!("postgresql-{}-backup", upgrade.from_version));
+        if backup_dir.exists() {
+            return Err(PostgresError::UpgradeFailed(format!(
+                "{:?} is in the way of the upgrade; move it elsewhere and restart",
+                backup_dir
+            )));
+        }
+
+        on_step(UpgradeStep::FindingOldBinaries);
+        let pg_home = std::env::var_os("PGHOME").map(PathBuf::from);
+        let old_bin_dir = find_bin_dir_for_version(
+            upgrade.from_version,
+            pg_home.as_deref(),
+            &old_install_dirs(),
+        )
+        .ok_or_else(|| {
+            PostgresError::UpgradeFailed(format!(
+                "The database was created with PostgreSQL {0}, which is needed to \
+                         upgrade it to {1}. Install PostgreSQL {0} (brew install \
+                         postgresql@{0}) and restart",
+                upgrade.from_version, upgrade.to_version
+            ))
+        })?;
+        tracing::info!(
+            "Upgrading with PostgreSQL {} binaries at {:?}",
+            upgrade.from_version,
+            old_bin_dir
+        );
+
+        let new_data_dir = self
+            .work_dir()
+            .join(format!("postgresql-{}-upgrade", upgrade.to_version));
+        on_step(UpgradeStep::CreatingDataDir);
+        self.create_upgrade_data_dir(&new_data_dir)?;
+
+        on_step(UpgradeStep::Upgrading);
+        let method = match self.run_pg_upgrade(&old_bin_dir, &new_data_dir) {
+            Ok(()) => UpgradeMethod::PgUpgrade,
+            Err(e) => {
+                tracing::warn!("pg_upgrade failed, dumping and restoring instead: {}", e);
+                on_step(UpgradeStep::DumpingAndRestoring);
+                self.create_upgrade_data_dir(&new_data_dir)?;
+                self.dump_and_restore(&old_bin_dir, &new_data_dir)
+                    .map_err(PostgresError::UpgradeFailed)?;
+                UpgradeMethod::DumpRestore
+            }
+        };
+
+        on_step(UpgradeStep::Finishing);
+        std::fs::rename(&self.data_dir, &backup_dir).map_err(|e| {
+            PostgresError::UpgradeFailed(format!("Failed to move the old data aside: {}", e))
+        })?;
+        if let Err(e) = std::fs::rename(&new_data_dir, &self.data_dir) {
+            // Put the old data back so the old version can still open it
+            let _ = std::fs::rename(&backup_dir, &self.data_dir);
+            return Err(PostgresError::UpgradeFailed(format!(
+                "Failed to move the upgraded data into place: {}",
+                e
+            )));
+        }
+        self.configure_postgresql()
+            .map_err(PostgresError::UpgradeFailed)?;
+
+        tracing::info!(
+            "Upgraded PostgreSQL data from {} to {} ({:?}); old data kept at {:?}",
+            upgrade.from_version,
+            upgrade.to_version,
+            method,
+            backup_dir
+        );
+        Ok(method)
+    }
+
+    /// Replace `data_dir` with an empty cluster for the upgrade
+    fn create_upgrade_data_dir(&self, data_dir: &Path) -> Result<(), PostgresError> {
+        if data_dir.exists() {
+            std::fs::remove_dir_all(data_dir).map_err(|e| {
+                PostgresError::UpgradeFailed(format!("Failed to clear {:?}: {}", data_dir, e))
+            })?;
+        }
+        self.run_initdb(data_dir)
+            .map_err(PostgresError::UpgradeFailed)
+    }
+
+    /// Upgrade the old cluster into the new one with pg_upgrade, copying the
+    /// files so the old cluster stays usable
+    fn run_pg_upgrade(&self, old_bin_dir: &Path, new_data_dir: &Path) -> Result<(), String> {
+        let pg_upgrade = self.bin_dir.join("pg_upgrade");
+        if !pg_upgrade.exists() {
+            return Err(format!("pg_upgrade not found at {:?}", pg_upgrade));
+        }
+        let port = self.get_port().to_string();
+
+        // pg_upgrade writes its logs and sockets to the working directory
+        run_tool(
+            Command::new(&pg_upgrade)
+                .current_dir(self.work_dir())
+                .arg("--old-bindir")
+                .arg(old_bin_dir)
+                .arg("--new-bindir")
+                .arg(&self.bin_dir)
+                .arg("--old-datadir")
+                .arg(&self.data_dir)
+                .arg("--new-datadir")
+                .arg(new_data_dir)
+                .arg("--username")
+                .arg(SUPERUSER)
+                .arg("--old-port")
+                .arg(&port)
+                .arg("--new-port")
+                .arg(&port)
+                .env("LC_ALL", "C")
+                .env("LANG", "C"),
+            "pg_upgrade",
+        )
+    }
+
+    /// Copy the old cluster into the new one through `pg_dumpall`, for
+    /// clusters pg_upgrade can't handle
+    fn dump_and_restore(&self, old_bin_dir: &Path, new_data_dir: &Path) -> Result<(), String> {
+        let port = self.get_port();
+        let dump = self.work_dir().join("postgresql-upgrade.sql");
+        let log = self.work_dir().join("postgresql-upgrade.log");
+
+        // The new pg_dumpall reads older servers
+        pg_ctl_start(old_bin_dir, &self.data_dir, port, &log)?;
+        let dumped = run_tool(
+            Command::new(self.bin_dir.join("pg_dumpall"))
+                .args(connection_args(port))
+                .arg("-f")
+                .arg(&dump),
+            "pg_dumpall",
+        );
+        pg_ctl_stop(old_bin_dir, &self.data_dir)?;
+        dumped?;
+
+        pg_ctl_start(&self.bin_dir, new_data_dir, port, &log)?;
+        // Recreating the superuser initdb made fails harmlessly, so errors
+        // don't stop the restore
+        let restored = run_tool(
+            Command::new(self.bin_dir.join("psql"))
+                .args(connection_args(port))
+                .arg("-d")
+                .arg("postgres")
+                .arg("-X")
+                .arg("-q")
+                .arg("-f")
+                .arg(&dump),
+            "psql",
+        );
+        let stopped = pg_ctl_stop(&self.bin_dir, new_data_dir);
+        let _ = std::fs::remove_file(&dump);
+        restored.and(stopped)
+    }
+
     /// Update postgresql.conf port setting (needed when port changes after init)
     pub fn update_port_config(&self) -> Result<(), String> {
         let conf_file = self.data_dir.join("postgresql.conf");
@@ -307,7 +934,173 @@ host    all             all             ::1/128                 trust
         std::fs::write(&conf_file, updated)
             .map_err(|e| format!("Failed to write postgresql.conf: {}", e))?;
 
-        log::info!("Updated postgresql.conf port to {}", port);
+        tracing::info!("Updated postgresql.conf port to {}", port);
+        Ok(())
+    }
+
+    /// Write the memory settings to postgresql.conf; they apply on the next start
+    pub fn apply_memory_settings(&self, memory: &PostgresMemory) -> Result<(), String> {
+        let conf_file = self.data_dir.join("postgresql.conf");
+        let content = std::fs::read_to_string(&conf_file)
+            .map_err(|e| format!("Failed to read postgresql.conf: {}", e))?;
+
+        let updated = set_conf_values(&content, &memory.conf_values());
+        if updated != content {
+            std::fs::write(&conf_file, updated)
+                .map_err(|e| format!("Failed to write postgresql.conf: {}", e))?;
+            tracing::info!("Updated postgresql.conf memory settings");
+        }
+        Ok(())
+    }
+
+    /// Rewrite postgresql.conf with `values` if any differ
+    fn update_conf(&self, values: &[(&str, String)]) -> Result<bool, String> {
+        let conf_file = self.data_dir.join("postgresql.conf");
+        let content = std::fs::read_to_string(&conf_file)
+            .map_err(|e| format!("Failed to read postgresql.conf: {}", e))?;
+        let updated = set_conf_values(&content, values);
+        if updated == content {
+            return Ok(false);
+        }
+        std::fs::write(&conf_file, updated)
+            .map_err(|e| format!("Failed to write postgresql.conf: {}", e))?;
+        Ok(true)
+    }
+
+    /// Archive WAL into `archive_dir`, or stop archiving with None; applies
+    /// on the next start
+    ///
+    /// Also lets local connections take base backups, which older data
+    /// directories weren't set up for.
+    pub fn apply_wal_archiving(&self, archive_dir: Option<&Path>) -> Result<(), String> {
+        if let Some(dir) = archive_dir {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create WAL archive directory: {}", e))?;
+            let hba_file = self.data_dir.join("pg_hba.conf");
+            let hba = std::fs::read_to_string(&hba_file)
+                .map_err(|e| format!("Failed to read pg_hba.conf: {}", e))?;
+            if !hba.contains("replication") {
+                std::fs::write(&hba_file, format!("{}{}", hba, REPLICATION_HBA))
+                    .map_err(|e| format!("Failed to write pg_hba.conf: {}", e))?;
+            }
+        }
+        if self.update_conf(&archive_conf_values(archive_dir))? {
+            tracing::info!(
+                "WAL archiving {} in postgresql.conf",
+                if archive_dir.is_some() {
+                    "enabled"
+                } else {
+                    "disabled"
+                }
+            );
+        }
+        Ok(())
+    }
+
+    /// Take a base backup of the running server into `dest`, which must not
+    /// exist yet
+    pub fn base_backup(&self, dest: &Path) -> Result<(), String> {
+        let pg_basebackup = self.bin_dir.join("pg_basebackup");
+        if !pg_basebackup.exists() {
+            return Err(format!("pg_basebackup not found at {:?}", pg_basebackup));
+        }
+        let port = *self.port.lock().unwrap();
+        let output = Command::new(&pg_basebackup)
+            .arg("-h")
+            .arg("localhost")
+            .arg("-p")
+            .arg(port.to_string())
+            .arg("-U")
+            .arg(SUPERUSER)
+            .arg("-D")
+            .arg(dest)
+            .arg("-Fp")
+            .arg("-X")
+            .arg("fetch")
+            .arg("-c")
+            .arg("fast")
+            .arg("-w")
+            .output()
+            .map_err(|e| format!("Failed to run pg_basebackup: {}", e))?;
+        if !output.status.success() {
+            let _ = std::fs::remove_dir_all(dest);
+            return Err(format!(
+                "pg_basebackup failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(())
+    }
+
+    /// Replace the data directory with the base backup `base` and replay the
+    /// WAL in `restore_dir` up to `target`
+    ///
+    /// The server must be stopped, and is stopped again once recovery has
+    /// finished. The current data directory is moved to `previous`, and
+    /// moved back if anything fails.
+    pub fn recover_to(
+        &self,
+        base: &Path,
+        restore_dir: &Path,
+        target: &str,
+        previous: &Path,
+    ) -> Result<(), String> {
+        if previous.exists() {
+            std::fs::remove_dir_all(previous)
+                .map_err(|e| format!("Failed to remove {:?}: {}", previous, e))?;
+        }
+        std::fs::rename(&self.data_dir, previous)
+            .map_err(|e| format!("Failed to move the data directory aside: {}", e))?;
+
+        let result = self.replay(base, restore_dir, target);
+        if let Err(ref e) = result {
+            tracing::error!("Point-in-time recovery failed, putting data back: {}", e);
+            let _ = self.stop();
+            let _ = std::fs::remove_dir_all(&self.data_dir);
+            std::fs::rename(previous, &self.data_dir)
+                .map_err(|e| format!("Failed to move the data directory back: {}", e))?;
+        }
+        result
+    }
+
+    fn replay(&self, base: &Path, restore_dir: &Path, target: &str) -> Result<(), String> {
+        copy_dir(base, &self.data_dir)
+            .map_err(|e| format!("Failed to copy the base backup: {}", e))?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&self.data_dir, std::fs::Permissions::from_mode(0o700))
+                .map_err(|e| format!("Failed to set data directory permissions: {}", e))?;
+        }
+        self.update_port_config()?;
+        self.update_conf(&recovery_conf_values(Some((restore_dir, target))))?;
+        std::fs::write(self.data_dir.join("recovery.signal"), "")
+            .map_err(|e| format!("Failed to write recovery.signal: {}", e))?;
+
+        tracing::info!("Replaying archived WAL up to {}", target);
+        self.start_with_retry().map_err(|e| e.to_string())?;
+        let timer = StartupTimer::new();
+        loop {
+            match self.run_sql("SELECT pg_is_in_recovery()") {
+                Ok(out) if out.trim() == "f" => break,
+                Ok(_) => {}
+                Err(e) if !self.is_running() => {
+                    return Err(format!("PostgreSQL stopped during recovery: {}", e));
+                }
+                Err(_) => {}
+            }
+            if timer.elapsed() > RECOVERY_TIMEOUT {
+                return Err(format!(
+                    "Recovery didn't finish within {:?}",
+                    RECOVERY_TIMEOUT
+                ));
+            }
+            std::thread::sleep(Duration::from_secs(1));
+        }
+        tracing::info!("Recovered to {} in {:?}", target, timer.elapsed());
+
+        self.stop()?;
+        self.update_conf(&recovery_conf_values(None))?;
         Ok(())
     }
 
@@ -332,11 +1125,11 @@ host    all             all             ::1/128                 trust
 
         // Check if already running
         if self.is_running() {
-            log::info!("PostgreSQL is already running on port {}", port);
+            tracing::info!("PostgreSQL is already running on port {}", port);
             return Ok(port);
         }
 
-        log::info!("Starting PostgreSQL on port {}...", port);
+        tracing::info!("Starting PostgreSQL on port {}...", port);
 
         let postgres_path = self.bin_dir.join("postgres");
 
@@ -362,21 +1155,22 @@ host    all             all             ::1/128                 trust
                             // Create database and enable extensions
                             self.setup_database().map_err(PostgresError::StartFailed)?;
 
-                            log::info!(
+                            tracing::info!(
                                 "PostgreSQL started successfully on port {} in {}ms",
                                 port,
                                 timer.elapsed_ms()
                             );
+                            *self.retries.lock().unwrap() = backoff.current_attempt();
                             return Ok(port);
                         }
                         Err(e) => {
-                            log::warn!("PostgreSQL not ready: {}", e);
+                            tracing::warn!("PostgreSQL not ready: {}", e);
                             // Kill the process before retry (will happen at start of next iteration)
                         }
                     }
                 }
                 Err(e) => {
-                    log::warn!("Failed to start PostgreSQL: {}", e);
+                    tracing::warn!("Failed to start PostgreSQL: {}", e);
                     // Kill any partially started process before retry
                     self.kill_process();
                 }
@@ -384,7 +1178,7 @@ host    all             all             ::1/128                 trust
 
             // Check if we should retry
             if let Some(delay) = backoff.next_delay() {
-                log::info!(
+                tracing::info!(
                     "Retrying PostgreSQL startup (attempt {}/{}) after {}ms...",
                     backoff.current_attempt(),
                     backoff.max_attempts(),
@@ -416,7 +1210,7 @@ host    all             all             ::1/128                 trust
             let pids = String::from_utf8_lossy(&output.stdout);
             for pid in pids.lines() {
                 if let Ok(pid_num) = pid.trim().parse::<i32>() {
-                    log::info!(
+                    tracing::info!(
                         "Killing orphaned PostgreSQL process {} on port {}",
                         pid_num,
                         port
@@ -435,6 +1229,7 @@ host    all             all             ::1/128                 trust
     }
 
     /// Single attempt to start PostgreSQL
+    #[tracing::instrument(name = "spawn", skip(self, postgres_path), fields(program = %postgres_path.display()))]
     fn attempt_start(
         &self,
         postgres_path: &std::path::Path,
@@ -448,7 +1243,10 @@ host    all             all             ::1/128                 trust
         //
         // LC_ALL=C is required to prevent "postmaster became multithreaded during startup"
         // error on macOS when spawning threads (like the stderr reader) early in the process.
-        let mut child = Command::new(postgres_path)
+        // tokio needs its runtime entered to spawn, and this runs on blocking threads
+        let runtime = tauri::async_runtime::handle();
+        let _runtime = runtime.inner().enter();
+        let mut child = tokio::process::Command::new(postgres_path)
             .arg("-D")
             .arg(&self.data_dir)
             .arg("-p")
@@ -459,33 +1257,13 @@ host    all             all             ::1/128                 trust
             .env("LANG", "C")
             .stdout(Stdio::null())
             .stderr(Stdio::piped()) // Keep stderr to capture startup errors
+            .kill_on_drop(true)
             .spawn()
             .map_err(|e| PostgresError::StartFailed(e.to_string()))?;
 
-        // Spawn a thread to consume stderr to prevent blocking
-        // This also logs any PostgreSQL errors
-        // Use panic handling to prevent silent thread failures (consistent with T2 fix in lib.rs)
+        // Consume stderr to prevent blocking; this also logs any PostgreSQL errors
         if let Some(stderr) = child.stderr.take() {
-            std::thread::Builder::new()
-                .name("postgres-stderr-monitor".to_string())
-                .spawn(move || {
-                    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
-                        let reader = BufReader::new(stderr);
-                        for line in reader.lines().map_while(Result::ok) {
-                            log::info!("[PostgreSQL] {}", line);
-                        }
-                    }));
-
-                    if let Err(e) = result {
-                        log::error!("[PostgreSQL stderr monitor] Thread panicked: {:?}", e);
-                    }
-                })
-                .map_err(|e| {
-                    PostgresError::StartFailed(format!(
-                        "Failed to spawn stderr monitor thread: {}",
-                        e
-                    ))
-                })?;
+            crate::spawn_line_logger(stderr, "PostgreSQL", log::Level::Info);
         }
 
         *self.process.lock().unwrap() = Some(child);
@@ -495,8 +1273,14 @@ host    all             all             ::1/128                 trust
     /// Kill the current PostgreSQL process
     fn kill_process(&self) {
         if let Some(mut child) = self.process.lock().unwrap().take() {
-            let _ = child.kill();
-            let _ = child.wait();
+            let _ = child.start_kill();
+            // Wait briefly so the port is free before a retry
+            for _ in 0..20 {
+                if !matches!(child.try_wait(), Ok(None)) {
+                    break;
+                }
+                std::thread::sleep(Duration::from_millis(100));
+            }
         }
     }
 
@@ -507,12 +1291,12 @@ host    all             all             ::1/128                 trust
 
         if !pg_isready.exists() {
             // If pg_isready doesn't exist, use a simple sleep and hope for the best
-            log::warn!("pg_isready not found, waiting 5 seconds for PostgreSQL to start");
+            tracing::warn!("pg_isready not found, waiting 5 seconds for PostgreSQL to start");
             std::thread::sleep(Duration::from_secs(5));
             return Ok(());
         }
 
-        log::info!("Waiting for PostgreSQL to be ready...");
+        tracing::info!("Waiting for PostgreSQL to be ready...");
 
         let timeout = Duration::from_secs(self.startup_config.timeout_secs);
         let start = std::time::Instant::now();
@@ -530,7 +1314,7 @@ host    all             all             ::1/128                 trust
 
             if let Ok(output) = result {
                 if output.status.success() {
-                    log::info!(
+                    tracing::info!(
                         "PostgreSQL is ready after {}ms",
                         start.elapsed().as_millis()
                     );
@@ -548,36 +1332,50 @@ host    all             all             ::1/128                 trust
 
     /// Stop the PostgreSQL server
     pub fn stop(&self) -> Result<(), String> {
-        log::info!("Stopping PostgreSQL...");
+        self.stop_within(Duration::from_secs(60))
+    }
 
-        // Try graceful shutdown first using pg_ctl
-        let pg_ctl_path = self.bin_dir.join("pg_ctl");
+    /// Stop PostgreSQL, giving a clean shutdown up to `timeout`
+    ///
+    /// A fast shutdown is tried first. If it doesn't finish in time, an
+    /// immediate shutdown skips the checkpoint, which is safe since recovery
+    /// replays the WAL at the next start; killing the process is the last
+    /// resort.
+    pub fn stop_within(&self, timeout: Duration) -> Result<(), String> {
+        tracing::info!("Stopping PostgreSQL...");
 
+        let pg_ctl_path = self.bin_dir.join("pg_ctl");
         if pg_ctl_path.exists() {
-            let result = Command::new(&pg_ctl_path)
-                .arg("stop")
-                .arg("-D")
-                .arg(&self.data_dir)
-                .arg("-m")
-                .arg("fast")
-                .arg("-w")
-                .output();
-
-            if let Ok(output) = result {
-                if output.status.success() {
-                    log::info!("PostgreSQL stopped gracefully");
-                    *self.process.lock().unwrap() = None;
-                    return Ok(());
+            let fast_secs = timeout.as_secs().max(1).to_string();
+            for (mode, secs) in [("fast", fast_secs.as_str()), ("immediate", "5")] {
+                let result = Command::new(&pg_ctl_path)
+                    .arg("stop")
+                    .arg("-D")
+                    .arg(&self.data_dir)
+                    .arg("-m")
+                    .arg(mode)
+                    .arg("-w")
+                    .arg("-t")
+                    .arg(secs)
+                    .output();
+
+                if let Ok(output) = result {
+                    if output.status.success() {
+                        tracing::info!("PostgreSQL stopped ({} shutdown)", mode);
+                        *self.process.lock().unwrap() = None;
+                        return Ok(());
+                    }
                 }
+                tracing::warn!("PostgreSQL {} shutdown didn't complete", mode);
             }
         }
 
         // Fallback: kill the process directly
         if let Some(mut child) = self.process.lock().unwrap().take() {
             child
-                .kill()
+                .start_kill()
                 .map_err(|e| format!("Failed to kill PostgreSQL: {}", e))?;
-            log::info!("PostgreSQL process killed");
+            tracing::info!("PostgreSQL process killed");
         }
 
         Ok(())
@@ -635,7 +1433,7 @@ host    all             all             ::1/128                 trust
             .contains("1");
 
         if !db_exists {
-            log::info!("Creating secondbrain database...");
+            tracing::info!("Creating secondbrain database...");
 
             let output = Command::new(&psql)
                 .arg("-h")
@@ -653,12 +1451,12 @@ host    all             all             ::1/128                 trust
 
             if !output.status.success() {
                 let stderr = String::from_utf8_lossy(&output.stderr);
-                log::warn!("Create database output: {}", stderr);
+                tracing::warn!("Create database output: {}", stderr);
             }
         }
 
         // Enable pgvector extension
-        log::info!("Enabling pgvector extension...");
+        tracing::info!("Enabling pgvector extension...");
         let output = Command::new(&psql)
             .arg("-h")
             .arg("localhost")
@@ -675,13 +1473,52 @@ host    all             all             ::1/128                 trust
 
         if !output.status.success() {
             let stderr = String::from_utf8_lossy(&output.stderr);
-            log::warn!("pgvector extension output: {}", stderr);
+            tracing::warn!("pgvector extension output: {}", stderr);
             // Don't fail - pgvector might not be installed in development
         }
 
         Ok(())
     }
 
+    /// Run SQL against the secondbrain database
+    ///
+    /// Returns tuples-only, unaligned output (`|`-separated columns).
+    pub fn run_sql(&self, sql: &str) -> Result<String, String> {
+        let psql = self.bin_dir.join("psql");
+        let port = *self.port.lock().unwrap();
+
+        if !psql.exists() {
+            return Err(format!("psql not found at {:?}", psql));
+        }
+
+        let output = Command::new(&psql)
+            .arg("-h")
+            .arg("localhost")
+            .arg("-p")
+            .arg(port.to_string())
+            .arg("-U")
+            .arg("secondbrain")
+            .arg("-d")
+            .arg("secondbrain")
+            .arg("-X")
+            .arg("-q")
+            .arg("-tA")
+            .arg("-v")
+            .arg("ON_ERROR_STOP=1")
+            .arg("-c")
+            .arg(sql)
+            .output()
+            .map_err(|e| format!("Failed to run psql: {}", e))?;
+
+        if !output.status.success() {
+            return Err(format!(
+                "Query failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+
     /// Get the connection string for the embedded database
     pub fn get_connection_string(&self) -> String {
         let port = *self.port.lock().unwrap();
@@ -696,10 +1533,31 @@ host    all             all             ::1/128                 trust
         *self.port.lock().unwrap()
     }
 
+    /// PID of the running postmaster, from the first line of postmaster.pid
+    pub fn postmaster_pid(&self) -> Option<i32> {
+        let contents = std::fs::read_to_string(self.data_dir.join("postmaster.pid")).ok()?;
+        contents.lines().next()?.trim().parse().ok()
+    }
+
+    /// Get the PostgreSQL bin directory
+    pub fn get_bin_dir(&self) -> &Path {
+        &self.bin_dir
+    }
+
+    /// Get where the PostgreSQL binaries come from
+    pub fn bin_source(&self) -> PostgresSource {
+        self.bin_source
+    }
+
     /// Get startup metrics
     pub fn get_startup_config(&self) -> &StartupConfig {
         &self.startup_config
     }
+
+    /// Retries the last successful start needed
+    pub fn last_retry_count(&self) -> u32 {
+        *self.retries.lock().unwrap()
+    }
 }
 
 impl Drop for PostgresManager {
@@ -763,7 +1621,7 @@ mod tests {
     #[test]
     fn test_find_postgres_bin_dir_returns_path() {
         let temp_dir = TempDir::new().unwrap();
-        let bin_dir = PostgresManager::find_postgres_bin_dir(&temp_dir.path().to_path_buf());
+        let (bin_dir, _) = PostgresManager::find_postgres_bin_dir(temp_dir.path());
 
         // Should return a path (even if PostgreSQL isn't installed)
         assert!(!bin_dir.as_os_str().is_empty());
@@ -773,7 +1631,7 @@ mod tests {
     fn test_find_postgres_bin_dir_checks_known_paths() {
         // This test verifies the function checks expected paths
         let temp_dir = TempDir::new().unwrap();
-        let bin_dir = PostgresManager::find_postgres_bin_dir(&temp_dir.path().to_path_buf());
+        let (bin_dir, _) = PostgresManager::find_postgres_bin_dir(temp_dir.path());
 
         let path_str = bin_dir.to_string_lossy();
 
@@ -793,6 +1651,99 @@ mod tests {
         assert!(is_valid || path_str.contains("postgresql"));
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn test_find_postgres_bin_dir_prefers_bundled() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let fake_bin = |dir: &Path, exit_code: u8| {
+            std::fs::create_dir_all(dir).unwrap();
+            for name in ["initdb", "postgres"] {
+                let path = dir.join(name);
+                std::fs::write(&path, format!("#!/bin/sh\nexit {}\n", exit_code)).unwrap();
+                std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+            }
+        };
+        let bundled = temp_dir.path().join(BUNDLED_DIR);
+
+        // A build that doesn't run here is skipped
+        fake_bin(&bundled.join(bundled_platform()).join("bin"), 1);
+        let (_, source) = PostgresManager::find_postgres_bin_dir(temp_dir.path());
+        assert_eq!(source, PostgresSource::System);
+
+        fake_bin(&bundled.join("bin"), 0);
+        assert_eq!(
+            PostgresManager::find_postgres_bin_dir(temp_dir.path()),
+            (bundled.join("bin"), PostgresSource::Bundled)
+        );
+
+        // The build for this platform wins over the single one
+        fake_bin(&bundled.join(bundled_platform()).join("bin"), 0);
+        assert_eq!(
+            PostgresManager::find_postgres_bin_dir(temp_dir.path()),
+            (
+                bundled.join(bundled_platform()).join("bin"),
+                PostgresSource::Bundled
+            )
+        );
+    }
+
+    #[test]
+    fn test_parse_major_version() {
+        assert_eq!(
+            parse_major_version("postgres (PostgreSQL) 18.1 (Debian 18.1-1.pgdg120+1)"),
+            Some(18)
+        );
+        assert_eq!(parse_major_version("9.6"), Some(9));
+        assert_eq!(parse_major_version("18beta2"), Some(18));
+        assert_eq!(parse_major_version("main"), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_find_versioned_bin_dir() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let fake_install = |bin_dir: &Path, version: &str| {
+            std::fs::create_dir_all(bin_dir).unwrap();
+            std::fs::write(bin_dir.join("initdb"), "").unwrap();
+            let postgres = bin_dir.join("postgres");
+            let script = format!("#!/bin/sh\necho 'postgres (PostgreSQL) {}'\n", version);
+            std::fs::write(&postgres, script).unwrap();
+            std::fs::set_permissions(&postgres, std::fs::Permissions::from_mode(0o755)).unwrap();
+        };
+        let debian = temp_dir.path().join("lib/postgresql");
+        let usr = temp_dir.path().join("usr");
+        fake_install(&debian.join("16/bin"), "16.4");
+        fake_install(&debian.join("18/bin"), "18.1");
+        fake_install(&usr.join("pgsql-19/bin"), "19.0");
+        // Client-only packages have no server binaries
+        std::fs::create_dir_all(debian.join("20/bin")).unwrap();
+        let install_dirs = vec![(debian.clone(), ""), (usr.clone(), "pgsql-")];
+
+        assert_eq!(
+            find_versioned_bin_dir(None, &install_dirs),
+            Some((19, usr.join("pgsql-19/bin")))
+        );
+        assert_eq!(
+            find_versioned_bin_dir(None, &install_dirs[..1]),
+            Some((18, debian.join("18/bin")))
+        );
+
+        // PGHOME wins when new enough and is ignored when too old
+        assert_eq!(
+            find_versioned_bin_dir(Some(&debian.join("18")), &install_dirs),
+            Some((18, debian.join("18/bin")))
+        );
+        assert_eq!(
+            find_versioned_bin_dir(Some(&debian.join("16")), &install_dirs[..1]),
+            Some((18, debian.join("18/bin")))
+        );
+        assert_eq!(find_versioned_bin_dir(Some(&debian.join("16")), &[]), None);
+    }
+
     // ============================================================
     // Connection String Tests
     // ============================================================
@@ -868,8 +1819,10 @@ mod tests {
             process: Mutex::new(None),
             data_dir: temp_dir.path().join("postgresql"),
             bin_dir: fake_bin,
+            bin_source: PostgresSource::System,
             port: Mutex::new(5433),
             initialized: Mutex::new(false),
+            retries: Mutex::new(0),
             startup_config: StartupConfig::default(),
         };
 
@@ -894,8 +1847,10 @@ mod tests {
             process: Mutex::new(None),
             data_dir: data_dir.clone(),
             bin_dir: temp_dir.path().to_path_buf(),
+            bin_source: PostgresSource::System,
             port: Mutex::new(5433),
             initialized: Mutex::new(false),
+            retries: Mutex::new(0),
             startup_config: StartupConfig::default(),
         };
 
@@ -930,8 +1885,10 @@ mod tests {
             process: Mutex::new(None),
             data_dir: data_dir.clone(),
             bin_dir: temp_dir.path().to_path_buf(),
+            bin_source: PostgresSource::System,
             port: Mutex::new(9999),
             initialized: Mutex::new(false),
+            retries: Mutex::new(0),
             startup_config: StartupConfig::default(),
         };
 
@@ -941,6 +1898,107 @@ mod tests {
         assert!(conf_content.contains("port = 9999"));
     }
 
+    #[test]
+    fn test_apply_memory_settings() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = PostgresManager::new(temp_dir.path().to_path_buf(), PathBuf::new(), 5433);
+        std::fs::create_dir_all(&manager.data_dir).unwrap();
+        manager.configure_postgresql().unwrap();
+
+        let memory = PostgresMemory {
+            shared_buffers_mb: 512,
+            max_connections: 40,
+            ..Default::default()
+        };
+        manager.apply_memory_settings(&memory).unwrap();
+
+        let conf = std::fs::read_to_string(manager.data_dir.join("postgresql.conf")).unwrap();
+        assert!(conf.contains("shared_buffers = 512MB"));
+        assert!(conf.contains("max_connections = 40"));
+        assert_eq!(conf.matches("shared_buffers").count(), 1);
+        assert!(conf.contains("port = 5433"));
+    }
+
+    #[test]
+    fn test_set_conf_values_appends_missing() {
+        let updated = set_conf_values(
+            "# comment\nwork_mem = 4MB\n",
+            &[
+                ("work_mem", "8MB".to_string()),
+                ("shared_buffers", "256MB".to_string()),
+            ],
+        );
+        assert_eq!(
+            updated,
+            "# comment\nwork_mem = 8MB\nshared_buffers = 256MB\n"
+        );
+    }
+
+    #[test]
+    fn test_archive_conf_values() {
+        let dir = Path::new("/data/wal-archive/wal");
+        let values = archive_conf_values(Some(dir));
+        let get = |name| {
+            values
+                .iter()
+                .find(|(n, _)| *n == name)
+                .map(|(_, v)| v.as_str())
+        };
+        assert_eq!(get("archive_mode"), Some("on"));
+        assert_eq!(get("wal_level"), Some("replica"));
+        #[cfg(unix)]
+        assert_eq!(
+            get("archive_command"),
+            Some(
+                "'test ! -f \"/data/wal-archive/wal/%f\" && cp \"%p\" \"/data/wal-archive/wal/%f\"'"
+            )
+        );
+
+        let off = archive_conf_values(None);
+        assert!(off.contains(&("archive_mode", "off".to_string())));
+    }
+
+    #[test]
+    fn test_conf_string_escapes() {
+        assert_eq!(conf_string("it's"), "'it''s'");
+        assert_eq!(conf_string(r"C:\data"), r"'C:\\data'");
+    }
+
+    #[test]
+    fn test_recovery_conf_values() {
+        let values = recovery_conf_values(Some((
+            Path::new("/data/wal-restore"),
+            "2025-03-17 12:00:00+00",
+        )));
+        assert!(values.contains(&(
+            "recovery_target_time",
+            "'2025-03-17 12:00:00+00'".to_string()
+        )));
+        assert!(values.contains(&("recovery_target_action", "'promote'".to_string())));
+        let cleared = recovery_conf_values(None);
+        assert!(cleared.contains(&("restore_command", "''".to_string())));
+    }
+
+    #[test]
+    fn test_copy_dir() {
+        let temp_dir = TempDir::new().unwrap();
+        let from = temp_dir.path().join("from");
+        std::fs::create_dir_all(from.join("base/1")).unwrap();
+        std::fs::write(from.join("PG_VERSION"), "18").unwrap();
+        std::fs::write(from.join("base/1/1259"), "rel").unwrap();
+
+        let to = temp_dir.path().join("to");
+        copy_dir(&from, &to).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(to.join("PG_VERSION")).unwrap(),
+            "18"
+        );
+        assert_eq!(
+            std::fs::read_to_string(to.join("base/1/1259")).unwrap(),
+            "rel"
+        );
+    }
+
     // ============================================================
     // is_running Tests
     // ============================================================
@@ -953,8 +2011,10 @@ mod tests {
             process: Mutex::new(None),
             data_dir: temp_dir.path().to_path_buf(),
             bin_dir: temp_dir.path().join("nonexistent"),
+            bin_source: PostgresSource::System,
             port: Mutex::new(5433),
             initialized: Mutex::new(false),
+            retries: Mutex::new(0),
             startup_config: StartupConfig::default(),
         };
 
@@ -973,8 +2033,10 @@ mod tests {
             process: Mutex::new(None),
             data_dir: temp_dir.path().to_path_buf(),
             bin_dir: temp_dir.path().to_path_buf(),
+            bin_source: PostgresSource::System,
             port: Mutex::new(5433),
             initialized: Mutex::new(false),
+            retries: Mutex::new(0),
             startup_config: StartupConfig::default(),
         };
 
@@ -992,8 +2054,10 @@ mod tests {
             process: Mutex::new(None),
             data_dir: temp_dir.path().to_path_buf(),
             bin_dir: temp_dir.path().to_path_buf(),
+            bin_source: PostgresSource::System,
             port: Mutex::new(5433),
             initialized: Mutex::new(false),
+            retries: Mutex::new(0),
             startup_config: StartupConfig::default(),
         };
 
@@ -1015,8 +2079,10 @@ mod tests {
                 process: Mutex::new(None),
                 data_dir: temp_dir.path().to_path_buf(),
                 bin_dir: temp_dir.path().to_path_buf(),
+                bin_source: PostgresSource::System,
                 port: Mutex::new(5433),
                 initialized: Mutex::new(false),
+                retries: Mutex::new(0),
                 startup_config: StartupConfig::default(),
             };
             // Manager will be dropped here
@@ -0,0 +1,228 @@
+//! Throwaway demo instances (`--demo`).
+//!
+//! This module provides:
+//! - Sample notes, added once the demo account has signed in
+//! - Deletion of the demo's credentials and data directory, PostgreSQL
+//!   cluster included, when the app exits
+//! - A sweep of demo directories left behind by a crash, run at every launch
+//! - Starting a demo instance next to the running app (File menu)
+//!
+//! A demo runs as its own profile (see `profile`), so its database, secrets,
+//! ports and settings are separate from real data.
+
+use serde_json::json;
+use std::path::Path;
+use std::process::Stdio;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+use crate::error::AppError;
+use crate::profile::{self, ProfileLock};
+use crate::AppState;
+
+/// How often to check whether the demo account has signed in
+const SEED_POLL: Duration = Duration::from_secs(2);
+
+/// Marker in the directory names of demo profiles
+const DEMO_DIR_MARKER: &str = ".profile-demo-";
+
+struct SampleNote {
+    title: &'static str,
+    content: &'static str,
+    tags: &'static [&'static str],
+}
+
+const SAMPLE_NOTES: &[SampleNote] = &[
+    SampleNote {
+        title: "Welcome to Second Brain",
+        content: "# Welcome\n\nThis is a demo. Anything you add here is deleted when \
+                  the app quits.\n\n- Write notes in Markdown\n- Tag them to group ideas\n\
+                  - Ask the assistant about what you've written",
+        tags: &["demo", "getting-started"],
+    },
+    SampleNote {
+        title: "Reading list",
+        content: "# Reading list\n\n- [ ] How to Take Smart Notes — Sönke Ahrens\n\
+                  - [ ] Building a Second Brain — Tiago Forte\n\
+                  - [x] The Pragmatic Programmer",
+        tags: &["books"],
+    },
+    SampleNote {
+        title: "Project kickoff",
+        content: "# Project kickoff\n\n**Goal:** ship the beta by the end of the quarter.\n\n\
+                  ## Decisions\n- Weekly demos on Fridays\n- Feedback goes in the tracker\n\n\
+                  ## Open questions\n- Who owns onboarding?",
+        tags: &["work", "meetings"],
+    },
+    SampleNote {
+        title: "Sourdough",
+        content: "# Sourdough\n\n500g flour, 350g water, 100g starter, 10g salt.\n\n\
+                  Bulk ferment 4–5 hours, shape, proof overnight in the fridge, \
+                  bake at 250°C covered for 20 minutes and uncovered for 25.",
+        tags: &["recipes"],
+    },
+    SampleNote {
+        title: "Ideas",
+        content: "# Ideas\n\n- A weekly review template\n- Link meeting notes to projects\n\
+                  - Summarise long articles into one paragraph",
+        tags: &["ideas"],
+    },
+];
+
+/// Add the sample notes once the demo account has signed in
+async fn seed(app: AppHandle) {
+    loop {
+        let signed_in = {
+            let state = app.state::<AppState>();
+            let ready = *state.is_backend_ready.read();
+            ready && state.backend_auth.read().is_some()
+        };
+        if signed_in {
+            break;
+        }
+        tokio::time::sleep(SEED_POLL).await;
+    }
+
+    for note in SAMPLE_NOTES {
+        let body = json!({
+            "title": note.title,
+            "content": note.content,
+            "tags": note.tags,
+        });
+        if let Err(e) =
+            crate::write_queue::send_or_queue(&app, "POST", "/notes", Some(body), None).await
+        {
+            tracing::warn!("Failed to add demo note '{}': {}", note.title, e);
+            return;
+        }
+    }
+    tracing::info!("Added {} demo notes", SAMPLE_NOTES.len());
+}
+
+/// Delete a demo's credentials, then its data directory
+///
+/// The demo's credential store service is its own, and its accounts are only
+/// known from the index in the data directory, so credentials go first. The
+/// directory is kept if they can't be deleted, so the next sweep retries.
+fn remove_demo(app_data_dir: &Path) -> Result<(), String> {
+    crate::keychain::delete_all(app_data_dir)?;
+    std::fs::remove_dir_all(app_data_dir).map_err(|e| e.to_string())
+}
+
+/// Delete demo directories no running instance holds
+///
+/// `data_dir` is the directory app data directories live in.
+fn sweep(data_dir: &Path, current: &Path) {
+    let Ok(entries) = std::fs::read_dir(data_dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let is_demo = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.contains(DEMO_DIR_MARKER));
+        if !is_demo || path == current || !path.is_dir() {
+            continue;
+        }
+        // A demo that is still running holds its lock
+        let Ok(lock) = ProfileLock::acquire(&path) else {
+            continue;
+        };
+        lock.release();
+        match remove_demo(&path) {
+            Ok(()) => tracing::info!("Removed leftover demo data {:?}", path),
+            Err(e) => tracing::warn!("Failed to remove leftover demo data {:?}: {}", path, e),
+        }
+    }
+}
+
+/// Sweep leftover demos and, in a demo, start seeding
+pub fn start(app: &AppHandle) {
+    if let Ok(app_data_dir) = app.path().app_data_dir() {
+        if let Some(data_dir) = app_data_dir.parent().map(Path::to_path_buf) {
+            tauri::async_runtime::spawn_blocking(move || sweep(&data_dir, &app_data_dir));
+        }
+    }
+
+    if profile::current(app).demo {
+        tracing::info!("Running as a demo; its data is deleted on exit");
+        tauri::async_runtime::spawn(seed(app.clone()));
+    }
+}
+
+/// Delete the demo's credentials and data; call after services have stopped
+pub fn cleanup(app: &AppHandle) {
+    if !profile::current(app).demo {
+        return;
+    }
+    let Ok(app_data_dir) = app.path().app_data_dir() else {
+        return;
+    };
+    // Windows won't delete a file that is still open
+    if let Some(lock) = app.try_state::<ProfileLock>() {
+        lock.release();
+    }
+    match remove_demo(&app_data_dir) {
+        Ok(()) => tracing::info!("Deleted demo data {:?}", app_data_dir),
+        Err(e) => tracing::warn!(
+            "Failed to delete demo data {:?}, it will be removed on next launch: {}",
+            app_data_dir,
+            e
+        ),
+    }
+}
+
+/// Start a separate demo instance of the app
+pub fn launch_instance() -> Result<(), AppError> {
+    let exe = std::env::current_exe()?;
+    std::process::Command::new(exe)
+        .arg("--demo")
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    Ok(())
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_sweep_removes_unlocked_demos() {
+        let root = TempDir::new().unwrap();
+        let dir = |name: &str| {
+            let path = root.path().join(name);
+            std::fs::create_dir_all(path.join("postgres")).unwrap();
+            path
+        };
+        let real = dir("com.secondbrain.desktop");
+        let stale = dir("com.secondbrain.desktop.profile-demo-111");
+        let running = dir("com.secondbrain.desktop.profile-demo-222");
+        let current = dir("com.secondbrain.desktop.profile-demo-333");
+        let _running_lock = ProfileLock::acquire(&running).unwrap();
+
+        sweep(root.path(), &current);
+
+        assert!(real.exists());
+        assert!(!stale.exists());
+        assert!(current.exists());
+        #[cfg(any(unix, windows))]
+        assert!(running.exists());
+    }
+
+    #[test]
+    fn test_sample_notes() {
+        assert!(!SAMPLE_NOTES.is_empty());
+        for note in SAMPLE_NOTES {
+            assert!(note.content.starts_with("# "), "{}", note.title);
+            assert!(!note.tags.is_empty());
+        }
+    }
+}
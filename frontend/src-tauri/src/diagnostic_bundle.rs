@@ -0,0 +1,386 @@
+//! Diagnostic bundles for bug reports.
+//!
+//! This module provides:
+//! - `export_diagnostics`, which writes one zip with the diagnostic report,
+//!   recent app, backend and PostgreSQL logs, and `service-config.json`
+//! - PostgreSQL server output picked out of the app logs, where its stderr
+//!   is logged, plus the log of the last dump-and-restore upgrade
+//! - Redaction of every text file with `secrets::redact_env_vars` before it
+//!   goes into the archive
+//!
+//! Logs are capped at the newest `MAX_LOG_FILES` files per source and the
+//! last `MAX_LOG_BYTES` of each, so a bundle stays small enough to attach.
+
+use serde::Serialize;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use crate::error::AppError;
+use crate::secrets::redact_env_vars;
+
+/// Newest log files included from each source
+const MAX_LOG_FILES: usize = 3;
+
+/// Most bytes kept from the end of each log file
+const MAX_LOG_BYTES: u64 = 5 * 1024 * 1024;
+
+/// Prefix `spawn_line_logger` puts on PostgreSQL's stderr lines
+const POSTGRES_LOG_TAG: &str = "[PostgreSQL] ";
+
+/// A file in the bundle
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BundleEntry {
+    /// Path inside the archive
+    pub name: String,
+    /// Size before compression
+    pub size_bytes: u64,
+}
+
+/// The written bundle
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DiagnosticBundle {
+    pub path: String,
+    pub size_bytes: u64,
+    pub entries: Vec<BundleEntry>,
+}
+
+/// The last `max_bytes` of a file, starting at a line boundary when cut
+fn read_log_tail(path: &Path, max_bytes: u64) -> std::io::Result<String> {
+    let mut file = std::fs::File::open(path)?;
+    let len = file.metadata()?.len();
+    let mut bytes = Vec::new();
+    if len > max_bytes {
+        file.seek(SeekFrom::Start(len - max_bytes))?;
+        file.read_to_end(&mut bytes)?;
+        // Drop the partial first line
+        let start = bytes.iter().position(|&b| b == b'\n').map_or(0, |i| i + 1);
+        bytes.drain(..start);
+    } else {
+        file.read_to_end(&mut bytes)?;
+    }
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// Newest `.log` files directly in `dir`, newest first
+fn recent_logs(dir: &Path, limit: usize) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut logs: Vec<(std::time::SystemTime, PathBuf)> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "log"))
+        .map(|path| {
+            let modified = path
+                .metadata()
+                .and_then(|m| m.modified())
+                .unwrap_or(std::time::UNIX_EPOCH);
+            (modified, path)
+        })
+        .collect();
+    logs.sort_by(|a, b| b.cmp(a));
+    logs.into_iter().take(limit).map(|(_, path)| path).collect()
+}
+
+/// Text files for the bundle, as (archive name, source file)
+fn log_sources(app_log_dir: &Path, app_data_dir: &Path) -> Vec<(String, PathBuf)> {
+    let sources = [
+        ("logs/app", app_log_dir.to_path_buf()),
+        ("logs/backend", app_data_dir.join("logs")),
+    ];
+    let mut files: Vec<(String, PathBuf)> = sources
+        .iter()
+        .flat_map(|(folder, dir)| {
+            recent_logs(dir, MAX_LOG_FILES)
+                .into_iter()
+                .map(move |path| {
+                    let name = path.file_name().unwrap_or_default().to_string_lossy();
+                    (format!("{}/{}", folder, name), path.to_path_buf())
+                })
+        })
+        .collect();
+    // Written next to the data directory by a dump-and-restore upgrade
+    files.push((
+        "logs/postgresql/postgresql-upgrade.log".to_string(),
+        app_data_dir.join("postgresql-upgrade.log"),
+    ));
+    files.push((
+        "service-config.json".to_string(),
+        crate::config::ServiceConfig::config_path(app_data_dir),
+    ));
+    files
+}
+
+/// PostgreSQL's lines from an app log, or None if it has none
+///
+/// The logging collector is off, so the server's stderr only reaches the app
+/// log through `spawn_line_logger`.
+fn postgres_lines(app_log: &str) -> Option<String> {
+    let lines: String = app_log
+        .lines()
+        .filter(|line| line.contains(POSTGRES_LOG_TAG))
+        .flat_map(|line| [line, "\n"])
+        .collect();
+    (!lines.is_empty()).then_some(lines)
+}
+
+/// Redact `text` and add it to the archive
+fn add_text<W: Write + Seek>(
+    zip: &mut ZipWriter<W>,
+    entries: &mut Vec<BundleEntry>,
+    name: &str,
+    text: &str,
+) -> std::io::Result<()> {
+    let redacted = redact_env_vars(text);
+    let options = SimpleFileOptions::default()
+        .compression_method(CompressionMethod::Deflated)
+        .last_modified_time(modified_time())
+        .large_file(true);
+    zip.start_file(name, options)?;
+    zip.write_all(redacted.as_bytes())?;
+    entries.push(BundleEntry {
+        name: name.to_string(),
+        size_bytes: redacted.len() as u64,
+    });
+    Ok(())
+}
+
+/// Local time for archive entries, which store no time zone
+fn modified_time() -> zip::DateTime {
+    use chrono::{Datelike, Timelike};
+    let now = chrono::Local::now().naive_local();
+    zip::DateTime::from_date_and_time(
+        now.year().clamp(1980, 2107) as u16,
+        now.month() as u8,
+        now.day() as u8,
+        now.hour() as u8,
+        now.minute() as u8,
+        now.second() as u8,
+    )
+    .unwrap_or_default()
+}
+
+fn write_zip(
+    file: std::fs::File,
+    report_json: &str,
+    sources: &[(String, PathBuf)],
+) -> std::io::Result<Vec<BundleEntry>> {
+    let mut zip = ZipWriter::new(std::io::BufWriter::new(file));
+    let mut entries = Vec::new();
+    add_text(
+        &mut zip,
+        &mut entries,
+        "diagnostic-report.json",
+        report_json,
+    )?;
+    for (name, source) in sources {
+        let text = match read_log_tail(source, MAX_LOG_BYTES) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => {
+                tracing::warn!("Left {:?} out of the diagnostic bundle: {}", source, e);
+                continue;
+            }
+        };
+        add_text(&mut zip, &mut entries, name, &text)?;
+        let server_log = name.strip_prefix("logs/app/").zip(postgres_lines(&text));
+        if let Some((file_name, lines)) = server_log {
+            let name = format!("logs/postgresql/{}", file_name);
+            add_text(&mut zip, &mut entries, &name, &lines)?;
+        }
+    }
+    zip.finish()?
+        .into_inner()
+        .map_err(|e| e.into_error())?
+        .sync_all()?;
+    Ok(entries)
+}
+
+/// Write the bundle to `path` through a temporary file
+fn write_bundle(
+    path: &Path,
+    report_json: &str,
+    sources: &[(String, PathBuf)],
+) -> Result<Vec<BundleEntry>, AppError> {
+    let partial = path.with_extension("zip.partial");
+    let file = std::fs::File::create(&partial)?;
+    match write_zip(file, report_json, sources) {
+        Ok(entries) => {
+            std::fs::rename(&partial, path)?;
+            Ok(entries)
+        }
+        Err(e) => {
+            let _ = std::fs::remove_file(&partial);
+            Err(e.into())
+        }
+    }
+}
+
+/// Destination for the bundle, with a .zip extension added if missing
+fn bundle_path(destination: &str) -> Result<PathBuf, AppError> {
+    let path = PathBuf::from(destination);
+    if !path.is_absolute() {
+        return Err(AppError::InvalidInput(format!(
+            "Bundle path must be absolute: {}",
+            path.display()
+        )));
+    }
+    if !path.parent().is_some_and(Path::is_dir) {
+        return Err(AppError::NotFound(format!(
+            "Folder doesn't exist: {}",
+            path.display()
+        )));
+    }
+    let is_zip = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("zip"));
+    Ok(if is_zip {
+        path
+    } else {
+        path.with_extension("zip")
+    })
+}
+
+// ============================================================
+// Commands
+// ============================================================
+
+/// Export the diagnostic report, recent logs and service config as one zip
+/// to attach to bug reports
+#[tauri::command]
+pub async fn export_diagnostics(
+    app: AppHandle,
+    destination: String,
+) -> Result<DiagnosticBundle, AppError> {
+    let path = bundle_path(&destination)?;
+    let report = crate::get_diagnostic_report(app.clone()).await?;
+    let report_json = serde_json::to_string_pretty(&report)?;
+    let sources = log_sources(&app.path().app_log_dir()?, &app.path().app_data_dir()?);
+
+    let target = path.clone();
+    let entries =
+        tokio::task::spawn_blocking(move || write_bundle(&target, &report_json, &sources))
+            .await??;
+    let size_bytes = std::fs::metadata(&path)?.len();
+    tracing::info!(
+        "Exported diagnostic bundle with {} files to {:?}",
+        entries.len(),
+        path
+    );
+    Ok(DiagnosticBundle {
+        path: path.to_string_lossy().to_string(),
+        size_bytes,
+        entries,
+    })
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// Names and contents of every entry in an archive
+    fn read_zip(bytes: &[u8]) -> Vec<(String, String)> {
+        let mut archive = zip::ZipArchive::new(std::io::Cursor::new(bytes)).unwrap();
+        (0..archive.len())
+            .map(|i| {
+                let mut file = archive.by_index(i).unwrap();
+                let mut text = String::new();
+                file.read_to_string(&mut text).unwrap();
+                (file.name().to_string(), text)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_postgres_lines() {
+        let log = "[2026-03-14][09:26:52][second_brain][INFO] [Backend] Listening\n\
+                   [2026-03-14][09:26:53][second_brain][INFO] [PostgreSQL] LOG:  ready\n";
+        assert_eq!(
+            postgres_lines(log).as_deref(),
+            Some("[2026-03-14][09:26:53][second_brain][INFO] [PostgreSQL] LOG:  ready\n")
+        );
+        assert_eq!(postgres_lines("[Backend] Listening\n"), None);
+    }
+
+    #[test]
+    fn test_read_log_tail_starts_at_a_line() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("app.log");
+        std::fs::write(&path, "first line\nsecond line\nthird\n").unwrap();
+
+        assert_eq!(
+            read_log_tail(&path, 1024).unwrap(),
+            "first line\nsecond line\nthird\n"
+        );
+        assert_eq!(read_log_tail(&path, 15).unwrap(), "third\n");
+    }
+
+    #[test]
+    fn test_write_bundle_redacts_and_skips_missing_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let app_log_dir = temp_dir.path().join("app-logs");
+        let app_data_dir = temp_dir.path().join("data");
+        std::fs::create_dir_all(&app_log_dir).unwrap();
+        std::fs::create_dir_all(app_data_dir.join("logs")).unwrap();
+        std::fs::write(
+            app_log_dir.join("Second Brain.log"),
+            "[Backend] OPENAI_API_KEY=sk-abcdefghijklmnopqrstuvwxyz0123456789\n\
+             [PostgreSQL] LOG:  database system is ready\n",
+        )
+        .unwrap();
+        std::fs::write(app_data_dir.join("logs").join("notes.txt"), "not a log").unwrap();
+
+        let sources = log_sources(&app_log_dir, &app_data_dir);
+        let path = temp_dir.path().join("bundle.zip");
+        let entries = write_bundle(&path, "{}", &sources).unwrap();
+
+        // Neither service-config.json nor an upgrade log exists
+        let names: Vec<&str> = entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "diagnostic-report.json",
+                "logs/app/Second Brain.log",
+                "logs/postgresql/Second Brain.log",
+            ]
+        );
+        let contents = read_zip(&std::fs::read(&path).unwrap());
+        assert_eq!(contents.len(), 3);
+        assert!(!contents[1].1.contains("sk-abcdefghijklmnopqrstuvwxyz"));
+        assert_eq!(
+            contents[2].1,
+            "[PostgreSQL] LOG:  database system is ready\n"
+        );
+        assert!(!temp_dir.path().join("bundle.zip.partial").exists());
+    }
+
+    #[test]
+    fn test_bundle_path() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        assert_eq!(
+            bundle_path(&dir.join("report").to_string_lossy()).unwrap(),
+            dir.join("report.zip")
+        );
+        assert_eq!(
+            bundle_path(&dir.join("report.ZIP").to_string_lossy()).unwrap(),
+            dir.join("report.ZIP")
+        );
+        assert!(matches!(
+            bundle_path("report.zip"),
+            Err(AppError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            bundle_path(&dir.join("missing/report.zip").to_string_lossy()),
+            Err(AppError::NotFound(_))
+        ));
+    }
+}
@@ -9,6 +9,12 @@
 use serde::{Deserialize, Serialize};
 use std::path::Path;
 
+use crate::ai_cache::AiCacheStats;
+use crate::attachments::{AttachmentAuditSummary, AttachmentStats};
+use crate::clock::ClockCheck;
+use crate::database::{parse_major_version, PostgresSource, MIN_POSTGRES_VERSION};
+use crate::github::GitHubTokenCheck;
+
 /// System information for diagnostics
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SystemInfo {
@@ -91,14 +97,21 @@ impl ServiceState {
 pub struct PostgresInfo {
     /// Path to PostgreSQL binaries
     pub bin_path: String,
+    /// Whether the binaries are bundled with the app or installed on the
+    /// system
+    pub source: PostgresSource,
     /// PostgreSQL version if available
     pub version: Option<String>,
+    /// Major version, e.g. 18
+    pub major_version: Option<u32>,
+    /// Whether the version is one the app's database works with
+    pub version_supported: bool,
     /// Whether pgvector extension is available
     pub pgvector_available: bool,
 }
 
 impl PostgresInfo {
-    pub fn detect(bin_dir: &Path) -> Self {
+    pub fn detect(bin_dir: &Path, source: PostgresSource) -> Self {
         let postgres_path = bin_dir.join("postgres");
         let version = if postgres_path.exists() {
             get_postgres_version(&postgres_path)
@@ -106,10 +119,15 @@ impl PostgresInfo {
             None
         };
 
+        let major_version = version.as_deref().and_then(parse_major_version);
+
         Self {
             bin_path: bin_dir.to_string_lossy().to_string(),
+            source,
             version,
-            pgvector_available: check_pgvector_available(bin_dir),
+            major_version,
+            version_supported: major_version.is_some_and(|v| v >= MIN_POSTGRES_VERSION),
+            pgvector_available: check_pgvector_available(bin_dir, major_version),
         }
     }
 }
@@ -131,6 +149,17 @@ pub struct DiagnosticReport {
     pub log_dir: String,
     /// Report timestamp (ISO 8601)
     pub timestamp: String,
+    /// AI response cache statistics
+    pub ai_cache: Option<AiCacheStats>,
+    /// Attachment integrity audit counts
+    pub attachments: Option<AttachmentAuditSummary>,
+    /// Latest system clock check
+    pub clock: Option<ClockCheck>,
+    /// Latest GitHub token check, with any expiry warning
+    pub github_token: Option<GitHubTokenCheck>,
+    /// CORS origins the backend is started with
+    #[serde(default)]
+    pub allowed_origins: Vec<String>,
 }
 
 impl DiagnosticReport {
@@ -144,7 +173,7 @@ impl DiagnosticReport {
         backend_port: u16,
         data_dir: &Path,
         log_dir: &Path,
-        postgres_bin_dir: Option<&Path>,
+        postgres_bin: Option<(&Path, PostgresSource)>,
     ) -> Self {
         let system = SystemInfo::collect(app_version);
 
@@ -161,7 +190,8 @@ impl DiagnosticReport {
             },
         };
 
-        let postgres_info = postgres_bin_dir.map(PostgresInfo::detect);
+        let postgres_info =
+            postgres_bin.map(|(bin_dir, source)| PostgresInfo::detect(bin_dir, source));
 
         let recent_logs = read_recent_logs(log_dir, 50);
 
@@ -173,6 +203,11 @@ impl DiagnosticReport {
             data_dir: data_dir.to_string_lossy().to_string(),
             log_dir: log_dir.to_string_lossy().to_string(),
             timestamp: chrono_lite_timestamp(),
+            ai_cache: None,
+            attachments: None,
+            clock: None,
+            github_token: None,
+            allowed_origins: Vec::new(),
         }
     }
 }
@@ -229,14 +264,21 @@ fn get_postgres_version(postgres_path: &Path) -> Option<String> {
 }
 
 /// Check if pgvector extension is available
-fn check_pgvector_available(bin_dir: &Path) -> bool {
-    // Check common extension directories relative to bin
-    let lib_dir = bin_dir
-        .parent()
-        .map(|p| p.join("share/postgresql/extension"));
-
-    if let Some(ref ext_dir) = lib_dir {
-        if ext_dir.join("vector.control").exists() {
+fn check_pgvector_available(bin_dir: &Path, major_version: Option<u32>) -> bool {
+    // Check common extension directories relative to bin, including the
+    // PGDG layout (`/usr/pgsql-18/share/extension`)
+    if let Some(prefix) = bin_dir.parent() {
+        for ext_dir in ["share/postgresql/extension", "share/extension"] {
+            if prefix.join(ext_dir).join("vector.control").exists() {
+                return true;
+            }
+        }
+    }
+
+    // Debian and Ubuntu keep extensions apart from the binaries
+    if let Some(version) = major_version {
+        let debian = format!("/usr/share/postgresql/{}/extension/vector.control", version);
+        if Path::new(&debian).exists() {
             return true;
         }
     }
@@ -291,6 +333,76 @@ fn read_recent_logs(log_dir: &Path, max_lines: usize) -> Vec<String> {
     logs
 }
 
+/// Disk usage of one kind of app data
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageCategory {
+    /// Category name
+    pub name: String,
+    /// Directory path
+    pub path: String,
+    /// Size in bytes
+    pub bytes: u64,
+}
+
+/// Disk usage of the app data directory by category
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageBreakdown {
+    /// Known data directories
+    pub categories: Vec<StorageCategory>,
+    /// Total size of the app data directory in bytes
+    pub total_bytes: u64,
+    /// Attachment store statistics
+    pub attachments: Option<AttachmentStats>,
+}
+
+/// App data subdirectories reported in the storage breakdown
+const STORAGE_CATEGORIES: &[(&str, &str)] = &[
+    ("Database", "postgresql"),
+    ("Attachments", "attachments"),
+    ("AI cache", "ai-cache"),
+    ("Note history", "note-history"),
+    ("Email inbox", "email-inbox"),
+    ("Logs", "logs"),
+];
+
+impl StorageBreakdown {
+    /// Measure the app data directory
+    pub fn collect(data_dir: &Path) -> Self {
+        let categories = STORAGE_CATEGORIES
+            .iter()
+            .map(|(name, dir)| {
+                let path = data_dir.join(dir);
+                StorageCategory {
+                    name: name.to_string(),
+                    bytes: dir_size(&path),
+                    path: path.to_string_lossy().to_string(),
+                }
+            })
+            .collect();
+
+        Self {
+            categories,
+            total_bytes: dir_size(data_dir),
+            attachments: None,
+        }
+    }
+}
+
+/// Total size of the files under a directory, without following symlinks
+pub fn dir_size(path: &Path) -> u64 {
+    let Ok(entries) = std::fs::read_dir(path) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| match entry.file_type() {
+            Ok(t) if t.is_dir() => dir_size(&entry.path()),
+            Ok(t) if t.is_file() => entry.metadata().map(|m| m.len()).unwrap_or(0),
+            _ => 0,
+        })
+        .sum()
+}
+
 /// Generate a simple ISO 8601 timestamp without external dependencies
 fn chrono_lite_timestamp() -> String {
     use std::time::{SystemTime, UNIX_EPOCH};
@@ -443,10 +555,12 @@ mod tests {
     #[test]
     fn test_postgres_info_detect_nonexistent() {
         let temp_dir = TempDir::new().unwrap();
-        let info = PostgresInfo::detect(temp_dir.path());
+        let info = PostgresInfo::detect(temp_dir.path(), PostgresSource::System);
 
         // Version should be None since postgres binary doesn't exist in temp dir
         assert!(info.version.is_none());
+        assert!(info.major_version.is_none());
+        assert!(!info.version_supported);
         // pgvector availability depends on system - may be available via Homebrew
         // So we just check the function runs without panic
     }
@@ -476,4 +590,22 @@ mod tests {
         assert!(report.services.backend.running);
         assert!(!report.timestamp.is_empty());
     }
+
+    #[test]
+    fn test_storage_breakdown_collect() {
+        let temp_dir = TempDir::new().unwrap();
+        let data_dir = temp_dir.path();
+        std::fs::create_dir_all(data_dir.join("attachments/ab")).unwrap();
+        std::fs::write(data_dir.join("attachments/ab/file"), [0u8; 100]).unwrap();
+        std::fs::write(data_dir.join("config.json"), [0u8; 10]).unwrap();
+
+        let breakdown = StorageBreakdown::collect(data_dir);
+        let attachments = breakdown
+            .categories
+            .iter()
+            .find(|c| c.name == "Attachments")
+            .unwrap();
+        assert_eq!(attachments.bytes, 100);
+        assert_eq!(breakdown.total_bytes, 110);
+    }
 }
@@ -0,0 +1,350 @@
+//! Free-space guardrails for the app data volume.
+//!
+//! This module provides:
+//! - Periodic free-space checks of the volume holding the app data
+//!   directory, against thresholds saved in disk-space.json
+//! - Progressive degradation as space runs out: a `disk-space-low` warning
+//!   first, then ingestion and backup jobs paused, then PostgreSQL refused
+//!   a start, and `disk-space-recovered` once there's room again
+//! - `get_disk_space` and settings commands for the UI
+//!
+//! PostgreSQL can corrupt its data files when a write fails halfway, so a
+//! clear startup error is far better than letting it run into a full disk.
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::config::{load_json, save_json_atomic};
+use crate::error::AppError;
+use crate::notifications::{Notice, Severity};
+
+/// Time between checks
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+const MB: u64 = 1024 * 1024;
+
+/// Free-space thresholds, in megabytes
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DiskSpaceSettings {
+    /// Below this, warn the user
+    pub warn_below_mb: u64,
+    /// Below this, pause ingestion and backup jobs
+    pub pause_below_mb: u64,
+    /// Below this, refuse to start PostgreSQL
+    pub refuse_below_mb: u64,
+}
+
+impl Default for DiskSpaceSettings {
+    fn default() -> Self {
+        Self {
+            warn_below_mb: 5 * 1024,
+            pause_below_mb: 2 * 1024,
+            refuse_below_mb: 512,
+        }
+    }
+}
+
+impl DiskSpaceSettings {
+    fn path(app_data_dir: &Path) -> PathBuf {
+        app_data_dir.join("disk-space.json")
+    }
+
+    /// Load settings, returning defaults if missing or invalid
+    pub fn load(app_data_dir: &Path) -> Self {
+        load_json(&Self::path(app_data_dir)).unwrap_or_default()
+    }
+
+    /// Save settings atomically
+    pub fn save(&self, app_data_dir: &Path) -> Result<(), String> {
+        save_json_atomic(&Self::path(app_data_dir), self)
+    }
+
+    /// Validate settings
+    pub fn validate(&self) -> Result<(), String> {
+        if self.refuse_below_mb == 0 {
+            return Err("The database threshold must be above zero".to_string());
+        }
+        if !(self.warn_below_mb >= self.pause_below_mb
+            && self.pause_below_mb >= self.refuse_below_mb)
+        {
+            return Err(
+                "Thresholds must go down from warning to pausing jobs to refusing the database"
+                    .to_string(),
+            );
+        }
+        Ok(())
+    }
+
+    /// Level for the given free space
+    fn level(&self, free_bytes: u64) -> DiskLevel {
+        if free_bytes < self.refuse_below_mb * MB {
+            DiskLevel::Full
+        } else if free_bytes < self.pause_below_mb * MB {
+            DiskLevel::Critical
+        } else if free_bytes < self.warn_below_mb * MB {
+            DiskLevel::Low
+        } else {
+            DiskLevel::Ok
+        }
+    }
+}
+
+/// How far free space has fallen, mildest first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiskLevel {
+    Ok,
+    /// Warning only
+    Low,
+    /// Ingestion and backup jobs paused
+    Critical,
+    /// PostgreSQL won't be started
+    Full,
+}
+
+/// Free space on the app data volume
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DiskSpaceStatus {
+    pub level: DiskLevel,
+    pub free_bytes: u64,
+    pub total_bytes: u64,
+    pub path: String,
+}
+
+/// Latest status, kept in Tauri state
+#[derive(Default)]
+pub struct DiskMonitor {
+    status: Mutex<Option<DiskSpaceStatus>>,
+}
+
+/// Free and total bytes on the volume holding `path`
+pub fn free_space(path: &Path) -> std::io::Result<(u64, u64)> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStrExt;
+
+        let path = std::ffi::CString::new(path.as_os_str().as_bytes())
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+        let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+        if unsafe { libc::statvfs(path.as_ptr(), &mut stat) } != 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        // Field widths differ between platforms
+        #[allow(clippy::unnecessary_cast)]
+        let block = stat.f_frsize as u64;
+        #[allow(clippy::unnecessary_cast)]
+        Ok((stat.f_bavail as u64 * block, stat.f_blocks as u64 * block))
+    }
+
+    #[cfg(windows)]
+    {
+        use windows::core::HSTRING;
+        use windows::Win32::Storage::FileSystem::GetDiskFreeSpaceExW;
+
+        let mut free = 0u64;
+        let mut total = 0u64;
+        unsafe {
+            GetDiskFreeSpaceExW(
+                &HSTRING::from(path),
+                Some(&mut free),
+                Some(&mut total),
+                None,
+            )
+        }
+        .map_err(|e| std::io::Error::other(e.to_string()))?;
+        Ok((free, total))
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    {
+        let _ = path;
+        Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            "Free space isn't available on this platform",
+        ))
+    }
+}
+
+/// Measure free space now, record it, and announce a change of level
+pub fn check<R: Runtime>(app: &AppHandle<R>) -> Result<DiskSpaceStatus, AppError> {
+    let app_data_dir = app.path().app_data_dir()?;
+    let settings = DiskSpaceSettings::load(&app_data_dir);
+    // The directory may not exist on first launch; its parent is on the
+    // same volume
+    let probe = if app_data_dir.exists() {
+        app_data_dir.as_path()
+    } else {
+        app_data_dir.parent().unwrap_or(&app_data_dir)
+    };
+    let (free_bytes, total_bytes) = free_space(probe)?;
+    let status = DiskSpaceStatus {
+        level: settings.level(free_bytes),
+        free_bytes,
+        total_bytes,
+        path: app_data_dir.to_string_lossy().to_string(),
+    };
+
+    let previous = app
+        .state::<DiskMonitor>()
+        .status
+        .lock()
+        .replace(status.clone())
+        .map_or(DiskLevel::Ok, |previous| previous.level);
+    if status.level > previous {
+        tracing::warn!(
+            level = ?status.level,
+            free_mb = free_bytes / MB,
+            "Disk space is running out"
+        );
+        crate::events::emit_critical(app, "disk-space-low", &status);
+        let (severity, consequence) = match status.level {
+            DiskLevel::Full => (Severity::Critical, "The database won't start."),
+            DiskLevel::Critical => (Severity::Error, "Syncing and backups are paused."),
+            _ => (
+                Severity::Warning,
+                "Free up space to keep syncing and backups running.",
+            ),
+        };
+        crate::notifications::notify(
+            app,
+            Notice::new(
+                "disk_space",
+                severity,
+                "Disk space is running out",
+                format!("{} MB free. {}", free_bytes / MB, consequence),
+            ),
+        );
+    } else if status.level == DiskLevel::Ok && previous != DiskLevel::Ok {
+        tracing::info!(free_mb = free_bytes / MB, "Disk space recovered");
+        crate::events::emit_critical(app, "disk-space-recovered", &status);
+    }
+    Ok(status)
+}
+
+/// Why heavy writes are paused, if free space is below the pause threshold
+pub fn pause_reason(app: &AppHandle) -> Option<String> {
+    let monitor = app.try_state::<DiskMonitor>()?;
+    let status = monitor.status.lock();
+    let status = status.as_ref()?;
+    (status.level >= DiskLevel::Critical).then(|| {
+        format!(
+            "paused while the disk is almost full ({} MB free)",
+            status.free_bytes / MB
+        )
+    })
+}
+
+/// Refuse to start the database when the disk is almost full
+pub fn ensure_room_for_database<R: Runtime>(app: &AppHandle<R>) -> Result<(), AppError> {
+    let status = match check(app) {
+        Ok(status) => status,
+        Err(e) => {
+            // Not knowing is no reason to keep the database down
+            tracing::warn!("Couldn't check free disk space: {}", e);
+            return Ok(());
+        }
+    };
+    if status.level == DiskLevel::Full {
+        return Err(AppError::Io(format!(
+            "Only {} MB is free on the disk holding {}. PostgreSQL won't start until more \
+             space is free, so the database can't be damaged by a full disk.",
+            status.free_bytes / MB,
+            status.path
+        )));
+    }
+    Ok(())
+}
+
+/// Check free space for as long as the app runs
+pub fn start(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+            let checked = app.clone();
+            match tokio::task::spawn_blocking(move || check(&checked)).await {
+                Ok(Err(e)) => tracing::debug!("Disk space check failed: {}", e),
+                Err(e) => tracing::debug!("Disk space check panicked: {}", e),
+                Ok(Ok(_)) => {}
+            }
+        }
+    });
+}
+
+// ============================================================
+// Commands
+// ============================================================
+
+/// Get free space on the app data volume
+#[tauri::command]
+pub async fn get_disk_space(app: AppHandle) -> Result<DiskSpaceStatus, AppError> {
+    tokio::task::spawn_blocking(move || check(&app)).await?
+}
+
+/// Get the free-space thresholds
+#[tauri::command]
+pub async fn get_disk_space_settings(app: AppHandle) -> Result<DiskSpaceSettings, AppError> {
+    let app_data_dir = app.path().app_data_dir()?;
+    Ok(DiskSpaceSettings::load(&app_data_dir))
+}
+
+/// Update the free-space thresholds and re-check against them
+#[tauri::command]
+pub async fn set_disk_space_settings(
+    app: AppHandle,
+    settings: DiskSpaceSettings,
+) -> Result<DiskSpaceStatus, AppError> {
+    settings.validate()?;
+    let app_data_dir = app.path().app_data_dir()?;
+    settings.save(&app_data_dir)?;
+    tokio::task::spawn_blocking(move || check(&app)).await?
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_levels() {
+        let settings = DiskSpaceSettings::default();
+        assert_eq!(settings.level(100 * 1024 * MB), DiskLevel::Ok);
+        assert_eq!(settings.level(3 * 1024 * MB), DiskLevel::Low);
+        assert_eq!(settings.level(1024 * MB), DiskLevel::Critical);
+        assert_eq!(settings.level(100 * MB), DiskLevel::Full);
+        assert!(DiskLevel::Full > DiskLevel::Critical);
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(DiskSpaceSettings::default().validate().is_ok());
+        let out_of_order = DiskSpaceSettings {
+            warn_below_mb: 100,
+            pause_below_mb: 200,
+            refuse_below_mb: 50,
+        };
+        assert!(out_of_order.validate().is_err());
+        let zero = DiskSpaceSettings {
+            refuse_below_mb: 0,
+            ..DiskSpaceSettings::default()
+        };
+        assert!(zero.validate().is_err());
+    }
+
+    #[test]
+    fn test_free_space() {
+        let dir = TempDir::new().unwrap();
+        let (free, total) = free_space(dir.path()).unwrap();
+        assert!(total > 0);
+        assert!(free <= total);
+    }
+}
@@ -0,0 +1,589 @@
+//! IMAP email ingestion watcher.
+//!
+//! This module provides:
+//! - A background IMAP IDLE watcher for a configured folder
+//! - Sender/subject filters for which emails are forwarded
+//! - Conversion of email bodies to markdown and staging of attachments
+//! - Forwarding of matching emails to the backend inbox
+//!
+//! The IMAP password is kept in the keychain (see `keychain`), never in the
+//! watcher config file.
+
+use futures_util::StreamExt;
+use mail_parser::{MessageParser, MimeHeaders};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::config::{load_json, save_json_atomic};
+use crate::error::AppError;
+use crate::keychain;
+
+/// Backend endpoint that receives forwarded emails
+const INBOX_EMAIL_PATH: &str = "/inbox/email";
+
+/// Re-issue IDLE before the 30 minute server timeout (RFC 2177)
+const IDLE_TIMEOUT: Duration = Duration::from_secs(25 * 60);
+
+/// Longest wait between reconnect attempts
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(15 * 60);
+
+/// Attachments larger than this are not staged
+const MAX_ATTACHMENT_BYTES: usize = 25 * 1024 * 1024;
+
+/// Which emails are forwarded; empty filters match everything
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EmailFilter {
+    /// Case-insensitive substring of the sender address
+    pub from_contains: Option<String>,
+    /// Case-insensitive substring of the subject
+    pub subject_contains: Option<String>,
+}
+
+impl EmailFilter {
+    pub fn matches(&self, from: &str, subject: &str) -> bool {
+        let contains = |haystack: &str, needle: &Option<String>| match needle {
+            Some(n) if !n.trim().is_empty() => {
+                haystack.to_lowercase().contains(&n.trim().to_lowercase())
+            }
+            _ => true,
+        };
+        contains(from, &self.from_contains) && contains(subject, &self.subject_contains)
+    }
+}
+
+/// Watcher configuration (the password lives in the keychain)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EmailWatcherConfig {
+    pub enabled: bool,
+    pub host: String,
+    pub port: u16,
+    pub username: String,
+    /// Mailbox or Gmail label to watch
+    pub folder: String,
+    #[serde(default)]
+    pub filter: EmailFilter,
+}
+
+impl Default for EmailWatcherConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            host: String::new(),
+            port: 993,
+            username: String::new(),
+            folder: "INBOX".to_string(),
+            filter: EmailFilter::default(),
+        }
+    }
+}
+
+impl EmailWatcherConfig {
+    fn path(app_data_dir: &Path) -> PathBuf {
+        app_data_dir.join("email-watcher.json")
+    }
+
+    pub fn load(app_data_dir: &Path) -> Self {
+        load_json(&Self::path(app_data_dir)).unwrap_or_default()
+    }
+
+    pub fn save(&self, app_data_dir: &Path) -> Result<(), String> {
+        save_json_atomic(&Self::path(app_data_dir), self)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.enabled {
+            return Ok(());
+        }
+        if self.host.trim().is_empty() {
+            return Err("IMAP host is required".to_string());
+        }
+        if self.username.trim().is_empty() {
+            return Err("IMAP username is required".to_string());
+        }
+        if self.folder.trim().is_empty() {
+            return Err("IMAP folder is required".to_string());
+        }
+        Ok(())
+    }
+
+    /// Keychain account holding the password
+    pub fn keychain_account(&self) -> String {
+        format!("imap:{}@{}", self.username, self.host)
+    }
+}
+
+/// Position in the watched mailbox, so restarts only forward new mail
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct WatcherCursor {
+    uid_validity: u32,
+    last_uid: u32,
+}
+
+impl WatcherCursor {
+    fn path(app_data_dir: &Path) -> PathBuf {
+        app_data_dir.join("email-watcher-state.json")
+    }
+}
+
+/// Runtime status surfaced to the UI
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EmailWatcherStatus {
+    pub running: bool,
+    pub connected: bool,
+    pub forwarded_count: u64,
+    /// Last time the mailbox was checked (RFC 3339)
+    pub last_checked: Option<String>,
+    pub last_error: Option<String>,
+}
+
+/// Managed state for the watcher task
+#[derive(Default)]
+pub struct EmailWatcher {
+    task: Mutex<Option<tauri::async_runtime::JoinHandle<()>>>,
+    status: Mutex<EmailWatcherStatus>,
+}
+
+impl EmailWatcher {
+    fn update(&self, f: impl FnOnce(&mut EmailWatcherStatus)) {
+        f(&mut self.status.lock().unwrap());
+    }
+
+    pub fn status(&self) -> EmailWatcherStatus {
+        self.status.lock().unwrap().clone()
+    }
+}
+
+/// An email reduced to what the backend inbox needs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParsedEmail {
+    pub message_id: Option<String>,
+    pub from: String,
+    pub subject: String,
+    pub date: Option<String>,
+    /// Body converted to markdown
+    pub body_markdown: String,
+    /// Paths of staged attachment files
+    pub attachments: Vec<String>,
+}
+
+/// Parse a raw RFC 822 message, staging attachments under `staging_dir`
+fn parse_email(raw: &[u8], staging_dir: &Path) -> Option<ParsedEmail> {
+    let message = MessageParser::default().parse(raw)?;
+
+    let from = message
+        .from()
+        .and_then(|a| a.first())
+        .and_then(|a| a.address())
+        .unwrap_or_default()
+        .to_string();
+    let subject = message.subject().unwrap_or("(no subject)").to_string();
+
+    // mail-parser synthesizes a text body from HTML-only mail; convert the
+    // original HTML instead so headings, links, and emphasis survive
+    let has_plain_text = message
+        .text_part(0)
+        .is_some_and(|part| part.is_text() && !part.is_text_html());
+    let body_markdown = if has_plain_text {
+        message
+            .body_text(0)
+            .map(|text| text.trim().to_string())
+            .unwrap_or_default()
+    } else {
+        message
+            .body_html(0)
+            .and_then(|html| htmd::convert(&html).ok())
+            .unwrap_or_default()
+    };
+
+    let mut attachments = Vec::new();
+    for (index, part) in message.attachments().enumerate() {
+        let contents = part.contents();
+        if contents.is_empty() || contents.len() > MAX_ATTACHMENT_BYTES {
+            continue;
+        }
+        let name = part
+            .attachment_name()
+            .map(sanitize_filename)
+            .unwrap_or_else(|| format!("attachment-{}", index + 1));
+
+        if std::fs::create_dir_all(staging_dir).is_err() {
+            break;
+        }
+        let path = staging_dir.join(format!("{}-{}", index + 1, name));
+        if std::fs::write(&path, contents).is_ok() {
+            attachments.push(path.to_string_lossy().to_string());
+        }
+    }
+
+    Some(ParsedEmail {
+        message_id: message.message_id().map(str::to_string),
+        from,
+        subject,
+        date: message.date().map(|d| d.to_rfc3339()),
+        body_markdown,
+        attachments,
+    })
+}
+
+fn sanitize_filename(name: &str) -> String {
+    let cleaned: String = name
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '\0' => '_',
+            c => c,
+        })
+        .collect();
+    let cleaned = cleaned.trim_start_matches('.').trim();
+    if cleaned.is_empty() {
+        "attachment".to_string()
+    } else {
+        cleaned.to_string()
+    }
+}
+
+type ImapSession = async_imap::Session<tokio_native_tls::TlsStream<tokio::net::TcpStream>>;
+
+async fn connect(config: &EmailWatcherConfig, password: &str) -> Result<ImapSession, String> {
+    let tcp = tokio::net::TcpStream::connect((config.host.as_str(), config.port))
+        .await
+        .map_err(|e| format!("Failed to connect to {}: {}", config.host, e))?;
+
+    let connector = tokio_native_tls::native_tls::TlsConnector::new()
+        .map(tokio_native_tls::TlsConnector::from)
+        .map_err(|e| format!("Failed to create TLS connector: {}", e))?;
+    let tls = connector
+        .connect(&config.host, tcp)
+        .await
+        .map_err(|e| format!("TLS handshake failed: {}", e))?;
+
+    let mut client = async_imap::Client::new(tls);
+    client
+        .read_response()
+        .await
+        .map_err(|e| format!("Failed to read IMAP greeting: {}", e))?;
+
+    client
+        .login(&config.username, password)
+        .await
+        .map_err(|(e, _)| format!("IMAP login failed: {}", e))
+}
+
+/// Forward every message after the cursor, advancing it as each one succeeds
+async fn forward_new_messages(
+    app: &AppHandle,
+    session: &mut ImapSession,
+    config: &EmailWatcherConfig,
+    cursor: &mut WatcherCursor,
+    app_data_dir: &Path,
+) -> Result<(), String> {
+    let mut uids: Vec<u32> = session
+        .uid_search(format!("UID {}:*", cursor.last_uid + 1))
+        .await
+        .map_err(|e| format!("IMAP search failed: {}", e))?
+        .into_iter()
+        // `n:*` always returns the highest UID, even when it is below n
+        .filter(|uid| *uid > cursor.last_uid)
+        .collect();
+    uids.sort_unstable();
+
+    for uid in uids {
+        // Stop before staging anything; the cursor picks up here later
+        if let Some(reason) = crate::disk_space::pause_reason(app) {
+            return Err(format!("Email forwarding {}", reason));
+        }
+
+        let raw = {
+            let mut fetches = session
+                .uid_fetch(uid.to_string(), "(UID BODY.PEEK[])")
+                .await
+                .map_err(|e| format!("IMAP fetch failed: {}", e))?;
+            let mut raw = None;
+            while let Some(fetch) = fetches.next().await {
+                let fetch = fetch.map_err(|e| format!("IMAP fetch failed: {}", e))?;
+                if let Some(body) = fetch.body() {
+                    raw = Some(body.to_vec());
+                }
+            }
+            raw
+        };
+
+        let staging_dir = app_data_dir.join("email-inbox").join(uid.to_string());
+        if let Some(email) = raw.and_then(|raw| parse_email(&raw, &staging_dir)) {
+            if config.filter.matches(&email.from, &email.subject) {
+                crate::proxy::send_backend_request(
+                    app,
+                    "POST",
+                    INBOX_EMAIL_PATH,
+                    Some(&serde_json::json!(email)),
+                    None,
+                )
+                .await?;
+
+                tracing::info!("Forwarded email '{}' to inbox", email.subject);
+                app.state::<EmailWatcher>()
+                    .update(|s| s.forwarded_count += 1);
+                let _ = app.emit("email-forwarded", &email);
+            } else {
+                let _ = std::fs::remove_dir_all(&staging_dir);
+            }
+        }
+
+        cursor.last_uid = uid;
+        save_json_atomic(&WatcherCursor::path(app_data_dir), cursor)?;
+    }
+
+    app.state::<EmailWatcher>()
+        .update(|s| s.last_checked = Some(chrono::Local::now().to_rfc3339()));
+    Ok(())
+}
+
+/// Connect, catch up, then IDLE until the connection drops
+async fn watch_once(
+    app: &AppHandle,
+    config: &EmailWatcherConfig,
+    password: &str,
+    app_data_dir: &Path,
+) -> Result<(), String> {
+    let mut session = connect(config, password).await?;
+    let mailbox = session
+        .select(&config.folder)
+        .await
+        .map_err(|e| format!("Failed to open folder '{}': {}", config.folder, e))?;
+
+    let mut cursor: WatcherCursor =
+        load_json(&WatcherCursor::path(app_data_dir)).unwrap_or_default();
+    let uid_validity = mailbox.uid_validity.unwrap_or(0);
+
+    if cursor.uid_validity != uid_validity {
+        // First run or mailbox rebuilt: start from the newest message instead
+        // of forwarding the whole history
+        cursor = WatcherCursor {
+            uid_validity,
+            last_uid: mailbox.uid_next.unwrap_or(1).saturating_sub(1),
+        };
+        save_json_atomic(&WatcherCursor::path(app_data_dir), &cursor)?;
+    }
+
+    app.state::<EmailWatcher>().update(|s| {
+        s.connected = true;
+        s.last_error = None;
+    });
+
+    loop {
+        forward_new_messages(app, &mut session, config, &mut cursor, app_data_dir).await?;
+
+        let mut idle = session.idle();
+        idle.init()
+            .await
+            .map_err(|e| format!("IMAP IDLE failed: {}", e))?;
+        let (wait, _stop) = idle.wait_with_timeout(IDLE_TIMEOUT);
+        wait.await.map_err(|e| format!("IMAP IDLE failed: {}", e))?;
+        session = idle
+            .done()
+            .await
+            .map_err(|e| format!("IMAP IDLE failed: {}", e))?;
+    }
+}
+
+async fn run_watcher(app: AppHandle, config: EmailWatcherConfig, app_data_dir: PathBuf) {
+    let mut failures: u32 = 0;
+
+    loop {
+        let password = match keychain::get_secret(&app_data_dir, &config.keychain_account()) {
+            Ok(Some(password)) => password,
+            Ok(None) => {
+                app.state::<EmailWatcher>().update(|s| {
+                    s.running = false;
+                    s.last_error = Some("IMAP password not set".to_string());
+                });
+                return;
+            }
+            Err(e) => {
+                app.state::<EmailWatcher>().update(|s| {
+                    s.running = false;
+                    s.last_error = Some(e);
+                });
+                return;
+            }
+        };
+
+        if let Err(e) = watch_once(&app, &config, &password, &app_data_dir).await {
+            tracing::warn!("Email watcher disconnected: {}", e);
+            app.state::<EmailWatcher>().update(|s| {
+                s.connected = false;
+                s.last_error = Some(e);
+            });
+        }
+
+        failures = failures.saturating_add(1);
+        let delay = Duration::from_secs(5u64.saturating_mul(1 << failures.min(10)))
+            .min(MAX_RECONNECT_DELAY);
+        tokio::time::sleep(delay).await;
+    }
+}
+
+/// Stop any running watcher and start a new one if enabled
+pub fn restart(app: &AppHandle) {
+    let Ok(app_data_dir) = app.path().app_data_dir() else {
+        return;
+    };
+    let config = EmailWatcherConfig::load(&app_data_dir);
+    let watcher = app.state::<EmailWatcher>();
+
+    if let Some(task) = watcher.task.lock().unwrap().take() {
+        task.abort();
+    }
+    watcher.update(|s| {
+        s.running = config.enabled;
+        s.connected = false;
+    });
+
+    if config.enabled && config.validate().is_ok() {
+        tracing::info!(
+            "Starting email watcher for {} on {}",
+            config.folder,
+            config.host
+        );
+        let task = tauri::async_runtime::spawn(run_watcher(app.clone(), config, app_data_dir));
+        *watcher.task.lock().unwrap() = Some(task);
+    }
+}
+
+/// Get the email watcher configuration
+#[tauri::command]
+pub async fn get_email_watcher_config(app: AppHandle) -> Result<EmailWatcherConfig, AppError> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    Ok(EmailWatcherConfig::load(&app_data_dir))
+}
+
+/// Update the email watcher configuration and restart it
+///
+/// A provided password is stored in the keychain; `None` keeps the existing one.
+#[tauri::command]
+pub async fn set_email_watcher_config(
+    app: AppHandle,
+    config: EmailWatcherConfig,
+    password: Option<String>,
+) -> Result<(), AppError> {
+    config.validate()?;
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+
+    let previous = EmailWatcherConfig::load(&app_data_dir);
+    if previous.keychain_account() != config.keychain_account() && !previous.host.is_empty() {
+        let _ = keychain::delete_secret(&app_data_dir, &previous.keychain_account());
+    }
+    if let Some(password) = password.filter(|p| !p.is_empty()) {
+        keychain::set_secret(&app_data_dir, &config.keychain_account(), &password)?;
+    }
+
+    // A different mailbox invalidates the saved position
+    if previous.host != config.host
+        || previous.username != config.username
+        || previous.folder != config.folder
+    {
+        let _ = std::fs::remove_file(WatcherCursor::path(&app_data_dir));
+    }
+
+    config.save(&app_data_dir)?;
+    restart(&app);
+    Ok(())
+}
+
+/// Get the email watcher status
+#[tauri::command]
+pub async fn get_email_watcher_status(app: AppHandle) -> Result<EmailWatcherStatus, AppError> {
+    Ok(app.state::<EmailWatcher>().status())
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const PLAIN_EMAIL: &[u8] = b"From: Ada <ada@example.com>\r\n\
+Subject: Reading list\r\n\
+Message-ID: <1@example.com>\r\n\
+Date: Mon, 10 Mar 2025 09:00:00 +0000\r\n\
+Content-Type: text/plain\r\n\
+\r\n\
+Remember to read the paper.\r\n";
+
+    const HTML_WITH_ATTACHMENT: &[u8] = b"From: bob@example.com\r\n\
+Subject: Slides\r\n\
+MIME-Version: 1.0\r\n\
+Content-Type: multipart/mixed; boundary=\"b\"\r\n\
+\r\n\
+--b\r\n\
+Content-Type: text/html\r\n\
+\r\n\
+<h1>Deck</h1><p>See <strong>attached</strong></p>\r\n\
+--b\r\n\
+Content-Type: text/plain; name=\"../notes.txt\"\r\n\
+Content-Disposition: attachment; filename=\"../notes.txt\"\r\n\
+\r\n\
+hello\r\n\
+--b--\r\n";
+
+    #[test]
+    fn test_filter_matching() {
+        let filter = EmailFilter {
+            from_contains: Some("Example.com".to_string()),
+            subject_contains: Some("[note]".to_string()),
+        };
+        assert!(filter.matches("ada@example.com", "[Note] idea"));
+        assert!(!filter.matches("ada@other.org", "[note] idea"));
+        assert!(!filter.matches("ada@example.com", "hello"));
+        assert!(EmailFilter::default().matches("x", "y"));
+    }
+
+    #[test]
+    fn test_config_validation() {
+        assert!(EmailWatcherConfig::default().validate().is_ok());
+
+        let config = EmailWatcherConfig {
+            enabled: true,
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_parse_plain_email() {
+        let temp_dir = TempDir::new().unwrap();
+        let email = parse_email(PLAIN_EMAIL, temp_dir.path()).unwrap();
+
+        assert_eq!(email.from, "ada@example.com");
+        assert_eq!(email.subject, "Reading list");
+        assert_eq!(email.message_id.as_deref(), Some("1@example.com"));
+        assert_eq!(email.body_markdown, "Remember to read the paper.");
+        assert!(email.attachments.is_empty());
+    }
+
+    #[test]
+    fn test_parse_html_email_stages_attachment() {
+        let temp_dir = TempDir::new().unwrap();
+        let email = parse_email(HTML_WITH_ATTACHMENT, temp_dir.path()).unwrap();
+
+        assert!(email.body_markdown.contains("# Deck"));
+        assert!(email.body_markdown.contains("**attached**"));
+        assert_eq!(email.attachments.len(), 1);
+
+        let staged = Path::new(&email.attachments[0]);
+        assert!(staged.starts_with(temp_dir.path()));
+        assert_eq!(std::fs::read_to_string(staged).unwrap().trim(), "hello");
+    }
+
+    #[test]
+    fn test_sanitize_filename() {
+        assert_eq!(sanitize_filename("../etc/passwd"), "_etc_passwd");
+        assert_eq!(sanitize_filename("..."), "attachment");
+        assert_eq!(sanitize_filename("report.pdf"), "report.pdf");
+    }
+}
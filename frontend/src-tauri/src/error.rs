@@ -0,0 +1,234 @@
+//! Structured errors returned by commands.
+//!
+//! This module provides:
+//! - `AppError`, serialized to the frontend as `{ kind, message, remediation }`
+//! - Conversions from the error types used across the shell
+//!
+//! Internal helpers still return `Result<_, String>`; `?` turns those into
+//! `AppError::Internal`, and an `AppError` converts back to its message where
+//! a helper needs a `String`. Code that knows what went wrong should return a
+//! specific kind so the frontend can branch on it.
+
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+
+use crate::database::PostgresError;
+
+/// Error returned by every command
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum AppError {
+    /// A file, record or remote object doesn't exist
+    #[error("{0}")]
+    NotFound(String),
+    /// Arguments or settings failed validation
+    #[error("{0}")]
+    InvalidInput(String),
+    /// PostgreSQL or the backend isn't running yet
+    #[error("{0}")]
+    NotReady(String),
+    /// The embedded database failed
+    #[error("{0}")]
+    Database(String),
+    /// The backend answered with an error
+    #[error("{0}")]
+    Backend(String),
+    /// A remote service couldn't be reached
+    #[error("{0}")]
+    Network(String),
+    /// The OS denied access
+    #[error("{0}")]
+    Permission(String),
+    /// Reading or writing local files failed
+    #[error("{0}")]
+    Io(String),
+    /// Another operation is in progress or the data changed underneath
+    #[error("{0}")]
+    Conflict(String),
+    /// The user or a newer request cancelled the operation
+    #[error("{0}")]
+    Cancelled(String),
+    /// Anything else
+    #[error("{0}")]
+    Internal(String),
+}
+
+impl AppError {
+    /// Stable identifier the frontend branches on
+    pub fn kind(&self) -> &'static str {
+        match self {
+            AppError::NotFound(_) => "not_found",
+            AppError::InvalidInput(_) => "invalid_input",
+            AppError::NotReady(_) => "not_ready",
+            AppError::Database(_) => "database",
+            AppError::Backend(_) => "backend",
+            AppError::Network(_) => "network",
+            AppError::Permission(_) => "permission",
+            AppError::Io(_) => "io",
+            AppError::Conflict(_) => "conflict",
+            AppError::Cancelled(_) => "cancelled",
+            AppError::Internal(_) => "internal",
+        }
+    }
+
+    /// What the user can do about it, if anything
+    pub fn remediation(&self) -> Option<&'static str> {
+        match self {
+            AppError::NotFound(_) => None,
+            AppError::InvalidInput(_) => Some("Check the values you entered and try again."),
+            AppError::NotReady(_) => Some(
+                "Wait for Second Brain to finish starting, or restart services from the tray menu.",
+            ),
+            AppError::Database(_) => Some(
+                "Restart the database from the tray menu. If it keeps failing, export diagnostics and report a bug.",
+            ),
+            AppError::Backend(_) => Some("Restart the backend from the tray menu and try again."),
+            AppError::Network(_) => Some("Check your internet connection and try again."),
+            AppError::Permission(_) => {
+                Some("Grant Second Brain access in System Settings > Privacy & Security.")
+            }
+            AppError::Io(_) => Some(
+                "Check that the disk has free space and Second Brain can write to its data folder.",
+            ),
+            AppError::Conflict(_) => Some("Wait for the current operation to finish and try again."),
+            AppError::Cancelled(_) => None,
+            AppError::Internal(_) => {
+                Some("If this keeps happening, export diagnostics and report a bug.")
+            }
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            AppError::NotFound(m)
+            | AppError::InvalidInput(m)
+            | AppError::NotReady(m)
+            | AppError::Database(m)
+            | AppError::Backend(m)
+            | AppError::Network(m)
+            | AppError::Permission(m)
+            | AppError::Io(m)
+            | AppError::Conflict(m)
+            | AppError::Cancelled(m)
+            | AppError::Internal(m) => m,
+        }
+    }
+}
+
+impl Serialize for AppError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("AppError", 3)?;
+        state.serialize_field("kind", self.kind())?;
+        state.serialize_field("message", self.message())?;
+        state.serialize_field("remediation", &self.remediation())?;
+        state.end()
+    }
+}
+
+impl From<AppError> for String {
+    fn from(err: AppError) -> String {
+        err.to_string()
+    }
+}
+
+impl From<String> for AppError {
+    fn from(message: String) -> Self {
+        AppError::Internal(message)
+    }
+}
+
+impl From<&str> for AppError {
+    fn from(message: &str) -> Self {
+        AppError::Internal(message.to_string())
+    }
+}
+
+impl From<std::io::Error> for AppError {
+    fn from(err: std::io::Error) -> Self {
+        match err.kind() {
+            std::io::ErrorKind::NotFound => AppError::NotFound(err.to_string()),
+            std::io::ErrorKind::PermissionDenied => AppError::Permission(err.to_string()),
+            _ => AppError::Io(err.to_string()),
+        }
+    }
+}
+
+impl From<serde_json::Error> for AppError {
+    fn from(err: serde_json::Error) -> Self {
+        AppError::InvalidInput(err.to_string())
+    }
+}
+
+impl From<reqwest::Error> for AppError {
+    fn from(err: reqwest::Error) -> Self {
+        if err.is_connect() || err.is_timeout() {
+            AppError::Network(err.to_string())
+        } else {
+            AppError::Backend(err.to_string())
+        }
+    }
+}
+
+impl From<tauri::Error> for AppError {
+    fn from(err: tauri::Error) -> Self {
+        AppError::Internal(err.to_string())
+    }
+}
+
+impl From<tokio::task::JoinError> for AppError {
+    fn from(err: tokio::task::JoinError) -> Self {
+        AppError::Internal(format!("Task panicked: {}", err))
+    }
+}
+
+impl From<PostgresError> for AppError {
+    fn from(err: PostgresError) -> Self {
+        match err {
+            PostgresError::NotInitialized => AppError::NotReady(err.to_string()),
+            PostgresError::PortConflict { .. } => AppError::Conflict(err.to_string()),
+            PostgresError::ConfigError(_) => AppError::InvalidInput(err.to_string()),
+            _ => AppError::Database(err.to_string()),
+        }
+    }
+}
+
+/// Error for commands that need the embedded database before it has started
+pub fn database_not_running() -> AppError {
+    AppError::NotReady("Database is not running".to_string())
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serializes_kind_message_and_remediation() {
+        let json = serde_json::to_value(AppError::NotReady("Backend not ready".into())).unwrap();
+        assert_eq!(json["kind"], "not_ready");
+        assert_eq!(json["message"], "Backend not ready");
+        assert!(json["remediation"].as_str().unwrap().contains("tray menu"));
+
+        let json = serde_json::to_value(AppError::Cancelled("Stopped".into())).unwrap();
+        assert!(json["remediation"].is_null());
+    }
+
+    #[test]
+    fn test_conversions() {
+        assert_eq!(
+            AppError::from("boom".to_string()),
+            AppError::Internal("boom".to_string())
+        );
+        let missing = std::io::Error::new(std::io::ErrorKind::NotFound, "gone");
+        assert_eq!(AppError::from(missing).kind(), "not_found");
+        let denied = std::io::Error::new(std::io::ErrorKind::PermissionDenied, "no");
+        assert_eq!(AppError::from(denied).kind(), "permission");
+        assert_eq!(
+            AppError::from(PostgresError::NotInitialized).kind(),
+            "not_ready"
+        );
+        assert_eq!(AppError::Database("x".into()).to_string(), "x");
+    }
+}
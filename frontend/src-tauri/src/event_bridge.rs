@@ -0,0 +1,505 @@
+//! WebSocket bridge relaying app events to local tools.
+//!
+//! This module provides:
+//! - An opt-in WebSocket server on 127.0.0.1 that forwards selected Tauri
+//!   events (startup, service state, backend health, note-created, user
+//!   presence, clock skew and low disk space by default)
+//! - Authentication with the control API's current token, given as a bearer
+//!   header or, for browser dashboards, a `token` query parameter; a rotated
+//!   token applies to the next connection
+//! - A snapshot of service state and backend health sent on connect, so
+//!   clients don't wait for the next change
+//! - Only status events while the app is locked; events that may carry note
+//!   content, such as note-created, are dropped until it is unlocked
+//!
+//! Messages are JSON text frames: `{"type": "event", "event", "payload"}`,
+//! plus `hello` on connect and `lagged` when a slow client missed events.
+//! Client messages other than ping and close are ignored.
+
+use futures_util::{SinkExt, StreamExt};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::{AppHandle, EventId, Listener, Manager};
+use tokio::io::AsyncWriteExt;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::broadcast;
+use tokio_tungstenite::tungstenite::handshake::derive_accept_key;
+use tokio_tungstenite::tungstenite::protocol::{Role, WebSocketConfig};
+use tokio_tungstenite::tungstenite::{self, Message};
+use tokio_tungstenite::WebSocketStream;
+
+use crate::config::{load_json, save_json_atomic};
+use crate::control_api::{self, HttpRequest};
+use crate::error::AppError;
+
+/// Default port for the event bridge
+const DEFAULT_PORT: u16 = 47823;
+
+/// Events relayed when the settings don't list any
+const DEFAULT_EVENTS: &[&str] = &[
+    "startup-event",
+    "service-state",
+    "backend-health",
+    "api-url-changed",
+    "note-created",
+    "user-idle",
+    "user-active",
+    "clock-skew-detected",
+    "disk-space-low",
+    "usage-budget-alert",
+];
+
+/// Events relayed while the app is locked, none of which carry note content
+const RELAYED_WHILE_LOCKED: &[&str] = &[
+    "startup-event",
+    "service-state",
+    "backend-health",
+    "api-url-changed",
+    "user-idle",
+    "user-active",
+    "clock-skew-detected",
+    "disk-space-low",
+    "usage-budget-alert",
+];
+
+/// Events buffered per client before it is reported as lagging
+const CHANNEL_CAPACITY: usize = 256;
+
+/// Largest accepted client message
+const MAX_CLIENT_MESSAGE: usize = 64 * 1024;
+
+/// Interval between pings that keep idle connections open
+const PING_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Timeout for the opening handshake
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Event bridge settings
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EventBridgeSettings {
+    pub enabled: bool,
+    pub port: u16,
+    /// Event names to relay
+    pub events: Vec<String>,
+}
+
+impl Default for EventBridgeSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: DEFAULT_PORT,
+            events: DEFAULT_EVENTS.iter().map(|e| e.to_string()).collect(),
+        }
+    }
+}
+
+impl EventBridgeSettings {
+    fn path(app_data_dir: &Path) -> PathBuf {
+        app_data_dir.join("event-bridge.json")
+    }
+
+    pub fn load(app_data_dir: &Path) -> Self {
+        load_json(&Self::path(app_data_dir)).unwrap_or_default()
+    }
+
+    pub fn save(&self, app_data_dir: &Path) -> Result<(), String> {
+        save_json_atomic(&Self::path(app_data_dir), self)
+    }
+
+    fn validate(&self) -> Result<(), AppError> {
+        if self.port < 1024 {
+            return Err(AppError::InvalidInput(format!(
+                "Port {} is reserved; choose 1024 or above",
+                self.port
+            )));
+        }
+        let valid_name = |name: &String| {
+            !name.is_empty()
+                && name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | ':' | '/'))
+        };
+        if let Some(name) = self.events.iter().find(|name| !valid_name(name)) {
+            return Err(AppError::InvalidInput(format!(
+                "Invalid event name '{}'",
+                name
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// A message sent to clients
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum BridgeMessage {
+    Hello {
+        events: Vec<String>,
+    },
+    Event {
+        event: String,
+        payload: serde_json::Value,
+    },
+    Lagged {
+        skipped: u64,
+    },
+}
+
+/// Listener state, kept in Tauri state
+#[derive(Default)]
+pub struct EventBridge {
+    server: Mutex<Option<tauri::async_runtime::JoinHandle<()>>>,
+    listeners: Mutex<Vec<EventId>>,
+}
+
+// ============================================================
+// Connections
+// ============================================================
+
+/// Check the upgrade request and token; returns the client key
+fn check_upgrade(request: &HttpRequest, token: &str) -> Result<String, AppError> {
+    if request.method != "GET" || request.path.split('?').next() != Some("/v1/events") {
+        return Err(AppError::NotFound(format!("No route for {}", request.path)));
+    }
+    if !request
+        .header("host")
+        .is_some_and(control_api::is_local_host)
+    {
+        return Err(AppError::Permission("Host must be localhost".to_string()));
+    }
+    let upgrade = request
+        .header("upgrade")
+        .is_some_and(|value| value.eq_ignore_ascii_case("websocket"));
+    let key = request.header("sec-websocket-key").unwrap_or_default();
+    if !upgrade || key.is_empty() {
+        return Err(AppError::InvalidInput(
+            "Expected a WebSocket upgrade".to_string(),
+        ));
+    }
+
+    let from_header = request
+        .header("authorization")
+        .and_then(|value| value.strip_prefix("Bearer "));
+    let from_query = request
+        .path
+        .split_once('?')
+        .and_then(|(_, query)| query.split('&').find_map(|p| p.strip_prefix("token=")));
+    let presented = from_header.or(from_query).unwrap_or_default().trim();
+    if token.is_empty() || !control_api::constant_time_eq(presented.as_bytes(), token.as_bytes()) {
+        return Err(AppError::Permission("Missing or invalid token".to_string()));
+    }
+    Ok(key.to_string())
+}
+
+impl BridgeMessage {
+    /// Text message carrying this message
+    fn to_message(&self) -> Message {
+        Message::Text(serde_json::to_string(self).unwrap_or_default())
+    }
+}
+
+fn socket_error(e: tungstenite::Error) -> AppError {
+    AppError::Network(format!("WebSocket error: {}", e))
+}
+
+/// Current service state and backend health, as events
+fn snapshot(app: &AppHandle) -> Vec<BridgeMessage> {
+    let mut messages = Vec::new();
+    if let Some(services) = app.try_state::<crate::services::ServiceManager>() {
+        if let Ok(payload) = serde_json::to_value(services.state()) {
+            messages.push(BridgeMessage::Event {
+                event: "service-state".to_string(),
+                payload,
+            });
+        }
+    }
+    if let Some(health) = app.try_state::<crate::health::HealthMonitor>() {
+        if let Ok(payload) = serde_json::to_value(health.snapshot()) {
+            messages.push(BridgeMessage::Event {
+                event: "backend-health".to_string(),
+                payload,
+            });
+        }
+    }
+    messages
+}
+
+async fn handle_connection(
+    app: AppHandle,
+    mut stream: TcpStream,
+    events: Vec<String>,
+    mut relay: broadcast::Receiver<(String, serde_json::Value)>,
+) -> Result<(), AppError> {
+    let request = tokio::time::timeout(HANDSHAKE_TIMEOUT, control_api::read_request(&mut stream))
+        .await
+        .map_err(|_| AppError::Network("Handshake timed out".to_string()))??;
+    let token = control_api::current_token(&app)?;
+    let key = match check_upgrade(&request, &token) {
+        Ok(key) => key,
+        Err(e) => {
+            let (code, reason) = match e {
+                AppError::NotFound(_) => (404, "Not Found"),
+                AppError::Permission(_) => (403, "Forbidden"),
+                _ => (400, "Bad Request"),
+            };
+            let response = format!(
+                "HTTP/1.1 {} {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                code, reason
+            );
+            stream.write_all(response.as_bytes()).await?;
+            return Err(e);
+        }
+    };
+    let response = format!(
+        "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+        derive_accept_key(key.trim().as_bytes())
+    );
+    stream.write_all(response.as_bytes()).await?;
+
+    let config = WebSocketConfig {
+        max_message_size: Some(MAX_CLIENT_MESSAGE),
+        max_frame_size: Some(MAX_CLIENT_MESSAGE),
+        ..Default::default()
+    };
+    let mut socket = WebSocketStream::from_raw_socket(stream, Role::Server, Some(config)).await;
+    socket
+        .send(BridgeMessage::Hello { events }.to_message())
+        .await
+        .map_err(socket_error)?;
+    for message in snapshot(&app) {
+        socket
+            .send(message.to_message())
+            .await
+            .map_err(socket_error)?;
+    }
+
+    let mut ping = tokio::time::interval(PING_INTERVAL);
+    ping.tick().await;
+    loop {
+        let message = tokio::select! {
+            received = relay.recv() => match received {
+                Ok((event, payload)) => BridgeMessage::Event { event, payload }.to_message(),
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    BridgeMessage::Lagged { skipped }.to_message()
+                }
+                Err(broadcast::error::RecvError::Closed) => return Ok(()),
+            },
+            // The socket answers pings and closes itself; other client
+            // messages are ignored
+            incoming = socket.next() => match incoming {
+                Some(Ok(_)) => continue,
+                Some(Err(tungstenite::Error::ConnectionClosed)) | None => return Ok(()),
+                Some(Err(e)) => return Err(socket_error(e)),
+            },
+            _ = ping.tick() => Message::Ping(Vec::new()),
+        };
+        socket.send(message).await.map_err(socket_error)?;
+    }
+}
+
+/// Stop the server and listeners, then start them again if enabled
+pub fn apply_settings(app: &AppHandle, settings: &EventBridgeSettings) -> Result<(), AppError> {
+    let bridge = app.state::<EventBridge>();
+    if let Some(task) = bridge.server.lock().take() {
+        task.abort();
+    }
+    for id in bridge.listeners.lock().drain(..) {
+        app.unlisten(id);
+    }
+    if !settings.enabled {
+        return Ok(());
+    }
+
+    let (relay, _) = broadcast::channel(CHANNEL_CAPACITY);
+    let mut listeners = bridge.listeners.lock();
+    for name in &settings.events {
+        let relay = relay.clone();
+        let event = name.clone();
+        let app_handle = app.clone();
+        let is_status = RELAYED_WHILE_LOCKED.contains(&name.as_str());
+        listeners.push(app.listen_any(name.clone(), move |received| {
+            if !is_status && crate::app_lock::is_locked(&app_handle) {
+                return;
+            }
+            let payload = serde_json::from_str(received.payload()).unwrap_or_default();
+            // No receivers just means no client is connected
+            let _ = relay.send((event.clone(), payload));
+        }));
+    }
+    drop(listeners);
+
+    let port = settings.port;
+    let events = settings.events.clone();
+    let app_for_task = app.clone();
+    let task = tauri::async_runtime::spawn(async move {
+        let listener = match TcpListener::bind(("127.0.0.1", port)).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                tracing::warn!("Event bridge could not listen on port {}: {}", port, e);
+                return;
+            }
+        };
+        tracing::info!("Event bridge listening on 127.0.0.1:{}", port);
+        while let Ok((stream, _)) = listener.accept().await {
+            let app = app_for_task.clone();
+            let (events, receiver) = (events.clone(), relay.subscribe());
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = handle_connection(app, stream, events, receiver).await {
+                    tracing::debug!("Event bridge connection ended: {}", e);
+                }
+            });
+        }
+    });
+    *bridge.server.lock() = Some(task);
+    Ok(())
+}
+
+/// Load persisted settings and start the bridge if enabled
+pub fn start(app: &AppHandle) {
+    app.manage(EventBridge::default());
+    if let Ok(app_data_dir) = app.path().app_data_dir() {
+        if let Err(e) = apply_settings(app, &EventBridgeSettings::load(&app_data_dir)) {
+            tracing::warn!("Event bridge not started: {}", e);
+        }
+    }
+}
+
+// ============================================================
+// Commands
+// ============================================================
+
+/// Get the event bridge settings
+#[tauri::command]
+pub async fn get_event_bridge_settings(app: AppHandle) -> Result<EventBridgeSettings, AppError> {
+    Ok(EventBridgeSettings::load(&app.path().app_data_dir()?))
+}
+
+/// Enable, disable or reconfigure the event bridge
+#[tauri::command]
+pub async fn set_event_bridge_settings(
+    app: AppHandle,
+    settings: EventBridgeSettings,
+) -> Result<(), AppError> {
+    settings.validate()?;
+    settings.save(&app.path().app_data_dir()?)?;
+    apply_settings(&app, &settings)
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn upgrade(path: &str, headers: &[(&str, &str)]) -> HttpRequest {
+        let mut all = vec![
+            ("Host".to_string(), "127.0.0.1:47823".to_string()),
+            ("Upgrade".to_string(), "websocket".to_string()),
+            (
+                "Sec-WebSocket-Key".to_string(),
+                "dGhlIHNhbXBsZSBub25jZQ==".to_string(),
+            ),
+        ];
+        all.extend(headers.iter().map(|(k, v)| (k.to_string(), v.to_string())));
+        HttpRequest {
+            method: "GET".to_string(),
+            path: path.to_string(),
+            headers: all,
+            body: Vec::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rejects_oversized_client_messages() {
+        let (server, client) = tokio::io::duplex(4 * MAX_CLIENT_MESSAGE);
+        let config = WebSocketConfig {
+            max_message_size: Some(MAX_CLIENT_MESSAGE),
+            max_frame_size: Some(MAX_CLIENT_MESSAGE),
+            ..Default::default()
+        };
+        let mut server = WebSocketStream::from_raw_socket(server, Role::Server, Some(config)).await;
+        let mut client = WebSocketStream::from_raw_socket(client, Role::Client, None).await;
+
+        client.send(Message::Text("hi".to_string())).await.unwrap();
+        assert_eq!(
+            server.next().await.unwrap().unwrap(),
+            Message::Text("hi".to_string())
+        );
+        client
+            .send(Message::Binary(vec![0; MAX_CLIENT_MESSAGE + 1]))
+            .await
+            .unwrap();
+        assert!(server.next().await.unwrap().is_err());
+    }
+
+    #[test]
+    fn test_check_upgrade() {
+        let token = "tok";
+        assert!(check_upgrade(&upgrade("/v1/events?token=tok", &[]), token).is_ok());
+        assert!(check_upgrade(
+            &upgrade("/v1/events", &[("Authorization", "Bearer tok")]),
+            token
+        )
+        .is_ok());
+        // Browser dashboards send an Origin; the token still decides
+        assert!(check_upgrade(
+            &upgrade(
+                "/v1/events?token=tok",
+                &[("Origin", "http://localhost:3000")]
+            ),
+            token
+        )
+        .is_ok());
+
+        let kind = |request: HttpRequest| check_upgrade(&request, token).err().map(|e| e.kind());
+        assert_eq!(
+            kind(upgrade("/v1/events?token=nope", &[])),
+            Some("permission")
+        );
+        assert_eq!(
+            kind(upgrade("/v1/status?token=tok", &[])),
+            Some("not_found")
+        );
+        let mut no_upgrade = upgrade("/v1/events?token=tok", &[]);
+        no_upgrade.headers.retain(|(k, _)| k != "Upgrade");
+        assert_eq!(kind(no_upgrade), Some("invalid_input"));
+        let mut remote = upgrade("/v1/events?token=tok", &[]);
+        remote.headers[0].1 = "attacker.example".to_string();
+        assert_eq!(kind(remote), Some("permission"));
+    }
+
+    #[test]
+    fn test_settings_validation() {
+        assert!(EventBridgeSettings::default().validate().is_ok());
+        let bad = EventBridgeSettings {
+            events: vec!["ok".to_string(), "bad event".to_string()],
+            ..Default::default()
+        };
+        assert!(bad.validate().is_err());
+    }
+
+    #[test]
+    fn test_content_events_are_withheld_while_locked() {
+        assert!(!RELAYED_WHILE_LOCKED.contains(&"note-created"));
+        assert!(RELAYED_WHILE_LOCKED
+            .iter()
+            .all(|event| DEFAULT_EVENTS.contains(event)));
+    }
+
+    #[test]
+    fn test_message_shape() {
+        let message = BridgeMessage::Event {
+            event: "note-created".to_string(),
+            payload: serde_json::json!({ "id": "n1" }),
+        };
+        assert_eq!(
+            serde_json::to_value(&message).unwrap(),
+            serde_json::json!({ "type": "event", "event": "note-created", "payload": { "id": "n1" } })
+        );
+    }
+}
@@ -0,0 +1,276 @@
+//! Rate-limited event emission to the webview.
+//!
+//! This module provides:
+//! - Coalesced events, where only the latest payload per key is kept
+//!   (startup progress, retry countdowns)
+//! - Batched events, where payloads are collected and sent together as
+//!   `{ items, dropped }` (service log lines)
+//! - Critical events, emitted immediately after anything still pending so
+//!   ordering with earlier progress is preserved
+//!
+//! Pending events are flushed at most every `FLUSH_INTERVAL`, so a chatty
+//! backend or a tight health-check loop can't flood the webview during
+//! startup.
+
+use serde::Serialize;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Runtime};
+use tokio::sync::Notify;
+
+/// Shortest time between two flushes
+const FLUSH_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Most items kept per batched event between flushes; older ones are dropped
+const MAX_BATCH: usize = 500;
+
+static DISPATCHER: OnceLock<EventDispatcher> = OnceLock::new();
+
+/// Events waiting for the next flush, in first-queued order
+#[derive(Debug, Default)]
+struct Pending {
+    events: Vec<PendingEvent>,
+}
+
+#[derive(Debug)]
+enum PendingEvent {
+    Latest {
+        event: &'static str,
+        key: String,
+        payload: serde_json::Value,
+    },
+    Batch {
+        event: &'static str,
+        items: Vec<serde_json::Value>,
+        dropped: usize,
+    },
+}
+
+/// Payload of a batched event
+#[derive(Debug, Serialize)]
+struct BatchPayload {
+    items: Vec<serde_json::Value>,
+    /// Items discarded because more than `MAX_BATCH` arrived between flushes
+    dropped: usize,
+}
+
+impl Pending {
+    fn coalesce(&mut self, event: &'static str, key: String, payload: serde_json::Value) {
+        for pending in &mut self.events {
+            if let PendingEvent::Latest {
+                event: e,
+                key: k,
+                payload: p,
+            } = pending
+            {
+                if *e == event && *k == key {
+                    *p = payload;
+                    return;
+                }
+            }
+        }
+        self.events.push(PendingEvent::Latest {
+            event,
+            key,
+            payload,
+        });
+    }
+
+    fn batch(&mut self, event: &'static str, item: serde_json::Value) {
+        for pending in &mut self.events {
+            if let PendingEvent::Batch {
+                event: e,
+                items,
+                dropped,
+            } = pending
+            {
+                if *e == event {
+                    if items.len() >= MAX_BATCH {
+                        items.remove(0);
+                        *dropped += 1;
+                    }
+                    items.push(item);
+                    return;
+                }
+            }
+        }
+        self.events.push(PendingEvent::Batch {
+            event,
+            items: vec![item],
+            dropped: 0,
+        });
+    }
+
+    /// Take everything queued as (event, payload) pairs
+    fn take(&mut self) -> Vec<(&'static str, serde_json::Value)> {
+        std::mem::take(&mut self.events)
+            .into_iter()
+            .map(|pending| match pending {
+                PendingEvent::Latest { event, payload, .. } => (event, payload),
+                PendingEvent::Batch {
+                    event,
+                    items,
+                    dropped,
+                } => (
+                    event,
+                    serde_json::to_value(BatchPayload { items, dropped })
+                        .unwrap_or(serde_json::Value::Null),
+                ),
+            })
+            .collect()
+    }
+}
+
+/// Queues events and flushes them at a capped rate
+pub struct EventDispatcher {
+    app: AppHandle,
+    pending: Arc<parking_lot::Mutex<Pending>>,
+    notify: Arc<Notify>,
+}
+
+impl EventDispatcher {
+    fn spawn(app: &AppHandle) -> Self {
+        let pending = Arc::new(parking_lot::Mutex::new(Pending::default()));
+        let notify = Arc::new(Notify::new());
+        let (task_app, task_pending, task_notify) = (app.clone(), pending.clone(), notify.clone());
+        tauri::async_runtime::spawn(async move {
+            loop {
+                task_notify.notified().await;
+                flush_pending(&task_app, &task_pending);
+                tokio::time::sleep(FLUSH_INTERVAL).await;
+            }
+        });
+        Self {
+            app: app.clone(),
+            pending,
+            notify,
+        }
+    }
+
+    fn enqueue(&self, change: impl FnOnce(&mut Pending)) {
+        change(&mut self.pending.lock());
+        self.notify.notify_one();
+    }
+
+    fn critical(&self, event: &str, payload: serde_json::Value) {
+        // Hold the lock while emitting so a concurrent flush can't reorder events
+        let mut pending = self.pending.lock();
+        for (queued, queued_payload) in pending.take() {
+            emit_now(&self.app, queued, &queued_payload);
+        }
+        emit_now(&self.app, event, &payload);
+    }
+}
+
+fn flush_pending(app: &AppHandle, pending: &parking_lot::Mutex<Pending>) {
+    let mut pending = pending.lock();
+    for (event, payload) in pending.take() {
+        emit_now(app, event, &payload);
+    }
+}
+
+fn emit_now<R: Runtime>(app: &AppHandle<R>, event: &str, payload: &serde_json::Value) {
+    if let Err(e) = app.emit(event, payload) {
+        tracing::warn!("Failed to emit {}: {}", event, e);
+    }
+}
+
+fn to_payload<T: Serialize>(payload: &T) -> Option<serde_json::Value> {
+    match serde_json::to_value(payload) {
+        Ok(value) => Some(value),
+        Err(e) => {
+            tracing::warn!("Failed to serialize event payload: {}", e);
+            None
+        }
+    }
+}
+
+/// Start the dispatcher; events sent before this are emitted directly
+pub fn init(app: &AppHandle) {
+    if DISPATCHER.set(EventDispatcher::spawn(app)).is_err() {
+        tracing::warn!("Event dispatcher already initialized");
+    }
+}
+
+/// Emit a state transition immediately, after anything still pending
+pub fn emit_critical<R: Runtime, T: Serialize>(
+    app: &AppHandle<R>,
+    event: &'static str,
+    payload: &T,
+) {
+    let Some(payload) = to_payload(payload) else {
+        return;
+    };
+    match DISPATCHER.get() {
+        Some(dispatcher) => dispatcher.critical(event, payload),
+        None => emit_now(app, event, &payload),
+    }
+}
+
+/// Queue an event, replacing any pending one with the same event and key
+pub fn emit_coalesced<R: Runtime, T: Serialize>(
+    app: &AppHandle<R>,
+    event: &'static str,
+    key: impl Into<String>,
+    payload: &T,
+) {
+    let Some(payload) = to_payload(payload) else {
+        return;
+    };
+    match DISPATCHER.get() {
+        Some(dispatcher) => dispatcher.enqueue(|p| p.coalesce(event, key.into(), payload)),
+        None => emit_now(app, event, &payload),
+    }
+}
+
+/// Queue an item to be sent with others in the next batch of `event`
+///
+/// Needs no app handle, so output readers without one can use it; items are
+/// discarded until the dispatcher has started.
+pub fn emit_batched<T: Serialize>(event: &'static str, item: &T) {
+    if let (Some(dispatcher), Some(item)) = (DISPATCHER.get(), to_payload(item)) {
+        dispatcher.enqueue(|p| p.batch(event, item));
+    }
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_coalesce_keeps_latest_per_key_in_order() {
+        let mut pending = Pending::default();
+        pending.coalesce("startup-progress", "backend".into(), json!(1));
+        pending.coalesce("startup-progress", "postgres".into(), json!(10));
+        pending.coalesce("startup-progress", "backend".into(), json!(2));
+
+        let flushed = pending.take();
+        assert_eq!(
+            flushed,
+            vec![
+                ("startup-progress", json!(2)),
+                ("startup-progress", json!(10)),
+            ]
+        );
+        assert!(pending.take().is_empty());
+    }
+
+    #[test]
+    fn test_batch_caps_items_and_counts_dropped() {
+        let mut pending = Pending::default();
+        for i in 0..MAX_BATCH + 3 {
+            pending.batch("service-log", json!(i));
+        }
+        let flushed = pending.take();
+        assert_eq!(flushed.len(), 1);
+        let payload = &flushed[0].1;
+        assert_eq!(payload["dropped"], 3);
+        assert_eq!(payload["items"].as_array().unwrap().len(), MAX_BATCH);
+        assert_eq!(payload["items"][0], 3);
+    }
+}
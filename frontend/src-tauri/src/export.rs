@@ -0,0 +1,453 @@
+//! Selective export of notes to files.
+//!
+//! This module provides:
+//! - Filters by tag, notebook (folder) and last-updated date range
+//! - One file per note as markdown, JSON or PDF (via the print renderer)
+//! - Folder structure mirroring notebooks, with collision-free file names
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+
+use crate::error::AppError;
+use crate::jobs::JobContext;
+use crate::obsidian::sanitize_component;
+use crate::streams::StreamRegistry;
+
+/// Backend endpoint returning all notes with full content
+const EXPORT_NOTES_PATH: &str = "/notes/for-export";
+
+/// File format for exported notes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[default]
+    #[serde(rename = "md")]
+    Markdown,
+    Json,
+    Pdf,
+}
+
+impl ExportFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "md",
+            ExportFormat::Json => "json",
+            ExportFormat::Pdf => "pdf",
+        }
+    }
+}
+
+/// Which notes to export; empty fields match everything
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportFilter {
+    /// Notes with any of these tags (case-insensitive)
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Notes in this folder or its subfolders
+    pub notebook: Option<String>,
+    /// Updated on or after this date (`YYYY-MM-DD` or RFC 3339)
+    pub updated_from: Option<String>,
+    /// Updated on or before this date (`YYYY-MM-DD` or RFC 3339)
+    pub updated_to: Option<String>,
+    #[serde(default)]
+    pub include_archived: bool,
+}
+
+/// A note as returned by the export endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ExportNote {
+    id: String,
+    title: String,
+    content: String,
+    #[serde(default)]
+    tags: Vec<String>,
+    folder: Option<String>,
+    created_at: String,
+    updated_at: String,
+    #[serde(default)]
+    is_archived: bool,
+}
+
+/// Result of an export
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExportSummary {
+    pub destination: String,
+    pub exported: usize,
+    /// Notes that didn't match the filter
+    pub skipped: usize,
+}
+
+type Timestamp = chrono::DateTime<chrono::Utc>;
+
+/// Parse a date bound; plain dates cover the whole day
+fn parse_bound(value: &str, end_of_day: bool) -> Result<Timestamp, String> {
+    if let Ok(timestamp) = chrono::DateTime::parse_from_rfc3339(value) {
+        return Ok(timestamp.with_timezone(&chrono::Utc));
+    }
+    let date = chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map_err(|_| format!("Invalid date: {}", value))?;
+    let time = if end_of_day {
+        chrono::NaiveTime::from_hms_milli_opt(23, 59, 59, 999)
+    } else {
+        chrono::NaiveTime::from_hms_opt(0, 0, 0)
+    }
+    .expect("valid time of day");
+    Ok(date.and_time(time).and_utc())
+}
+
+/// A filter with its dates parsed
+struct CompiledFilter {
+    tags: HashSet<String>,
+    notebook: Option<String>,
+    from: Option<Timestamp>,
+    to: Option<Timestamp>,
+    include_archived: bool,
+}
+
+impl ExportFilter {
+    fn compile(&self) -> Result<CompiledFilter, String> {
+        let from = self
+            .updated_from
+            .as_deref()
+            .map(|v| parse_bound(v, false))
+            .transpose()?;
+        let to = self
+            .updated_to
+            .as_deref()
+            .map(|v| parse_bound(v, true))
+            .transpose()?;
+        if let (Some(from), Some(to)) = (from, to) {
+            if from > to {
+                return Err("Start date is after end date".to_string());
+            }
+        }
+        Ok(CompiledFilter {
+            tags: self.tags.iter().map(|t| t.trim().to_lowercase()).collect(),
+            notebook: self
+                .notebook
+                .as_deref()
+                .map(|n| n.trim().trim_matches('/').to_lowercase())
+                .filter(|n| !n.is_empty()),
+            from,
+            to,
+            include_archived: self.include_archived,
+        })
+    }
+}
+
+impl CompiledFilter {
+    fn matches(&self, note: &ExportNote) -> bool {
+        if note.is_archived && !self.include_archived {
+            return false;
+        }
+        if !self.tags.is_empty()
+            && !note
+                .tags
+                .iter()
+                .any(|t| self.tags.contains(&t.trim().to_lowercase()))
+        {
+            return false;
+        }
+        if let Some(ref notebook) = self.notebook {
+            let folder = note
+                .folder
+                .as_deref()
+                .unwrap_or("")
+                .trim_matches('/')
+                .to_lowercase();
+            if folder != *notebook && !folder.starts_with(&format!("{}/", notebook)) {
+                return false;
+            }
+        }
+        if self.from.is_some() || self.to.is_some() {
+            let Ok(updated) = chrono::DateTime::parse_from_rfc3339(&note.updated_at) else {
+                return false;
+            };
+            let updated = updated.with_timezone(&chrono::Utc);
+            if self.from.is_some_and(|from| updated < from)
+                || self.to.is_some_and(|to| updated > to)
+            {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+fn render(note: &ExportNote, format: ExportFormat) -> Result<Vec<u8>, String> {
+    match format {
+        ExportFormat::Markdown => Ok(format!(
+            "---\ntitle: {}\ntags: {}\nfolder: {}\ncreated: {}\nupdated: {}\n---\n\n{}\n",
+            serde_json::to_string(&note.title).unwrap_or_default(),
+            serde_json::to_string(&note.tags).unwrap_or_default(),
+            serde_json::to_string(&note.folder).unwrap_or_default(),
+            note.created_at,
+            note.updated_at,
+            note.content.trim_end()
+        )
+        .into_bytes()),
+        ExportFormat::Json => serde_json::to_vec_pretty(note).map_err(|e| e.to_string()),
+        ExportFormat::Pdf => Ok(crate::pdf::render_markdown(&note.title, &note.content)),
+    }
+}
+
+/// Relative output path mirroring the note's folder, unique within `taken`
+fn output_path(note: &ExportNote, format: ExportFormat, taken: &mut HashSet<String>) -> PathBuf {
+    let mut path: PathBuf = note
+        .folder
+        .as_deref()
+        .unwrap_or("")
+        .split('/')
+        .filter(|part| !part.trim().is_empty())
+        .map(sanitize_component)
+        .collect();
+    let stem = sanitize_component(&note.title);
+    let extension = format.extension();
+
+    let mut name = format!("{}.{}", stem, extension);
+    if !taken.insert(path.join(&name).to_string_lossy().to_lowercase()) {
+        let short_id: String = note.id.chars().take(8).collect();
+        name = format!("{} ({}).{}", stem, short_id, extension);
+        taken.insert(path.join(&name).to_string_lossy().to_lowercase());
+    }
+    path.push(name);
+    path
+}
+
+/// Write each note, calling `checkpoint` with the count written so far
+/// before each one; an error from it stops the export
+fn write_notes(
+    destination: &Path,
+    notes: &[ExportNote],
+    format: ExportFormat,
+    mut checkpoint: impl FnMut(usize) -> Result<(), AppError>,
+) -> Result<usize, AppError> {
+    let mut taken = HashSet::new();
+    for (i, note) in notes.iter().enumerate() {
+        checkpoint(i)?;
+        let path = destination.join(output_path(note, format, &mut taken));
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        std::fs::write(&path, render(note, format)?)
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    }
+    Ok(notes.len())
+}
+
+/// Export notes matching a filter, one file per note
+pub async fn export_notes(
+    job: &JobContext,
+    destination: PathBuf,
+    format: Option<ExportFormat>,
+    filter: Option<ExportFilter>,
+) -> Result<ExportSummary, AppError> {
+    let app = job.app();
+    if !destination.is_absolute() {
+        return Err(AppError::InvalidInput(
+            "Export folder must be an absolute path".to_string(),
+        ));
+    }
+    let format = format.unwrap_or_default();
+    let filter = filter.unwrap_or_default().compile()?;
+
+    job.progress("fetching", 0, None);
+    let response = job
+        .cancellable(crate::proxy::send_backend_request(
+            app,
+            "GET",
+            EXPORT_NOTES_PATH,
+            None,
+            None,
+        ))
+        .await?;
+    let notes: Vec<ExportNote> =
+        serde_json::from_value(response).map_err(|e| format!("Invalid notes response: {}", e))?;
+    let total = notes.len();
+    let selected: Vec<ExportNote> = notes.into_iter().filter(|n| filter.matches(n)).collect();
+
+    let target = destination.clone();
+    let writer = job.clone();
+    let exported = tokio::task::spawn_blocking(move || {
+        let count = selected.len() as u64;
+        write_notes(&target, &selected, format, |written| {
+            writer.check()?;
+            writer.progress("writing", written as u64, Some(count));
+            Ok(())
+        })
+    })
+    .await??;
+
+    tracing::info!(
+        "Exported {} of {} notes as {} to {:?}",
+        exported,
+        total,
+        format.extension(),
+        destination
+    );
+    // Let the webview stream the exported files back with `stream_file`
+    app.state::<StreamRegistry>().allow_read(&destination);
+    Ok(ExportSummary {
+        destination: destination.to_string_lossy().to_string(),
+        exported,
+        skipped: total - exported,
+    })
+}
+
+/// Export notes matching a filter, one file per note
+///
+/// Use `start_job` with kind `export` for progress and cancellation.
+#[tauri::command]
+pub async fn export_markdown(
+    app: AppHandle,
+    destination: String,
+    format: Option<ExportFormat>,
+    filter: Option<ExportFilter>,
+) -> Result<ExportSummary, AppError> {
+    export_notes(
+        &JobContext::untracked(&app),
+        PathBuf::from(destination),
+        format,
+        filter,
+    )
+    .await
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn note(
+        id: &str,
+        title: &str,
+        tags: &[&str],
+        folder: Option<&str>,
+        updated: &str,
+    ) -> ExportNote {
+        ExportNote {
+            id: id.to_string(),
+            title: title.to_string(),
+            content: "Body".to_string(),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            folder: folder.map(str::to_string),
+            created_at: "2024-01-01T00:00:00Z".to_string(),
+            updated_at: updated.to_string(),
+            is_archived: false,
+        }
+    }
+
+    #[test]
+    fn test_filter_by_tag_and_notebook() {
+        let filter = ExportFilter {
+            tags: vec!["Blog".to_string()],
+            notebook: Some("Writing/".to_string()),
+            ..Default::default()
+        }
+        .compile()
+        .unwrap();
+
+        let day = "2024-03-01T12:00:00Z";
+        assert!(filter.matches(&note("1", "a", &["blog"], Some("Writing"), day)));
+        assert!(filter.matches(&note("2", "b", &["x", "BLOG"], Some("writing/Drafts"), day)));
+        assert!(!filter.matches(&note("3", "c", &["work"], Some("Writing"), day)));
+        assert!(!filter.matches(&note("4", "d", &["blog"], Some("Writings"), day)));
+        assert!(!filter.matches(&note("5", "e", &["blog"], None, day)));
+
+        let mut archived = note("6", "f", &["blog"], Some("Writing"), day);
+        archived.is_archived = true;
+        assert!(!filter.matches(&archived));
+    }
+
+    #[test]
+    fn test_filter_by_date_range() {
+        let filter = ExportFilter {
+            updated_from: Some("2024-03-01".to_string()),
+            updated_to: Some("2024-03-31".to_string()),
+            ..Default::default()
+        }
+        .compile()
+        .unwrap();
+
+        assert!(filter.matches(&note("1", "a", &[], None, "2024-03-01T00:00:00Z")));
+        assert!(filter.matches(&note("2", "b", &[], None, "2024-03-31T23:30:00+00:00")));
+        assert!(!filter.matches(&note("3", "c", &[], None, "2024-04-01T00:00:01Z")));
+        assert!(!filter.matches(&note("4", "d", &[], None, "not a date")));
+
+        let reversed = ExportFilter {
+            updated_from: Some("2024-04-01".to_string()),
+            updated_to: Some("2024-03-01".to_string()),
+            ..Default::default()
+        };
+        assert!(reversed.compile().is_err());
+    }
+
+    #[test]
+    fn test_write_notes_in_each_format() {
+        let temp_dir = TempDir::new().unwrap();
+        let day = "2024-03-01T12:00:00Z";
+        let notes = vec![
+            note(
+                "aaaaaaaa-1",
+                "Post: One",
+                &["blog"],
+                Some("Writing/Drafts"),
+                day,
+            ),
+            note(
+                "bbbbbbbb-2",
+                "post: one",
+                &["blog"],
+                Some("Writing/Drafts"),
+                day,
+            ),
+        ];
+
+        for format in [
+            ExportFormat::Markdown,
+            ExportFormat::Json,
+            ExportFormat::Pdf,
+        ] {
+            assert_eq!(
+                write_notes(temp_dir.path(), &notes, format, |_| Ok(())).unwrap(),
+                2
+            );
+        }
+
+        let cancelled = write_notes(temp_dir.path(), &notes, ExportFormat::Json, |written| {
+            if written == 1 {
+                Err(AppError::Cancelled("stop".to_string()))
+            } else {
+                Ok(())
+            }
+        });
+        assert_eq!(cancelled.unwrap_err().kind(), "cancelled");
+        let dir = temp_dir.path().join("Writing/Drafts");
+        let markdown = std::fs::read_to_string(dir.join("Post- One.md")).unwrap();
+        assert!(markdown.contains("title: \"Post: One\""));
+        assert!(dir.join("post- one (bbbbbbbb).md").exists());
+
+        let json: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(dir.join("Post- One.json")).unwrap()).unwrap();
+        assert_eq!(json["id"], "aaaaaaaa-1");
+        assert!(std::fs::read(dir.join("Post- One.pdf"))
+            .unwrap()
+            .starts_with(b"%PDF"));
+    }
+
+    #[test]
+    fn test_format_deserializes_from_extension() {
+        let format: ExportFormat = serde_json::from_str("\"md\"").unwrap();
+        assert_eq!(format, ExportFormat::Markdown);
+        let format: ExportFormat = serde_json::from_str("\"pdf\"").unwrap();
+        assert_eq!(format, ExportFormat::Pdf);
+    }
+}
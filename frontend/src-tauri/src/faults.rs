@@ -0,0 +1,255 @@
+//! Simulated failures for QA.
+//!
+//! This module provides:
+//! - `inject_fault`, a hidden command that breaks a service on demand so the
+//!   orchestrator's recovery paths can be exercised:
+//!   - `backend-crash`: kill the backend process
+//!   - `postgres-hang`: stop PostgreSQL with SIGSTOP, resuming it after the
+//!     fault's duration
+//!   - `port-steal`: kill the backend and hold its port while it restarts
+//!   - `slow-health`: make health checks time out for the fault's duration
+//!
+//! Faults are only accepted in debug builds or when the app was started with
+//! `--fault-injection`, and every injection is logged as a warning.
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+use crate::error::AppError;
+use crate::startup_deps::{BoxFuture, HealthProbe, HEALTH_REQUEST_TIMEOUT};
+use crate::AppState;
+
+/// How long a fault lasts when no duration is given
+const DEFAULT_DURATION: Duration = Duration::from_secs(30);
+
+/// Longest a fault may last
+const MAX_DURATION: Duration = Duration::from_secs(600);
+
+/// How long to keep trying to bind a killed backend's port
+#[cfg(unix)]
+const STEAL_ATTEMPTS: u32 = 50;
+#[cfg(unix)]
+const STEAL_RETRY: Duration = Duration::from_millis(100);
+
+/// The real health probe while `slow-health` is in effect
+static REAL_HEALTH: Mutex<Option<Arc<dyn HealthProbe>>> = Mutex::new(None);
+
+/// A failure to simulate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FaultKind {
+    BackendCrash,
+    PostgresHang,
+    PortSteal,
+    SlowHealth,
+}
+
+/// What an injection did
+#[derive(Debug, Clone, Serialize)]
+pub struct InjectedFault {
+    pub kind: FaultKind,
+    /// How long the fault lasts; 0 for one-off faults like a crash
+    pub duration_secs: u64,
+    pub detail: String,
+}
+
+/// Health probe that never answers in time
+struct SlowHealth {
+    delay: Duration,
+}
+
+impl HealthProbe for SlowHealth {
+    fn check(&self, _port: u16) -> BoxFuture<'_, Result<(), String>> {
+        Box::pin(async move {
+            tokio::time::sleep(self.delay).await;
+            Err("Health check timed out (injected fault)".to_string())
+        })
+    }
+}
+
+fn injection_allowed(app: &AppHandle) -> bool {
+    cfg!(debug_assertions) || crate::cli::options(app).fault_injection
+}
+
+#[cfg(unix)]
+fn require_real_backend(app: &AppHandle, kind: FaultKind) -> Result<(), AppError> {
+    if crate::cli::options(app).mock_backend {
+        return Err(AppError::NotReady(format!(
+            "{:?} needs the real services, not --mock-backend",
+            kind
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn crash_backend(app: &AppHandle) -> Result<String, AppError> {
+    require_real_backend(app, FaultKind::BackendCrash)?;
+    let port = *app.state::<AppState>().backend_port.read();
+    crate::kill_process_on_port(port);
+    Ok(format!("Killed the backend on port {}", port))
+}
+
+#[cfg(unix)]
+fn hang_postgres(app: &AppHandle, duration: Duration) -> Result<String, AppError> {
+    require_real_backend(app, FaultKind::PostgresHang)?;
+    let pid = app
+        .state::<AppState>()
+        .postgres_manager
+        .read()
+        .as_ref()
+        .and_then(|manager| manager.postmaster_pid())
+        .ok_or_else(|| AppError::NotReady("PostgreSQL is not running".to_string()))?;
+
+    if unsafe { libc::kill(pid, libc::SIGSTOP) } != 0 {
+        return Err(AppError::Internal(format!(
+            "Failed to stop PostgreSQL ({}): {}",
+            pid,
+            std::io::Error::last_os_error()
+        )));
+    }
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(duration).await;
+        tracing::warn!("Fault injection: resuming PostgreSQL ({})", pid);
+        unsafe { libc::kill(pid, libc::SIGCONT) };
+    });
+    Ok(format!("Stopped PostgreSQL ({})", pid))
+}
+
+#[cfg(unix)]
+async fn steal_port(app: &AppHandle, duration: Duration) -> Result<String, AppError> {
+    require_real_backend(app, FaultKind::PortSteal)?;
+    let port = *app.state::<AppState>().backend_port.read();
+    crate::kill_process_on_port(port);
+
+    // The killed process may take a moment to let go of the port
+    let mut listener = None;
+    for _ in 0..STEAL_ATTEMPTS {
+        if let Ok(bound) = tokio::net::TcpListener::bind(("127.0.0.1", port)).await {
+            listener = Some(bound);
+            break;
+        }
+        tokio::time::sleep(STEAL_RETRY).await;
+    }
+    let listener =
+        listener.ok_or_else(|| AppError::Conflict(format!("Could not bind port {}", port)))?;
+
+    app.state::<crate::services::ServiceManager>()
+        .dispatch(crate::services::ServiceCommand::RestartBackend);
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(duration).await;
+        tracing::warn!("Fault injection: releasing port {}", port);
+        drop(listener);
+    });
+    Ok(format!(
+        "Killed the backend and holding port {} while it restarts",
+        port
+    ))
+}
+
+fn slow_health(app: &AppHandle, duration: Duration) -> String {
+    let slow: Arc<dyn HealthProbe> = Arc::new(SlowHealth {
+        delay: HEALTH_REQUEST_TIMEOUT + Duration::from_secs(1),
+    });
+    {
+        let state = app.state::<AppState>();
+        let mut deps = state.startup_deps.write();
+        let previous = std::mem::replace(&mut deps.health, slow.clone());
+        // A repeated injection only extends the one in effect
+        REAL_HEALTH.lock().get_or_insert(previous);
+    }
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(duration).await;
+        let state = app.state::<AppState>();
+        let mut deps = state.startup_deps.write();
+        // A later injection restores the probe when it ends instead
+        if !Arc::ptr_eq(&deps.health, &slow) {
+            return;
+        }
+        if let Some(real) = REAL_HEALTH.lock().take() {
+            tracing::warn!("Fault injection: health checks back to normal");
+            deps.health = real;
+        }
+    });
+    "Health checks will time out".to_string()
+}
+
+// ============================================================
+// Commands
+// ============================================================
+
+/// Simulate a failure (debug builds or `--fault-injection` only)
+#[tauri::command]
+pub async fn inject_fault(
+    app: AppHandle,
+    kind: FaultKind,
+    duration_secs: Option<u64>,
+) -> Result<InjectedFault, AppError> {
+    if !injection_allowed(&app) {
+        return Err(AppError::Permission(
+            "Fault injection is disabled; start the app with --fault-injection".to_string(),
+        ));
+    }
+    let duration = duration_secs
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_DURATION)
+        .min(MAX_DURATION);
+    tracing::warn!("Fault injection: {:?} for {}s", kind, duration.as_secs());
+
+    let (detail, lasts) = match kind {
+        #[cfg(unix)]
+        FaultKind::BackendCrash => (crash_backend(&app)?, Duration::ZERO),
+        #[cfg(unix)]
+        FaultKind::PostgresHang => (hang_postgres(&app, duration)?, duration),
+        #[cfg(unix)]
+        FaultKind::PortSteal => (steal_port(&app, duration).await?, duration),
+        FaultKind::SlowHealth => (slow_health(&app, duration), duration),
+        #[cfg(not(unix))]
+        other => {
+            return Err(AppError::InvalidInput(format!(
+                "{:?} is not supported on this platform",
+                other
+            )))
+        }
+    };
+
+    tracing::warn!("Fault injection: {}", detail);
+    Ok(InjectedFault {
+        kind,
+        duration_secs: lasts.as_secs(),
+        detail,
+    })
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fault_kind_names() {
+        let kind: FaultKind = serde_json::from_str("\"backend-crash\"").unwrap();
+        assert_eq!(kind, FaultKind::BackendCrash);
+        assert_eq!(
+            serde_json::to_string(&FaultKind::SlowHealth).unwrap(),
+            "\"slow-health\""
+        );
+        assert!(serde_json::from_str::<FaultKind>("\"disk-full\"").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_slow_health_fails() {
+        let health = SlowHealth {
+            delay: Duration::from_millis(1),
+        };
+        assert!(health.check(5001).await.is_err());
+    }
+}
@@ -0,0 +1,647 @@
+//! RSS/Atom feed subscriptions.
+//!
+//! This module provides:
+//! - User-configured RSS/Atom feeds with per-feed refresh intervals
+//! - Conditional fetching (ETag / Last-Modified) and de-duplication of items
+//! - Forwarding of new items to the backend inbox or read-later list
+//! - Per-feed status for the settings UI
+//!
+//! Feeds are polled by the background scheduler (see `scheduler`).
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::config::{load_json, save_json_atomic};
+use crate::error::AppError;
+use crate::scheduler::{JobAction, Schedule, ScheduledJob, Scheduler, FEED_REFRESH_JOB_ID};
+
+/// Backend endpoint for inbox items
+const INBOX_ITEMS_PATH: &str = "/inbox/items";
+
+/// Backend endpoint for the read-later list
+const READ_LATER_PATH: &str = "/read-later";
+
+/// Items pushed when a feed is first added; older items are marked as seen
+const INITIAL_BACKFILL: usize = 5;
+
+/// Default time between refreshes of a feed
+const DEFAULT_REFRESH_MINS: u32 = 60;
+
+/// Shortest allowed time between refreshes of a feed
+const MIN_REFRESH_MINS: u32 = 5;
+
+/// Feed documents larger than this are rejected
+const MAX_FEED_BYTES: usize = 10 * 1024 * 1024;
+
+/// How often the scheduler checks for feeds that are due
+const POLL_INTERVAL_SECS: u64 = 5 * 60;
+
+/// Where new items from a feed are sent
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FeedDestination {
+    #[default]
+    Inbox,
+    ReadLater,
+}
+
+impl FeedDestination {
+    fn path(&self) -> &'static str {
+        match self {
+            FeedDestination::Inbox => INBOX_ITEMS_PATH,
+            FeedDestination::ReadLater => READ_LATER_PATH,
+        }
+    }
+}
+
+/// Result of the most recent refreshes of a feed
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeedStatus {
+    /// Last refresh attempt (Unix epoch seconds)
+    pub last_checked: Option<i64>,
+    /// Last successful refresh (Unix epoch seconds)
+    pub last_success: Option<i64>,
+    /// Error from the last refresh, if it failed
+    pub last_error: Option<String>,
+    /// Items in the feed document at the last refresh
+    pub item_count: usize,
+    /// Items forwarded by the last refresh
+    pub new_items: usize,
+    /// Items forwarded since the feed was added
+    pub total_forwarded: u64,
+}
+
+/// A feed subscription
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Feed {
+    /// Stable ID derived from the URL
+    pub id: String,
+    pub url: String,
+    pub title: String,
+    pub destination: FeedDestination,
+    pub refresh_interval_mins: u32,
+    pub status: FeedStatus,
+}
+
+impl Feed {
+    /// Whether the feed should be refreshed at `now` (Unix epoch seconds)
+    pub fn is_due(&self, now: i64) -> bool {
+        match self.status.last_checked {
+            Some(last) => now - last >= self.refresh_interval_mins as i64 * 60,
+            None => true,
+        }
+    }
+}
+
+/// A feed with the bookkeeping that is not surfaced to the UI
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StoredFeed {
+    #[serde(flatten)]
+    feed: Feed,
+    /// Entry IDs already forwarded (or skipped on first add)
+    #[serde(default)]
+    seen: Vec<String>,
+    #[serde(default)]
+    etag: Option<String>,
+    #[serde(default)]
+    last_modified: Option<String>,
+}
+
+/// Persisted feed subscriptions
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct FeedStore {
+    feeds: Vec<StoredFeed>,
+}
+
+impl FeedStore {
+    fn path(app_data_dir: &Path) -> PathBuf {
+        app_data_dir.join("feeds.json")
+    }
+
+    fn load(app_data_dir: &Path) -> Self {
+        load_json(&Self::path(app_data_dir)).unwrap_or_default()
+    }
+
+    fn save(&self, app_data_dir: &Path) -> Result<(), String> {
+        save_json_atomic(&Self::path(app_data_dir), self)
+    }
+
+    fn get_mut(&mut self, id: &str) -> Result<&mut StoredFeed, String> {
+        self.feeds
+            .iter_mut()
+            .find(|f| f.feed.id == id)
+            .ok_or_else(|| format!("Feed not found: {}", id))
+    }
+}
+
+/// Managed state serializing access to the feed store
+#[derive(Default)]
+pub struct FeedManager {
+    lock: tokio::sync::Mutex<()>,
+}
+
+/// An entry as forwarded to the backend
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeedItem {
+    pub feed_id: String,
+    pub feed_title: String,
+    pub entry_id: String,
+    pub title: String,
+    pub url: Option<String>,
+    /// Summary or content converted to markdown
+    pub summary: String,
+    pub published: Option<String>,
+}
+
+/// Event payload emitted after a feed is refreshed
+#[derive(Debug, Clone, Serialize)]
+pub struct FeedRefreshedEvent {
+    pub id: String,
+    pub status: FeedStatus,
+}
+
+fn feed_id(url: &str) -> String {
+    Sha256::digest(url.as_bytes())
+        .iter()
+        .take(8)
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+fn validate_url(url: &str) -> Result<String, String> {
+    let parsed = reqwest::Url::parse(url.trim()).map_err(|e| format!("Invalid feed URL: {}", e))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err("Feed URL must use http or https".to_string());
+    }
+    Ok(parsed.to_string())
+}
+
+fn text_to_markdown(text: &feed_rs::model::Text) -> String {
+    if text.content_type.subty().as_str() == "html" {
+        htmd::convert(&text.content).unwrap_or_else(|_| text.content.clone())
+    } else {
+        text.content.trim().to_string()
+    }
+}
+
+/// Convert a parsed entry to the forwarded form
+fn to_item(feed: &Feed, entry: &feed_rs::model::Entry) -> FeedItem {
+    let summary = entry
+        .summary
+        .as_ref()
+        .map(text_to_markdown)
+        .or_else(|| {
+            let content = entry.content.as_ref()?;
+            let body = content.body.as_ref()?;
+            Some(if content.content_type.subty().as_str() == "html" {
+                htmd::convert(body).unwrap_or_else(|_| body.clone())
+            } else {
+                body.trim().to_string()
+            })
+        })
+        .unwrap_or_default();
+
+    FeedItem {
+        feed_id: feed.id.clone(),
+        feed_title: feed.title.clone(),
+        entry_id: entry.id.clone(),
+        title: entry
+            .title
+            .as_ref()
+            .map(|t| t.content.trim().to_string())
+            .filter(|t| !t.is_empty())
+            .unwrap_or_else(|| "(untitled)".to_string()),
+        url: entry.links.first().map(|l| l.href.clone()),
+        summary,
+        published: entry
+            .published
+            .or(entry.updated)
+            .map(|d: DateTime<Utc>| d.to_rfc3339()),
+    }
+}
+
+/// Unseen entries, oldest first
+fn unseen_entries<'a>(
+    entries: &'a [feed_rs::model::Entry],
+    seen: &HashSet<&str>,
+) -> Vec<&'a feed_rs::model::Entry> {
+    let mut unseen: Vec<_> = entries
+        .iter()
+        .filter(|e| !seen.contains(e.id.as_str()))
+        .collect();
+    unseen.sort_by_key(|e| e.published.or(e.updated));
+    unseen
+}
+
+/// Mark all but the newest `keep` entries as seen
+fn seed_seen(entries: &[feed_rs::model::Entry], keep: usize) -> Vec<String> {
+    let all = unseen_entries(entries, &HashSet::new());
+    let skip = all.len().saturating_sub(keep);
+    all.into_iter().take(skip).map(|e| e.id.clone()).collect()
+}
+
+enum FetchResult {
+    NotModified,
+    Fetched {
+        feed: Box<feed_rs::model::Feed>,
+        etag: Option<String>,
+        last_modified: Option<String>,
+    },
+}
+
+async fn fetch(app: &AppHandle, stored: &StoredFeed) -> Result<FetchResult, String> {
+    let mut request = crate::http::external(app)
+        .get(&stored.feed.url)
+        .timeout(Duration::from_secs(30));
+    if let Some(ref etag) = stored.etag {
+        request = request.header("If-None-Match", etag);
+    }
+    if let Some(ref last_modified) = stored.last_modified {
+        request = request.header("If-Modified-Since", last_modified);
+    }
+
+    let response = request
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch feed: {}", e))?;
+
+    if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+        return Ok(FetchResult::NotModified);
+    }
+    if !response.status().is_success() {
+        return Err(format!("Feed returned HTTP {}", response.status()));
+    }
+
+    let header = |name: &str| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string)
+    };
+    let etag = header("etag");
+    let last_modified = header("last-modified");
+
+    let bytes = response
+        .bytes()
+        .await
+        .map_err(|e| format!("Failed to read feed: {}", e))?;
+    if bytes.len() > MAX_FEED_BYTES {
+        return Err("Feed document is too large".to_string());
+    }
+
+    let feed = feed_rs::parser::parse(&bytes[..]).map_err(|e| format!("Invalid feed: {}", e))?;
+    Ok(FetchResult::Fetched {
+        feed: Box::new(feed),
+        etag,
+        last_modified,
+    })
+}
+
+/// Fetch a feed and forward its unseen entries, updating its status
+async fn refresh(app: &AppHandle, stored: &mut StoredFeed) -> Result<(), String> {
+    let now = Utc::now().timestamp();
+    stored.feed.status.last_checked = Some(now);
+    stored.feed.status.new_items = 0;
+
+    let result = refresh_inner(app, stored).await;
+    match result {
+        Ok(()) => {
+            stored.feed.status.last_success = Some(now);
+            stored.feed.status.last_error = None;
+        }
+        Err(ref e) => {
+            tracing::warn!("Failed to refresh feed {}: {}", stored.feed.url, e);
+            stored.feed.status.last_error = Some(e.clone());
+        }
+    }
+
+    let _ = app.emit(
+        "feed-refreshed",
+        FeedRefreshedEvent {
+            id: stored.feed.id.clone(),
+            status: stored.feed.status.clone(),
+        },
+    );
+    result
+}
+
+async fn refresh_inner(app: &AppHandle, stored: &mut StoredFeed) -> Result<(), String> {
+    let (parsed, etag, last_modified) = match fetch(app, stored).await? {
+        FetchResult::NotModified => return Ok(()),
+        FetchResult::Fetched {
+            feed,
+            etag,
+            last_modified,
+        } => (feed, etag, last_modified),
+    };
+
+    if stored.feed.title.is_empty() {
+        if let Some(ref title) = parsed.title {
+            stored.feed.title = title.content.trim().to_string();
+        }
+    }
+    stored.feed.status.item_count = parsed.entries.len();
+
+    // Forget IDs that have dropped out of the feed so the list stays bounded
+    let current: HashSet<&str> = parsed.entries.iter().map(|e| e.id.as_str()).collect();
+    stored.seen.retain(|id| current.contains(id.as_str()));
+
+    let seen: HashSet<&str> = stored.seen.iter().map(String::as_str).collect();
+    let unseen: Vec<FeedItem> = unseen_entries(&parsed.entries, &seen)
+        .into_iter()
+        .map(|entry| to_item(&stored.feed, entry))
+        .collect();
+
+    let destination = stored.feed.destination.path();
+    for item in unseen {
+        crate::proxy::send_backend_request(
+            app,
+            "POST",
+            destination,
+            Some(&serde_json::json!(item)),
+            None,
+        )
+        .await?;
+
+        stored.seen.push(item.entry_id);
+        stored.feed.status.new_items += 1;
+        stored.feed.status.total_forwarded += 1;
+    }
+
+    // Only advance the validators once every item has been forwarded, so a
+    // failed push is retried on the next refresh instead of returning 304
+    stored.etag = etag;
+    stored.last_modified = last_modified;
+    Ok(())
+}
+
+/// Refresh every feed whose interval has elapsed
+pub async fn refresh_due_feeds(app: &AppHandle) -> Result<(), String> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let manager = app.state::<FeedManager>();
+    let _guard = manager.lock.lock().await;
+
+    let mut store = FeedStore::load(&app_data_dir);
+    let now = Utc::now().timestamp();
+    let mut failed = 0;
+
+    for stored in store.feeds.iter_mut().filter(|f| f.feed.is_due(now)) {
+        if refresh(app, stored).await.is_err() {
+            failed += 1;
+        }
+    }
+
+    store.save(&app_data_dir)?;
+    if failed > 0 {
+        return Err(format!("{} feed(s) failed to refresh", failed));
+    }
+    Ok(())
+}
+
+/// Register the feed polling job with the scheduler
+pub fn start(app: &AppHandle) {
+    app.state::<Scheduler>().upsert_job(
+        ScheduledJob {
+            id: FEED_REFRESH_JOB_ID.to_string(),
+            name: "Feed refresh".to_string(),
+            schedule: Schedule::Interval {
+                every_secs: POLL_INTERVAL_SECS,
+            },
+            // Conditional GETs are cheap enough to run on battery
+            skip_on_battery: false,
+            wait_for_idle: false,
+            jitter_secs: 60,
+            action: JobAction::RefreshFeeds,
+        },
+        chrono::Local::now(),
+    );
+}
+
+/// Subscribe to a feed
+///
+/// The feed is fetched immediately; its newest few items are forwarded and the
+/// rest are marked as seen.
+#[tauri::command]
+pub async fn add_feed(
+    app: AppHandle,
+    url: String,
+    destination: Option<FeedDestination>,
+    refresh_interval_mins: Option<u32>,
+) -> Result<Feed, AppError> {
+    let url = validate_url(&url)?;
+    let refresh_interval_mins = refresh_interval_mins.unwrap_or(DEFAULT_REFRESH_MINS);
+    if refresh_interval_mins < MIN_REFRESH_MINS {
+        return Err(AppError::InvalidInput(format!(
+            "Refresh interval must be at least {} minutes",
+            MIN_REFRESH_MINS
+        )));
+    }
+
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let manager = app.state::<FeedManager>();
+    let _guard = manager.lock.lock().await;
+
+    let mut store = FeedStore::load(&app_data_dir);
+    let id = feed_id(&url);
+    if store.feeds.iter().any(|f| f.feed.id == id) {
+        return Err(AppError::Conflict(
+            "Already subscribed to this feed".to_string(),
+        ));
+    }
+
+    let mut stored = StoredFeed {
+        feed: Feed {
+            id,
+            url,
+            title: String::new(),
+            destination: destination.unwrap_or_default(),
+            refresh_interval_mins,
+            status: FeedStatus::default(),
+        },
+        seen: Vec::new(),
+        etag: None,
+        last_modified: None,
+    };
+
+    // Validate the URL points at a feed before subscribing
+    let FetchResult::Fetched { feed: parsed, .. } = fetch(&app, &stored).await? else {
+        return Err(AppError::Network("Feed returned no content".to_string()));
+    };
+    stored.seen = seed_seen(&parsed.entries, INITIAL_BACKFILL);
+
+    // Forwarding failures are recorded in the status and retried on schedule
+    let _ = refresh(&app, &mut stored).await;
+
+    tracing::info!("Subscribed to feed {}", stored.feed.url);
+    let feed = stored.feed.clone();
+    store.feeds.push(stored);
+    store.save(&app_data_dir)?;
+    Ok(feed)
+}
+
+/// List feed subscriptions with their status
+#[tauri::command]
+pub async fn list_feeds(app: AppHandle) -> Result<Vec<Feed>, AppError> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    Ok(FeedStore::load(&app_data_dir)
+        .feeds
+        .into_iter()
+        .map(|f| f.feed)
+        .collect())
+}
+
+/// Refresh a feed now, regardless of its interval
+#[tauri::command]
+pub async fn refresh_feed(app: AppHandle, id: String) -> Result<Feed, AppError> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let manager = app.state::<FeedManager>();
+    let _guard = manager.lock.lock().await;
+
+    let mut store = FeedStore::load(&app_data_dir);
+    let stored = store.get_mut(&id)?;
+    let result = refresh(&app, stored).await;
+    let feed = stored.feed.clone();
+    store.save(&app_data_dir)?;
+
+    result.map(|_| feed).map_err(AppError::from)
+}
+
+/// Unsubscribe from a feed
+#[tauri::command]
+pub async fn remove_feed(app: AppHandle, id: String) -> Result<(), AppError> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let manager = app.state::<FeedManager>();
+    let _guard = manager.lock.lock().await;
+
+    let mut store = FeedStore::load(&app_data_dir);
+    store.get_mut(&id)?;
+    store.feeds.retain(|f| f.feed.id != id);
+    store.save(&app_data_dir).map_err(AppError::from)
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const RSS: &str = r#"<?xml version="1.0"?>
+<rss version="2.0"><channel>
+  <title>Example Blog</title>
+  <item>
+    <guid>post-2</guid>
+    <title>Second post</title>
+    <link>https://example.com/2</link>
+    <description>&lt;p&gt;Hello &lt;em&gt;world&lt;/em&gt;&lt;/p&gt;</description>
+    <pubDate>Tue, 11 Mar 2025 09:00:00 GMT</pubDate>
+  </item>
+  <item>
+    <guid>post-1</guid>
+    <title>First post</title>
+    <link>https://example.com/1</link>
+    <pubDate>Mon, 10 Mar 2025 09:00:00 GMT</pubDate>
+  </item>
+  <item>
+    <guid>post-3</guid>
+    <title>Third post</title>
+    <pubDate>Wed, 12 Mar 2025 09:00:00 GMT</pubDate>
+  </item>
+</channel></rss>"#;
+
+    fn parsed() -> feed_rs::model::Feed {
+        feed_rs::parser::parse(RSS.as_bytes()).unwrap()
+    }
+
+    fn feed() -> Feed {
+        Feed {
+            id: feed_id("https://example.com/feed"),
+            url: "https://example.com/feed".to_string(),
+            title: "Example Blog".to_string(),
+            destination: FeedDestination::ReadLater,
+            refresh_interval_mins: 60,
+            status: FeedStatus::default(),
+        }
+    }
+
+    #[test]
+    fn test_validate_url() {
+        assert!(validate_url("https://example.com/feed.xml").is_ok());
+        assert!(validate_url("file:///etc/passwd").is_err());
+        assert!(validate_url("not a url").is_err());
+    }
+
+    #[test]
+    fn test_feed_id_is_stable() {
+        assert_eq!(feed_id("https://a.example"), feed_id("https://a.example"));
+        assert_ne!(feed_id("https://a.example"), feed_id("https://b.example"));
+        assert_eq!(feed_id("https://a.example").len(), 16);
+    }
+
+    #[test]
+    fn test_unseen_entries_oldest_first() {
+        let parsed = parsed();
+        let seen: HashSet<&str> = ["post-2"].into_iter().collect();
+
+        let ids: Vec<&str> = unseen_entries(&parsed.entries, &seen)
+            .iter()
+            .map(|e| e.id.as_str())
+            .collect();
+        assert_eq!(ids, vec!["post-1", "post-3"]);
+    }
+
+    #[test]
+    fn test_seed_seen_keeps_newest() {
+        let parsed = parsed();
+        assert_eq!(seed_seen(&parsed.entries, 1), vec!["post-1", "post-2"]);
+        assert!(seed_seen(&parsed.entries, 5).is_empty());
+    }
+
+    #[test]
+    fn test_to_item_converts_html_summary() {
+        let parsed = parsed();
+        let item = to_item(&feed(), &parsed.entries[0]);
+
+        assert_eq!(item.entry_id, "post-2");
+        assert_eq!(item.title, "Second post");
+        assert_eq!(item.url.as_deref(), Some("https://example.com/2"));
+        assert_eq!(item.summary, "Hello *world*");
+        assert!(item.published.unwrap().starts_with("2025-03-11"));
+    }
+
+    #[test]
+    fn test_is_due() {
+        let mut feed = feed();
+        assert!(feed.is_due(1_000));
+
+        feed.status.last_checked = Some(1_000);
+        assert!(!feed.is_due(1_000 + 59 * 60));
+        assert!(feed.is_due(1_000 + 60 * 60));
+    }
+
+    #[test]
+    fn test_store_roundtrip() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut store = FeedStore::default();
+        store.feeds.push(StoredFeed {
+            feed: feed(),
+            seen: vec!["post-1".to_string()],
+            etag: Some("\"abc\"".to_string()),
+            last_modified: None,
+        });
+        store.save(temp_dir.path()).unwrap();
+
+        let loaded = FeedStore::load(temp_dir.path());
+        assert_eq!(loaded.feeds[0].feed, feed());
+        assert_eq!(loaded.feeds[0].seen, vec!["post-1"]);
+        assert_eq!(loaded.feeds[0].etag.as_deref(), Some("\"abc\""));
+    }
+}
@@ -0,0 +1,488 @@
+//! Time-boxed focus sessions.
+//!
+//! This module provides:
+//! - `start_focus_session`/`end_focus_session`, for a session of a set
+//!   length, optionally on a note
+//! - Holding back notices from the configured sources while a session runs,
+//!   with critical notices always getting through (see `notifications`)
+//! - A countdown beside the tray icon
+//! - Turning macOS Focus on and off by running the user's Shortcuts
+//! - Logging finished sessions to the backend for productivity stats, with a
+//!   local history kept in focus-history.json either way
+//!
+//! The webview's own notifications use the source `app`; it checks
+//! `get_focus_session` before showing them.
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+
+use crate::config::{load_json, save_json_atomic};
+use crate::error::AppError;
+use crate::notifications::{Notice, Severity};
+
+/// Longest session that can be started
+const MAX_MINUTES: u32 = 240;
+
+/// Finished sessions kept in the local history
+const HISTORY_LIMIT: usize = 500;
+
+/// Time between countdown updates
+const TICK: Duration = Duration::from_secs(1);
+
+/// Timeout for running a Shortcut
+const SHORTCUT_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Focus session settings
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FocusSettings {
+    /// Notice sources held back during a session, e.g. "usage"; "app" is the
+    /// webview's own notifications
+    pub blocked_sources: Vec<String>,
+    /// Shortcut run at the start of a session, e.g. one turning on a Focus
+    pub start_shortcut: Option<String>,
+    /// Shortcut run when a session ends
+    pub end_shortcut: Option<String>,
+}
+
+impl Default for FocusSettings {
+    fn default() -> Self {
+        Self {
+            blocked_sources: ["app", "github", "usage"]
+                .into_iter()
+                .map(str::to_string)
+                .collect(),
+            start_shortcut: None,
+            end_shortcut: None,
+        }
+    }
+}
+
+impl FocusSettings {
+    fn path(app_data_dir: &Path) -> PathBuf {
+        app_data_dir.join("focus.json")
+    }
+
+    /// Load the settings, returning defaults if missing or invalid
+    pub fn load(app_data_dir: &Path) -> Self {
+        load_json(&Self::path(app_data_dir)).unwrap_or_default()
+    }
+
+    /// Save the settings atomically
+    pub fn save(&self, app_data_dir: &Path) -> Result<(), String> {
+        save_json_atomic(&Self::path(app_data_dir), self)
+    }
+
+    /// Validate the settings
+    pub fn validate(&self) -> Result<(), String> {
+        if self.blocked_sources.iter().any(|s| s.trim().is_empty()) {
+            return Err("A blocked source can't be blank".to_string());
+        }
+        if [&self.start_shortcut, &self.end_shortcut]
+            .into_iter()
+            .flatten()
+            .any(|name| name.trim().is_empty())
+        {
+            return Err("A Shortcut name can't be blank; leave it unset instead".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// The session in progress
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ActiveFocusSession {
+    pub id: String,
+    pub note_id: Option<String>,
+    /// Unix epoch seconds
+    pub started_at: i64,
+    pub ends_at: i64,
+    /// Sources held back, from the settings when the session started
+    pub blocked_sources: Vec<String>,
+    /// Notices held back so far
+    pub held_notices: u32,
+}
+
+/// A finished session
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FocusSession {
+    pub id: String,
+    pub note_id: Option<String>,
+    pub started_at: i64,
+    pub ended_at: i64,
+    pub planned_minutes: u32,
+    pub focused_seconds: u64,
+    /// Whether it ran its full length rather than being ended early
+    pub completed: bool,
+    pub held_notices: u32,
+    /// Whether the backend recorded it
+    pub logged: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct FocusHistory {
+    sessions: Vec<FocusSession>,
+}
+
+impl FocusHistory {
+    fn path(app_data_dir: &Path) -> PathBuf {
+        app_data_dir.join("focus-history.json")
+    }
+
+    fn load(app_data_dir: &Path) -> Self {
+        load_json(&Self::path(app_data_dir)).unwrap_or_default()
+    }
+
+    /// Add a session, dropping the oldest past the limit
+    fn record(&mut self, session: FocusSession) {
+        self.sessions.push(session);
+        let excess = self.sessions.len().saturating_sub(HISTORY_LIMIT);
+        self.sessions.drain(..excess);
+    }
+}
+
+/// Focus session state, kept in Tauri state
+#[derive(Default)]
+pub struct FocusManager {
+    current: Mutex<Option<ActiveFocusSession>>,
+}
+
+fn new_id() -> String {
+    let mut random = [0u8; 8];
+    let _ = getrandom::fill(&mut random);
+    random.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Whether a session blocks this notice
+fn blocks(session: &ActiveFocusSession, notice: &Notice) -> bool {
+    notice.severity < Severity::Critical
+        && session
+            .blocked_sources
+            .iter()
+            .any(|source| source == notice.source)
+}
+
+/// Tray countdown for the time left, e.g. `Focus 24:59`
+fn countdown_label(remaining_secs: i64) -> String {
+    let remaining = remaining_secs.max(0);
+    format!("Focus {}:{:02}", remaining / 60, remaining % 60)
+}
+
+/// Hold back a notice if a focus session blocks its source, counting it
+pub fn holds_back<R: Runtime>(app: &AppHandle<R>, notice: &Notice) -> bool {
+    let Some(manager) = app.try_state::<FocusManager>() else {
+        return false;
+    };
+    let mut current = manager.current.lock();
+    match current.as_mut() {
+        Some(session) if blocks(session, notice) => {
+            session.held_notices += 1;
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Run a Shortcut by name; only macOS has them
+async fn run_shortcut(name: &str) {
+    if !cfg!(target_os = "macos") {
+        return;
+    }
+    let run = tokio::process::Command::new("shortcuts")
+        .arg("run")
+        .arg(name)
+        .kill_on_drop(true)
+        .output();
+    match tokio::time::timeout(SHORTCUT_TIMEOUT, run).await {
+        Ok(Ok(output)) if output.status.success() => {
+            tracing::info!("Ran Shortcut {:?}", name)
+        }
+        Ok(Ok(output)) => tracing::warn!(
+            "Shortcut {:?} failed: {}",
+            name,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ),
+        Ok(Err(e)) => tracing::warn!("Failed to run Shortcut {:?}: {}", name, e),
+        Err(_) => tracing::warn!("Shortcut {:?} timed out", name),
+    }
+}
+
+/// Record a finished session with the backend
+async fn log_session(app: &AppHandle, session: &FocusSession) -> Result<(), AppError> {
+    let body = serde_json::json!({
+        "noteId": session.note_id,
+        "startedAt": session.started_at,
+        "endedAt": session.ended_at,
+        "plannedMinutes": session.planned_minutes,
+        "focusedSeconds": session.focused_seconds,
+        "completed": session.completed,
+    });
+    crate::proxy::send_backend_request(app, "POST", "/stats/focus-sessions", Some(&body), None)
+        .await
+        .map(|_| ())
+}
+
+/// End the session with `id` if it's still the current one
+async fn finish(app: &AppHandle, id: &str, completed: bool) -> Option<FocusSession> {
+    let active = {
+        let manager = app.state::<FocusManager>();
+        let mut current = manager.current.lock();
+        if current.as_ref().map(|session| session.id.as_str()) != Some(id) {
+            return None;
+        }
+        current.take()?
+    };
+    crate::tray::set_focus_countdown(app, None);
+
+    let ended_at = chrono::Utc::now().timestamp();
+    let mut session = FocusSession {
+        id: active.id,
+        note_id: active.note_id,
+        started_at: active.started_at,
+        ended_at,
+        planned_minutes: ((active.ends_at - active.started_at) / 60) as u32,
+        focused_seconds: (ended_at.min(active.ends_at) - active.started_at).max(0) as u64,
+        completed,
+        held_notices: active.held_notices,
+        logged: false,
+    };
+    tracing::info!(
+        completed,
+        focused_seconds = session.focused_seconds,
+        "Focus session ended"
+    );
+
+    let app_data_dir = app.path().app_data_dir().ok();
+    if let Some(shortcut) = app_data_dir
+        .as_deref()
+        .and_then(|dir| FocusSettings::load(dir).end_shortcut)
+    {
+        run_shortcut(&shortcut).await;
+    }
+
+    match log_session(app, &session).await {
+        Ok(()) => session.logged = true,
+        Err(e) => tracing::warn!("Failed to log focus session: {}", e),
+    }
+    if let Some(dir) = app_data_dir.as_deref() {
+        let mut history = FocusHistory::load(dir);
+        history.record(session.clone());
+        if let Err(e) = save_json_atomic(&FocusHistory::path(dir), &history) {
+            tracing::warn!("Failed to save focus history: {}", e);
+        }
+    }
+
+    if completed {
+        let held = match session.held_notices {
+            0 => String::new(),
+            1 => " 1 notification was held back.".to_string(),
+            count => format!(" {} notifications were held back.", count),
+        };
+        crate::notifications::notify(
+            app,
+            Notice::new(
+                "focus",
+                Severity::Info,
+                "Focus session complete",
+                format!(
+                    "You focused for {} minutes.{}",
+                    session.planned_minutes, held
+                ),
+            ),
+        );
+    }
+    let _ = app.emit("focus-session-ended", &session);
+    Some(session)
+}
+
+/// Count down the session with `id`, finishing it when time is up
+fn run_countdown(app: AppHandle, id: String, ends_at: i64) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(TICK);
+        loop {
+            interval.tick().await;
+            let current = app
+                .state::<FocusManager>()
+                .current
+                .lock()
+                .as_ref()
+                .is_some_and(|session| session.id == id);
+            if !current {
+                return;
+            }
+            let remaining = ends_at - chrono::Utc::now().timestamp();
+            if remaining <= 0 {
+                finish(&app, &id, true).await;
+                return;
+            }
+            crate::tray::set_focus_countdown(&app, Some(countdown_label(remaining)));
+        }
+    });
+}
+
+// ============================================================
+// Commands
+// ============================================================
+
+/// Start a focus session of `duration_minutes`, optionally on a note
+#[tauri::command]
+pub async fn start_focus_session(
+    app: AppHandle,
+    duration_minutes: u32,
+    note_id: Option<String>,
+) -> Result<ActiveFocusSession, AppError> {
+    if !(1..=MAX_MINUTES).contains(&duration_minutes) {
+        return Err(AppError::InvalidInput(format!(
+            "A focus session lasts 1 to {} minutes",
+            MAX_MINUTES
+        )));
+    }
+    let app_data_dir = app.path().app_data_dir()?;
+    let settings = FocusSettings::load(&app_data_dir);
+
+    let started_at = chrono::Utc::now().timestamp();
+    let session = ActiveFocusSession {
+        id: new_id(),
+        note_id: note_id.filter(|id| !id.trim().is_empty()),
+        started_at,
+        ends_at: started_at + i64::from(duration_minutes) * 60,
+        blocked_sources: settings.blocked_sources,
+        held_notices: 0,
+    };
+    {
+        let manager = app.state::<FocusManager>();
+        let mut current = manager.current.lock();
+        if current.is_some() {
+            return Err(AppError::Conflict(
+                "A focus session is already running".to_string(),
+            ));
+        }
+        *current = Some(session.clone());
+    }
+    tracing::info!(duration_minutes, note_id = ?session.note_id, "Focus session started");
+
+    if let Some(shortcut) = settings.start_shortcut {
+        run_shortcut(&shortcut).await;
+    }
+    crate::tray::set_focus_countdown(
+        &app,
+        Some(countdown_label(session.ends_at - session.started_at)),
+    );
+    run_countdown(app.clone(), session.id.clone(), session.ends_at);
+    let _ = app.emit("focus-session-started", &session);
+    Ok(session)
+}
+
+/// End the running focus session early
+#[tauri::command]
+pub async fn end_focus_session(app: AppHandle) -> Result<FocusSession, AppError> {
+    let id = app
+        .state::<FocusManager>()
+        .current
+        .lock()
+        .as_ref()
+        .map(|session| session.id.clone())
+        .ok_or_else(|| AppError::NotFound("No focus session is running".to_string()))?;
+    finish(&app, &id, false)
+        .await
+        .ok_or_else(|| AppError::NotFound("The focus session already ended".to_string()))
+}
+
+/// Get the running focus session, if any
+#[tauri::command]
+pub async fn get_focus_session(app: AppHandle) -> Result<Option<ActiveFocusSession>, AppError> {
+    Ok(app.state::<FocusManager>().current.lock().clone())
+}
+
+/// List finished focus sessions, most recent first
+#[tauri::command]
+pub async fn list_focus_sessions(app: AppHandle) -> Result<Vec<FocusSession>, AppError> {
+    let app_data_dir = app.path().app_data_dir()?;
+    let mut sessions = FocusHistory::load(&app_data_dir).sessions;
+    sessions.reverse();
+    Ok(sessions)
+}
+
+/// Get the focus session settings
+#[tauri::command]
+pub async fn get_focus_settings(app: AppHandle) -> Result<FocusSettings, AppError> {
+    let app_data_dir = app.path().app_data_dir()?;
+    Ok(FocusSettings::load(&app_data_dir))
+}
+
+/// Update the focus session settings; a running session keeps its own
+#[tauri::command]
+pub async fn set_focus_settings(app: AppHandle, settings: FocusSettings) -> Result<(), AppError> {
+    settings.validate().map_err(AppError::InvalidInput)?;
+    let app_data_dir = app.path().app_data_dir()?;
+    settings.save(&app_data_dir)?;
+    Ok(())
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(blocked: &[&str]) -> ActiveFocusSession {
+        ActiveFocusSession {
+            id: "f1".to_string(),
+            note_id: None,
+            started_at: 0,
+            ends_at: 25 * 60,
+            blocked_sources: blocked.iter().map(|s| s.to_string()).collect(),
+            held_notices: 0,
+        }
+    }
+
+    #[test]
+    fn test_blocks() {
+        let session = session(&["usage", "github"]);
+        let notice = |source, severity| Notice::new(source, severity, "Title", "Body");
+        assert!(blocks(&session, &notice("usage", Severity::Warning)));
+        assert!(blocks(&session, &notice("github", Severity::Error)));
+        assert!(!blocks(&session, &notice("disk_space", Severity::Warning)));
+        // Critical notices always get through
+        assert!(!blocks(&session, &notice("usage", Severity::Critical)));
+    }
+
+    #[test]
+    fn test_countdown_label() {
+        assert_eq!(countdown_label(25 * 60), "Focus 25:00");
+        assert_eq!(countdown_label(61), "Focus 1:01");
+        assert_eq!(countdown_label(-5), "Focus 0:00");
+    }
+
+    #[test]
+    fn test_settings_and_history() {
+        assert!(FocusSettings::default().validate().is_ok());
+        let blank = FocusSettings {
+            start_shortcut: Some(" ".to_string()),
+            ..FocusSettings::default()
+        };
+        assert!(blank.validate().is_err());
+
+        let mut history = FocusHistory::default();
+        for i in 0..HISTORY_LIMIT + 2 {
+            history.record(FocusSession {
+                id: i.to_string(),
+                note_id: None,
+                started_at: 0,
+                ended_at: 0,
+                planned_minutes: 25,
+                focused_seconds: 0,
+                completed: false,
+                held_notices: 0,
+                logged: false,
+            });
+        }
+        assert_eq!(history.sessions.len(), HISTORY_LIMIT);
+        assert_eq!(history.sessions[0].id, "2");
+    }
+}
@@ -0,0 +1,280 @@
+//! Git repository roots configured for the backend's Git integration.
+//!
+//! This module provides:
+//! - Parsing of the comma-separated `git_allowed_repository_roots` secret,
+//!   shared with the backend launch
+//! - `validate_git_roots`, which checks that each root is an absolute path to
+//!   an existing directory with git repositories under it
+//! - `list_repositories`, which finds the repositories under the roots so the
+//!   UI can offer a picker instead of a free-text path
+//!
+//! Repositories are found by their `.git` entry, without descending into
+//! them, hidden folders or dependency folders.
+
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+
+use crate::error::AppError;
+
+/// How many levels below a root repositories are looked for; two levels
+/// cover user-scoped roots (`<root>/<user>/<repo>`) with room for grouping
+const MAX_DEPTH: usize = 4;
+
+/// Most repositories listed per root
+const MAX_REPOSITORIES: usize = 500;
+
+/// Folders never searched for repositories
+const SKIPPED_DIRS: &[&str] = &["node_modules", "target", "vendor"];
+
+/// What was found at a configured root
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GitRootStatus {
+    pub path: String,
+    pub exists: bool,
+    pub is_dir: bool,
+    pub repository_count: usize,
+    /// Why the backend can't serve repositories from this root, if it can't
+    pub problem: Option<String>,
+}
+
+/// A repository under one of the roots
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GitRepository {
+    /// Folder name
+    pub name: String,
+    pub path: PathBuf,
+    /// Root the repository was found under
+    pub root: String,
+}
+
+/// Roots in a comma-separated setting, as passed to the backend
+pub fn parse_roots(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|root| !root.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn is_repository(dir: &Path) -> bool {
+    dir.join(".git").exists()
+}
+
+fn should_search(dir: &Path) -> bool {
+    let Some(name) = dir.file_name().map(|name| name.to_string_lossy()) else {
+        return false;
+    };
+    !name.starts_with('.') && !SKIPPED_DIRS.contains(&name.as_ref())
+}
+
+/// Repositories at or below `root`, sorted by path
+fn find_repositories(root: &Path) -> Vec<PathBuf> {
+    let mut found = Vec::new();
+    let mut pending = vec![(root.to_path_buf(), 0)];
+    while let Some((dir, depth)) = pending.pop() {
+        if found.len() >= MAX_REPOSITORIES {
+            tracing::debug!("Stopped listing repositories under {:?}", root);
+            break;
+        }
+        if is_repository(&dir) {
+            found.push(dir);
+            continue;
+        }
+        if depth >= MAX_DEPTH {
+            continue;
+        }
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        // Symlinked folders are skipped so links can't loop
+        pending.extend(
+            entries
+                .flatten()
+                .filter(|entry| entry.file_type().is_ok_and(|t| t.is_dir()))
+                .map(|entry| entry.path())
+                .filter(|path| should_search(path))
+                .map(|path| (path, depth + 1)),
+        );
+    }
+    found.sort();
+    found
+}
+
+fn check_root(root: &str) -> GitRootStatus {
+    let path = Path::new(root);
+    let exists = path.exists();
+    let is_dir = path.is_dir();
+    let repository_count = if is_dir {
+        find_repositories(path).len()
+    } else {
+        0
+    };
+    let problem = if !path.is_absolute() {
+        Some("Must be an absolute path".to_string())
+    } else if !exists {
+        Some("Doesn't exist".to_string())
+    } else if !is_dir {
+        Some("Isn't a folder".to_string())
+    } else if repository_count == 0 {
+        Some("Contains no git repositories".to_string())
+    } else {
+        None
+    };
+    GitRootStatus {
+        path: root.to_string(),
+        exists,
+        is_dir,
+        repository_count,
+        problem,
+    }
+}
+
+/// Check each configured root
+pub fn validate_roots(roots: &[String]) -> Vec<GitRootStatus> {
+    roots.iter().map(|root| check_root(root)).collect()
+}
+
+async fn configured_roots(app: &AppHandle) -> Result<Vec<String>, AppError> {
+    let app_data_dir = app.path().app_data_dir()?;
+    let secrets = crate::load_secrets_async(app_data_dir).await;
+    Ok(secrets
+        .git_allowed_repository_roots
+        .as_deref()
+        .map(parse_roots)
+        .unwrap_or_default())
+}
+
+// ============================================================
+// Commands
+// ============================================================
+
+/// Check git repository roots, the configured ones unless `roots` is given
+///
+/// `roots` uses the setting's comma-separated format so a value can be
+/// checked before it's saved.
+#[tauri::command]
+pub async fn validate_git_roots(
+    app: AppHandle,
+    roots: Option<String>,
+) -> Result<Vec<GitRootStatus>, AppError> {
+    let roots = match roots {
+        Some(value) => parse_roots(&value),
+        None => configured_roots(&app).await?,
+    };
+    Ok(tokio::task::spawn_blocking(move || validate_roots(&roots)).await?)
+}
+
+/// List git repositories under the configured roots
+#[tauri::command]
+pub async fn list_repositories(app: AppHandle) -> Result<Vec<GitRepository>, AppError> {
+    let roots = configured_roots(&app).await?;
+    if roots.is_empty() {
+        return Err(AppError::NotReady(
+            "No git repository roots configured".to_string(),
+        ));
+    }
+    Ok(tokio::task::spawn_blocking(move || {
+        roots
+            .iter()
+            .flat_map(|root| {
+                find_repositories(Path::new(root))
+                    .into_iter()
+                    .map(move |path| GitRepository {
+                        name: path
+                            .file_name()
+                            .map(|name| name.to_string_lossy().to_string())
+                            .unwrap_or_default(),
+                        path,
+                        root: root.clone(),
+                    })
+            })
+            .collect()
+    })
+    .await?)
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_roots() {
+        assert_eq!(
+            parse_roots(" /home/a/repos, ,/srv/git "),
+            vec!["/home/a/repos", "/srv/git"]
+        );
+        assert!(parse_roots("").is_empty());
+    }
+
+    #[test]
+    fn test_find_repositories() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        for dir in [
+            "user-1/notes/.git",
+            "user-1/notes/vendor/lib/.git",
+            "user-1/work/api/.git",
+            "user-1/node_modules/pkg/.git",
+            ".cache/mirror/.git",
+            "empty",
+        ] {
+            std::fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        // A worktree's .git is a file
+        std::fs::create_dir_all(root.join("user-1/tree")).unwrap();
+        std::fs::write(root.join("user-1/tree/.git"), "gitdir: ../notes/.git").unwrap();
+
+        assert_eq!(
+            find_repositories(root),
+            vec![
+                root.join("user-1/notes"),
+                root.join("user-1/tree"),
+                root.join("user-1/work/api"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_roots() {
+        let temp_dir = TempDir::new().unwrap();
+        let repos = temp_dir.path().join("repos");
+        std::fs::create_dir_all(repos.join("app/.git")).unwrap();
+        let file = temp_dir.path().join("file.txt");
+        std::fs::write(&file, "").unwrap();
+        let empty = temp_dir.path().join("empty");
+        std::fs::create_dir_all(&empty).unwrap();
+        let missing = temp_dir.path().join("missing");
+
+        let roots: Vec<String> = [
+            repos.as_path(),
+            file.as_path(),
+            empty.as_path(),
+            missing.as_path(),
+            Path::new("relative/repos"),
+        ]
+        .iter()
+        .map(|path| path.to_string_lossy().to_string())
+        .collect();
+        let problems: Vec<Option<String>> = validate_roots(&roots)
+            .into_iter()
+            .map(|status| status.problem)
+            .collect();
+        assert_eq!(
+            problems,
+            vec![
+                None,
+                Some("Isn't a folder".to_string()),
+                Some("Contains no git repositories".to_string()),
+                Some("Doesn't exist".to_string()),
+                Some("Must be an absolute path".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,319 @@
+//! GitHub personal access token checks.
+//!
+//! This module provides:
+//! - `check_github_token`, which asks GitHub about the configured token and
+//!   reports its scopes, remaining rate limit and expiry
+//! - A check at launch and once a day while a token is configured, with a
+//!   notification when the token is about to expire or missing scopes
+//! - The latest result for the diagnostic report
+//!
+//! Scopes can only be verified for classic tokens; fine-grained tokens don't
+//! report them, so only their expiry and rate limit are checked.
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+use crate::error::AppError;
+use crate::notifications::{Notice, Severity};
+
+/// Endpoint used for the check; any authenticated call returns the headers
+const USER_URL: &str = "https://api.github.com/user";
+
+/// Classic token scopes the backend's GitHub integration needs
+const REQUIRED_SCOPES: &[&str] = &["repo"];
+
+/// Expiry closer than this counts as soon
+const EXPIRY_WARNING: chrono::Duration = chrono::Duration::days(7);
+
+/// Remaining requests below this fraction of the limit count as low
+const LOW_RATE_LIMIT: f64 = 0.1;
+
+/// Time between checks
+const CHECK_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Timeout for the request
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// GitHub's REST API rate limit for the token
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RateLimit {
+    pub limit: u32,
+    pub remaining: u32,
+    /// When the limit resets (Unix epoch seconds)
+    pub reset_at: i64,
+}
+
+/// Result of a token check
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GitHubTokenCheck {
+    /// Account the token belongs to
+    pub login: Option<String>,
+    /// Scopes granted, None for fine-grained tokens
+    pub scopes: Option<Vec<String>>,
+    /// Required scopes the token lacks
+    pub missing_scopes: Vec<String>,
+    pub rate_limit: Option<RateLimit>,
+    /// When the token expires (Unix epoch seconds), None if it doesn't
+    pub expires_at: Option<i64>,
+    pub expiring_soon: bool,
+    /// Problems to show the user
+    pub warnings: Vec<String>,
+    /// When the check ran (Unix epoch seconds)
+    pub checked_at: i64,
+}
+
+/// Latest token check, kept in Tauri state
+#[derive(Default)]
+pub struct GitHubTokenMonitor {
+    last: Mutex<Option<GitHubTokenCheck>>,
+}
+
+#[derive(Deserialize)]
+struct User {
+    login: String,
+}
+
+/// Scopes from an `X-OAuth-Scopes` header
+fn parse_scopes(header: &str) -> Vec<String> {
+    header
+        .split(',')
+        .map(str::trim)
+        .filter(|scope| !scope.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Required scopes not in `scopes`
+fn missing_scopes(scopes: &[String]) -> Vec<String> {
+    REQUIRED_SCOPES
+        .iter()
+        .filter(|required| !scopes.iter().any(|scope| scope == *required))
+        .map(|required| required.to_string())
+        .collect()
+}
+
+/// Time from a `GitHub-Authentication-Token-Expiration` header, which is
+/// either `2026-11-01 00:00:00 UTC` or has a numeric offset
+fn parse_expiration(header: &str) -> Option<DateTime<Utc>> {
+    let header = header.trim();
+    if let Ok(time) = DateTime::parse_from_str(header, "%Y-%m-%d %H:%M:%S %z") {
+        return Some(time.with_timezone(&Utc));
+    }
+    let naive = header.strip_suffix(" UTC")?;
+    NaiveDateTime::parse_from_str(naive, "%Y-%m-%d %H:%M:%S")
+        .ok()
+        .map(|time| time.and_utc())
+}
+
+/// Warnings for a check's results
+fn warnings(check: &GitHubTokenCheck, now: DateTime<Utc>) -> Vec<String> {
+    let mut warnings = Vec::new();
+    if !check.missing_scopes.is_empty() {
+        warnings.push(format!(
+            "The token is missing the {} scope; GitHub features may fail for private \
+             repositories",
+            check.missing_scopes.join(", ")
+        ));
+    }
+    if let Some(expires_at) = check
+        .expires_at
+        .and_then(|at| DateTime::from_timestamp(at, 0))
+    {
+        if expires_at <= now {
+            warnings.push("The token has expired".to_string());
+        } else if check.expiring_soon {
+            warnings.push(format!(
+                "The token expires on {}",
+                expires_at.format("%Y-%m-%d")
+            ));
+        }
+    }
+    if let Some(rate) = &check.rate_limit {
+        if (rate.remaining as f64) < rate.limit as f64 * LOW_RATE_LIMIT {
+            warnings.push(format!(
+                "Only {} of {} GitHub API requests left until the limit resets",
+                rate.remaining, rate.limit
+            ));
+        }
+    }
+    warnings
+}
+
+fn header<'a>(response: &'a reqwest::Response, name: &str) -> Option<&'a str> {
+    response
+        .headers()
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+}
+
+/// Ask GitHub about the configured token
+async fn inspect(app: &AppHandle) -> Result<GitHubTokenCheck, AppError> {
+    let app_data_dir = app.path().app_data_dir()?;
+    let token = crate::load_secrets_async(app_data_dir)
+        .await
+        .github_personal_access_token
+        .filter(|token| !token.trim().is_empty())
+        .ok_or_else(|| AppError::NotReady("No GitHub token configured".to_string()))?;
+
+    let response = crate::http::external(app)
+        .get(USER_URL)
+        .bearer_auth(token.trim())
+        .header(reqwest::header::ACCEPT, "application/vnd.github+json")
+        .timeout(REQUEST_TIMEOUT)
+        .send()
+        .await?;
+    if response.status() == reqwest::StatusCode::UNAUTHORIZED {
+        return Err(AppError::Permission(
+            "GitHub rejected the token; it may have expired or been revoked".to_string(),
+        ));
+    }
+
+    let now = Utc::now();
+    let scopes = header(&response, "x-oauth-scopes").map(parse_scopes);
+    let expires_at =
+        header(&response, "github-authentication-token-expiration").and_then(parse_expiration);
+    let rate_limit = match (
+        header(&response, "x-ratelimit-limit").and_then(|v| v.parse().ok()),
+        header(&response, "x-ratelimit-remaining").and_then(|v| v.parse().ok()),
+        header(&response, "x-ratelimit-reset").and_then(|v| v.parse().ok()),
+    ) {
+        (Some(limit), Some(remaining), Some(reset_at)) => Some(RateLimit {
+            limit,
+            remaining,
+            reset_at,
+        }),
+        _ => None,
+    };
+    let status = response.status();
+    let login = if status.is_success() {
+        response.json::<User>().await.ok().map(|user| user.login)
+    } else {
+        tracing::debug!("GitHub token check returned {}", status);
+        None
+    };
+
+    let mut check = GitHubTokenCheck {
+        login,
+        missing_scopes: scopes.as_deref().map(missing_scopes).unwrap_or_default(),
+        scopes,
+        rate_limit,
+        expires_at: expires_at.map(|at| at.timestamp()),
+        expiring_soon: expires_at.is_some_and(|at| at - now < EXPIRY_WARNING),
+        warnings: Vec::new(),
+        checked_at: now.timestamp(),
+    };
+    check.warnings = warnings(&check, now);
+    Ok(check)
+}
+
+/// Check the token, record the result and warn about problems
+pub async fn check(app: &AppHandle) -> Result<GitHubTokenCheck, AppError> {
+    let result = inspect(app).await?;
+    *app.state::<GitHubTokenMonitor>().last.lock() = Some(result.clone());
+    if !result.warnings.is_empty() {
+        tracing::warn!(warnings = ?result.warnings, "GitHub token needs attention");
+        crate::notifications::notify(
+            app,
+            Notice::new(
+                "github",
+                Severity::Warning,
+                "Your GitHub token needs attention",
+                result.warnings.join(". "),
+            ),
+        );
+    }
+    Ok(result)
+}
+
+/// Latest token check, if any has succeeded
+pub fn last_check(app: &AppHandle) -> Option<GitHubTokenCheck> {
+    app.try_state::<GitHubTokenMonitor>()
+        .and_then(|monitor| monitor.last.lock().clone())
+}
+
+/// Check the token at launch and daily after that
+pub fn start(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+            if let Err(e) = check(&app).await {
+                tracing::debug!("GitHub token check skipped: {}", e);
+            }
+        }
+    });
+}
+
+// ============================================================
+// Commands
+// ============================================================
+
+/// Check the configured GitHub token's scopes, rate limit and expiry
+#[tauri::command]
+pub async fn check_github_token(app: AppHandle) -> Result<GitHubTokenCheck, AppError> {
+    check(&app).await
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_scopes() {
+        let scopes = parse_scopes("repo, read:org,  workflow");
+        assert_eq!(scopes, vec!["repo", "read:org", "workflow"]);
+        assert!(missing_scopes(&scopes).is_empty());
+        assert_eq!(missing_scopes(&parse_scopes("public_repo")), vec!["repo"]);
+        assert_eq!(missing_scopes(&parse_scopes("")), vec!["repo"]);
+    }
+
+    #[test]
+    fn test_parse_expiration() {
+        let expected = Utc.with_ymd_and_hms(2026, 11, 1, 12, 30, 0).unwrap();
+        assert_eq!(parse_expiration("2026-11-01 12:30:00 UTC"), Some(expected));
+        assert_eq!(
+            parse_expiration("2026-11-01 13:30:00 +0100"),
+            Some(expected)
+        );
+        assert_eq!(parse_expiration("never"), None);
+    }
+
+    #[test]
+    fn test_warnings() {
+        let now = Utc.with_ymd_and_hms(2026, 10, 28, 0, 0, 0).unwrap();
+        let mut check = GitHubTokenCheck {
+            login: Some("octocat".to_string()),
+            scopes: Some(vec!["repo".to_string()]),
+            missing_scopes: Vec::new(),
+            rate_limit: Some(RateLimit {
+                limit: 5000,
+                remaining: 4999,
+                reset_at: 0,
+            }),
+            expires_at: None,
+            expiring_soon: false,
+            warnings: Vec::new(),
+            checked_at: now.timestamp(),
+        };
+        assert!(warnings(&check, now).is_empty());
+
+        check.expires_at = Some((now + chrono::Duration::days(3)).timestamp());
+        check.expiring_soon = true;
+        check.rate_limit.as_mut().unwrap().remaining = 20;
+        let found = warnings(&check, now);
+        assert_eq!(found.len(), 2);
+        assert!(found[0].contains("2026-10-31"));
+
+        check.expires_at = Some((now - chrono::Duration::days(1)).timestamp());
+        assert_eq!(warnings(&check, now)[0], "The token has expired");
+    }
+}
@@ -0,0 +1,186 @@
+//! Headless mode (`--headless`).
+//!
+//! This module provides:
+//! - Service status written to stdout as one JSON object per line, each
+//!   time PostgreSQL or the backend changes phase
+//! - SIGTERM and SIGINT handling that exits through the normal shutdown
+//!   path, so services are stopped exactly as when quitting from the tray
+//! - A non-zero exit when services fail before first becoming ready
+//!
+//! No window, tray or app menu is created in this mode; the app runs until
+//! signalled.
+
+use serde::Serialize;
+use std::io::Write;
+use tauri::{AppHandle, Manager};
+
+use crate::error::AppError;
+use crate::services::{ServiceManager, ServicePhase, ServiceState};
+use crate::AppState;
+
+/// Exit code when services fail to start
+const STARTUP_FAILED_EXIT: i32 = 1;
+
+/// One status line
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct StatusLine<'a> {
+    status: &'static str,
+    postgres: ServicePhase,
+    backend: ServicePhase,
+    api_url: Option<String>,
+    postgres_port: u16,
+    pid: u32,
+    error: Option<&'a AppError>,
+}
+
+/// Overall status for a service state
+fn overall(state: &ServiceState) -> &'static str {
+    match (state.postgres, state.backend) {
+        (ServicePhase::Failed, _) | (_, ServicePhase::Failed) => "failed",
+        (ServicePhase::Running, ServicePhase::Running) => "running",
+        (ServicePhase::Stopping, _) | (_, ServicePhase::Stopping) => "stopping",
+        (ServicePhase::Stopped, ServicePhase::Stopped) => "stopped",
+        _ => "starting",
+    }
+}
+
+fn status_line<'a>(
+    state: &'a ServiceState,
+    backend_port: u16,
+    postgres_port: u16,
+) -> StatusLine<'a> {
+    let status = overall(state);
+    StatusLine {
+        status,
+        postgres: state.postgres,
+        backend: state.backend,
+        api_url: (status == "running").then(|| crate::api_endpoint::api_url(backend_port)),
+        postgres_port,
+        pid: std::process::id(),
+        error: state.last_error.as_ref(),
+    }
+}
+
+fn print_status(app: &AppHandle, state: &ServiceState) {
+    let app_state = app.state::<AppState>();
+    let line = status_line(
+        state,
+        *app_state.backend_port.read(),
+        *app_state.postgres_port.read(),
+    );
+    if let Ok(json) = serde_json::to_string(&line) {
+        let mut stdout = std::io::stdout().lock();
+        let _ = writeln!(stdout, "{}", json);
+        let _ = stdout.flush();
+    }
+}
+
+/// Exit through the normal shutdown path on SIGTERM or SIGINT
+fn handle_signals(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        #[cfg(unix)]
+        {
+            use tokio::signal::unix::{signal, SignalKind};
+            let (Ok(mut term), Ok(mut int)) = (
+                signal(SignalKind::terminate()),
+                signal(SignalKind::interrupt()),
+            ) else {
+                tracing::error!("Failed to install signal handlers");
+                return;
+            };
+            let name = tokio::select! {
+                _ = term.recv() => "SIGTERM",
+                _ = int.recv() => "SIGINT",
+            };
+            tracing::info!("Received {}, shutting down", name);
+        }
+        #[cfg(not(unix))]
+        {
+            if tokio::signal::ctrl_c().await.is_err() {
+                return;
+            }
+            tracing::info!("Received Ctrl-C, shutting down");
+        }
+        // ExitRequested stops the services before the process ends
+        app.exit(0);
+    });
+}
+
+/// Report status and handle signals; called from setup once services are managed
+pub fn start(app: &AppHandle) {
+    #[cfg(target_os = "macos")]
+    let _ = app.set_activation_policy(tauri::ActivationPolicy::Accessory);
+
+    handle_signals(app);
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut services = app.state::<ServiceManager>().subscribe();
+        let mut printed: Option<(ServicePhase, ServicePhase)> = None;
+        let mut ready_once = false;
+        loop {
+            let state = services.borrow_and_update().clone();
+            let phases = (state.postgres, state.backend);
+            if printed != Some(phases) {
+                print_status(&app, &state);
+                printed = Some(phases);
+            }
+            match overall(&state) {
+                "running" => ready_once = true,
+                "failed" if !ready_once => {
+                    tracing::error!("Services failed to start in headless mode");
+                    app.exit(STARTUP_FAILED_EXIT);
+                    break;
+                }
+                _ => {}
+            }
+            if services.changed().await.is_err() {
+                break;
+            }
+        }
+    });
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(postgres: ServicePhase, backend: ServicePhase) -> ServiceState {
+        ServiceState {
+            postgres,
+            backend,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_overall() {
+        use ServicePhase::*;
+        assert_eq!(overall(&state(Running, Running)), "running");
+        assert_eq!(overall(&state(Running, Starting)), "starting");
+        assert_eq!(overall(&state(Failed, Stopped)), "failed");
+        assert_eq!(overall(&state(Running, Stopping)), "stopping");
+        assert_eq!(overall(&state(Stopped, Stopped)), "stopped");
+    }
+
+    #[test]
+    fn test_status_line_json() {
+        use ServicePhase::*;
+        let running = state(Running, Running);
+        let json = serde_json::to_value(status_line(&running, 5001, 5433)).unwrap();
+        assert_eq!(json["status"], "running");
+        assert_eq!(json["api_url"], "http://localhost:5001/api");
+        assert_eq!(json["postgres_port"], 5433);
+        assert_eq!(json["backend"], "running");
+
+        let starting = state(Running, Starting);
+        let json = serde_json::to_value(status_line(&starting, 5001, 5433)).unwrap();
+        assert_eq!(json["status"], "starting");
+        assert!(json["api_url"].is_null());
+    }
+}
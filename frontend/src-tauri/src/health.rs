@@ -0,0 +1,197 @@
+//! Backend health monitoring after startup.
+//!
+//! This module provides:
+//! - A background loop checking `/api/health` on the adaptive schedule from
+//!   `StartupConfig::health_interval`
+//! - An immediate re-check whenever the service state changes, including
+//!   when the backend process terminates
+//! - `backend-health` events when the backend becomes unhealthy or recovers
+//!
+//! The startup wait in `wait_for_backend_ready` covers the backend until it
+//! first answers; this loop takes over once it's running.
+
+use parking_lot::RwLock;
+use serde::Serialize;
+use std::time::Instant;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::error::AppError;
+use crate::services::{ServiceManager, ServicePhase};
+use crate::startup::random_jitter;
+use crate::AppState;
+
+/// Failed checks in a row before a running backend is reported unhealthy
+const FAILURE_THRESHOLD: u32 = 3;
+
+/// Latest health check result
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct BackendHealth {
+    pub healthy: bool,
+    pub consecutive_failures: u32,
+    /// Response time of the last successful check (milliseconds)
+    pub latency_ms: Option<u64>,
+    /// Last check (Unix epoch seconds)
+    pub checked_at: Option<i64>,
+    pub last_error: Option<String>,
+}
+
+impl BackendHealth {
+    /// Apply one check result; returns true when healthy/unhealthy flipped
+    fn record(&mut self, result: Result<u64, String>, now: i64) -> bool {
+        let was_healthy = self.healthy;
+        self.checked_at = Some(now);
+        match result {
+            Ok(latency_ms) => {
+                self.consecutive_failures = 0;
+                self.latency_ms = Some(latency_ms);
+                self.last_error = None;
+                self.healthy = true;
+            }
+            Err(e) => {
+                self.consecutive_failures += 1;
+                self.last_error = Some(e);
+                if self.consecutive_failures >= FAILURE_THRESHOLD {
+                    self.healthy = false;
+                }
+            }
+        }
+        was_healthy != self.healthy
+    }
+}
+
+/// Health state kept in Tauri state
+#[derive(Default)]
+pub struct HealthMonitor {
+    health: RwLock<BackendHealth>,
+}
+
+impl HealthMonitor {
+    /// Latest check result
+    pub fn snapshot(&self) -> BackendHealth {
+        self.health.read().clone()
+    }
+
+    /// False only once checks have failed; a backend not yet checked counts as healthy
+    pub fn is_healthy(&self) -> bool {
+        let health = self.health.read();
+        health.healthy || health.checked_at.is_none()
+    }
+}
+
+async fn check(app: &AppHandle) -> Result<u64, String> {
+    let (port, health) = {
+        let state = app.state::<AppState>();
+        let port = *state.backend_port.read();
+        let health = state.startup_deps.read().health.clone();
+        (port, health)
+    };
+    let started = Instant::now();
+    health.check(port).await?;
+    Ok(started.elapsed().as_millis() as u64)
+}
+
+/// Start the monitoring loop
+pub fn start(app: &AppHandle) {
+    app.manage(HealthMonitor::default());
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut services = app.state::<ServiceManager>().subscribe();
+        let mut restarted = Instant::now();
+
+        loop {
+            let (healthy, failures) = {
+                let health = app.state::<HealthMonitor>().health.read().clone();
+                (health.healthy, health.consecutive_failures)
+            };
+            let config = crate::startup_config(&app);
+            let interval =
+                config.health_interval(restarted.elapsed(), failures, healthy, random_jitter());
+
+            tokio::select! {
+                _ = tokio::time::sleep(interval) => {}
+                changed = services.changed() => {
+                    if changed.is_err() {
+                        break;
+                    }
+                    // Re-check right away and return to fast checks
+                    restarted = Instant::now();
+                }
+            }
+
+            // While starting or stopping, the service manager owns readiness
+            let phase = services.borrow().backend;
+            if !matches!(phase, ServicePhase::Running | ServicePhase::Failed) {
+                continue;
+            }
+
+            let result = check(&app).await;
+            let now = chrono::Utc::now().timestamp();
+            let (flipped, health) = {
+                let monitor = app.state::<HealthMonitor>();
+                let mut health = monitor.health.write();
+                let flipped = health.record(result, now);
+                (flipped, health.clone())
+            };
+            if flipped {
+                if health.healthy {
+                    tracing::info!("Backend is healthy again");
+                    crate::write_queue::replay_soon(&app);
+                } else {
+                    tracing::warn!(
+                        "Backend failed {} health checks: {}",
+                        health.consecutive_failures,
+                        health.last_error.as_deref().unwrap_or("unknown error")
+                    );
+                }
+                if phase == ServicePhase::Running {
+                    *app.state::<AppState>().is_backend_ready.write() = health.healthy;
+                }
+                crate::startup_trace::record_health(
+                    &app,
+                    health.healthy,
+                    health.last_error.clone(),
+                );
+                let _ = app.emit("backend-health", &health);
+                crate::tray::refresh_status(&app);
+                crate::accessibility::refresh(&app);
+            }
+        }
+    });
+}
+
+/// Get the latest backend health check result
+#[tauri::command]
+pub async fn get_backend_health(app: AppHandle) -> Result<BackendHealth, AppError> {
+    Ok(app
+        .try_state::<HealthMonitor>()
+        .map(|monitor| monitor.snapshot())
+        .unwrap_or_default())
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_flips_after_threshold() {
+        let mut health = BackendHealth::default();
+        assert!(health.record(Ok(12), 1));
+        assert_eq!(health.latency_ms, Some(12));
+
+        for i in 1..FAILURE_THRESHOLD {
+            assert!(!health.record(Err("refused".to_string()), 1 + i as i64));
+            assert!(health.healthy);
+        }
+        assert!(health.record(Err("refused".to_string()), 10));
+        assert!(!health.healthy);
+        assert_eq!(health.consecutive_failures, FAILURE_THRESHOLD);
+
+        assert!(health.record(Ok(8), 11));
+        assert_eq!(health.consecutive_failures, 0);
+        assert!(health.last_error.is_none());
+    }
+}
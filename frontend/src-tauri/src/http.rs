@@ -0,0 +1,210 @@
+//! Shared HTTP clients.
+//!
+//! This module provides:
+//! - One pooled client for calls to the local backend (health checks,
+//!   proxying, uploads), which never goes through a proxy
+//! - One pooled client for remote services (feeds, cloud backup targets,
+//!   reachability tests), which honours the configured proxy
+//! - Proxy settings persisted in http-settings.json
+//!
+//! Clients carry connect timeouts and pool settings only; each request sets
+//! its own overall timeout since a health check and a multi-gigabyte upload
+//! need very different limits.
+
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+use crate::config::{load_json, save_json_atomic};
+use crate::error::AppError;
+
+/// How long idle pooled connections are kept
+const POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+
+/// Idle connections kept per host
+const POOL_MAX_IDLE_PER_HOST: usize = 8;
+
+/// Connection settings for remote services
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HttpSettings {
+    /// Proxy URL (http or https); None uses the system proxy
+    pub proxy_url: Option<String>,
+    /// Comma-separated hosts that bypass the proxy
+    pub no_proxy: Option<String>,
+}
+
+impl HttpSettings {
+    pub fn path(app_data_dir: &Path) -> PathBuf {
+        app_data_dir.join("http-settings.json")
+    }
+
+    pub fn load(app_data_dir: &Path) -> Self {
+        load_json(&Self::path(app_data_dir)).unwrap_or_default()
+    }
+
+    pub fn save(&self, app_data_dir: &Path) -> Result<(), String> {
+        save_json_atomic(&Self::path(app_data_dir), self)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(ref url) = self.proxy_url {
+            let parsed =
+                reqwest::Url::parse(url).map_err(|e| format!("Invalid proxy URL: {}", e))?;
+            if !matches!(parsed.scheme(), "http" | "https") {
+                return Err("Proxy URL must use http or https".to_string());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// HTTP clients kept in Tauri state
+pub struct HttpClients {
+    backend: reqwest::Client,
+    external: RwLock<reqwest::Client>,
+    user_agent: String,
+}
+
+impl HttpClients {
+    pub fn new(user_agent: String, settings: &HttpSettings) -> Result<Self, String> {
+        let backend = reqwest::Client::builder()
+            .no_proxy()
+            .connect_timeout(Duration::from_secs(2))
+            .pool_idle_timeout(POOL_IDLE_TIMEOUT)
+            .pool_max_idle_per_host(POOL_MAX_IDLE_PER_HOST)
+            .build()
+            .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+        Ok(Self {
+            backend,
+            external: RwLock::new(build_external(&user_agent, settings)?),
+            user_agent,
+        })
+    }
+
+    /// Rebuild the remote client after the proxy settings changed
+    fn reconfigure(&self, settings: &HttpSettings) -> Result<(), String> {
+        *self.external.write() = build_external(&self.user_agent, settings)?;
+        Ok(())
+    }
+}
+
+fn build_external(user_agent: &str, settings: &HttpSettings) -> Result<reqwest::Client, String> {
+    let mut builder = reqwest::Client::builder()
+        .connect_timeout(Duration::from_secs(10))
+        .pool_idle_timeout(POOL_IDLE_TIMEOUT)
+        .pool_max_idle_per_host(POOL_MAX_IDLE_PER_HOST)
+        .user_agent(user_agent);
+    if let Some(ref url) = settings.proxy_url {
+        let proxy = reqwest::Proxy::all(url)
+            .map_err(|e| format!("Invalid proxy URL: {}", e))?
+            .no_proxy(
+                settings
+                    .no_proxy
+                    .as_deref()
+                    .and_then(reqwest::NoProxy::from_string),
+            );
+        builder = builder.proxy(proxy);
+    }
+    builder
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))
+}
+
+/// Create the clients and add them to Tauri state
+pub fn init(app: &AppHandle) -> Result<(), String> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let user_agent = format!("SecondBrain/{}", app.package_info().version);
+    let settings = HttpSettings::load(&app_data_dir);
+    let clients = match HttpClients::new(user_agent.clone(), &settings) {
+        Ok(clients) => clients,
+        Err(e) => {
+            // A bad saved proxy must not take the app down; fall back to the system proxy
+            tracing::warn!("Ignoring saved proxy settings: {}", e);
+            HttpClients::new(user_agent, &HttpSettings::default())?
+        }
+    };
+    app.manage(clients);
+    Ok(())
+}
+
+/// Client for requests to the local backend
+pub fn backend(app: &AppHandle) -> reqwest::Client {
+    app.state::<HttpClients>().backend.clone()
+}
+
+/// Client for requests to remote services
+pub fn external(app: &AppHandle) -> reqwest::Client {
+    app.state::<HttpClients>().external.read().clone()
+}
+
+/// Get the proxy settings for remote services
+#[tauri::command]
+pub async fn get_http_settings(app: AppHandle) -> Result<HttpSettings, AppError> {
+    let app_data_dir = app.path().app_data_dir()?;
+    Ok(HttpSettings::load(&app_data_dir))
+}
+
+/// Save the proxy settings and apply them to new requests
+#[tauri::command]
+pub async fn set_http_settings(app: AppHandle, settings: HttpSettings) -> Result<(), AppError> {
+    settings.validate().map_err(AppError::InvalidInput)?;
+    let app_data_dir = app.path().app_data_dir()?;
+    app.state::<HttpClients>()
+        .reconfigure(&settings)
+        .map_err(AppError::InvalidInput)?;
+    settings.save(&app_data_dir)?;
+    tracing::info!(
+        "HTTP proxy set to {}",
+        settings.proxy_url.as_deref().unwrap_or("system default")
+    );
+    Ok(())
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_settings_validation() {
+        assert!(HttpSettings::default().validate().is_ok());
+        let proxy = HttpSettings {
+            proxy_url: Some("https://127.0.0.1:3128".to_string()),
+            no_proxy: Some("localhost,.internal".to_string()),
+        };
+        assert!(proxy.validate().is_ok());
+        assert!(HttpSettings {
+            proxy_url: Some("ftp://proxy".to_string()),
+            no_proxy: None,
+        }
+        .validate()
+        .is_err());
+        assert!(HttpSettings {
+            proxy_url: Some("not a url".to_string()),
+            no_proxy: None,
+        }
+        .validate()
+        .is_err());
+    }
+
+    #[test]
+    fn test_settings_roundtrip_and_clients() {
+        let temp_dir = TempDir::new().unwrap();
+        let settings = HttpSettings {
+            proxy_url: Some("http://proxy.example:3128".to_string()),
+            no_proxy: None,
+        };
+        settings.save(temp_dir.path()).unwrap();
+        assert_eq!(HttpSettings::load(temp_dir.path()), settings);
+
+        let clients = HttpClients::new("SecondBrain/test".to_string(), &settings).unwrap();
+        assert!(clients.reconfigure(&HttpSettings::default()).is_ok());
+    }
+}
@@ -0,0 +1,248 @@
+//! User idle detection.
+//!
+//! This module provides:
+//! - Time since the last keyboard or mouse input anywhere on the system
+//!   (`ioreg` on macOS, `GetLastInputInfo` on Windows, `xprintidle` or
+//!   GNOME's idle monitor on Linux)
+//! - A background loop tracking whether the user is away, emitting
+//!   `user-idle` and `user-active` events when that changes
+//! - `is_user_idle`, which the scheduler uses to hold heavy jobs until the
+//!   user steps away
+//!
+//! When idle time can't be read, the user counts as idle so background work
+//! is never blocked on unsupported platforms.
+
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+use crate::error::AppError;
+
+/// Time without input after which the user is considered away
+const IDLE_THRESHOLD: Duration = Duration::from_secs(5 * 60);
+
+/// How often idle time is checked
+const POLL_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Idle state, emitted with `user-idle` and `user-active`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct IdleState {
+    pub idle: bool,
+    /// Seconds since the last input, if the platform reports it
+    pub idle_secs: Option<u64>,
+}
+
+impl IdleState {
+    fn from_idle_time(idle_time: Option<Duration>) -> Self {
+        Self {
+            idle: idle_time.map_or(true, |time| time >= IDLE_THRESHOLD),
+            idle_secs: idle_time.map(|time| time.as_secs()),
+        }
+    }
+}
+
+/// Latest idle state, kept in Tauri state
+#[derive(Default)]
+pub struct IdleMonitor {
+    state: Mutex<Option<IdleState>>,
+}
+
+impl IdleMonitor {
+    /// Record a reading; returns the new state if idle or active flipped
+    fn update(&self, next: IdleState) -> Option<IdleState> {
+        let previous = self.state.lock().replace(next);
+        match previous {
+            Some(previous) if previous.idle == next.idle => None,
+            // The first reading only announces that the user is away
+            None if !next.idle => None,
+            _ => Some(next),
+        }
+    }
+}
+
+/// Time since the last user input, or `None` if it can't be determined
+pub fn system_idle_time() -> Option<Duration> {
+    #[cfg(target_os = "macos")]
+    {
+        std::process::Command::new("ioreg")
+            .args(["-c", "IOHIDSystem", "-d", "4", "-r"])
+            .output()
+            .ok()
+            .and_then(|o| parse_ioreg_output(&String::from_utf8_lossy(&o.stdout)))
+    }
+
+    #[cfg(windows)]
+    {
+        use windows::Win32::System::SystemInformation::GetTickCount;
+        use windows::Win32::UI::Input::KeyboardAndMouse::{GetLastInputInfo, LASTINPUTINFO};
+
+        let mut info = LASTINPUTINFO {
+            cbSize: std::mem::size_of::<LASTINPUTINFO>() as u32,
+            dwTime: 0,
+        };
+        if !unsafe { GetLastInputInfo(&mut info) }.as_bool() {
+            return None;
+        }
+        // Both tick counts wrap after 49.7 days
+        let elapsed = unsafe { GetTickCount() }.wrapping_sub(info.dwTime);
+        Some(Duration::from_millis(u64::from(elapsed)))
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        let run = |program: &str, args: &[&str]| {
+            std::process::Command::new(program)
+                .args(args)
+                .output()
+                .ok()
+                .filter(|o| o.status.success())
+                .map(|o| String::from_utf8_lossy(&o.stdout).into_owned())
+        };
+        // X11 sessions, then GNOME on Wayland
+        run("xprintidle", &[])
+            .and_then(|out| out.trim().parse::<u64>().ok())
+            .or_else(|| {
+                run(
+                    "gdbus",
+                    &[
+                        "call",
+                        "--session",
+                        "--dest",
+                        "org.gnome.Mutter.IdleMonitor",
+                        "--object-path",
+                        "/org/gnome/Mutter/IdleMonitor/Core",
+                        "--method",
+                        "org.gnome.Mutter.IdleMonitor.GetIdletime",
+                    ],
+                )
+                .and_then(|out| parse_gdbus_idletime(&out))
+            })
+            .map(Duration::from_millis)
+    }
+
+    #[cfg(not(any(target_os = "macos", windows, target_os = "linux")))]
+    {
+        None
+    }
+}
+
+/// Parse `HIDIdleTime` (nanoseconds) from `ioreg -c IOHIDSystem`
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn parse_ioreg_output(output: &str) -> Option<Duration> {
+    output.lines().find_map(|line| {
+        let value = line.trim().strip_prefix("\"HIDIdleTime\" = ")?;
+        value.trim().parse::<u64>().ok().map(Duration::from_nanos)
+    })
+}
+
+/// Parse milliseconds from the idle monitor's `(uint64 12345,)` reply
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_gdbus_idletime(output: &str) -> Option<u64> {
+    output
+        .trim()
+        .strip_prefix("(uint64 ")?
+        .strip_suffix(",)")?
+        .parse()
+        .ok()
+}
+
+/// Whether the user is away; true when idle time can't be read
+pub fn is_user_idle(app: &AppHandle) -> bool {
+    app.try_state::<IdleMonitor>()
+        .and_then(|monitor| *monitor.state.lock())
+        .map_or(true, |state| state.idle)
+}
+
+/// Track idle time for as long as the app runs
+pub fn start(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(POLL_INTERVAL);
+        loop {
+            interval.tick().await;
+            let idle_time = tokio::task::spawn_blocking(system_idle_time)
+                .await
+                .ok()
+                .flatten();
+            let state = IdleState::from_idle_time(idle_time);
+            let Some(changed) = app.state::<IdleMonitor>().update(state) else {
+                continue;
+            };
+            // Presence isn't known, so there's nothing to announce
+            if changed.idle_secs.is_none() {
+                continue;
+            }
+            if changed.idle {
+                tracing::debug!("User is idle");
+                crate::events::emit_critical(&app, "user-idle", &changed);
+            } else {
+                tracing::debug!("User is active");
+                crate::events::emit_critical(&app, "user-active", &changed);
+            }
+        }
+    });
+}
+
+// ============================================================
+// Commands
+// ============================================================
+
+/// Get whether the user is away and for how long
+#[tauri::command]
+pub async fn get_idle_state(app: AppHandle) -> Result<IdleState, AppError> {
+    Ok(app
+        .state::<IdleMonitor>()
+        .state
+        .lock()
+        .unwrap_or_else(|| IdleState::from_idle_time(None)))
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ioreg_output() {
+        let output = r#"
++-o IOHIDSystem  <class IOHIDSystem, id 0x100000484>
+    {
+      "HIDIdleTimeDelta" = 0
+      "HIDIdleTime" = 2534117583
+    }
+"#;
+        assert_eq!(
+            parse_ioreg_output(output),
+            Some(Duration::from_nanos(2_534_117_583))
+        );
+        assert_eq!(parse_ioreg_output(""), None);
+    }
+
+    #[test]
+    fn test_parse_gdbus_idletime() {
+        assert_eq!(parse_gdbus_idletime("(uint64 12345,)\n"), Some(12345));
+        assert_eq!(parse_gdbus_idletime("Error: no such service"), None);
+    }
+
+    #[test]
+    fn test_idle_transitions() {
+        let monitor = IdleMonitor::default();
+        let active = IdleState::from_idle_time(Some(Duration::from_secs(3)));
+        let idle = IdleState::from_idle_time(Some(IDLE_THRESHOLD));
+        assert!(!active.idle);
+        assert!(idle.idle);
+
+        // Starting out active isn't news
+        assert_eq!(monitor.update(active), None);
+        assert_eq!(monitor.update(idle), Some(idle));
+        assert_eq!(monitor.update(idle), None);
+        assert_eq!(monitor.update(active), Some(active));
+
+        // Unknown idle time never holds work back
+        assert!(IdleState::from_idle_time(None).idle);
+    }
+}
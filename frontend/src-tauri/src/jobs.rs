@@ -0,0 +1,448 @@
+//! Cancelable long-running jobs with a shared progress protocol.
+//!
+//! This module provides:
+//! - `start_job`, which runs an export, backup, import or model download in
+//!   the background and returns its job ID straight away
+//! - `job-progress` events carrying the stage, counts and, once the job
+//!   ends, its result or error
+//! - `cancel_job`, which asks a running job to stop at its next checkpoint
+//!
+//! Operations receive a `JobContext` to report progress and check for
+//! cancellation; the same functions run untracked when called by their
+//! dedicated commands or the scheduler.
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tauri::{AppHandle, Manager};
+use tokio::sync::watch;
+
+use crate::apple_import::ImportSource;
+use crate::error::AppError;
+use crate::events::{emit_coalesced, emit_critical};
+use crate::export::{ExportFilter, ExportFormat};
+
+/// Event carrying job progress and completion
+const PROGRESS_EVENT: &str = "job-progress";
+
+/// Most jobs running at once
+const MAX_RUNNING: usize = 16;
+
+/// What a job does, with its arguments
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum JobRequest {
+    /// Export notes to files, as `export_markdown`
+    Export {
+        destination: String,
+        format: Option<ExportFormat>,
+        filter: Option<ExportFilter>,
+    },
+    /// Create an encrypted backup, as `create_encrypted_backup`
+    Backup,
+    /// Create a local backup, as `create_backup`
+    LocalBackup,
+    /// Restore a local backup, as `restore_backup`
+    RestoreBackup { path: String },
+    /// Import from Apple Notes or Reminders, as `import_from_apple`
+    AppleImport { source: ImportSource },
+    /// Download a model file into the app's models folder
+    ModelDownload {
+        url: String,
+        file_name: String,
+        sha256: Option<String>,
+    },
+}
+
+/// Kind of job, reported with its progress
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobKind {
+    Export,
+    Backup,
+    LocalBackup,
+    RestoreBackup,
+    AppleImport,
+    ModelDownload,
+}
+
+impl JobRequest {
+    pub fn kind(&self) -> JobKind {
+        match self {
+            JobRequest::Export { .. } => JobKind::Export,
+            JobRequest::Backup => JobKind::Backup,
+            JobRequest::LocalBackup => JobKind::LocalBackup,
+            JobRequest::RestoreBackup { .. } => JobKind::RestoreBackup,
+            JobRequest::AppleImport { .. } => JobKind::AppleImport,
+            JobRequest::ModelDownload { .. } => JobKind::ModelDownload,
+        }
+    }
+}
+
+/// Lifecycle of a job
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobState {
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+/// Payload of `job-progress` events
+#[derive(Debug, Clone, Serialize)]
+pub struct JobProgress {
+    pub id: String,
+    pub kind: JobKind,
+    pub state: JobState,
+    /// What the job is doing, e.g. `downloading` or `encrypting`
+    pub stage: String,
+    /// Units done so far (items or bytes, depending on the job)
+    pub done: u64,
+    /// Total units, when known
+    pub total: Option<u64>,
+    /// The operation's result once completed
+    pub result: Option<serde_json::Value>,
+    pub error: Option<AppError>,
+}
+
+/// A running job as reported by `list_jobs`
+#[derive(Debug, Clone, Serialize)]
+pub struct JobInfo {
+    pub id: String,
+    pub kind: JobKind,
+    /// Unix epoch seconds
+    pub started_at: i64,
+    pub cancel_requested: bool,
+    pub progress: Option<JobProgress>,
+}
+
+struct Job {
+    id: String,
+    kind: JobKind,
+    started_at: i64,
+    cancel: watch::Sender<bool>,
+    progress: Mutex<Option<JobProgress>>,
+}
+
+impl Job {
+    fn snapshot(&self, state: JobState) -> JobProgress {
+        let last = self.progress.lock().clone();
+        let (stage, done, total) = last.map(|p| (p.stage, p.done, p.total)).unwrap_or_default();
+        JobProgress {
+            id: self.id.clone(),
+            kind: self.kind,
+            state,
+            stage,
+            done,
+            total,
+            result: None,
+            error: None,
+        }
+    }
+
+    fn info(&self) -> JobInfo {
+        JobInfo {
+            id: self.id.clone(),
+            kind: self.kind,
+            started_at: self.started_at,
+            cancel_requested: *self.cancel.borrow(),
+            progress: self.progress.lock().clone(),
+        }
+    }
+}
+
+/// Running jobs kept in Tauri state
+#[derive(Default)]
+pub struct JobRegistry {
+    jobs: Mutex<HashMap<String, Arc<Job>>>,
+}
+
+impl JobRegistry {
+    fn insert(&self, kind: JobKind) -> Result<Arc<Job>, AppError> {
+        let mut jobs = self.jobs.lock();
+        if jobs.len() >= MAX_RUNNING {
+            return Err(AppError::Conflict(format!(
+                "{} jobs are already running",
+                MAX_RUNNING
+            )));
+        }
+        let job = Arc::new(Job {
+            id: new_id(),
+            kind,
+            started_at: chrono::Utc::now().timestamp(),
+            cancel: watch::channel(false).0,
+            progress: Mutex::new(None),
+        });
+        jobs.insert(job.id.clone(), job.clone());
+        Ok(job)
+    }
+
+    fn remove(&self, id: &str) {
+        self.jobs.lock().remove(id);
+    }
+
+    fn cancel(&self, id: &str) -> bool {
+        match self.jobs.lock().get(id) {
+            Some(job) => {
+                job.cancel.send_replace(true);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn list(&self) -> Vec<JobInfo> {
+        let mut jobs: Vec<JobInfo> = self.jobs.lock().values().map(|j| j.info()).collect();
+        jobs.sort_by_key(|j| j.started_at);
+        jobs
+    }
+}
+
+fn new_id() -> String {
+    let mut random = [0u8; 8];
+    let _ = getrandom::fill(&mut random);
+    random.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Handle given to an operation for progress and cancellation
+///
+/// Cheap to clone, so blocking sections can take their own copy.
+#[derive(Clone)]
+pub struct JobContext {
+    app: AppHandle,
+    /// None when the operation runs outside the job protocol
+    job: Option<Arc<Job>>,
+}
+
+impl JobContext {
+    /// Context for an operation run directly; never cancelled, reports nothing
+    pub fn untracked(app: &AppHandle) -> Self {
+        Self {
+            app: app.clone(),
+            job: None,
+        }
+    }
+
+    pub fn app(&self) -> &AppHandle {
+        &self.app
+    }
+
+    /// Report progress; rapid updates are coalesced per job
+    pub fn progress(&self, stage: &str, done: u64, total: Option<u64>) {
+        let Some(job) = &self.job else {
+            return;
+        };
+        let progress = JobProgress {
+            id: job.id.clone(),
+            kind: job.kind,
+            state: JobState::Running,
+            stage: stage.to_string(),
+            done,
+            total,
+            result: None,
+            error: None,
+        };
+        emit_coalesced(&self.app, PROGRESS_EVENT, job.id.clone(), &progress);
+        *job.progress.lock() = Some(progress);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.job.as_ref().is_some_and(|job| *job.cancel.borrow())
+    }
+
+    /// Checkpoint: fail with `Cancelled` if cancellation was requested
+    pub fn check(&self) -> Result<(), AppError> {
+        if self.is_cancelled() {
+            Err(AppError::Cancelled("Job was cancelled".to_string()))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Run a future that is safe to abandon, stopping early on cancellation
+    pub async fn cancellable<T, E>(
+        &self,
+        future: impl Future<Output = Result<T, E>>,
+    ) -> Result<T, AppError>
+    where
+        AppError: From<E>,
+    {
+        let Some(job) = &self.job else {
+            return future.await.map_err(AppError::from);
+        };
+        let mut cancel = job.cancel.subscribe();
+        tokio::select! {
+            result = future => result.map_err(AppError::from),
+            _ = cancel.wait_for(|&cancelled| cancelled) => {
+                Err(AppError::Cancelled("Job was cancelled".to_string()))
+            }
+        }
+    }
+}
+
+async fn run(job: &JobContext, request: JobRequest) -> Result<serde_json::Value, AppError> {
+    Ok(match request {
+        JobRequest::Export {
+            destination,
+            format,
+            filter,
+        } => serde_json::to_value(
+            crate::export::export_notes(job, PathBuf::from(destination), format, filter).await?,
+        )?,
+        JobRequest::Backup => {
+            serde_json::to_value(crate::backup::run_encrypted_backup(job).await?)?
+        }
+        JobRequest::LocalBackup => {
+            serde_json::to_value(crate::backup::run_local_backup(job).await?)?
+        }
+        JobRequest::RestoreBackup { path } => {
+            serde_json::to_value(crate::backup::run_local_restore(job, PathBuf::from(path)).await?)?
+        }
+        JobRequest::AppleImport { source } => {
+            serde_json::to_value(crate::apple_import::import_items(job, source).await?)?
+        }
+        JobRequest::ModelDownload {
+            url,
+            file_name,
+            sha256,
+        } => serde_json::to_value(
+            crate::models::download_model(job, &url, &file_name, sha256.as_deref()).await?,
+        )?,
+    })
+}
+
+/// Start a job in the background and return its ID
+pub fn spawn(app: &AppHandle, request: JobRequest) -> Result<String, AppError> {
+    let job = app.state::<JobRegistry>().insert(request.kind())?;
+    let id = job.id.clone();
+    tracing::info!(id = %id, kind = ?job.kind, "Job started");
+
+    let context = JobContext {
+        app: app.clone(),
+        job: Some(job.clone()),
+    };
+    context.progress("starting", 0, None);
+    tauri::async_runtime::spawn(async move {
+        let result = run(&context, request).await;
+        let mut progress = match result {
+            Ok(value) => JobProgress {
+                result: Some(value),
+                ..job.snapshot(JobState::Completed)
+            },
+            Err(AppError::Cancelled(_)) => job.snapshot(JobState::Cancelled),
+            Err(e) => JobProgress {
+                error: Some(e),
+                ..job.snapshot(JobState::Failed)
+            },
+        };
+        // Errors after a cancel request usually come from the interruption
+        if progress.state == JobState::Failed && context.is_cancelled() {
+            progress.state = JobState::Cancelled;
+        }
+        tracing::info!(id = %job.id, state = ?progress.state, "Job finished");
+        context.app.state::<JobRegistry>().remove(&job.id);
+        emit_critical(&context.app, PROGRESS_EVENT, &progress);
+    });
+    Ok(id)
+}
+
+/// Start a long-running operation; progress arrives as `job-progress` events
+#[tauri::command]
+pub async fn start_job(app: AppHandle, request: JobRequest) -> Result<String, AppError> {
+    spawn(&app, request)
+}
+
+/// Ask a running job to stop
+#[tauri::command]
+pub async fn cancel_job(app: AppHandle, id: String) -> Result<(), AppError> {
+    if app.state::<JobRegistry>().cancel(&id) {
+        Ok(())
+    } else {
+        Err(AppError::NotFound(format!("No running job '{}'", id)))
+    }
+}
+
+/// List running jobs, oldest first
+#[tauri::command]
+pub async fn list_jobs(app: AppHandle) -> Result<Vec<JobInfo>, AppError> {
+    Ok(app.state::<JobRegistry>().list())
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_deserializes_by_kind() {
+        let request: JobRequest = serde_json::from_value(serde_json::json!({
+            "kind": "export",
+            "destination": "/tmp/out",
+        }))
+        .unwrap();
+        assert_eq!(request.kind(), JobKind::Export);
+
+        let request: JobRequest =
+            serde_json::from_value(serde_json::json!({ "kind": "backup" })).unwrap();
+        assert_eq!(request.kind(), JobKind::Backup);
+
+        let request: JobRequest = serde_json::from_value(serde_json::json!({
+            "kind": "model_download",
+            "url": "https://example.com/model.bin",
+            "file_name": "model.bin",
+        }))
+        .unwrap();
+        assert_eq!(request.kind(), JobKind::ModelDownload);
+
+        assert!(
+            serde_json::from_value::<JobRequest>(serde_json::json!({ "kind": "format_disk" }))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_registry_cancel_and_limit() {
+        let registry = JobRegistry::default();
+        let job = registry.insert(JobKind::Backup).unwrap();
+        assert!(!*job.cancel.borrow());
+        assert!(registry.cancel(&job.id));
+        assert!(*job.cancel.borrow());
+        assert!(registry.list()[0].cancel_requested);
+
+        registry.remove(&job.id);
+        assert!(!registry.cancel(&job.id));
+
+        for _ in 0..MAX_RUNNING {
+            registry.insert(JobKind::Export).unwrap();
+        }
+        assert_eq!(
+            registry.insert(JobKind::Export).err().map(|e| e.kind()),
+            Some("conflict")
+        );
+    }
+
+    #[test]
+    fn test_snapshot_keeps_last_progress() {
+        let registry = JobRegistry::default();
+        let job = registry.insert(JobKind::ModelDownload).unwrap();
+        assert_eq!(job.snapshot(JobState::Cancelled).done, 0);
+
+        *job.progress.lock() = Some(JobProgress {
+            stage: "downloading".to_string(),
+            done: 512,
+            total: Some(1024),
+            ..job.snapshot(JobState::Running)
+        });
+        let snapshot = job.snapshot(JobState::Completed);
+        assert_eq!(snapshot.state, JobState::Completed);
+        assert_eq!(snapshot.stage, "downloading");
+        assert_eq!((snapshot.done, snapshot.total), (512, Some(1024)));
+    }
+}
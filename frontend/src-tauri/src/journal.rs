@@ -0,0 +1,399 @@
+//! Crash-safe journal for the shell's own state files.
+//!
+//! This module provides:
+//! - `Batch`, which replaces or removes several JSON files in the app data
+//!   directory as one all-or-nothing update
+//! - A write-ahead log (journal/journal.log): each update is appended and
+//!   synced before any file is touched, so one interrupted by a crash or
+//!   power loss is finished on the next launch
+//! - A snapshot (journal/snapshot.json) of the latest contents of every
+//!   journaled file, which the log is folded into once it grows, and which
+//!   restores files left empty or unreadable by a power loss
+//!
+//! Files stay where they always were, so readers keep using `load_json`.
+//! Only files written through the journal are tracked; a file edited or
+//! deleted by hand is left alone unless it's no longer valid JSON.
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Component, Path, PathBuf};
+
+use crate::config::{load_json, save_json_atomic};
+
+/// Log entries kept before they're folded into the snapshot
+const COMPACT_AFTER: usize = 64;
+
+/// Serializes commits, recovery and compaction within the process
+static LOCK: Mutex<()> = Mutex::new(());
+
+/// File contents by name, `None` for a removed file
+type Writes = BTreeMap<String, Option<serde_json::Value>>;
+
+/// One committed update, a line of journal.log
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Entry {
+    seq: u64,
+    writes: Writes,
+    checksum: String,
+}
+
+impl Entry {
+    fn new(seq: u64, writes: Writes) -> Self {
+        let checksum = checksum(seq, &writes);
+        Self {
+            seq,
+            writes,
+            checksum,
+        }
+    }
+
+    fn is_intact(&self) -> bool {
+        self.checksum == checksum(self.seq, &self.writes)
+    }
+}
+
+fn checksum(seq: u64, writes: &Writes) -> String {
+    let json = serde_json::to_string(&(seq, writes)).unwrap_or_default();
+    let digest = Sha256::digest(json.as_bytes());
+    digest[..8].iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Latest contents of every journaled file, as of `seq`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+struct Snapshot {
+    seq: u64,
+    files: BTreeMap<String, serde_json::Value>,
+}
+
+impl Snapshot {
+    fn apply(&mut self, entry: &Entry) {
+        self.seq = self.seq.max(entry.seq);
+        for (name, value) in &entry.writes {
+            match value {
+                Some(value) => self.files.insert(name.clone(), value.clone()),
+                None => self.files.remove(name),
+            };
+        }
+    }
+}
+
+fn journal_dir(app_data_dir: &Path) -> PathBuf {
+    app_data_dir.join("journal")
+}
+
+fn log_path(app_data_dir: &Path) -> PathBuf {
+    journal_dir(app_data_dir).join("journal.log")
+}
+
+fn snapshot_path(app_data_dir: &Path) -> PathBuf {
+    journal_dir(app_data_dir).join("snapshot.json")
+}
+
+/// Intact log entries, stopping at the first torn or corrupt line
+fn read_log(app_data_dir: &Path) -> Vec<Entry> {
+    let Ok(file) = fs::File::open(log_path(app_data_dir)) else {
+        return Vec::new();
+    };
+    let mut entries: Vec<Entry> = Vec::new();
+    for line in BufReader::new(file).lines() {
+        let entry = line
+            .ok()
+            .and_then(|line| serde_json::from_str::<Entry>(&line).ok())
+            .filter(|entry| entry.is_intact())
+            .filter(|entry| entries.last().map_or(true, |last| entry.seq > last.seq));
+        match entry {
+            Some(entry) => entries.push(entry),
+            None => break,
+        }
+    }
+    entries
+}
+
+fn append(app_data_dir: &Path, entry: &Entry) -> Result<(), String> {
+    fs::create_dir_all(journal_dir(app_data_dir))
+        .map_err(|e| format!("Failed to create journal directory: {}", e))?;
+    let line = serde_json::to_string(entry)
+        .map_err(|e| format!("Failed to serialize journal entry: {}", e))?;
+
+    let mut options = fs::OpenOptions::new();
+    options.create(true).append(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options
+        .open(log_path(app_data_dir))
+        .map_err(|e| format!("Failed to open journal: {}", e))?;
+    writeln!(file, "{}", line).map_err(|e| format!("Failed to write journal: {}", e))?;
+    file.sync_all()
+        .map_err(|e| format!("Failed to sync journal: {}", e))
+}
+
+/// Write out an entry's files
+fn apply(app_data_dir: &Path, writes: &Writes) -> Result<(), String> {
+    for (name, value) in writes {
+        let path = app_data_dir.join(name);
+        match value {
+            Some(value) => save_json_atomic(&path, value)?,
+            None => match fs::remove_file(&path) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                    return Err(format!("Failed to remove {}: {}", name, e));
+                }
+                _ => {}
+            },
+        }
+    }
+    Ok(())
+}
+
+/// Fold the log into the snapshot and start a new log
+fn compact(app_data_dir: &Path, entries: &[Entry]) -> Result<(), String> {
+    let mut snapshot: Snapshot = load_json(&snapshot_path(app_data_dir)).unwrap_or_default();
+    for entry in entries {
+        snapshot.apply(entry);
+    }
+    save_json_atomic(&snapshot_path(app_data_dir), &snapshot)?;
+    match fs::remove_file(log_path(app_data_dir)) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+            Err(format!("Failed to reset journal: {}", e))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn validate_name(name: &str) -> Result<(), String> {
+    let path = Path::new(name);
+    let plain = !name.is_empty()
+        && path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)));
+    if !plain || path.starts_with("journal") {
+        return Err(format!("Invalid journaled file name: {}", name));
+    }
+    Ok(())
+}
+
+/// Files to replace or remove together
+#[derive(Debug, Default)]
+pub struct Batch {
+    writes: Writes,
+}
+
+impl Batch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace a file, named relative to the app data directory
+    pub fn write<T: Serialize>(mut self, name: &str, value: &T) -> Result<Self, String> {
+        validate_name(name)?;
+        let value =
+            serde_json::to_value(value).map_err(|e| format!("Failed to serialize: {}", e))?;
+        self.writes.insert(name.to_string(), Some(value));
+        Ok(self)
+    }
+
+    /// Remove a file, named relative to the app data directory
+    pub fn remove(mut self, name: &str) -> Result<Self, String> {
+        validate_name(name)?;
+        self.writes.insert(name.to_string(), None);
+        Ok(self)
+    }
+
+    /// Apply every change, or none of them if interrupted before the log
+    /// entry is synced
+    ///
+    /// An error after that point leaves the update to be finished on the
+    /// next launch.
+    pub fn commit(self, app_data_dir: &Path) -> Result<(), String> {
+        if self.writes.is_empty() {
+            return Ok(());
+        }
+        let _lock = LOCK.lock();
+
+        let entries = read_log(app_data_dir);
+        let last_seq = match entries.last() {
+            Some(entry) => entry.seq,
+            None => {
+                load_json::<Snapshot>(&snapshot_path(app_data_dir))
+                    .unwrap_or_default()
+                    .seq
+            }
+        };
+        let entry = Entry::new(last_seq + 1, self.writes);
+        append(app_data_dir, &entry)?;
+        apply(app_data_dir, &entry.writes)?;
+
+        if entries.len() + 1 >= COMPACT_AFTER {
+            let mut entries = entries;
+            entries.push(entry);
+            if let Err(e) = compact(app_data_dir, &entries) {
+                tracing::warn!("Failed to compact journal: {}", e);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Replace a single journaled file
+pub fn write<T: Serialize>(app_data_dir: &Path, name: &str, value: &T) -> Result<(), String> {
+    Batch::new().write(name, value)?.commit(app_data_dir)
+}
+
+/// Finish updates interrupted by a crash and repair damaged files
+///
+/// Runs once at launch, before anything reads the journaled files.
+pub fn recover(app_data_dir: &Path) {
+    let _lock = LOCK.lock();
+
+    let entries = read_log(app_data_dir);
+    if !entries.is_empty() {
+        tracing::info!("Replaying {} journaled state updates", entries.len());
+    }
+    for entry in &entries {
+        if let Err(e) = apply(app_data_dir, &entry.writes) {
+            // Keep the log so the next launch tries again
+            tracing::warn!("Failed to replay journal entry {}: {}", entry.seq, e);
+            return;
+        }
+    }
+    if let Err(e) = compact(app_data_dir, &entries) {
+        tracing::warn!("Failed to compact journal: {}", e);
+        return;
+    }
+
+    let snapshot: Snapshot = load_json(&snapshot_path(app_data_dir)).unwrap_or_default();
+    for (name, value) in &snapshot.files {
+        let path = app_data_dir.join(name);
+        let damaged = fs::read(&path)
+            .map(|bytes| serde_json::from_slice::<serde_json::Value>(&bytes).is_err())
+            .unwrap_or(false);
+        if damaged {
+            tracing::warn!("Restoring {} from the journal snapshot", name);
+            if let Err(e) = save_json_atomic(&path, value) {
+                tracing::warn!("Failed to restore {}: {}", name, e);
+            }
+        }
+    }
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    fn read(dir: &Path, name: &str) -> Option<serde_json::Value> {
+        load_json(&dir.join(name))
+    }
+
+    #[test]
+    fn test_batch_commit() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("stale.json"), "{}").unwrap();
+
+        Batch::new()
+            .write("a.json", &json!({ "x": 1 }))
+            .unwrap()
+            .write("b.json", &json!([1, 2]))
+            .unwrap()
+            .remove("stale.json")
+            .unwrap()
+            .commit(dir.path())
+            .unwrap();
+
+        assert_eq!(read(dir.path(), "a.json"), Some(json!({ "x": 1 })));
+        assert_eq!(read(dir.path(), "b.json"), Some(json!([1, 2])));
+        assert!(!dir.path().join("stale.json").exists());
+        assert_eq!(read_log(dir.path()).len(), 1);
+    }
+
+    #[test]
+    fn test_recover_finishes_interrupted_update() {
+        let dir = TempDir::new().unwrap();
+        write(dir.path(), "a.json", &json!(1)).unwrap();
+
+        // Crash after the entry was synced but before any file was written
+        let mut writes = Writes::new();
+        writes.insert("a.json".to_string(), Some(json!(2)));
+        writes.insert("b.json".to_string(), Some(json!(2)));
+        append(dir.path(), &Entry::new(2, writes)).unwrap();
+
+        recover(dir.path());
+        assert_eq!(read(dir.path(), "a.json"), Some(json!(2)));
+        assert_eq!(read(dir.path(), "b.json"), Some(json!(2)));
+        assert!(read_log(dir.path()).is_empty());
+    }
+
+    #[test]
+    fn test_torn_entry_is_ignored() {
+        let dir = TempDir::new().unwrap();
+        write(dir.path(), "a.json", &json!(1)).unwrap();
+
+        let mut writes = Writes::new();
+        writes.insert("a.json".to_string(), Some(json!(2)));
+        let line = serde_json::to_string(&Entry::new(2, writes)).unwrap();
+        let mut log = fs::OpenOptions::new()
+            .append(true)
+            .open(log_path(dir.path()))
+            .unwrap();
+        log.write_all(&line.as_bytes()[..line.len() / 2]).unwrap();
+
+        assert_eq!(read_log(dir.path()).len(), 1);
+        recover(dir.path());
+        assert_eq!(read(dir.path(), "a.json"), Some(json!(1)));
+    }
+
+    #[test]
+    fn test_recover_restores_damaged_file() {
+        let dir = TempDir::new().unwrap();
+        write(dir.path(), "a.json", &json!({ "x": 1 })).unwrap();
+        recover(dir.path());
+
+        // A power loss can leave a renamed file empty
+        fs::write(dir.path().join("a.json"), "").unwrap();
+        recover(dir.path());
+        assert_eq!(read(dir.path(), "a.json"), Some(json!({ "x": 1 })));
+
+        // Removed by hand stays removed
+        fs::remove_file(dir.path().join("a.json")).unwrap();
+        recover(dir.path());
+        assert!(!dir.path().join("a.json").exists());
+    }
+
+    #[test]
+    fn test_compaction() {
+        let dir = TempDir::new().unwrap();
+        for i in 0..COMPACT_AFTER as u64 + 3 {
+            write(dir.path(), "a.json", &json!(i)).unwrap();
+        }
+        assert_eq!(read_log(dir.path()).len(), 3);
+
+        let snapshot: Snapshot = load_json(&snapshot_path(dir.path())).unwrap();
+        assert_eq!(snapshot.seq, COMPACT_AFTER as u64);
+        assert_eq!(
+            snapshot.files.get("a.json"),
+            Some(&json!(COMPACT_AFTER as u64 - 1))
+        );
+        // Sequence numbers carry on from the snapshot
+        assert_eq!(read_log(dir.path())[0].seq, COMPACT_AFTER as u64 + 1);
+    }
+
+    #[test]
+    fn test_invalid_names() {
+        for name in ["", "../escape.json", "/abs.json", "journal/journal.log"] {
+            assert!(Batch::new().write(name, &json!(1)).is_err(), "{}", name);
+        }
+        assert!(Batch::new().write("nested/ok.json", &json!(1)).is_ok());
+    }
+}
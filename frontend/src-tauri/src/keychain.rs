@@ -0,0 +1,313 @@
+//! Credential storage backed by the system credential store.
+//!
+//! This module provides:
+//! - Storing, reading, and deleting named credentials, or all of a
+//!   profile's at once
+//! - The platform credential store through `keyring`: the macOS login
+//!   keychain, Windows Credential Manager, or the Secret Service on Linux
+//! - A file store created owner-only, used on Linux when no Secret Service
+//!   is reachable, and in unit tests so they never touch the user's keychain
+//! - Moving credentials older versions kept in files into the platform store
+//!   the first time they are read
+//! - An index of stored account names, since credential stores can't list a
+//!   service's items
+
+use parking_lot::Mutex;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::config::{load_json, save_json_atomic};
+
+/// Keychain service name credentials are stored under
+const SERVICE: &str = "com.secondbrain.desktop";
+
+/// Account names stored for a data directory
+pub(crate) const INDEX_FILE: &str = "credential-accounts.json";
+
+/// Serializes updates to the account index
+static INDEX_LOCK: Mutex<()> = Mutex::new(());
+
+/// Service for a data directory, so each profile keeps its own credentials
+///
+/// Profile data directories are named after the profile's identifier, which
+/// extends the default one.
+fn service(app_data_dir: &Path) -> &str {
+    app_data_dir
+        .file_name()
+        .and_then(|name| name.to_str())
+        .filter(|name| name.starts_with(SERVICE))
+        .unwrap_or(SERVICE)
+}
+
+/// Whether credentials go to the platform store rather than files
+fn use_platform_store() -> bool {
+    cfg!(not(test))
+}
+
+/// Whether a store error means no credential store is reachable at all, so
+/// the file store is used instead
+///
+/// Only on Linux, where a desktop may run without a Secret Service; a locked
+/// or refused keychain elsewhere is reported rather than bypassed.
+fn store_unavailable(error: &keyring::Error) -> bool {
+    cfg!(target_os = "linux")
+        && matches!(
+            error,
+            keyring::Error::PlatformFailure(_) | keyring::Error::NoStorageAccess(_)
+        )
+}
+
+fn entry(app_data_dir: &Path, account: &str) -> keyring::Result<keyring::Entry> {
+    keyring::Entry::new(service(app_data_dir), account)
+}
+
+/// Store a credential, replacing any existing value
+pub fn set_secret(app_data_dir: &Path, account: &str, value: &str) -> Result<(), String> {
+    if use_platform_store() {
+        match entry(app_data_dir, account).and_then(|entry| entry.set_password(value)) {
+            Ok(()) => {
+                remove_file(app_data_dir, account)?;
+                return record_account(app_data_dir, account);
+            }
+            Err(e) if store_unavailable(&e) => {
+                tracing::warn!(
+                    "No credential store available, keeping '{}' in a file: {}",
+                    account,
+                    e
+                );
+            }
+            Err(e) => return Err(format!("Failed to store credential: {}", e)),
+        }
+    }
+    write_file(app_data_dir, account, value)?;
+    record_account(app_data_dir, account)
+}
+
+/// Read a credential, returning None if it does not exist
+pub fn get_secret(app_data_dir: &Path, account: &str) -> Result<Option<String>, String> {
+    if use_platform_store() {
+        match entry(app_data_dir, account).and_then(|entry| entry.get_password()) {
+            Ok(value) => {
+                record_account(app_data_dir, account)?;
+                return Ok(Some(value));
+            }
+            Err(keyring::Error::NoEntry) => {}
+            Err(e) if store_unavailable(&e) => {}
+            Err(e) => return Err(format!("Failed to read credential: {}", e)),
+        }
+    }
+
+    let value = read_file(app_data_dir, account)?;
+    if let (true, Some(value)) = (use_platform_store(), &value) {
+        // Written by an older version, or while no store was available
+        if entry(app_data_dir, account)
+            .and_then(|entry| entry.set_password(value))
+            .is_ok()
+        {
+            remove_file(app_data_dir, account)?;
+            tracing::info!("Moved credential '{}' into the credential store", account);
+        }
+    }
+    Ok(value)
+}
+
+/// Delete a credential if it exists
+pub fn delete_secret(app_data_dir: &Path, account: &str) -> Result<(), String> {
+    if use_platform_store() {
+        match entry(app_data_dir, account).and_then(|entry| entry.delete_credential()) {
+            Ok(()) | Err(keyring::Error::NoEntry) => {}
+            Err(e) if store_unavailable(&e) => {}
+            Err(e) => return Err(format!("Failed to delete credential: {}", e)),
+        }
+    }
+    remove_file(app_data_dir, account)?;
+
+    let _guard = INDEX_LOCK.lock();
+    let mut accounts = load_index(app_data_dir);
+    if accounts.iter().any(|a| a == account) {
+        accounts.retain(|a| a != account);
+        save_json_atomic(&index_path(app_data_dir), &accounts)?;
+    }
+    Ok(())
+}
+
+/// Delete every credential stored for a data directory, returning how many
+/// were removed
+pub fn delete_all(app_data_dir: &Path) -> Result<usize, String> {
+    let _guard = INDEX_LOCK.lock();
+    let mut deleted = 0;
+    if use_platform_store() {
+        for account in load_index(app_data_dir) {
+            match entry(app_data_dir, &account).and_then(|entry| entry.delete_credential()) {
+                Ok(()) => deleted += 1,
+                Err(keyring::Error::NoEntry) => {}
+                Err(e) if store_unavailable(&e) => {}
+                Err(e) => return Err(format!("Failed to delete credential: {}", e)),
+            }
+        }
+    }
+
+    let dir = app_data_dir.join("credentials");
+    match std::fs::read_dir(&dir) {
+        Ok(entries) => {
+            deleted += entries.count();
+            std::fs::remove_dir_all(&dir)
+                .map_err(|e| format!("Failed to delete credentials: {}", e))?;
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => return Err(format!("Failed to read credentials: {}", e)),
+    }
+    match std::fs::remove_file(index_path(app_data_dir)) {
+        Ok(()) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => return Err(format!("Failed to delete the credential index: {}", e)),
+    }
+    Ok(deleted)
+}
+
+fn index_path(app_data_dir: &Path) -> PathBuf {
+    app_data_dir.join(INDEX_FILE)
+}
+
+fn load_index(app_data_dir: &Path) -> Vec<String> {
+    load_json(&index_path(app_data_dir)).unwrap_or_default()
+}
+
+/// Add an account to the index, so `delete_all` can find it
+fn record_account(app_data_dir: &Path, account: &str) -> Result<(), String> {
+    let _guard = INDEX_LOCK.lock();
+    let mut accounts = load_index(app_data_dir);
+    if accounts.iter().any(|a| a == account) {
+        return Ok(());
+    }
+    accounts.push(account.to_string());
+    save_json_atomic(&index_path(app_data_dir), &accounts)
+}
+
+/// Write a credential file, owner-only from the moment it exists
+fn write_file(app_data_dir: &Path, account: &str, value: &str) -> Result<(), String> {
+    let path = credential_path(app_data_dir, account);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create credentials directory: {}", e))?;
+    }
+
+    let temp_path = path.with_extension("tmp");
+    // A leftover from an interrupted write would keep its old permissions
+    let _ = std::fs::remove_file(&temp_path);
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options
+        .open(&temp_path)
+        .map_err(|e| format!("Failed to write credential: {}", e))?;
+    file.write_all(value.as_bytes())
+        .and_then(|()| file.sync_all())
+        .map_err(|e| format!("Failed to write credential: {}", e))?;
+    drop(file);
+
+    std::fs::rename(&temp_path, &path).map_err(|e| format!("Failed to save credential: {}", e))
+}
+
+fn read_file(app_data_dir: &Path, account: &str) -> Result<Option<String>, String> {
+    match std::fs::read_to_string(credential_path(app_data_dir, account)) {
+        Ok(value) => Ok(Some(value)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(format!("Failed to read credential: {}", e)),
+    }
+}
+
+fn remove_file(app_data_dir: &Path, account: &str) -> Result<(), String> {
+    match std::fs::remove_file(credential_path(app_data_dir, account)) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(format!("Failed to delete credential: {}", e)),
+    }
+}
+
+/// File used for an account in the file store
+fn credential_path(app_data_dir: &Path, account: &str) -> PathBuf {
+    let safe: String = account
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '.' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    app_data_dir.join("credentials").join(safe)
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_credential_path_is_sanitized() {
+        let path = credential_path(Path::new("/data"), "imap:me@example.com/../x");
+        assert_eq!(
+            path,
+            Path::new("/data/credentials/imap_me_example.com_.._x")
+        );
+    }
+
+    #[test]
+    fn test_service_follows_profile() {
+        assert_eq!(service(Path::new("/data/com.secondbrain.desktop")), SERVICE);
+        assert_eq!(
+            service(Path::new("/data/com.secondbrain.desktop.profile-work")),
+            "com.secondbrain.desktop.profile-work"
+        );
+        assert_eq!(service(Path::new("/tmp/other")), SERVICE);
+    }
+
+    #[test]
+    fn test_file_store_roundtrip() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+
+        assert_eq!(get_secret(temp_dir.path(), "acct").unwrap(), None);
+
+        set_secret(temp_dir.path(), "acct", "hunter2").unwrap();
+        assert_eq!(
+            get_secret(temp_dir.path(), "acct").unwrap().as_deref(),
+            Some("hunter2")
+        );
+
+        delete_secret(temp_dir.path(), "acct").unwrap();
+        assert_eq!(get_secret(temp_dir.path(), "acct").unwrap(), None);
+        assert!(delete_secret(temp_dir.path(), "acct").is_ok());
+        assert!(load_index(temp_dir.path()).is_empty());
+
+        set_secret(temp_dir.path(), "a", "1").unwrap();
+        set_secret(temp_dir.path(), "b", "2").unwrap();
+        set_secret(temp_dir.path(), "b", "3").unwrap();
+        assert_eq!(load_index(temp_dir.path()), ["a", "b"]);
+        assert_eq!(delete_all(temp_dir.path()).unwrap(), 2);
+        assert_eq!(get_secret(temp_dir.path(), "a").unwrap(), None);
+        assert_eq!(delete_all(temp_dir.path()).unwrap(), 0);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_credential_file_is_owner_only() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        set_secret(temp_dir.path(), "acct", "hunter2").unwrap();
+        let mode = std::fs::metadata(credential_path(temp_dir.path(), "acct"))
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+}
@@ -0,0 +1,205 @@
+//! Acting on files, links and captures given at launch.
+//!
+//! This module provides:
+//! - One entry point for requests from the app's own command line and from
+//!   secondary instances forwarded by the single-instance plugin
+//! - `--capture` text saved as a note through the control API's capture path
+//! - Files and `secondbrain://` links handed to the webview as
+//!   `launch-request` events, held until the frontend has loaded
+//!
+//! The frontend drains held requests with `take_launch_requests` once it is
+//! listening; later requests are emitted straight away.
+
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::cli::LaunchOptions;
+use crate::control_api::{self, CaptureRequest};
+use crate::error::AppError;
+
+/// Requests held for the frontend before it is listening; older ones are dropped
+const MAX_PENDING: usize = 50;
+
+/// Something for the webview to open
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LaunchRequest {
+    OpenFile { path: String },
+    DeepLink { url: String },
+}
+
+/// Requests waiting for the frontend, kept in Tauri state
+#[derive(Debug, Default)]
+pub struct LaunchQueue {
+    inner: Mutex<QueueState>,
+}
+
+#[derive(Debug, Default)]
+struct QueueState {
+    /// Set once the frontend has drained the queue
+    listening: bool,
+    pending: Vec<LaunchRequest>,
+}
+
+impl LaunchQueue {
+    /// Hold a request, or return it if the frontend is listening
+    fn hold(&self, request: LaunchRequest) -> Option<LaunchRequest> {
+        let mut state = self.inner.lock();
+        if state.listening {
+            return Some(request);
+        }
+        if state.pending.len() >= MAX_PENDING {
+            state.pending.remove(0);
+        }
+        state.pending.push(request);
+        None
+    }
+
+    fn take(&self) -> Vec<LaunchRequest> {
+        let mut state = self.inner.lock();
+        state.listening = true;
+        std::mem::take(&mut state.pending)
+    }
+}
+
+/// Webview requests for the given options, with files resolved against `cwd`
+///
+/// Files that don't exist are skipped, since any stray argument lands there.
+fn webview_requests(options: &LaunchOptions, cwd: &Path) -> Vec<LaunchRequest> {
+    let files = options
+        .files
+        .iter()
+        .map(|file| cwd.join(file))
+        .filter(|path| path.is_file())
+        .map(|path| LaunchRequest::OpenFile {
+            path: path
+                .canonicalize()
+                .unwrap_or(path)
+                .to_string_lossy()
+                .to_string(),
+        });
+    let links = options
+        .links
+        .iter()
+        .map(|url| LaunchRequest::DeepLink { url: url.clone() });
+    files.chain(links).collect()
+}
+
+/// Act on the files, links and captures in `options`
+pub fn handle(app: &AppHandle, options: &LaunchOptions, cwd: &Path) {
+    if !options.has_requests() {
+        return;
+    }
+    let queue = app.state::<LaunchQueue>();
+    for request in webview_requests(options, cwd) {
+        tracing::info!("Launch request: {:?}", request);
+        if let Some(request) = queue.hold(request) {
+            let _ = app.emit("launch-request", &request);
+        }
+    }
+
+    for text in &options.captures {
+        let app = app.clone();
+        let capture = CaptureRequest::text(text.as_str());
+        tauri::async_runtime::spawn(async move {
+            match control_api::capture_note(&app, &capture).await {
+                Ok(_) => tracing::info!("Saved note captured from the command line"),
+                Err(e) => tracing::warn!("Command-line capture failed: {}", e),
+            }
+        });
+    }
+}
+
+/// Handle the requests this process was launched with
+pub fn start(app: &AppHandle) {
+    let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    handle(app, &crate::cli::options(app), &cwd);
+}
+
+/// Handle a secondary instance's arguments, as forwarded by the
+/// single-instance plugin
+pub fn forward(app: &AppHandle, args: &[String], cwd: &str) {
+    // The first argument is the secondary instance's program path
+    let options = LaunchOptions::parse(args.iter().skip(1));
+    handle(app, &options, Path::new(cwd));
+}
+
+// ============================================================
+// Commands
+// ============================================================
+
+/// Take the requests held since launch; later ones arrive as events
+#[tauri::command]
+pub async fn take_launch_requests(app: AppHandle) -> Result<Vec<LaunchRequest>, AppError> {
+    Ok(app.state::<LaunchQueue>().take())
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_webview_requests() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("todo.md"), "- [ ] milk").unwrap();
+        let options = LaunchOptions::parse([
+            "todo.md",
+            "missing.md",
+            "secondbrain://note/42",
+            "--capture",
+            "idea",
+        ]);
+
+        let requests = webview_requests(&options, dir.path());
+        let expected = dir.path().join("todo.md").canonicalize().unwrap();
+        assert_eq!(
+            requests,
+            vec![
+                LaunchRequest::OpenFile {
+                    path: expected.to_string_lossy().to_string()
+                },
+                LaunchRequest::DeepLink {
+                    url: "secondbrain://note/42".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_queue_holds_until_taken() {
+        let queue = LaunchQueue::default();
+        let link = |n: usize| LaunchRequest::DeepLink {
+            url: format!("secondbrain://note/{}", n),
+        };
+
+        for n in 0..MAX_PENDING + 2 {
+            assert_eq!(queue.hold(link(n)), None);
+        }
+        let held = queue.take();
+        assert_eq!(held.len(), MAX_PENDING);
+        assert_eq!(held[0], link(2));
+
+        // Once the frontend listens, requests go straight through
+        assert_eq!(queue.hold(link(99)), Some(link(99)));
+        assert!(queue.take().is_empty());
+    }
+
+    #[test]
+    fn test_serialization() {
+        let value = serde_json::to_value(LaunchRequest::OpenFile {
+            path: "/tmp/a.md".to_string(),
+        })
+        .unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({ "type": "open_file", "path": "/tmp/a.md" })
+        );
+    }
+}
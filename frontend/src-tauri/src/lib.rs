@@ -1,26 +1,117 @@
-use std::io::{BufRead, BufReader};
+use parking_lot::{Mutex, RwLock};
 use std::path::Path;
-use std::process::{Child, Command, Stdio};
-use std::sync::{Arc, Mutex};
+use std::process::Stdio;
+use std::sync::Arc;
 use tauri::{
     menu::{Menu, MenuItem, PredefinedMenuItem, Submenu},
-    tray::TrayIconBuilder,
-    AppHandle, Emitter, Manager,
+    AppHandle, Emitter, Manager, Runtime,
 };
-
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::process::{Child, Command};
+use tracing::Instrument;
+
+pub mod accessibility;
+pub mod ai_cache;
+pub mod api_endpoint;
+pub mod app_lock;
+pub mod apple_import;
+pub mod archives;
+pub mod asset_protocol;
+pub mod attachments;
+pub mod backup;
+pub mod batch;
+pub mod calendar;
+pub mod cli;
+pub mod clock;
+pub mod cloud_backup;
 mod commands;
 pub mod config;
+pub mod config_recovery;
+pub mod contacts;
+pub mod control_api;
 pub mod database;
+pub mod demo;
+pub mod diagnostic_bundle;
 pub mod diagnostics;
+pub mod disk_space;
+pub mod email_watcher;
+pub mod error;
+pub mod event_bridge;
+pub mod events;
+pub mod export;
+pub mod faults;
+pub mod feeds;
+pub mod focus;
+pub mod git_roots;
+pub mod github;
+pub mod headless;
+pub mod health;
+pub mod http;
+pub mod idle;
+pub mod jobs;
+pub mod journal;
+pub mod keychain;
+pub mod launch;
+pub mod logging;
+pub mod login_item;
+pub mod logs;
+pub mod mock_backend;
+pub mod models;
+pub mod native_messaging;
+pub mod note_history;
+pub mod notifications;
+pub mod obsidian;
+pub mod osascript;
+pub mod pdf;
+pub mod peer_sync;
+pub mod pinecone;
 pub mod port_utils;
+pub mod power;
+pub mod presentation;
+pub mod print;
+pub mod profile;
+pub mod proxy;
+pub mod query_cli;
+pub mod quicklook;
+pub mod relaunch;
+pub mod reminders;
+pub mod resource_monitor;
+pub mod sanitize;
+pub mod scheduler;
+pub mod screen_privacy;
 pub mod secrets;
+pub mod services;
+pub mod shell_health;
+pub mod shutdown;
+pub mod snapshots;
+pub mod splash;
 pub mod startup;
-
+pub mod startup_deps;
+pub mod startup_trace;
+pub mod streams;
+pub mod tokens;
+pub mod trash;
+pub mod tray;
+pub mod tunnel;
+pub mod uninstall;
+pub mod uploads;
+pub mod usage;
+pub mod voice;
+pub mod wal_archive;
+pub mod webview_watchdog;
+pub mod window_session;
+pub mod write_queue;
+pub mod zoom;
+
+use ai_cache::AiCache;
 use config::ServiceConfig;
-use database::PostgresManager;
-use port_utils::{find_available_port, is_port_available};
+use database::{PendingUpgrade, PostgresManager};
+use error::AppError;
+use port_utils::is_port_available_async;
 pub use secrets::{generate_jwt_secret, Secrets};
+use services::{ServiceCommand, ServiceManager};
 use startup::{StartupConfig, StartupEvent, StartupMetrics, StartupTimer};
+use startup_deps::StartupDeps;
 
 /// Load secrets from file (synchronous, for use during startup)
 pub fn load_secrets(app_data_dir: &Path) -> Secrets {
@@ -30,19 +121,24 @@ pub fn load_secrets(app_data_dir: &Path) -> Secrets {
         match std::fs::read_to_string(&secrets_path) {
             Ok(contents) => match serde_json::from_str::<Secrets>(&contents) {
                 Ok(secrets) => {
-                    log::info!("Loaded API secrets from {:?}", secrets_path);
+                    tracing::info!("Loaded API secrets from {:?}", secrets_path);
                     return secrets;
                 }
                 Err(e) => {
-                    log::warn!("Failed to parse secrets.json: {}", e);
+                    return config_recovery::recover(
+                        &secrets_path,
+                        &contents,
+                        &e.to_string(),
+                        Secrets::default(),
+                    );
                 }
             },
             Err(e) => {
-                log::warn!("Failed to read secrets.json: {}", e);
+                tracing::warn!("Failed to read secrets.json: {}", e);
             }
         }
     } else {
-        log::info!(
+        tracing::info!(
             "No secrets.json found at {:?}, using defaults",
             secrets_path
         );
@@ -97,7 +193,7 @@ pub fn save_secrets(app_data_dir: &Path, secrets: &Secrets) -> Result<(), String
     std::fs::rename(&temp_path, &secrets_path)
         .map_err(|e| format!("Failed to rename secrets file: {}", e))?;
 
-    log::info!("Saved API secrets to {:?}", secrets_path);
+    tracing::info!("Saved API secrets to {:?}", secrets_path);
     Ok(())
 }
 
@@ -112,28 +208,39 @@ pub async fn save_secrets_async(
 }
 
 // Application state
+//
+// Field locks are short-lived: take one at a time, copy or clone what you
+// need, and release it before any `.await` or blocking call. Starting,
+// restarting and stopping services goes through `services::ServiceManager`,
+// which also owns the backend process.
 pub struct AppState {
-    pub backend_process: Mutex<Option<Child>>,
-    pub backend_port: Mutex<u16>,
-    pub postgres_port: Mutex<u16>,
-    pub is_backend_ready: Mutex<bool>,
-    pub is_postgres_ready: Mutex<bool>,
-    pub postgres_manager: Mutex<Option<Arc<PostgresManager>>>,
+    pub backend_port: RwLock<u16>,
+    pub postgres_port: RwLock<u16>,
+    pub is_backend_ready: RwLock<bool>,
+    pub is_postgres_ready: RwLock<bool>,
+    pub postgres_manager: RwLock<Option<Arc<PostgresManager>>>,
     pub startup_metrics: Mutex<StartupMetrics>,
-    pub service_config: Mutex<Option<ServiceConfig>>,
+    pub service_config: RwLock<Option<ServiceConfig>>,
+    pub ai_cache: RwLock<Option<Arc<AiCache>>>,
+    /// Authorization header registered by the frontend for shell-initiated backend calls
+    pub backend_auth: RwLock<Option<String>>,
+    /// Ports, health checks and process spawning used by service startup
+    pub startup_deps: RwLock<StartupDeps>,
 }
 
 impl Default for AppState {
     fn default() -> Self {
         Self {
-            backend_process: Mutex::new(None),
-            backend_port: Mutex::new(5001),
-            postgres_port: Mutex::new(5433), // Use non-standard port to avoid conflicts
-            is_backend_ready: Mutex::new(false),
-            is_postgres_ready: Mutex::new(false),
-            postgres_manager: Mutex::new(None),
+            backend_port: RwLock::new(5001),
+            postgres_port: RwLock::new(5433), // Use non-standard port to avoid conflicts
+            is_backend_ready: RwLock::new(false),
+            is_postgres_ready: RwLock::new(false),
+            postgres_manager: RwLock::new(None),
             startup_metrics: Mutex::new(StartupMetrics::new()),
-            service_config: Mutex::new(None),
+            service_config: RwLock::new(None),
+            ai_cache: RwLock::new(None),
+            backend_auth: RwLock::new(None),
+            startup_deps: RwLock::new(StartupDeps::default()),
         }
     }
 }
@@ -142,34 +249,30 @@ impl AppState {
     /// Create new state with ports from cached config
     pub fn with_config(config: &ServiceConfig) -> Self {
         Self {
-            backend_process: Mutex::new(None),
-            backend_port: Mutex::new(config.backend_port),
-            postgres_port: Mutex::new(config.postgres_port),
-            is_backend_ready: Mutex::new(false),
-            is_postgres_ready: Mutex::new(false),
-            postgres_manager: Mutex::new(None),
-            startup_metrics: Mutex::new(StartupMetrics::new()),
-            service_config: Mutex::new(Some(config.clone())),
+            backend_port: RwLock::new(config.backend_port),
+            postgres_port: RwLock::new(config.postgres_port),
+            service_config: RwLock::new(Some(config.clone())),
+            ..Self::default()
         }
     }
 }
 
 #[tauri::command]
-async fn get_backend_url(state: tauri::State<'_, AppState>) -> Result<String, String> {
-    let port = state.backend_port.lock().unwrap();
-    Ok(format!("http://localhost:{}/api", *port))
+async fn get_backend_url(state: tauri::State<'_, AppState>) -> Result<String, AppError> {
+    let port = *state.backend_port.read();
+    Ok(api_endpoint::api_url(port))
 }
 
 #[tauri::command]
-async fn is_backend_ready(state: tauri::State<'_, AppState>) -> Result<bool, String> {
-    let ready = state.is_backend_ready.lock().unwrap();
+async fn is_backend_ready(state: tauri::State<'_, AppState>) -> Result<bool, AppError> {
+    let ready = state.is_backend_ready.read();
     Ok(*ready)
 }
 
 #[tauri::command]
-async fn get_database_status(state: tauri::State<'_, AppState>) -> Result<String, String> {
-    let postgres_ready = *state.is_postgres_ready.lock().unwrap();
-    let backend_ready = *state.is_backend_ready.lock().unwrap();
+async fn get_database_status(state: tauri::State<'_, AppState>) -> Result<String, AppError> {
+    let postgres_ready = *state.is_postgres_ready.read();
+    let backend_ready = *state.is_backend_ready.read();
 
     if !postgres_ready {
         Ok("Starting PostgreSQL...".to_string())
@@ -182,38 +285,65 @@ async fn get_database_status(state: tauri::State<'_, AppState>) -> Result<String
 
 /// Get startup metrics for diagnostics
 #[tauri::command]
-async fn get_startup_metrics(state: tauri::State<'_, AppState>) -> Result<StartupMetrics, String> {
-    let metrics = state.startup_metrics.lock().unwrap().clone();
+async fn get_startup_metrics(
+    state: tauri::State<'_, AppState>,
+) -> Result<StartupMetrics, AppError> {
+    let metrics = state.startup_metrics.lock().clone();
     Ok(metrics)
 }
 
+/// Get p50/p95 startup times, retry frequency and the trend over recent boots
+#[tauri::command]
+async fn get_startup_stats(app: AppHandle) -> Result<startup::StartupStats, AppError> {
+    let app_data_dir = app.path().app_data_dir()?;
+    Ok(startup::StartupHistory::load(&app_data_dir).stats())
+}
+
+/// Add the latest startup metrics to the persisted history
+pub(crate) fn record_startup(app: &AppHandle) {
+    let Ok(app_data_dir) = app.path().app_data_dir() else {
+        return;
+    };
+    let metrics = app.state::<AppState>().startup_metrics.lock().clone();
+    let mut history = startup::StartupHistory::load(&app_data_dir);
+    history.record(startup::BootRecord {
+        at: chrono::Utc::now().timestamp(),
+        version: app.package_info().version.to_string(),
+        metrics,
+    });
+    if let Err(e) = history.save(&app_data_dir) {
+        tracing::warn!("Failed to save startup history: {}", e);
+    }
+}
+
 /// Get current port configuration
 #[tauri::command]
-async fn get_port_config(state: tauri::State<'_, AppState>) -> Result<(u16, u16), String> {
-    let postgres_port = *state.postgres_port.lock().unwrap();
-    let backend_port = *state.backend_port.lock().unwrap();
+async fn get_port_config(state: tauri::State<'_, AppState>) -> Result<(u16, u16), AppError> {
+    let postgres_port = *state.postgres_port.read();
+    let backend_port = *state.backend_port.read();
     Ok((postgres_port, backend_port))
 }
 
 /// Check if a port is available
 #[tauri::command]
-async fn check_port_available(port: u16) -> Result<bool, String> {
-    Ok(is_port_available(port))
+async fn check_port_available(port: u16) -> Result<bool, AppError> {
+    Ok(is_port_available_async(port).await)
 }
 
 /// Copy text to clipboard (used by tray menu)
 #[tauri::command]
-async fn copy_to_clipboard(app: AppHandle, text: String) -> Result<(), String> {
+async fn copy_to_clipboard(app: AppHandle, text: String) -> Result<(), AppError> {
     use tauri_plugin_clipboard_manager::ClipboardExt;
     app.clipboard()
         .write_text(&text)
         .map_err(|e| format!("Failed to copy to clipboard: {}", e))
+        .map_err(AppError::from)
 }
 
 /// Set dock badge on macOS (for unread counts, etc.)
 #[cfg(target_os = "macos")]
 #[tauri::command]
-async fn set_dock_badge(_app: AppHandle, badge: Option<String>) -> Result<(), String> {
+async fn set_dock_badge(_app: AppHandle, badge: Option<String>) -> Result<(), AppError> {
     use objc2::MainThreadMarker;
     use objc2_app_kit::NSApplication;
     use objc2_foundation::NSString;
@@ -239,14 +369,14 @@ async fn set_dock_badge(_app: AppHandle, badge: Option<String>) -> Result<(), St
 
 #[cfg(not(target_os = "macos"))]
 #[tauri::command]
-async fn set_dock_badge(_app: AppHandle, _badge: Option<String>) -> Result<(), String> {
+async fn set_dock_badge(_app: AppHandle, _badge: Option<String>) -> Result<(), AppError> {
     // No-op on non-macOS platforms
     Ok(())
 }
 
 /// Generate a diagnostic report for troubleshooting
 #[tauri::command]
-async fn get_diagnostic_report(app: AppHandle) -> Result<diagnostics::DiagnosticReport, String> {
+async fn get_diagnostic_report(app: AppHandle) -> Result<diagnostics::DiagnosticReport, AppError> {
     let state = app.state::<AppState>();
 
     let app_version = app
@@ -255,25 +385,22 @@ async fn get_diagnostic_report(app: AppHandle) -> Result<diagnostics::Diagnostic
         .clone()
         .unwrap_or_else(|| "unknown".to_string());
 
-    let postgres_ready = *state.is_postgres_ready.lock().unwrap();
-    let postgres_port = *state.postgres_port.lock().unwrap();
-    let backend_ready = *state.is_backend_ready.lock().unwrap();
-    let backend_port = *state.backend_port.lock().unwrap();
+    let postgres_ready = *state.is_postgres_ready.read();
+    let postgres_port = *state.postgres_port.read();
+    let backend_ready = *state.is_backend_ready.read();
+    let backend_port = *state.backend_port.read();
 
     let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
     let log_dir = app_data_dir.join("logs");
 
     // Get PostgreSQL bin directory if manager exists
-    let postgres_bin_dir = state.postgres_manager.lock().unwrap().as_ref().map(|_| {
-        // Get the bin directory from the standard locations
-        if cfg!(target_os = "macos") {
-            std::path::PathBuf::from("/opt/homebrew/opt/postgresql@18/bin")
-        } else {
-            std::path::PathBuf::from("/usr/bin")
-        }
-    });
+    let postgres_bin = state
+        .postgres_manager
+        .read()
+        .as_ref()
+        .map(|manager| (manager.get_bin_dir().to_path_buf(), manager.bin_source()));
 
-    let report = diagnostics::DiagnosticReport::generate(
+    let mut report = diagnostics::DiagnosticReport::generate(
         app_version,
         postgres_ready,
         postgres_port,
@@ -281,97 +408,85 @@ async fn get_diagnostic_report(app: AppHandle) -> Result<diagnostics::Diagnostic
         backend_port,
         &app_data_dir,
         &log_dir,
-        postgres_bin_dir.as_deref(),
+        postgres_bin
+            .as_ref()
+            .map(|(bin_dir, source)| (bin_dir.as_path(), *source)),
     );
 
-    Ok(report)
-}
-
-/// Get recent application logs
-#[tauri::command]
-async fn get_recent_logs(app: AppHandle, max_lines: Option<usize>) -> Result<Vec<String>, String> {
-    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
-    let log_dir = app_data_dir.join("logs");
+    if let Ok(ai_cache) = proxy::ai_cache(&app) {
+        report.ai_cache = Some(ai_cache.stats());
+    }
 
-    let lines = max_lines.unwrap_or(100);
-
-    // Read from log files
-    let mut logs = Vec::new();
-
-    if let Ok(entries) = std::fs::read_dir(&log_dir) {
-        let mut log_files: Vec<_> = entries
-            .filter_map(|e| e.ok())
-            .filter(|e| {
-                e.path()
-                    .extension()
-                    .map(|ext| ext == "log")
-                    .unwrap_or(false)
-            })
-            .collect();
-
-        // Sort by modification time (newest first)
-        log_files.sort_by(|a, b| {
-            let a_time = a.metadata().and_then(|m| m.modified()).ok();
-            let b_time = b.metadata().and_then(|m| m.modified()).ok();
-            b_time.cmp(&a_time)
-        });
+    report.allowed_origins = config::current_settings(&state).effective_origins();
+    report.clock = clock::last_check(&app);
+    report.github_token = github::last_check(&app);
 
-        // Read from most recent log file
-        if let Some(entry) = log_files.first() {
-            if let Ok(content) = std::fs::read_to_string(entry.path()) {
-                let file_lines: Vec<_> = content.lines().rev().take(lines).collect();
-                logs = file_lines
-                    .into_iter()
-                    .rev()
-                    .map(|s| s.to_string())
-                    .collect();
-            }
+    if backend_ready {
+        match attachments::audit_attachments(app.clone()).await {
+            Ok(audit) => report.attachments = Some(audit.summary()),
+            Err(e) => tracing::warn!("Attachment audit failed: {}", e),
         }
     }
 
-    Ok(logs)
+    Ok(report)
 }
 
+/// Get disk usage of the app data directory
 #[tauri::command]
-async fn restart_backend(app: AppHandle) -> Result<(), String> {
-    let state = app.state::<AppState>();
-
-    // Stop existing backend
-    if let Some(mut child) = state.backend_process.lock().unwrap().take() {
-        let _ = child.kill();
-        let _ = child.wait();
-    }
-
-    *state.is_backend_ready.lock().unwrap() = false;
+async fn get_storage_breakdown(app: AppHandle) -> Result<diagnostics::StorageBreakdown, AppError> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
 
-    // Start new backend (PostgreSQL should already be running)
-    start_backend_internal(&app).await
+    // Unreferenced counts need the backend; report sizes without them if it's down
+    let referenced = attachments::referenced_hashes(&app).await.ok();
+
+    tokio::task::spawn_blocking(move || {
+        let mut breakdown = diagnostics::StorageBreakdown::collect(&app_data_dir);
+        breakdown.attachments =
+            Some(attachments::AttachmentStore::new(&app_data_dir).stats(referenced.as_ref()));
+        breakdown
+    })
+    .await
+    .map_err(AppError::from)
 }
 
 #[tauri::command]
-async fn restart_database(app: AppHandle) -> Result<(), String> {
-    let state = app.state::<AppState>();
-
-    // Stop backend first
-    if let Some(mut child) = state.backend_process.lock().unwrap().take() {
-        let _ = child.kill();
-        let _ = child.wait();
-    }
-    *state.is_backend_ready.lock().unwrap() = false;
+async fn restart_backend(app: AppHandle) -> Result<(), AppError> {
+    // PostgreSQL should already be running
+    app.state::<ServiceManager>()
+        .send(ServiceCommand::RestartBackend)
+        .await
+}
 
-    // Stop PostgreSQL
-    if let Some(ref manager) = *state.postgres_manager.lock().unwrap() {
-        manager.stop()?;
-    }
-    *state.is_postgres_ready.lock().unwrap() = false;
+/// Replace the backend's JWT secret and restart the backend with it
+///
+/// Sessions signed with the old secret stop working, so the frontend is told
+/// to sign in again once the backend is back.
+#[tauri::command]
+async fn rotate_jwt_secret(app: AppHandle) -> Result<(), AppError> {
+    let app_data_dir = app.path().app_data_dir()?;
+    tokio::task::spawn_blocking(move || secrets::rotate_jwt_secret(&app_data_dir)).await??;
+    // A backend still starting read the old secret, so it's restarted too
+    let services = app.state::<ServiceManager>();
+    if matches!(
+        services.state().backend,
+        services::ServicePhase::Running | services::ServicePhase::Starting
+    ) {
+        services.send(ServiceCommand::RestartBackend).await?;
+    }
+    let _ = app.emit("jwt-secret-rotated", ());
+    Ok(())
+}
 
-    // Restart everything
-    start_services_internal(&app).await
+#[tauri::command]
+async fn restart_database(app: AppHandle) -> Result<(), AppError> {
+    app.state::<ServiceManager>()
+        .send(ServiceCommand::RestartDatabase)
+        .await
 }
 
 /// Get API secrets
 #[tauri::command]
-async fn get_secrets(app: AppHandle) -> Result<Secrets, String> {
+async fn get_secrets(app: AppHandle) -> Result<Secrets, AppError> {
     let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
 
     Ok(load_secrets(&app_data_dir))
@@ -379,7 +494,7 @@ async fn get_secrets(app: AppHandle) -> Result<Secrets, String> {
 
 /// Save API secrets and optionally restart the backend
 #[tauri::command]
-async fn save_secrets_cmd(app: AppHandle, secrets: Secrets, restart: bool) -> Result<(), String> {
+async fn save_secrets_cmd(app: AppHandle, secrets: Secrets, restart: bool) -> Result<(), AppError> {
     let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
 
     save_secrets(&app_data_dir, &secrets)?;
@@ -394,7 +509,7 @@ async fn save_secrets_cmd(app: AppHandle, secrets: Secrets, restart: bool) -> Re
 
 /// Get the path to the secrets storage location
 #[tauri::command]
-async fn get_secrets_path(app: AppHandle) -> Result<String, String> {
+async fn get_secrets_path(app: AppHandle) -> Result<String, AppError> {
     let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
 
     Ok(app_data_dir
@@ -404,106 +519,136 @@ async fn get_secrets_path(app: AppHandle) -> Result<String, String> {
 }
 
 /// Start PostgreSQL and the backend with improved startup flow
-async fn start_services_internal(app: &AppHandle) -> Result<(), String> {
+///
+/// Returns the backend process; only `services::ServiceManager` calls this.
+#[tracing::instrument(name = "startup", skip_all)]
+async fn start_services_internal<R: Runtime>(app: &AppHandle<R>) -> Result<Child, AppError> {
     let overall_timer = StartupTimer::new();
     let state = app.state::<AppState>();
 
     // Reset startup metrics
-    *state.startup_metrics.lock().unwrap() = StartupMetrics::new();
+    let metrics = state.startup_metrics.lock().restarted();
+    *state.startup_metrics.lock() = metrics;
 
     // Load cached config if available
     if let Ok(app_data_dir) = app.path().app_data_dir() {
-        let cached_config = ServiceConfig::load(&app_data_dir);
+        let cached_config =
+            ServiceConfig::load_or(&app_data_dir, profile::current(app).service_config());
 
         // Use cached ports if they're available
-        if is_port_available(cached_config.postgres_port) {
-            *state.postgres_port.lock().unwrap() = cached_config.postgres_port;
+        let ports = state.startup_deps.read().ports.clone();
+        let (postgres_free, backend_free) = tokio::join!(
+            ports.is_available(cached_config.postgres_port),
+            ports.is_available(cached_config.backend_port)
+        );
+        if postgres_free {
+            *state.postgres_port.write() = cached_config.postgres_port;
         }
-        if is_port_available(cached_config.backend_port) {
-            *state.backend_port.lock().unwrap() = cached_config.backend_port;
+        if backend_free {
+            *state.backend_port.write() = cached_config.backend_port;
         }
 
-        *state.service_config.lock().unwrap() = Some(cached_config);
+        *state.service_config.write() = Some(cached_config);
     }
 
     // Start PostgreSQL first
     let pg_timer = StartupTimer::new();
-    let postgres_port = *state.postgres_port.lock().unwrap();
+    let postgres_port = *state.postgres_port.read();
 
     StartupEvent::PostgresStarting {
         port: postgres_port,
     }
     .emit(app);
 
-    match start_postgres_internal(app) {
+    // PostgreSQL startup blocks, so keep it off the async runtime threads
+    let app_for_postgres = app.clone();
+    let postgres_span = tracing::info_span!("postgres", port = postgres_port);
+    let postgres_result = tokio::task::spawn_blocking(move || {
+        let _span = postgres_span.entered();
+        start_postgres_internal(&app_for_postgres)
+    })
+    .await
+    .map_err(|e| format!("Task panicked: {}", e))?;
+    match postgres_result {
         Ok(()) => {
-            let actual_port = *state.postgres_port.lock().unwrap();
+            let actual_port = *state.postgres_port.read();
             StartupEvent::PostgresReady {
                 port: actual_port,
                 duration_ms: pg_timer.elapsed_ms(),
             }
             .emit(app);
 
-            state.startup_metrics.lock().unwrap().mark_postgres_started(
+            let retries = state
+                .postgres_manager
+                .read()
+                .as_ref()
+                .map_or(0, |manager| manager.last_retry_count());
+            state.startup_metrics.lock().mark_postgres_started(
                 pg_timer.elapsed(),
                 actual_port,
-                0,
+                retries,
             );
         }
         Err(e) => {
             StartupEvent::PostgresFailed {
-                error: e.clone(),
+                error: e.to_string(),
                 port: postgres_port,
             }
             .emit(app);
 
-            state.startup_metrics.lock().unwrap().mark_failed(e.clone());
-            StartupEvent::StartupFailed { error: e.clone() }.emit(app);
+            state.startup_metrics.lock().mark_failed(e.to_string());
+            StartupEvent::StartupFailed {
+                error: e.to_string(),
+            }
+            .emit(app);
             return Err(e);
         }
     }
 
     // Then start the backend
     let backend_timer = StartupTimer::new();
-    let backend_port = *state.backend_port.lock().unwrap();
+    let backend_port = *state.backend_port.read();
 
     StartupEvent::BackendStarting { port: backend_port }.emit(app);
 
-    match start_backend_internal(app).await {
-        Ok(()) => {
-            let actual_port = *state.backend_port.lock().unwrap();
+    let child = match start_backend_internal(app)
+        .instrument(tracing::info_span!("backend", port = backend_port))
+        .await
+    {
+        Ok(child) => {
+            let actual_port = *state.backend_port.read();
             StartupEvent::BackendReady {
                 port: actual_port,
                 duration_ms: backend_timer.elapsed_ms(),
             }
             .emit(app);
 
-            state.startup_metrics.lock().unwrap().mark_backend_started(
+            state.startup_metrics.lock().mark_backend_started(
                 backend_timer.elapsed(),
                 actual_port,
                 0,
             );
+            child
         }
         Err(e) => {
             StartupEvent::BackendFailed {
-                error: e.clone(),
+                error: e.to_string(),
                 port: backend_port,
             }
             .emit(app);
 
-            state.startup_metrics.lock().unwrap().mark_failed(e.clone());
-            StartupEvent::StartupFailed { error: e.clone() }.emit(app);
+            state.startup_metrics.lock().mark_failed(e.to_string());
+            StartupEvent::StartupFailed {
+                error: e.to_string(),
+            }
+            .emit(app);
             return Err(e);
         }
-    }
+    };
 
     // Mark complete and cache successful config
     let total_duration = overall_timer.elapsed();
-    state
-        .startup_metrics
-        .lock()
-        .unwrap()
-        .mark_complete(total_duration);
+    state.startup_metrics.lock().mark_complete(total_duration);
 
     StartupEvent::AllServicesReady {
         total_duration_ms: overall_timer.elapsed_ms(),
@@ -512,48 +657,48 @@ async fn start_services_internal(app: &AppHandle) -> Result<(), String> {
 
     // Save successful config for next startup
     if let Ok(app_data_dir) = app.path().app_data_dir() {
-        let postgres_port = *state.postgres_port.lock().unwrap();
-        let backend_port = *state.backend_port.lock().unwrap();
+        let postgres_port = *state.postgres_port.read();
+        let backend_port = *state.backend_port.read();
 
-        let mut config = ServiceConfig::default();
+        // Keep fields recorded elsewhere, like the last backup time
+        let mut config = ServiceConfig::load(&app_data_dir);
         config.mark_successful_startup(postgres_port, backend_port);
 
         if let Err(e) = config.save(&app_data_dir) {
-            log::warn!("Failed to save service config: {}", e);
+            tracing::warn!("Failed to save service config: {}", e);
         }
     }
 
-    Ok(())
+    Ok(child)
 }
 
 /// Start the embedded PostgreSQL instance with port conflict handling
-fn start_postgres_internal(app: &AppHandle) -> Result<(), String> {
-    let state = app.state::<AppState>();
-    let mut port = *state.postgres_port.lock().unwrap();
+fn start_postgres_internal<R: Runtime>(app: &AppHandle<R>) -> Result<(), AppError> {
+    disk_space::ensure_room_for_database(app)?;
 
-    // Check if port is available, find alternative if not
-    if !is_port_available(port) {
-        log::warn!("Port {} is in use, searching for alternative...", port);
-
-        StartupEvent::PortConflict {
-            port,
-            service: "PostgreSQL".to_string(),
-        }
-        .emit(app);
-
-        if let Some(new_port) = find_available_port(port + 1, 10) {
-            log::info!("Found alternative PostgreSQL port: {}", new_port);
-            port = new_port;
-            *state.postgres_port.lock().unwrap() = new_port;
-        } else {
-            return Err(format!(
-                "Port {} is in use and no alternatives available in range {}-{}",
-                port,
-                port + 1,
-                port + 10
-            ));
-        }
-    }
+    let state = app.state::<AppState>();
+    let settings = config::current_settings(&state);
+    let range = settings.postgres_ports;
+    let preferred = *state.postgres_port.read();
+    let preferred = range.map_or(preferred, |range| range.preferred(preferred));
+
+    // Check if port is available, find alternative if not; this runs on a
+    // blocking thread, so waiting on the probe here is fine
+    let ports = state.startup_deps.read().ports.clone();
+    let port = tauri::async_runtime::block_on(startup_deps::claim_port(
+        ports.as_ref(),
+        preferred,
+        range,
+        "PostgreSQL",
+        || {
+            StartupEvent::PortConflict {
+                port: preferred,
+                service: "PostgreSQL".to_string(),
+            }
+            .emit(app)
+        },
+    ))?;
+    *state.postgres_port.write() = port;
 
     // Get app data directory
     let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
@@ -575,17 +720,18 @@ fn start_postgres_internal(app: &AppHandle) -> Result<(), String> {
         app.path().resource_dir().map_err(|e| e.to_string())?
     };
 
-    log::info!("App data directory: {:?}", app_data_dir);
-    log::info!("Resource directory: {:?}", resource_dir);
+    tracing::info!("App data directory: {:?}", app_data_dir);
+    tracing::info!("Resource directory: {:?}", resource_dir);
 
     // Create PostgreSQL manager with custom startup config
-    let startup_config = StartupConfig {
+    let startup_config = settings.apply_to(StartupConfig {
         initial_delay_ms: 500,
         max_delay_ms: 5000,
         backoff_multiplier: 1.5,
         max_attempts: 5,
         timeout_secs: 60,
-    };
+        ..StartupConfig::default()
+    });
 
     let manager = Arc::new(PostgresManager::with_config(
         app_data_dir.clone(),
@@ -594,56 +740,87 @@ fn start_postgres_internal(app: &AppHandle) -> Result<(), String> {
         startup_config,
     ));
 
-    // Initialize and start PostgreSQL
-    log::info!("Initializing PostgreSQL database...");
-    manager.init_database()?;
+    // A data directory from an older major version can't be opened as is
+    if let Some(upgrade) = manager.pending_upgrade()? {
+        upgrade_postgres_data(app, &manager, upgrade)?;
+    }
 
-    log::info!("Starting PostgreSQL server on port {}...", port);
-    manager.start()?;
+    // Initialize and start PostgreSQL
+    tracing::info!("Initializing PostgreSQL database...");
+    manager.init_database().map_err(AppError::Database)?;
+    manager
+        .apply_memory_settings(&settings.postgres_memory)
+        .map_err(AppError::Database)?;
+    manager
+        .apply_wal_archiving(wal_archive::enabled_wal_dir(&app_data_dir).as_deref())
+        .map_err(AppError::Database)?;
+
+    tracing::info!("Starting PostgreSQL server on port {}...", port);
+    manager.start_with_retry()?;
 
     // Update state with actual port (may have changed due to conflict)
     let actual_port = manager.get_port();
-    *state.postgres_port.lock().unwrap() = actual_port;
+    *state.postgres_port.write() = actual_port;
 
     // Store manager in state
-    *state.postgres_manager.lock().unwrap() = Some(manager);
-    *state.is_postgres_ready.lock().unwrap() = true;
+    *state.postgres_manager.write() = Some(manager);
+    *state.is_postgres_ready.write() = true;
 
-    log::info!("PostgreSQL is ready on port {}", actual_port);
+    tracing::info!("PostgreSQL is ready on port {}", actual_port);
     Ok(())
 }
 
-async fn start_backend_internal(app: &AppHandle) -> Result<(), String> {
+/// Upgrade the data directory to the installed PostgreSQL, reporting progress
+fn upgrade_postgres_data<R: Runtime>(
+    app: &AppHandle<R>,
+    manager: &PostgresManager,
+    upgrade: PendingUpgrade,
+) -> Result<(), AppError> {
+    let timer = StartupTimer::new();
+    tracing::info!(
+        "Upgrading PostgreSQL data from {} to {}",
+        upgrade.from_version,
+        upgrade.to_version
+    );
+    let method = manager.upgrade_data_dir(upgrade, |step| {
+        StartupEvent::DatabaseUpgrading {
+            from_version: upgrade.from_version,
+            to_version: upgrade.to_version,
+            step,
+            percent: step.percent(),
+        }
+        .emit(app)
+    })?;
+    StartupEvent::DatabaseUpgraded {
+        from_version: upgrade.from_version,
+        to_version: upgrade.to_version,
+        method,
+        duration_ms: timer.elapsed_ms(),
+    }
+    .emit(app);
+    Ok(())
+}
+
+async fn start_backend_internal<R: Runtime>(app: &AppHandle<R>) -> Result<Child, AppError> {
     let state = app.state::<AppState>();
-    let mut backend_port = *state.backend_port.lock().unwrap();
-    let postgres_port = *state.postgres_port.lock().unwrap();
+    let deps = state.startup_deps.read().clone();
+    let settings = config::current_settings(&state);
+    let range = settings.backend_ports;
+    let preferred = *state.backend_port.read();
+    let preferred = range.map_or(preferred, |range| range.preferred(preferred));
+    let postgres_port = *state.postgres_port.read();
 
     // Check if port is available, find alternative if not
-    if !is_port_available(backend_port) {
-        log::warn!(
-            "Port {} is in use, searching for alternative...",
-            backend_port
-        );
-
-        StartupEvent::PortConflict {
-            port: backend_port,
-            service: "Backend".to_string(),
-        }
-        .emit(app);
-
-        if let Some(new_port) = find_available_port(backend_port + 1, 10) {
-            log::info!("Found alternative backend port: {}", new_port);
-            backend_port = new_port;
-            *state.backend_port.lock().unwrap() = new_port;
-        } else {
-            return Err(format!(
-                "Port {} is in use and no alternatives available in range {}-{}",
-                backend_port,
-                backend_port + 1,
-                backend_port + 10
-            ));
-        }
-    }
+    let backend_port =
+        startup_deps::claim_port(deps.ports.as_ref(), preferred, range, "backend", || {
+            StartupEvent::PortConflict {
+                port: preferred,
+                service: "Backend".to_string(),
+            }
+            .emit(app)
+        })
+        .await?;
+    *state.backend_port.write() = backend_port;
 
     // Get app data directory for logs
     let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
@@ -653,8 +830,8 @@ async fn start_backend_internal(app: &AppHandle) -> Result<(), String> {
     // Ensure directories exist
     std::fs::create_dir_all(&log_path).map_err(|e| e.to_string())?;
 
-    log::info!("Starting backend on port {}", backend_port);
-    log::info!("Log directory: {:?}", log_path);
+    tracing::info!("Starting backend on port {}", backend_port);
+    tracing::info!("Log directory: {:?}", log_path);
 
     // Build connection string for embedded PostgreSQL
     // Include Client Encoding=UTF8 to ensure proper handling of Unicode characters (emojis, etc.)
@@ -664,29 +841,22 @@ async fn start_backend_internal(app: &AppHandle) -> Result<(), String> {
     );
 
     // Load API secrets from config file
-    let mut secrets = load_secrets(&app_data_dir);
+    let secrets = load_secrets(&app_data_dir);
 
-    // Ensure we have a JWT secret - generate one if not present
-    let jwt_secret = if let Some(ref existing_secret) = secrets.jwt_secret {
-        log::info!("Using existing JWT secret from secrets.json");
-        existing_secret.clone()
-    } else {
-        log::info!("Generating new JWT secret for desktop app");
-        let new_secret = generate_jwt_secret();
-        secrets.jwt_secret = Some(new_secret.clone());
-        // Save the updated secrets with the new JWT secret
-        if let Err(e) = save_secrets(&app_data_dir, &secrets) {
-            log::warn!("Failed to save JWT secret to secrets.json: {}. Secret will be regenerated on next start.", e);
-        }
-        new_secret
-    };
+    // Per-install JWT secret from the keychain, generated on first run
+    let jwt_secret = secrets::load_or_create_jwt_secret(&app_data_dir)?;
 
     // Find the backend executable
     let backend_path = find_backend_path(app)?;
-    log::info!("Backend path: {:?}", backend_path);
+    tracing::info!("Backend path: {:?}", backend_path);
 
     // Build and start the command
     let mut command = Command::new(&backend_path);
+    // Never leave a backend running behind a dropped handle
+    command.kill_on_drop(true);
+    // Own process group so CTRL_BREAK can ask just the backend to exit
+    #[cfg(windows)]
+    command.creation_flags(0x0000_0200); // CREATE_NEW_PROCESS_GROUP
     command
         .current_dir(backend_path.parent().unwrap_or(&backend_path))
         .env(
@@ -694,7 +864,10 @@ async fn start_backend_internal(app: &AppHandle) -> Result<(), String> {
             format!("http://localhost:{}", backend_port),
         )
         .env("ASPNETCORE_ENVIRONMENT", "Production")
-        .env("Logging__LogLevel__Default", "Warning")
+        .env(
+            "Logging__LogLevel__Default",
+            settings.backend_log_level.as_str(),
+        )
         .env("ConnectionStrings__DefaultConnection", connection_string)
         .env(
             "SecondBrain__LogPath",
@@ -704,13 +877,13 @@ async fn start_backend_internal(app: &AppHandle) -> Result<(), String> {
         .env("Jwt__SecretKey", jwt_secret)
         .env("Jwt__Issuer", "SecondBrainDesktop")
         .env("Jwt__Audience", "SecondBrainDesktopUsers")
-        // CORS settings for Tauri webview
-        .env("Cors__AllowedOrigins__0", "tauri://localhost")
-        .env("Cors__AllowedOrigins__1", "https://tauri.localhost")
-        .env("Cors__AllowedOrigins__2", "http://localhost")
-        .env("Cors__AllowedOrigins__3", "http://127.0.0.1")
         .env("Cors__AllowLocalNetworkIps", "true");
 
+    // CORS origins: the Tauri webview's, then any configured ones
+    for (i, origin) in settings.effective_origins().iter().enumerate() {
+        command.env(format!("Cors__AllowedOrigins__{}", i), origin);
+    }
+
     // Add AI provider API keys from secrets
     if let Some(ref openai_key) = secrets.openai_api_key {
         command.env("AIProviders__OpenAI__ApiKey", openai_key);
@@ -753,10 +926,11 @@ async fn start_backend_internal(app: &AppHandle) -> Result<(), String> {
     // Add Git integration settings
     if let Some(ref git_roots) = secrets.git_allowed_repository_roots {
         // Support comma-separated list of paths
-        for (i, root) in git_roots.split(',').map(|s| s.trim()).enumerate() {
-            if !root.is_empty() {
-                command.env(format!("Git__AllowedRepositoryRoots__{}", i), root);
+        for (i, root) in git_roots::parse_roots(git_roots).iter().enumerate() {
+            if !std::path::Path::new(root).is_dir() {
+                tracing::warn!("Git repository root {:?} is not a folder", root);
             }
+            command.env(format!("Git__AllowedRepositoryRoots__{}", i), root);
         }
     }
     if let Some(require_user_scoped) = secrets.git_require_user_scoped_root {
@@ -794,69 +968,31 @@ async fn start_backend_internal(app: &AppHandle) -> Result<(), String> {
 
     command.stdout(Stdio::piped()).stderr(Stdio::piped());
 
-    let mut child = command
-        .spawn()
-        .map_err(|e| format!("Failed to spawn backend: {}", e))?;
-
-    // Capture stdout for logging
-    let stdout = child.stdout.take();
-    let stderr = child.stderr.take();
-    let app_handle = app.clone();
-
-    // Monitor stdout with panic handling (T2 fix)
-    if let Some(stdout) = stdout {
-        let app_clone = app_handle.clone();
-        std::thread::Builder::new()
-            .name("backend-stdout-monitor".to_string())
-            .spawn(move || {
-                let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
-                    let reader = BufReader::new(stdout);
-                    for line in reader.lines().map_while(Result::ok) {
-                        log::info!("[Backend] {}", line);
-                    }
-                }));
-
-                if let Err(e) = result {
-                    log::error!("[Backend stdout monitor] Thread panicked: {:?}", e);
-                }
+    let mut child = {
+        let _span = tracing::info_span!("spawn", program = %backend_path.display()).entered();
+        startup_deps::spawn(deps.spawner.as_ref(), &mut command)?
+    };
+    tracing::info!(pid = child.id(), "Backend process started");
 
-                let _ = app_clone.emit("backend-terminated", ());
-            })
-            .map_err(|e| format!("Failed to spawn stdout monitor thread: {}", e))?;
+    // Forward output to the log; readers end when the process exits
+    if let Some(stdout) = child.stdout.take() {
+        spawn_line_logger(stdout, "Backend", log::Level::Info);
     }
-
-    // Monitor stderr with panic handling (T2 fix)
-    if let Some(stderr) = stderr {
-        std::thread::Builder::new()
-            .name("backend-stderr-monitor".to_string())
-            .spawn(move || {
-                let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
-                    let reader = BufReader::new(stderr);
-                    for line in reader.lines().map_while(Result::ok) {
-                        log::warn!("[Backend] {}", line);
-                    }
-                }));
-
-                if let Err(e) = result {
-                    log::error!("[Backend stderr monitor] Thread panicked: {:?}", e);
-                }
-            })
-            .map_err(|e| format!("Failed to spawn stderr monitor thread: {}", e))?;
+    if let Some(stderr) = child.stderr.take() {
+        spawn_line_logger(stderr, "Backend", log::Level::Warn);
     }
 
-    // Wait for backend to be ready BEFORE storing the process (T1 fix)
-    // This prevents storing a stale process reference if startup fails
+    // Wait for backend to be ready BEFORE handing out the process (T1 fix)
+    // This prevents keeping a stale process reference if startup fails
     match wait_for_backend_ready(app, backend_port).await {
         Ok(()) => {
-            // Only store the process after confirming it's ready
-            *state.backend_process.lock().unwrap() = Some(child);
-            Ok(())
+            api_endpoint::backend_ready(app, backend_port);
+            Ok(child)
         }
         Err(e) => {
             // Startup failed - kill the process and don't store it
-            log::error!("Backend failed to become ready, killing process: {}", e);
-            let _ = child.kill();
-            let _ = child.wait();
+            tracing::error!("Backend failed to become ready, stopping process: {}", e);
+            services::terminate(&mut child, services::shutdown_grace(&state)).await;
             // Also try to kill any orphaned process on the port
             kill_process_on_port(backend_port);
             Err(e)
@@ -865,7 +1001,7 @@ async fn start_backend_internal(app: &AppHandle) -> Result<(), String> {
 }
 
 /// Find the backend executable path
-fn find_backend_path(app: &AppHandle) -> Result<std::path::PathBuf, String> {
+fn find_backend_path<R: Runtime>(app: &AppHandle<R>) -> Result<std::path::PathBuf, AppError> {
     // In development mode, look for the backend in resources/backend
     let possible_paths = if cfg!(debug_assertions) {
         let exe_path = std::env::current_exe().map_err(|e| e.to_string())?;
@@ -895,135 +1031,124 @@ fn find_backend_path(app: &AppHandle) -> Result<std::path::PathBuf, String> {
     };
 
     for path in &possible_paths {
-        log::info!("Checking backend path: {:?}", path);
+        tracing::info!("Checking backend path: {:?}", path);
         if path.exists() {
             return Ok(path.clone());
         }
     }
 
-    Err(format!(
+    Err(AppError::NotFound(format!(
         "Backend executable not found. Tried: {:?}",
         possible_paths
-    ))
+    )))
 }
 
-/// Health check configuration
-struct HealthCheckConfig {
-    /// Initial check interval (ms)
-    initial_interval_ms: u64,
-    /// Maximum check interval (ms) after backoff
-    max_interval_ms: u64,
-    /// Backoff multiplier
-    backoff_multiplier: f64,
-    /// Maximum total wait time (seconds)
-    max_wait_secs: u64,
+/// Startup timing from the PostgreSQL manager, or the defaults before it
+/// exists, with the health-check settings in effect
+pub(crate) fn startup_config<R: Runtime>(app: &AppHandle<R>) -> StartupConfig {
+    let state = app.state::<AppState>();
+    let config = state
+        .postgres_manager
+        .read()
+        .as_ref()
+        .map(|manager| manager.get_startup_config().clone())
+        .unwrap_or_default();
+    config::current_settings(&state).apply_to(config)
 }
 
-impl Default for HealthCheckConfig {
-    fn default() -> Self {
-        Self {
-            initial_interval_ms: 500,
-            max_interval_ms: 2000,
-            backoff_multiplier: 1.5,
-            max_wait_secs: 120, // Longer timeout for first start with migrations
-        }
-    }
-}
+#[tracing::instrument(skip(app))]
+async fn wait_for_backend_ready<R: Runtime>(app: &AppHandle<R>, port: u16) -> Result<(), AppError> {
+    let config = startup_config(app);
+    let health = app.state::<AppState>().startup_deps.read().health.clone();
 
-async fn wait_for_backend_ready(app: &AppHandle, port: u16) -> Result<(), String> {
-    let health_url = format!("http://localhost:{}/api/health", port);
-    let config = HealthCheckConfig::default();
-
-    // Create client with reasonable timeouts
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(5))
-        .connect_timeout(std::time::Duration::from_secs(2))
-        .build()
-        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
-
-    let start = std::time::Instant::now();
-    let max_duration = std::time::Duration::from_secs(config.max_wait_secs);
-    let mut current_interval = config.initial_interval_ms;
-
-    log::info!("Waiting for backend to be ready...");
-
-    while start.elapsed() < max_duration {
-        match client.get(&health_url).send().await {
-            Ok(response) if response.status().is_success() => {
-                log::info!("Backend is ready after {}ms!", start.elapsed().as_millis());
-                let state = app.state::<AppState>();
-                *state.is_backend_ready.lock().unwrap() = true;
-                return Ok(());
-            }
-            Ok(response) => {
-                log::debug!("Backend health check returned: {}", response.status());
-            }
-            Err(e) => {
-                log::debug!("Backend not ready yet: {}", e);
+    tracing::info!("Waiting for backend to be ready...");
+    let waited =
+        startup_deps::wait_for_health(health.as_ref(), port, &config, |attempt, elapsed| {
+            StartupEvent::BackendWaiting {
+                port,
+                attempt,
+                elapsed_ms: elapsed.as_millis() as u64,
             }
-        }
+            .emit(app)
+        })
+        .await?;
 
-        // Sleep with current interval
-        tokio::time::sleep(tokio::time::Duration::from_millis(current_interval)).await;
+    tracing::info!("Backend is ready after {}ms!", waited.as_millis());
+    *app.state::<AppState>().is_backend_ready.write() = true;
+    Ok(())
+}
 
-        // Increase interval with backoff (capped at max)
-        current_interval = ((current_interval as f64) * config.backoff_multiplier)
-            .min(config.max_interval_ms as f64) as u64;
-    }
+/// One line of service output, sent in batches as `service-log` events
+#[derive(serde::Serialize)]
+struct ServiceLogLine<'a> {
+    source: &'static str,
+    level: &'static str,
+    line: &'a str,
+}
 
-    Err(format!(
-        "Backend failed to start within {} seconds",
-        config.max_wait_secs
-    ))
+/// Log each line of a child process's output stream on the async runtime
+pub(crate) fn spawn_line_logger<R>(stream: R, source: &'static str, level: log::Level)
+where
+    R: AsyncRead + Unpin + Send + 'static,
+{
+    tauri::async_runtime::spawn(async move {
+        let _reader = resource_monitor::track_reader();
+        let mut lines = BufReader::new(stream).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            let line = secrets::redact_env_vars(&line);
+            log::log!(level, "[{}] {}", source, line);
+            events::emit_batched(
+                "service-log",
+                &ServiceLogLine {
+                    source,
+                    level: level.as_str(),
+                    line: &line,
+                },
+            );
+        }
+    });
 }
 
-/// Shutdown all services gracefully
-fn shutdown_services(app: &AppHandle) {
+/// Stop PostgreSQL, giving it up to `timeout` for a clean shutdown
+pub(crate) fn stop_postgres(app: &AppHandle, timeout: std::time::Duration) {
     let state = app.state::<AppState>();
-    let backend_port = *state.backend_port.lock().unwrap();
-
-    // Stop backend
-    if let Some(mut child) = state.backend_process.lock().unwrap().take() {
-        log::info!("Stopping backend process...");
-
-        // Try graceful kill first
-        let _ = child.kill();
-
-        // Wait with timeout
-        match child.try_wait() {
-            Ok(Some(_)) => {
-                log::info!("Backend process terminated");
-            }
-            Ok(None) => {
-                // Process still running, wait a bit more
-                std::thread::sleep(std::time::Duration::from_millis(500));
-                let _ = child.wait();
-            }
-            Err(e) => {
-                log::error!("Error waiting for backend: {}", e);
-            }
+    // Clone the Arc to avoid lifetime issues
+    let manager_opt = state.postgres_manager.read().clone();
+    if let Some(manager) = manager_opt {
+        if let Err(e) = manager.stop_within(timeout) {
+            tracing::warn!("Failed to stop PostgreSQL: {}", e);
         }
     }
+    *state.is_postgres_ready.write() = false;
+}
 
-    // Also kill any process still using the backend port (fallback cleanup)
-    kill_process_on_port(backend_port);
-
-    // Stop PostgreSQL - clone the Arc to avoid lifetime issues
-    let postgres_port = *state.postgres_port.lock().unwrap();
-    let manager_opt = state.postgres_manager.lock().unwrap().clone();
-    if let Some(manager) = manager_opt {
-        log::info!("Stopping PostgreSQL...");
-        let _ = manager.stop();
+/// Kill any process still using the service ports (fallback cleanup),
+/// except the mock backend served by this process
+pub(crate) fn sweep_ports(app: &AppHandle) {
+    let state = app.state::<AppState>();
+    if !cli::options(app).mock_backend {
+        kill_process_on_port(*state.backend_port.read());
     }
+    kill_process_on_port(*state.postgres_port.read());
+}
 
-    // Also kill any postgres processes on our port (fallback cleanup)
-    kill_process_on_port(postgres_port);
-
-    log::info!("All services stopped");
+/// Stop all services directly
+///
+/// Used when `services::ServiceManager` can't run its shutdown plan in
+/// time; port cleanup stops a backend whose handle isn't available.
+fn stop_services(app: &AppHandle) {
+    let state = app.state::<AppState>();
+    *state.is_backend_ready.write() = false;
+    if !cli::options(app).mock_backend {
+        kill_process_on_port(*state.backend_port.read());
+    }
+    stop_postgres(app, shutdown::POSTGRES_STOP);
+    kill_process_on_port(*state.postgres_port.read());
+    tracing::info!("All services stopped");
 }
 
 /// Open a folder in the system file manager
-fn open_folder(path: &std::path::Path) {
+pub(crate) fn open_folder(path: &std::path::Path) {
     #[cfg(target_os = "macos")]
     {
         let _ = std::process::Command::new("open").arg(path).spawn();
@@ -1050,7 +1175,7 @@ fn kill_process_on_port(port: u16) {
             let pids = String::from_utf8_lossy(&output.stdout);
             for pid in pids.lines() {
                 if let Ok(pid_num) = pid.trim().parse::<i32>() {
-                    log::info!("Killing orphaned process {} on port {}", pid_num, port);
+                    tracing::info!("Killing orphaned process {} on port {}", pid_num, port);
                     let _ = std::process::Command::new("kill")
                         .args(["-9", &pid_num.to_string()])
                         .output();
@@ -1060,83 +1185,6 @@ fn kill_process_on_port(port: u16) {
     }
 }
 
-fn create_tray_menu(app: &AppHandle) -> tauri::Result<Menu<tauri::Wry>> {
-    // Window controls
-    let show = MenuItem::with_id(app, "show", "Show Second Brain", true, None::<&str>)?;
-    let hide = MenuItem::with_id(app, "hide", "Hide", true, None::<&str>)?;
-
-    // Quick actions
-    let new_note = MenuItem::with_id(app, "tray_new_note", "New Note", true, None::<&str>)?;
-    let new_chat = MenuItem::with_id(app, "tray_new_chat", "New Chat", true, None::<&str>)?;
-
-    // Settings and info
-    let settings = MenuItem::with_id(app, "settings", "Settings...", true, None::<&str>)?;
-    let copy_api_url = MenuItem::with_id(app, "copy_api_url", "Copy API URL", true, None::<&str>)?;
-
-    // Service controls submenu
-    let restart_all = MenuItem::with_id(
-        app,
-        "restart_all",
-        "Restart All Services",
-        true,
-        None::<&str>,
-    )?;
-    let restart_backend_item = MenuItem::with_id(
-        app,
-        "restart_backend",
-        "Restart Backend Only",
-        true,
-        None::<&str>,
-    )?;
-    let restart_db_item = MenuItem::with_id(
-        app,
-        "restart_database",
-        "Restart Database Only",
-        true,
-        None::<&str>,
-    )?;
-
-    let services_submenu = Submenu::with_items(
-        app,
-        "Services",
-        true,
-        &[&restart_all, &restart_backend_item, &restart_db_item],
-    )?;
-
-    // Folders
-    let open_logs = MenuItem::with_id(app, "open_logs", "Open Logs Folder", true, None::<&str>)?;
-    let open_data = MenuItem::with_id(app, "open_data", "Open Data Folder", true, None::<&str>)?;
-
-    // Quit
-    let quit = MenuItem::with_id(app, "quit", "Quit Second Brain", true, None::<&str>)?;
-
-    // Separators
-    let separator1 = PredefinedMenuItem::separator(app)?;
-    let separator2 = PredefinedMenuItem::separator(app)?;
-    let separator3 = PredefinedMenuItem::separator(app)?;
-    let separator4 = PredefinedMenuItem::separator(app)?;
-
-    Menu::with_items(
-        app,
-        &[
-            &show,
-            &hide,
-            &separator1,
-            &new_note,
-            &new_chat,
-            &separator2,
-            &settings,
-            &copy_api_url,
-            &separator3,
-            &services_submenu,
-            &open_logs,
-            &open_data,
-            &separator4,
-            &quit,
-        ],
-    )
-}
-
 fn create_app_menu(app: &AppHandle) -> tauri::Result<Menu<tauri::Wry>> {
     // App menu (Second Brain)
     let about = MenuItem::with_id(app, "about", "About Second Brain", true, None::<&str>)?;
@@ -1147,6 +1195,13 @@ fn create_app_menu(app: &AppHandle) -> tauri::Result<Menu<tauri::Wry>> {
         true,
         Some("CmdOrCtrl+,"),
     )?;
+    let lock = MenuItem::with_id(
+        app,
+        "lock_app",
+        "Lock Second Brain",
+        true,
+        Some("CmdOrCtrl+Shift+L"),
+    )?;
     let separator = PredefinedMenuItem::separator(app)?;
     let hide = PredefinedMenuItem::hide(app, Some("Hide Second Brain"))?;
     let hide_others = PredefinedMenuItem::hide_others(app, Some("Hide Others"))?;
@@ -1161,6 +1216,7 @@ fn create_app_menu(app: &AppHandle) -> tauri::Result<Menu<tauri::Wry>> {
             &about,
             &separator,
             &preferences,
+            &lock,
             &separator,
             &hide,
             &hide_others,
@@ -1173,8 +1229,14 @@ fn create_app_menu(app: &AppHandle) -> tauri::Result<Menu<tauri::Wry>> {
     // File menu
     let new_note = MenuItem::with_id(app, "new_note", "New Note", true, Some("CmdOrCtrl+N"))?;
     let new_chat = MenuItem::with_id(app, "new_chat", "New Chat", true, Some("CmdOrCtrl+Shift+N"))?;
+    let open_demo = MenuItem::with_id(app, "open_demo", "Open Demo", true, None::<&str>)?;
 
-    let file_menu = Submenu::with_items(app, "File", true, &[&new_note, &new_chat])?;
+    let file_menu = Submenu::with_items(
+        app,
+        "File",
+        true,
+        &[&new_note, &new_chat, &separator, &open_demo],
+    )?;
 
     // Edit menu
     let undo = PredefinedMenuItem::undo(app, None)?;
@@ -1256,268 +1318,504 @@ fn create_app_menu(app: &AppHandle) -> tauri::Result<Menu<tauri::Wry>> {
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    let options = cli::LaunchOptions::from_env();
+    let profile = if options.demo {
+        profile::Profile::demo()
+    } else {
+        match profile::Profile::new(options.profile.as_deref()) {
+            Ok(profile) => profile,
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(2);
+            }
+        }
+    };
+    let mut context = tauri::generate_context!();
+    // A profile gets its own data directory and single-instance lock
+    let identifier = profile.identifier(&context.config().identifier);
+    context.config_mut().identifier = identifier;
+    for window in &mut context.config_mut().app.windows {
+        window.title = profile.title(&window.title);
+    }
+    if options.native_messaging {
+        std::process::exit(native_messaging::run_host(&context.config().identifier));
+    }
+    if let Some(query) = &options.query {
+        std::process::exit(query_cli::run(&context.config().identifier, query));
+    }
+    if options.headless {
+        // Windows from the config are created at startup unless removed here
+        context.config_mut().app.windows.clear();
+    }
+
     tauri::Builder::default()
+        .register_asynchronous_uri_scheme_protocol(asset_protocol::SCHEME, asset_protocol::handle)
         .plugin(
+            // Levels are checked per record so `set_log_level` applies at once
             tauri_plugin_log::Builder::default()
-                .level(log::LevelFilter::Warn)
+                .level(log::LevelFilter::Trace)
+                .filter(logging::enabled)
                 .build(),
         )
-        .plugin(tauri_plugin_shell::init())
-        .plugin(tauri_plugin_fs::init())
-        .plugin(tauri_plugin_dialog::init())
+        // Plugins that reach files, the clipboard or programs stop while locked
+        .plugin(app_lock::guard_plugin(tauri_plugin_shell::init()))
+        .plugin(app_lock::guard_plugin(tauri_plugin_fs::init()))
+        .plugin(app_lock::guard_plugin(tauri_plugin_dialog::init()))
         .plugin(tauri_plugin_notification::init())
-        .plugin(tauri_plugin_clipboard_manager::init())
+        .plugin(app_lock::guard_plugin(
+            tauri_plugin_clipboard_manager::init(),
+        ))
         .plugin(tauri_plugin_process::init())
         .plugin(tauri_plugin_os::init())
-        .plugin(tauri_plugin_single_instance::init(|app, _args, _cwd| {
+        .plugin(tauri_plugin_single_instance::init(|app, args, cwd| {
             // Focus the main window when a second instance is attempted
             if let Some(window) = app.get_webview_window("main") {
                 let _ = window.set_focus();
             }
+            launch::forward(app, &args, &cwd);
         }))
+        .manage(options)
+        .manage(profile)
         .manage(AppState::default())
+        .manage(scheduler::Scheduler::new())
+        .manage(email_watcher::EmailWatcher::default())
+        .manage(feeds::FeedManager::default())
+        .manage(obsidian::ObsidianSync::default())
+        .manage(peer_sync::PeerSync::default())
+        .manage(note_history::NoteHistory::default())
+        .manage(trash::TrashManager::default())
+        .manage(uploads::UploadManager::default())
+        .manage(streams::StreamRegistry::default())
+        .manage(logs::LogCursors::default())
+        .manage(jobs::JobRegistry::default())
+        .manage(launch::LaunchQueue::default())
+        .manage(app_lock::AppLock::default())
+        .manage(webview_watchdog::WebviewWatchdog::default())
+        .manage(print::PrintJobs::default())
+        .manage(presentation::Presentation::default())
+        .manage(window_session::WindowSession::default())
+        .manage(idle::IdleMonitor::default())
+        .manage(zoom::Zoom::default())
+        .manage(clock::ClockMonitor::default())
+        .manage(github::GitHubTokenMonitor::default())
+        .manage(disk_space::DiskMonitor::default())
+        .manage(shell_health::ShellHealth::default())
+        .manage(usage::UsageTracker::default())
+        .manage(focus::FocusManager::default())
         .setup(|app| {
             let app_handle = app.handle().clone();
-
-            // Create and set the app menu
-            let menu = create_app_menu(&app_handle)?;
-            app.set_menu(menu)?;
-
-            // Handle app menu events (About, Preferences, etc.)
-            app.on_menu_event(move |app, event| {
-                match event.id.as_ref() {
-                    "about" => {
-                        // Emit event to show custom About dialog in frontend
-                        let _ = app.emit("show-about-dialog", ());
-                    }
-                    "preferences" => {
-                        // Show the app and navigate to settings
-                        if let Some(window) = app.get_webview_window("main") {
-                            let _ = window.show();
-                            let _ = window.set_focus();
-                            let _ = app.emit("navigate-to-settings", ());
-                        }
-                    }
-                    "new_note" => {
-                        // Emit event to create new note
-                        if let Some(window) = app.get_webview_window("main") {
-                            let _ = window.show();
-                            let _ = window.set_focus();
-                            let _ = app.emit("create-new-note", ());
-                        }
-                    }
-                    "new_chat" => {
-                        // Emit event to create new chat
-                        if let Some(window) = app.get_webview_window("main") {
-                            let _ = window.show();
-                            let _ = window.set_focus();
-                            let _ = app.emit("create-new-chat", ());
-                        }
-                    }
-                    "reload" => {
-                        if let Some(window) = app.get_webview_window("main") {
-                            let _ = window.eval("window.location.reload()");
-                        }
-                    }
-                    "documentation" => {
-                        // Emit event to open documentation URL
-                        let _ = app.emit("open-documentation", ());
-                    }
-                    "report_issue" => {
-                        // Emit event to open issue reporting URL
-                        let _ = app.emit("open-report-issue", ());
-                    }
-                    _ => {}
-                }
-            });
-
-            // Create system tray with template icon for macOS menu bar
-            let tray_menu = create_tray_menu(&app_handle)?;
-
-            // Load the tray icon from resources (template icon for macOS)
-            let tray_icon = {
-                let icon_path = app
-                    .path()
-                    .resource_dir()
-                    .map(|p| p.join("icons/tray/tray-icon.png"))
-                    .ok()
-                    .and_then(|p| if p.exists() { Some(p) } else { None });
-
-                match icon_path {
-                    Some(path) => {
-                        log::info!("Loading tray icon from: {:?}", path);
-                        tauri::image::Image::from_path(&path).ok()
-                    }
-                    None => {
-                        log::info!("Using default window icon for tray");
-                        None
-                    }
-                }
-            };
-
-            let mut tray_builder = TrayIconBuilder::new();
-
-            // Use template icon if available, otherwise fall back to window icon
-            if let Some(icon) = tray_icon {
-                tray_builder = tray_builder.icon(icon);
-            } else {
-                tray_builder = tray_builder.icon(app.default_window_icon().unwrap().clone());
+            logging::init(&app_handle);
+            profile::lock(&app_handle)?;
+            // Finish state updates a crash interrupted before anything reads them
+            if let Ok(app_data_dir) = app_handle.path().app_data_dir() {
+                journal::recover(&app_data_dir);
             }
-
-            let _tray = tray_builder
-                .icon_as_template(true) // Important for macOS menu bar
-                .menu(&tray_menu)
-                .show_menu_on_left_click(true) // Show menu on left-click (standard macOS behavior)
-                .on_menu_event(move |app, event| {
+            api_endpoint::init(&app_handle);
+            startup_trace::init(&app_handle);
+            app_lock::start(&app_handle);
+            screen_privacy::start(&app_handle);
+            webview_watchdog::start(&app_handle);
+            http::init(&app_handle)?;
+            // Health checks share the backend client and its connect timeout
+            *app_handle.state::<AppState>().startup_deps.write() =
+                StartupDeps::system(http::backend(&app_handle));
+            events::init(&app_handle);
+
+            let headless = cli::options(&app_handle).headless;
+            if !headless {
+                // The main window starts hidden; the splash covers startup
+                splash::open(&app_handle);
+
+                // Create and set the app menu
+                let menu = create_app_menu(&app_handle)?;
+                app.set_menu(menu)?;
+
+                // Handle app menu events (About, Preferences, etc.)
+                app.on_menu_event(move |app, event| {
                     match event.id.as_ref() {
-                        "show" => {
+                        "about" => {
+                            // Emit event to show custom About dialog in frontend
+                            let _ = app.emit("show-about-dialog", ());
+                        }
+                        "preferences" => {
+                            // Show the app and navigate to settings
                             if let Some(window) = app.get_webview_window("main") {
                                 let _ = window.show();
                                 let _ = window.set_focus();
+                                let _ = app.emit("navigate-to-settings", ());
                             }
                         }
-                        "hide" => {
-                            if let Some(window) = app.get_webview_window("main") {
-                                let _ = window.hide();
-                            }
-                        }
-                        "tray_new_note" => {
+                        "new_note" => {
+                            // Emit event to create new note
                             if let Some(window) = app.get_webview_window("main") {
                                 let _ = window.show();
                                 let _ = window.set_focus();
                                 let _ = app.emit("create-new-note", ());
                             }
                         }
-                        "tray_new_chat" => {
+                        "new_chat" => {
+                            // Emit event to create new chat
                             if let Some(window) = app.get_webview_window("main") {
                                 let _ = window.show();
                                 let _ = window.set_focus();
                                 let _ = app.emit("create-new-chat", ());
                             }
                         }
-                        "settings" => {
-                            // Show the app and navigate to settings
-                            if let Some(window) = app.get_webview_window("main") {
-                                let _ = window.show();
-                                let _ = window.set_focus();
-                                // Emit event to navigate to settings
-                                let _ = app.emit("navigate-to-settings", ());
+                        "lock_app" => app_lock::lock_now(app),
+                        "open_demo" => {
+                            if let Err(e) = demo::launch_instance() {
+                                tracing::warn!("Failed to start demo: {}", e);
                             }
                         }
-                        "copy_api_url" => {
-                            // Copy API URL to clipboard
-                            let state = app.state::<AppState>();
-                            let port = *state.backend_port.lock().unwrap();
-                            let url = format!("http://localhost:{}/api", port);
-                            let _ = app.emit("copy-to-clipboard", url);
+                        "zoom_in" | "zoom_out" | "actual_size" => {
+                            zoom::on_menu(app, event.id.as_ref());
                         }
-                        "restart_all" => {
-                            let app = app.clone();
-                            tauri::async_runtime::spawn(async move {
-                                if let Err(e) = restart_database(app).await {
-                                    log::error!("Failed to restart all services: {}", e);
-                                }
-                            });
-                        }
-                        "restart_backend" => {
-                            let app = app.clone();
-                            tauri::async_runtime::spawn(async move {
-                                if let Err(e) = restart_backend(app).await {
-                                    log::error!("Failed to restart backend: {}", e);
-                                }
-                            });
-                        }
-                        "restart_database" => {
-                            let app = app.clone();
-                            tauri::async_runtime::spawn(async move {
-                                if let Err(e) = restart_database(app).await {
-                                    log::error!("Failed to restart database: {}", e);
-                                }
-                            });
-                        }
-                        "open_logs" => {
-                            // Open the logs folder
-                            if let Ok(app_data_dir) = app.path().app_data_dir() {
-                                let log_path = app_data_dir.join("logs");
-                                let _ = std::fs::create_dir_all(&log_path);
-                                open_folder(&log_path);
+                        "reload" => {
+                            if let Some(window) = app.get_webview_window("main") {
+                                let _ = window.eval("window.location.reload()");
                             }
                         }
-                        "open_data" => {
-                            // Open the data folder
-                            if let Ok(app_data_dir) = app.path().app_data_dir() {
-                                open_folder(&app_data_dir);
-                            }
+                        "documentation" => {
+                            // Emit event to open documentation URL
+                            let _ = app.emit("open-documentation", ());
                         }
-                        "quit" => {
-                            // Graceful shutdown
-                            shutdown_services(app);
-                            app.exit(0);
+                        "report_issue" => {
+                            // Emit event to open issue reporting URL
+                            let _ = app.emit("open-report-issue", ());
                         }
                         _ => {}
                     }
-                })
-                .build(app)?;
+                });
+            }
 
             // Start services (PostgreSQL + Backend) on app launch
-            let app_handle_for_services = app_handle.clone();
-            tauri::async_runtime::spawn(async move {
-                if let Err(e) = start_services_internal(&app_handle_for_services).await {
-                    log::error!("Failed to start services: {}", e);
-                }
-            });
+            let services = ServiceManager::spawn(&app_handle);
+            services.dispatch(ServiceCommand::StartAll);
+            app.manage(services);
+            health::start(&app_handle);
+            if headless {
+                headless::start(&app_handle);
+            } else {
+                tray::start(&app_handle);
+                accessibility::start(&app_handle);
+                zoom::start(&app_handle);
+                window_session::start(&app_handle);
+            }
+            resource_monitor::start(&app_handle);
+            disk_space::start(&app_handle);
+            shell_health::start(&app_handle);
+            clock::start(&app_handle);
+            github::start(&app_handle);
+            control_api::start(&app_handle);
+            event_bridge::start(&app_handle);
+            tunnel::start(&app_handle);
+            write_queue::start(&app_handle);
+            reminders::start(&app_handle);
+            login_item::start(&app_handle);
+            relaunch::start(&app_handle);
+            launch::start(&app_handle);
+            demo::start(&app_handle);
+
+            // Start background job scheduler, holding heavy jobs until idle
+            idle::start(&app_handle);
+            scheduler::start(app_handle.clone());
+            feeds::start(&app_handle);
+            backup::start(&app_handle);
+            wal_archive::start(&app_handle);
+            obsidian::start(&app_handle);
+            peer_sync::start(&app_handle);
+            note_history::start(&app_handle);
+            trash::start(&app_handle);
+            snapshots::start(&app_handle);
+
+            // Start email watcher if configured
+            email_watcher::restart(&app_handle);
 
             Ok(())
         })
+        .on_page_load(zoom::on_page_load)
         .on_window_event(|window, event| {
             match event {
                 tauri::WindowEvent::CloseRequested { api, .. } => {
-                    // Hide window instead of closing (macOS behavior)
+                    // Hide the main window instead of closing (macOS behavior)
                     #[cfg(target_os = "macos")]
-                    {
+                    if window.label() == "main" {
                         let _ = window.hide();
                         api.prevent_close();
                     }
                 }
+                tauri::WindowEvent::Focused(true) => {
+                    screen_privacy::on_focus(window.app_handle(), window.label());
+                    if window.label() == "main" {
+                        tray::clear_badge(window.app_handle());
+                    }
+                }
+                tauri::WindowEvent::ThemeChanged(_) if window.label() == "main" => {
+                    tray::on_theme_changed(window.app_handle());
+                }
                 tauri::WindowEvent::Destroyed => {
                     // Window was destroyed, cleanup services
-                    shutdown_services(window.app_handle());
+                    let app = window.app_handle();
+                    screen_privacy::on_destroyed(app, window.label());
+                    webview_watchdog::on_destroyed(app, window.label());
+                    presentation::on_destroyed(app, window.label());
+                    window_session::on_destroyed(app, window.label());
+                    // Other windows, like print previews, close on their own;
+                    // a hung main window being rebuilt isn't the app closing
+                    if window.label() != "main" || webview_watchdog::take_recreating(app) {
+                        return;
+                    }
+                    // Windows still open now are reopened next launch
+                    window_session::freeze(app);
+                    if let Some(services) = app.try_state::<ServiceManager>() {
+                        services.shutdown_blocking(app);
+                    }
                 }
                 _ => {}
             }
         })
-        .invoke_handler(tauri::generate_handler![
-            get_backend_url,
-            is_backend_ready,
-            get_database_status,
-            restart_backend,
-            restart_database,
-            get_secrets,
-            save_secrets_cmd,
-            get_secrets_path,
-            get_startup_metrics,
-            get_port_config,
-            check_port_available,
-            copy_to_clipboard,
-            set_dock_badge,
-            get_diagnostic_report,
-            get_recent_logs,
-            commands::open_data_directory,
-            commands::open_log_directory,
-            commands::get_app_version,
-        ])
-        .build(tauri::generate_context!())
+        .invoke_handler(logging::with_command_span(app_lock::guard(
+            tauri::generate_handler![
+                get_backend_url,
+                is_backend_ready,
+                get_database_status,
+                restart_backend,
+                rotate_jwt_secret,
+                restart_database,
+                services::get_service_state,
+                health::get_backend_health,
+                write_queue::list_queued_writes,
+                write_queue::replay_queued_writes,
+                write_queue::discard_queued_write,
+                batch::invoke_batch,
+                streams::ack_stream,
+                streams::cancel_stream,
+                streams::stream_file,
+                http::get_http_settings,
+                http::set_http_settings,
+                get_secrets,
+                save_secrets_cmd,
+                get_secrets_path,
+                config_recovery::recover_config,
+                config::get_settings,
+                config::update_settings,
+                config::set_allowed_origins,
+                login_item::get_launch_at_login,
+                login_item::set_launch_at_login,
+                relaunch::relaunch_app,
+                get_startup_metrics,
+                get_startup_stats,
+                get_port_config,
+                check_port_available,
+                copy_to_clipboard,
+                set_dock_badge,
+                get_diagnostic_report,
+                diagnostic_bundle::export_diagnostics,
+                startup_trace::export_startup_trace,
+                get_storage_breakdown,
+                logs::get_recent_logs,
+                logs::reset_log_cursor,
+                logging::set_log_level,
+                logging::get_log_settings,
+                tray::set_tray_recent_notes,
+                tray::get_tray_settings,
+                tray::set_tray_settings,
+                resource_monitor::get_resource_usage,
+                control_api::get_control_api,
+                control_api::set_control_api_settings,
+                control_api::rotate_control_api_token,
+                event_bridge::get_event_bridge_settings,
+                event_bridge::set_event_bridge_settings,
+                native_messaging::get_native_messaging_hosts,
+                native_messaging::install_native_messaging_host,
+                native_messaging::uninstall_native_messaging_host,
+                tunnel::get_tunnel_settings,
+                tunnel::set_tunnel_settings,
+                tunnel::start_tunnel,
+                tunnel::stop_tunnel,
+                tunnel::get_tunnel_status,
+                profile::get_profile,
+                launch::take_launch_requests,
+                app_lock::get_app_lock,
+                app_lock::set_app_lock_settings,
+                app_lock::set_app_lock_passphrase,
+                app_lock::lock_app,
+                app_lock::unlock_app,
+                app_lock::unlock_app_with_biometrics,
+                app_lock::report_app_activity,
+                screen_privacy::get_screen_privacy,
+                screen_privacy::set_screen_privacy,
+                screen_privacy::set_window_screen_privacy,
+                webview_watchdog::webview_heartbeat,
+                webview_watchdog::reload_webview,
+                webview_watchdog::recreate_window,
+                print::export_note_pdf,
+                print::print_note,
+                print::print_page_ready,
+                quicklook::generate_export_previews,
+                presentation::enter_presentation_mode,
+                presentation::exit_presentation_mode,
+                presentation::get_presentation_state,
+                window_session::open_popout_window,
+                window_session::open_floating_chat,
+                window_session::report_window_route,
+                window_session::get_session_settings,
+                window_session::set_session_settings,
+                window_session::clear_session_state,
+                zoom::set_zoom,
+                zoom::get_zoom,
+                jobs::start_job,
+                jobs::cancel_job,
+                jobs::list_jobs,
+                commands::open_data_directory,
+                commands::open_log_directory,
+                commands::get_app_version,
+                tokens::count_tokens,
+                proxy::proxy_request,
+                proxy::clear_ai_cache,
+                proxy::get_ai_cache_stats,
+                proxy::set_backend_auth,
+                scheduler::get_schedule_settings,
+                idle::get_idle_state,
+                clock::get_clock_status,
+                clock::check_clock,
+                github::check_github_token,
+                git_roots::validate_git_roots,
+                git_roots::list_repositories,
+                pinecone::check_pinecone_index,
+                pinecone::create_pinecone_index,
+                disk_space::get_disk_space,
+                disk_space::get_disk_space_settings,
+                disk_space::set_disk_space_settings,
+                uninstall::uninstall_cleanup,
+                shell_health::get_shell_health,
+                usage::get_usage_stats,
+                usage::get_usage_settings,
+                usage::set_usage_settings,
+                notifications::get_notification_rules,
+                notifications::set_notification_rules,
+                focus::start_focus_session,
+                focus::end_focus_session,
+                focus::get_focus_session,
+                focus::list_focus_sessions,
+                focus::get_focus_settings,
+                focus::set_focus_settings,
+                reminders::schedule_reminder,
+                reminders::list_reminders,
+                reminders::snooze_reminder,
+                reminders::complete_reminder,
+                reminders::cancel_reminder,
+                faults::inject_fault,
+                scheduler::set_schedule_settings,
+                scheduler::list_scheduled_jobs,
+                scheduler::run_job_now,
+                calendar::get_calendar_permission,
+                calendar::request_calendar_access,
+                calendar::get_events,
+                calendar::push_events_to_daily_note,
+                contacts::get_contacts_status,
+                contacts::set_contacts_enabled,
+                contacts::lookup_contact,
+                voice::check_voice_setup,
+                voice::list_audio_devices,
+                voice::set_audio_device,
+                apple_import::preview_apple_import,
+                apple_import::import_from_apple,
+                email_watcher::get_email_watcher_config,
+                email_watcher::set_email_watcher_config,
+                email_watcher::get_email_watcher_status,
+                feeds::add_feed,
+                feeds::list_feeds,
+                feeds::refresh_feed,
+                feeds::remove_feed,
+                backup::get_encrypted_backup_settings,
+                backup::set_encrypted_backup_settings,
+                backup::create_encrypted_backup,
+                backup::list_encrypted_backups,
+                backup::verify_encrypted_backup,
+                backup::preview_restore,
+                backup::restore_encrypted_backup,
+                backup::export_backup_key,
+                backup::create_backup,
+                backup::list_backups,
+                backup::delete_backup,
+                backup::restore_backup,
+                backup::get_backup_schedule,
+                backup::set_backup_schedule,
+                wal_archive::get_wal_archive_settings,
+                wal_archive::set_wal_archive_settings,
+                wal_archive::get_wal_archive_status,
+                wal_archive::restore_to_timestamp,
+                sanitize::export_sanitized_db,
+                snapshots::get_snapshot_settings,
+                snapshots::set_snapshot_settings,
+                snapshots::list_snapshots,
+                snapshots::take_snapshot,
+                snapshots::restore_snapshot,
+                cloud_backup::get_cloud_backup_settings,
+                cloud_backup::set_cloud_backup_settings,
+                cloud_backup::test_backup_target,
+                cloud_backup::list_cloud_backups,
+                cloud_backup::upload_backup_to_cloud,
+                obsidian::get_obsidian_settings,
+                obsidian::set_obsidian_settings,
+                obsidian::sync_obsidian_now,
+                obsidian::get_obsidian_sync_status,
+                obsidian::list_sync_conflicts,
+                obsidian::resolve_conflict,
+                peer_sync::get_peer_sync_settings,
+                peer_sync::set_peer_sync_settings,
+                peer_sync::get_pairing_info,
+                peer_sync::start_pairing,
+                peer_sync::respond_to_pairing,
+                peer_sync::list_peers,
+                peer_sync::remove_peer,
+                peer_sync::sync_with_peer,
+                peer_sync::get_peer_sync_status,
+                note_history::get_note_history_settings,
+                note_history::set_note_history_settings,
+                note_history::commit_note_history_now,
+                note_history::get_note_history_status,
+                note_history::get_note_history,
+                note_history::get_note_version,
+                note_history::restore_note_version,
+                attachments::store_attachment,
+                attachments::get_attachment_path,
+                attachments::reclaim_space,
+                attachments::audit_attachments,
+                attachments::repair_attachments,
+                trash::get_trash_settings,
+                trash::set_trash_settings,
+                trash::get_trash_stats,
+                trash::empty_trash_now,
+                archives::archive_old_data,
+                archives::list_archives,
+                archives::extract_archive,
+                export::export_markdown,
+                uploads::start_upload,
+                uploads::resume_upload,
+                uploads::cancel_upload,
+                uploads::list_uploads,
+            ],
+        )))
+        .build(context)
         .expect("error while building tauri application")
         .run(|app_handle, event| {
             match event {
                 tauri::RunEvent::ExitRequested { code, .. } => {
                     // Always allow exit but ensure cleanup happens
-                    log::info!("Exit requested with code: {:?}", code);
-                    shutdown_services(app_handle);
+                    tracing::info!("Exit requested with code: {:?}", code);
+                    window_session::freeze(app_handle);
+                    if let Some(services) = app_handle.try_state::<ServiceManager>() {
+                        services.shutdown_blocking(app_handle);
+                    }
                 }
                 tauri::RunEvent::Exit => {
-                    log::info!("Application exiting, cleaning up services...");
-                    shutdown_services(app_handle);
+                    tracing::info!("Application exiting, cleaning up services...");
+                    if let Some(services) = app_handle.try_state::<ServiceManager>() {
+                        services.shutdown_blocking(app_handle);
+                    }
+                    demo::cleanup(app_handle);
                 }
                 _ => {}
             }
@@ -1771,12 +2069,11 @@ mod tests {
     fn test_app_state_default() {
         let state = AppState::default();
 
-        assert!(state.backend_process.lock().unwrap().is_none());
-        assert_eq!(*state.backend_port.lock().unwrap(), 5001);
-        assert_eq!(*state.postgres_port.lock().unwrap(), 5433);
-        assert!(!*state.is_backend_ready.lock().unwrap());
-        assert!(!*state.is_postgres_ready.lock().unwrap());
-        assert!(state.postgres_manager.lock().unwrap().is_none());
+        assert_eq!(*state.backend_port.read(), 5001);
+        assert_eq!(*state.postgres_port.read(), 5433);
+        assert!(!*state.is_backend_ready.read());
+        assert!(!*state.is_postgres_ready.read());
+        assert!(state.postgres_manager.read().is_none());
     }
 
     #[test]
@@ -1791,7 +2088,7 @@ mod tests {
         for i in 0..10 {
             let state_clone = Arc::clone(&state);
             let handle = thread::spawn(move || {
-                let mut port = state_clone.backend_port.lock().unwrap();
+                let mut port = state_clone.backend_port.write();
                 *port = 5001 + i;
             });
             handles.push(handle);
@@ -1802,7 +2099,7 @@ mod tests {
         }
 
         // State should be accessible after concurrent modifications
-        let port = state.backend_port.lock().unwrap();
+        let port = state.backend_port.read();
         assert!(*port >= 5001 && *port <= 5010);
     }
 
@@ -1810,22 +2107,76 @@ mod tests {
     fn test_app_state_backend_ready_flag() {
         let state = AppState::default();
 
-        assert!(!*state.is_backend_ready.lock().unwrap());
+        assert!(!*state.is_backend_ready.read());
 
-        *state.is_backend_ready.lock().unwrap() = true;
+        *state.is_backend_ready.write() = true;
 
-        assert!(*state.is_backend_ready.lock().unwrap());
+        assert!(*state.is_backend_ready.read());
     }
 
     #[test]
     fn test_app_state_postgres_ready_flag() {
         let state = AppState::default();
 
-        assert!(!*state.is_postgres_ready.lock().unwrap());
+        assert!(!*state.is_postgres_ready.read());
+
+        *state.is_postgres_ready.write() = true;
 
-        *state.is_postgres_ready.lock().unwrap() = true;
+        assert!(*state.is_postgres_ready.read());
+    }
+
+    // ============================================================
+    // Service Startup Tests
+    // ============================================================
 
-        assert!(*state.is_postgres_ready.lock().unwrap());
+    fn mock_app(deps: StartupDeps) -> tauri::App<tauri::test::MockRuntime> {
+        let state = AppState::default();
+        *state.startup_deps.write() = deps;
+        tauri::test::mock_builder()
+            .manage(state)
+            .manage(disk_space::DiskMonitor::default())
+            .build(tauri::test::mock_context(tauri::test::noop_assets()))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_startup_fails_on_port_conflict() {
+        use startup_deps::fakes::{BusyPorts, FailingSpawner, FakeHealth};
+
+        let app = mock_app(StartupDeps {
+            ports: Arc::new(BusyPorts),
+            health: Arc::new(FakeHealth::new(0)),
+            spawner: Arc::new(FailingSpawner),
+        });
+        let err = start_services_internal(app.handle()).await.unwrap_err();
+        assert!(matches!(err, AppError::Conflict(_)), "{:?}", err);
+
+        let state = app.state::<AppState>();
+        assert!(!*state.is_postgres_ready.read());
+        assert!(!*state.is_backend_ready.read());
+        let metrics = state.startup_metrics.lock();
+        assert!(!metrics.success);
+        assert!(metrics
+            .error
+            .as_deref()
+            .is_some_and(|e| e.contains("in use")));
+    }
+
+    #[tokio::test]
+    async fn test_backend_waits_for_slow_health() {
+        use startup_deps::fakes::{FailingSpawner, FakeHealth, FakePorts};
+
+        let health = Arc::new(FakeHealth::new(2));
+        let app = mock_app(StartupDeps {
+            ports: Arc::new(FakePorts {
+                busy: Default::default(),
+            }),
+            health: health.clone(),
+            spawner: Arc::new(FailingSpawner),
+        });
+        wait_for_backend_ready(app.handle(), 5001).await.unwrap();
+        assert_eq!(health.checks(), 3);
+        assert!(*app.state::<AppState>().is_backend_ready.read());
     }
 
     // ============================================================
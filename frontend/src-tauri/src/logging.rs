@@ -0,0 +1,287 @@
+//! Runtime log level control.
+//!
+//! This module provides:
+//! - A global level with per-module overrides, checked by the log plugin's
+//!   filter for every record so changes apply without a restart
+//! - Persistence of those levels in log-settings.json
+//! - A span around every command dispatch
+//! - Commands to read and change the levels
+//!
+//! Application code logs through `tracing`. With no tracing subscriber
+//! installed, its events and spans are forwarded to `log`, so they end up in
+//! the same log files as plugin and service output.
+
+use log::LevelFilter;
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use tauri::ipc::Invoke;
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::config::{load_json, save_json_atomic};
+use crate::error::AppError;
+
+/// Level used when no settings have been saved
+const DEFAULT_LEVEL: LevelFilter = LevelFilter::Warn;
+
+/// Module level that removes an override
+const INHERIT: &str = "inherit";
+
+static FILTER: Lazy<RwLock<LogFilter>> = Lazy::new(|| RwLock::new(LogFilter::default()));
+
+/// Persisted log levels
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LogSettings {
+    /// Level for everything without an override
+    pub level: String,
+    /// Overrides keyed by module path, e.g. `health` or `reqwest`
+    pub modules: BTreeMap<String, String>,
+}
+
+impl Default for LogSettings {
+    fn default() -> Self {
+        Self {
+            level: DEFAULT_LEVEL.as_str().to_ascii_lowercase(),
+            modules: BTreeMap::new(),
+        }
+    }
+}
+
+/// Parsed levels, checked for each record
+#[derive(Debug, Clone, PartialEq)]
+struct LogFilter {
+    level: LevelFilter,
+    /// The override matching the longest part of a target wins
+    modules: Vec<(String, LevelFilter)>,
+}
+
+impl Default for LogFilter {
+    fn default() -> Self {
+        Self {
+            level: DEFAULT_LEVEL,
+            modules: Vec::new(),
+        }
+    }
+}
+
+fn parse_level(level: &str) -> Result<LevelFilter, AppError> {
+    LevelFilter::from_str(level.trim()).map_err(|_| {
+        AppError::InvalidInput(format!(
+            "Unknown log level '{}'; expected off, error, warn, info, debug or trace",
+            level
+        ))
+    })
+}
+
+/// Length of the prefix `module` matches in `target`, if it names `target`
+/// or one of its parents
+///
+/// Modules of this crate can be named without the crate prefix.
+fn match_len(module: &str, target: &str) -> Option<usize> {
+    let within = |prefix: &str| {
+        target == prefix
+            || target
+                .strip_prefix(prefix)
+                .is_some_and(|rest| rest.starts_with("::"))
+    };
+    let qualified = format!("{}::{}", env!("CARGO_CRATE_NAME"), module);
+    if within(&qualified) {
+        Some(qualified.len())
+    } else if within(module) {
+        Some(module.len())
+    } else {
+        None
+    }
+}
+
+impl LogFilter {
+    fn from_settings(settings: &LogSettings) -> Result<Self, AppError> {
+        let modules = settings
+            .modules
+            .iter()
+            .map(|(module, level)| Ok((module.clone(), parse_level(level)?)))
+            .collect::<Result<Vec<_>, AppError>>()?;
+        Ok(Self {
+            level: parse_level(&settings.level)?,
+            modules,
+        })
+    }
+
+    fn level_for(&self, target: &str) -> LevelFilter {
+        self.modules
+            .iter()
+            .filter_map(|(module, level)| Some((match_len(module, target)?, *level)))
+            .max_by_key(|(len, _)| *len)
+            .map_or(self.level, |(_, level)| level)
+    }
+
+    /// Most verbose level anything is enabled at
+    fn max_level(&self) -> LevelFilter {
+        self.modules
+            .iter()
+            .map(|(_, level)| *level)
+            .fold(self.level, std::cmp::max)
+    }
+}
+
+/// Log plugin filter; reads the current levels for every record
+pub fn enabled(metadata: &log::Metadata) -> bool {
+    metadata.level() <= FILTER.read().level_for(metadata.target())
+}
+
+fn settings_path(app_data_dir: &Path) -> PathBuf {
+    app_data_dir.join("log-settings.json")
+}
+
+fn apply(settings: &LogSettings) -> Result<(), AppError> {
+    let filter = LogFilter::from_settings(settings)?;
+    // Skip formatting records nothing will accept
+    log::set_max_level(filter.max_level());
+    *FILTER.write() = filter;
+    Ok(())
+}
+
+/// Apply saved levels; called once the app data directory is known
+pub fn init(app: &AppHandle) {
+    let settings = match app.path().app_data_dir() {
+        Ok(app_data_dir) => load_json(&settings_path(&app_data_dir)).unwrap_or_default(),
+        Err(_) => LogSettings::default(),
+    };
+    if let Err(e) = apply(&settings) {
+        tracing::warn!("Ignoring saved log levels: {}", e);
+        let _ = apply(&LogSettings::default());
+    }
+}
+
+/// Wrap a command handler so each dispatch runs in a `command` span
+pub fn with_command_span<R: Runtime>(
+    handler: impl Fn(Invoke<R>) -> bool + Send + Sync + 'static,
+) -> impl Fn(Invoke<R>) -> bool + Send + Sync + 'static {
+    move |invoke| {
+        let _span = tracing::debug_span!("command", name = invoke.message.command()).entered();
+        handler(invoke)
+    }
+}
+
+/// Change the global log level, or one module's level with `module`
+///
+/// A module level of `inherit` removes its override. The change applies
+/// immediately and is saved for later launches.
+#[tauri::command]
+pub async fn set_log_level(
+    app: AppHandle,
+    level: String,
+    module: Option<String>,
+) -> Result<LogSettings, AppError> {
+    let path = settings_path(&app.path().app_data_dir()?);
+    let mut settings: LogSettings = load_json(&path).unwrap_or_default();
+
+    match module.as_deref().map(str::trim) {
+        Some("") => return Err(AppError::InvalidInput("Module name is empty".to_string())),
+        Some(module) if level.trim().eq_ignore_ascii_case(INHERIT) => {
+            settings.modules.remove(module);
+        }
+        Some(module) => {
+            parse_level(&level)?;
+            settings
+                .modules
+                .insert(module.to_string(), level.trim().to_ascii_lowercase());
+        }
+        None => {
+            parse_level(&level)?;
+            settings.level = level.trim().to_ascii_lowercase();
+        }
+    }
+
+    apply(&settings)?;
+    save_json_atomic(&path, &settings)?;
+    tracing::info!(
+        level = %level,
+        module = module.as_deref().unwrap_or("*"),
+        "Log level changed"
+    );
+    Ok(settings)
+}
+
+/// Get the current log levels
+#[tauri::command]
+pub async fn get_log_settings(app: AppHandle) -> Result<LogSettings, AppError> {
+    let path = settings_path(&app.path().app_data_dir()?);
+    Ok(load_json(&path).unwrap_or_default())
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn settings(level: &str, modules: &[(&str, &str)]) -> LogSettings {
+        LogSettings {
+            level: level.to_string(),
+            modules: modules
+                .iter()
+                .map(|(m, l)| (m.to_string(), l.to_string()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_match_len() {
+        assert_eq!(match_len("reqwest", "reqwest"), Some(7));
+        assert_eq!(match_len("reqwest", "reqwest::connect"), Some(7));
+        assert_eq!(match_len("reqwest", "reqwest_middleware"), None);
+        assert_eq!(match_len("health", "app_lib::health"), Some(15));
+        assert_eq!(match_len("app_lib::health", "app_lib::health"), Some(15));
+        assert_eq!(match_len("health", "app_lib::health_check"), None);
+    }
+
+    #[test]
+    fn test_most_specific_override_wins() {
+        let filter = LogFilter::from_settings(&settings(
+            "warn",
+            &[("app_lib", "info"), ("health", "trace"), ("reqwest", "off")],
+        ))
+        .unwrap();
+        assert_eq!(filter.level_for("app_lib::health"), LevelFilter::Trace);
+        assert_eq!(filter.level_for("app_lib::proxy"), LevelFilter::Info);
+        assert_eq!(filter.level_for("reqwest::connect"), LevelFilter::Off);
+        assert_eq!(filter.level_for("hyper"), LevelFilter::Warn);
+        assert_eq!(filter.max_level(), LevelFilter::Trace);
+    }
+
+    #[test]
+    fn test_invalid_level_is_rejected() {
+        let err = LogFilter::from_settings(&settings("loud", &[])).unwrap_err();
+        assert_eq!(err.kind(), "invalid_input");
+        assert!(LogFilter::from_settings(&settings("DEBUG", &[("x", "verbose")])).is_err());
+        assert_eq!(
+            LogFilter::from_settings(&settings(" Debug ", &[]))
+                .unwrap()
+                .level,
+            LevelFilter::Debug
+        );
+    }
+
+    #[test]
+    fn test_settings_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = settings_path(temp_dir.path());
+        assert!(load_json::<LogSettings>(&path).is_none());
+
+        let saved = settings("info", &[("scheduler", "debug")]);
+        save_json_atomic(&path, &saved).unwrap();
+        assert_eq!(load_json::<LogSettings>(&path), Some(saved));
+
+        // Missing fields fall back to defaults
+        std::fs::write(&path, r#"{"modules":{}}"#).unwrap();
+        assert_eq!(load_json::<LogSettings>(&path).unwrap().level, "warn");
+    }
+}
@@ -0,0 +1,282 @@
+//! Launching the app at login.
+//!
+//! This module provides:
+//! - A login item registered the platform's own way: a LaunchAgent on
+//!   macOS, an XDG autostart entry on Linux and a `Run` registry value on
+//!   Windows, under the names `uninstall` removes
+//! - The choice kept in the service settings, with the item written again
+//!   at launch so it follows the app when it's moved or updated
+//! - Commands to read and change it, mirrored by a tray checkbox
+//!
+//! The item starts the app with the profile it was turned on from. Demo
+//! profiles never register one.
+
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager};
+
+use crate::config::{self, ServiceConfig};
+use crate::error::AppError;
+
+/// Program and arguments the login item runs
+fn program_args(app: &AppHandle) -> Result<Vec<String>, AppError> {
+    let exe = std::env::current_exe()?;
+    let mut args = vec![exe.to_string_lossy().to_string()];
+    if let Some(name) = crate::profile::current(app).name {
+        args.push("--profile".to_string());
+        args.push(name);
+    }
+    Ok(args)
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// LaunchAgent plist running `args` at login
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn launch_agent_plist(label: &str, args: &[String]) -> String {
+    let args: String = args
+        .iter()
+        .map(|arg| format!("    <string>{}</string>\n", xml_escape(arg)))
+        .collect();
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+  <key>Label</key>
+  <string>{}</string>
+  <key>ProgramArguments</key>
+  <array>
+{}  </array>
+  <key>RunAtLoad</key>
+  <true/>
+</dict>
+</plist>
+"#,
+        xml_escape(label),
+        args
+    )
+}
+
+/// Quote an argument for a desktop entry's `Exec` key
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn desktop_quote(arg: &str) -> String {
+    if arg
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || "-_./=".contains(c))
+    {
+        return arg.to_string();
+    }
+    let escaped = arg
+        .replace('\\', "\\\\\\\\")
+        .replace('"', "\\\\\"")
+        .replace('`', "\\\\`")
+        .replace('$', "\\\\$")
+        .replace('%', "%%");
+    format!("\"{}\"", escaped)
+}
+
+/// XDG autostart entry running `args` at login
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn desktop_entry(name: &str, args: &[String]) -> String {
+    let exec: Vec<String> = args.iter().map(|arg| desktop_quote(arg)).collect();
+    format!(
+        "[Desktop Entry]\nType=Application\nName={}\nExec={}\nTerminal=false\nX-GNOME-Autostart-enabled=true\n",
+        name,
+        exec.join(" ")
+    )
+}
+
+/// Where the login item lives on macOS and Linux
+#[cfg(not(windows))]
+fn item_path(app: &AppHandle) -> Result<PathBuf, AppError> {
+    let home = app.path().home_dir()?;
+    Ok(if cfg!(target_os = "macos") {
+        home.join("Library/LaunchAgents")
+            .join(format!("{}.plist", app.config().identifier))
+    } else {
+        home.join(".config/autostart")
+            .join(format!("{}.desktop", app.package_info().name))
+    })
+}
+
+/// Write the login item for the running executable
+#[cfg(not(windows))]
+fn register(app: &AppHandle) -> Result<(), AppError> {
+    let args = program_args(app)?;
+    let contents = if cfg!(target_os = "macos") {
+        launch_agent_plist(&app.config().identifier, &args)
+    } else {
+        desktop_entry(&app.package_info().name, &args)
+    };
+    let path = item_path(app)?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(&path, contents)?;
+    Ok(())
+}
+
+/// Remove the login item; a missing one isn't an error
+#[cfg(not(windows))]
+fn unregister(app: &AppHandle) -> Result<(), AppError> {
+    match std::fs::remove_file(item_path(app)?) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(windows)]
+const RUN_KEY: &str = r"HKCU\Software\Microsoft\Windows\CurrentVersion\Run";
+
+#[cfg(windows)]
+fn run_reg(args: &[&str]) -> Result<(), AppError> {
+    let output = std::process::Command::new("reg").args(args).output()?;
+    if !output.status.success() {
+        return Err(AppError::Permission(format!(
+            "reg {} failed: {}",
+            args[0],
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(())
+}
+
+/// Write the login item for the running executable
+#[cfg(windows)]
+fn register(app: &AppHandle) -> Result<(), AppError> {
+    let command = program_args(app)?
+        .iter()
+        .map(|arg| format!("\"{}\"", arg))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let name = app.package_info().name.clone();
+    run_reg(&[
+        "add", RUN_KEY, "/v", &name, "/t", "REG_SZ", "/d", &command, "/f",
+    ])
+}
+
+/// Remove the login item; a missing one isn't an error
+#[cfg(windows)]
+fn unregister(app: &AppHandle) -> Result<(), AppError> {
+    let name = app.package_info().name.clone();
+    // `reg delete` fails when the value doesn't exist
+    if run_reg(&["query", RUN_KEY, "/v", &name]).is_err() {
+        return Ok(());
+    }
+    run_reg(&["delete", RUN_KEY, "/v", &name, "/f"])
+}
+
+/// Register or remove the login item and update the tray checkbox, without
+/// saving the choice
+pub(crate) fn apply(app: &AppHandle, enabled: bool) -> Result<(), AppError> {
+    if enabled && crate::profile::current(app).demo {
+        return Err(AppError::InvalidInput(
+            "Demo profiles can't launch at login".to_string(),
+        ));
+    }
+    if enabled {
+        register(app)?;
+    } else {
+        unregister(app)?;
+    }
+    crate::tray::set_launch_at_login(app, enabled);
+    tracing::info!(
+        "Launch at login {}",
+        if enabled { "enabled" } else { "disabled" }
+    );
+    Ok(())
+}
+
+/// Turn launching at login on or off and save the choice
+pub fn set(app: &AppHandle, enabled: bool) -> Result<(), AppError> {
+    apply(app, enabled)?;
+    let app_data_dir = app.path().app_data_dir()?;
+    let mut settings = ServiceConfig::load(&app_data_dir).settings;
+    settings.launch_at_login = enabled;
+    config::save_settings(app, settings)
+}
+
+/// Whether launching at login is turned on, read from disk so it's right
+/// before the services have loaded their config
+pub fn is_enabled(app: &AppHandle) -> bool {
+    app.path()
+        .app_data_dir()
+        .map(|dir| ServiceConfig::load(&dir).settings.launch_at_login)
+        .unwrap_or(false)
+}
+
+/// Rewrite the login item if it's turned on, so it points at this executable
+pub fn start(app: &AppHandle) {
+    if !is_enabled(app) || crate::profile::current(app).demo {
+        return;
+    }
+    let app = app.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        if let Err(e) = register(&app) {
+            tracing::warn!("Failed to refresh the login item: {}", e);
+        }
+    });
+}
+
+// ============================================================
+// Commands
+// ============================================================
+
+/// Whether the app starts when the user logs in
+#[tauri::command]
+pub async fn get_launch_at_login(app: AppHandle) -> Result<bool, AppError> {
+    Ok(is_enabled(&app))
+}
+
+/// Start the app when the user logs in, or stop doing so
+#[tauri::command]
+pub async fn set_launch_at_login(app: AppHandle, enabled: bool) -> Result<bool, AppError> {
+    tauri::async_runtime::spawn_blocking(move || set(&app, enabled).map(|()| enabled)).await?
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_launch_agent_plist() {
+        let plist = launch_agent_plist(
+            "com.secondbrain.desktop",
+            &[
+                "/Applications/Second Brain.app/Contents/MacOS/second-brain".to_string(),
+                "--profile".to_string(),
+                "R&D".to_string(),
+            ],
+        );
+        assert!(plist.contains("<string>com.secondbrain.desktop</string>"));
+        assert!(plist.contains(
+            "<string>/Applications/Second Brain.app/Contents/MacOS/second-brain</string>"
+        ));
+        assert!(plist.contains("<string>R&amp;D</string>"));
+        assert!(plist.contains("<key>RunAtLoad</key>\n  <true/>"));
+    }
+
+    #[test]
+    fn test_desktop_entry_quotes_exec() {
+        let entry = desktop_entry(
+            "Second Brain",
+            &[
+                "/opt/Second Brain/second-brain".to_string(),
+                "--profile".to_string(),
+                "work".to_string(),
+            ],
+        );
+        assert!(entry.starts_with("[Desktop Entry]\n"));
+        assert!(entry.contains("Exec=\"/opt/Second Brain/second-brain\" --profile work\n"));
+        assert_eq!(desktop_quote("100%"), "\"100%%\"");
+    }
+}
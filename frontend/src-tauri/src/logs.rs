@@ -0,0 +1,359 @@
+//! Incremental log reading.
+//!
+//! This module provides:
+//! - Tail reads that scan the newest log file backwards from the end, so
+//!   only the requested lines are read however large the file has grown
+//! - A cursor per consumer (the log viewer, diagnostics) so each poll only
+//!   sends the bytes written since that consumer's last read
+//!
+//! A cursor is reset, and the consumer gets a fresh tail, when the newest
+//! log file changes, shrinks, or has grown by more than `MAX_INCREMENT`.
+
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use tauri::ipc::Channel;
+use tauri::{AppHandle, Manager};
+
+use crate::error::AppError;
+use crate::streams::{text_chunks, ByteStream, StreamSummary};
+
+/// Block size for backwards scans
+const TAIL_BLOCK: u64 = 64 * 1024;
+
+/// Most new bytes sent for one poll before falling back to a tail
+const MAX_INCREMENT: u64 = 4 * 1024 * 1024;
+
+/// Most consumers tracked at once; the oldest cursor is dropped beyond this
+const MAX_CURSORS: usize = 32;
+
+/// Where a consumer last stopped reading
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct LogCursor {
+    path: PathBuf,
+    offset: u64,
+    /// Order of last use, for evicting the oldest cursor
+    used: u64,
+}
+
+/// Log cursors kept in Tauri state
+#[derive(Default)]
+pub struct LogCursors {
+    cursors: Mutex<HashMap<String, LogCursor>>,
+    clock: Mutex<u64>,
+}
+
+impl LogCursors {
+    fn get(&self, consumer: &str) -> Option<LogCursor> {
+        self.cursors.lock().get(consumer).cloned()
+    }
+
+    fn set(&self, consumer: &str, path: PathBuf, offset: u64) {
+        let used = {
+            let mut clock = self.clock.lock();
+            *clock += 1;
+            *clock
+        };
+        let mut cursors = self.cursors.lock();
+        cursors.insert(consumer.to_string(), LogCursor { path, offset, used });
+        if cursors.len() > MAX_CURSORS {
+            if let Some(oldest) = cursors
+                .iter()
+                .min_by_key(|(_, c)| c.used)
+                .map(|(k, _)| k.clone())
+            {
+                cursors.remove(&oldest);
+            }
+        }
+    }
+
+    fn remove(&self, consumer: &str) {
+        self.cursors.lock().remove(consumer);
+    }
+}
+
+/// Result of one log read
+#[derive(Debug, Clone, Serialize)]
+pub struct LogRead {
+    #[serde(flatten)]
+    pub stream: StreamSummary,
+    /// Log file that was read, if any exists
+    pub path: Option<String>,
+    /// Offset the next read for this consumer starts from
+    pub offset: u64,
+    /// True when the bytes are a fresh tail rather than new lines to append
+    pub reset: bool,
+}
+
+/// Newest .log file in the app's log folder
+pub fn latest_log_file(log_dir: &Path) -> Option<PathBuf> {
+    let mut log_files: Vec<_> = std::fs::read_dir(log_dir)
+        .ok()?
+        .filter_map(|e| e.ok())
+        .filter(|e| {
+            e.path()
+                .extension()
+                .map(|ext| ext == "log")
+                .unwrap_or(false)
+        })
+        .collect();
+
+    // Sort by modification time (newest first)
+    log_files.sort_by(|a, b| {
+        let a_time = a.metadata().and_then(|m| m.modified()).ok();
+        let b_time = b.metadata().and_then(|m| m.modified()).ok();
+        b_time.cmp(&a_time)
+    });
+    log_files.first().map(|entry| entry.path())
+}
+
+/// Read the last `max_lines` complete lines, scanning back from the end
+///
+/// Returns the bytes and the offset just past the last complete line; a
+/// partial line still being written is left for the next read.
+pub fn read_tail(path: &Path, max_lines: usize) -> std::io::Result<(Vec<u8>, u64)> {
+    let mut file = File::open(path)?;
+    let mut pos = file.metadata()?.len();
+    let mut buffer: Vec<u8> = Vec::new();
+
+    // Finding where the first wanted line starts takes one newline more
+    while pos > 0 && count_newlines(&buffer) <= max_lines {
+        let start = pos.saturating_sub(TAIL_BLOCK);
+        let mut block = vec![0u8; (pos - start) as usize];
+        file.seek(SeekFrom::Start(start))?;
+        file.read_exact(&mut block)?;
+        block.append(&mut buffer);
+        buffer = block;
+        pos = start;
+    }
+
+    let end = match buffer.iter().rposition(|&b| b == b'\n') {
+        Some(i) => i + 1,
+        None => return Ok((Vec::new(), pos)),
+    };
+    let start = buffer[..end]
+        .iter()
+        .enumerate()
+        .rev()
+        .filter(|(_, &b)| b == b'\n')
+        .nth(max_lines)
+        .map(|(i, _)| i + 1)
+        .unwrap_or(0);
+    Ok((buffer[start..end].to_vec(), pos + end as u64))
+}
+
+fn count_newlines(buffer: &[u8]) -> usize {
+    buffer.iter().filter(|&&b| b == b'\n').count()
+}
+
+/// Read complete lines written after `offset`
+///
+/// Returns None when the file shrank (it was truncated or replaced) or grew
+/// by more than `MAX_INCREMENT`, in which case a fresh tail is better.
+pub fn read_from(path: &Path, offset: u64) -> std::io::Result<Option<(Vec<u8>, u64)>> {
+    let mut file = File::open(path)?;
+    let len = file.metadata()?.len();
+    if len < offset || len - offset > MAX_INCREMENT {
+        return Ok(None);
+    }
+    let mut buffer = Vec::with_capacity((len - offset) as usize);
+    file.seek(SeekFrom::Start(offset))?;
+    file.take(len - offset).read_to_end(&mut buffer)?;
+    let end = buffer
+        .iter()
+        .rposition(|&b| b == b'\n')
+        .map_or(0, |i| i + 1);
+    buffer.truncate(end);
+    Ok(Some((buffer, offset + end as u64)))
+}
+
+/// What a consumer should receive for one poll
+#[derive(Debug, PartialEq, Eq)]
+struct Increment {
+    path: Option<PathBuf>,
+    bytes: Vec<u8>,
+    offset: u64,
+    reset: bool,
+}
+
+fn next_increment(
+    log_dir: &Path,
+    cursor: Option<LogCursor>,
+    max_lines: usize,
+) -> std::io::Result<Increment> {
+    let Some(path) = latest_log_file(log_dir) else {
+        return Ok(Increment {
+            path: None,
+            bytes: Vec::new(),
+            offset: 0,
+            reset: true,
+        });
+    };
+    if let Some(cursor) = cursor.filter(|c| c.path == path) {
+        if let Some((bytes, offset)) = read_from(&path, cursor.offset)? {
+            return Ok(Increment {
+                path: Some(path),
+                bytes,
+                offset,
+                reset: false,
+            });
+        }
+    }
+    let (bytes, offset) = read_tail(&path, max_lines)?;
+    Ok(Increment {
+        path: Some(path),
+        bytes,
+        offset,
+        reset: true,
+    })
+}
+
+/// Stream recent application logs to the webview as UTF-8 chunks
+///
+/// With a `consumer` name, the first call sends the last `max_lines` lines
+/// and later calls send only lines written since that consumer's last read.
+#[tauri::command]
+pub async fn get_recent_logs(
+    app: AppHandle,
+    max_lines: Option<usize>,
+    consumer: Option<String>,
+    on_chunk: Channel,
+) -> Result<LogRead, AppError> {
+    let log_dir = app.path().app_data_dir()?.join("logs");
+    let max_lines = max_lines.unwrap_or(100);
+    let cursor = consumer
+        .as_deref()
+        .and_then(|c| app.state::<LogCursors>().get(c));
+
+    let increment =
+        tokio::task::spawn_blocking(move || next_increment(&log_dir, cursor, max_lines)).await??;
+
+    let mut stream = ByteStream::open(&app, on_chunk);
+    for chunk in text_chunks(&String::from_utf8_lossy(&increment.bytes)) {
+        stream.send(chunk).await?;
+    }
+    let summary = stream.finish()?;
+
+    if let (Some(consumer), Some(path)) = (consumer.as_deref(), increment.path.as_ref()) {
+        app.state::<LogCursors>()
+            .set(consumer, path.clone(), increment.offset);
+    }
+    Ok(LogRead {
+        stream: summary,
+        path: increment.path.map(|p| p.to_string_lossy().to_string()),
+        offset: increment.offset,
+        reset: increment.reset,
+    })
+}
+
+/// Forget a consumer's cursor so its next read starts with a fresh tail
+#[tauri::command]
+pub async fn reset_log_cursor(app: AppHandle, consumer: String) -> Result<(), AppError> {
+    app.state::<LogCursors>().remove(&consumer);
+    Ok(())
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    fn numbered_lines(range: std::ops::Range<usize>) -> String {
+        range.map(|i| format!("line {}\n", i)).collect()
+    }
+
+    #[test]
+    fn test_read_tail() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("app.log");
+        // Large enough to need several backwards blocks
+        let content = numbered_lines(0..20_000);
+        std::fs::write(&path, format!("{}partial", content)).unwrap();
+
+        let (bytes, offset) = read_tail(&path, 3).unwrap();
+        assert_eq!(bytes, b"line 19997\nline 19998\nline 19999\n");
+        assert_eq!(offset, content.len() as u64);
+
+        let (bytes, _) = read_tail(&path, 50_000).unwrap();
+        assert_eq!(bytes, content.as_bytes());
+
+        std::fs::write(&path, "no newline yet").unwrap();
+        assert_eq!(read_tail(&path, 10).unwrap(), (Vec::new(), 0));
+    }
+
+    #[test]
+    fn test_read_from_returns_only_new_complete_lines() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("app.log");
+        std::fs::write(&path, "a\nb\n").unwrap();
+        let (_, offset) = read_tail(&path, 10).unwrap();
+
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap();
+        file.write_all(b"c\nd").unwrap();
+        let (bytes, offset) = read_from(&path, offset).unwrap().unwrap();
+        assert_eq!(bytes, b"c\n");
+
+        file.write_all(b"\n").unwrap();
+        let (bytes, offset) = read_from(&path, offset).unwrap().unwrap();
+        assert_eq!(bytes, b"d\n");
+        assert_eq!(read_from(&path, offset).unwrap().unwrap().0, b"");
+
+        // Truncated files fall back to a tail
+        std::fs::write(&path, "x\n").unwrap();
+        assert!(read_from(&path, offset).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_next_increment_resets_on_new_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let missing = next_increment(temp_dir.path(), None, 10).unwrap();
+        assert!(missing.path.is_none() && missing.bytes.is_empty());
+
+        let path = temp_dir.path().join("app.log");
+        std::fs::write(&path, "one\ntwo\n").unwrap();
+        let first = next_increment(temp_dir.path(), None, 1).unwrap();
+        assert_eq!(first.bytes, b"two\n");
+        assert!(first.reset);
+
+        let cursor = LogCursor {
+            path: path.clone(),
+            offset: first.offset,
+            used: 0,
+        };
+        let idle = next_increment(temp_dir.path(), Some(cursor.clone()), 1).unwrap();
+        assert!(idle.bytes.is_empty() && !idle.reset);
+
+        let rotated = LogCursor {
+            path: temp_dir.path().join("old.log"),
+            ..cursor
+        };
+        assert!(
+            next_increment(temp_dir.path(), Some(rotated), 1)
+                .unwrap()
+                .reset
+        );
+    }
+
+    #[test]
+    fn test_cursor_eviction() {
+        let cursors = LogCursors::default();
+        for i in 0..=MAX_CURSORS {
+            cursors.set(&i.to_string(), PathBuf::from("app.log"), i as u64);
+        }
+        assert!(cursors.get("0").is_none());
+        assert_eq!(cursors.get("1").unwrap().offset, 1);
+        cursors.remove("1");
+        assert!(cursors.get("1").is_none());
+    }
+}
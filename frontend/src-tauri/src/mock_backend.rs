@@ -0,0 +1,631 @@
+//! Fixture API served in place of the .NET backend.
+//!
+//! This module provides:
+//! - An in-process HTTP server on the backend port, started by
+//!   `services::ServiceManager` when the app is launched with
+//!   `--mock-backend`, so the frontend can be worked on without the .NET SDK
+//!   or PostgreSQL
+//! - A few sample notes and conversations, kept in memory and reset when the
+//!   mock restarts
+//!
+//! Routes (under `/api`, all JSON):
+//! - `GET /health`, `/health/ready`, `/health/live`
+//! - `POST /auth/login`, `/auth/register`, `/auth/logout`, which accept any
+//!   credentials
+//! - `GET /notes`, `GET /notes/paged`, `POST /notes`,
+//!   `GET|PUT|DELETE /notes/{id}`
+//! - `GET /chat/conversations`, `GET /chat/conversations/{id}`
+//!
+//! Anything else is answered with 404, so the frontend shows its usual error
+//! states for features the mock doesn't cover.
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Manager};
+use tokio::io::AsyncWriteExt;
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::asset_protocol::percent_decode;
+use crate::control_api::{read_request, HttpRequest};
+use crate::error::AppError;
+use crate::startup::StartupEvent;
+use crate::AppState;
+
+/// Timeout for reading a request or writing a response
+const IO_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// User every mock sign-in resolves to
+const MOCK_USER_ID: &str = "mock-user";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct MockNote {
+    id: String,
+    title: String,
+    content: String,
+    created_at: String,
+    updated_at: String,
+    tags: Vec<String>,
+    is_archived: bool,
+    folder: Option<String>,
+    user_id: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct MockMessage {
+    id: String,
+    role: String,
+    content: String,
+    timestamp: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct MockConversation {
+    id: String,
+    title: String,
+    provider: String,
+    model: String,
+    rag_enabled: bool,
+    agent_enabled: bool,
+    image_generation_enabled: bool,
+    messages: Vec<MockMessage>,
+    user_id: String,
+    created_at: String,
+    updated_at: String,
+}
+
+/// Body of note creates and updates
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct NoteInput {
+    title: Option<String>,
+    content: Option<String>,
+    tags: Option<Vec<String>>,
+    is_archived: Option<bool>,
+    folder: Option<String>,
+}
+
+/// Body of sign-in and registration
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct AuthInput {
+    email: Option<String>,
+    display_name: Option<String>,
+}
+
+/// The mock's data
+struct Fixtures {
+    notes: Vec<MockNote>,
+    conversations: Vec<MockConversation>,
+    next_id: u64,
+}
+
+impl Fixtures {
+    fn sample() -> Self {
+        let now = chrono::Utc::now().to_rfc3339();
+        let note =
+            |id: &str, title: &str, content: &str, tags: &[&str], folder: Option<&str>| MockNote {
+                id: id.to_string(),
+                title: title.to_string(),
+                content: content.to_string(),
+                created_at: now.clone(),
+                updated_at: now.clone(),
+                tags: tags.iter().map(|tag| tag.to_string()).collect(),
+                is_archived: false,
+                folder: folder.map(str::to_string),
+                user_id: MOCK_USER_ID.to_string(),
+            };
+        let notes = vec![
+            note(
+                "mock-note-1",
+                "Welcome to the mock backend",
+                "# Welcome\n\nThese notes are served by the desktop shell. Changes last until the app quits.",
+                &["mock"],
+                None,
+            ),
+            note(
+                "mock-note-2",
+                "Shopping list",
+                "- Coffee\n- Bread\n- Batteries",
+                &["personal", "todo"],
+                Some("Personal"),
+            ),
+            note(
+                "mock-note-3",
+                "Meeting notes",
+                "## Agenda\n\n1. Roadmap\n2. Hiring\n\n---\n\n## Actions\n\n- Send the summary",
+                &["work"],
+                Some("Work"),
+            ),
+        ];
+        let conversations = vec![MockConversation {
+            id: "mock-conversation-1".to_string(),
+            title: "Sample conversation".to_string(),
+            provider: "OpenAI".to_string(),
+            model: "mock".to_string(),
+            rag_enabled: false,
+            agent_enabled: false,
+            image_generation_enabled: false,
+            messages: vec![
+                MockMessage {
+                    id: "mock-message-1".to_string(),
+                    role: "user".to_string(),
+                    content: "What is in my notes?".to_string(),
+                    timestamp: now.clone(),
+                },
+                MockMessage {
+                    id: "mock-message-2".to_string(),
+                    role: "assistant".to_string(),
+                    content: "This is a canned reply from the mock backend.".to_string(),
+                    timestamp: now.clone(),
+                },
+            ],
+            user_id: MOCK_USER_ID.to_string(),
+            created_at: now.clone(),
+            updated_at: now,
+        }];
+        Self {
+            notes,
+            conversations,
+            next_id: 1,
+        }
+    }
+
+    fn note(&self, id: &str) -> Result<&MockNote, AppError> {
+        self.notes
+            .iter()
+            .find(|note| note.id == id)
+            .ok_or_else(|| AppError::NotFound(format!("Note {} not found", id)))
+    }
+
+    fn note_mut(&mut self, id: &str) -> Result<&mut MockNote, AppError> {
+        self.notes
+            .iter_mut()
+            .find(|note| note.id == id)
+            .ok_or_else(|| AppError::NotFound(format!("Note {} not found", id)))
+    }
+
+    fn create_note(&mut self, input: NoteInput) -> MockNote {
+        let now = chrono::Utc::now().to_rfc3339();
+        let note = MockNote {
+            id: format!("mock-new-{}", self.next_id),
+            title: input.title.unwrap_or_else(|| "Untitled".to_string()),
+            content: input.content.unwrap_or_default(),
+            created_at: now.clone(),
+            updated_at: now,
+            tags: input.tags.unwrap_or_default(),
+            is_archived: input.is_archived.unwrap_or(false),
+            folder: input.folder.filter(|folder| !folder.is_empty()),
+            user_id: MOCK_USER_ID.to_string(),
+        };
+        self.next_id += 1;
+        self.notes.insert(0, note.clone());
+        note
+    }
+
+    fn update_note(&mut self, id: &str, input: NoteInput) -> Result<MockNote, AppError> {
+        let note = self.note_mut(id)?;
+        if let Some(title) = input.title {
+            note.title = title;
+        }
+        if let Some(content) = input.content {
+            note.content = content;
+        }
+        if let Some(tags) = input.tags {
+            note.tags = tags;
+        }
+        if let Some(is_archived) = input.is_archived {
+            note.is_archived = is_archived;
+        }
+        if let Some(folder) = input.folder {
+            note.folder = Some(folder).filter(|folder| !folder.is_empty());
+        }
+        note.updated_at = chrono::Utc::now().to_rfc3339();
+        Ok(note.clone())
+    }
+
+    fn delete_note(&mut self, id: &str) -> Result<(), AppError> {
+        let before = self.notes.len();
+        self.notes.retain(|note| note.id != id);
+        if self.notes.len() == before {
+            return Err(AppError::NotFound(format!("Note {} not found", id)));
+        }
+        Ok(())
+    }
+
+    /// A page of notes, filtered like the backend's paged endpoint
+    fn notes_page(&self, query: &str) -> serde_json::Value {
+        let param = |name: &str| {
+            query
+                .split('&')
+                .find_map(|pair| percent_decode(pair.strip_prefix(name)?.strip_prefix('=')?))
+        };
+        let page = param("page")
+            .and_then(|page| page.parse::<usize>().ok())
+            .unwrap_or(1)
+            .max(1);
+        let page_size = param("pageSize")
+            .and_then(|size| size.parse::<usize>().ok())
+            .unwrap_or(20)
+            .clamp(1, 100);
+        let include_archived = param("includeArchived").is_some_and(|value| value == "true");
+        let folder = param("folder");
+        let search = param("search").map(|search| search.to_lowercase());
+
+        let matching: Vec<&MockNote> = self
+            .notes
+            .iter()
+            .filter(|note| include_archived || !note.is_archived)
+            .filter(|note| match folder.as_deref() {
+                None => true,
+                // An empty folder means notes without one
+                Some("") => note.folder.is_none(),
+                Some(folder) => note.folder.as_deref() == Some(folder),
+            })
+            .filter(|note| {
+                search.as_ref().map_or(true, |search| {
+                    note.title.to_lowercase().contains(search)
+                        || note.content.to_lowercase().contains(search)
+                })
+            })
+            .collect();
+        let total = matching.len();
+        let total_pages = total.div_ceil(page_size);
+        let items: Vec<&MockNote> = matching
+            .into_iter()
+            .skip((page - 1) * page_size)
+            .take(page_size)
+            .collect();
+        serde_json::json!({
+            "items": items,
+            "totalCount": total,
+            "page": page,
+            "pageSize": page_size,
+            "hasNextPage": page < total_pages,
+            "hasPreviousPage": page > 1,
+            "totalPages": total_pages,
+        })
+    }
+}
+
+fn parse_body<T: serde::de::DeserializeOwned + Default>(body: &[u8]) -> Result<T, AppError> {
+    if body.iter().all(u8::is_ascii_whitespace) {
+        return Ok(T::default());
+    }
+    Ok(serde_json::from_slice(body)?)
+}
+
+fn auth_response(input: AuthInput, is_new_user: bool) -> serde_json::Value {
+    let email = input
+        .email
+        .filter(|email| !email.trim().is_empty())
+        .unwrap_or_else(|| "dev@example.com".to_string());
+    serde_json::json!({
+        "userId": MOCK_USER_ID,
+        "email": email,
+        "displayName": input.display_name.unwrap_or_else(|| "Mock User".to_string()),
+        "token": "mock-token",
+        "isNewUser": is_new_user,
+    })
+}
+
+/// Answer one request; `None` is an empty 204
+fn route(
+    fixtures: &Mutex<Fixtures>,
+    request: &HttpRequest,
+) -> Result<(u16, Option<serde_json::Value>), AppError> {
+    let (path, query) = request
+        .path
+        .split_once('?')
+        .unwrap_or((request.path.as_str(), ""));
+    let Some(path) = path.strip_prefix("/api") else {
+        return Err(AppError::NotFound(format!("No route for {}", path)));
+    };
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    let json = |value| Ok((200, Some(value)));
+
+    match (request.method.as_str(), segments.as_slice()) {
+        ("GET", ["health"] | ["health", "ready" | "live"]) => {
+            json(serde_json::json!({ "status": "Healthy", "mock": true }))
+        }
+        ("POST", ["auth", "login"]) => json(auth_response(parse_body(&request.body)?, false)),
+        ("POST", ["auth", "register"]) => json(auth_response(parse_body(&request.body)?, true)),
+        ("POST", ["auth", "logout"]) => Ok((204, None)),
+        ("GET", ["notes"]) => json(serde_json::to_value(&fixtures.lock().notes)?),
+        ("GET", ["notes", "paged"]) => json(fixtures.lock().notes_page(query)),
+        ("POST", ["notes"]) => {
+            let note = fixtures.lock().create_note(parse_body(&request.body)?);
+            Ok((201, Some(serde_json::to_value(note)?)))
+        }
+        ("GET", ["notes", id]) => json(serde_json::to_value(fixtures.lock().note(id)?)?),
+        ("PUT", ["notes", id]) => {
+            let note = fixtures
+                .lock()
+                .update_note(id, parse_body(&request.body)?)?;
+            json(serde_json::to_value(note)?)
+        }
+        ("DELETE", ["notes", id]) => {
+            fixtures.lock().delete_note(id)?;
+            Ok((204, None))
+        }
+        ("GET", ["chat", "conversations"]) => {
+            json(serde_json::to_value(&fixtures.lock().conversations)?)
+        }
+        ("GET", ["chat", "conversations", id]) => {
+            let fixtures = fixtures.lock();
+            let conversation = fixtures
+                .conversations
+                .iter()
+                .find(|conversation| conversation.id == *id)
+                .ok_or_else(|| AppError::NotFound(format!("Conversation {} not found", id)))?;
+            json(serde_json::to_value(conversation)?)
+        }
+        _ => Err(AppError::NotFound(format!(
+            "{} {} is not served by the mock backend",
+            request.method, path
+        ))),
+    }
+}
+
+fn status_line(code: u16) -> &'static str {
+    match code {
+        200 => "200 OK",
+        201 => "201 Created",
+        204 => "204 No Content",
+        400 => "400 Bad Request",
+        404 => "404 Not Found",
+        _ => "500 Internal Server Error",
+    }
+}
+
+async fn write_response(
+    stream: &mut TcpStream,
+    code: u16,
+    body: Option<&serde_json::Value>,
+) -> std::io::Result<()> {
+    let body = body.map(|body| body.to_string()).unwrap_or_default();
+    // The dev server's origin differs from the backend's, so allow any
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nAccess-Control-Allow-Origin: *\r\nAccess-Control-Allow-Methods: GET, POST, PUT, PATCH, DELETE, OPTIONS\r\nAccess-Control-Allow-Headers: *\r\nConnection: close\r\n\r\n{}",
+        status_line(code),
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+async fn handle_connection(
+    fixtures: Arc<Mutex<Fixtures>>,
+    mut stream: TcpStream,
+) -> std::io::Result<()> {
+    let request = match tokio::time::timeout(IO_TIMEOUT, read_request(&mut stream)).await {
+        Ok(Ok(request)) => request,
+        Ok(Err(e)) => {
+            let body = serde_json::json!({ "error": e.to_string() });
+            return write_response(&mut stream, 400, Some(&body)).await;
+        }
+        Err(_) => return Ok(()),
+    };
+    let (code, body) = if request.method == "OPTIONS" {
+        // CORS preflight
+        (204, None)
+    } else {
+        match route(&fixtures, &request) {
+            Ok(response) => response,
+            Err(e) => {
+                let code = match e {
+                    AppError::NotFound(_) => 404,
+                    AppError::InvalidInput(_) => 400,
+                    _ => 500,
+                };
+                (code, Some(serde_json::json!({ "error": e.to_string() })))
+            }
+        }
+    };
+    tracing::debug!(
+        "Mock backend {} {} -> {}",
+        request.method,
+        request.path,
+        code
+    );
+    tokio::time::timeout(IO_TIMEOUT, write_response(&mut stream, code, body.as_ref()))
+        .await
+        .unwrap_or(Ok(()))
+}
+
+/// Serve the fixture API on the backend port, moving to a nearby port if
+/// it's taken; the returned task serves until aborted
+pub async fn start(app: &AppHandle) -> Result<JoinHandle<()>, AppError> {
+    let state = app.state::<AppState>();
+    let preferred = *state.backend_port.read();
+    StartupEvent::BackendStarting { port: preferred }.emit(app);
+
+    let mut listener = None;
+    for port in preferred..=preferred.saturating_add(10) {
+        match TcpListener::bind(("127.0.0.1", port)).await {
+            Ok(bound) => {
+                listener = Some((bound, port));
+                break;
+            }
+            Err(e) => tracing::debug!("Mock backend could not listen on {}: {}", port, e),
+        }
+    }
+    let Some((listener, port)) = listener else {
+        let error = AppError::Conflict(format!(
+            "Ports {}-{} are in use",
+            preferred,
+            preferred.saturating_add(10)
+        ));
+        StartupEvent::BackendFailed {
+            error: error.to_string(),
+            port: preferred,
+        }
+        .emit(app);
+        return Err(error);
+    };
+    if port != preferred {
+        StartupEvent::PortConflict {
+            port: preferred,
+            service: "Backend".to_string(),
+        }
+        .emit(app);
+        *state.backend_port.write() = port;
+    }
+
+    let fixtures = Arc::new(Mutex::new(Fixtures::sample()));
+    let task = tauri::async_runtime::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let fixtures = fixtures.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = handle_connection(fixtures, stream).await {
+                    tracing::debug!("Mock backend connection failed: {}", e);
+                }
+            });
+        }
+    });
+
+    tracing::info!("Mock backend listening on 127.0.0.1:{}", port);
+    *state.is_backend_ready.write() = true;
+    StartupEvent::BackendReady {
+        port,
+        duration_ms: 0,
+    }
+    .emit(app);
+    StartupEvent::AllServicesReady {
+        total_duration_ms: 0,
+    }
+    .emit(app);
+    Ok(task)
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(method: &str, path: &str, body: &str) -> HttpRequest {
+        HttpRequest {
+            method: method.to_string(),
+            path: path.to_string(),
+            headers: Vec::new(),
+            body: body.as_bytes().to_vec(),
+        }
+    }
+
+    fn call(fixtures: &Mutex<Fixtures>, method: &str, path: &str, body: &str) -> serde_json::Value {
+        route(fixtures, &request(method, path, body))
+            .unwrap()
+            .1
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn test_health_and_auth() {
+        let fixtures = Mutex::new(Fixtures::sample());
+        assert_eq!(
+            call(&fixtures, "GET", "/api/health", "")["status"],
+            "Healthy"
+        );
+
+        let login = call(
+            &fixtures,
+            "POST",
+            "/api/auth/login",
+            r#"{"email":"ada@example.com","password":"x"}"#,
+        );
+        assert_eq!(login["email"], "ada@example.com");
+        assert_eq!(login["userId"], MOCK_USER_ID);
+        assert_eq!(login["isNewUser"], false);
+        assert_eq!(
+            call(&fixtures, "POST", "/api/auth/register", "")["isNewUser"],
+            true
+        );
+    }
+
+    #[test]
+    fn test_note_crud() {
+        let fixtures = Mutex::new(Fixtures::sample());
+        let count = call(&fixtures, "GET", "/api/notes", "")
+            .as_array()
+            .unwrap()
+            .len();
+
+        let (code, created) = route(
+            &fixtures,
+            &request("POST", "/api/notes", r#"{"title":"New","content":"Body"}"#),
+        )
+        .unwrap();
+        assert_eq!(code, 201);
+        let id = created.unwrap()["id"].as_str().unwrap().to_string();
+        assert_eq!(
+            call(&fixtures, "GET", "/api/notes", "")
+                .as_array()
+                .unwrap()
+                .len(),
+            count + 1
+        );
+
+        let path = format!("/api/notes/{}", id);
+        let updated = call(&fixtures, "PUT", &path, r#"{"title":"Renamed"}"#);
+        assert_eq!(updated["title"], "Renamed");
+        assert_eq!(updated["content"], "Body");
+
+        assert_eq!(
+            route(&fixtures, &request("DELETE", &path, "")).unwrap().0,
+            204
+        );
+        assert!(matches!(
+            route(&fixtures, &request("GET", &path, "")),
+            Err(AppError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_notes_page() {
+        let fixtures = Mutex::new(Fixtures::sample());
+        let page = call(&fixtures, "GET", "/api/notes/paged?page=1&pageSize=2", "");
+        assert_eq!(page["items"].as_array().unwrap().len(), 2);
+        assert_eq!(page["totalCount"], 3);
+        assert_eq!(page["hasNextPage"], true);
+
+        let search = call(
+            &fixtures,
+            "GET",
+            "/api/notes/paged?search=shopping+list",
+            "",
+        );
+        assert_eq!(search["totalCount"], 1);
+        let work = call(&fixtures, "GET", "/api/notes/paged?folder=Work", "");
+        assert_eq!(work["items"][0]["folder"], "Work");
+    }
+
+    #[test]
+    fn test_unknown_routes() {
+        let fixtures = Mutex::new(Fixtures::sample());
+        for (method, path) in [
+            ("GET", "/api/stats/dashboard"),
+            ("GET", "/health"),
+            ("POST", "/api/notes/mock-note-1"),
+            ("GET", "/api/chat/conversations/missing"),
+        ] {
+            assert!(
+                matches!(
+                    route(&fixtures, &request(method, path, "")),
+                    Err(AppError::NotFound(_))
+                ),
+                "{} {}",
+                method,
+                path
+            );
+        }
+    }
+}
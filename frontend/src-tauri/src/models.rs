@@ -0,0 +1,174 @@
+//! Model file downloads.
+//!
+//! This module provides:
+//! - Streaming downloads into the app's models folder, run as jobs so they
+//!   report byte progress and can be cancelled
+//! - Optional SHA-256 verification before the file is put in place
+//!
+//! Files are written under a `.partial` name and renamed once complete, so
+//! a cancelled or failed download never leaves a truncated model behind.
+
+use futures_util::StreamExt;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::Manager;
+use tokio::io::AsyncWriteExt;
+
+use crate::error::AppError;
+use crate::jobs::JobContext;
+
+/// Longest a single download may take
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// A downloaded model file
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelFile {
+    pub path: String,
+    pub size_bytes: u64,
+    pub sha256: String,
+}
+
+/// Folder holding downloaded models
+pub fn models_dir(app_data_dir: &Path) -> PathBuf {
+    app_data_dir.join("models")
+}
+
+fn validate_file_name(file_name: &str) -> Result<(), AppError> {
+    let valid = !file_name.is_empty()
+        && !file_name.starts_with('.')
+        && !file_name.contains(['/', '\\', ':'])
+        && file_name.len() <= 255;
+    if valid {
+        Ok(())
+    } else {
+        Err(AppError::InvalidInput(format!(
+            "Invalid model file name '{}'",
+            file_name
+        )))
+    }
+}
+
+fn validate_url(url: &str) -> Result<(), AppError> {
+    if url.starts_with("https://") {
+        Ok(())
+    } else {
+        Err(AppError::InvalidInput(
+            "Model downloads must use https".to_string(),
+        ))
+    }
+}
+
+/// Download `url` into the models folder as `file_name`
+pub async fn download_model(
+    job: &JobContext,
+    url: &str,
+    file_name: &str,
+    sha256: Option<&str>,
+) -> Result<ModelFile, AppError> {
+    validate_url(url)?;
+    validate_file_name(file_name)?;
+    let dir = models_dir(&job.app().path().app_data_dir()?);
+    tokio::fs::create_dir_all(&dir).await?;
+    let final_path = dir.join(file_name);
+    let partial_path = dir.join(format!(".{}.partial", file_name));
+
+    let result = fetch(job, url, &partial_path)
+        .await
+        .and_then(|(size, digest)| {
+            if let Some(expected) = sha256 {
+                if !digest.eq_ignore_ascii_case(expected.trim()) {
+                    return Err(AppError::InvalidInput(format!(
+                        "Checksum mismatch for {}: expected {}, got {}",
+                        file_name, expected, digest
+                    )));
+                }
+            }
+            Ok((size, digest))
+        });
+    let (size_bytes, digest) = match result {
+        Ok(done) => done,
+        Err(e) => {
+            let _ = tokio::fs::remove_file(&partial_path).await;
+            return Err(e);
+        }
+    };
+    tokio::fs::rename(&partial_path, &final_path).await?;
+
+    tracing::info!("Downloaded model {} ({} bytes)", file_name, size_bytes);
+    Ok(ModelFile {
+        path: final_path.to_string_lossy().to_string(),
+        size_bytes,
+        sha256: digest,
+    })
+}
+
+/// Stream the response body to `path`; returns its size and SHA-256
+async fn fetch(job: &JobContext, url: &str, path: &Path) -> Result<(u64, String), AppError> {
+    let response = job
+        .cancellable(
+            crate::http::external(job.app())
+                .get(url)
+                .timeout(DOWNLOAD_TIMEOUT)
+                .send(),
+        )
+        .await?;
+    if !response.status().is_success() {
+        return Err(AppError::Network(format!(
+            "Download failed with status {}",
+            response.status()
+        )));
+    }
+    let total = response.content_length();
+    let mut body = response.bytes_stream();
+    let mut file = tokio::fs::File::create(path).await?;
+    let mut hasher = Sha256::new();
+    let mut size = 0u64;
+
+    job.progress("downloading", 0, total);
+    while let Some(chunk) = job
+        .cancellable(async { Ok::<_, AppError>(body.next().await) })
+        .await?
+    {
+        let chunk = chunk?;
+        hasher.update(&chunk);
+        file.write_all(&chunk).await?;
+        size += chunk.len() as u64;
+        job.progress("downloading", size, total);
+    }
+    file.flush().await?;
+    file.sync_all().await?;
+
+    let digest = hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    Ok((size, digest))
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_file_name() {
+        assert!(validate_file_name("ggml-base.en.bin").is_ok());
+        assert!(validate_file_name("").is_err());
+        assert!(validate_file_name(".hidden").is_err());
+        assert!(validate_file_name("../escape.bin").is_err());
+        assert!(validate_file_name("dir\\model.bin").is_err());
+    }
+
+    #[test]
+    fn test_validate_url() {
+        assert!(validate_url("https://huggingface.co/model.bin").is_ok());
+        assert!(validate_url("http://example.com/model.bin").is_err());
+        assert!(validate_url("file:///etc/passwd").is_err());
+    }
+}
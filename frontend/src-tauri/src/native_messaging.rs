@@ -0,0 +1,806 @@
+//! Native messaging host for the browser extension.
+//!
+//! This module provides:
+//! - A host mode (`second-brain --native-messaging`) speaking the Chrome and
+//!   Firefox stdio protocol: each message is a 32-bit native-endian length
+//!   followed by that many bytes of UTF-8 JSON
+//! - Forwarding of clips and searches to the running app through the control
+//!   API, so the host never touches the database or backend itself
+//! - Commands that install, list and remove the host manifest per browser
+//!
+//! Browsers start the executable named in the manifest with arguments of their
+//! own and can't add `--native-messaging`, so installing also writes a small
+//! wrapper script that does. On Windows the manifest is found through a
+//! registry key under HKCU rather than a well-known directory.
+//!
+//! Messages from the extension (the optional `id` is echoed back):
+//! - `{"type": "ping"}`
+//! - `{"type": "status"}`
+//! - `{"type": "clip", "url", "title"?, "html"?, "text"?, "tags"?, "folder"?}`
+//! - `{"type": "search", "query", "limit"?}`
+//!
+//! Replies are `{"id", "ok": true, "result"}` or `{"id", "ok": false, "error"}`.
+//! When the backend moved to another port since the previous message, an
+//! unsolicited `{"type": "api_url_changed", "old_url", "new_url", ...}` is
+//! sent before the reply.
+
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+use crate::api_endpoint::{self, ApiUrlChanged};
+use crate::control_api::{self, ControlApiSettings};
+use crate::error::AppError;
+
+/// Host name registered with browsers
+pub const HOST_NAME: &str = "com.secondbrain.desktop";
+
+/// Largest message accepted from the browser
+const MAX_INCOMING_BYTES: usize = 8 * 1024 * 1024;
+
+/// Largest message browsers accept from a host
+const MAX_OUTGOING_BYTES: usize = 1024 * 1024;
+
+/// Timeout for a call to the control API
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Browsers the host can be registered with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Browser {
+    Chrome,
+    Chromium,
+    Edge,
+    Brave,
+    Firefox,
+}
+
+impl Browser {
+    pub const ALL: [Browser; 5] = [
+        Browser::Chrome,
+        Browser::Chromium,
+        Browser::Edge,
+        Browser::Brave,
+        Browser::Firefox,
+    ];
+
+    fn slug(self) -> &'static str {
+        match self {
+            Browser::Chrome => "chrome",
+            Browser::Chromium => "chromium",
+            Browser::Edge => "edge",
+            Browser::Brave => "brave",
+            Browser::Firefox => "firefox",
+        }
+    }
+
+    fn is_firefox(self) -> bool {
+        self == Browser::Firefox
+    }
+
+    /// Directory the browser reads manifests from, relative to home
+    #[cfg(target_os = "macos")]
+    fn manifest_dir(self, home: &Path) -> PathBuf {
+        let support = home.join("Library/Application Support");
+        match self {
+            Browser::Chrome => support.join("Google/Chrome/NativeMessagingHosts"),
+            Browser::Chromium => support.join("Chromium/NativeMessagingHosts"),
+            Browser::Edge => support.join("Microsoft Edge/NativeMessagingHosts"),
+            Browser::Brave => support.join("BraveSoftware/Brave-Browser/NativeMessagingHosts"),
+            Browser::Firefox => support.join("Mozilla/NativeMessagingHosts"),
+        }
+    }
+
+    /// Directory the browser reads manifests from, relative to home
+    #[cfg(all(unix, not(target_os = "macos")))]
+    fn manifest_dir(self, home: &Path) -> PathBuf {
+        match self {
+            Browser::Chrome => home.join(".config/google-chrome/NativeMessagingHosts"),
+            Browser::Chromium => home.join(".config/chromium/NativeMessagingHosts"),
+            Browser::Edge => home.join(".config/microsoft-edge/NativeMessagingHosts"),
+            Browser::Brave => home.join(".config/BraveSoftware/Brave-Browser/NativeMessagingHosts"),
+            Browser::Firefox => home.join(".mozilla/native-messaging-hosts"),
+        }
+    }
+
+    /// Registry key pointing at the manifest
+    #[cfg(windows)]
+    fn registry_key(self) -> String {
+        let base = match self {
+            Browser::Chrome => r"HKCU\Software\Google\Chrome\NativeMessagingHosts",
+            Browser::Chromium => r"HKCU\Software\Chromium\NativeMessagingHosts",
+            Browser::Edge => r"HKCU\Software\Microsoft\Edge\NativeMessagingHosts",
+            Browser::Brave => r"HKCU\Software\BraveSoftware\Brave-Browser\NativeMessagingHosts",
+            Browser::Firefox => r"HKCU\Software\Mozilla\NativeMessagingHosts",
+        };
+        format!(r"{}\{}", base, HOST_NAME)
+    }
+}
+
+/// Registration state for one browser
+#[derive(Debug, Clone, Serialize)]
+pub struct NativeHostStatus {
+    pub browser: Browser,
+    pub installed: bool,
+    pub manifest_path: String,
+    pub extension_id: Option<String>,
+}
+
+// ============================================================
+// Protocol
+// ============================================================
+
+/// Read one message; None when the browser closed the pipe
+pub fn read_message<R: Read>(reader: &mut R) -> std::io::Result<Option<Vec<u8>>> {
+    let mut length = [0u8; 4];
+    match reader.read_exact(&mut length) {
+        Ok(()) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e),
+    }
+    let length = u32::from_ne_bytes(length) as usize;
+    if length > MAX_INCOMING_BYTES {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("Message of {} bytes is too large", length),
+        ));
+    }
+    let mut message = vec![0u8; length];
+    reader.read_exact(&mut message)?;
+    Ok(Some(message))
+}
+
+/// Write one message, replacing it with an error if browsers would reject it
+pub fn write_message<W: Write>(writer: &mut W, message: &serde_json::Value) -> std::io::Result<()> {
+    let mut bytes = serde_json::to_vec(message)?;
+    if bytes.len() > MAX_OUTGOING_BYTES {
+        let error = AppError::InvalidInput(format!(
+            "Reply of {} bytes exceeds the browser limit",
+            bytes.len()
+        ));
+        bytes = serde_json::to_vec(&serde_json::json!({
+            "id": message.get("id").cloned().unwrap_or_default(),
+            "ok": false,
+            "error": error,
+        }))?;
+    }
+    writer.write_all(&(bytes.len() as u32).to_ne_bytes())?;
+    writer.write_all(&bytes)?;
+    writer.flush()
+}
+
+/// A page or selection clipped by the extension
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+struct ClipRequest {
+    url: String,
+    title: Option<String>,
+    html: Option<String>,
+    text: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+    folder: Option<String>,
+}
+
+/// A message from the extension
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum HostRequest {
+    Ping,
+    Status,
+    Clip(ClipRequest),
+    Search { query: String, limit: Option<u32> },
+}
+
+/// Markdown body for a clip, ending with a link back to the page
+fn clip_content(clip: &ClipRequest) -> Result<String, AppError> {
+    let body = match (&clip.html, &clip.text) {
+        (Some(html), _) if !html.trim().is_empty() => htmd::convert(html)
+            .map_err(|e| AppError::InvalidInput(format!("Failed to convert clip: {}", e)))?,
+        (_, Some(text)) => text.clone(),
+        _ => String::new(),
+    };
+    let body = body.trim();
+    if body.is_empty() {
+        return Err(AppError::InvalidInput("Clip is empty".to_string()));
+    }
+    Ok(format!("{}\n\nSource: <{}>", body, clip.url.trim()))
+}
+
+/// Control API call for a request, or None if answered locally
+fn control_call(
+    request: &HostRequest,
+) -> Result<Option<(&'static str, &'static str, serde_json::Value)>, AppError> {
+    Ok(match request {
+        HostRequest::Ping => None,
+        HostRequest::Status => Some(("GET", "/v1/status", serde_json::Value::Null)),
+        HostRequest::Clip(clip) => {
+            let url = reqwest::Url::parse(clip.url.trim())
+                .map_err(|e| AppError::InvalidInput(format!("Invalid page URL: {}", e)))?;
+            if !matches!(url.scheme(), "http" | "https" | "file") {
+                return Err(AppError::InvalidInput(format!(
+                    "Pages served over {} can't be clipped",
+                    url.scheme()
+                )));
+            }
+            let mut tags = clip.tags.clone();
+            if !tags.iter().any(|tag| tag == "web-clip") {
+                tags.push("web-clip".to_string());
+            }
+            Some((
+                "POST",
+                "/v1/capture",
+                serde_json::json!({
+                    "content": clip_content(clip)?,
+                    "title": clip.title.as_deref().map(str::trim).filter(|t| !t.is_empty()),
+                    "tags": tags,
+                    "folder": clip.folder,
+                }),
+            ))
+        }
+        HostRequest::Search { query, limit } => Some((
+            "POST",
+            "/v1/notes/search",
+            serde_json::json!({ "query": query, "limit": limit }),
+        )),
+    })
+}
+
+// ============================================================
+// Host
+// ============================================================
+
+/// Connection to the running app, read from its data directory
+pub(crate) struct Host {
+    app_data_dir: PathBuf,
+    client: reqwest::Client,
+}
+
+impl Host {
+    /// Connect to the app with the given bundle identifier
+    pub(crate) fn new(identifier: &str) -> Result<Self, String> {
+        let dirs = directories::BaseDirs::new().ok_or("no home directory")?;
+        let client = reqwest::Client::builder()
+            .no_proxy()
+            .connect_timeout(Duration::from_secs(2))
+            .build()
+            .map_err(|e| e.to_string())?;
+        Ok(Self {
+            app_data_dir: dirs.data_dir().join(identifier),
+            client,
+        })
+    }
+
+    /// Move of the backend API since `known`, updating it
+    fn api_url_change(&self, known: &mut Option<u16>) -> Option<ApiUrlChanged> {
+        let port = api_endpoint::read(&self.app_data_dir)?.port;
+        api_endpoint::change(known.replace(port), port)
+    }
+
+    /// Call a control API route
+    pub(crate) async fn call(
+        &self,
+        method: &str,
+        path: &str,
+        body: serde_json::Value,
+    ) -> Result<serde_json::Value, AppError> {
+        // Read per call so enabling the API or rotating the token applies at once
+        let settings = ControlApiSettings::load(&self.app_data_dir);
+        if !settings.enabled {
+            return Err(AppError::NotReady(
+                "The control API is turned off in Second Brain".to_string(),
+            ));
+        }
+        let token = control_api::stored_token(&self.app_data_dir)?
+            .ok_or_else(|| AppError::NotReady("Second Brain has not been set up".to_string()))?;
+
+        let url = format!("http://127.0.0.1:{}{}", settings.port, path);
+        let mut request = match method {
+            "GET" => self.client.get(&url),
+            _ => self.client.post(&url).json(&body),
+        };
+        request = request.bearer_auth(token).timeout(REQUEST_TIMEOUT);
+        let response = request.send().await.map_err(|e| {
+            if e.is_connect() {
+                AppError::NotReady("Second Brain is not running".to_string())
+            } else {
+                AppError::Network(format!("Control API request failed: {}", e))
+            }
+        })?;
+
+        let status = response.status();
+        let value: serde_json::Value = response.json().await.unwrap_or_default();
+        if status.is_success() {
+            return Ok(value);
+        }
+        let message = value
+            .get("message")
+            .and_then(|m| m.as_str())
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("Control API returned {}", status));
+        Err(match value.get("kind").and_then(|k| k.as_str()) {
+            Some("not_found") => AppError::NotFound(message),
+            Some("invalid_input") => AppError::InvalidInput(message),
+            Some("not_ready") => AppError::NotReady(message),
+            Some("permission") => AppError::Permission(message),
+            _ => AppError::Backend(message),
+        })
+    }
+
+    async fn handle(&self, request: &HostRequest) -> Result<serde_json::Value, AppError> {
+        match control_call(request)? {
+            None => Ok(serde_json::json!({
+                "host": HOST_NAME,
+                "version": env!("CARGO_PKG_VERSION"),
+            })),
+            Some((method, path, body)) => self.call(method, path, body).await,
+        }
+    }
+}
+
+/// Reply to one raw message
+async fn reply(host: &Host, message: &[u8]) -> serde_json::Value {
+    let parsed: Result<serde_json::Value, AppError> =
+        serde_json::from_slice(message).map_err(AppError::from);
+    let id = parsed
+        .as_ref()
+        .ok()
+        .and_then(|value| value.get("id").cloned())
+        .unwrap_or_default();
+    let result = match parsed.and_then(|value| Ok(serde_json::from_value::<HostRequest>(value)?)) {
+        Ok(request) => host.handle(&request).await,
+        Err(e) => Err(e),
+    };
+    match result {
+        Ok(result) => serde_json::json!({ "id": id, "ok": true, "result": result }),
+        Err(error) => serde_json::json!({ "id": id, "ok": false, "error": error }),
+    }
+}
+
+/// Serve the browser over stdin and stdout until it disconnects
+///
+/// Runs instead of the app, so no window, tray or services are started.
+/// Returns the process exit code.
+pub fn run_host(identifier: &str) -> i32 {
+    let runtime = match tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+    {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            eprintln!("Native messaging host: {}", e);
+            return 1;
+        }
+    };
+    let host = match Host::new(identifier) {
+        Ok(host) => host,
+        Err(e) => {
+            eprintln!("Native messaging host: {}", e);
+            return 1;
+        }
+    };
+
+    // stdout carries the protocol, so diagnostics go to stderr only
+    let mut stdin = std::io::stdin().lock();
+    let mut stdout = std::io::stdout().lock();
+    let mut known_port = api_endpoint::read(&host.app_data_dir).map(|endpoint| endpoint.port);
+    loop {
+        let message = match read_message(&mut stdin) {
+            Ok(Some(message)) => message,
+            Ok(None) => return 0,
+            Err(e) => {
+                eprintln!("Native messaging host: {}", e);
+                return 1;
+            }
+        };
+        let response = runtime.block_on(reply(&host, &message));
+        if let Some(changed) = host.api_url_change(&mut known_port) {
+            let mut notice = serde_json::to_value(changed).unwrap_or_default();
+            notice["type"] = "api_url_changed".into();
+            if let Err(e) = write_message(&mut stdout, &notice) {
+                eprintln!("Native messaging host: {}", e);
+                return 1;
+            }
+        }
+        if let Err(e) = write_message(&mut stdout, &response) {
+            eprintln!("Native messaging host: {}", e);
+            return 1;
+        }
+    }
+}
+
+// ============================================================
+// Installation
+// ============================================================
+
+fn validate_extension_id(browser: Browser, id: &str) -> Result<(), AppError> {
+    let valid = if browser.is_firefox() {
+        // Either an email-like id or a braced UUID
+        !id.is_empty()
+            && id.len() <= 128
+            && id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "@.-_{}".contains(c))
+    } else {
+        id.len() == 32 && id.chars().all(|c| ('a'..='p').contains(&c))
+    };
+    if valid {
+        Ok(())
+    } else {
+        Err(AppError::InvalidInput(format!(
+            "'{}' is not a valid {} extension id",
+            id,
+            browser.slug()
+        )))
+    }
+}
+
+/// Manifest contents for a browser
+fn manifest(browser: Browser, wrapper: &Path, extension_id: &str) -> serde_json::Value {
+    let mut manifest = serde_json::json!({
+        "name": HOST_NAME,
+        "description": "Second Brain",
+        "path": wrapper.to_string_lossy(),
+        "type": "stdio",
+    });
+    if browser.is_firefox() {
+        manifest["allowed_extensions"] = serde_json::json!([extension_id]);
+    } else {
+        manifest["allowed_origins"] =
+            serde_json::json!([format!("chrome-extension://{}/", extension_id)]);
+    }
+    manifest
+}
+
+/// Extension id a manifest allows, if it is ours
+fn manifest_extension_id(manifest: &serde_json::Value) -> Option<String> {
+    if manifest.get("name").and_then(|n| n.as_str()) != Some(HOST_NAME) {
+        return None;
+    }
+    if let Some(origin) = manifest["allowed_origins"][0].as_str() {
+        return origin
+            .strip_prefix("chrome-extension://")
+            .map(|id| id.trim_end_matches('/').to_string());
+    }
+    manifest["allowed_extensions"][0]
+        .as_str()
+        .map(str::to_string)
+}
+
+/// Wrapper that starts the executable in host mode
+fn wrapper_script(exe: &Path) -> String {
+    let exe = exe.to_string_lossy();
+    if cfg!(windows) {
+        format!("@echo off\r\n\"{}\" --native-messaging %*\r\n", exe)
+    } else {
+        format!(
+            "#!/bin/sh\nexec '{}' --native-messaging \"$@\"\n",
+            exe.replace('\'', r"'\''")
+        )
+    }
+}
+
+fn host_dir(app_data_dir: &Path) -> PathBuf {
+    app_data_dir.join("native-messaging")
+}
+
+fn wrapper_path(app_data_dir: &Path) -> PathBuf {
+    let name = if cfg!(windows) {
+        "second-brain-host.bat"
+    } else {
+        "second-brain-host"
+    };
+    host_dir(app_data_dir).join(name)
+}
+
+/// Where the manifest for a browser lives
+fn manifest_path(browser: Browser, app_data_dir: &Path) -> Result<PathBuf, AppError> {
+    let file = format!("{}.json", HOST_NAME);
+    #[cfg(windows)]
+    {
+        Ok(host_dir(app_data_dir).join(browser.slug()).join(file))
+    }
+    #[cfg(not(windows))]
+    {
+        let _ = app_data_dir;
+        let dirs = directories::BaseDirs::new()
+            .ok_or_else(|| AppError::Internal("No home directory".to_string()))?;
+        Ok(browser.manifest_dir(dirs.home_dir()).join(file))
+    }
+}
+
+fn write_wrapper(app_data_dir: &Path) -> Result<PathBuf, AppError> {
+    let exe = std::env::current_exe()?;
+    let path = wrapper_path(app_data_dir);
+    std::fs::create_dir_all(host_dir(app_data_dir))?;
+    std::fs::write(&path, wrapper_script(&exe))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))?;
+    }
+    Ok(path)
+}
+
+#[cfg(windows)]
+fn run_reg(args: &[&str]) -> Result<(), AppError> {
+    let output = std::process::Command::new("reg").args(args).output()?;
+    if !output.status.success() {
+        return Err(AppError::Permission(format!(
+            "reg {} failed: {}",
+            args[0],
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(())
+}
+
+fn status(browser: Browser, app_data_dir: &Path) -> Result<NativeHostStatus, AppError> {
+    let path = manifest_path(browser, app_data_dir)?;
+    let extension_id = crate::config::load_json::<serde_json::Value>(&path)
+        .as_ref()
+        .and_then(manifest_extension_id);
+    Ok(NativeHostStatus {
+        browser,
+        installed: extension_id.is_some(),
+        manifest_path: path.to_string_lossy().into_owned(),
+        extension_id,
+    })
+}
+
+fn install(
+    browser: Browser,
+    extension_id: &str,
+    app_data_dir: &Path,
+) -> Result<NativeHostStatus, AppError> {
+    validate_extension_id(browser, extension_id)?;
+    let wrapper = write_wrapper(app_data_dir)?;
+    let path = manifest_path(browser, app_data_dir)?;
+    crate::config::save_json_atomic(&path, &manifest(browser, &wrapper, extension_id))?;
+    #[cfg(windows)]
+    run_reg(&[
+        "add",
+        &browser.registry_key(),
+        "/ve",
+        "/t",
+        "REG_SZ",
+        "/d",
+        &path.to_string_lossy(),
+        "/f",
+    ])?;
+    status(browser, app_data_dir)
+}
+
+fn uninstall(browser: Browser, app_data_dir: &Path) -> Result<NativeHostStatus, AppError> {
+    let path = manifest_path(browser, app_data_dir)?;
+    // Never remove a manifest some other app registered under our name
+    if status(browser, app_data_dir)?.installed {
+        std::fs::remove_file(&path)?;
+        #[cfg(windows)]
+        run_reg(&["delete", &browser.registry_key(), "/f"])?;
+    }
+    status(browser, app_data_dir)
+}
+
+/// Remove the host registration from every browser that has it, returning
+/// whether any had
+pub(crate) fn uninstall_all(app_data_dir: &Path) -> Result<bool, AppError> {
+    let mut removed = false;
+    for browser in Browser::ALL {
+        if status(browser, app_data_dir)?.installed {
+            uninstall(browser, app_data_dir)?;
+            removed = true;
+        }
+    }
+    Ok(removed)
+}
+
+// ============================================================
+// Commands
+// ============================================================
+
+/// Registration state for every supported browser
+#[tauri::command]
+pub async fn get_native_messaging_hosts(app: AppHandle) -> Result<Vec<NativeHostStatus>, AppError> {
+    let app_data_dir = app.path().app_data_dir()?;
+    Browser::ALL
+        .iter()
+        .map(|browser| status(*browser, &app_data_dir))
+        .collect()
+}
+
+/// Register the host for an extension; turns on the control API it relies on
+#[tauri::command]
+pub async fn install_native_messaging_host(
+    app: AppHandle,
+    browser: Browser,
+    extension_id: String,
+) -> Result<NativeHostStatus, AppError> {
+    let app_data_dir = app.path().app_data_dir()?;
+    let extension_id = extension_id.trim().to_string();
+    let app_data_for_task = app_data_dir.clone();
+    let status =
+        tokio::task::spawn_blocking(move || install(browser, &extension_id, &app_data_for_task))
+            .await??;
+
+    let mut settings = ControlApiSettings::load(&app_data_dir);
+    if !settings.enabled {
+        settings.enabled = true;
+        control_api::apply_settings(&app, &settings)?;
+        settings.save(&app_data_dir)?;
+    }
+    tracing::info!("Registered native messaging host for {:?}", browser);
+    Ok(status)
+}
+
+/// Remove the host registration for a browser
+#[tauri::command]
+pub async fn uninstall_native_messaging_host(
+    app: AppHandle,
+    browser: Browser,
+) -> Result<NativeHostStatus, AppError> {
+    let app_data_dir = app.path().app_data_dir()?;
+    tokio::task::spawn_blocking(move || uninstall(browser, &app_data_dir)).await?
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHROME_ID: &str = "abcdefghijklmnopabcdefghijklmnop";
+
+    #[test]
+    fn test_message_round_trip() {
+        let mut buffer = Vec::new();
+        write_message(&mut buffer, &serde_json::json!({ "type": "ping" })).unwrap();
+        assert_eq!(&buffer[..4], &15u32.to_ne_bytes());
+
+        let mut reader = std::io::Cursor::new(buffer);
+        let message = read_message(&mut reader).unwrap().unwrap();
+        assert_eq!(message, br#"{"type":"ping"}"#.to_vec());
+        assert!(read_message(&mut reader).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_read_rejects_oversized_and_truncated() {
+        let length = (MAX_INCOMING_BYTES as u32 + 1).to_ne_bytes();
+        let mut reader = std::io::Cursor::new(length.to_vec());
+        assert!(read_message(&mut reader).is_err());
+
+        let mut truncated = 10u32.to_ne_bytes().to_vec();
+        truncated.extend_from_slice(b"{}");
+        assert!(read_message(&mut std::io::Cursor::new(truncated)).is_err());
+    }
+
+    #[test]
+    fn test_oversized_reply_becomes_error() {
+        let big = "x".repeat(MAX_OUTGOING_BYTES);
+        let mut buffer = Vec::new();
+        write_message(&mut buffer, &serde_json::json!({ "id": 7, "result": big })).unwrap();
+        let reply: serde_json::Value = serde_json::from_slice(&buffer[4..]).unwrap();
+        assert_eq!(reply["id"], 7);
+        assert_eq!(reply["ok"], false);
+        assert_eq!(reply["error"]["kind"], "invalid_input");
+    }
+
+    #[test]
+    fn test_parse_requests() {
+        let parse = |json: &str| serde_json::from_str::<HostRequest>(json).ok();
+        assert_eq!(parse(r#"{"type":"ping","id":1}"#), Some(HostRequest::Ping));
+        assert_eq!(
+            parse(r#"{"type":"search","query":"rust"}"#),
+            Some(HostRequest::Search {
+                query: "rust".to_string(),
+                limit: None
+            })
+        );
+        assert!(matches!(
+            parse(r#"{"type":"clip","url":"https://example.com","html":"<p>Hi</p>"}"#),
+            Some(HostRequest::Clip(_))
+        ));
+        assert_eq!(parse(r#"{"type":"delete_everything"}"#), None);
+    }
+
+    fn clip(url: &str, html: Option<&str>, text: Option<&str>) -> ClipRequest {
+        ClipRequest {
+            url: url.to_string(),
+            title: Some("  Page  ".to_string()),
+            html: html.map(str::to_string),
+            text: text.map(str::to_string),
+            tags: vec!["rust".to_string()],
+            folder: None,
+        }
+    }
+
+    #[test]
+    fn test_clip_becomes_capture() {
+        let request = HostRequest::Clip(clip(
+            "https://example.com/post",
+            Some("<h1>Title</h1><p>Some <b>bold</b> text</p>"),
+            None,
+        ));
+        let (method, path, body) = control_call(&request).unwrap().unwrap();
+        assert_eq!((method, path), ("POST", "/v1/capture"));
+        assert_eq!(body["title"], "Page");
+        assert_eq!(body["tags"], serde_json::json!(["rust", "web-clip"]));
+        let content = body["content"].as_str().unwrap();
+        assert!(content.contains("**bold**"));
+        assert!(content.ends_with("Source: <https://example.com/post>"));
+
+        let text_only = clip("https://example.com", None, Some("plain"));
+        assert!(clip_content(&text_only).unwrap().starts_with("plain"));
+    }
+
+    #[test]
+    fn test_clip_rejections() {
+        let kind = |clip: ClipRequest| {
+            control_call(&HostRequest::Clip(clip))
+                .err()
+                .map(|e| e.kind())
+        };
+        assert_eq!(
+            kind(clip("https://example.com", Some("  "), None)),
+            Some("invalid_input")
+        );
+        assert_eq!(
+            kind(clip("chrome://settings", None, Some("x"))),
+            Some("invalid_input")
+        );
+        assert_eq!(
+            kind(clip("not a url", None, Some("x"))),
+            Some("invalid_input")
+        );
+        assert_eq!(control_call(&HostRequest::Ping).unwrap(), None);
+    }
+
+    #[test]
+    fn test_validate_extension_id() {
+        assert!(validate_extension_id(Browser::Chrome, CHROME_ID).is_ok());
+        assert!(validate_extension_id(Browser::Edge, "ABCDEFGHIJKLMNOPABCDEFGHIJKLMNOP").is_err());
+        assert!(validate_extension_id(Browser::Chrome, "short").is_err());
+        assert!(validate_extension_id(Browser::Firefox, "clipper@secondbrain.app").is_ok());
+        assert!(
+            validate_extension_id(Browser::Firefox, "{d9a7e7c4-3b8e-4a31-9f2a-1c2b3d4e5f60}")
+                .is_ok()
+        );
+        assert!(validate_extension_id(Browser::Firefox, "bad id\"").is_err());
+        assert!(validate_extension_id(Browser::Firefox, "").is_err());
+    }
+
+    #[test]
+    fn test_manifest_shape() {
+        let wrapper = Path::new("/opt/sb/second-brain-host");
+        let chrome = manifest(Browser::Chrome, wrapper, CHROME_ID);
+        assert_eq!(chrome["type"], "stdio");
+        assert_eq!(
+            chrome["allowed_origins"][0],
+            format!("chrome-extension://{}/", CHROME_ID)
+        );
+        assert!(chrome.get("allowed_extensions").is_none());
+        assert_eq!(manifest_extension_id(&chrome).as_deref(), Some(CHROME_ID));
+
+        let firefox = manifest(Browser::Firefox, wrapper, "clipper@secondbrain.app");
+        assert_eq!(
+            manifest_extension_id(&firefox).as_deref(),
+            Some("clipper@secondbrain.app")
+        );
+
+        let foreign = serde_json::json!({ "name": "com.other.app", "allowed_origins": ["x"] });
+        assert_eq!(manifest_extension_id(&foreign), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_wrapper_script_quotes_path() {
+        let script = wrapper_script(Path::new("/Apps/It's Here/second-brain"));
+        assert!(script.starts_with("#!/bin/sh\n"));
+        assert!(
+            script.contains(r#"exec '/Apps/It'\''s Here/second-brain' --native-messaging "$@""#)
+        );
+    }
+}
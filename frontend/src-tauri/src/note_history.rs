@@ -0,0 +1,592 @@
+//! Git-backed version history of notes.
+//!
+//! This module provides:
+//! - A markdown export of every note into a local git repository
+//! - Automatic commits on a schedule or once enough notes have changed
+//! - Per-note history and restore of any committed version
+//!
+//! The repository lives in the app data directory with one file per note,
+//! named by note ID so renames don't break history. Requires `git` on PATH.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+
+use crate::config::{load_json, save_json_atomic};
+use crate::error::AppError;
+use crate::scheduler::{JobAction, Schedule, ScheduledJob, Scheduler, NOTE_HISTORY_JOB_ID};
+
+/// Backend endpoint returning all notes with full content
+const EXPORT_NOTES_PATH: &str = "/notes/for-export";
+
+/// Directory inside the repository holding note files
+const NOTES_DIR: &str = "notes";
+
+/// Seconds between export passes that check for pending changes
+const CHECK_INTERVAL_SECS: u64 = 300;
+
+/// Note history settings
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NoteHistorySettings {
+    pub enabled: bool,
+    /// Commit pending changes at least this often
+    pub commit_interval_mins: u32,
+    /// Commit early once this many notes have changed
+    pub commit_after_changes: u32,
+}
+
+impl Default for NoteHistorySettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            commit_interval_mins: 60,
+            commit_after_changes: 20,
+        }
+    }
+}
+
+impl NoteHistorySettings {
+    fn path(app_data_dir: &Path) -> PathBuf {
+        app_data_dir.join("note-history.json")
+    }
+
+    /// Load settings, returning defaults if missing or invalid
+    pub fn load(app_data_dir: &Path) -> Self {
+        load_json(&Self::path(app_data_dir)).unwrap_or_default()
+    }
+
+    /// Save settings atomically
+    pub fn save(&self, app_data_dir: &Path) -> Result<(), String> {
+        save_json_atomic(&Self::path(app_data_dir), self)
+    }
+
+    /// Validate settings
+    pub fn validate(&self) -> Result<(), String> {
+        if self.commit_interval_mins < 5 {
+            return Err("Commit interval must be at least 5 minutes".to_string());
+        }
+        if self.commit_after_changes == 0 {
+            return Err("Change threshold must be at least 1".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Location of the history repository
+fn repo_dir(app_data_dir: &Path) -> PathBuf {
+    app_data_dir.join("note-history")
+}
+
+/// A note as returned by the export endpoint
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ExportedNote {
+    id: String,
+    title: String,
+    content: String,
+    #[serde(default)]
+    tags: Vec<String>,
+    folder: Option<String>,
+    updated_at: String,
+    #[serde(default)]
+    is_archived: bool,
+}
+
+/// One committed version of a note
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NoteVersion {
+    pub commit: String,
+    /// Commit time (RFC 3339)
+    pub timestamp: String,
+    pub message: String,
+}
+
+/// Note fields recovered from a committed file
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NoteSnapshot {
+    pub title: String,
+    pub content: String,
+    pub tags: Vec<String>,
+    pub folder: Option<String>,
+}
+
+/// Runtime status
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NoteHistoryStatus {
+    pub last_commit: Option<String>,
+    pub pending_changes: usize,
+    pub last_error: Option<String>,
+}
+
+/// Managed state for note history
+#[derive(Default)]
+pub struct NoteHistory {
+    /// Serializes passes over the repository
+    lock: tokio::sync::Mutex<()>,
+    status: Mutex<NoteHistoryStatus>,
+}
+
+// ============================================================
+// Rendering
+// ============================================================
+
+fn note_file(note_id: &str) -> String {
+    format!("{}/{}.md", NOTES_DIR, note_id)
+}
+
+/// Note IDs are used as file names, so only allow safe characters
+pub(crate) fn validate_note_id(note_id: &str) -> Result<(), String> {
+    if note_id.is_empty()
+        || !note_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(format!("Invalid note ID: {}", note_id));
+    }
+    Ok(())
+}
+
+fn validate_commit(commit: &str) -> Result<(), String> {
+    if commit.len() < 7 || commit.len() > 64 || !commit.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!("Invalid commit: {}", commit));
+    }
+    Ok(())
+}
+
+/// Render a note with JSON-quoted frontmatter fields
+fn render_note(note: &ExportedNote) -> String {
+    format!(
+        "---\ntitle: {}\ntags: {}\nfolder: {}\nupdated: {}\n---\n\n{}\n",
+        serde_json::to_string(&note.title).unwrap_or_default(),
+        serde_json::to_string(&note.tags).unwrap_or_default(),
+        serde_json::to_string(&note.folder).unwrap_or_default(),
+        note.updated_at,
+        note.content.trim_end()
+    )
+}
+
+/// Parse a file written by `render_note`
+fn parse_note(content: &str) -> Result<NoteSnapshot, String> {
+    let rest = content
+        .strip_prefix("---\n")
+        .ok_or_else(|| "Missing frontmatter".to_string())?;
+    let end = rest
+        .find("\n---\n")
+        .ok_or_else(|| "Unterminated frontmatter".to_string())?;
+
+    let mut note = NoteSnapshot {
+        content: rest[end + 5..]
+            .strip_prefix('\n')
+            .unwrap_or(&rest[end + 5..])
+            .trim_end()
+            .to_string(),
+        ..Default::default()
+    };
+    for line in rest[..end].lines() {
+        let Some((key, value)) = line.split_once(": ") else {
+            continue;
+        };
+        let invalid = |e: serde_json::Error| format!("Invalid {}: {}", key, e);
+        match key {
+            "title" => note.title = serde_json::from_str(value).map_err(invalid)?,
+            "tags" => note.tags = serde_json::from_str(value).map_err(invalid)?,
+            "folder" => note.folder = serde_json::from_str(value).map_err(invalid)?,
+            _ => {}
+        }
+    }
+    Ok(note)
+}
+
+// ============================================================
+// Git
+// ============================================================
+
+fn git(repo: &Path) -> Command {
+    let mut command = Command::new("git");
+    command
+        .arg("-C")
+        .arg(repo)
+        .args(["-c", "user.name=Second Brain"])
+        .args(["-c", "user.email=history@secondbrain.local"])
+        .args(["-c", "commit.gpgsign=false"]);
+    command
+}
+
+fn run(mut command: Command) -> Result<String, String> {
+    let output = command
+        .output()
+        .map_err(|e| format!("Failed to run git (is it installed?): {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "git failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Create the repository if it doesn't exist yet
+fn ensure_repo(repo: &Path) -> Result<(), String> {
+    if repo.join(".git").exists() {
+        return Ok(());
+    }
+    std::fs::create_dir_all(repo.join(NOTES_DIR))
+        .map_err(|e| format!("Failed to create {}: {}", repo.display(), e))?;
+    let mut command = git(repo);
+    command.args(["init", "--quiet"]);
+    run(command).map(|_| ())
+}
+
+/// Write all notes into the working tree, returning the number of changed files
+fn write_notes(repo: &Path, notes: &[ExportedNote]) -> Result<usize, String> {
+    let notes_dir = repo.join(NOTES_DIR);
+    std::fs::create_dir_all(&notes_dir)
+        .map_err(|e| format!("Failed to create {}: {}", notes_dir.display(), e))?;
+
+    let mut keep = std::collections::HashSet::new();
+    for note in notes.iter().filter(|n| !n.is_archived) {
+        if validate_note_id(&note.id).is_err() {
+            tracing::warn!("Skipping note with unexpected ID {:?}", note.id);
+            continue;
+        }
+        let path = repo.join(note_file(&note.id));
+        let rendered = render_note(note);
+        if std::fs::read_to_string(&path).ok().as_deref() != Some(rendered.as_str()) {
+            std::fs::write(&path, rendered)
+                .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        }
+        keep.insert(format!("{}.md", note.id));
+    }
+
+    // Deleted and archived notes drop out of the tree but stay in history
+    if let Ok(entries) = std::fs::read_dir(&notes_dir) {
+        for entry in entries.flatten() {
+            if !keep.contains(entry.file_name().to_string_lossy().as_ref()) {
+                let _ = std::fs::remove_file(entry.path());
+            }
+        }
+    }
+
+    pending_changes(repo)
+}
+
+fn pending_changes(repo: &Path) -> Result<usize, String> {
+    let mut command = git(repo);
+    command.args(["status", "--porcelain", "--", NOTES_DIR]);
+    Ok(run(command)?.lines().count())
+}
+
+/// Time of the last commit, None for an empty repository
+fn last_commit_time(repo: &Path) -> Option<chrono::DateTime<chrono::FixedOffset>> {
+    let mut command = git(repo);
+    command.args(["log", "-1", "--format=%cI"]);
+    let output = run(command).ok()?;
+    chrono::DateTime::parse_from_rfc3339(output.trim()).ok()
+}
+
+/// Commit all pending changes, returning the new commit hash
+fn commit_all(repo: &Path, changes: usize) -> Result<String, String> {
+    let mut add = git(repo);
+    add.args(["add", "--all", "--", NOTES_DIR]);
+    run(add)?;
+
+    let message = format!(
+        "Snapshot {} ({} note{} changed)",
+        chrono::Local::now().format("%Y-%m-%d %H:%M"),
+        changes,
+        if changes == 1 { "" } else { "s" }
+    );
+    let mut commit = git(repo);
+    commit.args(["commit", "--quiet", "-m", &message]);
+    run(commit)?;
+
+    let mut head = git(repo);
+    head.args(["rev-parse", "HEAD"]);
+    Ok(run(head)?.trim().to_string())
+}
+
+fn note_log(repo: &Path, note_id: &str) -> Result<Vec<NoteVersion>, String> {
+    if !repo.join(".git").exists() || last_commit_time(repo).is_none() {
+        return Ok(Vec::new());
+    }
+    let mut command = git(repo);
+    command.args(["log", "--format=%H%x1f%cI%x1f%s", "--", &note_file(note_id)]);
+    Ok(run(command)?
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.splitn(3, '\u{1f}');
+            Some(NoteVersion {
+                commit: parts.next()?.to_string(),
+                timestamp: parts.next()?.to_string(),
+                message: parts.next().unwrap_or_default().to_string(),
+            })
+        })
+        .collect())
+}
+
+fn read_version(repo: &Path, note_id: &str, commit: &str) -> Result<NoteSnapshot, String> {
+    let mut command = git(repo);
+    command
+        .arg("show")
+        .arg(format!("{}:{}", commit, note_file(note_id)));
+    parse_note(&run(command)?)
+}
+
+// ============================================================
+// Passes
+// ============================================================
+
+/// Export notes and commit if the schedule or change threshold says so
+async fn run_pass(app: &AppHandle, force: bool) -> Result<Option<String>, String> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let settings = NoteHistorySettings::load(&app_data_dir);
+    let repo = repo_dir(&app_data_dir);
+
+    let history = app.state::<NoteHistory>();
+    let _guard = history.lock.lock().await;
+
+    let response =
+        crate::proxy::send_backend_request(app, "GET", EXPORT_NOTES_PATH, None, None).await?;
+    let notes: Vec<ExportedNote> =
+        serde_json::from_value(response).map_err(|e| format!("Invalid notes response: {}", e))?;
+
+    let result = tokio::task::spawn_blocking(move || {
+        ensure_repo(&repo)?;
+        let changes = write_notes(&repo, &notes)?;
+        let interval = chrono::Duration::minutes(settings.commit_interval_mins as i64);
+        let due = match last_commit_time(&repo) {
+            Some(last) => chrono::Local::now().fixed_offset() - last >= interval,
+            None => true,
+        };
+
+        if changes == 0 || !(force || due || changes >= settings.commit_after_changes as usize) {
+            return Ok((None, changes));
+        }
+        commit_all(&repo, changes).map(|commit| (Some(commit), 0))
+    })
+    .await
+    .map_err(|e| format!("Task panicked: {}", e))?;
+
+    let mut status = history.status.lock().unwrap();
+    match result {
+        Ok((commit, pending)) => {
+            if let Some(ref commit) = commit {
+                tracing::info!("Committed note history snapshot {}", commit);
+                status.last_commit = Some(chrono::Local::now().to_rfc3339());
+            }
+            status.pending_changes = pending;
+            status.last_error = None;
+            Ok(commit)
+        }
+        Err(e) => {
+            status.last_error = Some(e.clone());
+            Err(e)
+        }
+    }
+}
+
+/// Scheduler entry point
+pub async fn snapshot_notes(app: &AppHandle) -> Result<(), String> {
+    run_pass(app, false).await.map(|_| ())
+}
+
+/// Register or remove the snapshot job to match settings
+pub fn apply_settings(app: &AppHandle, settings: &NoteHistorySettings) {
+    let scheduler = app.state::<Scheduler>();
+    if !settings.enabled {
+        scheduler.remove_job(NOTE_HISTORY_JOB_ID);
+        return;
+    }
+    scheduler.upsert_job(
+        ScheduledJob {
+            id: NOTE_HISTORY_JOB_ID.to_string(),
+            name: "Note history snapshot".to_string(),
+            schedule: Schedule::Interval {
+                every_secs: CHECK_INTERVAL_SECS,
+            },
+            skip_on_battery: false,
+            wait_for_idle: false,
+            jitter_secs: 30,
+            action: JobAction::NoteHistorySnapshot,
+        },
+        chrono::Local::now(),
+    );
+}
+
+/// Load persisted settings and register the snapshot job if enabled
+pub fn start(app: &AppHandle) {
+    if let Ok(app_data_dir) = app.path().app_data_dir() {
+        apply_settings(app, &NoteHistorySettings::load(&app_data_dir));
+    }
+}
+
+// ============================================================
+// Commands
+// ============================================================
+
+/// Get the note history settings
+#[tauri::command]
+pub async fn get_note_history_settings(app: AppHandle) -> Result<NoteHistorySettings, AppError> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    Ok(NoteHistorySettings::load(&app_data_dir))
+}
+
+/// Update the note history settings
+#[tauri::command]
+pub async fn set_note_history_settings(
+    app: AppHandle,
+    settings: NoteHistorySettings,
+) -> Result<(), AppError> {
+    settings.validate()?;
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    settings.save(&app_data_dir)?;
+    apply_settings(&app, &settings);
+    Ok(())
+}
+
+/// Export and commit all notes now, returning the commit if anything changed
+#[tauri::command]
+pub async fn commit_note_history_now(app: AppHandle) -> Result<Option<String>, AppError> {
+    run_pass(&app, true).await.map_err(AppError::from)
+}
+
+/// Get the runtime status of note history
+#[tauri::command]
+pub async fn get_note_history_status(app: AppHandle) -> Result<NoteHistoryStatus, AppError> {
+    Ok(app.state::<NoteHistory>().status.lock().unwrap().clone())
+}
+
+/// List committed versions of a note, newest first
+#[tauri::command]
+pub async fn get_note_history(
+    app: AppHandle,
+    note_id: String,
+) -> Result<Vec<NoteVersion>, AppError> {
+    validate_note_id(&note_id)?;
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let repo = repo_dir(&app_data_dir);
+    tokio::task::spawn_blocking(move || note_log(&repo, &note_id))
+        .await?
+        .map_err(AppError::from)
+}
+
+/// Get a note's contents as of a commit
+#[tauri::command]
+pub async fn get_note_version(
+    app: AppHandle,
+    note_id: String,
+    commit: String,
+) -> Result<NoteSnapshot, AppError> {
+    validate_note_id(&note_id)?;
+    validate_commit(&commit)?;
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let repo = repo_dir(&app_data_dir);
+    tokio::task::spawn_blocking(move || read_version(&repo, &note_id, &commit))
+        .await?
+        .map_err(AppError::from)
+}
+
+/// Restore a note to its contents as of a commit
+///
+/// The restore is an ordinary edit, so it shows up in history as a new
+/// version and can itself be undone.
+#[tauri::command]
+pub async fn restore_note_version(
+    app: AppHandle,
+    note_id: String,
+    commit: String,
+) -> Result<NoteSnapshot, AppError> {
+    let snapshot = get_note_version(app.clone(), note_id.clone(), commit.clone()).await?;
+    crate::proxy::send_backend_request(
+        &app,
+        "PUT",
+        &format!("/notes/{}", note_id),
+        Some(&serde_json::json!({
+            "title": snapshot.title,
+            "content": snapshot.content,
+            "tags": snapshot.tags,
+            "folder": snapshot.folder,
+            "updateFolder": true,
+            "updateContentJson": true,
+            "isArchived": false,
+        })),
+        None,
+    )
+    .await?;
+    tracing::info!("Restored note {} to version {}", note_id, commit);
+    Ok(snapshot)
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn note(id: &str, title: &str, content: &str) -> ExportedNote {
+        ExportedNote {
+            id: id.to_string(),
+            title: title.to_string(),
+            content: content.to_string(),
+            tags: vec!["a".to_string(), "b: c".to_string()],
+            folder: Some("Work".to_string()),
+            updated_at: "2024-01-01T00:00:00Z".to_string(),
+            is_archived: false,
+        }
+    }
+
+    #[test]
+    fn test_render_parse_roundtrip() {
+        let original = note("n1", "Title: with \"quotes\"", "# Body\n\n---\nmore\n");
+        let parsed = parse_note(&render_note(&original)).unwrap();
+        assert_eq!(parsed.title, original.title);
+        assert_eq!(parsed.content, "# Body\n\n---\nmore");
+        assert_eq!(parsed.tags, original.tags);
+        assert_eq!(parsed.folder, original.folder);
+    }
+
+    #[test]
+    fn test_validation_rejects_unsafe_input() {
+        assert!(validate_note_id("3fa85f64-5717-4562-b3fc-2c963f66afa6").is_ok());
+        assert!(validate_note_id("../secrets").is_err());
+        assert!(validate_commit("abcdef1").is_ok());
+        assert!(validate_commit("--output=x").is_err());
+        assert!(validate_commit("HEAD").is_err());
+    }
+
+    #[test]
+    fn test_history_and_read_version() {
+        let dir = TempDir::new().unwrap();
+        let repo = dir.path().join("repo");
+        if ensure_repo(&repo).is_err() {
+            // git is not installed on this machine
+            return;
+        }
+        assert!(note_log(&repo, "n1").unwrap().is_empty());
+
+        assert_eq!(write_notes(&repo, &[note("n1", "First", "v1")]).unwrap(), 1);
+        let first = commit_all(&repo, 1).unwrap();
+        assert_eq!(write_notes(&repo, &[note("n1", "First", "v1")]).unwrap(), 0);
+
+        let changed = vec![note("n1", "First", "v2"), note("n2", "Second", "x")];
+        assert_eq!(write_notes(&repo, &changed).unwrap(), 2);
+        commit_all(&repo, 2).unwrap();
+
+        let history = note_log(&repo, "n1").unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[1].commit, first);
+        assert_eq!(read_version(&repo, "n1", &first).unwrap().content, "v1");
+        assert_eq!(note_log(&repo, "n2").unwrap().len(), 1);
+
+        // Removed notes leave the tree but keep their history
+        assert_eq!(write_notes(&repo, &[note("n2", "Second", "x")]).unwrap(), 1);
+        commit_all(&repo, 1).unwrap();
+        assert!(!repo.join(note_file("n1")).exists());
+        assert_eq!(note_log(&repo, "n1").unwrap().len(), 3);
+    }
+}
@@ -0,0 +1,372 @@
+//! Routing of shell notices to system notifications, the tray badge or the
+//! log.
+//!
+//! This module provides:
+//! - `notify`, used by shell subsystems instead of showing notifications
+//!   directly
+//! - Rules matching on source, severity, quiet hours and whether the app is
+//!   focused, where the first matching rule decides the route, saved in
+//!   notifications.json
+//! - `get_notification_rules`/`set_notification_rules` for the UI
+//!
+//! A focus session holds back notices from its blocked sources before the
+//! rules apply (see `focus`).
+//!
+//! Every notice is logged whatever its route. Events sent to the webview
+//! are separate and unaffected by these rules.
+
+use chrono::NaiveTime;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager, Runtime};
+use tauri_plugin_notification::NotificationExt;
+
+use crate::config::{load_json, save_json_atomic};
+use crate::error::AppError;
+
+/// How serious a notice is, mildest first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+    Critical,
+}
+
+/// Where a notice ends up
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Route {
+    /// A native notification
+    System,
+    /// A count on the tray icon until the main window is focused
+    TrayBadge,
+    /// Only the log
+    Log,
+}
+
+/// Something the shell wants the user to know
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Notice {
+    /// Subsystem it comes from, e.g. "disk_space"
+    pub source: &'static str,
+    pub severity: Severity,
+    pub title: String,
+    pub body: String,
+}
+
+impl Notice {
+    pub fn new(
+        source: &'static str,
+        severity: Severity,
+        title: impl Into<String>,
+        body: impl Into<String>,
+    ) -> Self {
+        Self {
+            source,
+            severity,
+            title: title.into(),
+            body: body.into(),
+        }
+    }
+}
+
+/// A rule; unset conditions match anything
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NotificationRule {
+    #[serde(default)]
+    pub source: Option<String>,
+    #[serde(default)]
+    pub min_severity: Option<Severity>,
+    /// Match only inside (true) or outside (false) quiet hours
+    #[serde(default)]
+    pub quiet_hours: Option<bool>,
+    /// Match only while an app window is (true) or isn't (false) focused
+    #[serde(default)]
+    pub focused: Option<bool>,
+    pub route: Route,
+}
+
+impl NotificationRule {
+    fn matches(&self, notice: &Notice, quiet: bool, focused: bool) -> bool {
+        self.source.as_deref().map_or(true, |s| s == notice.source)
+            && self.min_severity.map_or(true, |min| notice.severity >= min)
+            && self.quiet_hours.map_or(true, |q| q == quiet)
+            && self.focused.map_or(true, |f| f == focused)
+    }
+}
+
+/// Daily span with no system notifications unless a rule says otherwise,
+/// as local "HH:MM" times; may span midnight
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuietHours {
+    pub start: String,
+    pub end: String,
+}
+
+impl QuietHours {
+    fn parse(time: &str) -> Result<NaiveTime, String> {
+        NaiveTime::parse_from_str(time.trim(), "%H:%M")
+            .map_err(|_| format!("'{}' isn't a time like 22:00", time))
+    }
+
+    fn contains(&self, now: NaiveTime) -> bool {
+        let (Ok(start), Ok(end)) = (Self::parse(&self.start), Self::parse(&self.end)) else {
+            return false;
+        };
+        if start <= end {
+            start <= now && now < end
+        } else {
+            now >= start || now < end
+        }
+    }
+}
+
+/// Routing rules, tried in order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationRules {
+    pub rules: Vec<NotificationRule>,
+    pub quiet_hours: Option<QuietHours>,
+    /// Route when no rule matches
+    pub default_route: Route,
+}
+
+impl Default for NotificationRules {
+    fn default() -> Self {
+        let rule = |min_severity, quiet_hours, focused, route| NotificationRule {
+            source: None,
+            min_severity,
+            quiet_hours,
+            focused,
+            route,
+        };
+        Self {
+            rules: vec![
+                // Critical notices always get through
+                rule(Some(Severity::Critical), None, None, Route::System),
+                rule(Some(Severity::Warning), Some(true), None, Route::TrayBadge),
+                // The app already shows what's happening while in front
+                rule(None, None, Some(true), Route::Log),
+                rule(Some(Severity::Warning), None, None, Route::System),
+            ],
+            quiet_hours: None,
+            default_route: Route::Log,
+        }
+    }
+}
+
+impl NotificationRules {
+    fn path(app_data_dir: &Path) -> PathBuf {
+        app_data_dir.join("notifications.json")
+    }
+
+    /// Load rules, returning defaults if missing or invalid
+    pub fn load(app_data_dir: &Path) -> Self {
+        load_json(&Self::path(app_data_dir)).unwrap_or_default()
+    }
+
+    /// Save rules atomically
+    pub fn save(&self, app_data_dir: &Path) -> Result<(), String> {
+        save_json_atomic(&Self::path(app_data_dir), self)
+    }
+
+    /// Validate rules
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(quiet) = &self.quiet_hours {
+            QuietHours::parse(&quiet.start)?;
+            QuietHours::parse(&quiet.end)?;
+        }
+        if self
+            .rules
+            .iter()
+            .any(|rule| rule.source.as_deref().is_some_and(|s| s.trim().is_empty()))
+        {
+            return Err("A rule's source can't be blank; leave it unset to match any".to_string());
+        }
+        Ok(())
+    }
+
+    /// Route for a notice under the given conditions
+    fn route(&self, notice: &Notice, now: NaiveTime, focused: bool) -> Route {
+        let quiet = self
+            .quiet_hours
+            .as_ref()
+            .is_some_and(|hours| hours.contains(now));
+        self.rules
+            .iter()
+            .find(|rule| rule.matches(notice, quiet, focused))
+            .map_or(self.default_route, |rule| rule.route)
+    }
+}
+
+fn app_focused<R: Runtime>(app: &AppHandle<R>) -> bool {
+    app.webview_windows()
+        .values()
+        .any(|window| window.is_focused().unwrap_or(false))
+}
+
+/// Log a notice and deliver it wherever the rules send it
+pub fn notify<R: Runtime>(app: &AppHandle<R>, notice: Notice) -> Route {
+    let rules = app
+        .path()
+        .app_data_dir()
+        .map(|dir| NotificationRules::load(&dir))
+        .unwrap_or_default();
+    let now = chrono::Local::now().time();
+    let route = if crate::focus::holds_back(app, &notice) {
+        Route::Log
+    } else {
+        rules.route(&notice, now, app_focused(app))
+    };
+
+    match notice.severity {
+        Severity::Info => tracing::info!(source = notice.source, ?route, "{}", notice.title),
+        _ => tracing::warn!(
+            source = notice.source,
+            severity = ?notice.severity,
+            ?route,
+            "{}: {}",
+            notice.title,
+            notice.body
+        ),
+    }
+
+    match route {
+        Route::System => {
+            if let Err(e) = app
+                .notification()
+                .builder()
+                .title(&notice.title)
+                .body(&notice.body)
+                .show()
+            {
+                tracing::warn!("Failed to show notification: {}", e);
+            }
+        }
+        Route::TrayBadge => crate::tray::add_badge(app),
+        Route::Log => {}
+    }
+    route
+}
+
+// ============================================================
+// Commands
+// ============================================================
+
+/// Get the notification routing rules
+#[tauri::command]
+pub async fn get_notification_rules(app: AppHandle) -> Result<NotificationRules, AppError> {
+    let app_data_dir = app.path().app_data_dir()?;
+    Ok(NotificationRules::load(&app_data_dir))
+}
+
+/// Replace the notification routing rules
+#[tauri::command]
+pub async fn set_notification_rules(
+    app: AppHandle,
+    rules: NotificationRules,
+) -> Result<(), AppError> {
+    rules.validate()?;
+    let app_data_dir = app.path().app_data_dir()?;
+    rules.save(&app_data_dir)?;
+    Ok(())
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(h: u32, m: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(h, m, 0).unwrap()
+    }
+
+    fn notice(source: &'static str, severity: Severity) -> Notice {
+        Notice::new(source, severity, "Title", "Body")
+    }
+
+    #[test]
+    fn test_default_routes() {
+        let rules = NotificationRules {
+            quiet_hours: Some(QuietHours {
+                start: "22:00".to_string(),
+                end: "07:00".to_string(),
+            }),
+            ..NotificationRules::default()
+        };
+        let day = time(14, 0);
+        let night = time(23, 30);
+
+        let warning = notice("disk_space", Severity::Warning);
+        assert_eq!(rules.route(&warning, day, false), Route::System);
+        assert_eq!(rules.route(&warning, night, false), Route::TrayBadge);
+        assert_eq!(rules.route(&warning, day, true), Route::Log);
+
+        let critical = notice("disk_space", Severity::Critical);
+        assert_eq!(rules.route(&critical, night, true), Route::System);
+
+        let info = notice("clock", Severity::Info);
+        assert_eq!(rules.route(&info, day, false), Route::Log);
+    }
+
+    #[test]
+    fn test_source_rule() {
+        let rules = NotificationRules {
+            rules: vec![NotificationRule {
+                source: Some("usage".to_string()),
+                min_severity: None,
+                quiet_hours: None,
+                focused: None,
+                route: Route::TrayBadge,
+            }],
+            quiet_hours: None,
+            default_route: Route::System,
+        };
+        let now = time(12, 0);
+        assert_eq!(
+            rules.route(&notice("usage", Severity::Warning), now, false),
+            Route::TrayBadge
+        );
+        assert_eq!(
+            rules.route(&notice("clock", Severity::Warning), now, false),
+            Route::System
+        );
+    }
+
+    #[test]
+    fn test_quiet_hours() {
+        let overnight = QuietHours {
+            start: "22:00".to_string(),
+            end: "07:00".to_string(),
+        };
+        assert!(overnight.contains(time(23, 0)));
+        assert!(overnight.contains(time(6, 59)));
+        assert!(!overnight.contains(time(7, 0)));
+        assert!(!overnight.contains(time(12, 0)));
+
+        let lunch = QuietHours {
+            start: "12:00".to_string(),
+            end: "13:00".to_string(),
+        };
+        assert!(lunch.contains(time(12, 30)));
+        assert!(!lunch.contains(time(13, 30)));
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(NotificationRules::default().validate().is_ok());
+        let bad_time = NotificationRules {
+            quiet_hours: Some(QuietHours {
+                start: "10pm".to_string(),
+                end: "07:00".to_string(),
+            }),
+            ..NotificationRules::default()
+        };
+        assert!(bad_time.validate().is_err());
+    }
+}
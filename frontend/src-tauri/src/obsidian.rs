@@ -0,0 +1,1352 @@
+//! Two-way sync between an Obsidian vault folder and the backend.
+//!
+//! This module provides:
+//! - A file watcher that pushes vault edits and new files to the backend
+//! - Periodic export of notes to markdown files with deterministic names
+//! - A sync ledger of file hashes and modification times to tell which side changed
+//! - Conflict copies and `sync-conflict` events when both sides changed, with
+//!   both versions kept until the user picks one with `resolve_conflict`
+//!
+//! Exported files carry a small frontmatter block (`sb_id`, `title`, `tags`,
+//! `updated`). Files without `sb_id` were created in Obsidian and are imported
+//! as new notes keyed by their vault path.
+
+use notify::{RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::config::{load_json, save_json_atomic};
+use crate::error::AppError;
+use crate::journal;
+use crate::scheduler::{JobAction, Schedule, ScheduledJob, Scheduler, OBSIDIAN_SYNC_JOB_ID};
+
+/// Backend endpoint returning all notes with full content
+const EXPORT_NOTES_PATH: &str = "/notes/for-export";
+
+/// Backend endpoint that creates or updates notes by external ID
+const IMPORT_NOTES_PATH: &str = "/import/notes";
+
+/// External ID prefix for notes created in the vault
+const VAULT_ID_PREFIX: &str = "obsidian:";
+
+/// Marker in the names of conflict copies, which are never imported
+const CONFLICT_MARKER: &str = ".conflict-";
+
+/// Quiet period after the last file event before importing
+const WATCH_DEBOUNCE: Duration = Duration::from_secs(2);
+
+/// Longest file name stem generated from a note title
+const MAX_STEM_LEN: usize = 100;
+
+/// Suffix for a vault edit kept as a separate note when resolving a conflict
+const VAULT_COPY_SUFFIX: &str = " (vault copy)";
+
+/// Obsidian sync settings
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ObsidianSyncSettings {
+    /// Vault root, sync disabled when None
+    pub vault_path: Option<PathBuf>,
+    /// Folder inside the vault that mirrors the app's notes
+    pub subfolder: String,
+    /// Minutes between full sync passes (app→vault export)
+    pub export_interval_mins: u32,
+}
+
+impl Default for ObsidianSyncSettings {
+    fn default() -> Self {
+        Self {
+            vault_path: None,
+            subfolder: "Second Brain".to_string(),
+            export_interval_mins: 15,
+        }
+    }
+}
+
+impl ObsidianSyncSettings {
+    fn path(app_data_dir: &Path) -> PathBuf {
+        app_data_dir.join("obsidian-sync.json")
+    }
+
+    /// Load settings, returning defaults if missing or invalid
+    pub fn load(app_data_dir: &Path) -> Self {
+        load_json(&Self::path(app_data_dir)).unwrap_or_default()
+    }
+
+    /// Save settings through the state journal
+    pub fn save(&self, app_data_dir: &Path) -> Result<(), String> {
+        journal::write(app_data_dir, "obsidian-sync.json", self)
+    }
+
+    /// Validate settings
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(ref vault) = self.vault_path {
+            if !vault.is_absolute() || !vault.is_dir() {
+                return Err(format!("Vault folder not found: {}", vault.display()));
+            }
+        }
+        if self.subfolder.contains("..") || Path::new(&self.subfolder).is_absolute() {
+            return Err("Sync folder must be a relative path inside the vault".to_string());
+        }
+        if self.export_interval_mins == 0 {
+            return Err("Export interval must be at least 1 minute".to_string());
+        }
+        Ok(())
+    }
+
+    /// Folder that mirrors the app's notes
+    pub fn sync_root(&self) -> Option<PathBuf> {
+        self.vault_path
+            .as_ref()
+            .map(|vault| vault.join(self.subfolder.trim()))
+    }
+}
+
+/// A note as returned by the export endpoint
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RemoteNote {
+    id: String,
+    title: String,
+    content: String,
+    #[serde(default)]
+    tags: Vec<String>,
+    folder: Option<String>,
+    updated_at: String,
+    #[serde(default)]
+    is_archived: bool,
+    external_id: Option<String>,
+}
+
+/// Sync state of one vault file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct LedgerEntry {
+    /// Path relative to the sync root, `/`-separated
+    path: String,
+    /// Backend note ID, unknown until a vault-created note is exported back
+    note_id: Option<String>,
+    /// Hash of the file as last written or imported
+    file_hash: String,
+    /// Note `updatedAt` as of the last sync
+    note_updated_at: Option<String>,
+    /// File modification time as of the last sync; unchanged files aren't re-read
+    #[serde(default)]
+    file_modified_ms: Option<u64>,
+}
+
+/// Persisted sync ledger
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct SyncLedger {
+    entries: Vec<LedgerEntry>,
+}
+
+impl SyncLedger {
+    fn path(app_data_dir: &Path) -> PathBuf {
+        app_data_dir.join("obsidian-sync-state.json")
+    }
+
+    fn load(app_data_dir: &Path) -> Self {
+        load_json(&Self::path(app_data_dir)).unwrap_or_default()
+    }
+
+    fn save(&self, app_data_dir: &Path) -> Result<(), String> {
+        save_json_atomic(&Self::path(app_data_dir), self)
+    }
+
+    fn by_path(&self, path: &str) -> Option<&LedgerEntry> {
+        self.entries.iter().find(|e| e.path == path)
+    }
+
+    fn by_note(&self, note_id: &str) -> Option<&LedgerEntry> {
+        self.entries
+            .iter()
+            .find(|e| e.note_id.as_deref() == Some(note_id))
+    }
+
+    /// Replace any entry for the same path or note
+    fn upsert(&mut self, entry: LedgerEntry) {
+        self.entries.retain(|e| {
+            e.path != entry.path && (entry.note_id.is_none() || e.note_id != entry.note_id)
+        });
+        self.entries.push(entry);
+    }
+}
+
+/// Why a conflict was raised
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictKind {
+    /// The note and its file both changed since the last sync
+    BothModified,
+    /// The note was deleted in the app but its file was edited
+    DeletedInApp,
+}
+
+/// An unresolved conflict, emitted as `sync-conflict` and kept until resolved
+///
+/// While a conflict is pending, neither side of its file is synced.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncConflict {
+    pub id: String,
+    pub kind: ConflictKind,
+    pub note_id: Option<String>,
+    /// Vault file involved, relative to the sync folder
+    pub path: String,
+    /// Copy holding the other version, relative to the sync folder
+    pub conflict_path: String,
+    /// Detection time (RFC 3339)
+    pub detected_at: String,
+}
+
+/// Which version to keep when resolving a conflict
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictResolution {
+    /// Keep the vault file, replacing the app's version
+    Vault,
+    /// Keep the app's version, replacing the vault file
+    App,
+    /// Keep the app's version and import the vault edit as a separate note
+    Both,
+}
+
+fn conflicts_path(app_data_dir: &Path) -> PathBuf {
+    app_data_dir.join("obsidian-sync-conflicts.json")
+}
+
+fn load_conflicts(app_data_dir: &Path) -> Vec<SyncConflict> {
+    load_json(&conflicts_path(app_data_dir)).unwrap_or_default()
+}
+
+fn save_conflicts(app_data_dir: &Path, conflicts: &[SyncConflict]) -> Result<(), String> {
+    save_json_atomic(&conflicts_path(app_data_dir), &conflicts)
+}
+
+/// Runtime sync status
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ObsidianSyncStatus {
+    pub enabled: bool,
+    pub watching: bool,
+    /// Last completed export (RFC 3339)
+    pub last_export: Option<String>,
+    /// Last completed import (RFC 3339)
+    pub last_import: Option<String>,
+    pub exported_count: usize,
+    pub imported_count: usize,
+    pub conflict_count: usize,
+    pub last_error: Option<String>,
+}
+
+/// Managed state for vault sync
+#[derive(Default)]
+pub struct ObsidianSync {
+    lock: tokio::sync::Mutex<()>,
+    watcher: Mutex<Option<notify::RecommendedWatcher>>,
+    status: Mutex<ObsidianSyncStatus>,
+}
+
+impl ObsidianSync {
+    fn update(&self, f: impl FnOnce(&mut ObsidianSyncStatus)) {
+        f(&mut self.status.lock().unwrap());
+    }
+}
+
+// ============================================================
+// Files
+// ============================================================
+
+fn file_modified_ms(path: &Path) -> Option<u64> {
+    let modified = std::fs::metadata(path).and_then(|m| m.modified()).ok()?;
+    modified
+        .duration_since(std::time::UNIX_EPOCH)
+        .ok()
+        .map(|d| d.as_millis() as u64)
+}
+
+fn hash(content: &str) -> String {
+    Sha256::digest(content.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Make a string safe as an Obsidian file or folder name
+pub(crate) fn sanitize_component(name: &str) -> String {
+    let replaced: String = name
+        .chars()
+        .map(|c| match c {
+            '*' | '"' | '\\' | '/' | '<' | '>' | ':' | '|' | '?' | '#' | '^' | '[' | ']' => '-',
+            c if c.is_control() => ' ',
+            c => c,
+        })
+        .collect();
+    let collapsed = replaced.split_whitespace().collect::<Vec<_>>().join(" ");
+    let trimmed: String = collapsed
+        .trim_matches(|c: char| c == '.' || c == ' ')
+        .chars()
+        .take(MAX_STEM_LEN)
+        .collect();
+    if trimmed.is_empty() {
+        "Untitled".to_string()
+    } else {
+        trimmed.trim_end().to_string()
+    }
+}
+
+/// Preferred and fallback (ID-suffixed) paths for a note
+fn candidate_paths(note: &RemoteNote) -> (String, String) {
+    let mut dir: Vec<String> = note
+        .folder
+        .as_deref()
+        .unwrap_or("")
+        .split('/')
+        .filter(|part| !part.trim().is_empty())
+        .map(sanitize_component)
+        .collect();
+    let stem = sanitize_component(&note.title);
+    let short_id: String = note.id.chars().take(8).collect();
+
+    dir.push(format!("{}.md", stem));
+    let preferred = dir.join("/");
+    dir.pop();
+    dir.push(format!("{} ({}).md", stem, short_id));
+    (preferred, dir.join("/"))
+}
+
+/// Assign a unique, deterministic path to every note
+///
+/// Notes created in the vault keep their original path. Notes already synced
+/// keep their current path while their title is unchanged, so a new note with
+/// the same title never renames an existing file.
+fn assign_paths(notes: &[&RemoteNote], ledger: &SyncLedger) -> HashMap<String, String> {
+    let mut sorted: Vec<&RemoteNote> = notes.to_vec();
+    sorted.sort_by(|a, b| a.id.cmp(&b.id));
+
+    let mut paths = HashMap::new();
+    let mut taken = HashSet::new();
+
+    let vault_path = |note: &RemoteNote| {
+        note.external_id
+            .as_deref()
+            .and_then(|id| id.strip_prefix(VAULT_ID_PREFIX))
+            .map(str::to_string)
+    };
+
+    // Vault-created notes and notes whose current path is still valid first
+    for note in &sorted {
+        let existing = vault_path(note).or_else(|| {
+            let current = ledger.by_note(&note.id)?.path.clone();
+            let (preferred, fallback) = candidate_paths(note);
+            (current == preferred || current == fallback).then_some(current)
+        });
+        if let Some(path) = existing {
+            if taken.insert(path.to_lowercase()) {
+                paths.insert(note.id.clone(), path);
+            }
+        }
+    }
+
+    for note in &sorted {
+        if paths.contains_key(&note.id) {
+            continue;
+        }
+        let (preferred, fallback) = candidate_paths(note);
+        let path = if taken.insert(preferred.to_lowercase()) {
+            preferred
+        } else {
+            taken.insert(fallback.to_lowercase());
+            fallback
+        };
+        paths.insert(note.id.clone(), path);
+    }
+    paths
+}
+
+fn render_note(note: &RemoteNote) -> String {
+    format!(
+        "---\nsb_id: {}\ntitle: {}\ntags: {}\nupdated: {}\n---\n\n{}\n",
+        note.id,
+        serde_json::to_string(&note.title).unwrap_or_default(),
+        serde_json::to_string(&note.tags).unwrap_or_default(),
+        note.updated_at,
+        note.content.trim_end()
+    )
+}
+
+/// A vault file split into frontmatter fields and body
+#[derive(Debug, Clone, Default, PartialEq)]
+struct VaultNote {
+    sb_id: Option<String>,
+    title: Option<String>,
+    tags: Vec<String>,
+    body: String,
+}
+
+/// Parse the frontmatter fields written by `render_note`
+///
+/// Only flat `key: value` lines are understood; other keys are ignored and
+/// files without frontmatter are treated as plain bodies.
+fn parse_note(content: &str) -> VaultNote {
+    let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+    let Some(rest) = content
+        .strip_prefix("---\n")
+        .or_else(|| content.strip_prefix("---\r\n"))
+    else {
+        return VaultNote {
+            body: content.trim().to_string(),
+            ..Default::default()
+        };
+    };
+    let Some(end) = rest.find("\n---") else {
+        return VaultNote {
+            body: content.trim().to_string(),
+            ..Default::default()
+        };
+    };
+
+    let mut note = VaultNote {
+        body: rest[end + 4..].trim().to_string(),
+        ..Default::default()
+    };
+    for line in rest[..end].lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        match key.trim() {
+            "sb_id" if !value.is_empty() => note.sb_id = Some(value.to_string()),
+            "title" => {
+                note.title = Some(
+                    serde_json::from_str::<String>(value)
+                        .unwrap_or_else(|_| value.trim_matches('\'').to_string()),
+                )
+            }
+            "tags" => {
+                note.tags = serde_json::from_str::<Vec<String>>(value).unwrap_or_else(|_| {
+                    value
+                        .trim_matches(|c| c == '[' || c == ']')
+                        .split(',')
+                        .map(|t| t.trim().trim_matches(|c| c == '"' || c == '\'').to_string())
+                        .filter(|t| !t.is_empty())
+                        .collect()
+                })
+            }
+            _ => {}
+        }
+    }
+    note
+}
+
+/// Re-render a vault file without `sb_id` so it is imported as a new note
+fn detach(content: &str, title_suffix: &str) -> String {
+    let parsed = parse_note(content);
+    format!(
+        "---\ntitle: {}\ntags: {}\n---\n\n{}\n",
+        serde_json::to_string(&format!(
+            "{}{}",
+            parsed.title.unwrap_or_default(),
+            title_suffix
+        ))
+        .unwrap_or_default(),
+        serde_json::to_string(&parsed.tags).unwrap_or_default(),
+        parsed.body
+    )
+}
+
+/// A free path next to `rel` for a file that must not replace it
+fn unused_path(root: &Path, rel: &str, suffix: &str) -> String {
+    let stem = rel.strip_suffix(".md").unwrap_or(rel);
+    let mut candidate = format!("{}{}.md", stem, suffix);
+    let mut n = 2;
+    while root.join(&candidate).exists() {
+        candidate = format!("{}{} {}.md", stem, suffix, n);
+        n += 1;
+    }
+    candidate
+}
+
+/// Whether a note changed in the app since the ledger last saw it
+fn changed_in_app(entry: &LedgerEntry, remote_updated_at: Option<&str>) -> bool {
+    match (entry.note_updated_at.as_deref(), remote_updated_at) {
+        (Some(synced), Some(remote)) => synced != remote,
+        _ => false,
+    }
+}
+
+fn is_syncable(rel: &str) -> bool {
+    rel.ends_with(".md")
+        && !rel.contains(CONFLICT_MARKER)
+        && !rel.split('/').any(|part| part.starts_with('.'))
+}
+
+/// Markdown files under `root` as `/`-separated relative paths
+fn scan_files(root: &Path) -> Vec<String> {
+    fn walk(root: &Path, dir: &Path, out: &mut Vec<String>) {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                if !entry.file_name().to_string_lossy().starts_with('.') {
+                    walk(root, &path, out);
+                }
+            } else if let Ok(rel) = path.strip_prefix(root) {
+                let rel = rel
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                if is_syncable(&rel) {
+                    out.push(rel);
+                }
+            }
+        }
+    }
+
+    let mut files = Vec::new();
+    walk(root, root, &mut files);
+    files.sort();
+    files
+}
+
+fn write_file(root: &Path, rel: &str, content: &str) -> Result<(), String> {
+    let path = root.join(rel);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let file_name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let temp_path = path.with_file_name(format!(".{}.tmp", file_name));
+    std::fs::write(&temp_path, content)
+        .map_err(|e| format!("Failed to write {}: {}", temp_path.display(), e))?;
+    std::fs::rename(&temp_path, &path)
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+fn conflict_path(rel: &str) -> String {
+    let stem = rel.strip_suffix(".md").unwrap_or(rel);
+    format!(
+        "{}{}{}.md",
+        stem,
+        CONFLICT_MARKER,
+        chrono::Local::now().format("%Y%m%d-%H%M%S")
+    )
+}
+
+// ============================================================
+// Sync passes
+// ============================================================
+
+/// Record a conflict and emit `sync-conflict`
+fn raise_conflict(
+    app: &AppHandle,
+    conflicts: &mut Vec<SyncConflict>,
+    kind: ConflictKind,
+    note_id: Option<String>,
+    path: &str,
+    conflict_path: String,
+) {
+    let conflict = SyncConflict {
+        id: hash(&format!("{}\n{}", path, conflict_path))[..12].to_string(),
+        kind,
+        note_id,
+        path: path.to_string(),
+        conflict_path,
+        detected_at: chrono::Local::now().to_rfc3339(),
+    };
+    tracing::warn!(
+        "Obsidian sync conflict ({:?}) for {}, saved {}",
+        conflict.kind,
+        conflict.path,
+        conflict.conflict_path
+    );
+    app.state::<ObsidianSync>()
+        .update(|s| s.conflict_count += 1);
+    let _ = app.emit("sync-conflict", &conflict);
+    conflicts.push(conflict);
+}
+
+async fn fetch_notes(app: &AppHandle) -> Result<Vec<RemoteNote>, String> {
+    let response =
+        crate::proxy::send_backend_request(app, "GET", EXPORT_NOTES_PATH, None, None).await?;
+    serde_json::from_value(response).map_err(|e| format!("Invalid notes response: {}", e))
+}
+
+/// Push new and edited vault files to the backend
+async fn import_pass(
+    app: &AppHandle,
+    root: &Path,
+    ledger: &mut SyncLedger,
+    conflicts: &mut Vec<SyncConflict>,
+) -> Result<usize, String> {
+    let files = scan_files(root);
+    let pending: HashSet<String> = conflicts.iter().map(|c| c.path.clone()).collect();
+    // Fetched on the first edited file that may also have changed in the app
+    let mut remote: Option<HashMap<String, RemoteNote>> = None;
+    let mut imported = 0;
+
+    for rel in &files {
+        if pending.contains(rel) {
+            continue;
+        }
+        let entry = ledger.by_path(rel).cloned();
+        let modified_ms = file_modified_ms(&root.join(rel));
+        if entry
+            .as_ref()
+            .is_some_and(|e| e.file_modified_ms.is_some() && e.file_modified_ms == modified_ms)
+        {
+            continue;
+        }
+        let Ok(content) = std::fs::read_to_string(root.join(rel)) else {
+            continue;
+        };
+        let file_hash = hash(&content);
+        if let Some(ref entry) = entry {
+            if entry.file_hash == file_hash {
+                // Touched but not edited
+                ledger.upsert(LedgerEntry {
+                    file_modified_ms: modified_ms,
+                    ..entry.clone()
+                });
+                continue;
+            }
+        }
+
+        let parsed = parse_note(&content);
+        if let (Some(ref id), Some(ref entry)) = (&parsed.sb_id, &entry) {
+            if remote.is_none() {
+                remote = Some(
+                    fetch_notes(app)
+                        .await?
+                        .into_iter()
+                        .map(|n| (n.id.clone(), n))
+                        .collect(),
+                );
+            }
+            let note = remote.as_ref().and_then(|notes| notes.get(id));
+            if changed_in_app(entry, note.map(|n| n.updated_at.as_str())) {
+                // Both sides changed; keep the app's version next to the file
+                let note = note.expect("changed_in_app requires a remote note");
+                let conflict = conflict_path(rel);
+                write_file(root, &conflict, &render_note(note))?;
+                raise_conflict(
+                    app,
+                    conflicts,
+                    ConflictKind::BothModified,
+                    Some(id.clone()),
+                    rel,
+                    conflict,
+                );
+                continue;
+            }
+        }
+
+        let title = parsed.title.clone().unwrap_or_else(|| {
+            Path::new(rel)
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_default()
+        });
+
+        let (note_id, note_updated_at) = match parsed.sb_id {
+            Some(ref id) => {
+                let response = crate::proxy::send_backend_request(
+                    app,
+                    "PUT",
+                    &format!("/notes/{}", id),
+                    Some(&serde_json::json!({
+                        "title": title,
+                        "content": parsed.body,
+                        "tags": parsed.tags,
+                        // Markdown from the vault becomes the canonical content
+                        "updateContentJson": true,
+                    })),
+                    None,
+                )
+                .await?;
+                let updated = response
+                    .get("updatedAt")
+                    .and_then(|v| v.as_str())
+                    .map(str::to_string);
+                (Some(id.clone()), updated)
+            }
+            None => {
+                let folder = rel.rsplit_once('/').map(|(dir, _)| dir.to_string());
+                let modified = std::fs::metadata(root.join(rel))
+                    .and_then(|m| m.modified())
+                    .map(chrono::DateTime::<chrono::Utc>::from)
+                    .unwrap_or_else(|_| chrono::Utc::now())
+                    .to_rfc3339();
+                crate::proxy::send_backend_request(
+                    app,
+                    "POST",
+                    IMPORT_NOTES_PATH,
+                    Some(&serde_json::json!({
+                        "notes": [{
+                            "id": format!("{}{}", VAULT_ID_PREFIX, rel),
+                            "title": title,
+                            "body": parsed.body,
+                            "folder": folder,
+                            "source": "obsidian",
+                            "created_at": modified,
+                            "updated_at": modified,
+                            "tags": parsed.tags,
+                        }]
+                    })),
+                    None,
+                )
+                .await?;
+                (entry.as_ref().and_then(|e| e.note_id.clone()), None)
+            }
+        };
+
+        ledger.upsert(LedgerEntry {
+            path: rel.clone(),
+            note_id,
+            file_hash,
+            note_updated_at,
+            file_modified_ms: modified_ms,
+        });
+        imported += 1;
+    }
+
+    // Files deleted in the vault archive their note rather than deleting it
+    let present: HashSet<&String> = files.iter().collect();
+    let removed: Vec<LedgerEntry> = ledger
+        .entries
+        .iter()
+        .filter(|e| !present.contains(&e.path) && !pending.contains(&e.path))
+        .cloned()
+        .collect();
+    for entry in removed {
+        if let Some(ref id) = entry.note_id {
+            crate::proxy::send_backend_request(
+                app,
+                "PUT",
+                &format!("/notes/{}", id),
+                Some(&serde_json::json!({ "isArchived": true })),
+                None,
+            )
+            .await?;
+            tracing::info!("Archived note {} deleted from the vault", id);
+        }
+        ledger.entries.retain(|e| e.path != entry.path);
+    }
+
+    Ok(imported)
+}
+
+/// Write new and changed notes to the vault
+async fn export_pass(
+    app: &AppHandle,
+    root: &Path,
+    ledger: &mut SyncLedger,
+    conflicts: &mut Vec<SyncConflict>,
+) -> Result<usize, String> {
+    let notes = fetch_notes(app).await?;
+    let pending: HashSet<String> = conflicts.iter().map(|c| c.path.clone()).collect();
+    let active: Vec<&RemoteNote> = notes.iter().filter(|n| !n.is_archived).collect();
+    let paths = assign_paths(&active, ledger);
+    let mut exported = 0;
+
+    for note in &active {
+        let target = &paths[&note.id];
+        let entry = ledger
+            .by_note(&note.id)
+            .or_else(|| ledger.by_path(target))
+            .cloned();
+
+        if let Some(ref entry) = entry {
+            if pending.contains(&entry.path)
+                || (entry.note_updated_at.as_deref() == Some(note.updated_at.as_str())
+                    && entry.path == *target)
+            {
+                continue;
+            }
+
+            let on_disk = std::fs::read_to_string(root.join(&entry.path)).ok();
+            if on_disk.is_some_and(|content| hash(&content) != entry.file_hash) {
+                // The file changed too; keep both versions until the user picks one
+                let conflict = conflict_path(&entry.path);
+                write_file(root, &conflict, &render_note(note))?;
+                raise_conflict(
+                    app,
+                    conflicts,
+                    ConflictKind::BothModified,
+                    Some(note.id.clone()),
+                    &entry.path,
+                    conflict,
+                );
+                continue;
+            }
+        } else if root.join(target).exists() {
+            // Unknown file at the target; leave it for the next import pass
+            continue;
+        }
+
+        let content = render_note(note);
+        write_file(root, target, &content)?;
+        if let Some(ref entry) = entry {
+            if entry.path != *target {
+                let _ = std::fs::remove_file(root.join(&entry.path));
+            }
+        }
+        ledger.upsert(LedgerEntry {
+            path: target.clone(),
+            note_id: Some(note.id.clone()),
+            file_hash: hash(&content),
+            note_updated_at: Some(note.updated_at.clone()),
+            file_modified_ms: file_modified_ms(&root.join(target)),
+        });
+        exported += 1;
+    }
+
+    // Notes deleted or archived in the app
+    let live: HashSet<&str> = active.iter().map(|n| n.id.as_str()).collect();
+    let removed: Vec<LedgerEntry> = ledger
+        .entries
+        .iter()
+        .filter(|e| e.note_id.as_deref().is_some_and(|id| !live.contains(id)))
+        .filter(|e| !pending.contains(&e.path))
+        .cloned()
+        .collect();
+    for entry in removed {
+        let path = root.join(&entry.path);
+        match std::fs::read_to_string(&path) {
+            Ok(content) if hash(&content) != entry.file_hash => {
+                // Edited in the vault after the note was deleted; keep the edit
+                let conflict = conflict_path(&entry.path);
+                std::fs::rename(&path, root.join(&conflict))
+                    .map_err(|e| format!("Failed to move {}: {}", path.display(), e))?;
+                raise_conflict(
+                    app,
+                    conflicts,
+                    ConflictKind::DeletedInApp,
+                    entry.note_id.clone(),
+                    &entry.path,
+                    conflict,
+                );
+            }
+            Ok(_) => {
+                let _ = std::fs::remove_file(&path);
+            }
+            Err(_) => {}
+        }
+        ledger.entries.retain(|e| e.path != entry.path);
+    }
+
+    Ok(exported)
+}
+
+/// Run an import pass and, if requested, an export pass
+async fn run_sync(app: &AppHandle, export: bool) -> Result<(), String> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let settings = ObsidianSyncSettings::load(&app_data_dir);
+    let Some(root) = settings.sync_root() else {
+        return Ok(());
+    };
+
+    let sync = app.state::<ObsidianSync>();
+    let _guard = sync.lock.lock().await;
+    std::fs::create_dir_all(&root)
+        .map_err(|e| format!("Failed to create {}: {}", root.display(), e))?;
+
+    let mut ledger = SyncLedger::load(&app_data_dir);
+    let mut conflicts = load_conflicts(&app_data_dir);
+    let result = async {
+        let imported = import_pass(app, &root, &mut ledger, &mut conflicts).await?;
+        sync.update(|s| {
+            s.imported_count += imported;
+            s.last_import = Some(chrono::Local::now().to_rfc3339());
+        });
+
+        if export {
+            let exported = export_pass(app, &root, &mut ledger, &mut conflicts).await?;
+            sync.update(|s| {
+                s.exported_count += exported;
+                s.last_export = Some(chrono::Local::now().to_rfc3339());
+            });
+        }
+        Ok::<(), String>(())
+    }
+    .await;
+
+    // Keep progress made before a failure
+    ledger.save(&app_data_dir)?;
+    save_conflicts(&app_data_dir, &conflicts)?;
+    sync.update(|s| s.last_error = result.as_ref().err().cloned());
+    let _ = app.emit(
+        "obsidian-sync-completed",
+        sync.status.lock().unwrap().clone(),
+    );
+    result
+}
+
+/// Full two-way sync pass, run by the scheduler
+pub async fn sync_vault(app: &AppHandle) -> Result<(), String> {
+    run_sync(app, true).await
+}
+
+/// Watch the sync folder and import changes after a quiet period
+fn start_watcher(app: &AppHandle, root: &Path) -> Result<(), String> {
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<()>();
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        if let Ok(event) = event {
+            let relevant = event.paths.iter().any(|p| {
+                p.extension().is_some_and(|ext| ext == "md")
+                    && !p.to_string_lossy().contains(CONFLICT_MARKER)
+            });
+            if relevant {
+                let _ = tx.send(());
+            }
+        }
+    })
+    .map_err(|e| format!("Failed to create vault watcher: {}", e))?;
+    watcher
+        .watch(root, RecursiveMode::Recursive)
+        .map_err(|e| format!("Failed to watch {}: {}", root.display(), e))?;
+
+    let app_for_task = app.clone();
+    tauri::async_runtime::spawn(async move {
+        // Ends when the watcher (and its sender) is dropped
+        while rx.recv().await.is_some() {
+            while let Ok(Some(())) = tokio::time::timeout(WATCH_DEBOUNCE, rx.recv()).await {}
+            if !*app_for_task
+                .state::<crate::AppState>()
+                .is_backend_ready
+                .read()
+            {
+                continue;
+            }
+            if let Err(e) = run_sync(&app_for_task, false).await {
+                tracing::warn!("Obsidian vault import failed: {}", e);
+            }
+        }
+    });
+
+    *app.state::<ObsidianSync>().watcher.lock().unwrap() = Some(watcher);
+    Ok(())
+}
+
+/// Apply settings: (re)start the watcher and schedule periodic sync
+pub fn apply_settings(app: &AppHandle, settings: &ObsidianSyncSettings) {
+    let sync = app.state::<ObsidianSync>();
+    // Dropping the watcher stops it and ends its import task
+    sync.watcher.lock().unwrap().take();
+    app.state::<Scheduler>().remove_job(OBSIDIAN_SYNC_JOB_ID);
+
+    let root = settings.sync_root();
+    sync.update(|s| {
+        s.enabled = root.is_some();
+        s.watching = false;
+    });
+    let Some(root) = root else {
+        return;
+    };
+
+    if let Err(e) = std::fs::create_dir_all(&root)
+        .map_err(|e| format!("Failed to create {}: {}", root.display(), e))
+        .and_then(|_| start_watcher(app, &root))
+    {
+        tracing::warn!("{}", e);
+        sync.update(|s| s.last_error = Some(e));
+    } else {
+        sync.update(|s| s.watching = true);
+    }
+
+    app.state::<Scheduler>().upsert_job(
+        ScheduledJob {
+            id: OBSIDIAN_SYNC_JOB_ID.to_string(),
+            name: "Obsidian vault sync".to_string(),
+            schedule: Schedule::Interval {
+                every_secs: settings.export_interval_mins.max(1) as u64 * 60,
+            },
+            skip_on_battery: false,
+            wait_for_idle: false,
+            jitter_secs: 15,
+            action: JobAction::ObsidianSync,
+        },
+        chrono::Local::now(),
+    );
+}
+
+/// Load persisted settings and start syncing if a vault is configured
+pub fn start(app: &AppHandle) {
+    if let Ok(app_data_dir) = app.path().app_data_dir() {
+        apply_settings(app, &ObsidianSyncSettings::load(&app_data_dir));
+    }
+}
+
+/// Get the Obsidian sync settings
+#[tauri::command]
+pub async fn get_obsidian_settings(app: AppHandle) -> Result<ObsidianSyncSettings, AppError> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    Ok(ObsidianSyncSettings::load(&app_data_dir))
+}
+
+/// Update the Obsidian sync settings
+///
+/// Changing the vault or folder resets the sync ledger, so the next pass
+/// treats every file in the new location as unsynced.
+#[tauri::command]
+pub async fn set_obsidian_settings(
+    app: AppHandle,
+    settings: ObsidianSyncSettings,
+) -> Result<(), AppError> {
+    settings.validate()?;
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+
+    let previous = ObsidianSyncSettings::load(&app_data_dir);
+    if previous.sync_root() != settings.sync_root() {
+        // The ledger and conflicts belong to the old root, so they go with
+        // the settings change or not at all
+        let sync = app.state::<ObsidianSync>();
+        let _guard = sync.lock.lock().await;
+        journal::Batch::new()
+            .remove("obsidian-sync-state.json")?
+            .remove("obsidian-sync-conflicts.json")?
+            .write("obsidian-sync.json", &settings)?
+            .commit(&app_data_dir)?;
+    } else {
+        settings.save(&app_data_dir)?;
+    }
+    apply_settings(&app, &settings);
+    Ok(())
+}
+
+/// Run a full two-way sync now
+#[tauri::command]
+pub async fn sync_obsidian_now(app: AppHandle) -> Result<ObsidianSyncStatus, AppError> {
+    sync_vault(&app).await?;
+    Ok(app.state::<ObsidianSync>().status.lock().unwrap().clone())
+}
+
+/// Get the Obsidian sync status
+#[tauri::command]
+pub async fn get_obsidian_sync_status(app: AppHandle) -> Result<ObsidianSyncStatus, AppError> {
+    Ok(app.state::<ObsidianSync>().status.lock().unwrap().clone())
+}
+
+/// List unresolved sync conflicts
+#[tauri::command]
+pub async fn list_sync_conflicts(app: AppHandle) -> Result<Vec<SyncConflict>, AppError> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    Ok(load_conflicts(&app_data_dir))
+}
+
+/// Write the app's current version of a note over its vault file
+async fn keep_app_version(
+    app: &AppHandle,
+    root: &Path,
+    ledger: &mut SyncLedger,
+    conflict: &SyncConflict,
+    note_id: &str,
+) -> Result<(), String> {
+    let note = fetch_notes(app)
+        .await?
+        .into_iter()
+        .find(|n| n.id == note_id)
+        .ok_or_else(|| format!("Note {} no longer exists", note_id))?;
+    let content = render_note(&note);
+    write_file(root, &conflict.path, &content)?;
+    ledger.upsert(LedgerEntry {
+        path: conflict.path.clone(),
+        note_id: Some(note.id.clone()),
+        file_hash: hash(&content),
+        note_updated_at: Some(note.updated_at.clone()),
+        file_modified_ms: file_modified_ms(&root.join(&conflict.path)),
+    });
+    Ok(())
+}
+
+/// Resolve a sync conflict by keeping the vault version, the app version, or both
+///
+/// Keeping both leaves the app's version in place and turns the vault edit
+/// into a separate note, imported on the next pass.
+#[tauri::command]
+pub async fn resolve_conflict(
+    app: AppHandle,
+    id: String,
+    keep: ConflictResolution,
+) -> Result<(), AppError> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let root = ObsidianSyncSettings::load(&app_data_dir)
+        .sync_root()
+        .ok_or_else(|| "Obsidian sync is not configured".to_string())?;
+
+    {
+        let sync = app.state::<ObsidianSync>();
+        let _guard = sync.lock.lock().await;
+        let mut conflicts = load_conflicts(&app_data_dir);
+        let conflict = conflicts
+            .iter()
+            .find(|c| c.id == id)
+            .cloned()
+            .ok_or_else(|| format!("Conflict not found: {}", id))?;
+        let mut ledger = SyncLedger::load(&app_data_dir);
+        let read = |rel: &str| {
+            std::fs::read_to_string(root.join(rel))
+                .map_err(|e| format!("Failed to read {}: {}", rel, e))
+        };
+
+        match (conflict.kind, keep, conflict.note_id.as_deref()) {
+            (ConflictKind::BothModified, ConflictResolution::Vault, Some(note_id)) => {
+                let content = read(&conflict.path)?;
+                let parsed = parse_note(&content);
+                let response = crate::proxy::send_backend_request(
+                    &app,
+                    "PUT",
+                    &format!("/notes/{}", note_id),
+                    Some(&serde_json::json!({
+                        "title": parsed.title,
+                        "content": parsed.body,
+                        "tags": parsed.tags,
+                        "updateContentJson": true,
+                    })),
+                    None,
+                )
+                .await?;
+                ledger.upsert(LedgerEntry {
+                    path: conflict.path.clone(),
+                    note_id: Some(note_id.to_string()),
+                    file_hash: hash(&content),
+                    note_updated_at: response
+                        .get("updatedAt")
+                        .and_then(|v| v.as_str())
+                        .map(str::to_string),
+                    file_modified_ms: file_modified_ms(&root.join(&conflict.path)),
+                });
+            }
+            (ConflictKind::BothModified, ConflictResolution::App, Some(note_id)) => {
+                keep_app_version(&app, &root, &mut ledger, &conflict, note_id).await?;
+            }
+            (ConflictKind::BothModified, ConflictResolution::Both, Some(note_id)) => {
+                let vault_edit = read(&conflict.path)?;
+                let copy = unused_path(&root, &conflict.path, VAULT_COPY_SUFFIX);
+                write_file(&root, &copy, &detach(&vault_edit, VAULT_COPY_SUFFIX))?;
+                keep_app_version(&app, &root, &mut ledger, &conflict, note_id).await?;
+            }
+            (ConflictKind::DeletedInApp, ConflictResolution::App, _) => {}
+            (ConflictKind::DeletedInApp, _, _) => {
+                // Bring the edited file back as a new note
+                let content = read(&conflict.conflict_path)?;
+                let target = if root.join(&conflict.path).exists() {
+                    unused_path(&root, &conflict.path, VAULT_COPY_SUFFIX)
+                } else {
+                    conflict.path.clone()
+                };
+                write_file(&root, &target, &detach(&content, ""))?;
+            }
+            (ConflictKind::BothModified, _, None) => {
+                return Err(AppError::Internal(
+                    "Conflict has no note to resolve against".to_string(),
+                ));
+            }
+        }
+
+        let _ = std::fs::remove_file(root.join(&conflict.conflict_path));
+        conflicts.retain(|c| c.id != id);
+        ledger.save(&app_data_dir)?;
+        save_conflicts(&app_data_dir, &conflicts)?;
+        tracing::info!("Resolved sync conflict for {} ({:?})", conflict.path, keep);
+    }
+
+    // Import any file the resolution created
+    run_sync(&app, false).await.map_err(AppError::from)
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn note(id: &str, title: &str, folder: Option<&str>) -> RemoteNote {
+        RemoteNote {
+            id: id.to_string(),
+            title: title.to_string(),
+            content: "Body".to_string(),
+            tags: vec!["a".to_string()],
+            folder: folder.map(str::to_string),
+            updated_at: "2025-03-10T09:00:00Z".to_string(),
+            is_archived: false,
+            external_id: None,
+        }
+    }
+
+    #[test]
+    fn test_sanitize_component() {
+        assert_eq!(sanitize_component("What: a/b #1?"), "What- a-b -1-");
+        assert_eq!(sanitize_component("  ..hidden.  "), "hidden");
+        assert_eq!(sanitize_component(""), "Untitled");
+        assert_eq!(sanitize_component(&"x".repeat(300)).len(), MAX_STEM_LEN);
+    }
+
+    #[test]
+    fn test_assign_paths_is_deterministic_and_unique() {
+        let a = note("aaaaaaaa-1", "Ideas", Some("Work/Projects"));
+        let b = note("bbbbbbbb-2", "Ideas", Some("Work/Projects"));
+        let c = note("cccccccc-3", "Ideas", None);
+        let ledger = SyncLedger::default();
+
+        let paths = assign_paths(&[&b, &c, &a], &ledger);
+        assert_eq!(paths["aaaaaaaa-1"], "Work/Projects/Ideas.md");
+        assert_eq!(paths["bbbbbbbb-2"], "Work/Projects/Ideas (bbbbbbbb).md");
+        assert_eq!(paths["cccccccc-3"], "Ideas.md");
+        assert_eq!(assign_paths(&[&a, &c, &b], &ledger), paths);
+    }
+
+    #[test]
+    fn test_assign_paths_keeps_existing_file() {
+        // An older note already owns "Ideas.md"; a new note with a smaller ID
+        // must not take it over
+        let existing = note("zzzz-old", "Ideas", None);
+        let new = note("aaaa-new", "Ideas", None);
+        let mut ledger = SyncLedger::default();
+        ledger.upsert(LedgerEntry {
+            path: "Ideas.md".to_string(),
+            note_id: Some("zzzz-old".to_string()),
+            file_hash: String::new(),
+            note_updated_at: None,
+            file_modified_ms: None,
+        });
+
+        let paths = assign_paths(&[&new, &existing], &ledger);
+        assert_eq!(paths["zzzz-old"], "Ideas.md");
+        assert_eq!(paths["aaaa-new"], "Ideas (aaaa-new).md");
+    }
+
+    #[test]
+    fn test_assign_paths_vault_notes_keep_their_path() {
+        let mut vault_note = note("id-1", "Renamed in app", None);
+        vault_note.external_id = Some("obsidian:Inbox/original.md".to_string());
+
+        let paths = assign_paths(&[&vault_note], &SyncLedger::default());
+        assert_eq!(paths["id-1"], "Inbox/original.md");
+    }
+
+    #[test]
+    fn test_render_parse_roundtrip() {
+        let mut n = note("id-1", "Quote \"me\": yes", None);
+        n.tags = vec!["x".to_string(), "y z".to_string()];
+        let parsed = parse_note(&render_note(&n));
+
+        assert_eq!(parsed.sb_id.as_deref(), Some("id-1"));
+        assert_eq!(parsed.title.as_deref(), Some("Quote \"me\": yes"));
+        assert_eq!(parsed.tags, vec!["x", "y z"]);
+        assert_eq!(parsed.body, "Body");
+    }
+
+    #[test]
+    fn test_parse_obsidian_authored_files() {
+        let plain = parse_note("# Heading\n\ntext");
+        assert_eq!(plain.sb_id, None);
+        assert_eq!(plain.body, "# Heading\n\ntext");
+
+        let yaml = parse_note("---\ntags: [one, 'two']\naliases: x\n---\nhello");
+        assert_eq!(yaml.tags, vec!["one", "two"]);
+        assert_eq!(yaml.body, "hello");
+    }
+
+    #[test]
+    fn test_scan_files_skips_hidden_and_conflicts() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        write_file(root, "a.md", "a").unwrap();
+        write_file(root, "Sub/b.md", "b").unwrap();
+        write_file(root, "Sub/b.conflict-20250101-000000.md", "b").unwrap();
+        write_file(root, ".obsidian/workspace.md", "x").unwrap();
+        write_file(root, "image.png", "x").unwrap();
+
+        assert_eq!(scan_files(root), vec!["Sub/b.md", "a.md"]);
+    }
+
+    #[test]
+    fn test_ledger_upsert_replaces_by_path_and_note() {
+        let mut ledger = SyncLedger::default();
+        ledger.upsert(LedgerEntry {
+            path: "old.md".to_string(),
+            note_id: Some("n1".to_string()),
+            file_hash: "h1".to_string(),
+            note_updated_at: None,
+            file_modified_ms: None,
+        });
+        ledger.upsert(LedgerEntry {
+            path: "new.md".to_string(),
+            note_id: Some("n1".to_string()),
+            file_hash: "h2".to_string(),
+            note_updated_at: None,
+            file_modified_ms: None,
+        });
+
+        assert_eq!(ledger.entries.len(), 1);
+        assert_eq!(ledger.by_note("n1").unwrap().path, "new.md");
+    }
+
+    #[test]
+    fn test_changed_in_app() {
+        let entry = LedgerEntry {
+            path: "a.md".to_string(),
+            note_id: Some("n1".to_string()),
+            file_hash: "h".to_string(),
+            note_updated_at: Some("2025-03-10T09:00:00Z".to_string()),
+            file_modified_ms: Some(1),
+        };
+        assert!(!changed_in_app(&entry, Some("2025-03-10T09:00:00Z")));
+        assert!(changed_in_app(&entry, Some("2025-03-11T09:00:00Z")));
+        // Deleted notes are handled by the export pass
+        assert!(!changed_in_app(&entry, None));
+
+        let unsynced = LedgerEntry {
+            note_updated_at: None,
+            ..entry
+        };
+        assert!(!changed_in_app(&unsynced, Some("2025-03-11T09:00:00Z")));
+    }
+
+    #[test]
+    fn test_detach_removes_note_link() {
+        let rendered = render_note(&note("id-1", "Plan", None));
+        let detached = detach(&rendered, VAULT_COPY_SUFFIX);
+        let parsed = parse_note(&detached);
+
+        assert_eq!(parsed.sb_id, None);
+        assert_eq!(parsed.title.as_deref(), Some("Plan (vault copy)"));
+        assert_eq!(parsed.tags, vec!["a"]);
+        assert_eq!(parsed.body, "Body");
+    }
+
+    #[test]
+    fn test_unused_path_and_conflict_store() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        write_file(root, "Sub/Plan (vault copy).md", "x").unwrap();
+        assert_eq!(
+            unused_path(root, "Sub/Plan.md", VAULT_COPY_SUFFIX),
+            "Sub/Plan (vault copy) 2.md"
+        );
+
+        let conflict = SyncConflict {
+            id: "abc".to_string(),
+            kind: ConflictKind::BothModified,
+            note_id: Some("n1".to_string()),
+            path: "Sub/Plan.md".to_string(),
+            conflict_path: conflict_path("Sub/Plan.md"),
+            detected_at: "2025-03-10T09:00:00Z".to_string(),
+        };
+        assert!(!is_syncable(&conflict.conflict_path));
+        save_conflicts(root, std::slice::from_ref(&conflict)).unwrap();
+        assert_eq!(load_conflicts(root), vec![conflict]);
+    }
+}
@@ -0,0 +1,111 @@
+//! Script bridge for macOS system frameworks.
+//!
+//! This module provides:
+//! - Execution of JavaScript for Automation (JXA) scripts via `osascript`
+//! - Mapping of privacy (TCC) permission states reported by those scripts
+
+use serde::{Deserialize, Serialize};
+
+/// Authorization state for a protected macOS data class (Calendars, Contacts, ...)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PermissionStatus {
+    /// The user has not been asked yet
+    NotDetermined,
+    /// Access is blocked by device policy
+    Restricted,
+    /// The user declined access
+    Denied,
+    /// Full access granted
+    Authorized,
+    /// Write-only access granted (Calendars on macOS 14+)
+    WriteOnly,
+    /// The integration is not available on this platform
+    Unavailable,
+}
+
+impl PermissionStatus {
+    /// Map a framework authorization status code
+    ///
+    /// EventKit and Contacts share the same numbering: 0 not determined,
+    /// 1 restricted, 2 denied, 3 authorized (full access), 4 write-only.
+    pub fn from_code(code: i64) -> Self {
+        match code {
+            0 => PermissionStatus::NotDetermined,
+            1 => PermissionStatus::Restricted,
+            2 => PermissionStatus::Denied,
+            3 => PermissionStatus::Authorized,
+            4 => PermissionStatus::WriteOnly,
+            _ => PermissionStatus::Unavailable,
+        }
+    }
+
+    pub fn is_authorized(&self) -> bool {
+        *self == PermissionStatus::Authorized
+    }
+}
+
+/// Run a JXA script with arguments and return its trimmed stdout
+///
+/// The script's `run(argv)` function receives `args`; its return value is
+/// printed by `osascript`.
+#[cfg(target_os = "macos")]
+pub fn run_jxa(script: &str, args: &[&str]) -> Result<String, String> {
+    let output = std::process::Command::new("osascript")
+        .args(["-l", "JavaScript", "-e", script])
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run osascript: {}", e))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "osascript failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Run a JXA script (unsupported on this platform)
+#[cfg(not(target_os = "macos"))]
+pub fn run_jxa(_script: &str, _args: &[&str]) -> Result<String, String> {
+    Err("This integration is only available on macOS".to_string())
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_permission_from_code() {
+        assert_eq!(
+            PermissionStatus::from_code(0),
+            PermissionStatus::NotDetermined
+        );
+        assert_eq!(PermissionStatus::from_code(2), PermissionStatus::Denied);
+        assert_eq!(PermissionStatus::from_code(3), PermissionStatus::Authorized);
+        assert_eq!(PermissionStatus::from_code(4), PermissionStatus::WriteOnly);
+        assert_eq!(
+            PermissionStatus::from_code(99),
+            PermissionStatus::Unavailable
+        );
+    }
+
+    #[test]
+    fn test_only_full_access_is_authorized() {
+        assert!(PermissionStatus::Authorized.is_authorized());
+        assert!(!PermissionStatus::WriteOnly.is_authorized());
+        assert!(!PermissionStatus::NotDetermined.is_authorized());
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    #[test]
+    fn test_run_jxa_unsupported() {
+        assert!(run_jxa("1", &[]).is_err());
+    }
+}
@@ -0,0 +1,401 @@
+//! Minimal PDF rendering of markdown notes for printing and export.
+//!
+//! This module provides:
+//! - Line-based markdown layout (headings, lists, code blocks, paragraphs)
+//! - Word wrapping and pagination on A4 pages
+//! - A self-contained PDF writer using the standard Helvetica and Courier fonts
+//!
+//! Text is encoded as WinAnsi; characters outside it are replaced with `?`.
+
+/// A4 page size in points
+const PAGE_WIDTH: f32 = 595.0;
+const PAGE_HEIGHT: f32 = 842.0;
+const MARGIN: f32 = 56.0;
+
+/// Average glyph width as a fraction of the font size
+const HELVETICA_WIDTH: f32 = 0.5;
+const COURIER_WIDTH: f32 = 0.6;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Font {
+    Regular,
+    Bold,
+    Mono,
+}
+
+impl Font {
+    fn resource(self) -> &'static str {
+        match self {
+            Font::Regular => "F1",
+            Font::Bold => "F2",
+            Font::Mono => "F3",
+        }
+    }
+
+    fn char_width(self, size: f32) -> f32 {
+        match self {
+            Font::Mono => COURIER_WIDTH * size,
+            _ => HELVETICA_WIDTH * size,
+        }
+    }
+}
+
+/// One laid-out line of text
+#[derive(Debug, Clone, PartialEq)]
+struct Line {
+    font: Font,
+    size: f32,
+    text: String,
+    /// Extra space above the line
+    space_before: f32,
+}
+
+/// Strip inline markdown markers that don't survive as plain text
+fn strip_inline(text: &str) -> String {
+    let text = text.replace("**", "").replace("__", "").replace('`', "");
+    // [label](url) -> label
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text.as_str();
+    while let Some(start) = rest.find('[') {
+        let Some(mid) = rest[start..].find("](") else {
+            break;
+        };
+        let Some(end) = rest[start + mid..].find(')') else {
+            break;
+        };
+        out.push_str(&rest[..start]);
+        out.push_str(&rest[start + 1..start + mid]);
+        rest = &rest[start + mid + end + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Split text into lines of at most `max_chars`, breaking at spaces
+fn wrap(text: &str, max_chars: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    for word in text.split_whitespace() {
+        let mut word = word.to_string();
+        // Hard-break words longer than a line
+        while word.chars().count() > max_chars {
+            if !current.is_empty() {
+                lines.push(std::mem::take(&mut current));
+            }
+            let head: String = word.chars().take(max_chars).collect();
+            word = word.chars().skip(max_chars).collect();
+            lines.push(head);
+        }
+        if current.is_empty() {
+            current = word;
+        } else if current.chars().count() + 1 + word.chars().count() <= max_chars {
+            current.push(' ');
+            current.push_str(&word);
+        } else {
+            lines.push(std::mem::replace(&mut current, word));
+        }
+    }
+    if !current.is_empty() {
+        lines.push(current);
+    }
+    lines
+}
+
+/// Lay out a title and markdown body as lines
+fn layout(title: &str, markdown: &str) -> Vec<Line> {
+    let content_width = PAGE_WIDTH - 2.0 * MARGIN;
+    let mut lines = Vec::new();
+    let mut push = |font: Font, size: f32, text: &str, space_before: f32, wrap_text: bool| {
+        let max_chars = (content_width / font.char_width(size)).floor().max(1.0) as usize;
+        let wrapped = if wrap_text {
+            wrap(text, max_chars)
+        } else {
+            text.chars()
+                .collect::<Vec<_>>()
+                .chunks(max_chars)
+                .map(|c| c.iter().collect())
+                .collect()
+        };
+        for (i, text) in wrapped.into_iter().enumerate() {
+            lines.push(Line {
+                font,
+                size,
+                text,
+                space_before: if i == 0 { space_before } else { 0.0 },
+            });
+        }
+    };
+
+    push(Font::Bold, 20.0, title, 0.0, true);
+
+    let mut in_code = false;
+    let mut pending_space = 8.0;
+    for raw in markdown.lines() {
+        if raw.trim_start().starts_with("```") {
+            in_code = !in_code;
+            pending_space = 4.0;
+            continue;
+        }
+        if in_code {
+            let expanded = raw.replace('\t', "    ");
+            if expanded.is_empty() {
+                push(Font::Mono, 9.0, " ", pending_space, false);
+            } else {
+                push(Font::Mono, 9.0, &expanded, pending_space, false);
+            }
+            pending_space = 0.0;
+            continue;
+        }
+
+        let line = raw.trim();
+        if line.is_empty() {
+            pending_space = 6.0;
+            continue;
+        }
+        let heading_level = line.chars().take_while(|&c| c == '#').count();
+        if (1..=6).contains(&heading_level) && line[heading_level..].starts_with(' ') {
+            let size = match heading_level {
+                1 => 17.0,
+                2 => 14.0,
+                _ => 12.0,
+            };
+            push(
+                Font::Bold,
+                size,
+                &strip_inline(line[heading_level..].trim()),
+                pending_space + 6.0,
+                true,
+            );
+        } else if let Some(item) = line
+            .strip_prefix("- ")
+            .or_else(|| line.strip_prefix("* "))
+            .or_else(|| line.strip_prefix("+ "))
+        {
+            push(
+                Font::Regular,
+                11.0,
+                &format!("\u{2022} {}", strip_inline(item)),
+                pending_space,
+                true,
+            );
+        } else {
+            push(
+                Font::Regular,
+                11.0,
+                &strip_inline(line),
+                pending_space,
+                true,
+            );
+        }
+        pending_space = 0.0;
+    }
+    lines
+}
+
+/// Break lines into pages of `(x, y, line)` placements
+fn paginate(lines: Vec<Line>) -> Vec<Vec<(f32, Line)>> {
+    let mut pages = vec![Vec::new()];
+    let mut y = PAGE_HEIGHT - MARGIN;
+    for line in lines {
+        let height = line.size * 1.35;
+        let first_on_page = pages.last().is_some_and(|p| p.is_empty());
+        let advance = if first_on_page {
+            height
+        } else {
+            height + line.space_before
+        };
+        if y - advance < MARGIN && !first_on_page {
+            pages.push(Vec::new());
+            y = PAGE_HEIGHT - MARGIN;
+            y -= height;
+        } else {
+            y -= advance;
+        }
+        pages.last_mut().unwrap().push((y, line));
+    }
+    pages
+}
+
+/// Encode text as an escaped WinAnsi PDF string literal
+fn pdf_string(text: &str) -> Vec<u8> {
+    let mut out = vec![b'('];
+    for c in text.chars() {
+        let byte = match c {
+            '(' | ')' | '\\' => {
+                out.push(b'\\');
+                c as u8
+            }
+            ' '..='~' => c as u8,
+            '\u{a0}'..='\u{ff}' => c as u32 as u8,
+            '\u{20ac}' => 0x80,
+            '\u{2026}' => 0x85,
+            '\u{2018}' => 0x91,
+            '\u{2019}' => 0x92,
+            '\u{201c}' => 0x93,
+            '\u{201d}' => 0x94,
+            '\u{2022}' => 0x95,
+            '\u{2013}' => 0x96,
+            '\u{2014}' => 0x97,
+            _ => b'?',
+        };
+        out.push(byte);
+    }
+    out.push(b')');
+    out
+}
+
+/// Render a note as a PDF document
+pub fn render_markdown(title: &str, markdown: &str) -> Vec<u8> {
+    let pages = paginate(layout(title, markdown));
+
+    // Objects: 1 catalog, 2 page tree, 3-5 fonts, 6 info, then page + content pairs
+    let mut objects: Vec<Vec<u8>> =
+        vec![
+        b"<< /Type /Catalog /Pages 2 0 R >>".to_vec(),
+        Vec::new(),
+        b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>"
+            .to_vec(),
+        b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica-Bold /Encoding /WinAnsiEncoding >>"
+            .to_vec(),
+        b"<< /Type /Font /Subtype /Type1 /BaseFont /Courier /Encoding /WinAnsiEncoding >>"
+            .to_vec(),
+        [b"<< /Title ".as_slice(), &pdf_string(title), b" /Producer (Second Brain) >>"].concat(),
+    ];
+
+    let mut kids = Vec::new();
+    for page in &pages {
+        let mut stream = Vec::new();
+        for (y, line) in page {
+            stream.extend_from_slice(
+                format!(
+                    "BT /{} {} Tf {} {:.2} Td ",
+                    line.font.resource(),
+                    line.size,
+                    MARGIN,
+                    y
+                )
+                .as_bytes(),
+            );
+            stream.extend_from_slice(&pdf_string(&line.text));
+            stream.extend_from_slice(b" Tj ET\n");
+        }
+
+        let page_id = objects.len() + 1;
+        kids.push(format!("{} 0 R", page_id));
+        objects.push(
+            format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] \
+                 /Resources << /Font << /F1 3 0 R /F2 4 0 R /F3 5 0 R >> >> /Contents {} 0 R >>",
+                PAGE_WIDTH,
+                PAGE_HEIGHT,
+                page_id + 1
+            )
+            .into_bytes(),
+        );
+        objects.push(
+            [
+                format!("<< /Length {} >>\nstream\n", stream.len()).as_bytes(),
+                &stream,
+                b"endstream",
+            ]
+            .concat(),
+        );
+    }
+    objects[1] = format!(
+        "<< /Type /Pages /Kids [{}] /Count {} >>",
+        kids.join(" "),
+        pages.len()
+    )
+    .into_bytes();
+
+    let mut pdf = b"%PDF-1.4\n%\xe2\xe3\xcf\xd3\n".to_vec();
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.extend_from_slice(format!("{} 0 obj\n", i + 1).as_bytes());
+        pdf.extend_from_slice(object);
+        pdf.extend_from_slice(b"\nendobj\n");
+    }
+    let xref = pdf.len();
+    pdf.extend_from_slice(
+        format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).as_bytes(),
+    );
+    for offset in offsets {
+        pdf.extend_from_slice(format!("{:010} 00000 n \n", offset).as_bytes());
+    }
+    pdf.extend_from_slice(
+        format!(
+            "trailer\n<< /Size {} /Root 1 0 R /Info 6 0 R >>\nstartxref\n{}\n%%EOF\n",
+            objects.len() + 1,
+            xref
+        )
+        .as_bytes(),
+    );
+    pdf
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page_count(pdf: &[u8]) -> usize {
+        String::from_utf8_lossy(pdf).matches("/Type /Page ").count()
+    }
+
+    #[test]
+    fn test_render_produces_valid_structure() {
+        let pdf = render_markdown("Title (draft)", "# Heading\n\nSome **bold** text.\n- item");
+        let text = String::from_utf8_lossy(&pdf);
+        assert!(pdf.starts_with(b"%PDF-1.4"));
+        assert!(text.ends_with("%%EOF\n"));
+        assert!(text.contains("(Title \\(draft\\))"));
+        assert!(text.contains("(Some bold text.)"));
+        assert_eq!(page_count(&pdf), 1);
+
+        // Every xref entry points at its object
+        let xref_start: usize = text
+            .rsplit("startxref\n")
+            .next()
+            .and_then(|s| s.lines().next())
+            .and_then(|s| s.parse().ok())
+            .unwrap();
+        let entries: Vec<usize> = text[xref_start..]
+            .lines()
+            .skip(3)
+            .take_while(|l| l.ends_with(" n "))
+            .map(|l| l[..10].parse().unwrap())
+            .collect();
+        for (i, offset) in entries.iter().enumerate() {
+            assert!(text[*offset..].starts_with(&format!("{} 0 obj", i + 1)));
+        }
+    }
+
+    #[test]
+    fn test_long_notes_paginate() {
+        let body = "A paragraph line.\n\n".repeat(200);
+        assert!(page_count(&render_markdown("Long", &body)) > 1);
+    }
+
+    #[test]
+    fn test_wrap() {
+        assert_eq!(wrap("one two three", 7), vec!["one two", "three"]);
+        assert_eq!(wrap("abcdefghij", 4), vec!["abcd", "efgh", "ij"]);
+        assert!(wrap("   ", 10).is_empty());
+    }
+
+    #[test]
+    fn test_strip_inline_and_encoding() {
+        assert_eq!(
+            strip_inline("see [the docs](https://x.y) and `code`"),
+            "see the docs and code"
+        );
+        assert_eq!(
+            pdf_string("caf\u{e9} \u{2022} \u{4e2d}"),
+            b"(caf\xe9 \x95 ?)".to_vec()
+        );
+    }
+}
@@ -0,0 +1,1638 @@
+//! LAN device-to-device sync.
+//!
+//! This module provides:
+//! - Pairing of two desktops with an X25519 key exchange, verified by a
+//!   six-digit code both users confirm (the pairing URI can be shown as a QR
+//!   code). The initiator commits to its public key before seeing the
+//!   responder's, so a man in the middle can't search for keys that make the
+//!   codes on both connections match.
+//! - End-to-end encrypted sessions (AES-256-GCM, per-session keys via HKDF)
+//! - Vector-clock versioning of notes with conflict detection
+//! - Periodic and on-demand sync with every paired device
+//!
+//! Notes keep a stable sync ID across devices: the ID on the device that
+//! created them. Copies received from peers are imported with a `peer:`
+//! external ID that maps back to it. Deletions are not synced.
+
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use hkdf::Hkdf;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use x25519_dalek::{PublicKey, StaticSecret};
+
+use crate::config::{load_json, save_json_atomic};
+use crate::error::AppError;
+use crate::keychain;
+use crate::scheduler::{JobAction, Schedule, ScheduledJob, Scheduler, PEER_SYNC_JOB_ID};
+
+/// Default TCP port for incoming pairing and sync connections
+const DEFAULT_PORT: u16 = 47821;
+
+/// Wire protocol version
+const PROTOCOL_VERSION: u32 = 1;
+
+/// Pairing handshake version; version 1 sent public keys without a
+/// commitment
+const PAIRING_VERSION: u32 = 2;
+
+/// Largest accepted frame
+const MAX_FRAME_BYTES: usize = 64 * 1024 * 1024;
+
+/// How long a pairing request waits for the user to confirm
+const PAIRING_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Timeout for a single network read or write
+const IO_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Associated data bound to every encrypted message
+const MAGIC_AAD: &[u8] = b"second-brain-peer-sync-v1";
+
+/// Pairing URI scheme (encoded as a QR code by the UI)
+const URI_SCHEME: &str = "sbsync://";
+
+/// External ID prefix for notes received from peers
+const PEER_ID_PREFIX: &str = "peer:";
+
+/// Backend endpoint returning all notes with full content
+const EXPORT_NOTES_PATH: &str = "/notes/for-export";
+
+/// Backend endpoint that creates or updates notes by external ID
+const IMPORT_NOTES_PATH: &str = "/import/notes";
+
+// ============================================================
+// Vector Clocks
+// ============================================================
+
+/// Per-device edit counters for one note
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct VectorClock(BTreeMap<String, u64>);
+
+/// How two clocks relate
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClockOrdering {
+    Equal,
+    /// Every counter is <= the other's: this version is older
+    Before,
+    /// Every counter is >= the other's: this version is newer
+    After,
+    /// Each side has edits the other has not seen
+    Concurrent,
+}
+
+impl VectorClock {
+    pub fn increment(&mut self, device_id: &str) {
+        *self.0.entry(device_id.to_string()).or_insert(0) += 1;
+    }
+
+    /// Take the per-device maximum of both clocks
+    pub fn merge(&mut self, other: &VectorClock) {
+        for (device, &count) in &other.0 {
+            let entry = self.0.entry(device.clone()).or_insert(0);
+            *entry = (*entry).max(count);
+        }
+    }
+
+    pub fn compare(&self, other: &VectorClock) -> ClockOrdering {
+        let devices: HashSet<&String> = self.0.keys().chain(other.0.keys()).collect();
+        let (mut less, mut greater) = (false, false);
+        for device in devices {
+            let a = self.0.get(device).copied().unwrap_or(0);
+            let b = other.0.get(device).copied().unwrap_or(0);
+            less |= a < b;
+            greater |= a > b;
+        }
+        match (less, greater) {
+            (false, false) => ClockOrdering::Equal,
+            (true, false) => ClockOrdering::Before,
+            (false, true) => ClockOrdering::After,
+            (true, true) => ClockOrdering::Concurrent,
+        }
+    }
+}
+
+// ============================================================
+// Persisted State
+// ============================================================
+
+/// Peer sync settings
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PeerSyncSettings {
+    /// Accept pairing and sync connections on the LAN
+    pub enabled: bool,
+    pub port: u16,
+    /// Name shown to other devices
+    pub device_name: String,
+    /// Minutes between automatic syncs with paired devices, disabled when None
+    pub sync_interval_mins: Option<u32>,
+}
+
+impl Default for PeerSyncSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: DEFAULT_PORT,
+            device_name: "Second Brain".to_string(),
+            sync_interval_mins: Some(10),
+        }
+    }
+}
+
+impl PeerSyncSettings {
+    fn path(app_data_dir: &Path) -> PathBuf {
+        app_data_dir.join("peer-sync.json")
+    }
+
+    /// Load settings, returning defaults if missing or invalid
+    pub fn load(app_data_dir: &Path) -> Self {
+        load_json(&Self::path(app_data_dir)).unwrap_or_default()
+    }
+
+    /// Save settings atomically
+    pub fn save(&self, app_data_dir: &Path) -> Result<(), String> {
+        save_json_atomic(&Self::path(app_data_dir), self)
+    }
+
+    /// Validate settings
+    pub fn validate(&self) -> Result<(), String> {
+        if self.port < 1024 {
+            return Err("Sync port must be 1024 or higher".to_string());
+        }
+        if self.device_name.trim().is_empty() {
+            return Err("Device name is required".to_string());
+        }
+        if self.sync_interval_mins == Some(0) {
+            return Err("Sync interval must be at least 1 minute".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Sync metadata for one note
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct NoteMeta {
+    clock: VectorClock,
+    /// Hash of the synced fields as last seen
+    hash: String,
+    /// ID of the note in the local backend, once known
+    local_id: Option<String>,
+}
+
+/// This device's identity and note versions
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct SyncState {
+    device_id: String,
+    notes: HashMap<String, NoteMeta>,
+}
+
+impl SyncState {
+    fn path(app_data_dir: &Path) -> PathBuf {
+        app_data_dir.join("peer-sync-state.json")
+    }
+
+    /// Load state, generating a device ID on first use
+    fn load(app_data_dir: &Path) -> Result<Self, String> {
+        let mut state: SyncState = load_json(&Self::path(app_data_dir)).unwrap_or_default();
+        if state.device_id.is_empty() {
+            state.device_id = encode_hex(&random_bytes::<16>()?);
+            state.save(app_data_dir)?;
+        }
+        Ok(state)
+    }
+
+    fn save(&self, app_data_dir: &Path) -> Result<(), String> {
+        save_json_atomic(&Self::path(app_data_dir), self)
+    }
+}
+
+/// A paired device
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Peer {
+    pub id: String,
+    pub name: String,
+    /// Last known `host:port`
+    pub address: String,
+    /// Pairing time (RFC 3339)
+    pub paired_at: String,
+    /// Last successful sync (RFC 3339)
+    pub last_sync: Option<String>,
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct PeerList {
+    peers: Vec<Peer>,
+}
+
+impl PeerList {
+    fn path(app_data_dir: &Path) -> PathBuf {
+        app_data_dir.join("peers.json")
+    }
+
+    fn load(app_data_dir: &Path) -> Self {
+        load_json(&Self::path(app_data_dir)).unwrap_or_default()
+    }
+
+    fn save(&self, app_data_dir: &Path) -> Result<(), String> {
+        save_json_atomic(&Self::path(app_data_dir), self)
+    }
+
+    fn upsert(&mut self, peer: Peer) {
+        self.peers.retain(|p| p.id != peer.id);
+        self.peers.push(peer);
+    }
+
+    fn update(app_data_dir: &Path, id: &str, f: impl FnOnce(&mut Peer)) {
+        let mut list = Self::load(app_data_dir);
+        if let Some(peer) = list.peers.iter_mut().find(|p| p.id == id) {
+            f(peer);
+            let _ = list.save(app_data_dir);
+        }
+    }
+}
+
+fn key_account(peer_id: &str) -> String {
+    format!("peer-sync:{}", peer_id)
+}
+
+fn load_peer_key(app_data_dir: &Path, peer_id: &str) -> Result<[u8; 32], String> {
+    let hex = keychain::get_secret(app_data_dir, &key_account(peer_id))?
+        .ok_or_else(|| format!("Device {} is not paired", peer_id))?;
+    decode_hex(&hex)
+        .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+        .ok_or_else(|| "Stored pairing key is invalid".to_string())
+}
+
+// ============================================================
+// Crypto and Framing
+// ============================================================
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if hex.len() % 2 != 0 || !hex.is_ascii() {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+        .collect()
+}
+
+fn random_bytes<const N: usize>() -> Result<[u8; N], String> {
+    let mut bytes = [0u8; N];
+    getrandom::fill(&mut bytes).map_err(|e| format!("Failed to generate random bytes: {}", e))?;
+    Ok(bytes)
+}
+
+fn hkdf_expand(salt: &[u8], ikm: &[u8], info: &[u8]) -> [u8; 32] {
+    let mut out = [0u8; 32];
+    Hkdf::<Sha256>::new(Some(salt), ikm)
+        .expand(info, &mut out)
+        .expect("32 bytes is a valid HKDF-SHA256 output length");
+    out
+}
+
+/// Derive the long-term pairing key from an X25519 exchange
+///
+/// Both device IDs and public keys are bound into the key, in a fixed order,
+/// so both sides derive the same key and a relayed exchange derives another.
+fn derive_pairing_key(
+    secret: &StaticSecret,
+    their_public: &PublicKey,
+    our_id: &str,
+    their_id: &str,
+) -> [u8; 32] {
+    let shared = secret.diffie_hellman(their_public);
+    let our_public = PublicKey::from(secret);
+
+    let mut parts = [
+        (our_id, our_public.as_bytes().to_vec()),
+        (their_id, their_public.as_bytes().to_vec()),
+    ];
+    parts.sort();
+    let mut info = b"second-brain-peer-pair".to_vec();
+    for (id, public) in &parts {
+        info.extend_from_slice(id.as_bytes());
+        info.extend_from_slice(public);
+    }
+    hkdf_expand(b"second-brain-peer-sync-v1", shared.as_bytes(), &info)
+}
+
+/// Commitment to a public key, sent before the key itself
+fn key_commitment(public: &PublicKey) -> String {
+    let digest = Sha256::new()
+        .chain_update(b"second-brain-peer-commit")
+        .chain_update(public.as_bytes())
+        .finalize();
+    encode_hex(&digest)
+}
+
+fn parse_public_key(hex: &str) -> Result<PublicKey, String> {
+    decode_hex(hex)
+        .and_then(|b| <[u8; 32]>::try_from(b).ok())
+        .map(PublicKey::from)
+        .ok_or_else(|| "Invalid public key".to_string())
+}
+
+/// Six-digit code both users compare to confirm pairing
+fn verification_code(key: &[u8; 32]) -> String {
+    let digest = Sha256::new()
+        .chain_update(key)
+        .chain_update(b"verification-code")
+        .finalize();
+    let value = u32::from_be_bytes([digest[0], digest[1], digest[2], digest[3]]);
+    format!("{:06}", value % 1_000_000)
+}
+
+/// Fresh per-connection key from the pairing key and both sides' nonces
+fn derive_session_key(
+    pairing_key: &[u8; 32],
+    initiator_nonce: &[u8],
+    responder_nonce: &[u8],
+) -> [u8; 32] {
+    let salt = [initiator_nonce, responder_nonce].concat();
+    hkdf_expand(&salt, pairing_key, b"second-brain-peer-session")
+}
+
+async fn write_frame<S: AsyncWrite + Unpin>(stream: &mut S, data: &[u8]) -> Result<(), String> {
+    if data.len() > MAX_FRAME_BYTES {
+        return Err("Message too large".to_string());
+    }
+    tokio::time::timeout(IO_TIMEOUT, async {
+        stream.write_all(&(data.len() as u32).to_be_bytes()).await?;
+        stream.write_all(data).await?;
+        stream.flush().await
+    })
+    .await
+    .map_err(|_| "Timed out sending to peer".to_string())?
+    .map_err(|e| format!("Failed to send to peer: {}", e))
+}
+
+async fn read_frame<S: AsyncRead + Unpin>(
+    stream: &mut S,
+    timeout: Duration,
+) -> Result<Vec<u8>, String> {
+    tokio::time::timeout(timeout, async {
+        let mut len = [0u8; 4];
+        stream.read_exact(&mut len).await?;
+        let len = u32::from_be_bytes(len) as usize;
+        if len > MAX_FRAME_BYTES {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "frame too large",
+            ));
+        }
+        let mut data = vec![0u8; len];
+        stream.read_exact(&mut data).await?;
+        Ok(data)
+    })
+    .await
+    .map_err(|_| "Timed out waiting for peer".to_string())?
+    .map_err(|e| format!("Failed to read from peer: {}", e))
+}
+
+/// Unencrypted opening message of a connection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Hello {
+    /// Initiator's pairing request, committing to its public key
+    Pair {
+        version: u32,
+        device_id: String,
+        device_name: String,
+        /// Port the sender accepts connections on
+        port: u16,
+        /// `key_commitment` of the initiator's public key
+        commitment: String,
+    },
+    /// Responder's public key
+    PairKey {
+        version: u32,
+        device_id: String,
+        device_name: String,
+        public_key: String,
+    },
+    /// Initiator's public key, checked against its commitment
+    Reveal {
+        public_key: String,
+    },
+    Sync {
+        version: u32,
+        device_id: String,
+        nonce: String,
+    },
+    Error {
+        message: String,
+    },
+}
+
+async fn send_hello<S: AsyncWrite + Unpin>(stream: &mut S, hello: &Hello) -> Result<(), String> {
+    let data = serde_json::to_vec(hello).map_err(|e| e.to_string())?;
+    write_frame(stream, &data).await
+}
+
+async fn recv_hello<S: AsyncRead + Unpin>(stream: &mut S) -> Result<Hello, String> {
+    let data = read_frame(stream, IO_TIMEOUT).await?;
+    match serde_json::from_slice(&data).map_err(|e| format!("Invalid handshake: {}", e))? {
+        Hello::Error { message } => Err(message),
+        Hello::Pair { version, .. } | Hello::PairKey { version, .. }
+            if version != PAIRING_VERSION =>
+        {
+            Err(format!("Unsupported pairing protocol version {}", version))
+        }
+        Hello::Sync { version, .. } if version != PROTOCOL_VERSION => {
+            Err(format!("Unsupported sync protocol version {}", version))
+        }
+        hello => Ok(hello),
+    }
+}
+
+/// A note's synced fields
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct SyncNote {
+    sync_id: String,
+    title: String,
+    content: String,
+    tags: Vec<String>,
+    folder: Option<String>,
+    created_at: String,
+    updated_at: String,
+    clock: VectorClock,
+}
+
+/// Encrypted message after the handshake
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Message {
+    PairAccepted,
+    PairRejected,
+    Manifest {
+        entries: HashMap<String, VectorClock>,
+    },
+    Request {
+        ids: Vec<String>,
+    },
+    Notes {
+        notes: Vec<SyncNote>,
+    },
+}
+
+/// Authenticated, encrypted message stream
+///
+/// Nonces are a direction byte and a per-direction counter, so a key is never
+/// reused for two messages and replayed or reordered frames fail to decrypt.
+struct SecureChannel<S> {
+    stream: S,
+    cipher: Aes256Gcm,
+    is_initiator: bool,
+    send_counter: u64,
+    recv_counter: u64,
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> SecureChannel<S> {
+    fn new(stream: S, key: &[u8; 32], is_initiator: bool) -> Self {
+        Self {
+            stream,
+            cipher: Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key)),
+            is_initiator,
+            send_counter: 0,
+            recv_counter: 0,
+        }
+    }
+
+    fn nonce(from_initiator: bool, counter: u64) -> [u8; 12] {
+        let mut nonce = [0u8; 12];
+        nonce[0] = from_initiator as u8;
+        nonce[4..].copy_from_slice(&counter.to_be_bytes());
+        nonce
+    }
+
+    async fn send(&mut self, message: &Message) -> Result<(), String> {
+        let plaintext = serde_json::to_vec(message).map_err(|e| e.to_string())?;
+        let nonce = Self::nonce(self.is_initiator, self.send_counter);
+        let ciphertext = self
+            .cipher
+            .encrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: &plaintext,
+                    aad: MAGIC_AAD,
+                },
+            )
+            .map_err(|_| "Encryption failed".to_string())?;
+        self.send_counter += 1;
+        write_frame(&mut self.stream, &ciphertext).await
+    }
+
+    async fn recv(&mut self, timeout: Duration) -> Result<Message, String> {
+        let ciphertext = read_frame(&mut self.stream, timeout).await?;
+        let nonce = Self::nonce(!self.is_initiator, self.recv_counter);
+        let plaintext = self
+            .cipher
+            .decrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: &ciphertext,
+                    aad: MAGIC_AAD,
+                },
+            )
+            .map_err(|_| "Peer message failed authentication".to_string())?;
+        self.recv_counter += 1;
+        serde_json::from_slice(&plaintext).map_err(|e| format!("Invalid peer message: {}", e))
+    }
+
+    /// Send our message and receive theirs; the initiator always sends first
+    async fn exchange(&mut self, message: &Message) -> Result<Message, String> {
+        if self.is_initiator {
+            self.send(message).await?;
+            self.recv(IO_TIMEOUT).await
+        } else {
+            let theirs = self.recv(IO_TIMEOUT).await?;
+            self.send(message).await?;
+            Ok(theirs)
+        }
+    }
+}
+
+// ============================================================
+// Notes
+// ============================================================
+
+/// A note as returned by the export endpoint
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LocalNote {
+    id: String,
+    title: String,
+    content: String,
+    #[serde(default)]
+    tags: Vec<String>,
+    folder: Option<String>,
+    created_at: String,
+    updated_at: String,
+    #[serde(default)]
+    is_archived: bool,
+    external_id: Option<String>,
+}
+
+impl LocalNote {
+    fn sync_id(&self) -> String {
+        self.external_id
+            .as_deref()
+            .and_then(|id| id.strip_prefix(PEER_ID_PREFIX))
+            .unwrap_or(&self.id)
+            .to_string()
+    }
+}
+
+fn content_hash(title: &str, content: &str, tags: &[String], folder: Option<&str>) -> String {
+    let mut hasher = Sha256::new();
+    for part in [title, content, &tags.join("\n"), folder.unwrap_or("")] {
+        hasher.update(part.as_bytes());
+        hasher.update([0u8]);
+    }
+    encode_hex(&hasher.finalize())
+}
+
+/// Fetch local notes and bump this device's counter for any that changed
+async fn refresh_local(
+    app: &AppHandle,
+    state: &mut SyncState,
+) -> Result<HashMap<String, LocalNote>, String> {
+    let response =
+        crate::proxy::send_backend_request(app, "GET", EXPORT_NOTES_PATH, None, None).await?;
+    let notes: Vec<LocalNote> =
+        serde_json::from_value(response).map_err(|e| format!("Invalid notes response: {}", e))?;
+
+    let mut local = HashMap::new();
+    for note in notes.into_iter().filter(|n| !n.is_archived) {
+        let sync_id = note.sync_id();
+        let hash = content_hash(
+            &note.title,
+            &note.content,
+            &note.tags,
+            note.folder.as_deref(),
+        );
+        let meta = state.notes.entry(sync_id.clone()).or_default();
+        meta.local_id = Some(note.id.clone());
+        if meta.hash != hash {
+            meta.hash = hash;
+            meta.clock.increment(&state.device_id);
+        }
+        local.insert(sync_id, note);
+    }
+    Ok(local)
+}
+
+/// Sync IDs whose remote version we have not seen
+fn needed_ids(
+    ours: &HashMap<String, NoteMeta>,
+    theirs: &HashMap<String, VectorClock>,
+) -> Vec<String> {
+    let mut ids: Vec<String> = theirs
+        .iter()
+        .filter(|(id, clock)| match ours.get(*id) {
+            None => true,
+            Some(meta) => matches!(
+                meta.clock.compare(clock),
+                ClockOrdering::Before | ClockOrdering::Concurrent
+            ),
+        })
+        .map(|(id, _)| id.clone())
+        .collect();
+    ids.sort();
+    ids
+}
+
+/// Create or update the local copy of a note
+async fn write_note(
+    app: &AppHandle,
+    local_id: Option<&str>,
+    note: &SyncNote,
+) -> Result<(), String> {
+    match local_id {
+        Some(id) => crate::proxy::send_backend_request(
+            app,
+            "PUT",
+            &format!("/notes/{}", id),
+            Some(&serde_json::json!({
+                "title": note.title,
+                "content": note.content,
+                "tags": note.tags,
+                "folder": note.folder,
+                "updateFolder": true,
+                "updateContentJson": true,
+            })),
+            None,
+        )
+        .await
+        .map(|_| ())
+        .map_err(String::from),
+        None => import_note(app, &format!("{}{}", PEER_ID_PREFIX, note.sync_id), note).await,
+    }
+}
+
+async fn import_note(app: &AppHandle, external_id: &str, note: &SyncNote) -> Result<(), String> {
+    crate::proxy::send_backend_request(
+        app,
+        "POST",
+        IMPORT_NOTES_PATH,
+        Some(&serde_json::json!({
+            "notes": [{
+                "id": external_id,
+                "title": note.title,
+                "body": note.content,
+                "folder": note.folder,
+                "source": "peer_sync",
+                "created_at": note.created_at,
+                "updated_at": note.updated_at,
+                "tags": note.tags,
+            }]
+        })),
+        None,
+    )
+    .await
+    .map(|_| ())
+    .map_err(String::from)
+}
+
+/// Event payload emitted when concurrent edits were resolved
+#[derive(Debug, Clone, Serialize)]
+pub struct PeerConflictEvent {
+    pub peer_id: String,
+    pub sync_id: String,
+    pub title: String,
+    /// Which version stayed in place: "local" or "remote"
+    pub kept: String,
+    /// Title of the note holding the other version
+    pub copy_title: String,
+}
+
+/// Apply a received note, returning a conflict if edits were concurrent
+async fn apply_remote(
+    app: &AppHandle,
+    state: &mut SyncState,
+    local: &HashMap<String, LocalNote>,
+    peer_id: &str,
+    note: SyncNote,
+) -> Result<Option<PeerConflictEvent>, String> {
+    let remote_hash = content_hash(
+        &note.title,
+        &note.content,
+        &note.tags,
+        note.folder.as_deref(),
+    );
+    let meta = state.notes.get(&note.sync_id).cloned().unwrap_or_default();
+    let ordering = if state.notes.contains_key(&note.sync_id) {
+        meta.clock.compare(&note.clock)
+    } else {
+        ClockOrdering::Before
+    };
+
+    let mut merged = meta.clock.clone();
+    merged.merge(&note.clock);
+
+    let conflict = match ordering {
+        ClockOrdering::Equal | ClockOrdering::After => return Ok(None),
+        ClockOrdering::Before => {
+            write_note(app, meta.local_id.as_deref(), &note).await?;
+            None
+        }
+        ClockOrdering::Concurrent if meta.hash == remote_hash => None,
+        ClockOrdering::Concurrent => {
+            let Some(ours) = local.get(&note.sync_id) else {
+                return Ok(None);
+            };
+            // Last writer wins; the other version is kept as a separate note
+            let remote_wins = parse_time(&note.updated_at) > parse_time(&ours.updated_at);
+            let loser = if remote_wins {
+                SyncNote {
+                    sync_id: note.sync_id.clone(),
+                    title: ours.title.clone(),
+                    content: ours.content.clone(),
+                    tags: ours.tags.clone(),
+                    folder: ours.folder.clone(),
+                    created_at: ours.created_at.clone(),
+                    updated_at: ours.updated_at.clone(),
+                    clock: VectorClock::default(),
+                }
+            } else {
+                note.clone()
+            };
+            let copy = SyncNote {
+                title: format!("{} (conflict copy)", loser.title),
+                ..loser
+            };
+            import_note(
+                app,
+                &format!(
+                    "conflict:{}:{}",
+                    note.sync_id,
+                    chrono::Utc::now().timestamp_millis()
+                ),
+                &copy,
+            )
+            .await?;
+            if remote_wins {
+                write_note(app, meta.local_id.as_deref(), &note).await?;
+            }
+            // The resolution is a new version every device should converge on
+            merged.increment(&state.device_id);
+
+            Some(PeerConflictEvent {
+                peer_id: peer_id.to_string(),
+                sync_id: note.sync_id.clone(),
+                title: note.title.clone(),
+                kept: if remote_wins { "remote" } else { "local" }.to_string(),
+                copy_title: copy.title,
+            })
+        }
+    };
+
+    let hash = if conflict.as_ref().is_some_and(|c| c.kept == "local") {
+        meta.hash.clone()
+    } else {
+        remote_hash
+    };
+    state.notes.insert(
+        note.sync_id.clone(),
+        NoteMeta {
+            clock: merged,
+            hash,
+            local_id: meta.local_id,
+        },
+    );
+    Ok(conflict)
+}
+
+fn parse_time(value: &str) -> chrono::DateTime<chrono::Utc> {
+    chrono::DateTime::parse_from_rfc3339(value)
+        .map(|d| d.with_timezone(&chrono::Utc))
+        .unwrap_or_default()
+}
+
+// ============================================================
+// Sessions
+// ============================================================
+
+/// Result of a sync session
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SyncSummary {
+    pub peer_id: String,
+    pub sent: usize,
+    pub received: usize,
+    pub conflicts: usize,
+}
+
+/// Runtime status
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PeerSyncStatus {
+    pub listening: bool,
+    pub port: Option<u16>,
+    pub syncing: bool,
+    pub last_error: Option<String>,
+}
+
+/// Managed state for peer sync
+#[derive(Default)]
+pub struct PeerSync {
+    listener: Mutex<Option<tauri::async_runtime::JoinHandle<()>>>,
+    /// Pairing requests awaiting the user's answer, by peer ID
+    pending: Mutex<HashMap<String, tokio::sync::oneshot::Sender<bool>>>,
+    /// Serializes sessions so the sync state is never written concurrently
+    lock: tokio::sync::Mutex<()>,
+    status: Mutex<PeerSyncStatus>,
+}
+
+impl PeerSync {
+    fn update(&self, f: impl FnOnce(&mut PeerSyncStatus)) {
+        f(&mut self.status.lock().unwrap());
+    }
+}
+
+async fn run_session<S: AsyncRead + AsyncWrite + Unpin>(
+    app: &AppHandle,
+    channel: &mut SecureChannel<S>,
+    peer_id: &str,
+) -> Result<SyncSummary, String> {
+    if !*app.state::<crate::AppState>().is_backend_ready.read() {
+        return Err("Backend is not ready".to_string());
+    }
+
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let sync = app.state::<PeerSync>();
+    let _guard = sync.lock.lock().await;
+    sync.update(|s| s.syncing = true);
+
+    let result = async {
+        let mut state = SyncState::load(&app_data_dir)?;
+        let local = refresh_local(app, &mut state).await?;
+        state.save(&app_data_dir)?;
+
+        let manifest: HashMap<String, VectorClock> = state
+            .notes
+            .iter()
+            .filter(|(id, _)| local.contains_key(*id))
+            .map(|(id, meta)| (id.clone(), meta.clock.clone()))
+            .collect();
+        let Message::Manifest { entries: theirs } = channel
+            .exchange(&Message::Manifest { entries: manifest })
+            .await?
+        else {
+            return Err("Unexpected message from peer".to_string());
+        };
+
+        let wanted = needed_ids(&state.notes, &theirs);
+        let Message::Request { ids: requested } =
+            channel.exchange(&Message::Request { ids: wanted }).await?
+        else {
+            return Err("Unexpected message from peer".to_string());
+        };
+
+        let outgoing: Vec<SyncNote> = requested
+            .iter()
+            .filter_map(|id| {
+                let note = local.get(id)?;
+                Some(SyncNote {
+                    sync_id: id.clone(),
+                    title: note.title.clone(),
+                    content: note.content.clone(),
+                    tags: note.tags.clone(),
+                    folder: note.folder.clone(),
+                    created_at: note.created_at.clone(),
+                    updated_at: note.updated_at.clone(),
+                    clock: state.notes.get(id)?.clock.clone(),
+                })
+            })
+            .collect();
+        let sent = outgoing.len();
+        let Message::Notes { notes: incoming } = channel
+            .exchange(&Message::Notes { notes: outgoing })
+            .await?
+        else {
+            return Err("Unexpected message from peer".to_string());
+        };
+
+        let mut summary = SyncSummary {
+            peer_id: peer_id.to_string(),
+            sent,
+            ..Default::default()
+        };
+        for note in incoming {
+            let outcome = apply_remote(app, &mut state, &local, peer_id, note).await;
+            // Persist after every note so a failure doesn't replay applied ones
+            state.save(&app_data_dir)?;
+            if let Some(conflict) = outcome? {
+                summary.conflicts += 1;
+                let _ = app.emit("peer-sync-conflict", conflict);
+            }
+            summary.received += 1;
+        }
+        Ok(summary)
+    }
+    .await;
+
+    sync.update(|s| s.syncing = false);
+    let now = chrono::Local::now().to_rfc3339();
+    PeerList::update(&app_data_dir, peer_id, |peer| match result {
+        Ok(_) => {
+            peer.last_sync = Some(now);
+            peer.last_error = None;
+        }
+        Err(ref e) => peer.last_error = Some(e.clone()),
+    });
+    if let Ok(ref summary) = result {
+        tracing::info!(
+            "Synced with peer {}: sent {}, received {}, {} conflict(s)",
+            peer_id,
+            summary.sent,
+            summary.received,
+            summary.conflicts
+        );
+        let _ = app.emit("peer-sync-completed", summary);
+    }
+    result
+}
+
+/// Event payload for a pairing code to display
+#[derive(Debug, Clone, Serialize)]
+pub struct PairingCodeEvent {
+    pub peer_id: String,
+    pub device_name: String,
+    pub code: String,
+}
+
+/// Handle an incoming connection
+async fn handle_connection(app: AppHandle, mut stream: TcpStream) -> Result<(), String> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let settings = PeerSyncSettings::load(&app_data_dir);
+    let state = SyncState::load(&app_data_dir)?;
+    let remote_ip = stream
+        .peer_addr()
+        .map(|a| a.ip().to_string())
+        .unwrap_or_default();
+
+    match recv_hello(&mut stream).await? {
+        Hello::Pair {
+            device_id,
+            device_name,
+            port,
+            commitment,
+            ..
+        } => {
+            let secret = StaticSecret::from(random_bytes::<32>()?);
+            send_hello(
+                &mut stream,
+                &Hello::PairKey {
+                    version: PAIRING_VERSION,
+                    device_id: state.device_id.clone(),
+                    device_name: settings.device_name.clone(),
+                    public_key: encode_hex(PublicKey::from(&secret).as_bytes()),
+                },
+            )
+            .await?;
+            let Hello::Reveal { public_key } = recv_hello(&mut stream).await? else {
+                return Err("Unexpected handshake from peer".to_string());
+            };
+            let their_public = parse_public_key(&public_key)?;
+            if key_commitment(&their_public) != commitment {
+                return Err("Peer's public key doesn't match its commitment".to_string());
+            }
+
+            let key = derive_pairing_key(&secret, &their_public, &state.device_id, &device_id);
+            let mut channel = SecureChannel::new(stream, &key, false);
+            let request = PairingCodeEvent {
+                peer_id: device_id.clone(),
+                device_name: device_name.clone(),
+                code: verification_code(&key),
+            };
+            confirm_pairing(&app, &mut channel, "peer-pairing-request", request)
+                .await
+                .map_err(String::from)?;
+            save_peer(
+                &app_data_dir,
+                &device_id,
+                &device_name,
+                &format!("{}:{}", remote_ip, port),
+                &key,
+            )
+            .map(|_| ())
+        }
+        Hello::Sync {
+            device_id, nonce, ..
+        } => {
+            let Ok(pairing_key) = load_peer_key(&app_data_dir, &device_id) else {
+                let _ = send_hello(
+                    &mut stream,
+                    &Hello::Error {
+                        message: "This device is not paired".to_string(),
+                    },
+                )
+                .await;
+                return Err(format!("Sync attempt from unpaired device {}", device_id));
+            };
+            let their_nonce = decode_hex(&nonce).ok_or_else(|| "Invalid nonce".to_string())?;
+            let our_nonce = random_bytes::<16>()?;
+            send_hello(
+                &mut stream,
+                &Hello::Sync {
+                    version: PROTOCOL_VERSION,
+                    device_id: state.device_id.clone(),
+                    nonce: encode_hex(&our_nonce),
+                },
+            )
+            .await?;
+
+            let key = derive_session_key(&pairing_key, &their_nonce, &our_nonce);
+            let mut channel = SecureChannel::new(stream, &key, false);
+            run_session(&app, &mut channel, &device_id)
+                .await
+                .map(|_| ())
+        }
+        Hello::PairKey { .. } | Hello::Reveal { .. } => {
+            Err("Unexpected handshake from peer".to_string())
+        }
+        Hello::Error { message } => Err(message),
+    }
+}
+
+/// Ask this device's user to confirm the code, then swap answers with the
+/// peer; succeeds only when both users accepted
+///
+/// Answers travel over the channel keyed with the pairing key, so one only
+/// decrypts if both sides derived the same key.
+async fn confirm_pairing<S: AsyncRead + AsyncWrite + Unpin>(
+    app: &AppHandle,
+    channel: &mut SecureChannel<S>,
+    event: &str,
+    request: PairingCodeEvent,
+) -> Result<(), AppError> {
+    let peer_id = request.peer_id.clone();
+    let device_name = request.device_name.clone();
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    app.state::<PeerSync>()
+        .pending
+        .lock()
+        .unwrap()
+        .insert(peer_id.clone(), tx);
+    let _ = app.emit(event, request);
+
+    let accepted = tokio::time::timeout(PAIRING_TIMEOUT, rx)
+        .await
+        .ok()
+        .and_then(|r| r.ok())
+        .unwrap_or(false);
+    app.state::<PeerSync>()
+        .pending
+        .lock()
+        .unwrap()
+        .remove(&peer_id);
+
+    if !accepted {
+        let _ = channel.send(&Message::PairRejected).await;
+        return Err(AppError::Cancelled("Pairing declined".to_string()));
+    }
+    channel.send(&Message::PairAccepted).await?;
+    match channel.recv(PAIRING_TIMEOUT).await? {
+        Message::PairAccepted => Ok(()),
+        _ => Err(AppError::Cancelled(format!(
+            "{} declined pairing",
+            device_name
+        ))),
+    }
+}
+
+fn save_peer(
+    app_data_dir: &Path,
+    id: &str,
+    name: &str,
+    address: &str,
+    key: &[u8; 32],
+) -> Result<Peer, String> {
+    keychain::set_secret(app_data_dir, &key_account(id), &encode_hex(key))?;
+    let peer = Peer {
+        id: id.to_string(),
+        name: name.to_string(),
+        address: address.to_string(),
+        paired_at: chrono::Local::now().to_rfc3339(),
+        last_sync: None,
+        last_error: None,
+    };
+    let mut list = PeerList::load(app_data_dir);
+    list.upsert(peer.clone());
+    list.save(app_data_dir)?;
+    tracing::info!("Paired with device {} ({})", name, id);
+    Ok(peer)
+}
+
+/// Sync with a paired device
+pub async fn sync_with(app: &AppHandle, peer: &Peer) -> Result<SyncSummary, String> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let pairing_key = load_peer_key(&app_data_dir, &peer.id)?;
+    let state = SyncState::load(&app_data_dir)?;
+
+    let mut stream = tokio::time::timeout(IO_TIMEOUT, TcpStream::connect(&peer.address))
+        .await
+        .map_err(|_| format!("Timed out connecting to {}", peer.name))?
+        .map_err(|e| format!("Failed to connect to {}: {}", peer.name, e))?;
+
+    let our_nonce = random_bytes::<16>()?;
+    send_hello(
+        &mut stream,
+        &Hello::Sync {
+            version: PROTOCOL_VERSION,
+            device_id: state.device_id.clone(),
+            nonce: encode_hex(&our_nonce),
+        },
+    )
+    .await?;
+    let Hello::Sync {
+        device_id, nonce, ..
+    } = recv_hello(&mut stream).await?
+    else {
+        return Err("Unexpected handshake from peer".to_string());
+    };
+    if device_id != peer.id {
+        return Err(format!(
+            "{} answered with a different device identity",
+            peer.address
+        ));
+    }
+    let their_nonce = decode_hex(&nonce).ok_or_else(|| "Invalid nonce".to_string())?;
+
+    let key = derive_session_key(&pairing_key, &our_nonce, &their_nonce);
+    let mut channel = SecureChannel::new(stream, &key, true);
+    run_session(app, &mut channel, &peer.id).await
+}
+
+/// Sync with every paired device, run by the scheduler
+pub async fn sync_all(app: &AppHandle) -> Result<(), String> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let mut failed = 0;
+    for peer in PeerList::load(&app_data_dir).peers {
+        if let Err(e) = sync_with(app, &peer).await {
+            tracing::warn!("Sync with {} failed: {}", peer.name, e);
+            failed += 1;
+        }
+    }
+    if failed > 0 {
+        return Err(format!("{} peer(s) failed to sync", failed));
+    }
+    Ok(())
+}
+
+/// Parse a `host:port` address or a pairing URI
+fn parse_address(input: &str) -> Result<String, String> {
+    let input = input.trim();
+    let address = input
+        .strip_prefix(URI_SCHEME)
+        .map(|rest| rest.split(['/', '?']).next().unwrap_or(""))
+        .unwrap_or(input);
+
+    let (host, port) = address
+        .rsplit_once(':')
+        .ok_or_else(|| "Address must be host:port".to_string())?;
+    if host.is_empty() {
+        return Err("Address must be host:port".to_string());
+    }
+    port.parse::<u16>()
+        .map_err(|_| format!("Invalid port: {}", port))?;
+    Ok(address.to_string())
+}
+
+/// IP address of the interface used for outbound traffic
+fn local_ip() -> Option<std::net::IpAddr> {
+    let socket = std::net::UdpSocket::bind("0.0.0.0:0").ok()?;
+    // No packets are sent; connecting only selects a route
+    socket.connect("192.0.2.1:80").ok()?;
+    socket.local_addr().ok().map(|a| a.ip())
+}
+
+/// Start or stop the listener and periodic sync to match settings
+pub fn apply_settings(app: &AppHandle, settings: &PeerSyncSettings) {
+    let sync = app.state::<PeerSync>();
+    if let Some(task) = sync.listener.lock().unwrap().take() {
+        task.abort();
+    }
+    app.state::<Scheduler>().remove_job(PEER_SYNC_JOB_ID);
+    sync.update(|s| {
+        s.listening = false;
+        s.port = None;
+    });
+
+    if !settings.enabled {
+        return;
+    }
+
+    let port = settings.port;
+    let app_for_task = app.clone();
+    let task = tauri::async_runtime::spawn(async move {
+        let listener = match TcpListener::bind(("0.0.0.0", port)).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                tracing::warn!("Peer sync could not listen on port {}: {}", port, e);
+                app_for_task.state::<PeerSync>().update(|s| {
+                    s.last_error = Some(format!("Could not listen on port {}: {}", port, e))
+                });
+                return;
+            }
+        };
+        tracing::info!("Peer sync listening on port {}", port);
+        app_for_task.state::<PeerSync>().update(|s| {
+            s.listening = true;
+            s.port = Some(port);
+        });
+
+        while let Ok((stream, addr)) = listener.accept().await {
+            let app = app_for_task.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = handle_connection(app, stream).await {
+                    tracing::warn!("Peer connection from {} failed: {}", addr, e);
+                }
+            });
+        }
+    });
+    *sync.listener.lock().unwrap() = Some(task);
+
+    if let Some(mins) = settings.sync_interval_mins {
+        app.state::<Scheduler>().upsert_job(
+            ScheduledJob {
+                id: PEER_SYNC_JOB_ID.to_string(),
+                name: "LAN peer sync".to_string(),
+                schedule: Schedule::Interval {
+                    every_secs: mins.max(1) as u64 * 60,
+                },
+                skip_on_battery: false,
+                wait_for_idle: false,
+                // Keep paired devices from syncing with each other in lockstep
+                jitter_secs: 30,
+                action: JobAction::PeerSync,
+            },
+            chrono::Local::now(),
+        );
+    }
+}
+
+/// Load persisted settings and start listening if enabled
+pub fn start(app: &AppHandle) {
+    if let Ok(app_data_dir) = app.path().app_data_dir() {
+        apply_settings(app, &PeerSyncSettings::load(&app_data_dir));
+    }
+}
+
+// ============================================================
+// Commands
+// ============================================================
+
+/// What another device needs to pair with this one
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PairingInfo {
+    pub device_id: String,
+    pub device_name: String,
+    pub address: Option<String>,
+    /// `sbsync://` URI to show as a QR code
+    pub uri: Option<String>,
+}
+
+/// Get the peer sync settings
+#[tauri::command]
+pub async fn get_peer_sync_settings(app: AppHandle) -> Result<PeerSyncSettings, AppError> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    Ok(PeerSyncSettings::load(&app_data_dir))
+}
+
+/// Update the peer sync settings
+#[tauri::command]
+pub async fn set_peer_sync_settings(
+    app: AppHandle,
+    settings: PeerSyncSettings,
+) -> Result<(), AppError> {
+    settings.validate()?;
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    settings.save(&app_data_dir)?;
+    apply_settings(&app, &settings);
+    Ok(())
+}
+
+/// Get this device's pairing address and QR payload
+#[tauri::command]
+pub async fn get_pairing_info(app: AppHandle) -> Result<PairingInfo, AppError> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let settings = PeerSyncSettings::load(&app_data_dir);
+    let state = SyncState::load(&app_data_dir)?;
+
+    let address = settings
+        .enabled
+        .then(local_ip)
+        .flatten()
+        .map(|ip| match ip {
+            std::net::IpAddr::V6(ip) => format!("[{}]:{}", ip, settings.port),
+            ip => format!("{}:{}", ip, settings.port),
+        });
+    let uri = address
+        .as_ref()
+        .map(|a| format!("{}{}/{}", URI_SCHEME, a, state.device_id));
+
+    Ok(PairingInfo {
+        device_id: state.device_id,
+        device_name: settings.device_name,
+        address,
+        uri,
+    })
+}
+
+/// Pair with another device by address or pairing URI
+///
+/// Emits `peer-pairing-code` with the code to compare, which this device's
+/// user answers with `respond_to_pairing` as the other device's user does;
+/// the peer is saved only once both confirm the same code.
+#[tauri::command]
+pub async fn start_pairing(app: AppHandle, address: String) -> Result<Peer, AppError> {
+    let address = parse_address(&address)?;
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let settings = PeerSyncSettings::load(&app_data_dir);
+    let state = SyncState::load(&app_data_dir)?;
+
+    let mut stream = tokio::time::timeout(IO_TIMEOUT, TcpStream::connect(&address))
+        .await
+        .map_err(|_| format!("Timed out connecting to {}", address))?
+        .map_err(|e| format!("Failed to connect to {}: {}", address, e))?;
+
+    let secret = StaticSecret::from(random_bytes::<32>()?);
+    let our_public = PublicKey::from(&secret);
+    send_hello(
+        &mut stream,
+        &Hello::Pair {
+            version: PAIRING_VERSION,
+            device_id: state.device_id.clone(),
+            device_name: settings.device_name.clone(),
+            port: settings.port,
+            commitment: key_commitment(&our_public),
+        },
+    )
+    .await?;
+    let Hello::PairKey {
+        device_id,
+        device_name,
+        public_key,
+        ..
+    } = recv_hello(&mut stream).await?
+    else {
+        return Err(AppError::Network(
+            "Unexpected handshake from peer".to_string(),
+        ));
+    };
+    let their_public = parse_public_key(&public_key)?;
+    send_hello(
+        &mut stream,
+        &Hello::Reveal {
+            public_key: encode_hex(our_public.as_bytes()),
+        },
+    )
+    .await?;
+
+    let key = derive_pairing_key(&secret, &their_public, &state.device_id, &device_id);
+    let mut channel = SecureChannel::new(stream, &key, true);
+    let request = PairingCodeEvent {
+        peer_id: device_id.clone(),
+        device_name: device_name.clone(),
+        code: verification_code(&key),
+    };
+    confirm_pairing(&app, &mut channel, "peer-pairing-code", request).await?;
+    save_peer(&app_data_dir, &device_id, &device_name, &address, &key).map_err(AppError::from)
+}
+
+/// Accept or decline a pending pairing request, on either device
+#[tauri::command]
+pub async fn respond_to_pairing(
+    app: AppHandle,
+    peer_id: String,
+    accept: bool,
+) -> Result<(), AppError> {
+    let sender = app
+        .state::<PeerSync>()
+        .pending
+        .lock()
+        .unwrap()
+        .remove(&peer_id)
+        .ok_or_else(|| "No pending pairing request from this device".to_string())?;
+    sender
+        .send(accept)
+        .map_err(|_| "Pairing request expired".to_string())
+        .map_err(AppError::from)
+}
+
+/// List paired devices
+#[tauri::command]
+pub async fn list_peers(app: AppHandle) -> Result<Vec<Peer>, AppError> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    Ok(PeerList::load(&app_data_dir).peers)
+}
+
+/// Unpair a device and forget its key
+#[tauri::command]
+pub async fn remove_peer(app: AppHandle, peer_id: String) -> Result<(), AppError> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let mut list = PeerList::load(&app_data_dir);
+    list.peers.retain(|p| p.id != peer_id);
+    list.save(&app_data_dir)?;
+    keychain::delete_secret(&app_data_dir, &key_account(&peer_id)).map_err(AppError::from)
+}
+
+/// Sync with a paired device now
+#[tauri::command]
+pub async fn sync_with_peer(app: AppHandle, peer_id: String) -> Result<SyncSummary, AppError> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let peer = PeerList::load(&app_data_dir)
+        .peers
+        .into_iter()
+        .find(|p| p.id == peer_id)
+        .ok_or_else(|| format!("Device {} is not paired", peer_id))?;
+    sync_with(&app, &peer).await.map_err(AppError::from)
+}
+
+/// Get the peer sync status
+#[tauri::command]
+pub async fn get_peer_sync_status(app: AppHandle) -> Result<PeerSyncStatus, AppError> {
+    Ok(app.state::<PeerSync>().status.lock().unwrap().clone())
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clock(entries: &[(&str, u64)]) -> VectorClock {
+        VectorClock(entries.iter().map(|(d, c)| (d.to_string(), *c)).collect())
+    }
+
+    #[test]
+    fn test_clock_compare() {
+        let a = clock(&[("a", 2), ("b", 1)]);
+        assert_eq!(a.compare(&a.clone()), ClockOrdering::Equal);
+        assert_eq!(
+            a.compare(&clock(&[("a", 2), ("b", 2)])),
+            ClockOrdering::Before
+        );
+        assert_eq!(a.compare(&clock(&[("a", 1)])), ClockOrdering::After);
+        assert_eq!(
+            a.compare(&clock(&[("a", 1), ("b", 1), ("c", 1)])),
+            ClockOrdering::Concurrent
+        );
+    }
+
+    #[test]
+    fn test_clock_merge_and_increment() {
+        let mut a = clock(&[("a", 2), ("b", 1)]);
+        a.merge(&clock(&[("b", 3), ("c", 1)]));
+        a.increment("a");
+        assert_eq!(a, clock(&[("a", 3), ("b", 3), ("c", 1)]));
+    }
+
+    #[test]
+    fn test_needed_ids() {
+        let ours: HashMap<String, NoteMeta> = [
+            ("same", clock(&[("a", 1)])),
+            ("older", clock(&[("a", 1)])),
+            ("newer", clock(&[("a", 2)])),
+            ("forked", clock(&[("a", 2)])),
+        ]
+        .into_iter()
+        .map(|(id, clock)| {
+            (
+                id.to_string(),
+                NoteMeta {
+                    clock,
+                    ..Default::default()
+                },
+            )
+        })
+        .collect();
+        let theirs: HashMap<String, VectorClock> = [
+            ("same", clock(&[("a", 1)])),
+            ("older", clock(&[("a", 1), ("b", 1)])),
+            ("newer", clock(&[("a", 1)])),
+            ("forked", clock(&[("a", 1), ("b", 1)])),
+            ("missing", clock(&[("b", 1)])),
+        ]
+        .into_iter()
+        .map(|(id, clock)| (id.to_string(), clock))
+        .collect();
+
+        assert_eq!(
+            needed_ids(&ours, &theirs),
+            vec!["forked", "missing", "older"]
+        );
+    }
+
+    #[test]
+    fn test_pairing_keys_and_codes_match() {
+        let a = StaticSecret::from([1u8; 32]);
+        let b = StaticSecret::from([2u8; 32]);
+        let key_a = derive_pairing_key(&a, &PublicKey::from(&b), "device-a", "device-b");
+        let key_b = derive_pairing_key(&b, &PublicKey::from(&a), "device-b", "device-a");
+        assert_eq!(key_a, key_b);
+        assert_eq!(verification_code(&key_a), verification_code(&key_b));
+        assert_eq!(verification_code(&key_a).len(), 6);
+
+        // A relayed exchange with a different key pair yields a different code
+        let mallory = StaticSecret::from([3u8; 32]);
+        let key_m = derive_pairing_key(&a, &PublicKey::from(&mallory), "device-a", "device-b");
+        assert_ne!(key_a, key_m);
+    }
+
+    #[test]
+    fn test_key_commitment_binds_key() {
+        let a = PublicKey::from(&StaticSecret::from([1u8; 32]));
+        let b = PublicKey::from(&StaticSecret::from([2u8; 32]));
+        assert_eq!(key_commitment(&a), key_commitment(&a));
+        assert_ne!(key_commitment(&a), key_commitment(&b));
+        assert_eq!(
+            parse_public_key(&encode_hex(a.as_bytes()))
+                .unwrap()
+                .as_bytes(),
+            a.as_bytes()
+        );
+        assert!(parse_public_key("abcd").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_secure_channel_roundtrip_and_tamper() {
+        let key = derive_session_key(&[9u8; 32], b"initiator", b"responder");
+        let (client, server) = tokio::io::duplex(1024 * 1024);
+        let mut initiator = SecureChannel::new(client, &key, true);
+        let mut responder = SecureChannel::new(server, &key, false);
+
+        let responder_task = tokio::spawn(async move {
+            let reply = responder
+                .exchange(&Message::Request {
+                    ids: vec!["x".to_string()],
+                })
+                .await;
+            (reply, responder)
+        });
+        let reply = initiator.exchange(&Message::PairAccepted).await.unwrap();
+        assert!(matches!(reply, Message::Request { ref ids } if ids == &["x"]));
+        let (theirs, _) = responder_task.await.unwrap();
+        assert!(matches!(theirs.unwrap(), Message::PairAccepted));
+
+        // A channel keyed differently cannot read the stream
+        let (client, server) = tokio::io::duplex(1024);
+        let mut sender = SecureChannel::new(client, &key, true);
+        let mut wrong = SecureChannel::new(server, &[0u8; 32], false);
+        sender.send(&Message::PairAccepted).await.unwrap();
+        assert!(wrong.recv(IO_TIMEOUT).await.is_err());
+    }
+
+    #[test]
+    fn test_parse_address() {
+        assert_eq!(
+            parse_address("sbsync://192.168.1.5:47821/abcdef").unwrap(),
+            "192.168.1.5:47821"
+        );
+        assert_eq!(
+            parse_address(" host.local:5000 ").unwrap(),
+            "host.local:5000"
+        );
+        assert!(parse_address("host.local").is_err());
+        assert!(parse_address("host:99999").is_err());
+    }
+
+    #[test]
+    fn test_sync_id_maps_peer_copies_back() {
+        let note = LocalNote {
+            id: "local-1".to_string(),
+            title: String::new(),
+            content: String::new(),
+            tags: vec![],
+            folder: None,
+            created_at: String::new(),
+            updated_at: String::new(),
+            is_archived: false,
+            external_id: Some("peer:origin-9".to_string()),
+        };
+        assert_eq!(note.sync_id(), "origin-9");
+        assert_eq!(
+            LocalNote {
+                external_id: None,
+                ..note
+            }
+            .sync_id(),
+            "local-1"
+        );
+    }
+}
@@ -0,0 +1,349 @@
+//! Pinecone index setup checks.
+//!
+//! This module provides:
+//! - `check_pinecone_index`, which looks up the configured index and reports
+//!   whether it exists, is ready and matches what the backend writes, with
+//!   its vector count and fullness
+//! - `create_pinecone_index`, which creates a missing index as a serverless
+//!   index with the backend's dimension and metric
+//!
+//! The index name and environment come from the Pinecone secrets, with the
+//! same defaults as the backend's appsettings.json. Problems are reported
+//! here so RAG setup doesn't fail later with an opaque backend error.
+
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+use crate::error::AppError;
+use crate::Secrets;
+
+/// Pinecone's control plane
+const CONTROL_PLANE: &str = "https://api.pinecone.io";
+
+/// API version sent with every request
+const API_VERSION: &str = "2024-07";
+
+/// Embedding size the backend requires for Pinecone
+pub const DIMENSION: u32 = 1536;
+
+/// Similarity metric the backend's queries assume
+pub const METRIC: &str = "cosine";
+
+/// Index used when none is configured, as in the backend
+const DEFAULT_INDEX: &str = "second-brain-index";
+
+/// Environment used when none is configured, as in the backend
+const DEFAULT_ENVIRONMENT: &str = "us-east-1-aws";
+
+/// Timeout for each Pinecone request
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Where the configured index lives
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PineconeConfig {
+    api_key: String,
+    index_name: String,
+    cloud: String,
+    region: String,
+}
+
+impl PineconeConfig {
+    fn from_secrets(secrets: &Secrets) -> Result<Self, AppError> {
+        let non_empty = |value: &Option<String>| {
+            value
+                .as_deref()
+                .map(str::trim)
+                .filter(|v| !v.is_empty())
+                .map(str::to_string)
+        };
+        let api_key = non_empty(&secrets.pinecone_api_key)
+            .ok_or_else(|| AppError::NotReady("No Pinecone API key configured".to_string()))?;
+        let environment = non_empty(&secrets.pinecone_environment)
+            .unwrap_or_else(|| DEFAULT_ENVIRONMENT.to_string());
+        let (cloud, region) = parse_environment(&environment);
+        Ok(Self {
+            api_key,
+            index_name: non_empty(&secrets.pinecone_index_name)
+                .unwrap_or_else(|| DEFAULT_INDEX.to_string()),
+            cloud,
+            region,
+        })
+    }
+}
+
+/// Split an environment like `us-east-1-aws` into cloud and region; a bare
+/// region is taken to be on AWS
+fn parse_environment(environment: &str) -> (String, String) {
+    for cloud in ["aws", "gcp", "azure"] {
+        if let Some(region) = environment.strip_suffix(&format!("-{}", cloud)) {
+            return (cloud.to_string(), region.to_string());
+        }
+    }
+    ("aws".to_string(), environment.to_string())
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+struct IndexState {
+    #[serde(default)]
+    ready: bool,
+    #[serde(default)]
+    state: String,
+}
+
+/// An index as described by the control plane
+#[derive(Debug, Clone, Deserialize)]
+struct IndexDescription {
+    dimension: Option<u32>,
+    metric: Option<String>,
+    host: Option<String>,
+    #[serde(default)]
+    status: IndexState,
+}
+
+/// Contents of an index
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all(deserialize = "camelCase"))]
+pub struct IndexStats {
+    #[serde(default)]
+    pub total_vector_count: u64,
+    /// Fraction of the index's capacity in use (pod indexes only)
+    #[serde(default)]
+    pub index_fullness: f64,
+    #[serde(default)]
+    pub namespaces: serde_json::Map<String, serde_json::Value>,
+}
+
+/// What was found for the configured index
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct PineconeIndexStatus {
+    pub index_name: String,
+    pub exists: bool,
+    pub ready: bool,
+    /// Pinecone's state, e.g. `Ready` or `Initializing`
+    pub state: Option<String>,
+    pub dimension: Option<u32>,
+    pub metric: Option<String>,
+    /// None until the index is ready
+    pub stats: Option<IndexStats>,
+    /// Why the backend can't use the index as it is
+    pub problems: Vec<String>,
+}
+
+/// Mismatches between an index and what the backend writes
+fn problems(description: &IndexDescription) -> Vec<String> {
+    let mut problems = Vec::new();
+    if let Some(dimension) = description.dimension.filter(|d| *d != DIMENSION) {
+        problems.push(format!(
+            "The index has {} dimensions but the backend writes {}-dimension embeddings",
+            dimension, DIMENSION
+        ));
+    }
+    if let Some(metric) = description.metric.as_deref().filter(|m| *m != METRIC) {
+        problems.push(format!(
+            "The index uses the {} metric but the backend expects {}",
+            metric, METRIC
+        ));
+    }
+    problems
+}
+
+fn request(
+    client: &reqwest::Client,
+    config: &PineconeConfig,
+    method: reqwest::Method,
+    url: String,
+) -> reqwest::RequestBuilder {
+    client
+        .request(method, url)
+        .header("Api-Key", &config.api_key)
+        .header("X-Pinecone-API-Version", API_VERSION)
+        .timeout(REQUEST_TIMEOUT)
+}
+
+/// Turn an unsuccessful response into an error naming what failed
+async fn error_for(response: reqwest::Response, what: &str) -> AppError {
+    let status = response.status();
+    let body = response.text().await.unwrap_or_default();
+    let message = format!("{} failed ({}): {}", what, status, body.trim());
+    match status.as_u16() {
+        401 | 403 => AppError::Permission(message),
+        409 => AppError::Conflict(message),
+        400 | 422 => AppError::InvalidInput(message),
+        _ => AppError::Network(message),
+    }
+}
+
+async fn describe(
+    client: &reqwest::Client,
+    config: &PineconeConfig,
+) -> Result<Option<IndexDescription>, AppError> {
+    let url = format!("{}/indexes/{}", CONTROL_PLANE, config.index_name);
+    let response = request(client, config, reqwest::Method::GET, url)
+        .send()
+        .await?;
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    if !response.status().is_success() {
+        return Err(error_for(response, "Describing the Pinecone index").await);
+    }
+    Ok(Some(response.json().await?))
+}
+
+async fn stats(
+    client: &reqwest::Client,
+    config: &PineconeConfig,
+    host: &str,
+) -> Result<IndexStats, AppError> {
+    let url = format!("https://{}/describe_index_stats", host);
+    let response = request(client, config, reqwest::Method::POST, url)
+        .json(&serde_json::json!({}))
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        return Err(error_for(response, "Reading Pinecone index stats").await);
+    }
+    Ok(response.json().await?)
+}
+
+async fn status(
+    client: &reqwest::Client,
+    config: &PineconeConfig,
+) -> Result<PineconeIndexStatus, AppError> {
+    let mut status = PineconeIndexStatus {
+        index_name: config.index_name.clone(),
+        ..Default::default()
+    };
+    let Some(description) = describe(client, config).await? else {
+        status.problems.push(format!(
+            "The index {} doesn't exist; create it before indexing notes",
+            config.index_name
+        ));
+        return Ok(status);
+    };
+
+    status.exists = true;
+    status.ready = description.status.ready;
+    status.state = Some(description.status.state.clone()).filter(|s| !s.is_empty());
+    status.problems = problems(&description);
+    if let (true, Some(host)) = (status.ready, description.host.as_deref()) {
+        match stats(client, config, host).await {
+            Ok(stats) => status.stats = Some(stats),
+            Err(e) => status.problems.push(e.to_string()),
+        }
+    }
+    status.dimension = description.dimension;
+    status.metric = description.metric;
+    Ok(status)
+}
+
+async fn load_config(app: &AppHandle) -> Result<PineconeConfig, AppError> {
+    let app_data_dir = app.path().app_data_dir()?;
+    PineconeConfig::from_secrets(&crate::load_secrets_async(app_data_dir).await)
+}
+
+// ============================================================
+// Commands
+// ============================================================
+
+/// Check the configured Pinecone index and report its stats
+#[tauri::command]
+pub async fn check_pinecone_index(app: AppHandle) -> Result<PineconeIndexStatus, AppError> {
+    let config = load_config(&app).await?;
+    status(&crate::http::external(&app), &config).await
+}
+
+/// Create the configured Pinecone index if it doesn't exist
+///
+/// An existing index is left alone and reported as it is.
+#[tauri::command]
+pub async fn create_pinecone_index(app: AppHandle) -> Result<PineconeIndexStatus, AppError> {
+    let config = load_config(&app).await?;
+    let client = crate::http::external(&app);
+    if describe(&client, &config).await?.is_none() {
+        tracing::info!(
+            "Creating Pinecone index {} in {} {}",
+            config.index_name,
+            config.cloud,
+            config.region
+        );
+        let body = serde_json::json!({
+            "name": config.index_name,
+            "dimension": DIMENSION,
+            "metric": METRIC,
+            "spec": { "serverless": { "cloud": config.cloud, "region": config.region } },
+        });
+        let url = format!("{}/indexes", CONTROL_PLANE);
+        let response = request(&client, &config, reqwest::Method::POST, url)
+            .json(&body)
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(error_for(response, "Creating the Pinecone index").await);
+        }
+    }
+    status(&client, &config).await
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_environment() {
+        assert_eq!(
+            parse_environment("us-east-1-aws"),
+            ("aws".to_string(), "us-east-1".to_string())
+        );
+        assert_eq!(
+            parse_environment("europe-west4-gcp"),
+            ("gcp".to_string(), "europe-west4".to_string())
+        );
+        assert_eq!(
+            parse_environment("eu-west-1"),
+            ("aws".to_string(), "eu-west-1".to_string())
+        );
+    }
+
+    #[test]
+    fn test_config_from_secrets() {
+        assert!(PineconeConfig::from_secrets(&Secrets::default()).is_err());
+
+        let secrets = Secrets {
+            pinecone_api_key: Some("pc-key".to_string()),
+            pinecone_index_name: Some(" ".to_string()),
+            ..Secrets::default()
+        };
+        let config = PineconeConfig::from_secrets(&secrets).unwrap();
+        assert_eq!(config.index_name, DEFAULT_INDEX);
+        assert_eq!(config.region, "us-east-1");
+    }
+
+    #[test]
+    fn test_problems() {
+        let description: IndexDescription = serde_json::from_value(serde_json::json!({
+            "name": "second-brain-index",
+            "dimension": 768,
+            "metric": "dotproduct",
+            "host": "second-brain-index-abc.svc.pinecone.io",
+            "status": { "ready": true, "state": "Ready" }
+        }))
+        .unwrap();
+        assert_eq!(problems(&description).len(), 2);
+
+        let stats: IndexStats = serde_json::from_value(serde_json::json!({
+            "namespaces": { "user-1": { "vectorCount": 42 } },
+            "dimension": 1536,
+            "indexFullness": 0.1,
+            "totalVectorCount": 42
+        }))
+        .unwrap();
+        assert_eq!(stats.total_vector_count, 42);
+        assert_eq!(stats.namespaces.len(), 1);
+    }
+}
@@ -2,27 +2,68 @@
 //!
 //! This module provides:
 //! - Port availability checking
-//! - Finding alternative ports when conflicts occur
+//! - Concurrent probing of port ranges, with a bounded number of binds in
+//!   flight, for finding alternative ports when conflicts occur
 //! - Process identification on ports (macOS/Unix)
 
+use futures_util::stream::{self, StreamExt};
 use std::net::TcpListener;
 use std::process::Command;
 
+/// Most ports probed at once
+const MAX_CONCURRENT_PROBES: usize = 32;
+
 /// Check if a port is available for binding
 pub fn is_port_available(port: u16) -> bool {
     TcpListener::bind(("127.0.0.1", port)).is_ok()
 }
 
+/// Check if a port is available without blocking the async runtime
+pub async fn is_port_available_async(port: u16) -> bool {
+    tokio::net::TcpListener::bind(("127.0.0.1", port))
+        .await
+        .is_ok()
+}
+
+/// Probe ports concurrently; returns (port, available) in input order
+pub async fn probe_ports(ports: impl IntoIterator<Item = u16>) -> Vec<(u16, bool)> {
+    stream::iter(ports)
+        .map(|port| async move { (port, is_port_available_async(port).await) })
+        .buffered(MAX_CONCURRENT_PROBES)
+        .collect()
+        .await
+}
+
+/// Ports in a range, stopping at the top of the port space
+fn port_range(start_port: u16, max_attempts: u16) -> impl Iterator<Item = u16> {
+    (0..max_attempts).map_while(move |offset| start_port.checked_add(offset))
+}
+
 /// Find an available port starting from the given port
-/// Returns the first available port, or None if no port is available in range
+/// Returns the lowest available port, or None if no port is available in range
+pub async fn find_available_port_async(start_port: u16, max_attempts: u16) -> Option<u16> {
+    probe_ports(port_range(start_port, max_attempts))
+        .await
+        .into_iter()
+        .find(|(_, available)| *available)
+        .map(|(port, _)| port)
+}
+
+/// Blocking form of `find_available_port_async` for synchronous startup code
+///
+/// Must not be called from async code; use the async form there.
 pub fn find_available_port(start_port: u16, max_attempts: u16) -> Option<u16> {
-    for offset in 0..max_attempts {
-        let port = start_port.saturating_add(offset);
-        if is_port_available(port) {
-            return Some(port);
-        }
-    }
-    None
+    tauri::async_runtime::block_on(find_available_port_async(start_port, max_attempts))
+}
+
+/// Ports in use among those given, probed concurrently
+pub async fn ports_in_use(ports: impl IntoIterator<Item = u16>) -> Vec<u16> {
+    probe_ports(ports)
+        .await
+        .into_iter()
+        .filter(|(_, available)| !*available)
+        .map(|(port, _)| port)
+        .collect()
 }
 
 /// Get the process ID using a specific port (macOS/Unix only)
@@ -123,18 +164,18 @@ impl Default for PortRange {
 
 impl PortRange {
     /// Find available ports for both PostgreSQL and backend
-    pub fn find_available_ports(&self) -> Option<(u16, u16)> {
-        let postgres_port = find_available_port(
-            self.postgres_start,
-            self.postgres_end - self.postgres_start + 1,
-        )?;
-
-        let backend_port = find_available_port(
-            self.backend_start,
-            self.backend_end - self.backend_start + 1,
-        )?;
-
-        Some((postgres_port, backend_port))
+    pub async fn find_available_ports(&self) -> Option<(u16, u16)> {
+        let (postgres_port, backend_port) = tokio::join!(
+            find_available_port_async(
+                self.postgres_start,
+                self.postgres_end - self.postgres_start + 1,
+            ),
+            find_available_port_async(
+                self.backend_start,
+                self.backend_end - self.backend_start + 1,
+            ),
+        );
+        Some((postgres_port?, backend_port?))
     }
 }
 
@@ -202,8 +243,8 @@ mod tests {
         assert_eq!(range.backend_start, 5001);
     }
 
-    #[test]
-    fn test_port_range_find_available() {
+    #[tokio::test]
+    async fn test_port_range_find_available() {
         let range = PortRange {
             postgres_start: 55000,
             postgres_end: 55010,
@@ -211,10 +252,30 @@ mod tests {
             backend_end: 56010,
         };
 
-        let result = range.find_available_ports();
+        let result = range.find_available_ports().await;
         assert!(result.is_some());
     }
 
+    #[tokio::test]
+    async fn test_probe_ports_skips_ports_in_use() {
+        let held = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let port = held.local_addr().unwrap().port();
+
+        let results = probe_ports([port, port]).await;
+        assert_eq!(results, vec![(port, false), (port, false)]);
+        assert_eq!(ports_in_use([port]).await, vec![port]);
+        assert_ne!(find_available_port_async(port, 40).await, Some(port));
+    }
+
+    #[test]
+    fn test_port_range_stops_at_top() {
+        assert_eq!(
+            port_range(65533, 10).collect::<Vec<_>>(),
+            vec![65533, 65534, 65535]
+        );
+        assert_eq!(port_range(5000, 0).count(), 0);
+    }
+
     #[cfg(unix)]
     #[test]
     fn test_get_process_on_port_unused() {
@@ -0,0 +1,220 @@
+//! Power source detection and power assertions.
+//!
+//! This module provides:
+//! - Detection of whether the machine is running on battery power
+//! - `KeepAwake`, which stops the display and system from sleeping until
+//!   dropped (`caffeinate` on macOS, `systemd-inhibit` on Linux,
+//!   `SetThreadExecutionState` on Windows)
+
+use crate::error::AppError;
+
+/// Check whether the machine is currently running on battery power
+///
+/// Returns `false` when the power source cannot be determined, so background
+/// work is never blocked on desktops or unsupported platforms.
+pub fn is_on_battery() -> bool {
+    #[cfg(target_os = "macos")]
+    {
+        std::process::Command::new("pmset")
+            .args(["-g", "batt"])
+            .output()
+            .ok()
+            .map(|o| parse_pmset_output(&String::from_utf8_lossy(&o.stdout)))
+            .unwrap_or(false)
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        linux_on_battery(std::path::Path::new("/sys/class/power_supply"))
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "linux")))]
+    {
+        false
+    }
+}
+
+/// Parse `pmset -g batt` output
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn parse_pmset_output(output: &str) -> bool {
+    output.contains("'Battery Power'")
+}
+
+/// Inspect sysfs power supplies: on battery when no mains adapter is online
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn linux_on_battery(power_supply_dir: &std::path::Path) -> bool {
+    let Ok(entries) = std::fs::read_dir(power_supply_dir) else {
+        return false;
+    };
+
+    let mut has_battery = false;
+    for entry in entries.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        let supply_type = std::fs::read_to_string(path.join("type")).unwrap_or_default();
+
+        match supply_type.trim() {
+            "Mains" | "USB" => {
+                let online = std::fs::read_to_string(path.join("online")).unwrap_or_default();
+                if online.trim() == "1" {
+                    return false;
+                }
+            }
+            "Battery" => has_battery = true,
+            _ => {}
+        }
+    }
+
+    has_battery
+}
+
+/// Keeps the display and system awake until dropped
+pub struct KeepAwake {
+    #[cfg(any(target_os = "macos", target_os = "linux"))]
+    child: std::process::Child,
+    /// Dropping it ends the thread holding the execution state
+    #[cfg(windows)]
+    _release: std::sync::mpsc::Sender<()>,
+}
+
+impl KeepAwake {
+    /// Take a power assertion; `reason` is shown where the OS lists them
+    pub fn acquire(reason: &str) -> Result<Self, AppError> {
+        #[cfg(target_os = "macos")]
+        {
+            use std::process::{Command, Stdio};
+            let _ = reason;
+            // -d keeps the display on, -i the system; -w ends the assertion
+            // if the app exits without dropping it
+            let child = Command::new("caffeinate")
+                .args(["-d", "-i", "-w", &std::process::id().to_string()])
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .spawn()?;
+            Ok(Self { child })
+        }
+
+        #[cfg(target_os = "linux")]
+        {
+            use std::process::{Command, Stdio};
+            // The lock lasts as long as `cat`, which exits when its stdin
+            // closes: on drop, or when the app dies
+            let child = Command::new("systemd-inhibit")
+                .args([
+                    "--what=idle:sleep",
+                    "--who=Second Brain",
+                    &format!("--why={}", reason),
+                    "--mode=block",
+                    "cat",
+                ])
+                .stdin(Stdio::piped())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .spawn()?;
+            Ok(Self { child })
+        }
+
+        #[cfg(windows)]
+        {
+            use std::sync::mpsc;
+            use windows::Win32::System::Power::{
+                SetThreadExecutionState, ES_CONTINUOUS, ES_DISPLAY_REQUIRED, ES_SYSTEM_REQUIRED,
+            };
+            let _ = reason;
+            let (release, released) = mpsc::channel::<()>();
+            let (started_tx, started) = mpsc::channel();
+            // The execution state belongs to the thread that set it
+            std::thread::spawn(move || {
+                let previous = unsafe {
+                    SetThreadExecutionState(
+                        ES_CONTINUOUS | ES_DISPLAY_REQUIRED | ES_SYSTEM_REQUIRED,
+                    )
+                };
+                let _ = started_tx.send(previous.0 != 0);
+                let _ = released.recv();
+                unsafe { SetThreadExecutionState(ES_CONTINUOUS) };
+            });
+            match started.recv() {
+                Ok(true) => Ok(Self { _release: release }),
+                _ => Err(AppError::Internal(
+                    "Failed to keep the display awake".to_string(),
+                )),
+            }
+        }
+
+        #[cfg(not(any(target_os = "macos", target_os = "linux", windows)))]
+        {
+            let _ = reason;
+            Err(AppError::Internal(
+                "Keeping the display awake isn't supported on this platform".to_string(),
+            ))
+        }
+    }
+}
+
+impl Drop for KeepAwake {
+    fn drop(&mut self) {
+        #[cfg(target_os = "macos")]
+        let _ = self.child.kill();
+        #[cfg(target_os = "linux")]
+        drop(self.child.stdin.take());
+        #[cfg(any(target_os = "macos", target_os = "linux"))]
+        let _ = self.child.wait();
+    }
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write_supply(dir: &std::path::Path, name: &str, supply_type: &str, online: Option<&str>) {
+        let supply = dir.join(name);
+        std::fs::create_dir_all(&supply).unwrap();
+        std::fs::write(supply.join("type"), supply_type).unwrap();
+        if let Some(online) = online {
+            std::fs::write(supply.join("online"), online).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_parse_pmset_battery() {
+        let output =
+            "Now drawing from 'Battery Power'\n -InternalBattery-0 (id=1234)\t85%; discharging";
+        assert!(parse_pmset_output(output));
+    }
+
+    #[test]
+    fn test_parse_pmset_ac() {
+        let output = "Now drawing from 'AC Power'\n -InternalBattery-0 (id=1234)\t100%; charged";
+        assert!(!parse_pmset_output(output));
+    }
+
+    #[test]
+    fn test_linux_on_battery_when_mains_offline() {
+        let temp_dir = TempDir::new().unwrap();
+        write_supply(temp_dir.path(), "AC", "Mains\n", Some("0\n"));
+        write_supply(temp_dir.path(), "BAT0", "Battery\n", None);
+
+        assert!(linux_on_battery(temp_dir.path()));
+    }
+
+    #[test]
+    fn test_linux_on_ac_when_mains_online() {
+        let temp_dir = TempDir::new().unwrap();
+        write_supply(temp_dir.path(), "AC", "Mains\n", Some("1\n"));
+        write_supply(temp_dir.path(), "BAT0", "Battery\n", None);
+
+        assert!(!linux_on_battery(temp_dir.path()));
+    }
+
+    #[test]
+    fn test_linux_desktop_without_battery() {
+        let temp_dir = TempDir::new().unwrap();
+        assert!(!linux_on_battery(temp_dir.path()));
+    }
+}
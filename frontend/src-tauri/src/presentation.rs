@@ -0,0 +1,256 @@
+//! Presenting a note as slides in a full-screen window.
+//!
+//! This module provides:
+//! - `enter_presentation_mode`, which opens a borderless full-screen window
+//!   on the main window's monitor showing the note as slides
+//! - A power assertion for as long as the presentation runs, so the display
+//!   doesn't dim or sleep mid-talk
+//! - `exit_presentation_mode`, also run when the window is closed another
+//!   way, which releases the assertion and brings the main window back
+//! - A `presentation-changed` event
+//!
+//! A running presentation holds off the app lock's idle timeout, and locking
+//! the app ends it.
+//!
+//! The frontend's present route splits the note into slides on `---` rules
+//! and handles the keyboard; Escape calls `exit_presentation_mode`.
+
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tauri::{AppHandle, Manager, WebviewUrl, WebviewWindowBuilder};
+
+use crate::error::AppError;
+use crate::note_history::validate_note_id;
+use crate::power::KeepAwake;
+
+/// Prefix of presentation window labels; each window gets its own, so a
+/// closing window can't end the presentation that replaced it
+const LABEL_PREFIX: &str = "presentation-";
+
+struct Session {
+    label: String,
+    note_id: String,
+    /// `None` where the platform can't keep the display awake
+    keep_awake: Option<KeepAwake>,
+    /// Whether the main window was showing when the presentation started
+    main_was_visible: bool,
+}
+
+/// The running presentation, kept in Tauri state
+#[derive(Default)]
+pub struct Presentation {
+    next: AtomicU64,
+    session: Mutex<Option<Session>>,
+}
+
+/// Presentation state, emitted as `presentation-changed`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PresentationStatus {
+    pub active: bool,
+    pub note_id: Option<String>,
+    /// Whether sleep is being held off
+    pub keeping_awake: bool,
+}
+
+impl Presentation {
+    fn status(&self) -> PresentationStatus {
+        let session = self.session.lock();
+        PresentationStatus {
+            active: session.is_some(),
+            note_id: session.as_ref().map(|session| session.note_id.clone()),
+            keeping_awake: session
+                .as_ref()
+                .is_some_and(|session| session.keep_awake.is_some()),
+        }
+    }
+}
+
+/// Route of a note's slides, matching the frontend's router
+fn present_route(note_id: &str) -> String {
+    // The production build uses hash routing, the dev server browser routing
+    if cfg!(debug_assertions) {
+        format!("present/notes/{}", note_id)
+    } else {
+        format!("index.html#/present/notes/{}", note_id)
+    }
+}
+
+fn announce(app: &AppHandle) {
+    let status = app.state::<Presentation>().status();
+    crate::events::emit_critical(app, "presentation-changed", &status);
+}
+
+/// End the presentation if `label` is its window, or any presentation for
+/// `None`: release the power assertion, close the window and restore the
+/// main window
+fn finish(app: &AppHandle, label: Option<&str>) {
+    let session = {
+        let mut session = app.state::<Presentation>().session.lock();
+        if label.is_some_and(|label| session.as_ref().map(|s| s.label.as_str()) != Some(label)) {
+            return;
+        }
+        session.take()
+    };
+    let Some(session) = session else {
+        return;
+    };
+    let (label, main_was_visible) = (session.label.clone(), session.main_was_visible);
+    // Dropping the session releases the power assertion
+    drop(session);
+    tracing::info!("Presentation ended");
+
+    if let Some(window) = app.get_webview_window(&label) {
+        let _ = window.destroy();
+    }
+
+    if let Some(main) = app.get_webview_window("main") {
+        if main_was_visible {
+            let _ = main.show();
+            let _ = main.set_focus();
+        }
+    }
+    announce(app);
+}
+
+/// Whether a presentation is running
+pub fn is_active(app: &AppHandle) -> bool {
+    app.try_state::<Presentation>()
+        .is_some_and(|presentation| presentation.session.lock().is_some())
+}
+
+/// End any running presentation
+pub fn end(app: &AppHandle) {
+    if app.try_state::<Presentation>().is_some() {
+        finish(app, None);
+    }
+}
+
+/// End the presentation when its window closes
+pub fn on_destroyed(app: &AppHandle, label: &str) {
+    if label.starts_with(LABEL_PREFIX) && app.try_state::<Presentation>().is_some() {
+        finish(app, Some(label));
+    }
+}
+
+fn enter(app: &AppHandle, note_id: String) -> Result<PresentationStatus, AppError> {
+    validate_note_id(&note_id).map_err(AppError::InvalidInput)?;
+
+    // Presenting another note replaces the current one
+    finish(app, None);
+
+    let presentation = app.state::<Presentation>();
+    let label = format!(
+        "{}{}",
+        LABEL_PREFIX,
+        presentation.next.fetch_add(1, Ordering::Relaxed)
+    );
+    let main = app.get_webview_window("main");
+    let main_was_visible = main
+        .as_ref()
+        .is_some_and(|main| main.is_visible().unwrap_or(false));
+    let mut builder =
+        WebviewWindowBuilder::new(app, &label, WebviewUrl::App(present_route(&note_id).into()))
+            .title("Presentation — Second Brain")
+            .decorations(false)
+            .fullscreen(true)
+            .focused(true);
+    // Present on the screen the user is working on, e.g. a projector
+    if let Some(position) = main
+        .as_ref()
+        .and_then(|main| main.current_monitor().ok().flatten())
+        .map(|monitor| monitor.position().to_logical::<f64>(monitor.scale_factor()))
+    {
+        builder = builder.position(position.x, position.y);
+    }
+
+    let keep_awake = match KeepAwake::acquire("Presenting a note") {
+        Ok(keep_awake) => Some(keep_awake),
+        Err(e) => {
+            tracing::warn!("Presenting without keeping the display awake: {}", e);
+            None
+        }
+    };
+    *presentation.session.lock() = Some(Session {
+        label: label.clone(),
+        note_id: note_id.clone(),
+        keep_awake,
+        main_was_visible,
+    });
+
+    if let Err(e) = builder.build() {
+        finish(app, Some(&label));
+        return Err(e.into());
+    }
+    tracing::info!("Presenting note {}", note_id);
+    announce(app);
+    Ok(presentation.status())
+}
+
+// ============================================================
+// Commands
+// ============================================================
+
+/// Present a note as slides in a full-screen window
+#[tauri::command]
+pub async fn enter_presentation_mode(
+    app: AppHandle,
+    note_id: String,
+) -> Result<PresentationStatus, AppError> {
+    enter(&app, note_id)
+}
+
+/// Close the presentation and restore the main window
+#[tauri::command]
+pub async fn exit_presentation_mode(app: AppHandle) -> Result<PresentationStatus, AppError> {
+    end(&app);
+    Ok(app.state::<Presentation>().status())
+}
+
+/// Get whether a presentation is running
+#[tauri::command]
+pub async fn get_presentation_state(app: AppHandle) -> Result<PresentationStatus, AppError> {
+    Ok(app.state::<Presentation>().status())
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status() {
+        let presentation = Presentation::default();
+        assert_eq!(
+            presentation.status(),
+            PresentationStatus {
+                active: false,
+                note_id: None,
+                keeping_awake: false,
+            }
+        );
+
+        *presentation.session.lock() = Some(Session {
+            label: "presentation-0".to_string(),
+            note_id: "42".to_string(),
+            keep_awake: None,
+            main_was_visible: true,
+        });
+        assert_eq!(
+            presentation.status(),
+            PresentationStatus {
+                active: true,
+                note_id: Some("42".to_string()),
+                keeping_awake: false,
+            }
+        );
+    }
+
+    #[test]
+    fn test_present_route() {
+        assert!(present_route("42").ends_with("present/notes/42"));
+    }
+}
@@ -0,0 +1,375 @@
+//! Printing notes and saving them as PDF through the webview.
+//!
+//! This module provides:
+//! - `export_note_pdf`, which renders a note's print route in a hidden
+//!   window and saves it with the platform's print-to-PDF pipeline
+//! - `print_note`, which opens the same route in a preview window with the
+//!   native print dialog
+//! - `print_page_ready`, the print route's signal that the note has rendered
+//!
+//! PDFs come from `NSPrintOperation` on macOS, WebView2's `PrintToPdf` on
+//! Windows and a WebKitGTK print operation to a file on Linux. Unlike the
+//! plain-text renderer in `pdf`, they keep the app's formatting.
+
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::webview::PlatformWebview;
+use tauri::{AppHandle, Manager, WebviewUrl, WebviewWindow, WebviewWindowBuilder};
+use tokio::sync::oneshot;
+
+use crate::error::AppError;
+use crate::note_history::validate_note_id;
+
+/// How long the print route may take to load and render a note
+const READY_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How long the platform may take to write the PDF
+const PDF_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Letter-sized at 96 DPI, so layout matches the printed page width
+const PAGE_SIZE: (f64, f64) = (816.0, 1056.0);
+
+type Ready = oneshot::Receiver<Result<(), String>>;
+
+/// Print windows waiting for their page to render, kept in Tauri state
+#[derive(Default)]
+pub struct PrintJobs {
+    next: AtomicU64,
+    pending: Mutex<HashMap<String, oneshot::Sender<Result<(), String>>>>,
+}
+
+impl PrintJobs {
+    /// Reserve a window label and wait for its page
+    fn register(&self) -> (String, Ready) {
+        let label = format!("print-{}", self.next.fetch_add(1, Ordering::Relaxed));
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().insert(label.clone(), tx);
+        (label, rx)
+    }
+
+    /// Report a page as rendered; returns false for unknown windows
+    fn ready(&self, label: &str, result: Result<(), String>) -> bool {
+        match self.pending.lock().remove(label) {
+            Some(tx) => {
+                let _ = tx.send(result);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn cancel(&self, label: &str) {
+        self.pending.lock().remove(label);
+    }
+}
+
+/// Route of a note's print view, matching the frontend's router
+fn print_route(note_id: &str) -> String {
+    // The production build uses hash routing, the dev server browser routing
+    if cfg!(debug_assertions) {
+        format!("print/notes/{}", note_id)
+    } else {
+        format!("index.html#/print/notes/{}", note_id)
+    }
+}
+
+/// Completion of a platform print job, reported once
+#[derive(Clone)]
+struct Completion(Arc<Mutex<Option<oneshot::Sender<Result<(), String>>>>>);
+
+impl Completion {
+    fn new() -> (Self, oneshot::Receiver<Result<(), String>>) {
+        let (tx, rx) = oneshot::channel();
+        (Self(Arc::new(Mutex::new(Some(tx)))), rx)
+    }
+
+    fn finish(&self, result: Result<(), String>) {
+        if let Some(tx) = self.0.lock().take() {
+            let _ = tx.send(result);
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn print_to_pdf(webview: PlatformWebview, path: PathBuf, completion: Completion) {
+    use objc2::runtime::AnyObject;
+    use objc2_app_kit::{NSPrintInfo, NSPrintJobSavingURL, NSPrintSaveJob};
+    use objc2_foundation::NSURL;
+    use objc2_web_kit::WKWebView;
+
+    let Some(url) = NSURL::from_file_path(&path) else {
+        completion.finish(Err(format!("Invalid PDF path {:?}", path)));
+        return;
+    };
+    let url: &AnyObject = &url;
+    // Runs on the main thread, inside `with_webview`
+    let saved = unsafe {
+        let webview: &WKWebView = &*webview.inner().cast();
+        // A copy, so the app's shared print settings are left alone
+        let info = NSPrintInfo::initWithDictionary(
+            NSPrintInfo::alloc(),
+            &NSPrintInfo::sharedPrintInfo().dictionary(),
+        );
+        info.setJobDisposition(NSPrintSaveJob);
+        info.dictionary().insert(NSPrintJobSavingURL, url);
+
+        let operation = webview.printOperationWithPrintInfo(&info);
+        operation.setShowsPrintPanel(false);
+        operation.setShowsProgressPanel(false);
+        // WebKit renders blank pages unless the print view has a frame
+        if let Some(view) = operation.view() {
+            view.setFrame(webview.frame());
+        }
+        operation.runOperation()
+    };
+    completion.finish(if saved {
+        Ok(())
+    } else {
+        Err("The print operation failed".to_string())
+    });
+}
+
+#[cfg(windows)]
+fn print_to_pdf(webview: PlatformWebview, path: PathBuf, completion: Completion) {
+    use webview2_com::Microsoft::Web::WebView2::Win32::{
+        ICoreWebView2PrintSettings, ICoreWebView2_7,
+    };
+    use webview2_com::PrintToPdfCompletedHandler;
+    use windows::core::{Interface, HSTRING};
+
+    let done = completion.clone();
+    let handler = PrintToPdfCompletedHandler::create(Box::new(move |result, saved| {
+        done.finish(match result {
+            Ok(()) if saved => Ok(()),
+            Ok(()) => Err("WebView2 could not save the PDF".to_string()),
+            Err(e) => Err(e.to_string()),
+        });
+        Ok(())
+    }));
+    let started = unsafe {
+        webview
+            .controller()
+            .CoreWebView2()
+            .and_then(|core| core.cast::<ICoreWebView2_7>())
+            .and_then(|core| {
+                core.PrintToPdf(
+                    &HSTRING::from(path.as_path()),
+                    None::<&ICoreWebView2PrintSettings>,
+                    &handler,
+                )
+            })
+    };
+    if let Err(e) = started {
+        completion.finish(Err(e.to_string()));
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn print_to_pdf(webview: PlatformWebview, path: PathBuf, completion: Completion) {
+    use webkit2gtk::{PrintOperation, PrintOperationExt};
+
+    let Ok(uri) = tauri::Url::from_file_path(&path) else {
+        completion.finish(Err(format!("Invalid PDF path {:?}", path)));
+        return;
+    };
+    let settings = gtk::PrintSettings::new();
+    settings.set_printer("Print to File");
+    settings.set(gtk::PRINT_SETTINGS_OUTPUT_FILE_FORMAT, Some("pdf"));
+    settings.set(gtk::PRINT_SETTINGS_OUTPUT_URI, Some(uri.as_str()));
+
+    let operation = PrintOperation::new(&webview.inner());
+    operation.set_print_settings(&settings);
+    // `finished` follows `failed`, so a failure is reported first
+    let failed = completion.clone();
+    operation.connect_failed(move |_, e| failed.finish(Err(e.to_string())));
+    operation.connect_finished(move |_| completion.finish(Ok(())));
+    operation.print();
+}
+
+#[cfg(not(any(target_os = "macos", windows, target_os = "linux")))]
+fn print_to_pdf(_webview: PlatformWebview, _path: PathBuf, completion: Completion) {
+    completion.finish(Err(
+        "Saving as PDF isn't supported on this platform".to_string()
+    ));
+}
+
+/// Open a note's print route and wait until it has rendered
+async fn open(app: &AppHandle, note_id: &str, visible: bool) -> Result<WebviewWindow, AppError> {
+    validate_note_id(note_id).map_err(AppError::InvalidInput)?;
+    let jobs = app.state::<PrintJobs>();
+    let (label, ready) = jobs.register();
+
+    let window =
+        WebviewWindowBuilder::new(app, &label, WebviewUrl::App(print_route(note_id).into()))
+            .title("Print — Second Brain")
+            .inner_size(PAGE_SIZE.0, PAGE_SIZE.1)
+            .visible(visible)
+            .focused(visible)
+            .build();
+    let window = match window {
+        Ok(window) => window,
+        Err(e) => {
+            jobs.cancel(&label);
+            return Err(e.into());
+        }
+    };
+
+    let rendered = match tokio::time::timeout(READY_TIMEOUT, ready).await {
+        Ok(Ok(result)) => result.map_err(AppError::Backend),
+        Ok(Err(_)) => Err(AppError::Cancelled(
+            "The print window was closed".to_string(),
+        )),
+        Err(_) => Err(AppError::Internal(
+            "The note took too long to render".to_string(),
+        )),
+    };
+    if let Err(e) = rendered {
+        jobs.cancel(&label);
+        let _ = window.destroy();
+        return Err(e);
+    }
+    Ok(window)
+}
+
+/// Write a rendered print window to a PDF file
+async fn save_pdf(window: &WebviewWindow, path: &Path) -> Result<(), AppError> {
+    let (completion, done) = Completion::new();
+    let path = path.to_path_buf();
+    window.with_webview(move |webview| print_to_pdf(webview, path, completion))?;
+    match tokio::time::timeout(PDF_TIMEOUT, done).await {
+        Ok(Ok(result)) => result.map_err(AppError::Io),
+        Ok(Err(_)) => Err(AppError::Cancelled("The print job was dropped".to_string())),
+        Err(_) => Err(AppError::Internal(
+            "Saving the PDF took too long".to_string(),
+        )),
+    }
+}
+
+/// Check and normalize a PDF destination
+fn pdf_path(path: &str) -> Result<PathBuf, AppError> {
+    let path = PathBuf::from(path);
+    if !path.is_absolute() {
+        return Err(AppError::InvalidInput(format!(
+            "PDF path must be absolute: {}",
+            path.display()
+        )));
+    }
+    if !path.parent().is_some_and(Path::is_dir) {
+        return Err(AppError::NotFound(format!(
+            "Folder doesn't exist: {}",
+            path.display()
+        )));
+    }
+    let is_pdf = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("pdf"));
+    Ok(if is_pdf {
+        path
+    } else {
+        path.with_extension("pdf")
+    })
+}
+
+// ============================================================
+// Commands
+// ============================================================
+
+/// Save a note as a PDF, returning the file written
+#[tauri::command]
+pub async fn export_note_pdf(
+    app: AppHandle,
+    note_id: String,
+    path: String,
+) -> Result<String, AppError> {
+    let path = pdf_path(&path)?;
+    let window = open(&app, &note_id, false).await?;
+    let saved = save_pdf(&window, &path).await;
+    let _ = window.destroy();
+    saved?;
+    tracing::info!("Saved note {} as {:?}", note_id, path);
+    Ok(path.to_string_lossy().to_string())
+}
+
+/// Open a note's print preview with the native print dialog
+#[tauri::command]
+pub async fn print_note(app: AppHandle, note_id: String) -> Result<(), AppError> {
+    let window = open(&app, &note_id, true).await?;
+    // The dialog belongs to the preview, which the user closes when done
+    window.print()?;
+    Ok(())
+}
+
+/// Signal from the print route that its note has rendered, or failed to load
+#[tauri::command]
+pub async fn print_page_ready(
+    app: AppHandle,
+    window: WebviewWindow,
+    error: Option<String>,
+) -> Result<(), AppError> {
+    let result = error.map_or(Ok(()), Err);
+    if !app.state::<PrintJobs>().ready(window.label(), result) {
+        return Err(AppError::NotFound(format!(
+            "No print job for window {}",
+            window.label()
+        )));
+    }
+    Ok(())
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_jobs() {
+        let jobs = PrintJobs::default();
+        let (first, mut first_ready) = jobs.register();
+        let (second, _) = jobs.register();
+        assert_ne!(first, second);
+
+        assert!(jobs.ready(&first, Ok(())));
+        assert_eq!(first_ready.try_recv().unwrap(), Ok(()));
+        // Reported once
+        assert!(!jobs.ready(&first, Ok(())));
+
+        jobs.cancel(&second);
+        assert!(!jobs.ready(&second, Err("gone".to_string())));
+    }
+
+    #[test]
+    fn test_pdf_path() {
+        let dir = TempDir::new().unwrap();
+        let base = dir.path().join("Note");
+
+        assert_eq!(
+            pdf_path(base.to_str().unwrap()).unwrap(),
+            dir.path().join("Note.pdf")
+        );
+        let upper = dir.path().join("Note.PDF");
+        assert_eq!(pdf_path(upper.to_str().unwrap()).unwrap(), upper);
+
+        assert!(matches!(
+            pdf_path("relative.pdf"),
+            Err(AppError::InvalidInput(_))
+        ));
+        let missing = dir.path().join("missing").join("Note.pdf");
+        assert!(matches!(
+            pdf_path(missing.to_str().unwrap()),
+            Err(AppError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_print_route() {
+        assert!(print_route("42").ends_with("print/notes/42"));
+    }
+}
@@ -0,0 +1,369 @@
+//! Separate profiles selected with `--profile <name>`.
+//!
+//! This module provides:
+//! - Validation of profile names and the app identifier each one runs under,
+//!   which gives it its own app data directory, keychain service and
+//!   single-instance lock
+//! - A per-profile port offset so profiles started side by side don't race
+//!   for the same PostgreSQL and backend ports
+//! - A lock file in the profile's data directory held for the life of the
+//!   process
+//!
+//! The single-instance plugin stops a second launch of the same profile and
+//! focuses the running one. The lock file also covers launches the plugin
+//! can't see, such as a dev build and an installed build sharing a data
+//! directory. The OS releases it when the process exits, so a crash never
+//! leaves a stale lock behind.
+
+use parking_lot::Mutex;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::config::ServiceConfig;
+use crate::error::AppError;
+
+/// Longest accepted profile name
+const MAX_NAME_LEN: usize = 32;
+
+/// Distinct port offsets profiles are spread over
+const PORT_SLOTS: u16 = 99;
+
+/// Gap between two profiles' ports
+const PORT_STRIDE: u16 = 10;
+
+/// Lock file in the profile's app data directory
+pub(crate) const LOCK_FILE: &str = "instance.lock";
+
+/// Name prefix of throwaway demo profiles, reserved for `--demo`
+pub const DEMO_PREFIX: &str = "demo-";
+
+/// Profile the app runs as, kept in Tauri state
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Profile {
+    /// None for the default profile
+    pub name: Option<String>,
+    /// Throwaway profile deleted on exit (`--demo`)
+    pub demo: bool,
+}
+
+impl Profile {
+    /// Profile for a `--profile` value; "default" names the default profile
+    pub fn new(name: Option<&str>) -> Result<Self, AppError> {
+        let Some(name) = name.map(str::trim) else {
+            return Ok(Self::default());
+        };
+        if name == "default" {
+            return Ok(Self::default());
+        }
+        let valid = !name.is_empty()
+            && name.len() <= MAX_NAME_LEN
+            && name
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+            && !name.starts_with('-');
+        if !valid {
+            return Err(AppError::InvalidInput(format!(
+                "Invalid profile name '{}': use up to {} lowercase letters, digits or dashes",
+                name, MAX_NAME_LEN
+            )));
+        }
+        if name.starts_with(DEMO_PREFIX) {
+            return Err(AppError::InvalidInput(format!(
+                "Profile names starting with '{}' are reserved for --demo",
+                DEMO_PREFIX
+            )));
+        }
+        Ok(Self {
+            name: Some(name.to_string()),
+            demo: false,
+        })
+    }
+
+    /// Demo profile for this process, so demos never share data
+    pub fn demo() -> Self {
+        Self {
+            name: Some(format!("{}{}", DEMO_PREFIX, std::process::id())),
+            demo: true,
+        }
+    }
+
+    pub fn is_default(&self) -> bool {
+        self.name.is_none()
+    }
+
+    /// App identifier for this profile, derived from the bundle's
+    ///
+    /// Tauri names the app data directory after the identifier, and the
+    /// single-instance plugin scopes its lock to it.
+    pub fn identifier(&self, base: &str) -> String {
+        match &self.name {
+            Some(name) => format!("{}.profile-{}", base, name),
+            None => base.to_string(),
+        }
+    }
+
+    /// Amount added to the default service ports
+    pub fn port_offset(&self) -> u16 {
+        let Some(name) = &self.name else {
+            return 0;
+        };
+        let digest = Sha256::digest(name.as_bytes());
+        let slot = u16::from_be_bytes([digest[0], digest[1]]) % PORT_SLOTS + 1;
+        slot * PORT_STRIDE
+    }
+
+    /// Ports to try when the profile has no cached service config
+    pub fn service_config(&self) -> ServiceConfig {
+        let offset = self.port_offset();
+        let defaults = ServiceConfig::default();
+        ServiceConfig {
+            postgres_port: defaults.postgres_port + offset,
+            backend_port: defaults.backend_port + offset,
+            ..defaults
+        }
+    }
+
+    /// Window title naming the profile, so profiles are told apart on screen
+    pub fn title(&self, base: &str) -> String {
+        match &self.name {
+            Some(_) if self.demo => format!("{} (demo)", base),
+            Some(name) => format!("{} ({})", base, name),
+            None => base.to_string(),
+        }
+    }
+}
+
+/// Profile the app was launched with
+pub fn current<R: Runtime>(app: &AppHandle<R>) -> Profile {
+    app.try_state::<Profile>()
+        .map(|profile| profile.inner().clone())
+        .unwrap_or_default()
+}
+
+/// Exclusive lock on a profile's data directory, released on drop
+#[derive(Debug)]
+pub struct ProfileLock {
+    path: PathBuf,
+    file: Mutex<Option<File>>,
+}
+
+impl ProfileLock {
+    /// Take the lock, failing if another process holds it
+    pub fn acquire(app_data_dir: &Path) -> Result<Self, AppError> {
+        std::fs::create_dir_all(app_data_dir)?;
+        let path = app_data_dir.join(LOCK_FILE);
+        let mut file = open_exclusive(&path).map_err(|e| {
+            if is_held_elsewhere(&e) {
+                let holder = std::fs::read_to_string(&path)
+                    .ok()
+                    .and_then(|pid| pid.trim().parse::<u32>().ok());
+                AppError::Conflict(match holder {
+                    Some(pid) => format!(
+                        "This profile is already running (process {}); use --profile to start another",
+                        pid
+                    ),
+                    None => "This profile is already running; use --profile to start another"
+                        .to_string(),
+                })
+            } else {
+                AppError::from(e)
+            }
+        })?;
+
+        // The pid is informational; the OS lock is what excludes others
+        file.set_len(0)?;
+        write!(file, "{}", std::process::id())?;
+        file.sync_all()?;
+        Ok(Self {
+            path,
+            file: Mutex::new(Some(file)),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Release the lock early, e.g. before deleting the directory
+    pub fn release(&self) {
+        self.file.lock().take();
+    }
+}
+
+/// Whether opening the lock failed because someone else holds it
+fn is_held_elsewhere(error: &std::io::Error) -> bool {
+    // ERROR_SHARING_VIOLATION: another handle has the file open unshared
+    error.kind() == std::io::ErrorKind::WouldBlock
+        || (cfg!(windows) && error.raw_os_error() == Some(32))
+}
+
+#[cfg(unix)]
+fn open_exclusive(path: &Path) -> std::io::Result<File> {
+    use std::os::unix::io::AsRawFd;
+
+    let file = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(path)?;
+    // SAFETY: the descriptor belongs to `file`, which outlives the call
+    if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) } != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(file)
+}
+
+#[cfg(windows)]
+fn open_exclusive(path: &Path) -> std::io::Result<File> {
+    use std::os::windows::fs::OpenOptionsExt;
+
+    // No sharing: other opens fail until this handle is closed
+    std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .share_mode(0)
+        .open(path)
+}
+
+#[cfg(not(any(unix, windows)))]
+fn open_exclusive(path: &Path) -> std::io::Result<File> {
+    std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(path)
+}
+
+/// Take the profile lock for the app's data directory and keep it in state
+pub fn lock(app: &AppHandle) -> Result<(), AppError> {
+    let app_data_dir = app.path().app_data_dir()?;
+    let lock = ProfileLock::acquire(&app_data_dir)?;
+    tracing::info!("Holding profile lock {:?}", lock.path());
+    app.manage(lock);
+    Ok(())
+}
+
+// ============================================================
+// Commands
+// ============================================================
+
+/// Profile details shown in settings
+#[derive(Debug, Clone, Serialize)]
+pub struct ProfileInfo {
+    pub name: Option<String>,
+    /// The UI should mark itself as a demo whose data is discarded
+    pub demo: bool,
+    pub app_data_dir: String,
+    pub port_offset: u16,
+}
+
+/// Get the profile the app is running as
+#[tauri::command]
+pub async fn get_profile(app: AppHandle) -> Result<ProfileInfo, AppError> {
+    let profile = current(&app);
+    Ok(ProfileInfo {
+        app_data_dir: app.path().app_data_dir()?.to_string_lossy().to_string(),
+        port_offset: profile.port_offset(),
+        demo: profile.demo,
+        name: profile.name,
+    })
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_new() {
+        assert!(Profile::new(None).unwrap().is_default());
+        assert!(Profile::new(Some("default")).unwrap().is_default());
+        assert_eq!(
+            Profile::new(Some(" work ")).unwrap().name.as_deref(),
+            Some("work")
+        );
+        assert!(Profile::new(Some("client-2")).is_ok());
+
+        for invalid in [
+            "demo-1234",
+            "",
+            "Work",
+            "a/b",
+            "../x",
+            "-x",
+            "has space",
+            &"x".repeat(33),
+        ] {
+            assert!(Profile::new(Some(invalid)).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_identifier_and_title() {
+        let base = "com.secondbrain.desktop";
+        assert_eq!(Profile::default().identifier(base), base);
+        let work = Profile::new(Some("work")).unwrap();
+        assert_eq!(
+            work.identifier(base),
+            "com.secondbrain.desktop.profile-work"
+        );
+        assert_eq!(work.title("Second Brain"), "Second Brain (work)");
+        assert_eq!(Profile::default().title("Second Brain"), "Second Brain");
+
+        let demo = Profile::demo();
+        assert!(demo.demo);
+        assert!(demo.identifier(base).contains(".profile-demo-"));
+        assert_eq!(demo.title("Second Brain"), "Second Brain (demo)");
+    }
+
+    #[test]
+    fn test_port_offset() {
+        assert_eq!(Profile::default().port_offset(), 0);
+        assert_eq!(
+            Profile::default().service_config().backend_port,
+            ServiceConfig::default().backend_port
+        );
+
+        let work = Profile::new(Some("work")).unwrap();
+        let offset = work.port_offset();
+        assert!((PORT_STRIDE..=PORT_SLOTS * PORT_STRIDE).contains(&offset));
+        assert_eq!(offset % PORT_STRIDE, 0);
+        // Stable across launches
+        assert_eq!(offset, Profile::new(Some("work")).unwrap().port_offset());
+
+        let config = work.service_config();
+        assert_eq!(config.postgres_port, 5433 + offset);
+        assert_eq!(config.backend_port, 5001 + offset);
+    }
+
+    #[test]
+    fn test_lock_is_exclusive() {
+        let dir = TempDir::new().unwrap();
+        let lock = ProfileLock::acquire(dir.path()).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(lock.path()).unwrap(),
+            std::process::id().to_string()
+        );
+
+        #[cfg(any(unix, windows))]
+        assert!(matches!(
+            ProfileLock::acquire(dir.path()),
+            Err(AppError::Conflict(_))
+        ));
+
+        lock.release();
+        assert!(ProfileLock::acquire(dir.path()).is_ok());
+    }
+}
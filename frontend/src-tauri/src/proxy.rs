@@ -0,0 +1,197 @@
+//! Backend request proxy for calls routed through the Rust layer.
+//!
+//! This module provides:
+//! - Forwarding of webview requests to the embedded backend API
+//! - Response caching for idempotent AI operations (see `ai_cache`)
+//! - Queuing of note writes while the backend is down (see `write_queue`)
+//! - Recording of AI token usage and estimated cost (see `usage`)
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use tauri::{AppHandle, Manager};
+
+use crate::ai_cache::{AiCache, AiCacheStats};
+use crate::error::AppError;
+use crate::AppState;
+
+/// Get the shared AI cache, creating it on first use
+pub fn ai_cache(app: &AppHandle) -> Result<Arc<AiCache>, String> {
+    let state = app.state::<AppState>();
+    if let Some(ref existing) = *state.ai_cache.read() {
+        return Ok(existing.clone());
+    }
+
+    // Re-check under the write lock in case another caller created it first
+    let mut cache = state.ai_cache.write();
+    if let Some(ref existing) = *cache {
+        return Ok(existing.clone());
+    }
+
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let created = Arc::new(AiCache::new(&app_data_dir));
+    *cache = Some(created.clone());
+    Ok(created)
+}
+
+/// Send a request to the backend API, attaching the registered auth header
+///
+/// `path` is relative to `/api`. Extra headers override the registered auth.
+pub async fn send_backend_request(
+    app: &AppHandle,
+    method: &str,
+    path: &str,
+    body: Option<&serde_json::Value>,
+    headers: Option<HashMap<String, String>>,
+) -> Result<serde_json::Value, AppError> {
+    let state = app.state::<AppState>();
+    let port = *state.backend_port.read();
+    let auth = state.backend_auth.read().clone();
+    let url = format!("http://localhost:{}/api{}", port, path);
+
+    let method = reqwest::Method::from_bytes(method.to_ascii_uppercase().as_bytes())
+        .map_err(|e| AppError::InvalidInput(format!("Invalid HTTP method: {}", e)))?;
+
+    let headers = headers.unwrap_or_default();
+    let has_auth_override = headers
+        .keys()
+        .any(|name| name.eq_ignore_ascii_case("authorization"));
+
+    let mut request = crate::http::backend(app)
+        .request(method, &url)
+        .timeout(std::time::Duration::from_secs(300));
+    if let Some(auth) = auth.filter(|_| !has_auth_override) {
+        request = request.header("Authorization", auth);
+    }
+    for (name, value) in headers {
+        request = request.header(name, value);
+    }
+    if let Some(body) = body {
+        request = request.json(body);
+    }
+
+    let response = request.send().await.map_err(|e| {
+        let message = format!("Backend request failed: {}", e);
+        if e.is_connect() {
+            AppError::NotReady(message)
+        } else {
+            AppError::Backend(message)
+        }
+    })?;
+
+    let status = response.status();
+    let text = response
+        .text()
+        .await
+        .map_err(|e| AppError::Backend(format!("Failed to read backend response: {}", e)))?;
+    let value = serde_json::from_str(&text).unwrap_or(serde_json::Value::String(text));
+
+    if !status.is_success() {
+        let message = format!("Backend returned {}: {}", status, value);
+        return Err(match status {
+            reqwest::StatusCode::NOT_FOUND => AppError::NotFound(message),
+            reqwest::StatusCode::BAD_REQUEST | reqwest::StatusCode::UNPROCESSABLE_ENTITY => {
+                AppError::InvalidInput(message)
+            }
+            reqwest::StatusCode::UNAUTHORIZED | reqwest::StatusCode::FORBIDDEN => {
+                AppError::Permission(message)
+            }
+            reqwest::StatusCode::CONFLICT => AppError::Conflict(message),
+            _ => AppError::Backend(message),
+        });
+    }
+
+    Ok(value)
+}
+
+/// Register the Authorization header used for backend calls made by the shell
+///
+/// The frontend calls this after sign-in (and with `None` on sign-out) so that
+/// background jobs can act on behalf of the current user.
+#[tauri::command]
+pub async fn set_backend_auth(
+    app: AppHandle,
+    authorization: Option<String>,
+) -> Result<(), AppError> {
+    let state = app.state::<AppState>();
+    *state.backend_auth.write() = authorization.filter(|a| !a.trim().is_empty());
+    Ok(())
+}
+
+/// Forward a request to the backend, serving idempotent AI calls from the cache
+///
+/// Writes with `queue_offline` (by default, note writes) are queued while the
+/// backend is down and answered with `{ queued: true, id }`.
+#[tauri::command]
+pub async fn proxy_request(
+    app: AppHandle,
+    method: String,
+    path: String,
+    body: Option<serde_json::Value>,
+    headers: Option<HashMap<String, String>>,
+    cache: Option<bool>,
+    queue_offline: Option<bool>,
+) -> Result<serde_json::Value, AppError> {
+    if queue_offline.unwrap_or_else(|| crate::write_queue::is_queueable(&method, &path)) {
+        return crate::write_queue::send_or_queue(&app, &method, &path, body, headers).await;
+    }
+
+    let use_cache = cache.unwrap_or_else(|| AiCache::is_cacheable(&path));
+    let cache_key = AiCache::request_key(&method, &path, body.as_ref());
+
+    let ai_cache = if use_cache {
+        let ai_cache = ai_cache(&app)?;
+        let lookup = {
+            let ai_cache = ai_cache.clone();
+            let key = cache_key.clone();
+            tokio::task::spawn_blocking(move || ai_cache.get(&key))
+                .await
+                .map_err(|e| format!("Task panicked: {}", e))?
+        };
+        if let Some(cached) = lookup {
+            tracing::debug!("AI cache hit for {} {}", method, path);
+            return Ok(cached);
+        }
+        Some(ai_cache)
+    } else {
+        None
+    };
+
+    let value = send_backend_request(&app, &method, &path, body.as_ref(), headers).await?;
+
+    {
+        let app = app.clone();
+        let response = value.clone();
+        tokio::task::spawn_blocking(move || {
+            crate::usage::record(&app, body.as_ref(), &response);
+        });
+    }
+
+    if let Some(ai_cache) = ai_cache {
+        let stored = value.clone();
+        tokio::task::spawn_blocking(move || {
+            if let Err(e) = ai_cache.put(&cache_key, &stored) {
+                tracing::warn!("Failed to cache AI response: {}", e);
+            }
+        });
+    }
+
+    Ok(value)
+}
+
+/// Remove all cached AI responses
+#[tauri::command]
+pub async fn clear_ai_cache(app: AppHandle) -> Result<usize, AppError> {
+    let ai_cache = ai_cache(&app)?;
+    tokio::task::spawn_blocking(move || ai_cache.clear())
+        .await?
+        .map_err(AppError::from)
+}
+
+/// Get AI cache hit/miss statistics
+#[tauri::command]
+pub async fn get_ai_cache_stats(app: AppHandle) -> Result<AiCacheStats, AppError> {
+    let ai_cache = ai_cache(&app)?;
+    tokio::task::spawn_blocking(move || ai_cache.stats())
+        .await
+        .map_err(AppError::from)
+}
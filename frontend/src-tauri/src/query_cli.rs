@@ -0,0 +1,166 @@
+//! `second-brain query` for launchers such as Raycast and Alfred.
+//!
+//! This module provides:
+//! - A one-shot mode that searches notes, or saves a note with `--capture`,
+//!   in the running app through the control API, then exits
+//! - Plain-text output, one `title<TAB>secondbrain://note/<id>` line per
+//!   search result, or `--json` output shaped like the native messaging
+//!   replies: `{"ok": true, "result"}` or `{"ok": false, "error"}`
+//!
+//! Like the native messaging host, it runs instead of the app and never
+//! touches the database or backend itself, so the control API has to be
+//! enabled. The exit code is 0 on success and 1 on failure.
+
+use crate::cli::QueryArgs;
+use crate::error::AppError;
+use crate::native_messaging::Host;
+
+/// Control API call for a query
+fn control_call(args: &QueryArgs) -> Result<(&'static str, serde_json::Value), AppError> {
+    let text = args.text.trim();
+    if text.is_empty() {
+        return Err(AppError::InvalidInput(
+            "Usage: second-brain query <text> [--capture] [--limit <n>] [--json]".to_string(),
+        ));
+    }
+    Ok(if args.capture {
+        ("/v1/capture", serde_json::json!({ "content": text }))
+    } else {
+        (
+            "/v1/notes/search",
+            serde_json::json!({ "query": text, "limit": args.limit }),
+        )
+    })
+}
+
+fn note_line(note: &serde_json::Value) -> Option<String> {
+    let id = match note.get("id")? {
+        serde_json::Value::String(id) => id.clone(),
+        id => id.to_string(),
+    };
+    let title = note
+        .get("title")
+        .and_then(|title| title.as_str())
+        .filter(|title| !title.trim().is_empty())
+        .unwrap_or("Untitled");
+    Some(format!("{}\tsecondbrain://note/{}", title, id))
+}
+
+/// Plain-text rendering of a result
+fn plain_output(args: &QueryArgs, result: &serde_json::Value) -> String {
+    if args.capture {
+        if result.get("queued").and_then(|queued| queued.as_bool()) == Some(true) {
+            return "Queued until Second Brain's backend is back".to_string();
+        }
+        return note_line(result).unwrap_or_else(|| "Saved".to_string());
+    }
+    let notes = result
+        .get("items")
+        .or_else(|| result.get("data"))
+        .unwrap_or(result)
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    notes
+        .iter()
+        .filter_map(note_line)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Run the query against the running app and print the result
+///
+/// Returns the process exit code.
+pub fn run(identifier: &str, args: &QueryArgs) -> i32 {
+    let result = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|e| AppError::Internal(e.to_string()))
+        .and_then(|runtime| {
+            let (path, body) = control_call(args)?;
+            let host = Host::new(identifier).map_err(AppError::Internal)?;
+            runtime.block_on(host.call("POST", path, body))
+        });
+
+    match (result, args.json) {
+        (Ok(result), true) => {
+            println!("{}", serde_json::json!({ "ok": true, "result": result }));
+            0
+        }
+        (Ok(result), false) => {
+            let output = plain_output(args, &result);
+            if !output.is_empty() {
+                println!("{}", output);
+            }
+            0
+        }
+        (Err(error), true) => {
+            println!("{}", serde_json::json!({ "ok": false, "error": error }));
+            1
+        }
+        (Err(error), false) => {
+            eprintln!("{}", error);
+            1
+        }
+    }
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(text: &str, capture: bool) -> QueryArgs {
+        QueryArgs {
+            text: text.to_string(),
+            capture,
+            ..QueryArgs::default()
+        }
+    }
+
+    #[test]
+    fn test_control_call() {
+        let (path, body) = control_call(&args(" groceries ", false)).unwrap();
+        assert_eq!(path, "/v1/notes/search");
+        assert_eq!(body["query"], "groceries");
+
+        let (path, body) = control_call(&args("Call the dentist", true)).unwrap();
+        assert_eq!(path, "/v1/capture");
+        assert_eq!(body["content"], "Call the dentist");
+
+        assert!(matches!(
+            control_call(&args("  ", false)),
+            Err(AppError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_plain_output() {
+        let page = serde_json::json!({
+            "items": [
+                { "id": "n1", "title": "Groceries" },
+                { "id": 2, "title": "" },
+            ],
+            "totalCount": 2,
+        });
+        assert_eq!(
+            plain_output(&args("g", false), &page),
+            "Groceries\tsecondbrain://note/n1\nUntitled\tsecondbrain://note/2"
+        );
+        assert_eq!(
+            plain_output(&args("g", false), &serde_json::json!({ "items": [] })),
+            ""
+        );
+
+        let saved = serde_json::json!({ "id": "n3", "title": "Call the dentist" });
+        assert_eq!(
+            plain_output(&args("Call the dentist", true), &saved),
+            "Call the dentist\tsecondbrain://note/n3"
+        );
+        let queued = serde_json::json!({ "queued": true, "id": "q1" });
+        assert!(plain_output(&args("x", true), &queued).starts_with("Queued"));
+    }
+}
@@ -0,0 +1,336 @@
+//! Quick Look previews for exported notes and attachments.
+//!
+//! This module provides:
+//! - `generate_export_previews`, which gives each exported note or attachment
+//!   under a file or folder a thumbnail and a readable text preview
+//! - Thumbnails rendered with `qlmanage` and set as the file's Finder icon, so
+//!   exports show their content instead of a generic document icon
+//! - The UTF-8 text encoding attribute on Markdown and JSON, so Quick Look's
+//!   text preview doesn't misread non-ASCII characters
+//!
+//! Markdown is thumbnailed from a PDF rendered by `pdf` with its front matter
+//! left out, so the thumbnail shows the note rather than raw YAML. Previews
+//! are macOS-only; elsewhere the command reports that it isn't supported.
+
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+use crate::error::AppError;
+
+/// Thumbnail size in pixels, large enough for Finder's gallery view
+#[cfg(target_os = "macos")]
+const THUMBNAIL_SIZE: u32 = 512;
+
+/// Most files previewed in one run
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+const MAX_FILES: usize = 5000;
+
+/// How a file is previewed
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PreviewKind {
+    /// Markdown note: rendered thumbnail and UTF-8 text
+    Markdown,
+    /// JSON export: UTF-8 text and Quick Look's own thumbnail
+    Text,
+    /// PDF, image or other document Quick Look can render itself
+    Document,
+}
+
+/// Result of generating previews
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct PreviewSummary {
+    pub previewed: usize,
+    /// Files of a type that isn't previewed
+    pub skipped: usize,
+    /// Files that couldn't be previewed, with the reason
+    pub failed: Vec<String>,
+}
+
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn preview_kind(path: &Path) -> Option<PreviewKind> {
+    let extension = path.extension()?.to_string_lossy().to_lowercase();
+    match extension.as_str() {
+        "md" | "markdown" => Some(PreviewKind::Markdown),
+        "json" | "txt" => Some(PreviewKind::Text),
+        "pdf" | "png" | "jpg" | "jpeg" | "gif" | "heic" | "webp" | "tiff" | "svg" | "mp3"
+        | "m4a" | "wav" | "mp4" | "mov" | "docx" | "xlsx" | "pptx" | "csv" => {
+            Some(PreviewKind::Document)
+        }
+        _ => None,
+    }
+}
+
+/// Markdown without a leading `---` front matter block
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn strip_front_matter(markdown: &str) -> &str {
+    let Some(rest) = markdown.strip_prefix("---\n") else {
+        return markdown;
+    };
+    match rest.find("\n---\n") {
+        Some(end) => rest[end + 5..].trim_start_matches('\n'),
+        None => markdown,
+    }
+}
+
+/// Files at or below `root`, skipping hidden files and folders
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn collect_files(root: &Path) -> Vec<PathBuf> {
+    if root.is_file() {
+        return vec![root.to_path_buf()];
+    }
+    let mut files = Vec::new();
+    let mut pending = vec![root.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            if entry.file_name().to_string_lossy().starts_with('.') {
+                continue;
+            }
+            match entry.file_type() {
+                Ok(t) if t.is_dir() => pending.push(entry.path()),
+                Ok(t) if t.is_file() => files.push(entry.path()),
+                _ => {}
+            }
+        }
+    }
+    files.sort();
+    files.truncate(MAX_FILES);
+    files
+}
+
+/// Render a thumbnail PNG into `out_dir` with Quick Look's generators
+#[cfg(target_os = "macos")]
+fn render_thumbnail(source: &Path, out_dir: &Path) -> Result<PathBuf, String> {
+    let output = std::process::Command::new("qlmanage")
+        .arg("-t")
+        .args(["-s", &THUMBNAIL_SIZE.to_string()])
+        .arg("-o")
+        .arg(out_dir)
+        .arg(source)
+        .output()
+        .map_err(|e| format!("Failed to run qlmanage: {}", e))?;
+    let name = source
+        .file_name()
+        .ok_or_else(|| format!("Invalid path {:?}", source))?;
+    let thumbnail = out_dir.join(format!("{}.png", name.to_string_lossy()));
+    if output.status.success() && thumbnail.exists() {
+        Ok(thumbnail)
+    } else {
+        Err("Quick Look couldn't render a thumbnail".to_string())
+    }
+}
+
+/// Show `image` as the file's icon in Finder
+#[cfg(target_os = "macos")]
+fn set_finder_icon(path: &Path, image: &Path) -> Result<(), String> {
+    use objc2_app_kit::{NSImage, NSWorkspace, NSWorkspaceIconCreationOptions};
+    use objc2_foundation::NSString;
+
+    let set = unsafe {
+        let Some(icon) = NSImage::initWithContentsOfFile(
+            NSImage::alloc(),
+            &NSString::from_str(&image.to_string_lossy()),
+        ) else {
+            return Err("Couldn't load the thumbnail".to_string());
+        };
+        NSWorkspace::sharedWorkspace().setIcon_forFile_options(
+            Some(&icon),
+            &NSString::from_str(&path.to_string_lossy()),
+            NSWorkspaceIconCreationOptions::empty(),
+        )
+    };
+    if set {
+        Ok(())
+    } else {
+        Err("Finder didn't accept the icon".to_string())
+    }
+}
+
+/// Mark a text file as UTF-8 for Quick Look and TextEdit
+#[cfg(target_os = "macos")]
+fn set_utf8_encoding(path: &Path) -> Result<(), String> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    // "UTF-8;" followed by the CFStringEncoding for UTF-8
+    const VALUE: &[u8] = b"UTF-8;134217984";
+    let path_c = CString::new(path.as_os_str().as_bytes()).map_err(|e| e.to_string())?;
+    let name = CString::new("com.apple.TextEncoding").expect("no NUL in name");
+    // SAFETY: both strings are NUL-terminated and VALUE outlives the call
+    let result = unsafe {
+        libc::setxattr(
+            path_c.as_ptr(),
+            name.as_ptr(),
+            VALUE.as_ptr().cast(),
+            VALUE.len(),
+            0,
+            0,
+        )
+    };
+    if result == 0 {
+        Ok(())
+    } else {
+        Err(std::io::Error::last_os_error().to_string())
+    }
+}
+
+/// Give one file its previews, using `work_dir` for intermediate files
+#[cfg(target_os = "macos")]
+fn preview_file(path: &Path, kind: PreviewKind, work_dir: &Path) -> Result<(), String> {
+    let thumbnail_source = match kind {
+        PreviewKind::Markdown => {
+            set_utf8_encoding(path)?;
+            let markdown = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+            let title = path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let pdf = work_dir.join(format!(
+                "{}.pdf",
+                path.file_name().unwrap_or_default().to_string_lossy()
+            ));
+            std::fs::write(
+                &pdf,
+                crate::pdf::render_markdown(&title, strip_front_matter(&markdown)),
+            )
+            .map_err(|e| e.to_string())?;
+            pdf
+        }
+        PreviewKind::Text => {
+            set_utf8_encoding(path)?;
+            path.to_path_buf()
+        }
+        PreviewKind::Document => path.to_path_buf(),
+    };
+    let thumbnail = render_thumbnail(&thumbnail_source, work_dir)?;
+    let result = set_finder_icon(path, &thumbnail);
+    let _ = std::fs::remove_file(&thumbnail);
+    result
+}
+
+#[cfg(target_os = "macos")]
+fn generate_previews(root: &Path) -> Result<PreviewSummary, AppError> {
+    let work_dir = std::env::temp_dir().join(format!(
+        "second-brain-previews-{}-{}",
+        std::process::id(),
+        chrono::Utc::now().timestamp_millis()
+    ));
+    std::fs::create_dir_all(&work_dir)?;
+
+    let mut summary = PreviewSummary::default();
+    for path in collect_files(root) {
+        let Some(kind) = preview_kind(&path) else {
+            summary.skipped += 1;
+            continue;
+        };
+        match preview_file(&path, kind, &work_dir) {
+            Ok(()) => summary.previewed += 1,
+            Err(e) => {
+                tracing::debug!("No preview for {:?}: {}", path, e);
+                summary.failed.push(format!("{}: {}", path.display(), e));
+            }
+        }
+    }
+    let _ = std::fs::remove_dir_all(&work_dir);
+    Ok(summary)
+}
+
+// ============================================================
+// Commands
+// ============================================================
+
+/// Generate Quick Look previews for an exported file, or every file in an
+/// exported folder
+#[tauri::command]
+pub async fn generate_export_previews(path: String) -> Result<PreviewSummary, AppError> {
+    let root = PathBuf::from(path);
+    if !root.is_absolute() {
+        return Err(AppError::InvalidInput(
+            "Export path must be absolute".to_string(),
+        ));
+    }
+    if !root.exists() {
+        return Err(AppError::NotFound(format!(
+            "{} doesn't exist",
+            root.display()
+        )));
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        let summary = tokio::task::spawn_blocking(move || generate_previews(&root)).await??;
+        tracing::info!(
+            "Generated {} previews ({} skipped, {} failed)",
+            summary.previewed,
+            summary.skipped,
+            summary.failed.len()
+        );
+        Ok(summary)
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        Err(AppError::Internal(
+            "Quick Look previews are only available on macOS".to_string(),
+        ))
+    }
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_preview_kind() {
+        assert_eq!(
+            preview_kind(Path::new("/exports/Note.MD")),
+            Some(PreviewKind::Markdown)
+        );
+        assert_eq!(
+            preview_kind(Path::new("/exports/note.json")),
+            Some(PreviewKind::Text)
+        );
+        assert_eq!(
+            preview_kind(Path::new("/exports/scan.pdf")),
+            Some(PreviewKind::Document)
+        );
+        assert_eq!(preview_kind(Path::new("/exports/archive.zip")), None);
+        assert_eq!(preview_kind(Path::new("/exports/README")), None);
+    }
+
+    #[test]
+    fn test_strip_front_matter() {
+        assert_eq!(
+            strip_front_matter("---\ntitle: \"Plan\"\ntags: []\n---\n\n# Plan\nBody\n"),
+            "# Plan\nBody\n"
+        );
+        assert_eq!(strip_front_matter("# No front matter"), "# No front matter");
+        // An unterminated block is left alone
+        assert_eq!(strip_front_matter("---\ntitle: x\n"), "---\ntitle: x\n");
+    }
+
+    #[test]
+    fn test_collect_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        for file in ["a.md", "work/b.pdf", ".hidden/c.md", "work/.DS_Store"] {
+            let path = root.join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "").unwrap();
+        }
+
+        assert_eq!(
+            collect_files(root),
+            vec![root.join("a.md"), root.join("work/b.pdf")]
+        );
+        assert_eq!(collect_files(&root.join("a.md")), vec![root.join("a.md")]);
+    }
+}
@@ -0,0 +1,268 @@
+//! Relaunching the app when its services can't recover.
+//!
+//! This module provides:
+//! - Detection of a wedged state: neither service running, at least one
+//!   failed, and nothing left retrying, since startup retries and crash
+//!   restarts only leave a service failed once they've run out
+//! - A `relaunch-offered` event and a notice offering a clean relaunch, or
+//!   with `auto_relaunch` in the service settings, a relaunch on its own after
+//!   `AUTO_RELAUNCH_DELAY`
+//! - A guard kept in relaunch.json allowing `MAX_RELAUNCHES` within
+//!   `RELAUNCH_WINDOW`, so a failure a relaunch can't fix doesn't loop
+//! - `relaunch_app` for the UI's relaunch button
+//!
+//! Relaunching goes through `AppHandle::request_restart`, as the process
+//! plugin's `relaunch` does, so the usual exit handling shuts services down
+//! first.
+
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::config::{self, load_json, save_json_atomic};
+use crate::error::AppError;
+use crate::notifications::{Notice, Severity};
+use crate::services::{ServiceManager, ServicePhase, ServiceState};
+use crate::AppState;
+
+/// Event emitted when a relaunch is offered
+pub const EVENT: &str = "relaunch-offered";
+
+/// Relaunch history in the app data directory
+const FILE: &str = "relaunch.json";
+
+/// Most relaunches allowed within `RELAUNCH_WINDOW`
+const MAX_RELAUNCHES: usize = 2;
+
+/// Window in which relaunches count against the guard
+const RELAUNCH_WINDOW: Duration = Duration::from_secs(30 * 60);
+
+/// Time the user has to see the notice before an automatic relaunch
+const AUTO_RELAUNCH_DELAY: Duration = Duration::from_secs(10);
+
+/// Payload of `relaunch-offered`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RelaunchOffer {
+    /// Error that left the services failed, if one was recorded
+    pub reason: Option<String>,
+    /// Relaunches the guard still allows; 0 means relaunching has already
+    /// been tried and didn't help
+    pub remaining: u32,
+    /// Set when the app relaunches on its own after this long
+    pub auto_relaunch_in_ms: Option<u64>,
+}
+
+/// Recent relaunches, kept across them in relaunch.json
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+struct RelaunchHistory {
+    relaunches: Vec<DateTime<Utc>>,
+}
+
+impl RelaunchHistory {
+    fn path(app_data_dir: &Path) -> PathBuf {
+        app_data_dir.join(FILE)
+    }
+
+    fn load(app_data_dir: &Path) -> Self {
+        load_json(&Self::path(app_data_dir)).unwrap_or_default()
+    }
+
+    fn save(&self, app_data_dir: &Path) -> Result<(), String> {
+        save_json_atomic(&Self::path(app_data_dir), self)
+    }
+
+    /// Drop relaunches older than the window
+    fn prune(&mut self, now: DateTime<Utc>) {
+        let window = ChronoDuration::from_std(RELAUNCH_WINDOW).unwrap_or_default();
+        self.relaunches.retain(|at| now - *at < window);
+    }
+
+    /// Relaunches the guard still allows
+    fn remaining(&self, now: DateTime<Utc>) -> u32 {
+        let mut recent = self.clone();
+        recent.prune(now);
+        MAX_RELAUNCHES.saturating_sub(recent.relaunches.len()) as u32
+    }
+
+    fn record(&mut self, now: DateTime<Utc>) {
+        self.prune(now);
+        self.relaunches.push(now);
+    }
+}
+
+/// Whether the services are down for good: neither is running or on its
+/// way, at least one failed, and no command is still being handled
+fn is_wedged(state: &ServiceState) -> bool {
+    let down = |phase: ServicePhase| matches!(phase, ServicePhase::Stopped | ServicePhase::Failed);
+    state.busy.is_none()
+        && down(state.postgres)
+        && down(state.backend)
+        && (state.postgres == ServicePhase::Failed || state.backend == ServicePhase::Failed)
+}
+
+/// Record the relaunch and restart the app through the normal exit path
+fn relaunch(app: &AppHandle) {
+    match app.path().app_data_dir() {
+        Ok(app_data_dir) => {
+            let mut history = RelaunchHistory::load(&app_data_dir);
+            history.record(Utc::now());
+            if let Err(e) = history.save(&app_data_dir) {
+                tracing::warn!("Failed to write {}: {}", FILE, e);
+            }
+        }
+        Err(e) => tracing::warn!("Relaunch won't be counted: {}", e),
+    }
+    tracing::warn!("Relaunching the app to recover its services");
+    app.request_restart();
+}
+
+/// Offer a relaunch, or schedule one when `auto_relaunch` is on and the
+/// guard allows it
+fn offer(app: &AppHandle, state: &ServiceState) {
+    let remaining = app
+        .path()
+        .app_data_dir()
+        .map(|dir| RelaunchHistory::load(&dir).remaining(Utc::now()))
+        .unwrap_or(0);
+    let auto = remaining > 0 && config::current_settings(&app.state::<AppState>()).auto_relaunch;
+    let offer = RelaunchOffer {
+        reason: state.last_error.as_ref().map(|e| e.to_string()),
+        remaining,
+        auto_relaunch_in_ms: auto.then(|| AUTO_RELAUNCH_DELAY.as_millis() as u64),
+    };
+    tracing::error!(
+        "Services failed and can't recover ({} relaunches left)",
+        remaining
+    );
+    if let Err(e) = app.emit(EVENT, &offer) {
+        tracing::warn!("Failed to emit {}: {}", EVENT, e);
+    }
+
+    let (title, body) = if remaining == 0 {
+        (
+            "Second Brain's services still can't start",
+            "Relaunching didn't help, so it won't be tried again for now. The log has \
+             details.",
+        )
+    } else if auto {
+        (
+            "Second Brain's services couldn't recover",
+            "The app will relaunch itself in a few seconds.",
+        )
+    } else {
+        (
+            "Second Brain's services couldn't recover",
+            "Relaunch the app to try again; the log has details.",
+        )
+    };
+    crate::notifications::notify(app, Notice::new("services", Severity::Error, title, body));
+
+    if !auto {
+        return;
+    }
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(AUTO_RELAUNCH_DELAY).await;
+        // The user may have restarted services in the meantime
+        if is_wedged(&app.state::<ServiceManager>().state()) {
+            relaunch(&app);
+        }
+    });
+}
+
+/// Watch the services and offer a relaunch each time they become wedged
+pub fn start(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut services = app.state::<ServiceManager>().subscribe();
+        let mut offered = false;
+        while services.changed().await.is_ok() {
+            let state = services.borrow_and_update().clone();
+            if !is_wedged(&state) {
+                offered = false;
+                continue;
+            }
+            if !offered {
+                offered = true;
+                offer(&app, &state);
+            }
+        }
+    });
+}
+
+// ============================================================
+// Commands
+// ============================================================
+
+/// Relaunch the app, shutting services down first
+#[tauri::command]
+pub async fn relaunch_app(app: AppHandle) -> Result<(), AppError> {
+    relaunch(&app);
+    Ok(())
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(postgres: ServicePhase, backend: ServicePhase) -> ServiceState {
+        ServiceState {
+            postgres,
+            backend,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_is_wedged() {
+        assert!(is_wedged(&state(
+            ServicePhase::Failed,
+            ServicePhase::Stopped
+        )));
+        assert!(is_wedged(&state(
+            ServicePhase::Stopped,
+            ServicePhase::Failed
+        )));
+        // Shut down on purpose
+        assert!(!is_wedged(&state(
+            ServicePhase::Stopped,
+            ServicePhase::Stopped
+        )));
+        // A crashed backend is restarted while the database runs
+        assert!(!is_wedged(&state(
+            ServicePhase::Running,
+            ServicePhase::Failed
+        )));
+
+        let mut restarting = state(ServicePhase::Failed, ServicePhase::Stopped);
+        restarting.busy = Some(crate::services::ServiceCommand::RestartDatabase);
+        assert!(!is_wedged(&restarting));
+    }
+
+    #[test]
+    fn test_history_guard() {
+        let dir = tempfile::tempdir().unwrap();
+        let now = Utc::now();
+        let mut history = RelaunchHistory::load(dir.path());
+        assert_eq!(history.remaining(now), MAX_RELAUNCHES as u32);
+
+        history.record(now - ChronoDuration::minutes(5));
+        history.record(now);
+        history.save(dir.path()).unwrap();
+        let history = RelaunchHistory::load(dir.path());
+        assert_eq!(history.remaining(now), 0);
+
+        // Relaunches age out of the window
+        assert_eq!(history.remaining(now + ChronoDuration::minutes(27)), 1);
+        assert_eq!(
+            history.remaining(now + ChronoDuration::hours(1)),
+            MAX_RELAUNCHES as u32
+        );
+    }
+}
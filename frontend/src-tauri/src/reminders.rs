@@ -0,0 +1,386 @@
+//! Local reminders on notes.
+//!
+//! This module provides:
+//! - `schedule_reminder`, for the frontend, and `POST /v1/reminders` on the
+//!   control API, for the backend, to register a reminder on a note
+//! - Persistence in reminders.json through the state journal, so reminders
+//!   survive restarts and ones missed while the app was closed fire on launch
+//! - A timer that fires due reminders as `reminders` notices and
+//!   `reminder-due` events
+//! - `snooze_reminder`/`complete_reminder`, which the webview calls from its
+//!   reminder toast and which are passed on to the backend
+//!
+//! Desktop notifications can't carry buttons, so snoozing and completing go
+//! through the webview rather than the notification itself.
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::Notify;
+
+use crate::config::load_json;
+use crate::error::AppError;
+use crate::journal;
+use crate::notifications::{Notice, Severity};
+
+/// File the reminders are kept in
+const REMINDERS_FILE: &str = "reminders.json";
+
+/// Longest the timer sleeps, so a wake from sleep is noticed promptly
+const MAX_SLEEP: Duration = Duration::from_secs(30);
+
+/// Most reminders kept
+const MAX_REMINDERS: usize = 1000;
+
+/// Longest snooze (minutes)
+const MAX_SNOOZE_MINUTES: u32 = 7 * 24 * 60;
+
+/// A reminder on a note
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Reminder {
+    pub id: String,
+    pub note_id: String,
+    /// When it's due (Unix epoch seconds)
+    pub at: i64,
+    pub message: String,
+    pub created_at: i64,
+    /// Whether it has fired and is waiting to be snoozed or completed
+    #[serde(default)]
+    pub fired: bool,
+    #[serde(default)]
+    pub snooze_count: u32,
+}
+
+/// Reminders kept in Tauri state
+#[derive(Default)]
+pub struct Reminders {
+    reminders: Mutex<Vec<Reminder>>,
+    app_data_dir: Mutex<Option<PathBuf>>,
+    /// Wakes the timer when the next due time may have changed
+    changed: Notify,
+}
+
+impl Reminders {
+    fn load(app_data_dir: &Path) -> Self {
+        let reminders: Vec<Reminder> =
+            load_json(&app_data_dir.join(REMINDERS_FILE)).unwrap_or_default();
+        Self {
+            reminders: Mutex::new(reminders),
+            app_data_dir: Mutex::new(Some(app_data_dir.to_path_buf())),
+            changed: Notify::new(),
+        }
+    }
+
+    fn save(&self, reminders: &[Reminder]) {
+        if let Some(ref app_data_dir) = *self.app_data_dir.lock() {
+            if let Err(e) = journal::write(app_data_dir, REMINDERS_FILE, &reminders) {
+                tracing::warn!("Failed to save reminders: {}", e);
+            }
+        }
+    }
+
+    /// Apply a change to the reminders, saving and waking the timer after
+    fn update<T>(&self, change: impl FnOnce(&mut Vec<Reminder>) -> T) -> T {
+        let mut reminders = self.reminders.lock();
+        let result = change(&mut reminders);
+        self.save(&reminders);
+        self.changed.notify_one();
+        result
+    }
+
+    fn list(&self) -> Vec<Reminder> {
+        let mut reminders = self.reminders.lock().clone();
+        reminders.sort_by_key(|r| r.at);
+        reminders
+    }
+}
+
+fn new_id() -> String {
+    let mut random = [0u8; 8];
+    let _ = getrandom::fill(&mut random);
+    random.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Parse an RFC 3339 due time into epoch seconds
+fn parse_due(at: &str) -> Result<i64, String> {
+    chrono::DateTime::parse_from_rfc3339(at.trim())
+        .map(|time| time.timestamp())
+        .map_err(|_| format!("'{}' isn't a date and time like 2026-05-01T09:00:00Z", at))
+}
+
+/// Mark reminders due at `now` as fired, returning them
+fn take_due(reminders: &mut [Reminder], now: i64) -> Vec<Reminder> {
+    reminders
+        .iter_mut()
+        .filter(|r| !r.fired && r.at <= now)
+        .map(|r| {
+            r.fired = true;
+            r.clone()
+        })
+        .collect()
+}
+
+/// When the next unfired reminder is due
+fn next_due(reminders: &[Reminder]) -> Option<i64> {
+    reminders.iter().filter(|r| !r.fired).map(|r| r.at).min()
+}
+
+/// How long the timer sleeps before checking again
+fn sleep_for(next: Option<i64>, now: i64) -> Duration {
+    match next {
+        Some(at) => Duration::from_secs(at.saturating_sub(now).max(0) as u64).min(MAX_SLEEP),
+        None => MAX_SLEEP,
+    }
+}
+
+fn fire(app: &AppHandle, reminder: &Reminder) {
+    let body = if reminder.message.trim().is_empty() {
+        "A note is due for another look".to_string()
+    } else {
+        reminder.message.clone()
+    };
+    crate::notifications::notify(
+        app,
+        Notice::new("reminders", Severity::Info, "Reminder", body),
+    );
+    let _ = app.emit("reminder-due", reminder);
+}
+
+/// Tell the backend what happened to a reminder; the local state is the
+/// source of truth, so a failure is only logged
+async fn report(app: &AppHandle, reminder: &Reminder, action: &str, body: serde_json::Value) {
+    let path = format!(
+        "/notes/{}/reminders/{}/{}",
+        reminder.note_id, reminder.id, action
+    );
+    if let Err(e) = crate::proxy::send_backend_request(app, "POST", &path, Some(&body), None).await
+    {
+        tracing::debug!("Backend didn't record reminder {}: {}", action, e);
+    }
+}
+
+/// Register a reminder, checked as for `schedule_reminder`
+pub(crate) fn schedule(
+    app: &AppHandle,
+    note_id: &str,
+    at: &str,
+    message: &str,
+) -> Result<Reminder, AppError> {
+    crate::note_history::validate_note_id(note_id).map_err(AppError::InvalidInput)?;
+    let reminder = Reminder {
+        id: new_id(),
+        note_id: note_id.to_string(),
+        at: parse_due(at).map_err(AppError::InvalidInput)?,
+        message: message.trim().to_string(),
+        created_at: chrono::Utc::now().timestamp(),
+        fired: false,
+        snooze_count: 0,
+    };
+    app.state::<Reminders>().update(|reminders| {
+        if reminders.len() >= MAX_REMINDERS {
+            return Err(AppError::Conflict(format!(
+                "{} reminders are already scheduled",
+                MAX_REMINDERS
+            )));
+        }
+        reminders.push(reminder.clone());
+        Ok(())
+    })?;
+    tracing::info!("Scheduled reminder {} on note {}", reminder.id, note_id);
+    Ok(reminder)
+}
+
+/// Take a reminder out of the list
+fn remove(app: &AppHandle, id: &str) -> Result<Reminder, AppError> {
+    app.state::<Reminders>().update(|reminders| {
+        let index = reminders
+            .iter()
+            .position(|r| r.id == id)
+            .ok_or_else(|| AppError::NotFound(format!("No reminder {}", id)))?;
+        Ok(reminders.remove(index))
+    })
+}
+
+/// Load saved reminders and start the timer
+pub fn start(app: &AppHandle) {
+    let reminders = match app.path().app_data_dir() {
+        Ok(app_data_dir) => Reminders::load(&app_data_dir),
+        Err(e) => {
+            tracing::warn!("Reminders won't persist: {}", e);
+            Reminders::default()
+        }
+    };
+    app.manage(reminders);
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let reminders = app.state::<Reminders>();
+        loop {
+            let now = chrono::Utc::now().timestamp();
+            let due = {
+                let mut list = reminders.reminders.lock();
+                let due = take_due(&mut list, now);
+                if !due.is_empty() {
+                    reminders.save(&list);
+                }
+                due
+            };
+            for reminder in &due {
+                fire(&app, reminder);
+            }
+
+            let next = next_due(&reminders.reminders.lock());
+            tokio::select! {
+                _ = tokio::time::sleep(sleep_for(next, now)) => {}
+                _ = reminders.changed.notified() => {}
+            }
+        }
+    });
+}
+
+// ============================================================
+// Commands
+// ============================================================
+
+/// Schedule a reminder on a note at an RFC 3339 date and time
+#[tauri::command]
+pub async fn schedule_reminder(
+    app: AppHandle,
+    note_id: String,
+    at: String,
+    message: String,
+) -> Result<Reminder, AppError> {
+    schedule(&app, &note_id, &at, &message)
+}
+
+/// List reminders, soonest first, including fired ones not yet completed
+#[tauri::command]
+pub async fn list_reminders(app: AppHandle) -> Result<Vec<Reminder>, AppError> {
+    Ok(app.state::<Reminders>().list())
+}
+
+/// Snooze a reminder for `minutes` from now
+#[tauri::command]
+pub async fn snooze_reminder(
+    app: AppHandle,
+    id: String,
+    minutes: u32,
+) -> Result<Reminder, AppError> {
+    if !(1..=MAX_SNOOZE_MINUTES).contains(&minutes) {
+        return Err(AppError::InvalidInput(format!(
+            "A snooze lasts 1 to {} minutes",
+            MAX_SNOOZE_MINUTES
+        )));
+    }
+    let until = chrono::Utc::now().timestamp() + i64::from(minutes) * 60;
+    let reminder = app.state::<Reminders>().update(|reminders| {
+        let reminder = reminders
+            .iter_mut()
+            .find(|r| r.id == id)
+            .ok_or_else(|| AppError::NotFound(format!("No reminder {}", id)))?;
+        reminder.at = until;
+        reminder.fired = false;
+        reminder.snooze_count += 1;
+        Ok::<_, AppError>(reminder.clone())
+    })?;
+    report(
+        &app,
+        &reminder,
+        "snooze",
+        serde_json::json!({ "until": until }),
+    )
+    .await;
+    Ok(reminder)
+}
+
+/// Mark a reminder done, removing it
+#[tauri::command]
+pub async fn complete_reminder(app: AppHandle, id: String) -> Result<(), AppError> {
+    let reminder = remove(&app, &id)?;
+    report(
+        &app,
+        &reminder,
+        "complete",
+        serde_json::json!({ "completedAt": chrono::Utc::now().timestamp() }),
+    )
+    .await;
+    Ok(())
+}
+
+/// Delete a reminder without completing it
+#[tauri::command]
+pub async fn cancel_reminder(app: AppHandle, id: String) -> Result<(), AppError> {
+    remove(&app, &id)?;
+    Ok(())
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn reminder(id: &str, at: i64, fired: bool) -> Reminder {
+        Reminder {
+            id: id.to_string(),
+            note_id: "note-1".to_string(),
+            at,
+            message: String::new(),
+            created_at: 0,
+            fired,
+            snooze_count: 0,
+        }
+    }
+
+    #[test]
+    fn test_parse_due() {
+        assert_eq!(parse_due("2026-05-01T09:00:00Z"), Ok(1_777_626_000));
+        assert_eq!(parse_due(" 2026-05-01T11:00:00+02:00 "), Ok(1_777_626_000));
+        assert!(parse_due("tomorrow").is_err());
+    }
+
+    #[test]
+    fn test_take_due_fires_each_reminder_once() {
+        let mut reminders = vec![
+            reminder("past", 100, false),
+            reminder("now", 200, false),
+            reminder("later", 300, false),
+            reminder("already", 50, true),
+        ];
+        let due: Vec<String> = take_due(&mut reminders, 200)
+            .into_iter()
+            .map(|r| r.id)
+            .collect();
+        assert_eq!(due, vec!["past", "now"]);
+        assert!(take_due(&mut reminders, 200).is_empty());
+        assert_eq!(next_due(&reminders), Some(300));
+    }
+
+    #[test]
+    fn test_sleep_for() {
+        assert_eq!(sleep_for(Some(105), 100), Duration::from_secs(5));
+        assert_eq!(sleep_for(Some(90), 100), Duration::ZERO);
+        assert_eq!(sleep_for(Some(10_000), 100), MAX_SLEEP);
+        assert_eq!(sleep_for(None, 100), MAX_SLEEP);
+    }
+
+    #[test]
+    fn test_reminders_persist() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = Reminders::load(temp_dir.path());
+        store.update(|reminders| {
+            reminders.push(reminder("b", 200, false));
+            reminders.push(reminder("a", 100, true));
+        });
+
+        let loaded = Reminders::load(temp_dir.path());
+        let ids: Vec<String> = loaded.list().into_iter().map(|r| r.id).collect();
+        assert_eq!(ids, vec!["a", "b"]);
+        assert!(loaded.list()[0].fired);
+    }
+}
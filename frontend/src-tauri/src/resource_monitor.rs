@@ -0,0 +1,478 @@
+//! Resource usage self-monitoring.
+//!
+//! This module provides:
+//! - Periodic samples of the shell's own resident memory, open file
+//!   descriptors, threads and service output readers
+//! - Growth trends over the retained samples, logged periodically
+//! - `resource-warning` events when a metric crosses its limit
+//!
+//! Every service start spawns new output readers, so a restart path that
+//! fails to release them shows up here as steady growth long before the
+//! process runs out of descriptors.
+
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+use crate::error::AppError;
+
+/// Time between samples
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Samples kept for trends (two hours)
+const MAX_SAMPLES: usize = 120;
+
+/// Samples between trend log lines
+const TREND_LOG_EVERY: usize = 15;
+
+/// Resident memory above which a warning is raised
+const RSS_LIMIT_BYTES: u64 = 1536 * 1024 * 1024;
+
+/// Fraction of the descriptor limit above which a warning is raised
+const FD_LIMIT_FRACTION: f64 = 0.8;
+
+/// Descriptor limit used when the process limit cannot be read
+const DEFAULT_FD_LIMIT: u64 = 1024;
+
+/// Thread count above which a warning is raised
+const THREAD_LIMIT: u64 = 512;
+
+/// Output readers above which a warning is raised; a healthy session has
+/// one for PostgreSQL and two for the backend
+const READER_LIMIT: u64 = 12;
+
+/// A warning re-arms once the metric drops below this fraction of its limit
+const REARM_FRACTION: f64 = 0.9;
+
+/// Service output readers currently running
+static OUTPUT_READERS: AtomicU64 = AtomicU64::new(0);
+
+/// Counts a running output reader until dropped
+pub struct ReaderGuard(());
+
+impl Drop for ReaderGuard {
+    fn drop(&mut self) {
+        OUTPUT_READERS.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Register a service output reader; hold the guard for its lifetime
+pub fn track_reader() -> ReaderGuard {
+    OUTPUT_READERS.fetch_add(1, Ordering::Relaxed);
+    ReaderGuard(())
+}
+
+/// One measurement of the shell process
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ResourceSample {
+    /// Unix epoch seconds
+    pub at: i64,
+    pub rss_bytes: Option<u64>,
+    pub open_fds: Option<u64>,
+    pub threads: Option<u64>,
+    pub output_readers: u64,
+}
+
+/// A monitored metric
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Metric {
+    RssBytes,
+    OpenFds,
+    Threads,
+    OutputReaders,
+}
+
+impl Metric {
+    const ALL: [Metric; 4] = [
+        Metric::RssBytes,
+        Metric::OpenFds,
+        Metric::Threads,
+        Metric::OutputReaders,
+    ];
+
+    fn value(self, sample: &ResourceSample) -> Option<u64> {
+        match self {
+            Metric::RssBytes => sample.rss_bytes,
+            Metric::OpenFds => sample.open_fds,
+            Metric::Threads => sample.threads,
+            Metric::OutputReaders => Some(sample.output_readers),
+        }
+    }
+}
+
+/// Payload of `resource-warning` events
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ResourceWarning {
+    pub metric: Metric,
+    pub value: u64,
+    pub limit: u64,
+    /// Growth over the retained samples, scaled to one hour
+    pub growth_per_hour: Option<f64>,
+}
+
+/// Limits for each metric
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ResourceLimits {
+    pub rss_bytes: u64,
+    pub open_fds: u64,
+    pub threads: u64,
+    pub output_readers: u64,
+}
+
+impl ResourceLimits {
+    fn current() -> Self {
+        Self {
+            rss_bytes: RSS_LIMIT_BYTES,
+            open_fds: (fd_soft_limit().unwrap_or(DEFAULT_FD_LIMIT) as f64 * FD_LIMIT_FRACTION)
+                as u64,
+            threads: THREAD_LIMIT,
+            output_readers: READER_LIMIT,
+        }
+    }
+
+    fn get(&self, metric: Metric) -> u64 {
+        match metric {
+            Metric::RssBytes => self.rss_bytes,
+            Metric::OpenFds => self.open_fds,
+            Metric::Threads => self.threads,
+            Metric::OutputReaders => self.output_readers,
+        }
+    }
+}
+
+/// Retained samples and which warnings have fired
+#[derive(Debug, Default)]
+struct History {
+    samples: VecDeque<ResourceSample>,
+    /// Metrics over their limit since the last re-arm
+    alarmed: Vec<Metric>,
+}
+
+impl History {
+    fn push(&mut self, sample: ResourceSample) {
+        if self.samples.len() == MAX_SAMPLES {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+
+    /// Change per hour between the oldest and newest sample with a value
+    fn growth_per_hour(&self, metric: Metric) -> Option<f64> {
+        let mut points = self
+            .samples
+            .iter()
+            .filter_map(|s| Some((s.at, metric.value(s)?)));
+        let (first_at, first) = points.next()?;
+        let (last_at, last) = points.next_back()?;
+        let elapsed = (last_at - first_at) as f64;
+        (elapsed > 0.0).then(|| (last as f64 - first as f64) * 3600.0 / elapsed)
+    }
+
+    /// Warnings for metrics that crossed their limit with the latest sample
+    fn check(&mut self, limits: &ResourceLimits) -> Vec<ResourceWarning> {
+        let Some(latest) = self.samples.back().cloned() else {
+            return Vec::new();
+        };
+        let mut warnings = Vec::new();
+        for metric in Metric::ALL {
+            let Some(value) = metric.value(&latest) else {
+                continue;
+            };
+            let limit = limits.get(metric);
+            let alarmed = self.alarmed.contains(&metric);
+            if value > limit && !alarmed {
+                self.alarmed.push(metric);
+                warnings.push(ResourceWarning {
+                    metric,
+                    value,
+                    limit,
+                    growth_per_hour: self.growth_per_hour(metric),
+                });
+            } else if alarmed && (value as f64) < limit as f64 * REARM_FRACTION {
+                self.alarmed.retain(|m| *m != metric);
+            }
+        }
+        warnings
+    }
+}
+
+/// Resource usage kept in Tauri state
+#[derive(Default)]
+pub struct ResourceMonitor {
+    history: Mutex<History>,
+}
+
+/// Current usage, limits and trends
+#[derive(Debug, Clone, Serialize)]
+pub struct ResourceReport {
+    pub current: ResourceSample,
+    pub limits: ResourceLimits,
+    pub rss_growth_per_hour: Option<f64>,
+    pub fd_growth_per_hour: Option<f64>,
+    pub thread_growth_per_hour: Option<f64>,
+    pub samples: Vec<ResourceSample>,
+}
+
+/// Parse `VmRSS` (in bytes) and `Threads` from /proc/self/status
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_proc_status(status: &str) -> (Option<u64>, Option<u64>) {
+    let field = |name: &str| {
+        status
+            .lines()
+            .find_map(|line| line.strip_prefix(name))
+            .and_then(|rest| rest.split_whitespace().next())
+            .and_then(|value| value.parse::<u64>().ok())
+    };
+    (field("VmRSS:").map(|kb| kb * 1024), field("Threads:"))
+}
+
+/// Count entries in a descriptor directory, excluding the one used to read it
+#[cfg(unix)]
+fn count_fds(dir: &str) -> Option<u64> {
+    let count = std::fs::read_dir(dir).ok()?.count() as u64;
+    Some(count.saturating_sub(1))
+}
+
+#[cfg(unix)]
+fn fd_soft_limit() -> Option<u64> {
+    let mut limit = libc::rlimit {
+        rlim_cur: 0,
+        rlim_max: 0,
+    };
+    // SAFETY: `limit` is a valid, writable rlimit
+    let ok = unsafe { libc::getrlimit(libc::RLIMIT_NOFILE, &mut limit) == 0 };
+    (ok && limit.rlim_cur != libc::RLIM_INFINITY).then_some(limit.rlim_cur)
+}
+
+#[cfg(not(unix))]
+fn fd_soft_limit() -> Option<u64> {
+    None
+}
+
+/// Measure the shell process now
+pub fn sample() -> ResourceSample {
+    let mut sample = ResourceSample {
+        at: chrono::Utc::now().timestamp(),
+        output_readers: OUTPUT_READERS.load(Ordering::Relaxed),
+        ..Default::default()
+    };
+
+    #[cfg(target_os = "linux")]
+    {
+        if let Ok(status) = std::fs::read_to_string("/proc/self/status") {
+            (sample.rss_bytes, sample.threads) = parse_proc_status(&status);
+        }
+        sample.open_fds = count_fds("/proc/self/fd");
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        let mut info: libc::proc_taskinfo = unsafe { std::mem::zeroed() };
+        let size = std::mem::size_of::<libc::proc_taskinfo>() as libc::c_int;
+        // SAFETY: `info` is a writable buffer of `size` bytes
+        let written = unsafe {
+            libc::proc_pidinfo(
+                std::process::id() as libc::c_int,
+                libc::PROC_PIDTASKINFO,
+                0,
+                &mut info as *mut _ as *mut libc::c_void,
+                size,
+            )
+        };
+        if written == size {
+            sample.rss_bytes = Some(info.pti_resident_size);
+            sample.threads = Some(info.pti_threadnum.max(0) as u64);
+        }
+        sample.open_fds = count_fds("/dev/fd");
+    }
+
+    sample
+}
+
+fn log_trend(history: &History) {
+    let Some(latest) = history.samples.back() else {
+        return;
+    };
+    let growth = |metric| {
+        history
+            .growth_per_hour(metric)
+            .map_or_else(|| "n/a".to_string(), |g| format!("{:+.1}/h", g))
+    };
+    tracing::info!(
+        rss_mb = latest.rss_bytes.map(|b| b / (1024 * 1024)),
+        fds = latest.open_fds,
+        threads = latest.threads,
+        readers = latest.output_readers,
+        "Resource usage (rss {}, fds {}, threads {})",
+        history.growth_per_hour(Metric::RssBytes).map_or_else(
+            || "n/a".to_string(),
+            |g| format!("{:+.1} MB/h", g / 1048576.0)
+        ),
+        growth(Metric::OpenFds),
+        growth(Metric::Threads),
+    );
+}
+
+/// Start sampling in the background
+pub fn start(app: &AppHandle) {
+    app.manage(ResourceMonitor::default());
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let limits = ResourceLimits::current();
+        let mut taken = 0usize;
+        loop {
+            let current = tauri::async_runtime::spawn_blocking(sample)
+                .await
+                .unwrap_or_default();
+            taken += 1;
+            let warnings = {
+                let monitor = app.state::<ResourceMonitor>();
+                let mut history = monitor.history.lock();
+                history.push(current);
+                if taken % TREND_LOG_EVERY == 0 {
+                    log_trend(&history);
+                }
+                history.check(&limits)
+            };
+            for warning in warnings {
+                tracing::warn!(
+                    metric = ?warning.metric,
+                    value = warning.value,
+                    limit = warning.limit,
+                    growth_per_hour = warning.growth_per_hour,
+                    "Resource usage over limit"
+                );
+                crate::events::emit_critical(&app, "resource-warning", &warning);
+            }
+            tokio::time::sleep(SAMPLE_INTERVAL).await;
+        }
+    });
+}
+
+/// Get the shell's current resource usage and its recent trend
+#[tauri::command]
+pub async fn get_resource_usage(app: AppHandle) -> Result<ResourceReport, AppError> {
+    let current = tauri::async_runtime::spawn_blocking(sample).await?;
+    let (samples, rss, fds, threads) = match app.try_state::<ResourceMonitor>() {
+        Some(monitor) => {
+            let history = monitor.history.lock();
+            (
+                history.samples.iter().cloned().collect(),
+                history.growth_per_hour(Metric::RssBytes),
+                history.growth_per_hour(Metric::OpenFds),
+                history.growth_per_hour(Metric::Threads),
+            )
+        }
+        None => (Vec::new(), None, None, None),
+    };
+    Ok(ResourceReport {
+        current,
+        limits: ResourceLimits::current(),
+        rss_growth_per_hour: rss,
+        fd_growth_per_hour: fds,
+        thread_growth_per_hour: threads,
+        samples,
+    })
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(at: i64, fds: u64) -> ResourceSample {
+        ResourceSample {
+            at,
+            open_fds: Some(fds),
+            ..Default::default()
+        }
+    }
+
+    fn limits() -> ResourceLimits {
+        ResourceLimits {
+            rss_bytes: u64::MAX,
+            open_fds: 100,
+            threads: u64::MAX,
+            output_readers: u64::MAX,
+        }
+    }
+
+    #[test]
+    fn test_parse_proc_status() {
+        let status = "Name:\tapp\nVmRSS:\t  20480 kB\nThreads:\t17\n";
+        assert_eq!(parse_proc_status(status), (Some(20480 * 1024), Some(17)));
+        assert_eq!(parse_proc_status("Name:\tapp\n"), (None, None));
+    }
+
+    #[test]
+    fn test_growth_per_hour() {
+        let mut history = History::default();
+        history.push(at(0, 10));
+        assert_eq!(history.growth_per_hour(Metric::OpenFds), None);
+        history.push(at(1800, 15));
+        history.push(at(3600, 30));
+        assert_eq!(history.growth_per_hour(Metric::OpenFds), Some(20.0));
+        assert_eq!(history.growth_per_hour(Metric::RssBytes), None);
+    }
+
+    #[test]
+    fn test_history_is_bounded() {
+        let mut history = History::default();
+        for i in 0..(MAX_SAMPLES as i64 + 5) {
+            history.push(at(i, 1));
+        }
+        assert_eq!(history.samples.len(), MAX_SAMPLES);
+        assert_eq!(history.samples.front().unwrap().at, 5);
+    }
+
+    #[test]
+    fn test_warning_fires_once_until_rearmed() {
+        let mut history = History::default();
+        history.push(at(0, 50));
+        assert!(history.check(&limits()).is_empty());
+
+        history.push(at(60, 120));
+        let warnings = history.check(&limits());
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].metric, Metric::OpenFds);
+        assert_eq!(warnings[0].value, 120);
+        assert_eq!(warnings[0].growth_per_hour, Some(4200.0));
+
+        // Still over, or just under the limit: no repeat
+        history.push(at(120, 130));
+        assert!(history.check(&limits()).is_empty());
+        history.push(at(180, 95));
+        assert!(history.check(&limits()).is_empty());
+
+        // Well under re-arms, so the next crossing warns again
+        history.push(at(240, 80));
+        assert!(history.check(&limits()).is_empty());
+        history.push(at(300, 101));
+        assert_eq!(history.check(&limits()).len(), 1);
+    }
+
+    #[test]
+    fn test_reader_guard_counts() {
+        let before = OUTPUT_READERS.load(Ordering::Relaxed);
+        let guard = track_reader();
+        assert_eq!(OUTPUT_READERS.load(Ordering::Relaxed), before + 1);
+        drop(guard);
+        assert_eq!(OUTPUT_READERS.load(Ordering::Relaxed), before);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_sample_reads_own_process() {
+        let sample = sample();
+        assert!(sample.rss_bytes.unwrap() > 0);
+        assert!(sample.threads.unwrap() >= 1);
+        assert!(sample.open_fds.unwrap() >= 3);
+    }
+}
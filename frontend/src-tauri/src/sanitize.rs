@@ -0,0 +1,462 @@
+//! Sanitized database copies for bug reports.
+//!
+//! This module provides:
+//! - A dump with the live schema and row structure but no personal content
+//! - Short text replaced by truncated hashes salted per export, so equal
+//!   inputs stay equal within a dump but can't be looked up
+//! - Long text replaced by lorem text of the same length
+//! - JSON, binary, search vector and embedding columns cleared
+//!
+//! Columns are discovered from `information_schema`, so tables added by later
+//! migrations are covered without changes here. Keys, foreign keys,
+//! timestamps and enum-like columns are kept so the copy still reproduces
+//! backend behaviour. If any content column cannot be sanitized, or has a
+//! type not known to be safe, the export fails instead of writing a
+//! partially sanitized dump.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+
+use crate::backup::{pg_command, postgres_manager, query, run, ScratchDatabase, TempDump};
+use crate::database::PostgresManager;
+use crate::error::{database_not_running, AppError};
+use crate::streams::StreamRegistry;
+
+/// Scratch database the copy is sanitized in
+const SANITIZE_DATABASE: &str = "secondbrain_sanitize";
+
+/// Text columns that describe structure rather than content
+const STRUCTURAL_COLUMNS: &[&str] = &[
+    "id",
+    "status",
+    "role",
+    "provider",
+    "model",
+    "model_name",
+    "type",
+    "kind",
+    "source",
+    "language",
+    "format",
+    "mode",
+    "content_type",
+    "mime_type",
+    "finish_reason",
+    "stop_reason",
+    "version_source",
+];
+
+const LOREM: &str = "lorem ipsum dolor sit amet consectetur adipiscing elit ";
+
+/// Types that hold no free-form content and are kept as-is
+const KEPT_TYPES: &[&str] = &[
+    "smallint",
+    "integer",
+    "bigint",
+    "numeric",
+    "real",
+    "double precision",
+    "boolean",
+    "uuid",
+    "date",
+    "time without time zone",
+    "time with time zone",
+    "timestamp without time zone",
+    "timestamp with time zone",
+    "interval",
+];
+
+/// Public table columns in the scratch database
+const COLUMNS_SQL: &str = "SELECT c.table_name, c.column_name, c.data_type, c.udt_name, \
+     c.is_nullable, c.is_generated \
+     FROM information_schema.columns c \
+     JOIN information_schema.tables t \
+       ON t.table_schema = c.table_schema AND t.table_name = c.table_name \
+     WHERE c.table_schema = 'public' AND t.table_type = 'BASE TABLE' \
+       AND c.table_name <> '__EFMigrationsHistory' \
+     ORDER BY c.table_name, c.ordinal_position";
+
+/// A table column as reported by `information_schema`
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Column {
+    table: String,
+    name: String,
+    data_type: String,
+    udt_name: String,
+    nullable: bool,
+    generated: bool,
+}
+
+/// Result of a sanitized export
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SanitizedExport {
+    pub path: String,
+    pub size_bytes: u64,
+    pub tables: usize,
+    pub columns_sanitized: usize,
+}
+
+fn parse_columns(output: &str) -> Vec<Column> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split('|').collect();
+            let [table, name, data_type, udt_name, nullable, generated] = fields[..] else {
+                return None;
+            };
+            Some(Column {
+                table: table.to_string(),
+                name: name.to_string(),
+                data_type: data_type.to_string(),
+                udt_name: udt_name.to_string(),
+                nullable: nullable == "YES",
+                generated: generated == "ALWAYS",
+            })
+        })
+        .collect()
+}
+
+fn quote_ident(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+fn is_structural(name: &str) -> bool {
+    STRUCTURAL_COLUMNS.contains(&name) || name.ends_with("_id") || name.ends_with("_at")
+}
+
+/// Random salt for one export's hashes, as hex
+fn new_salt() -> Result<String, String> {
+    let mut bytes = [0u8; 16];
+    getrandom::fill(&mut bytes).map_err(|e| format!("Failed to generate salt: {}", e))?;
+    Ok(bytes.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// Replacement expression for a column, None if it is kept as-is, or an
+/// error if its type isn't one that can be sanitized or safely kept
+///
+/// `salt` must be hex, since it is written into the expression.
+fn replacement(column: &Column, salt: &str) -> Result<Option<String>, String> {
+    if column.generated || is_structural(&column.name) {
+        return Ok(None);
+    }
+    let col = quote_ident(&column.name);
+    let empty_or_null = |empty: &str| {
+        if column.nullable {
+            "NULL".to_string()
+        } else {
+            empty.to_string()
+        }
+    };
+    let expression = match (column.data_type.as_str(), column.udt_name.as_str()) {
+        (data_type, _) if KEPT_TYPES.contains(&data_type) => None,
+        ("text" | "character varying" | "character", _) => Some(format!(
+            "CASE WHEN length({col}) <= 32 THEN left(md5('{salt}' || {col}), length({col})) \
+             ELSE left(repeat('{lorem}', length({col}) / {len} + 1), length({col})) END",
+            col = col,
+            salt = salt,
+            lorem = LOREM,
+            len = LOREM.len()
+        )),
+        ("ARRAY", "_text" | "_varchar") => Some(format!(
+            "ARRAY(SELECT left(md5('{}' || v), 8) FROM unnest({}) AS v)",
+            salt, col
+        )),
+        ("json", _) => Some(empty_or_null("'{}'::json")),
+        ("jsonb", _) => Some(empty_or_null("'{}'::jsonb")),
+        ("bytea", _) => Some(empty_or_null("''::bytea")),
+        ("tsvector", _) => Some(empty_or_null("''::tsvector")),
+        // Embeddings can be inverted to approximate the text they came from
+        ("USER-DEFINED", udt @ ("vector" | "halfvec")) => Some(if column.nullable {
+            "NULL".to_string()
+        } else {
+            format!(
+                "array_fill(0::real, ARRAY[vector_dims({col})])::{udt}",
+                col = col,
+                udt = udt
+            )
+        }),
+        (data_type, udt) => {
+            return Err(format!(
+                "{}.{} ({})",
+                column.table,
+                column.name,
+                if data_type == "USER-DEFINED" || data_type == "ARRAY" {
+                    udt
+                } else {
+                    data_type
+                }
+            ))
+        }
+    };
+    Ok(expression)
+}
+
+/// Script sanitizing every content column, printing the ones that failed
+///
+/// Each column is updated in its own block; if the replacement violates a
+/// constraint it falls back to NULL, and if that fails too it is reported.
+/// Fails without a script if any column has an unsupported type.
+fn sanitize_script(columns: &[Column], salt: &str) -> Result<(String, usize), String> {
+    let mut script = String::from(
+        "SET session_replication_role = replica;\n\
+         CREATE TEMP TABLE sanitize_failures (col text);\n",
+    );
+    let mut count = 0;
+    let mut unsupported = Vec::new();
+    for column in columns {
+        let expression = match replacement(column, salt) {
+            Ok(Some(expression)) => expression,
+            Ok(None) => continue,
+            Err(e) => {
+                unsupported.push(e);
+                continue;
+            }
+        };
+        count += 1;
+        let table = quote_ident(&column.table);
+        let col = quote_ident(&column.name);
+        let update = format!("UPDATE {} SET {} = {}", table, col, expression);
+        let fallback = format!("UPDATE {} SET {} = NULL", table, col);
+        script.push_str(&format!(
+            "DO $sanitize$ BEGIN\n  BEGIN EXECUTE '{}';\n  EXCEPTION WHEN others THEN\n    \
+             BEGIN EXECUTE '{}';\n    EXCEPTION WHEN others THEN \
+             INSERT INTO sanitize_failures VALUES ('{}.{}');\n    END;\n  END;\nEND $sanitize$;\n",
+            update.replace('\'', "''"),
+            fallback.replace('\'', "''"),
+            column.table.replace('\'', "''"),
+            column.name.replace('\'', "''"),
+        ));
+    }
+    if !unsupported.is_empty() {
+        return Err(format!(
+            "Can't sanitize column(s) of unsupported types: {}",
+            unsupported.join(", ")
+        ));
+    }
+    script.push_str("SELECT col FROM sanitize_failures;\n");
+    Ok((script, count))
+}
+
+fn export_sanitized_blocking(
+    manager: &PostgresManager,
+    app_data_dir: &Path,
+    path: &Path,
+) -> Result<SanitizedExport, String> {
+    let dump = TempDump::new(app_data_dir)?;
+    let mut pg_dump = pg_command(manager, "pg_dump")?;
+    pg_dump
+        .arg("-d")
+        .arg("secondbrain")
+        .arg("-Fc")
+        .arg("-f")
+        .arg(&dump.0);
+    run(pg_dump, "pg_dump")?;
+
+    let scratch = ScratchDatabase::create(manager, SANITIZE_DATABASE)?;
+    scratch.restore(&dump.0)?;
+    drop(dump);
+
+    let columns = parse_columns(&query(manager, scratch.name, COLUMNS_SQL)?);
+    let (script, columns_sanitized) = sanitize_script(&columns, &new_salt()?)?;
+    let failures: Vec<String> = query(manager, scratch.name, &script)?
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .map(str::to_string)
+        .collect();
+    if !failures.is_empty() {
+        return Err(format!(
+            "Could not sanitize column(s): {}",
+            failures.join(", ")
+        ));
+    }
+
+    // Write under a temporary name so a failed dump never looks complete
+    let partial = path.with_extension("partial");
+    let mut pg_dump = pg_command(manager, "pg_dump")?;
+    pg_dump
+        .arg("-d")
+        .arg(scratch.name)
+        .arg("-Fc")
+        .arg("--no-owner")
+        .arg("-f")
+        .arg(&partial);
+    if let Err(e) = run(pg_dump, "pg_dump") {
+        let _ = std::fs::remove_file(&partial);
+        return Err(e);
+    }
+    std::fs::rename(&partial, path).map_err(|e| format!("Failed to save export: {}", e))?;
+
+    let tables = columns
+        .iter()
+        .map(|c| c.table.as_str())
+        .collect::<std::collections::HashSet<_>>()
+        .len();
+    tracing::info!(
+        "Exported sanitized database to {:?} ({} tables, {} columns sanitized)",
+        path,
+        tables,
+        columns_sanitized
+    );
+    Ok(SanitizedExport {
+        path: path.to_string_lossy().to_string(),
+        size_bytes: std::fs::metadata(path).map(|m| m.len()).unwrap_or_default(),
+        tables,
+        columns_sanitized,
+    })
+}
+
+/// Write a sanitized custom-format dump of the database for bug reports
+///
+/// The dump can then be read back in chunks with `stream_file`.
+#[tauri::command]
+pub async fn export_sanitized_db(
+    app: AppHandle,
+    path: String,
+) -> Result<SanitizedExport, AppError> {
+    let path = PathBuf::from(path);
+    if !path.is_absolute() {
+        return Err(AppError::InvalidInput(
+            "Export path must be absolute".to_string(),
+        ));
+    }
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let manager = postgres_manager(&app).ok_or_else(database_not_running)?;
+
+    let export = tokio::task::spawn_blocking(move || {
+        export_sanitized_blocking(&manager, &app_data_dir, &path)
+    })
+    .await??;
+
+    app.state::<StreamRegistry>()
+        .allow_read(Path::new(&export.path));
+    Ok(export)
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SALT: &str = "0123abcd";
+
+    fn column(name: &str, data_type: &str, udt_name: &str, nullable: bool) -> Column {
+        Column {
+            table: "notes".to_string(),
+            name: name.to_string(),
+            data_type: data_type.to_string(),
+            udt_name: udt_name.to_string(),
+            nullable,
+            generated: false,
+        }
+    }
+
+    #[test]
+    fn test_parse_columns() {
+        let columns = parse_columns(
+            "notes|title|text|text|NO|NEVER\nnotes|search_vector|tsvector|tsvector|YES|ALWAYS\nbad\n",
+        );
+        assert_eq!(columns.len(), 2);
+        assert_eq!(columns[0].name, "title");
+        assert!(!columns[0].nullable);
+        assert!(columns[1].generated);
+    }
+
+    #[test]
+    fn test_structural_columns_are_kept() {
+        let kept = |c: Column| replacement(&c, SALT).unwrap();
+        assert_eq!(kept(column("id", "text", "text", false)), None);
+        assert_eq!(kept(column("user_id", "text", "text", false)), None);
+        assert_eq!(kept(column("status", "text", "text", false)), None);
+        assert_eq!(
+            kept(column(
+                "created_at",
+                "timestamp with time zone",
+                "timestamptz",
+                false
+            )),
+            None
+        );
+        assert_eq!(kept(column("word_count", "integer", "int4", false)), None);
+
+        let mut generated = column("search_vector", "tsvector", "tsvector", true);
+        generated.generated = true;
+        assert_eq!(kept(generated), None);
+    }
+
+    #[test]
+    fn test_content_columns_are_replaced() {
+        let replaced = |c: Column| replacement(&c, SALT).unwrap().unwrap();
+        let title = replaced(column("title", "character varying", "varchar", false));
+        assert!(title.contains("md5('0123abcd' || \"title\")"));
+        assert!(title.contains("lorem"));
+
+        let tags = replaced(column("tags", "ARRAY", "_text", true));
+        assert!(tags.contains("md5('0123abcd' || v)"));
+        assert!(tags.contains("unnest(\"tags\")"));
+
+        assert_eq!(
+            replaced(column("content_json", "jsonb", "jsonb", true)),
+            "NULL"
+        );
+        assert_eq!(
+            replaced(column("settings", "jsonb", "jsonb", false)),
+            "'{}'::jsonb"
+        );
+        assert!(
+            replaced(column("embedding", "USER-DEFINED", "vector", false)).contains("vector_dims")
+        );
+    }
+
+    #[test]
+    fn test_unknown_types_are_refused() {
+        assert_eq!(
+            replacement(&column("email", "USER-DEFINED", "citext", false), SALT),
+            Err("notes.email (citext)".to_string())
+        );
+        assert!(replacement(&column("body", "xml", "xml", true), SALT).is_err());
+        assert!(replacement(&column("scores", "ARRAY", "_float4", true), SALT).is_err());
+
+        let columns = vec![
+            column("title", "text", "text", false),
+            column("email", "USER-DEFINED", "citext", false),
+            column("body", "xml", "xml", true),
+        ];
+        assert_eq!(
+            sanitize_script(&columns, SALT),
+            Err("Can't sanitize column(s) of unsupported types: notes.email (citext), notes.body (xml)"
+                .to_string())
+        );
+    }
+
+    #[test]
+    fn test_sanitize_script_escapes_and_counts() {
+        let columns = vec![
+            column("title", "text", "text", false),
+            column("id", "text", "text", false),
+            column("it's", "jsonb", "jsonb", false),
+        ];
+        let (script, count) = sanitize_script(&columns, SALT).unwrap();
+        assert_eq!(count, 2);
+        assert!(script.starts_with("SET session_replication_role = replica;"));
+        assert!(script.contains("EXECUTE 'UPDATE \"notes\" SET \"title\" = CASE"));
+        assert!(script.contains("md5(''0123abcd'' || \"title\")"));
+        assert!(script.contains("''{}''::jsonb"));
+        assert!(script.contains("VALUES ('notes.it''s')"));
+        assert!(script
+            .trim_end()
+            .ends_with("SELECT col FROM sanitize_failures;"));
+    }
+
+    #[test]
+    fn test_new_salt_is_random_hex() {
+        let salt = new_salt().unwrap();
+        assert_eq!(salt.len(), 32);
+        assert!(salt.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_ne!(salt, new_salt().unwrap());
+    }
+}
@@ -0,0 +1,1230 @@
+//! Background task scheduler for recurring backend jobs.
+//!
+//! This module provides:
+//! - Daily, weekly, interval and cron schedules evaluated in local time
+//! - Random jitter so jobs registered together don't all fire at once
+//! - Battery-aware deferral of heavy jobs, and holding them until the user
+//!   is idle (see `idle`)
+//! - Pausing ingestion and backup jobs while the disk is almost full (see
+//!   `disk_space`)
+//! - Catch-up of runs missed while the machine was asleep or the app closed,
+//!   using last-run state persisted in scheduler-state.json
+//! - Persisted schedule settings for summarization jobs
+//! - `list_scheduled_jobs` and `run_job_now` for the settings UI
+
+use chrono::{
+    DateTime, Datelike, Duration as ChronoDuration, Local, NaiveDate, TimeZone, Timelike,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::config::{load_json, save_json_atomic};
+use crate::error::AppError;
+use crate::AppState;
+
+/// How often due jobs are checked
+const TICK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Job ID for daily note summarization
+pub const DAILY_SUMMARY_JOB_ID: &str = "daily-note-summary";
+
+/// Job ID for weekly review generation
+pub const WEEKLY_REVIEW_JOB_ID: &str = "weekly-review";
+
+/// Job ID for pushing calendar events into the daily note
+pub const DAILY_NOTE_EVENTS_JOB_ID: &str = "daily-note-events";
+
+/// Job ID for polling RSS/Atom feeds
+pub const FEED_REFRESH_JOB_ID: &str = "feed-refresh";
+
+/// Job ID for encrypted database backups
+pub const ENCRYPTED_BACKUP_JOB_ID: &str = "encrypted-backup";
+
+/// Job ID for the Obsidian vault sync pass
+pub const OBSIDIAN_SYNC_JOB_ID: &str = "obsidian-sync";
+
+/// Job ID for syncing with paired LAN devices
+pub const PEER_SYNC_JOB_ID: &str = "peer-sync";
+
+/// Job ID for committing the git-backed note history
+pub const NOTE_HISTORY_JOB_ID: &str = "note-history";
+
+/// Job ID for purging trashed items past the retention period
+pub const TRASH_PURGE_JOB_ID: &str = "trash-purge";
+
+/// Job ID for periodic snapshots of the app data directory
+pub const SNAPSHOT_JOB_ID: &str = "data-snapshot";
+
+/// Job ID for local database backups
+pub const LOCAL_BACKUP_JOB_ID: &str = "local-backup";
+
+/// Job ID for WAL archive compression, base backups and pruning
+pub const WAL_ARCHIVE_JOB_ID: &str = "wal-archive";
+
+/// Backend endpoint that starts note summary generation
+const SUMMARY_START_PATH: &str = "/notes/summaries/start";
+
+/// Backend endpoint that generates the weekly review
+const WEEKLY_REVIEW_PATH: &str = "/reviews/weekly";
+
+/// How far ahead a cron schedule is searched for its next match
+const CRON_SEARCH_DAYS: i64 = 366 * 4;
+
+/// When a job should run
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Schedule {
+    /// Every day at a local time
+    Daily { hour: u32, minute: u32 },
+    /// Every week on a weekday (0 = Monday) at a local time
+    Weekly {
+        weekday: u32,
+        hour: u32,
+        minute: u32,
+    },
+    /// At a fixed interval
+    Interval { every_secs: u64 },
+    /// Five-field cron expression (minute hour day month weekday)
+    Cron { expression: String },
+}
+
+impl Schedule {
+    /// Validate schedule fields
+    pub fn validate(&self) -> Result<(), String> {
+        match *self {
+            Schedule::Daily { hour, minute } => validate_time(hour, minute),
+            Schedule::Cron { ref expression } => {
+                let cron = CronExpr::parse(expression)?;
+                if cron.next_after(Local::now()).is_none() {
+                    return Err(format!("Cron expression never matches: {}", expression));
+                }
+                Ok(())
+            }
+            Schedule::Weekly {
+                weekday,
+                hour,
+                minute,
+            } => {
+                if weekday > 6 {
+                    return Err(format!(
+                        "Invalid weekday {}: must be 0 (Monday) to 6 (Sunday)",
+                        weekday
+                    ));
+                }
+                validate_time(hour, minute)
+            }
+            Schedule::Interval { every_secs } => {
+                if every_secs < 60 {
+                    return Err("Interval must be at least 60 seconds".to_string());
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Next run strictly after `after`
+    pub fn next_after(&self, after: DateTime<Local>) -> DateTime<Local> {
+        match *self {
+            Schedule::Daily { hour, minute } => {
+                let today = local_at(after.date_naive(), hour, minute);
+                if today > after {
+                    today
+                } else {
+                    local_at(after.date_naive() + ChronoDuration::days(1), hour, minute)
+                }
+            }
+            Schedule::Weekly {
+                weekday,
+                hour,
+                minute,
+            } => {
+                let current = after.weekday().num_days_from_monday();
+                let days_ahead = (weekday + 7 - current) % 7;
+                let date = after.date_naive() + ChronoDuration::days(days_ahead as i64);
+                let candidate = local_at(date, hour, minute);
+                if candidate > after {
+                    candidate
+                } else {
+                    local_at(date + ChronoDuration::days(7), hour, minute)
+                }
+            }
+            Schedule::Interval { every_secs } => after + ChronoDuration::seconds(every_secs as i64),
+            // Validation rejects expressions that never match; this only
+            // guards against a hand-edited settings file
+            Schedule::Cron { ref expression } => CronExpr::parse(expression)
+                .ok()
+                .and_then(|cron| cron.next_after(after))
+                .unwrap_or(after + ChronoDuration::days(CRON_SEARCH_DAYS)),
+        }
+    }
+}
+
+/// A parsed cron expression
+///
+/// Fields are minute (0-59), hour (0-23), day of month (1-31), month (1-12)
+/// and weekday (0-7, both 0 and 7 meaning Sunday). Each field accepts `*`,
+/// numbers, ranges (`1-5`), lists (`1,15`) and steps (`*/15`, `0-30/10`).
+/// `@hourly`, `@daily`, `@weekly` and `@monthly` are also accepted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronExpr {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    /// Day of month was `*`, so only the weekday restricts days
+    any_day: bool,
+    /// Weekday was `*`, so only the day of month restricts days
+    any_weekday: bool,
+}
+
+impl CronExpr {
+    pub fn parse(expression: &str) -> Result<Self, String> {
+        let expanded = match expression.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            other => other,
+        };
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(format!(
+                "Cron expression must have 5 fields (minute hour day month weekday): {}",
+                expression
+            ));
+        }
+        let mut weekdays = parse_cron_field(fields[4], 0, 7)?;
+        // 7 is an alias for Sunday
+        if weekdays & (1 << 7) != 0 {
+            weekdays = (weekdays | 1) & !(1 << 7);
+        }
+        Ok(Self {
+            minutes: parse_cron_field(fields[0], 0, 59)?,
+            hours: parse_cron_field(fields[1], 0, 23)?,
+            days: parse_cron_field(fields[2], 1, 31)?,
+            months: parse_cron_field(fields[3], 1, 12)?,
+            weekdays,
+            any_day: fields[2] == "*",
+            any_weekday: fields[4] == "*",
+        })
+    }
+
+    fn matches_day(&self, date: NaiveDate) -> bool {
+        let day = self.days & (1 << date.day()) != 0;
+        let weekday = self.weekdays & (1 << date.weekday().num_days_from_sunday()) != 0;
+        match (self.any_day, self.any_weekday) {
+            (true, true) => true,
+            (true, false) => weekday,
+            (false, true) => day,
+            // Like cron, a restricted day and weekday match either
+            (false, false) => day || weekday,
+        }
+    }
+
+    /// First matching minute strictly after `after`, if any within four years
+    pub fn next_after(&self, after: DateTime<Local>) -> Option<DateTime<Local>> {
+        let start = after
+            .naive_local()
+            .with_second(0)
+            .and_then(|t| t.with_nanosecond(0))?
+            + ChronoDuration::minutes(1);
+        let limit = start + ChronoDuration::days(CRON_SEARCH_DAYS);
+        let mut t = start;
+
+        while t < limit {
+            if self.months & (1 << t.month()) == 0 {
+                t = first_of_next_month(t.date())?.and_hms_opt(0, 0, 0)?;
+            } else if !self.matches_day(t.date()) {
+                t = (t.date() + ChronoDuration::days(1)).and_hms_opt(0, 0, 0)?;
+            } else if self.hours & (1 << t.hour()) == 0 {
+                t = t.date().and_hms_opt(t.hour(), 0, 0)? + ChronoDuration::hours(1);
+            } else if self.minutes & (1 << t.minute()) == 0 {
+                t += ChronoDuration::minutes(1);
+            } else {
+                // Times inside a DST gap don't exist locally and are skipped
+                match Local.from_local_datetime(&t).earliest() {
+                    Some(resolved) if resolved > after => return Some(resolved),
+                    _ => t += ChronoDuration::minutes(1),
+                }
+            }
+        }
+        None
+    }
+}
+
+fn first_of_next_month(date: NaiveDate) -> Option<NaiveDate> {
+    if date.month() == 12 {
+        NaiveDate::from_ymd_opt(date.year() + 1, 1, 1)
+    } else {
+        NaiveDate::from_ymd_opt(date.year(), date.month() + 1, 1)
+    }
+}
+
+/// Parse one cron field into a bitset of allowed values
+fn parse_cron_field(field: &str, min: u32, max: u32) -> Result<u64, String> {
+    let mut bits = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step
+                    .parse()
+                    .map_err(|_| format!("Invalid cron step: {}", part))?;
+                if step == 0 {
+                    return Err(format!("Invalid cron step: {}", part));
+                }
+                (range, step)
+            }
+            None => (part, 1),
+        };
+        let (low, high) = if range == "*" {
+            (min, max)
+        } else if let Some((low, high)) = range.split_once('-') {
+            (parse_cron_value(low, part)?, parse_cron_value(high, part)?)
+        } else {
+            let value = parse_cron_value(range, part)?;
+            // `5/15` means every 15 starting at 5
+            (value, if step > 1 { max } else { value })
+        };
+        if low < min || high > max || low > high {
+            return Err(format!("Cron field {} out of range {}-{}", part, min, max));
+        }
+        for value in (low..=high).step_by(step as usize) {
+            bits |= 1 << value;
+        }
+    }
+    Ok(bits)
+}
+
+fn parse_cron_value(value: &str, part: &str) -> Result<u32, String> {
+    value
+        .parse()
+        .map_err(|_| format!("Invalid cron field: {}", part))
+}
+
+fn validate_time(hour: u32, minute: u32) -> Result<(), String> {
+    if hour > 23 || minute > 59 {
+        return Err(format!("Invalid time {:02}:{:02}", hour, minute));
+    }
+    Ok(())
+}
+
+/// Resolve a local wall-clock time, skipping forward over DST gaps
+fn local_at(date: NaiveDate, hour: u32, minute: u32) -> DateTime<Local> {
+    let mut naive = date.and_hms_opt(hour, minute, 0).unwrap_or_default();
+    for _ in 0..3 {
+        if let Some(resolved) = Local.from_local_datetime(&naive).earliest() {
+            return resolved;
+        }
+        naive += ChronoDuration::hours(1);
+    }
+    Local.from_utc_datetime(&naive)
+}
+
+/// What a job does when it runs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum JobAction {
+    /// Call a backend API endpoint (path relative to `/api`)
+    BackendRequest {
+        method: String,
+        path: String,
+        body: Option<serde_json::Value>,
+    },
+    /// Push today's calendar events into the daily note
+    PushCalendarEvents,
+    /// Refresh feeds whose interval has elapsed
+    RefreshFeeds,
+    /// Write an encrypted backup to the configured folder
+    EncryptedBackup,
+    /// Two-way sync with the configured Obsidian vault
+    ObsidianSync,
+    /// Sync notes with every paired LAN device
+    PeerSync,
+    /// Export notes to the history repository and commit when due
+    NoteHistorySnapshot,
+    /// Permanently delete trashed items past the retention period
+    PurgeTrash,
+    /// Snapshot the app data directory
+    DataSnapshot,
+    /// Back up the database into the local backup folder
+    LocalBackup,
+    /// Compress and prune the WAL archive, taking a base backup when due
+    WalArchiveMaintenance,
+}
+
+impl JobAction {
+    /// Whether the job ingests or backs up data, and so is paused when the
+    /// disk is almost full
+    pub fn writes_data(&self) -> bool {
+        matches!(
+            self,
+            JobAction::RefreshFeeds
+                | JobAction::EncryptedBackup
+                | JobAction::ObsidianSync
+                | JobAction::PeerSync
+                | JobAction::NoteHistorySnapshot
+                | JobAction::DataSnapshot
+                | JobAction::LocalBackup
+                | JobAction::WalArchiveMaintenance
+        )
+    }
+}
+
+/// A recurring job definition
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledJob {
+    pub id: String,
+    pub name: String,
+    pub schedule: Schedule,
+    /// Defer the job while the machine is on battery power
+    pub skip_on_battery: bool,
+    /// Defer the job while the user is at the computer
+    #[serde(default)]
+    pub wait_for_idle: bool,
+    /// Up to this many seconds are added at random to each planned run
+    #[serde(default)]
+    pub jitter_secs: u64,
+    pub action: JobAction,
+}
+
+/// Runtime status of a scheduled job
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobStatus {
+    pub job: ScheduledJob,
+    /// Next planned run (Unix epoch seconds)
+    pub next_run: i64,
+    /// Last completed run (Unix epoch seconds)
+    pub last_run: Option<i64>,
+    /// Error from the last run, if it failed
+    pub last_error: Option<String>,
+    /// Why a due job is currently being held back
+    pub deferred_reason: Option<String>,
+    /// Whether the job is running right now
+    #[serde(default)]
+    pub running: bool,
+}
+
+/// Run history kept across restarts, keyed by job ID in scheduler-state.json
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct PersistedRun {
+    /// Schedule the next run was planned with; a changed schedule replans
+    schedule: Schedule,
+    next_run: i64,
+    last_run: Option<i64>,
+    last_error: Option<String>,
+}
+
+fn state_path(app_data_dir: &Path) -> PathBuf {
+    app_data_dir.join("scheduler-state.json")
+}
+
+/// Random delay of up to `max_secs`
+fn jitter(max_secs: u64) -> i64 {
+    if max_secs == 0 {
+        return 0;
+    }
+    let mut bytes = [0u8; 8];
+    if getrandom::fill(&mut bytes).is_err() {
+        return 0;
+    }
+    (u64::from_le_bytes(bytes) % (max_secs + 1)) as i64
+}
+
+/// Next planned run of `job` after `now`, including jitter
+fn plan(job: &ScheduledJob, now: DateTime<Local>) -> i64 {
+    job.schedule.next_after(now).timestamp() + jitter(job.jitter_secs)
+}
+
+/// Event payload emitted after a job runs
+#[derive(Debug, Clone, Serialize)]
+pub struct JobRunEvent {
+    pub id: String,
+    pub success: bool,
+    pub error: Option<String>,
+    /// Whether the run was a catch-up for a missed time (e.g. after sleep)
+    pub missed: bool,
+}
+
+/// Registry of scheduled jobs, stored in managed state
+#[derive(Default)]
+pub struct Scheduler {
+    jobs: Mutex<Vec<JobStatus>>,
+    /// Run history loaded from and saved to `state_path`
+    persisted: Mutex<HashMap<String, PersistedRun>>,
+    state_path: Mutex<Option<PathBuf>>,
+}
+
+impl Scheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load persisted run history, so runs missed while the app was closed
+    /// are caught up and last-run times survive restarts
+    pub fn load_state(&self, app_data_dir: &Path) {
+        let path = state_path(app_data_dir);
+        let persisted: HashMap<String, PersistedRun> = load_json(&path).unwrap_or_default();
+        {
+            let mut jobs = self.jobs.lock().unwrap();
+            for status in jobs.iter_mut() {
+                if let Some(run) = persisted.get(&status.job.id) {
+                    restore(status, run);
+                }
+            }
+        }
+        *self.persisted.lock().unwrap() = persisted;
+        *self.state_path.lock().unwrap() = Some(path);
+    }
+
+    fn save_state(&self) {
+        let Some(path) = self.state_path.lock().unwrap().clone() else {
+            return;
+        };
+        // Same lock order as upsert_job: jobs before persisted
+        let jobs = self.list();
+        let mut persisted = self.persisted.lock().unwrap();
+        for status in &jobs {
+            persisted.insert(
+                status.job.id.clone(),
+                PersistedRun {
+                    schedule: status.job.schedule.clone(),
+                    next_run: status.next_run,
+                    last_run: status.last_run,
+                    last_error: status.last_error.clone(),
+                },
+            );
+        }
+        if let Err(e) = save_json_atomic(&path, &*persisted) {
+            tracing::warn!("Failed to save scheduler state: {}", e);
+        }
+    }
+
+    /// Add or replace a job, scheduling its next run from `now`
+    pub fn upsert_job(&self, job: ScheduledJob, now: DateTime<Local>) {
+        let mut jobs = self.jobs.lock().unwrap();
+        let next_run = plan(&job, now);
+
+        if let Some(existing) = jobs.iter_mut().find(|s| s.job.id == job.id) {
+            if existing.job.schedule != job.schedule {
+                existing.next_run = next_run;
+            }
+            existing.job = job;
+        } else {
+            let mut status = JobStatus {
+                job,
+                next_run,
+                last_run: None,
+                last_error: None,
+                deferred_reason: None,
+                running: false,
+            };
+            if let Some(run) = self.persisted.lock().unwrap().get(&status.job.id) {
+                restore(&mut status, run);
+            }
+            jobs.push(status);
+        }
+    }
+
+    /// Remove a job by ID
+    pub fn remove_job(&self, id: &str) {
+        self.jobs.lock().unwrap().retain(|s| s.job.id != id);
+    }
+
+    /// Jobs whose next run is at or before `now` and that aren't running
+    pub fn due_jobs(&self, now: DateTime<Local>) -> Vec<JobStatus> {
+        self.jobs
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|s| s.next_run <= now.timestamp() && !s.running)
+            .cloned()
+            .collect()
+    }
+
+    /// Mark a job as running, failing if it's unknown or already running
+    pub fn begin_run(&self, id: &str) -> Result<ScheduledJob, AppError> {
+        let mut jobs = self.jobs.lock().unwrap();
+        let status = jobs
+            .iter_mut()
+            .find(|s| s.job.id == id)
+            .ok_or_else(|| AppError::NotFound(format!("No scheduled job '{}'", id)))?;
+        if status.running {
+            return Err(AppError::Conflict(format!(
+                "Job '{}' is already running",
+                id
+            )));
+        }
+        status.running = true;
+        Ok(status.job.clone())
+    }
+
+    /// Hold a due job back without advancing its schedule
+    pub fn defer(&self, id: &str, reason: &str) {
+        if let Some(status) = self
+            .jobs
+            .lock()
+            .unwrap()
+            .iter_mut()
+            .find(|s| s.job.id == id)
+        {
+            status.deferred_reason = Some(reason.to_string());
+        }
+    }
+
+    /// Record a run and schedule the next one from `now`
+    ///
+    /// Scheduling from `now` rather than the missed time collapses any number
+    /// of missed runs (e.g. a week asleep) into a single catch-up run.
+    pub fn mark_ran(&self, id: &str, now: DateTime<Local>, result: Result<(), String>) {
+        if let Some(status) = self
+            .jobs
+            .lock()
+            .unwrap()
+            .iter_mut()
+            .find(|s| s.job.id == id)
+        {
+            status.last_run = Some(now.timestamp());
+            status.next_run = plan(&status.job, now);
+            status.last_error = result.err();
+            status.deferred_reason = None;
+            status.running = false;
+        }
+        self.save_state();
+    }
+
+    /// Whether the job list is locked right now, for contention sampling
+    pub fn is_locked(&self) -> bool {
+        matches!(
+            self.jobs.try_lock(),
+            Err(std::sync::TryLockError::WouldBlock)
+        )
+    }
+
+    /// Snapshot of all jobs
+    pub fn list(&self) -> Vec<JobStatus> {
+        self.jobs.lock().unwrap().clone()
+    }
+
+    /// Snapshot of one job
+    pub fn get(&self, id: &str) -> Option<JobStatus> {
+        self.jobs
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|s| s.job.id == id)
+            .cloned()
+    }
+}
+
+/// Apply persisted history to a job, keeping its planned run if the schedule
+/// is unchanged so a run missed while the app was closed is still due
+fn restore(status: &mut JobStatus, run: &PersistedRun) {
+    status.last_run = run.last_run;
+    status.last_error = run.last_error.clone();
+    if run.schedule == status.job.schedule {
+        status.next_run = run.next_run;
+    }
+}
+
+/// User-configurable summarization schedule
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScheduleSettings {
+    /// Daily note summarization time, disabled when None
+    pub daily_summary: Option<Schedule>,
+    /// Weekly review generation time, disabled when None
+    pub weekly_review: Option<Schedule>,
+    /// Time to push today's calendar events into the daily note, disabled when None
+    #[serde(default)]
+    pub daily_note_events: Option<Schedule>,
+    /// Defer jobs while on battery power
+    pub skip_on_battery: bool,
+    /// Hold summarization and reviews until the user is idle
+    pub wait_for_idle: bool,
+}
+
+impl Default for ScheduleSettings {
+    fn default() -> Self {
+        Self {
+            daily_summary: None,
+            weekly_review: None,
+            daily_note_events: None,
+            skip_on_battery: true,
+            wait_for_idle: true,
+        }
+    }
+}
+
+impl ScheduleSettings {
+    fn path(app_data_dir: &Path) -> PathBuf {
+        app_data_dir.join("schedule-settings.json")
+    }
+
+    /// Load settings, returning defaults if missing or invalid
+    pub fn load(app_data_dir: &Path) -> Self {
+        load_json(&Self::path(app_data_dir)).unwrap_or_default()
+    }
+
+    /// Save settings atomically
+    pub fn save(&self, app_data_dir: &Path) -> Result<(), String> {
+        save_json_atomic(&Self::path(app_data_dir), self)
+    }
+
+    /// Validate configured schedules
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(ref schedule) = self.daily_summary {
+            schedule.validate()?;
+        }
+        if let Some(ref schedule) = self.weekly_review {
+            schedule.validate()?;
+        }
+        if let Some(ref schedule) = self.daily_note_events {
+            schedule.validate()?;
+        }
+        Ok(())
+    }
+
+    /// Job definitions for the enabled schedules
+    pub fn jobs(&self) -> Vec<ScheduledJob> {
+        let mut jobs = Vec::new();
+
+        if let Some(ref schedule) = self.daily_summary {
+            jobs.push(ScheduledJob {
+                id: DAILY_SUMMARY_JOB_ID.to_string(),
+                name: "Daily note summarization".to_string(),
+                schedule: schedule.clone(),
+                skip_on_battery: self.skip_on_battery,
+                wait_for_idle: self.wait_for_idle,
+                jitter_secs: 0,
+                action: JobAction::BackendRequest {
+                    method: "POST".to_string(),
+                    path: SUMMARY_START_PATH.to_string(),
+                    body: Some(serde_json::json!({ "noteIds": [] })),
+                },
+            });
+        }
+
+        if let Some(ref schedule) = self.weekly_review {
+            jobs.push(ScheduledJob {
+                id: WEEKLY_REVIEW_JOB_ID.to_string(),
+                name: "Weekly review generation".to_string(),
+                schedule: schedule.clone(),
+                skip_on_battery: self.skip_on_battery,
+                wait_for_idle: self.wait_for_idle,
+                jitter_secs: 0,
+                action: JobAction::BackendRequest {
+                    method: "POST".to_string(),
+                    path: WEEKLY_REVIEW_PATH.to_string(),
+                    body: None,
+                },
+            });
+        }
+
+        if let Some(ref schedule) = self.daily_note_events {
+            jobs.push(ScheduledJob {
+                id: DAILY_NOTE_EVENTS_JOB_ID.to_string(),
+                name: "Calendar events to daily note".to_string(),
+                schedule: schedule.clone(),
+                // A single calendar query is cheap enough to run on battery
+                skip_on_battery: false,
+                // and the events are wanted before the day starts
+                wait_for_idle: false,
+                jitter_secs: 0,
+                action: JobAction::PushCalendarEvents,
+            });
+        }
+
+        jobs
+    }
+}
+
+/// Register the jobs from `settings`, removing disabled ones
+pub fn apply_settings(scheduler: &Scheduler, settings: &ScheduleSettings) {
+    let now = Local::now();
+    scheduler.remove_job(DAILY_SUMMARY_JOB_ID);
+    scheduler.remove_job(WEEKLY_REVIEW_JOB_ID);
+    scheduler.remove_job(DAILY_NOTE_EVENTS_JOB_ID);
+    for job in settings.jobs() {
+        scheduler.upsert_job(job, now);
+    }
+}
+
+/// Load persisted settings and run history and start the scheduler loop
+pub fn start(app: AppHandle) {
+    if let Ok(app_data_dir) = app.path().app_data_dir() {
+        app.state::<Scheduler>().load_state(&app_data_dir);
+        let settings = ScheduleSettings::load(&app_data_dir);
+        apply_settings(&app.state::<Scheduler>(), &settings);
+    }
+
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(TICK_INTERVAL).await;
+            run_due_jobs(&app).await;
+        }
+    });
+}
+
+/// Run every due job once
+async fn run_due_jobs(app: &AppHandle) {
+    let now = Local::now();
+    let due = app.state::<Scheduler>().due_jobs(now);
+
+    for status in due {
+        let job = status.job;
+
+        if job.skip_on_battery && crate::power::is_on_battery() {
+            tracing::debug!("Deferring job '{}' while on battery power", job.id);
+            app.state::<Scheduler>().defer(&job.id, "on battery power");
+            continue;
+        }
+
+        if job.wait_for_idle && !crate::idle::is_user_idle(app) {
+            tracing::debug!("Deferring job '{}' until the user is idle", job.id);
+            app.state::<Scheduler>()
+                .defer(&job.id, "waiting for the user to be idle");
+            continue;
+        }
+
+        if job.action.writes_data() {
+            if let Some(reason) = crate::disk_space::pause_reason(app) {
+                tracing::debug!("Deferring job '{}': {}", job.id, reason);
+                app.state::<Scheduler>().defer(&job.id, &reason);
+                continue;
+            }
+        }
+
+        if !*app.state::<AppState>().is_backend_ready.read() {
+            app.state::<Scheduler>().defer(&job.id, "backend not ready");
+            continue;
+        }
+
+        // run_job_now may have started it since due_jobs was taken
+        if app.state::<Scheduler>().begin_run(&job.id).is_err() {
+            continue;
+        }
+
+        // A run more than two ticks late was missed (sleep, app closed, deferral)
+        let missed = now.timestamp() - status.next_run > 2 * TICK_INTERVAL.as_secs() as i64;
+        if missed {
+            tracing::info!("Running missed job '{}'", job.id);
+        } else {
+            tracing::info!("Running scheduled job '{}'", job.id);
+        }
+        let _ = run_job(app, &job, missed).await;
+    }
+}
+
+/// Run a job already marked as running, then record the result
+async fn run_job(app: &AppHandle, job: &ScheduledJob, missed: bool) -> Result<(), String> {
+    let result = execute(app, &job.action).await;
+    if let Err(ref e) = result {
+        tracing::warn!("Scheduled job '{}' failed: {}", job.id, e);
+    }
+
+    let _ = app.emit(
+        "scheduled-job-ran",
+        JobRunEvent {
+            id: job.id.clone(),
+            success: result.is_ok(),
+            error: result.as_ref().err().cloned(),
+            missed,
+        },
+    );
+
+    app.state::<Scheduler>()
+        .mark_ran(&job.id, Local::now(), result.clone());
+    result
+}
+
+async fn execute(app: &AppHandle, action: &JobAction) -> Result<(), String> {
+    match action {
+        JobAction::BackendRequest { method, path, body } => {
+            crate::proxy::send_backend_request(app, method, path, body.as_ref(), None)
+                .await
+                .map(|_| ())
+                .map_err(String::from)
+        }
+        JobAction::PushCalendarEvents => crate::calendar::push_today_events(app).await,
+        JobAction::RefreshFeeds => crate::feeds::refresh_due_feeds(app).await,
+        JobAction::EncryptedBackup => {
+            crate::backup::run_encrypted_backup(&crate::jobs::JobContext::untracked(app))
+                .await
+                .map(|_| ())
+        }
+        JobAction::ObsidianSync => crate::obsidian::sync_vault(app).await,
+        JobAction::PeerSync => crate::peer_sync::sync_all(app).await,
+        JobAction::NoteHistorySnapshot => crate::note_history::snapshot_notes(app).await,
+        JobAction::PurgeTrash => crate::trash::purge_expired(app).await,
+        JobAction::DataSnapshot => crate::snapshots::run_scheduled_snapshot(app).await,
+        JobAction::LocalBackup => crate::backup::run_scheduled_local_backup(app).await,
+        JobAction::WalArchiveMaintenance => crate::wal_archive::run_maintenance(app).await,
+    }
+}
+
+/// List every registered job with its next and last run
+#[tauri::command]
+pub async fn list_scheduled_jobs(app: AppHandle) -> Result<Vec<JobStatus>, AppError> {
+    let mut jobs = app.state::<Scheduler>().list();
+    jobs.sort_by_key(|s| s.next_run);
+    Ok(jobs)
+}
+
+/// Run a job immediately, ignoring battery and idle deferral, and plan its next run
+#[tauri::command]
+pub async fn run_job_now(app: AppHandle, id: String) -> Result<JobStatus, AppError> {
+    if !*app.state::<AppState>().is_backend_ready.read() {
+        return Err(AppError::NotReady("Backend is not ready".to_string()));
+    }
+    if let Some(reason) = crate::disk_space::pause_reason(&app) {
+        let writes_data = app
+            .state::<Scheduler>()
+            .get(&id)
+            .is_some_and(|status| status.job.action.writes_data());
+        if writes_data {
+            return Err(AppError::Io(format!("Job '{}' is {}", id, reason)));
+        }
+    }
+    let job = app.state::<Scheduler>().begin_run(&id)?;
+    tracing::info!("Running job '{}' on request", id);
+    run_job(&app, &job, false).await?;
+    app.state::<Scheduler>()
+        .get(&id)
+        .ok_or_else(|| AppError::NotFound(format!("No scheduled job '{}'", id)))
+}
+
+/// Get the summarization schedule settings
+#[tauri::command]
+pub async fn get_schedule_settings(app: AppHandle) -> Result<ScheduleSettings, AppError> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    Ok(ScheduleSettings::load(&app_data_dir))
+}
+
+/// Update the summarization schedule settings and reschedule jobs
+#[tauri::command]
+pub async fn set_schedule_settings(
+    app: AppHandle,
+    settings: ScheduleSettings,
+) -> Result<(), AppError> {
+    settings.validate()?;
+
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    settings.save(&app_data_dir)?;
+
+    apply_settings(&app.state::<Scheduler>(), &settings);
+    Ok(())
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn local(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Local> {
+        local_at(NaiveDate::from_ymd_opt(y, m, d).unwrap(), h, min)
+    }
+
+    // ============================================================
+    // Schedule Tests
+    // ============================================================
+
+    #[test]
+    fn test_daily_next_after_later_today() {
+        let schedule = Schedule::Daily {
+            hour: 18,
+            minute: 0,
+        };
+        let next = schedule.next_after(local(2025, 3, 10, 9, 0));
+        assert_eq!(next, local(2025, 3, 10, 18, 0));
+    }
+
+    #[test]
+    fn test_daily_next_after_rolls_to_tomorrow() {
+        let schedule = Schedule::Daily {
+            hour: 8,
+            minute: 30,
+        };
+        let next = schedule.next_after(local(2025, 3, 10, 8, 30));
+        assert_eq!(next, local(2025, 3, 11, 8, 30));
+    }
+
+    #[test]
+    fn test_weekly_next_after() {
+        // 2025-03-10 is a Monday; ask for Friday (4)
+        let schedule = Schedule::Weekly {
+            weekday: 4,
+            hour: 17,
+            minute: 0,
+        };
+        let next = schedule.next_after(local(2025, 3, 10, 9, 0));
+        assert_eq!(next, local(2025, 3, 14, 17, 0));
+    }
+
+    #[test]
+    fn test_weekly_same_day_past_time_rolls_a_week() {
+        let schedule = Schedule::Weekly {
+            weekday: 0,
+            hour: 8,
+            minute: 0,
+        };
+        let next = schedule.next_after(local(2025, 3, 10, 9, 0));
+        assert_eq!(next, local(2025, 3, 17, 8, 0));
+    }
+
+    #[test]
+    fn test_interval_next_after() {
+        let schedule = Schedule::Interval { every_secs: 3600 };
+        let now = local(2025, 3, 10, 9, 0);
+        assert_eq!(schedule.next_after(now), local(2025, 3, 10, 10, 0));
+    }
+
+    #[test]
+    fn test_schedule_validation() {
+        assert!(Schedule::Daily {
+            hour: 24,
+            minute: 0
+        }
+        .validate()
+        .is_err());
+        assert!(Schedule::Weekly {
+            weekday: 7,
+            hour: 9,
+            minute: 0
+        }
+        .validate()
+        .is_err());
+        assert!(Schedule::Interval { every_secs: 10 }.validate().is_err());
+        assert!(Schedule::Daily {
+            hour: 7,
+            minute: 45
+        }
+        .validate()
+        .is_ok());
+    }
+
+    // ============================================================
+    // Scheduler Tests
+    // ============================================================
+
+    fn test_job(id: &str) -> ScheduledJob {
+        ScheduledJob {
+            id: id.to_string(),
+            name: id.to_string(),
+            schedule: Schedule::Daily { hour: 9, minute: 0 },
+            skip_on_battery: false,
+            wait_for_idle: false,
+            jitter_secs: 0,
+            action: JobAction::BackendRequest {
+                method: "POST".to_string(),
+                path: "/test".to_string(),
+                body: None,
+            },
+        }
+    }
+
+    #[test]
+    fn test_job_becomes_due() {
+        let scheduler = Scheduler::new();
+        scheduler.upsert_job(test_job("a"), local(2025, 3, 10, 8, 0));
+
+        assert!(scheduler.due_jobs(local(2025, 3, 10, 8, 59)).is_empty());
+        assert_eq!(scheduler.due_jobs(local(2025, 3, 10, 9, 0)).len(), 1);
+    }
+
+    #[test]
+    fn test_missed_runs_collapse_into_one() {
+        let scheduler = Scheduler::new();
+        scheduler.upsert_job(test_job("a"), local(2025, 3, 10, 8, 0));
+
+        // Asleep for several days, then woken
+        let wake = local(2025, 3, 14, 12, 0);
+        assert_eq!(scheduler.due_jobs(wake).len(), 1);
+
+        scheduler.mark_ran("a", wake, Ok(()));
+        assert!(scheduler.due_jobs(wake).is_empty());
+
+        let status = &scheduler.list()[0];
+        assert_eq!(status.next_run, local(2025, 3, 15, 9, 0).timestamp());
+    }
+
+    #[test]
+    fn test_defer_keeps_job_due() {
+        let scheduler = Scheduler::new();
+        scheduler.upsert_job(test_job("a"), local(2025, 3, 10, 8, 0));
+
+        let now = local(2025, 3, 10, 9, 1);
+        scheduler.defer("a", "on battery power");
+
+        assert_eq!(scheduler.due_jobs(now).len(), 1);
+        assert_eq!(
+            scheduler.list()[0].deferred_reason.as_deref(),
+            Some("on battery power")
+        );
+    }
+
+    #[test]
+    fn test_mark_ran_records_error() {
+        let scheduler = Scheduler::new();
+        scheduler.upsert_job(test_job("a"), local(2025, 3, 10, 8, 0));
+        scheduler.mark_ran("a", local(2025, 3, 10, 9, 0), Err("boom".to_string()));
+
+        let status = &scheduler.list()[0];
+        assert_eq!(status.last_error.as_deref(), Some("boom"));
+        assert!(status.last_run.is_some());
+    }
+
+    #[test]
+    fn test_begin_run_guards_concurrent_runs() {
+        let scheduler = Scheduler::new();
+        scheduler.upsert_job(test_job("a"), local(2025, 3, 10, 8, 0));
+
+        assert!(scheduler.begin_run("a").is_ok());
+        assert_eq!(scheduler.begin_run("a").unwrap_err().kind(), "conflict");
+        assert_eq!(scheduler.begin_run("b").unwrap_err().kind(), "not_found");
+        assert!(scheduler.due_jobs(local(2025, 3, 10, 9, 0)).is_empty());
+
+        scheduler.mark_ran("a", local(2025, 3, 10, 9, 0), Ok(()));
+        assert!(scheduler.begin_run("a").is_ok());
+    }
+
+    #[test]
+    fn test_jitter_stays_within_bounds() {
+        let mut job = test_job("a");
+        job.schedule = Schedule::Interval { every_secs: 600 };
+        job.jitter_secs = 120;
+        let now = local(2025, 3, 10, 8, 0);
+        for _ in 0..50 {
+            let offset = plan(&job, now) - now.timestamp();
+            assert!((600..=720).contains(&offset), "offset {}", offset);
+        }
+    }
+
+    #[test]
+    fn test_state_persists_across_restarts() {
+        let temp_dir = TempDir::new().unwrap();
+        let scheduler = Scheduler::new();
+        scheduler.load_state(temp_dir.path());
+        scheduler.upsert_job(test_job("a"), local(2025, 3, 10, 8, 0));
+        scheduler.mark_ran("a", local(2025, 3, 10, 9, 0), Err("boom".to_string()));
+
+        // Relaunched a week later: history is restored and the missed run is due
+        let restarted = Scheduler::new();
+        restarted.load_state(temp_dir.path());
+        let relaunch = local(2025, 3, 17, 12, 0);
+        restarted.upsert_job(test_job("a"), relaunch);
+        let status = &restarted.list()[0];
+        assert_eq!(status.last_run, Some(local(2025, 3, 10, 9, 0).timestamp()));
+        assert_eq!(status.last_error.as_deref(), Some("boom"));
+        assert_eq!(restarted.due_jobs(relaunch).len(), 1);
+
+        // A changed schedule replans from now instead
+        let replanned = Scheduler::new();
+        replanned.load_state(temp_dir.path());
+        let mut job = test_job("a");
+        job.schedule = Schedule::Daily {
+            hour: 18,
+            minute: 0,
+        };
+        replanned.upsert_job(job, relaunch);
+        assert!(replanned.due_jobs(relaunch).is_empty());
+    }
+
+    // ============================================================
+    // Cron Tests
+    // ============================================================
+
+    fn cron(expression: &str) -> Schedule {
+        Schedule::Cron {
+            expression: expression.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_cron_parse() {
+        assert!(CronExpr::parse("*/15 9-17 * * 1-5").is_ok());
+        assert!(CronExpr::parse("@daily").is_ok());
+        assert!(CronExpr::parse("0 9 * *").is_err());
+        assert!(CronExpr::parse("60 * * * *").is_err());
+        assert!(CronExpr::parse("*/0 * * * *").is_err());
+        assert!(CronExpr::parse("5-1 * * * *").is_err());
+        assert!(cron("0 0 31 2 *").validate().is_err());
+        assert!(cron("30 6 * * 7").validate().is_ok());
+    }
+
+    #[test]
+    fn test_cron_next_after() {
+        // 2025-03-10 is a Monday
+        let now = local(2025, 3, 10, 9, 7);
+        assert_eq!(
+            cron("*/15 * * * *").next_after(now),
+            local(2025, 3, 10, 9, 15)
+        );
+        assert_eq!(
+            cron("0 8 * * 1-5").next_after(now),
+            local(2025, 3, 11, 8, 0)
+        );
+        // Weekends only
+        assert_eq!(
+            cron("30 10 * * 6,0").next_after(now),
+            local(2025, 3, 15, 10, 30)
+        );
+        // First of the month, across a year boundary
+        assert_eq!(
+            cron("0 0 1 * *").next_after(local(2025, 12, 5, 0, 0)),
+            local(2026, 1, 1, 0, 0)
+        );
+        // Day of month or weekday, like cron
+        assert_eq!(
+            cron("0 12 20 * 5").next_after(now),
+            local(2025, 3, 14, 12, 0)
+        );
+    }
+
+    // ============================================================
+    // Settings Tests
+    // ============================================================
+
+    #[test]
+    fn test_settings_default_disabled() {
+        let settings = ScheduleSettings::default();
+        assert!(settings.jobs().is_empty());
+        assert!(settings.skip_on_battery);
+        assert!(settings.wait_for_idle);
+    }
+
+    #[test]
+    fn test_settings_roundtrip_and_apply() {
+        let temp_dir = TempDir::new().unwrap();
+        let settings = ScheduleSettings {
+            daily_summary: Some(Schedule::Daily { hour: 7, minute: 0 }),
+            weekly_review: Some(Schedule::Weekly {
+                weekday: 6,
+                hour: 18,
+                minute: 0,
+            }),
+            daily_note_events: Some(Schedule::Daily { hour: 6, minute: 0 }),
+            skip_on_battery: false,
+            wait_for_idle: false,
+        };
+
+        settings.save(temp_dir.path()).unwrap();
+        let loaded = ScheduleSettings::load(temp_dir.path());
+        assert_eq!(loaded, settings);
+
+        let scheduler = Scheduler::new();
+        apply_settings(&scheduler, &loaded);
+        assert_eq!(scheduler.list().len(), 3);
+
+        apply_settings(&scheduler, &ScheduleSettings::default());
+        assert!(scheduler.list().is_empty());
+    }
+}
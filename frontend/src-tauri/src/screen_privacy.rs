@@ -0,0 +1,259 @@
+//! Hiding app windows from screen capture.
+//!
+//! This module provides:
+//! - A global toggle plus per-window overrides, persisted in
+//!   screen-privacy.json
+//! - Content protection applied to every open window and re-checked when a
+//!   window gains focus, so windows opened later are covered too
+//! - A `screen-privacy-changed` event carrying which windows are protected,
+//!   for the UI's indicator
+//!
+//! Protection uses the window's content protection: `NSWindow.sharingType`
+//! on macOS and `SetWindowDisplayAffinity` on Windows. Other platforms have
+//! no equivalent, so windows stay capturable there.
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager, WebviewWindow};
+
+use crate::config::{load_json, save_json_atomic};
+use crate::error::AppError;
+
+/// Screen privacy settings
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScreenPrivacySettings {
+    /// Protect every window without an override
+    pub enabled: bool,
+    /// Per-window choices by label, taking precedence over `enabled`
+    pub windows: BTreeMap<String, bool>,
+}
+
+impl ScreenPrivacySettings {
+    fn path(app_data_dir: &Path) -> PathBuf {
+        app_data_dir.join("screen-privacy.json")
+    }
+
+    pub fn load(app_data_dir: &Path) -> Self {
+        load_json(&Self::path(app_data_dir)).unwrap_or_default()
+    }
+
+    pub fn save(&self, app_data_dir: &Path) -> Result<(), String> {
+        save_json_atomic(&Self::path(app_data_dir), self)
+    }
+
+    /// Whether a window should be hidden from capture
+    pub fn protects(&self, label: &str) -> bool {
+        self.windows.get(label).copied().unwrap_or(self.enabled)
+    }
+}
+
+/// Settings and what was last applied per window, kept in Tauri state
+#[derive(Default)]
+pub struct ScreenPrivacy {
+    settings: Mutex<ScreenPrivacySettings>,
+    applied: Mutex<HashMap<String, bool>>,
+}
+
+/// One window's protection
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WindowPrivacy {
+    pub label: String,
+    pub protected: bool,
+    /// Set per window rather than following the global toggle
+    pub overridden: bool,
+}
+
+/// Screen privacy state, emitted as `screen-privacy-changed`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ScreenPrivacyStatus {
+    pub enabled: bool,
+    /// Whether this platform can hide windows from capture
+    pub supported: bool,
+    pub windows: Vec<WindowPrivacy>,
+}
+
+fn is_supported() -> bool {
+    cfg!(any(target_os = "macos", windows))
+}
+
+/// Apply protection to one window if it changed since last time
+///
+/// Returns whether anything was applied.
+fn apply(app: &AppHandle, window: &WebviewWindow) -> bool {
+    let privacy = app.state::<ScreenPrivacy>();
+    let label = window.label().to_string();
+    let protect = privacy.settings.lock().protects(&label);
+    if privacy.applied.lock().get(&label) == Some(&protect) {
+        return false;
+    }
+    if let Err(e) = window.set_content_protected(protect) {
+        tracing::warn!("Failed to set screen privacy on {}: {}", label, e);
+        return false;
+    }
+    tracing::debug!(
+        "Screen privacy {} for {}",
+        if protect { "on" } else { "off" },
+        label
+    );
+    privacy.applied.lock().insert(label, protect);
+    true
+}
+
+fn status(app: &AppHandle) -> ScreenPrivacyStatus {
+    let privacy = app.state::<ScreenPrivacy>();
+    let settings = privacy.settings.lock().clone();
+    let mut labels: Vec<String> = app.webview_windows().into_keys().collect();
+    labels.sort();
+    ScreenPrivacyStatus {
+        enabled: settings.enabled,
+        supported: is_supported(),
+        windows: labels
+            .into_iter()
+            .map(|label| WindowPrivacy {
+                protected: settings.protects(&label),
+                overridden: settings.windows.contains_key(&label),
+                label,
+            })
+            .collect(),
+    }
+}
+
+/// Apply settings to every open window and announce the result
+fn apply_all(app: &AppHandle) -> ScreenPrivacyStatus {
+    for window in app.webview_windows().values() {
+        apply(app, window);
+    }
+    let status = status(app);
+    crate::events::emit_critical(app, "screen-privacy-changed", &status);
+    status
+}
+
+/// Protect a window that just gained focus, e.g. one opened after launch
+pub fn on_focus(app: &AppHandle, label: &str) {
+    // Focus can arrive before setup has loaded the settings
+    if app.try_state::<ScreenPrivacy>().is_none() {
+        return;
+    }
+    let Some(window) = app.get_webview_window(label) else {
+        return;
+    };
+    if apply(app, &window) {
+        crate::events::emit_critical(app, "screen-privacy-changed", &status(app));
+    }
+}
+
+/// Forget a closed window, so one reopened under its label is protected again
+pub fn on_destroyed(app: &AppHandle, label: &str) {
+    if let Some(privacy) = app.try_state::<ScreenPrivacy>() {
+        privacy.applied.lock().remove(label);
+    }
+}
+
+/// Load settings and protect open windows
+pub fn start(app: &AppHandle) {
+    let mut privacy = ScreenPrivacy::default();
+    if let Ok(app_data_dir) = app.path().app_data_dir() {
+        *privacy.settings.get_mut() = ScreenPrivacySettings::load(&app_data_dir);
+    }
+    app.manage(privacy);
+    apply_all(app);
+}
+
+fn update(
+    app: &AppHandle,
+    change: impl FnOnce(&mut ScreenPrivacySettings),
+) -> Result<ScreenPrivacyStatus, AppError> {
+    let app_data_dir = app.path().app_data_dir()?;
+    let settings = {
+        let privacy = app.state::<ScreenPrivacy>();
+        let mut settings = privacy.settings.lock();
+        change(&mut settings);
+        settings.clone()
+    };
+    settings.save(&app_data_dir)?;
+    Ok(apply_all(app))
+}
+
+// ============================================================
+// Commands
+// ============================================================
+
+/// Get screen privacy state for every window
+#[tauri::command]
+pub async fn get_screen_privacy(app: AppHandle) -> Result<ScreenPrivacyStatus, AppError> {
+    Ok(status(&app))
+}
+
+/// Turn screen privacy on or off for windows without an override
+#[tauri::command]
+pub async fn set_screen_privacy(
+    app: AppHandle,
+    enabled: bool,
+) -> Result<ScreenPrivacyStatus, AppError> {
+    update(&app, |settings| settings.enabled = enabled)
+}
+
+/// Override screen privacy for one window; `None` follows the global toggle
+#[tauri::command]
+pub async fn set_window_screen_privacy(
+    app: AppHandle,
+    label: String,
+    protected: Option<bool>,
+) -> Result<ScreenPrivacyStatus, AppError> {
+    if app.get_webview_window(&label).is_none() {
+        return Err(AppError::NotFound(format!("No window named {}", label)));
+    }
+    update(&app, |settings| match protected {
+        Some(protected) => {
+            settings.windows.insert(label, protected);
+        }
+        None => {
+            settings.windows.remove(&label);
+        }
+    })
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_protects() {
+        let mut settings = ScreenPrivacySettings::default();
+        assert!(!settings.protects("main"));
+
+        settings.enabled = true;
+        settings.windows.insert("quick-capture".to_string(), false);
+        assert!(settings.protects("main"));
+        assert!(!settings.protects("quick-capture"));
+
+        settings.enabled = false;
+        settings.windows.insert("main".to_string(), true);
+        assert!(settings.protects("main"));
+        assert!(!settings.protects("settings"));
+    }
+
+    #[test]
+    fn test_settings_round_trip() {
+        let dir = TempDir::new().unwrap();
+        assert_eq!(
+            ScreenPrivacySettings::load(dir.path()),
+            ScreenPrivacySettings::default()
+        );
+
+        let settings = ScreenPrivacySettings {
+            enabled: true,
+            windows: BTreeMap::from([("main".to_string(), false)]),
+        };
+        settings.save(dir.path()).unwrap();
+        assert_eq!(ScreenPrivacySettings::load(dir.path()), settings);
+    }
+}
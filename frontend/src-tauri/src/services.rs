@@ -0,0 +1,790 @@
+//! Service orchestration actor.
+//!
+//! This module provides:
+//! - A single task that starts, restarts and stops PostgreSQL and the backend
+//! - Commands sent over an mpsc channel and handled one at a time
+//! - Ownership of the backend process handle, with its exit awaited so an
+//!   unexpected crash is noticed immediately
+//! - Service state broadcast over a watch channel and as `service-state` events
+//! - Supervision of the backend: an unexpected exit is restarted with
+//!   exponential backoff, following the restart policy in the service
+//!   settings, until it crashes `max_crashes` times within the policy's
+//!   window, reported as `BackendCrashed`/`BackendRestarted` startup events
+//! - Graceful backend stops: the process is asked to exit (SIGTERM, or
+//!   CTRL_BREAK on Windows) and only killed once the configured grace period
+//!   runs out, with the outcome recorded in the startup metrics
+//! - Shutdown through `shutdown::ShutdownPlan`, one budgeted step at a time
+//!
+//! Startup, the restart commands, tray items and shutdown all go through
+//! the actor, so a restart can never interleave with startup or shutdown.
+//!
+//! With `--mock-backend` the actor runs `mock_backend`'s fixture server in
+//! place of both PostgreSQL and the backend process.
+
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::process::Child;
+use tokio::sync::{mpsc, oneshot, watch};
+
+use crate::config::{self, RestartPolicy};
+use crate::error::AppError;
+use crate::notifications::{Notice, Severity};
+use crate::shutdown::{self, ShutdownPlan, ShutdownStep};
+use crate::startup::{ExponentialBackoff, ShutdownMode, StartupConfig, StartupEvent};
+use crate::AppState;
+
+/// How long a blocking shutdown waits for the actor beyond the shutdown
+/// budget before cleaning up directly
+const SHUTDOWN_MARGIN: Duration = Duration::from_secs(2);
+
+/// How long to wait for a killed backend to exit
+const KILL_WAIT: Duration = Duration::from_secs(5);
+
+/// A request to the service manager
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ServiceCommand {
+    /// Start PostgreSQL and the backend
+    StartAll,
+    /// Restart the backend, leaving PostgreSQL running
+    RestartBackend,
+    /// Restart PostgreSQL and then the backend
+    RestartDatabase,
+    /// Stop the backend, leaving PostgreSQL running for maintenance such
+    /// as restores
+    StopBackend,
+    /// Stop both services
+    Shutdown,
+}
+
+/// Lifecycle phase of one service
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ServicePhase {
+    Stopped,
+    Starting,
+    Running,
+    Stopping,
+    Failed,
+}
+
+/// Snapshot of both services, broadcast on every change
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ServiceState {
+    pub postgres: ServicePhase,
+    pub backend: ServicePhase,
+    /// Command currently being handled
+    pub busy: Option<ServiceCommand>,
+    pub last_error: Option<AppError>,
+}
+
+impl Default for ServiceState {
+    fn default() -> Self {
+        Self {
+            postgres: ServicePhase::Stopped,
+            backend: ServicePhase::Stopped,
+            busy: None,
+            last_error: None,
+        }
+    }
+}
+
+struct Request {
+    command: ServiceCommand,
+    reply: Option<oneshot::Sender<Result<(), AppError>>>,
+}
+
+/// Handle to the service actor, kept in Tauri state
+pub struct ServiceManager {
+    tx: mpsc::UnboundedSender<Request>,
+    state: watch::Receiver<ServiceState>,
+}
+
+impl ServiceManager {
+    /// Spawn the actor task
+    pub fn spawn(app: &AppHandle) -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let (state_tx, state) = watch::channel(ServiceState::default());
+        let actor = Actor {
+            app: app.clone(),
+            backend: None,
+            state: state_tx,
+            supervisor: Supervisor::default(),
+            restart_at: None,
+        };
+        tauri::async_runtime::spawn(actor.run(rx));
+        Self { tx, state }
+    }
+
+    /// Send a command and wait until it has been handled
+    pub async fn send(&self, command: ServiceCommand) -> Result<(), AppError> {
+        let (reply, response) = oneshot::channel();
+        self.tx
+            .send(Request {
+                command,
+                reply: Some(reply),
+            })
+            .map_err(|_| AppError::NotReady("Service manager is not running".to_string()))?;
+        response.await.map_err(|_| {
+            AppError::Internal("Service manager stopped before replying".to_string())
+        })?
+    }
+
+    /// Queue a command without waiting; failures are logged
+    pub fn dispatch(&self, command: ServiceCommand) {
+        if self
+            .tx
+            .send(Request {
+                command,
+                reply: None,
+            })
+            .is_err()
+        {
+            tracing::error!("Service manager is not running, dropped {:?}", command);
+        }
+    }
+
+    /// Stop services from a synchronous context such as the event loop
+    ///
+    /// If the actor doesn't finish within the shutdown budget, e.g. because
+    /// it's busy with a slow startup, services are stopped directly so
+    /// quitting never hangs.
+    pub fn shutdown_blocking(&self, app: &AppHandle) {
+        let settings = config::current_settings(&app.state::<AppState>());
+        let wait = shutdown::budget(&settings) + SHUTDOWN_MARGIN;
+        let result = tauri::async_runtime::block_on(tokio::time::timeout(
+            wait,
+            self.send(ServiceCommand::Shutdown),
+        ));
+        match result {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => {
+                tracing::warn!("Service shutdown failed: {}", e);
+                crate::stop_services(app);
+            }
+            Err(_) => {
+                tracing::warn!("Service manager busy, stopping services directly");
+                crate::stop_services(app);
+            }
+        }
+    }
+
+    /// Current service state
+    pub fn state(&self) -> ServiceState {
+        self.state.borrow().clone()
+    }
+
+    /// Receiver notified on every state change
+    pub fn subscribe(&self) -> watch::Receiver<ServiceState> {
+        self.state.clone()
+    }
+}
+
+/// What is serving the backend API
+enum Backend {
+    Process(Child),
+    /// The fixture server from `mock_backend`
+    Mock(tauri::async_runtime::JoinHandle<()>),
+}
+
+/// Crash history of the backend, deciding whether and when to restart it
+struct Supervisor {
+    crashes: VecDeque<Instant>,
+    backoff: ExponentialBackoff,
+    policy: RestartPolicy,
+}
+
+impl Default for Supervisor {
+    fn default() -> Self {
+        Self {
+            crashes: VecDeque::new(),
+            backoff: ExponentialBackoff::new(StartupConfig::default()),
+            policy: RestartPolicy::default(),
+        }
+    }
+}
+
+impl Supervisor {
+    /// Record a crash; returns the delay before restarting, or None in a
+    /// crash loop or when restarts are turned off
+    fn on_crash(&mut self, now: Instant) -> Option<Duration> {
+        let window = self.policy.window();
+        self.crashes
+            .retain(|crash| now.duration_since(*crash) < window);
+        if self.crashes.is_empty() {
+            // Stable since the last crash, so start the backoff over
+            self.backoff.reset();
+        }
+        self.crashes.push_back(now);
+        if !self.policy.enabled || self.crashes.len() >= self.policy.max_crashes as usize {
+            return None;
+        }
+        self.backoff.next_delay()
+    }
+
+    fn recent_crashes(&self) -> u32 {
+        self.crashes.len() as u32
+    }
+
+    /// Forget crashes, e.g. after the user restarts services themselves
+    fn reset(&mut self) {
+        self.crashes.clear();
+        self.backoff.reset();
+    }
+}
+
+struct Actor {
+    app: AppHandle,
+    backend: Option<Backend>,
+    state: watch::Sender<ServiceState>,
+    supervisor: Supervisor,
+    /// When the crashed backend is due to be restarted
+    restart_at: Option<tokio::time::Instant>,
+}
+
+impl Actor {
+    async fn run(mut self, mut rx: mpsc::UnboundedReceiver<Request>) {
+        loop {
+            let request = tokio::select! {
+                request = rx.recv() => match request {
+                    Some(request) => request,
+                    None => break,
+                },
+                status = backend_exit(&mut self.backend) => {
+                    self.backend = None;
+                    self.on_backend_exit(status);
+                    continue;
+                }
+                _ = restart_due(self.restart_at) => {
+                    self.restart_at = None;
+                    self.restart_after_crash().await;
+                    continue;
+                }
+            };
+            // Any command supersedes a pending restart, and one asking for
+            // services to run starts the crash count over
+            self.restart_at = None;
+            if matches!(
+                request.command,
+                ServiceCommand::StartAll
+                    | ServiceCommand::RestartBackend
+                    | ServiceCommand::RestartDatabase
+            ) {
+                self.supervisor.reset();
+            }
+            self.update(|s| {
+                s.busy = Some(request.command);
+                s.last_error = None;
+            });
+            let result = self.handle(request.command).await;
+            if let Err(ref e) = result {
+                tracing::error!("Service command {:?} failed: {}", request.command, e);
+            }
+            self.update(|s| {
+                s.busy = None;
+                s.last_error = result.as_ref().err().cloned();
+            });
+            if let Some(reply) = request.reply {
+                let _ = reply.send(result);
+            }
+        }
+    }
+
+    #[tracing::instrument(name = "service_command", skip(self))]
+    async fn handle(&mut self, command: ServiceCommand) -> Result<(), AppError> {
+        match command {
+            ServiceCommand::StartAll => {
+                if self.backend.is_some() {
+                    return Ok(());
+                }
+                self.start_all().await
+            }
+            ServiceCommand::RestartBackend => {
+                self.stop_backend().await;
+                if self.is_mock() {
+                    return self.start_mock().await;
+                }
+                self.update(|s| s.backend = ServicePhase::Starting);
+                match crate::start_backend_internal(&self.app).await {
+                    Ok(child) => {
+                        self.backend = Some(Backend::Process(child));
+                        self.update(|s| s.backend = ServicePhase::Running);
+                        Ok(())
+                    }
+                    Err(e) => {
+                        self.update(|s| s.backend = ServicePhase::Failed);
+                        Err(e)
+                    }
+                }
+            }
+            ServiceCommand::RestartDatabase => {
+                self.stop_backend().await;
+                self.update(|s| s.postgres = ServicePhase::Stopping);
+                let state = self.app.state::<AppState>();
+                let manager = state.postgres_manager.read().clone();
+                if let Some(manager) = manager {
+                    tokio::task::spawn_blocking(move || manager.stop())
+                        .await?
+                        .map_err(AppError::Database)?;
+                }
+                *state.is_postgres_ready.write() = false;
+                self.update(|s| s.postgres = ServicePhase::Stopped);
+                self.start_all().await
+            }
+            ServiceCommand::StopBackend => {
+                self.update(|s| s.backend = ServicePhase::Stopping);
+                self.stop_backend().await;
+                Ok(())
+            }
+            ServiceCommand::Shutdown => {
+                self.shutdown().await;
+                Ok(())
+            }
+        }
+    }
+
+    fn is_mock(&self) -> bool {
+        crate::cli::options(&self.app).mock_backend
+    }
+
+    async fn start_all(&mut self) -> Result<(), AppError> {
+        if self.is_mock() {
+            return self.start_mock().await;
+        }
+        self.update(|s| {
+            s.postgres = ServicePhase::Starting;
+            s.backend = ServicePhase::Starting;
+        });
+        let result = crate::start_services_internal(&self.app).await;
+        crate::record_startup(&self.app);
+        let postgres_ready = *self.app.state::<AppState>().is_postgres_ready.read();
+        match result {
+            Ok(child) => {
+                self.backend = Some(Backend::Process(child));
+                self.update(|s| {
+                    s.postgres = ServicePhase::Running;
+                    s.backend = ServicePhase::Running;
+                });
+                Ok(())
+            }
+            Err(e) => {
+                self.update(|s| {
+                    if postgres_ready {
+                        s.postgres = ServicePhase::Running;
+                        s.backend = ServicePhase::Failed;
+                    } else {
+                        s.postgres = ServicePhase::Failed;
+                        s.backend = ServicePhase::Stopped;
+                    }
+                });
+                Err(e)
+            }
+        }
+    }
+
+    /// Serve fixtures in place of PostgreSQL and the backend
+    async fn start_mock(&mut self) -> Result<(), AppError> {
+        self.update(|s| {
+            s.postgres = ServicePhase::Starting;
+            s.backend = ServicePhase::Starting;
+        });
+        match crate::mock_backend::start(&self.app).await {
+            Ok(task) => {
+                self.backend = Some(Backend::Mock(task));
+                self.update(|s| {
+                    s.postgres = ServicePhase::Running;
+                    s.backend = ServicePhase::Running;
+                });
+                Ok(())
+            }
+            Err(e) => {
+                self.update(|s| {
+                    s.postgres = ServicePhase::Stopped;
+                    s.backend = ServicePhase::Failed;
+                });
+                Err(e)
+            }
+        }
+    }
+
+    fn on_backend_exit(&mut self, status: std::io::Result<std::process::ExitStatus>) {
+        let exit_code = match status {
+            Ok(status) => {
+                tracing::error!("Backend exited unexpectedly: {}", status);
+                status.code()
+            }
+            Err(e) => {
+                tracing::error!("Failed to wait for backend: {}", e);
+                None
+            }
+        };
+        *self.app.state::<AppState>().is_backend_ready.write() = false;
+        self.update(|s| s.backend = ServicePhase::Failed);
+        let _ = self.app.emit("backend-terminated", ());
+        self.schedule_restart(exit_code);
+    }
+
+    /// Restart a crashed backend after the backoff delay, unless it is
+    /// crashing in a loop
+    fn schedule_restart(&mut self, exit_code: Option<i32>) {
+        let policy = config::current_settings(&self.app.state::<AppState>()).auto_restart;
+        self.supervisor.policy = policy;
+        let delay = self.supervisor.on_crash(Instant::now());
+        let recent_crashes = self.supervisor.recent_crashes();
+        StartupEvent::BackendCrashed {
+            exit_code,
+            recent_crashes,
+            restart_in_ms: delay.map(|delay| delay.as_millis() as u64),
+        }
+        .emit(&self.app);
+
+        match delay {
+            Some(delay) => {
+                tracing::info!(
+                    "Restarting the backend in {:?} ({} recent crashes)",
+                    delay,
+                    recent_crashes
+                );
+                self.restart_at = Some(tokio::time::Instant::now() + delay);
+            }
+            None if !policy.enabled => {
+                tracing::error!("Backend crashed and automatic restarts are turned off");
+                crate::notifications::notify(
+                    &self.app,
+                    Notice::new(
+                        "services",
+                        Severity::Error,
+                        "Second Brain's backend stopped unexpectedly",
+                        "Automatic restarts are turned off. Restart it from the tray menu; \
+                         the log has details.",
+                    ),
+                );
+            }
+            None => {
+                tracing::error!(
+                    "Backend crashed {} times within {:?}, not restarting it",
+                    recent_crashes,
+                    policy.window()
+                );
+                crate::notifications::notify(
+                    &self.app,
+                    Notice::new(
+                        "services",
+                        Severity::Error,
+                        "Second Brain's backend keeps crashing",
+                        "It has been left stopped. Restart it from the tray menu once \
+                         the cause is fixed; the log has details.",
+                    ),
+                );
+            }
+        }
+    }
+
+    async fn restart_after_crash(&mut self) {
+        self.update(|s| {
+            s.busy = Some(ServiceCommand::RestartBackend);
+            s.backend = ServicePhase::Starting;
+        });
+        let result = crate::start_backend_internal(&self.app).await;
+        let attempt = self.supervisor.recent_crashes();
+        match result {
+            Ok(child) => {
+                self.backend = Some(Backend::Process(child));
+                self.update(|s| {
+                    s.busy = None;
+                    s.backend = ServicePhase::Running;
+                    s.last_error = None;
+                });
+                let port = *self.app.state::<AppState>().backend_port.read();
+                tracing::info!("Backend restarted after a crash on port {}", port);
+                StartupEvent::BackendRestarted { port, attempt }.emit(&self.app);
+            }
+            Err(e) => {
+                tracing::error!("Failed to restart the backend after a crash: {}", e);
+                self.update(|s| {
+                    s.busy = None;
+                    s.backend = ServicePhase::Failed;
+                    s.last_error = Some(e);
+                });
+                self.schedule_restart(None);
+            }
+        }
+    }
+
+    /// Run the shutdown plan; quitting asks more than once, so a second
+    /// request finds nothing to do
+    async fn shutdown(&mut self) {
+        let current = self.state.borrow().clone();
+        if self.backend.is_none()
+            && current.backend == ServicePhase::Stopped
+            && current.postgres == ServicePhase::Stopped
+        {
+            return;
+        }
+        self.update(|s| {
+            s.backend = ServicePhase::Stopping;
+            s.postgres = ServicePhase::Stopping;
+        });
+
+        let settings = config::current_settings(&self.app.state::<AppState>());
+        let plan = ShutdownPlan::new(&self.app, settings);
+        plan.step(
+            ShutdownStep::FlushQueue,
+            crate::write_queue::flush(&self.app),
+        )
+        .await;
+        if plan
+            .step(ShutdownStep::StopBackend, self.stop_backend())
+            .await
+            .is_none()
+        {
+            // Dropping the handle kills the process; the sweep catches it
+            // if it lingers
+            *self.app.state::<AppState>().is_backend_ready.write() = false;
+            self.update(|s| s.backend = ServicePhase::Stopped);
+        }
+        let app = self.app.clone();
+        plan.step(
+            ShutdownStep::StopPostgres,
+            tokio::task::spawn_blocking(move || {
+                crate::stop_postgres(&app, shutdown::POSTGRES_STOP)
+            }),
+        )
+        .await;
+        let app = self.app.clone();
+        plan.step(
+            ShutdownStep::PortSweep,
+            tokio::task::spawn_blocking(move || crate::sweep_ports(&app)),
+        )
+        .await;
+        plan.finish();
+
+        self.update(|s| {
+            s.backend = ServicePhase::Stopped;
+            s.postgres = ServicePhase::Stopped;
+        });
+    }
+
+    async fn stop_backend(&mut self) {
+        match self.backend.take() {
+            Some(Backend::Process(mut child)) => {
+                let state = self.app.state::<AppState>();
+                let grace = shutdown_grace(&state);
+                let started = Instant::now();
+                let mode = terminate(&mut child, grace).await;
+                tracing::info!(?mode, "Backend stopped");
+                state
+                    .startup_metrics
+                    .lock()
+                    .mark_shutdown(mode, started.elapsed());
+            }
+            Some(Backend::Mock(task)) => task.abort(),
+            None => {}
+        }
+        *self.app.state::<AppState>().is_backend_ready.write() = false;
+        self.update(|s| s.backend = ServicePhase::Stopped);
+    }
+
+    fn update(&self, change: impl FnOnce(&mut ServiceState)) {
+        let mut next = self.state.borrow().clone();
+        change(&mut next);
+        if self.state.send_if_modified(|current| {
+            if *current == next {
+                return false;
+            }
+            *current = next.clone();
+            true
+        }) {
+            let _ = self.app.emit("service-state", &next);
+        }
+    }
+}
+
+/// Grace period from the service settings, or the default before they're loaded
+pub(crate) fn shutdown_grace(state: &AppState) -> Duration {
+    config::current_settings(state).shutdown_grace()
+}
+
+/// Ask a process to exit cleanly; false if the request couldn't be sent
+fn request_exit(child: &Child) -> bool {
+    let Some(pid) = child.id() else {
+        return false;
+    };
+
+    #[cfg(unix)]
+    {
+        // SAFETY: kill only sends a signal to the process we spawned
+        unsafe { libc::kill(pid as libc::pid_t, libc::SIGTERM) == 0 }
+    }
+
+    #[cfg(windows)]
+    {
+        use windows::Win32::System::Console::{GenerateConsoleCtrlEvent, CTRL_BREAK_EVENT};
+        // The backend is started in its own process group, whose id is its pid
+        unsafe { GenerateConsoleCtrlEvent(CTRL_BREAK_EVENT, pid).is_ok() }
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    {
+        let _ = pid;
+        false
+    }
+}
+
+/// Stop a process, giving it `grace` to exit before it's killed
+///
+/// Killing straight away can cut off the backend's in-flight writes, so it
+/// is asked to exit first and only killed if it doesn't in time.
+pub(crate) async fn terminate(child: &mut Child, grace: Duration) -> ShutdownMode {
+    if matches!(child.try_wait(), Ok(Some(_))) {
+        return ShutdownMode::AlreadyExited;
+    }
+    if request_exit(child) {
+        match tokio::time::timeout(grace, child.wait()).await {
+            Ok(_) => return ShutdownMode::Graceful,
+            Err(_) => tracing::warn!("Backend did not exit within {:?}, killing it", grace),
+        }
+    } else {
+        tracing::warn!("Couldn't ask the backend to exit, killing it");
+    }
+    if tokio::time::timeout(KILL_WAIT, child.kill()).await.is_err() {
+        tracing::warn!(
+            "Backend did not exit within {:?} of being killed",
+            KILL_WAIT
+        );
+    }
+    ShutdownMode::Forced
+}
+
+/// Resolve when the backend process exits; never resolves while none is
+/// running or the mock is serving
+async fn backend_exit(backend: &mut Option<Backend>) -> std::io::Result<std::process::ExitStatus> {
+    match backend {
+        Some(Backend::Process(child)) => child.wait().await,
+        _ => std::future::pending().await,
+    }
+}
+
+/// Resolve at the scheduled restart; never resolves when none is scheduled
+async fn restart_due(at: Option<tokio::time::Instant>) {
+    match at {
+        Some(at) => tokio::time::sleep_until(at).await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Get the current state of PostgreSQL and the backend
+#[tauri::command]
+pub async fn get_service_state(app: AppHandle) -> Result<ServiceState, AppError> {
+    Ok(app.state::<ServiceManager>().state())
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_service_state_serialization() {
+        let state = ServiceState {
+            busy: Some(ServiceCommand::RestartBackend),
+            backend: ServicePhase::Starting,
+            ..ServiceState::default()
+        };
+        let json = serde_json::to_value(&state).unwrap();
+        assert_eq!(json["postgres"], "stopped");
+        assert_eq!(json["backend"], "starting");
+        assert_eq!(json["busy"], "restart_backend");
+        assert!(json["last_error"].is_null());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_terminate() {
+        let spawn = |script: &str| {
+            tokio::process::Command::new("sh")
+                .args(["-c", script])
+                .kill_on_drop(true)
+                .spawn()
+                .unwrap()
+        };
+        let grace = Duration::from_secs(5);
+
+        let mut child = spawn("exec sleep 30");
+        assert_eq!(terminate(&mut child, grace).await, ShutdownMode::Graceful);
+
+        let mut child = spawn("trap '' TERM; while :; do sleep 1; done");
+        // Let the shell install its trap before it's signalled
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(
+            terminate(&mut child, Duration::from_millis(200)).await,
+            ShutdownMode::Forced
+        );
+
+        let mut child = spawn("exit 0");
+        child.wait().await.unwrap();
+        assert_eq!(
+            terminate(&mut child, grace).await,
+            ShutdownMode::AlreadyExited
+        );
+    }
+
+    #[test]
+    fn test_supervisor_backoff_and_crash_loop() {
+        let mut supervisor = Supervisor::default();
+        let start = Instant::now();
+
+        let first = supervisor.on_crash(start).unwrap();
+        let second = supervisor.on_crash(start + Duration::from_secs(1)).unwrap();
+        assert!(second > first);
+
+        let limit = RestartPolicy::default().max_crashes;
+        for i in 2..limit - 1 {
+            assert!(supervisor
+                .on_crash(start + Duration::from_secs(i as u64))
+                .is_some());
+        }
+        assert_eq!(supervisor.on_crash(start + Duration::from_secs(10)), None);
+        assert_eq!(supervisor.recent_crashes(), limit);
+    }
+
+    #[test]
+    fn test_supervisor_follows_policy() {
+        let mut supervisor = Supervisor {
+            policy: RestartPolicy {
+                enabled: false,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert_eq!(supervisor.on_crash(Instant::now()), None);
+
+        supervisor.reset();
+        supervisor.policy = RestartPolicy {
+            enabled: true,
+            max_crashes: 2,
+            window_secs: 60,
+        };
+        let start = Instant::now();
+        assert!(supervisor.on_crash(start).is_some());
+        assert_eq!(supervisor.on_crash(start + Duration::from_secs(1)), None);
+    }
+
+    #[test]
+    fn test_supervisor_forgets_old_crashes() {
+        let mut supervisor = Supervisor::default();
+        let start = Instant::now();
+        let first = supervisor.on_crash(start).unwrap();
+        supervisor.on_crash(start + Duration::from_secs(1));
+
+        // A crash after a stable spell starts the backoff over
+        let later = start + RestartPolicy::default().window() + Duration::from_secs(2);
+        assert_eq!(supervisor.on_crash(later), Some(first));
+        assert_eq!(supervisor.recent_crashes(), 1);
+    }
+}
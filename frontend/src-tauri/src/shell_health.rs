@@ -0,0 +1,326 @@
+//! Health of the desktop shell itself, apart from the backend and database.
+//!
+//! This module provides:
+//! - A heartbeat measuring how late the async runtime wakes timers, and
+//!   sampling whether the shell's shared locks are held
+//! - `get_shell_health`, combining uptime, runtime lag, main-thread
+//!   response time, scheduler job statuses, lock contention and the
+//!   child processes the shell has running
+//!
+//! When "the app feels frozen", a slow main thread or a busy runtime points
+//! at the shell; a responsive shell points at the backend instead.
+
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::{BTreeMap, VecDeque};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+
+use crate::error::AppError;
+use crate::scheduler::{JobStatus, Scheduler};
+use crate::AppState;
+
+/// Time between heartbeats
+const HEARTBEAT_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Heartbeats kept (five minutes)
+const MAX_SAMPLES: usize = 600;
+
+/// How long the main thread gets to answer before it counts as blocked
+const MAIN_THREAD_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Heartbeat history, kept in Tauri state
+pub struct ShellHealth {
+    started: Instant,
+    /// Milliseconds each heartbeat woke late
+    lags: Mutex<VecDeque<u64>>,
+    locks: Mutex<BTreeMap<&'static str, LockStats>>,
+}
+
+impl Default for ShellHealth {
+    fn default() -> Self {
+        Self {
+            started: Instant::now(),
+            lags: Mutex::new(VecDeque::with_capacity(MAX_SAMPLES)),
+            locks: Mutex::new(BTreeMap::new()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct LockStats {
+    samples: u64,
+    held: u64,
+}
+
+impl ShellHealth {
+    fn record_lag(&self, lag_ms: u64) {
+        let mut lags = self.lags.lock();
+        if lags.len() == MAX_SAMPLES {
+            lags.pop_front();
+        }
+        lags.push_back(lag_ms);
+    }
+
+    fn record_locks(&self, probes: &[(&'static str, bool)]) {
+        let mut locks = self.locks.lock();
+        for (name, held) in probes {
+            let stats = locks.entry(*name).or_default();
+            stats.samples += 1;
+            stats.held += u64::from(*held);
+        }
+    }
+
+    fn runtime_lag(&self) -> RuntimeLag {
+        let lags = self.lags.lock();
+        let samples = lags.len();
+        RuntimeLag {
+            samples,
+            current_ms: lags.back().copied().unwrap_or(0),
+            average_ms: if samples == 0 {
+                0
+            } else {
+                lags.iter().sum::<u64>() / samples as u64
+            },
+            max_ms: lags.iter().copied().max().unwrap_or(0),
+        }
+    }
+}
+
+/// How late the async runtime has been waking timers
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RuntimeLag {
+    pub samples: usize,
+    pub current_ms: u64,
+    pub average_ms: u64,
+    pub max_ms: u64,
+}
+
+/// How often a shared lock was found held
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LockContention {
+    pub name: &'static str,
+    pub held_now: bool,
+    pub samples: u64,
+    /// Fraction of samples that found the lock held
+    pub held_ratio: f64,
+}
+
+/// A process the shell started that is still running
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ChildProcess {
+    pub pid: u32,
+    pub name: String,
+}
+
+/// Everything `get_shell_health` reports
+#[derive(Debug, Clone, Serialize)]
+pub struct ShellHealthReport {
+    pub uptime_secs: u64,
+    pub runtime_lag: RuntimeLag,
+    /// Round trip through the main thread's event loop; None if it didn't
+    /// answer within two seconds
+    pub main_thread_ms: Option<u64>,
+    pub jobs: Vec<JobStatus>,
+    pub locks: Vec<LockContention>,
+    pub children: Vec<ChildProcess>,
+}
+
+/// Whether each shared lock is held right now
+fn probe_locks(app: &AppHandle) -> Vec<(&'static str, bool)> {
+    let state = app.state::<AppState>();
+    vec![
+        ("startup_metrics", state.startup_metrics.is_locked()),
+        (
+            "postgres_manager",
+            state.postgres_manager.is_locked_exclusive(),
+        ),
+        ("service_config", state.service_config.is_locked_exclusive()),
+        ("ai_cache", state.ai_cache.is_locked_exclusive()),
+        ("backend_auth", state.backend_auth.is_locked_exclusive()),
+        ("scheduler", app.state::<Scheduler>().is_locked()),
+    ]
+}
+
+/// Parse `ps -A -o pid=,ppid=,comm=` into processes whose parent is `parent`
+#[cfg_attr(not(unix), allow(dead_code))]
+fn parse_ps_output(output: &str, parent: u32) -> Vec<ChildProcess> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let pid = fields.next()?.parse().ok()?;
+            let ppid: u32 = fields.next()?.parse().ok()?;
+            let command = fields.collect::<Vec<_>>().join(" ");
+            (ppid == parent && !command.is_empty()).then(|| ChildProcess {
+                pid,
+                name: command.rsplit('/').next().unwrap_or(&command).to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Processes started by the shell that are still running
+fn child_processes() -> Vec<ChildProcess> {
+    let parent = std::process::id();
+
+    #[cfg(unix)]
+    {
+        std::process::Command::new("ps")
+            .args(["-A", "-o", "pid=,ppid=,comm="])
+            .output()
+            .ok()
+            .map(|o| parse_ps_output(&String::from_utf8_lossy(&o.stdout), parent))
+            .unwrap_or_default()
+    }
+
+    #[cfg(windows)]
+    {
+        let filter = format!(
+            "Get-CimInstance Win32_Process -Filter 'ParentProcessId={}' | \
+             ForEach-Object {{ \"$($_.ProcessId) {} $($_.Name)\" }}",
+            parent, parent
+        );
+        std::process::Command::new("powershell")
+            .args(["-NoProfile", "-Command", &filter])
+            .output()
+            .ok()
+            .map(|o| parse_ps_output(&String::from_utf8_lossy(&o.stdout), parent))
+            .unwrap_or_default()
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    {
+        let _ = parent;
+        Vec::new()
+    }
+}
+
+/// Time a round trip through the main thread
+async fn main_thread_latency(app: &AppHandle) -> Option<u64> {
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    let sent = Instant::now();
+    app.run_on_main_thread(move || {
+        let _ = tx.send(sent.elapsed());
+    })
+    .ok()?;
+    tokio::time::timeout(MAIN_THREAD_TIMEOUT, rx)
+        .await
+        .ok()?
+        .ok()
+        .map(|elapsed| elapsed.as_millis() as u64)
+}
+
+/// Start the heartbeat
+pub fn start(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let before = Instant::now();
+            tokio::time::sleep(HEARTBEAT_INTERVAL).await;
+            let lag = before.elapsed().saturating_sub(HEARTBEAT_INTERVAL);
+            let health = app.state::<ShellHealth>();
+            health.record_lag(lag.as_millis() as u64);
+            health.record_locks(&probe_locks(&app));
+        }
+    });
+}
+
+// ============================================================
+// Commands
+// ============================================================
+
+/// Get the shell's own health, to tell shell problems from backend ones
+#[tauri::command]
+pub async fn get_shell_health(app: AppHandle) -> Result<ShellHealthReport, AppError> {
+    let main_thread_ms = main_thread_latency(&app).await;
+    let children = tauri::async_runtime::spawn_blocking(child_processes).await?;
+
+    let health = app.state::<ShellHealth>();
+    let held_now: BTreeMap<_, _> = probe_locks(&app).into_iter().collect();
+    let locks = health
+        .locks
+        .lock()
+        .iter()
+        .map(|(name, stats)| LockContention {
+            name: *name,
+            held_now: held_now.get(name).copied().unwrap_or(false),
+            samples: stats.samples,
+            held_ratio: stats.held as f64 / stats.samples.max(1) as f64,
+        })
+        .collect();
+
+    Ok(ShellHealthReport {
+        uptime_secs: health.started.elapsed().as_secs(),
+        runtime_lag: health.runtime_lag(),
+        main_thread_ms,
+        jobs: app.state::<Scheduler>().list(),
+        locks,
+        children,
+    })
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ps_output() {
+        let output = "    1     0 /sbin/launchd\n  512   100 /usr/local/bin/postgres\n  \
+                      513   100 SecondBrain.API\n  514   512 postgres: checkpointer\n";
+        assert_eq!(
+            parse_ps_output(output, 100),
+            vec![
+                ChildProcess {
+                    pid: 512,
+                    name: "postgres".to_string()
+                },
+                ChildProcess {
+                    pid: 513,
+                    name: "SecondBrain.API".to_string()
+                },
+            ]
+        );
+        assert!(parse_ps_output("garbage\n", 100).is_empty());
+    }
+
+    #[test]
+    fn test_runtime_lag() {
+        let health = ShellHealth::default();
+        assert_eq!(health.runtime_lag().samples, 0);
+
+        for lag in [2, 4, 30] {
+            health.record_lag(lag);
+        }
+        let lag = health.runtime_lag();
+        assert_eq!(lag.current_ms, 30);
+        assert_eq!(lag.average_ms, 12);
+        assert_eq!(lag.max_ms, 30);
+
+        for _ in 0..MAX_SAMPLES {
+            health.record_lag(1);
+        }
+        assert_eq!(health.runtime_lag().samples, MAX_SAMPLES);
+        assert_eq!(health.runtime_lag().max_ms, 1);
+    }
+
+    #[test]
+    fn test_lock_stats() {
+        let health = ShellHealth::default();
+        health.record_locks(&[("scheduler", true), ("ai_cache", false)]);
+        health.record_locks(&[("scheduler", false), ("ai_cache", false)]);
+        let locks = health.locks.lock();
+        assert_eq!(
+            locks["scheduler"],
+            LockStats {
+                samples: 2,
+                held: 1
+            }
+        );
+        assert_eq!(locks["ai_cache"].held, 0);
+    }
+}
@@ -0,0 +1,231 @@
+//! Ordered, time-budgeted service shutdown.
+//!
+//! This module provides:
+//! - The steps quitting goes through, in order: flush the write queue while
+//!   the backend can still take it, stop the backend gracefully, stop
+//!   PostgreSQL, then sweep both ports for leftover processes
+//! - `ShutdownPlan`, which runs each step within its own cap and what's left
+//!   of the overall budget, so a stuck step is abandoned rather than hanging
+//!   the app
+//! - A log line and a `shutdown-progress` event as each step starts and ends
+//!
+//! The service actor runs the plan; `ServiceManager::shutdown_blocking`
+//! waits for it a little longer than the budget before stopping services
+//! directly.
+
+use serde::Serialize;
+use std::future::Future;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+use crate::config::ServiceSettings;
+
+/// Event emitted as shutdown progresses
+pub const EVENT: &str = "shutdown-progress";
+
+/// Longest spent replaying queued writes before the backend stops
+const FLUSH_CAP: Duration = Duration::from_secs(3);
+
+/// Time allowed on top of the grace period for a killed backend to exit
+const BACKEND_KILL_CAP: Duration = Duration::from_secs(5);
+
+/// Longest PostgreSQL gets for a fast shutdown before an immediate one
+pub const POSTGRES_STOP: Duration = Duration::from_secs(15);
+
+/// Time allowed on top of `POSTGRES_STOP` for the immediate shutdown
+const POSTGRES_IMMEDIATE_CAP: Duration = Duration::from_secs(6);
+
+/// Longest spent killing leftover processes on the service ports
+const SWEEP_CAP: Duration = Duration::from_secs(3);
+
+/// A step of the shutdown plan
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShutdownStep {
+    FlushQueue,
+    StopBackend,
+    StopPostgres,
+    PortSweep,
+}
+
+impl ShutdownStep {
+    pub const ALL: [ShutdownStep; 4] = [
+        ShutdownStep::FlushQueue,
+        ShutdownStep::StopBackend,
+        ShutdownStep::StopPostgres,
+        ShutdownStep::PortSweep,
+    ];
+
+    /// Longest the step may take
+    fn cap(self, settings: &ServiceSettings) -> Duration {
+        match self {
+            Self::FlushQueue => FLUSH_CAP,
+            Self::StopBackend => settings.shutdown_grace() + BACKEND_KILL_CAP,
+            Self::StopPostgres => POSTGRES_STOP + POSTGRES_IMMEDIATE_CAP,
+            Self::PortSweep => SWEEP_CAP,
+        }
+    }
+}
+
+/// Overall time allowed for shutting down with these settings
+pub fn budget(settings: &ServiceSettings) -> Duration {
+    ShutdownStep::ALL
+        .iter()
+        .map(|step| step.cap(settings))
+        .sum()
+}
+
+/// How a step went
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StepStatus {
+    Started,
+    Done,
+    /// Abandoned when its time ran out
+    TimedOut,
+}
+
+/// Payload of `shutdown-progress`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ShutdownProgress {
+    pub step: ShutdownStep,
+    pub status: StepStatus,
+    /// 1-based position of the step in the plan
+    pub index: usize,
+    pub total: usize,
+    /// Time since shutdown began
+    pub elapsed_ms: u64,
+    pub budget_ms: u64,
+}
+
+/// Runs the shutdown steps against a shared budget
+pub struct ShutdownPlan {
+    app: AppHandle,
+    settings: ServiceSettings,
+    started: Instant,
+    budget: Duration,
+}
+
+impl ShutdownPlan {
+    pub fn new(app: &AppHandle, settings: ServiceSettings) -> Self {
+        let budget = budget(&settings);
+        tracing::info!("Shutting down services within {:?}", budget);
+        Self {
+            app: app.clone(),
+            settings,
+            started: Instant::now(),
+            budget,
+        }
+    }
+
+    /// Time a step may take: its own cap, or what's left of the budget
+    fn allowance(&self, step: ShutdownStep) -> Duration {
+        step_allowance(
+            step.cap(&self.settings),
+            self.budget,
+            self.started.elapsed(),
+        )
+    }
+
+    fn report(&self, step: ShutdownStep, status: StepStatus) {
+        let progress = ShutdownProgress {
+            step,
+            status,
+            index: ShutdownStep::ALL
+                .iter()
+                .position(|s| *s == step)
+                .unwrap_or(0)
+                + 1,
+            total: ShutdownStep::ALL.len(),
+            elapsed_ms: self.started.elapsed().as_millis() as u64,
+            budget_ms: self.budget.as_millis() as u64,
+        };
+        let _ = self.app.emit(EVENT, &progress);
+    }
+
+    /// Run one step; None if it was abandoned because its time ran out
+    pub async fn step<T>(&self, step: ShutdownStep, work: impl Future<Output = T>) -> Option<T> {
+        let allowance = self.allowance(step);
+        let step_started = Instant::now();
+        tracing::info!(?step, ?allowance, "Shutdown step started");
+        self.report(step, StepStatus::Started);
+
+        let result = tokio::time::timeout(allowance, work).await.ok();
+        if result.is_some() {
+            tracing::info!(?step, elapsed = ?step_started.elapsed(), "Shutdown step done");
+            self.report(step, StepStatus::Done);
+        } else {
+            tracing::warn!(?step, ?allowance, "Shutdown step timed out, moving on");
+            self.report(step, StepStatus::TimedOut);
+        }
+        result
+    }
+
+    /// Log how the plan went
+    pub fn finish(self) {
+        tracing::info!(
+            "Services shut down in {:?} (budget {:?})",
+            self.started.elapsed(),
+            self.budget
+        );
+    }
+}
+
+/// A step's cap, limited to what's left of the budget
+fn step_allowance(cap: Duration, budget: Duration, elapsed: Duration) -> Duration {
+    cap.min(budget.saturating_sub(elapsed))
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_budget_follows_grace_period() {
+        let settings = ServiceSettings::default();
+        assert_eq!(budget(&settings), Duration::from_secs(3 + 10 + 5 + 21 + 3));
+
+        let patient = ServiceSettings {
+            shutdown_grace_secs: 30,
+            ..Default::default()
+        };
+        assert_eq!(
+            budget(&patient) - budget(&settings),
+            Duration::from_secs(20)
+        );
+    }
+
+    #[test]
+    fn test_step_allowance() {
+        let cap = Duration::from_secs(5);
+        let budget = Duration::from_secs(40);
+        assert_eq!(step_allowance(cap, budget, Duration::from_secs(1)), cap);
+        assert_eq!(
+            step_allowance(cap, budget, Duration::from_secs(38)),
+            Duration::from_secs(2)
+        );
+        assert_eq!(
+            step_allowance(cap, budget, Duration::from_secs(60)),
+            Duration::ZERO
+        );
+    }
+
+    #[test]
+    fn test_progress_shape() {
+        let progress = ShutdownProgress {
+            step: ShutdownStep::StopPostgres,
+            status: StepStatus::TimedOut,
+            index: 3,
+            total: 4,
+            elapsed_ms: 12_000,
+            budget_ms: 42_000,
+        };
+        let json = serde_json::to_value(&progress).unwrap();
+        assert_eq!(json["step"], "stop_postgres");
+        assert_eq!(json["status"], "timed_out");
+    }
+}
@@ -0,0 +1,884 @@
+//! Local snapshots of the app data directory.
+//!
+//! This module provides:
+//! - Periodic snapshots of settings, credentials, attachments and history
+//! - Deduplicated storage: unchanged files are hard-linked to the previous
+//!   snapshot, changed files are cloned (APFS `clonefile`, Linux `FICLONE`)
+//!   and only copied when the filesystem can't share blocks
+//! - A database dump in each snapshot, since the live PostgreSQL directory
+//!   can't be copied consistently file by file
+//! - A size budget enforced by removing the oldest snapshots
+//! - Automatic snapshots before restores and after app upgrades
+//!
+//! Snapshot files are never modified in place: restores copy them back out.
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::UNIX_EPOCH;
+use tauri::{AppHandle, Manager};
+
+use crate::backup::{pg_command, postgres_manager, run};
+use crate::config::{load_json, save_json_atomic};
+use crate::database::PostgresManager;
+use crate::error::AppError;
+use crate::scheduler::{JobAction, Schedule, ScheduledJob, Scheduler, SNAPSHOT_JOB_ID};
+use crate::services::{ServiceCommand, ServiceManager};
+
+/// Top-level entries of the app data directory that are never snapshotted
+const EXCLUDED: &[&str] = &[
+    "postgresql",
+    "snapshots",
+    "logs",
+    "tmp",
+    "archives",
+    "crashes",
+    "backups",
+    "wal-archive",
+    // Live state of the running instance, never rolled back
+    crate::profile::LOCK_FILE,
+    crate::keychain::INDEX_FILE,
+];
+
+/// Name of the database dump inside a snapshot
+const DATABASE_DUMP: &str = "database.dump";
+
+/// Only one snapshot is created or restored at a time
+static SNAPSHOT_LOCK: Mutex<()> = Mutex::new(());
+
+/// Snapshot settings
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotSettings {
+    /// Take snapshots periodically
+    pub periodic: bool,
+    pub interval_hours: u32,
+    /// Take a snapshot before restores and after upgrades
+    pub before_risky_operations: bool,
+    /// Disk space snapshots may use; the oldest are removed beyond it
+    pub max_bytes: u64,
+}
+
+impl Default for SnapshotSettings {
+    fn default() -> Self {
+        Self {
+            periodic: false,
+            interval_hours: 24,
+            before_risky_operations: true,
+            max_bytes: 2 * 1024 * 1024 * 1024,
+        }
+    }
+}
+
+impl SnapshotSettings {
+    fn path(app_data_dir: &Path) -> PathBuf {
+        app_data_dir.join("snapshot-settings.json")
+    }
+
+    /// Load settings, returning defaults if missing or invalid
+    pub fn load(app_data_dir: &Path) -> Self {
+        load_json(&Self::path(app_data_dir)).unwrap_or_default()
+    }
+
+    /// Save settings atomically
+    pub fn save(&self, app_data_dir: &Path) -> Result<(), String> {
+        save_json_atomic(&Self::path(app_data_dir), self)
+    }
+
+    /// Validate settings
+    pub fn validate(&self) -> Result<(), String> {
+        if self.interval_hours == 0 {
+            return Err("Snapshot interval must be at least 1 hour".to_string());
+        }
+        if self.max_bytes < 100 * 1024 * 1024 {
+            return Err("Snapshot budget must be at least 100 MB".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// A file recorded in a snapshot
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct SnapshotFile {
+    /// Path relative to the app data directory, `/`-separated
+    path: String,
+    size: u64,
+    modified_ms: u64,
+}
+
+/// Manifest stored in each snapshot
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct SnapshotManifest {
+    id: String,
+    created_at: String,
+    reason: String,
+    app_version: String,
+    files: Vec<SnapshotFile>,
+    has_database: bool,
+}
+
+/// Snapshot details for listing
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotInfo {
+    pub id: String,
+    /// Creation time (RFC 3339)
+    pub created_at: String,
+    /// Why the snapshot was taken (e.g. "scheduled", "before-restore")
+    pub reason: String,
+    pub app_version: String,
+    pub file_count: usize,
+    /// Total size of the snapshotted files, before deduplication
+    pub logical_bytes: u64,
+    pub has_database: bool,
+}
+
+impl From<&SnapshotManifest> for SnapshotInfo {
+    fn from(manifest: &SnapshotManifest) -> Self {
+        Self {
+            id: manifest.id.clone(),
+            created_at: manifest.created_at.clone(),
+            reason: manifest.reason.clone(),
+            app_version: manifest.app_version.clone(),
+            file_count: manifest.files.len(),
+            logical_bytes: manifest.files.iter().map(|f| f.size).sum(),
+            has_database: manifest.has_database,
+        }
+    }
+}
+
+/// Result of restoring a snapshot
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SnapshotRestoreResult {
+    pub files_restored: usize,
+    /// Files created since the snapshot that were removed
+    pub files_removed: usize,
+    pub database_restored: bool,
+    /// The snapshot taken of the current state before restoring
+    pub safety_snapshot: Option<String>,
+}
+
+/// App version seen at the last launch, used to detect upgrades
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct SnapshotState {
+    last_app_version: Option<String>,
+}
+
+impl SnapshotState {
+    fn path(app_data_dir: &Path) -> PathBuf {
+        app_data_dir.join("snapshot-state.json")
+    }
+}
+
+fn snapshots_dir(app_data_dir: &Path) -> PathBuf {
+    app_data_dir.join("snapshots")
+}
+
+fn manifest_path(snapshot_dir: &Path) -> PathBuf {
+    snapshot_dir.join("snapshot.json")
+}
+
+/// Snapshot manifests, newest first
+fn list_manifests(app_data_dir: &Path) -> Vec<SnapshotManifest> {
+    let Ok(entries) = std::fs::read_dir(snapshots_dir(app_data_dir)) else {
+        return Vec::new();
+    };
+    let mut manifests: Vec<SnapshotManifest> = entries
+        .flatten()
+        .filter_map(|e| load_json(&manifest_path(&e.path())))
+        .collect();
+    manifests.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    manifests
+}
+
+// ============================================================
+// File Copies
+// ============================================================
+
+/// Clone a file so it shares blocks with the source, if the filesystem can
+#[cfg(target_os = "macos")]
+fn clone_file(src: &Path, dst: &Path) -> bool {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let (Ok(src), Ok(dst)) = (
+        CString::new(src.as_os_str().as_bytes()),
+        CString::new(dst.as_os_str().as_bytes()),
+    ) else {
+        return false;
+    };
+    // SAFETY: both arguments are valid NUL-terminated paths
+    unsafe { libc::clonefile(src.as_ptr(), dst.as_ptr(), 0) == 0 }
+}
+
+/// Clone a file so it shares blocks with the source, if the filesystem can
+#[cfg(target_os = "linux")]
+fn clone_file(src: &Path, dst: &Path) -> bool {
+    use std::os::unix::io::AsRawFd;
+
+    /// `_IOW(0x94, 9, int)` from linux/fs.h
+    const FICLONE: libc::c_ulong = 0x4004_9409;
+
+    let (Ok(source), Ok(target)) = (std::fs::File::open(src), std::fs::File::create(dst)) else {
+        return false;
+    };
+    // SAFETY: both descriptors are open for the duration of the call
+    let cloned = unsafe { libc::ioctl(target.as_raw_fd(), FICLONE as _, source.as_raw_fd()) == 0 };
+    if !cloned {
+        drop(target);
+        let _ = std::fs::remove_file(dst);
+    }
+    cloned
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+fn clone_file(_src: &Path, _dst: &Path) -> bool {
+    false
+}
+
+fn clone_or_copy(src: &Path, dst: &Path) -> Result<(), String> {
+    if clone_file(src, dst) {
+        return Ok(());
+    }
+    std::fs::copy(src, dst)
+        .map(|_| ())
+        .map_err(|e| format!("Failed to copy {}: {}", src.display(), e))
+}
+
+fn modified_ms(metadata: &std::fs::Metadata) -> u64 {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+/// Regular files under `dir`, skipping excluded top-level entries and symlinks
+fn collect_files(root: &Path, dir: &Path, out: &mut Vec<(PathBuf, std::fs::Metadata)>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if dir == root
+            && EXCLUDED
+                .iter()
+                .any(|name| entry.file_name() == std::ffi::OsStr::new(name))
+        {
+            continue;
+        }
+        let Ok(metadata) = std::fs::symlink_metadata(&path) else {
+            continue;
+        };
+        if metadata.is_dir() {
+            collect_files(root, &path, out);
+        } else if metadata.is_file() {
+            out.push((path, metadata));
+        }
+    }
+}
+
+/// Whether a `/`-separated relative path falls under an excluded entry
+fn is_excluded(key: &str) -> bool {
+    let top = key.split('/').next().unwrap_or(key);
+    EXCLUDED.contains(&top)
+}
+
+fn relative_key(root: &Path, path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(root).ok()?;
+    Some(
+        relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/"),
+    )
+}
+
+/// Disk space used by a directory tree, counting hard-linked files once
+fn disk_usage(dir: &Path) -> u64 {
+    #[cfg(unix)]
+    fn identity(metadata: &std::fs::Metadata) -> Option<(u64, u64)> {
+        use std::os::unix::fs::MetadataExt;
+        Some((metadata.dev(), metadata.ino()))
+    }
+    #[cfg(not(unix))]
+    fn identity(_metadata: &std::fs::Metadata) -> Option<(u64, u64)> {
+        None
+    }
+
+    let mut files = Vec::new();
+    collect_files(Path::new(""), dir, &mut files);
+    let mut seen = std::collections::HashSet::new();
+    files
+        .iter()
+        .filter(|(_, m)| identity(m).map_or(true, |id| seen.insert(id)))
+        .map(|(_, m)| m.len())
+        .sum()
+}
+
+// ============================================================
+// Snapshots
+// ============================================================
+
+fn unique_id(app_data_dir: &Path) -> String {
+    let base = format!("snapshot-{}", Local::now().format("%Y%m%d-%H%M%S"));
+    let dir = snapshots_dir(app_data_dir);
+    let mut id = base.clone();
+    let mut n = 2;
+    while dir.join(&id).exists() {
+        id = format!("{}-{}", base, n);
+        n += 1;
+    }
+    id
+}
+
+fn dump_database(manager: &PostgresManager, path: &Path) -> Result<(), String> {
+    let mut pg_dump = pg_command(manager, "pg_dump")?;
+    pg_dump
+        .arg("-d")
+        .arg("secondbrain")
+        .arg("-Fc")
+        .arg("-f")
+        .arg(path);
+    run(pg_dump, "pg_dump").map(|_| ())
+}
+
+fn create_snapshot_blocking(
+    app_data_dir: &Path,
+    manager: Option<&PostgresManager>,
+    reason: &str,
+    app_version: &str,
+    max_bytes: u64,
+) -> Result<SnapshotInfo, String> {
+    let _guard = SNAPSHOT_LOCK.lock().unwrap();
+
+    let previous = list_manifests(app_data_dir).into_iter().next();
+    let previous_files: HashMap<&str, &SnapshotFile> = previous
+        .iter()
+        .flat_map(|m| m.files.iter().map(|f| (f.path.as_str(), f)))
+        .collect();
+    let previous_dir = previous
+        .as_ref()
+        .map(|m| snapshots_dir(app_data_dir).join(&m.id).join("files"));
+
+    let id = unique_id(app_data_dir);
+    let final_dir = snapshots_dir(app_data_dir).join(&id);
+    let partial_dir = snapshots_dir(app_data_dir).join(format!(".{}.partial", id));
+    let files_dir = partial_dir.join("files");
+    std::fs::create_dir_all(&files_dir)
+        .map_err(|e| format!("Failed to create snapshot directory: {}", e))?;
+
+    let result: Result<SnapshotInfo, String> = (|| {
+        let mut live = Vec::new();
+        collect_files(app_data_dir, app_data_dir, &mut live);
+
+        let mut files = Vec::with_capacity(live.len());
+        let mut linked = 0;
+        for (path, metadata) in live {
+            let Some(key) = relative_key(app_data_dir, &path) else {
+                continue;
+            };
+            let file = SnapshotFile {
+                path: key,
+                size: metadata.len(),
+                modified_ms: modified_ms(&metadata),
+            };
+            let target = files_dir.join(&file.path);
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent)
+                    .map_err(|e| format!("Failed to create snapshot directory: {}", e))?;
+            }
+
+            let unchanged = previous_files.get(file.path.as_str()) == Some(&&file);
+            let reused = unchanged
+                && previous_dir
+                    .as_ref()
+                    .is_some_and(|dir| std::fs::hard_link(dir.join(&file.path), &target).is_ok());
+            if reused {
+                linked += 1;
+            } else {
+                clone_or_copy(&path, &target)?;
+            }
+            files.push(file);
+        }
+
+        let has_database = match manager {
+            Some(manager) => match dump_database(manager, &partial_dir.join(DATABASE_DUMP)) {
+                Ok(()) => true,
+                Err(e) => {
+                    tracing::warn!("Snapshot {} taken without database: {}", id, e);
+                    false
+                }
+            },
+            None => false,
+        };
+
+        let manifest = SnapshotManifest {
+            id: id.clone(),
+            created_at: Local::now().to_rfc3339(),
+            reason: reason.to_string(),
+            app_version: app_version.to_string(),
+            files,
+            has_database,
+        };
+        save_json_atomic(&manifest_path(&partial_dir), &manifest)?;
+        std::fs::rename(&partial_dir, &final_dir)
+            .map_err(|e| format!("Failed to save snapshot: {}", e))?;
+        tracing::info!(
+            "Created snapshot {} ({}, {} files, {} unchanged)",
+            id,
+            reason,
+            manifest.files.len(),
+            linked
+        );
+        Ok(SnapshotInfo::from(&manifest))
+    })();
+    if result.is_err() {
+        let _ = std::fs::remove_dir_all(&partial_dir);
+    }
+
+    let info = result?;
+    prune_to_budget(app_data_dir, max_bytes, &info.id);
+    Ok(info)
+}
+
+/// Remove the oldest snapshots until they fit the budget, keeping `protect`
+fn prune_to_budget(app_data_dir: &Path, max_bytes: u64, protect: &str) {
+    let dir = snapshots_dir(app_data_dir);
+    let mut manifests = list_manifests(app_data_dir);
+    while disk_usage(&dir) > max_bytes {
+        let Some(index) = manifests.iter().rposition(|m| m.id != protect) else {
+            break;
+        };
+        let oldest = manifests.remove(index);
+        tracing::info!("Removing snapshot {} to stay within budget", oldest.id);
+        if let Err(e) = std::fs::remove_dir_all(dir.join(&oldest.id)) {
+            tracing::warn!("Failed to remove snapshot {}: {}", oldest.id, e);
+            break;
+        }
+    }
+}
+
+/// Make the snapshotted part of the app data directory match a snapshot
+///
+/// Returns the number of files restored and removed.
+fn restore_files(
+    app_data_dir: &Path,
+    manifest: &SnapshotManifest,
+) -> Result<(usize, usize), String> {
+    let files_dir = snapshots_dir(app_data_dir).join(&manifest.id).join("files");
+    let wanted: std::collections::HashSet<&str> =
+        manifest.files.iter().map(|f| f.path.as_str()).collect();
+
+    let mut live = Vec::new();
+    collect_files(app_data_dir, app_data_dir, &mut live);
+    let mut removed = 0;
+    for (path, _) in live {
+        if relative_key(app_data_dir, &path).is_some_and(|key| !wanted.contains(key.as_str())) {
+            std::fs::remove_file(&path)
+                .map_err(|e| format!("Failed to remove {}: {}", path.display(), e))?;
+            removed += 1;
+        }
+    }
+
+    // Older snapshots may hold entries excluded since
+    let files: Vec<&SnapshotFile> = manifest
+        .files
+        .iter()
+        .filter(|file| !is_excluded(&file.path))
+        .collect();
+    for file in &files {
+        let target = app_data_dir.join(&file.path);
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        // Copy (never link) so later edits can't reach back into the snapshot
+        let temp = target.with_file_name(format!(
+            ".{}.restore",
+            target.file_name().unwrap_or_default().to_string_lossy()
+        ));
+        let _ = std::fs::remove_file(&temp);
+        clone_or_copy(&files_dir.join(&file.path), &temp)?;
+        std::fs::rename(&temp, &target)
+            .map_err(|e| format!("Failed to restore {}: {}", file.path, e))?;
+    }
+    Ok((files.len(), removed))
+}
+
+fn restore_database(manager: &PostgresManager, dump: &Path) -> Result<(), String> {
+    let mut pg_restore = pg_command(manager, "pg_restore")?;
+    pg_restore
+        .arg("-d")
+        .arg("secondbrain")
+        .arg("--clean")
+        .arg("--if-exists")
+        .arg("--no-owner")
+        .arg("--single-transaction")
+        .arg(dump);
+    run(pg_restore, "pg_restore").map(|_| ())
+}
+
+/// Take a snapshot now
+pub async fn create_snapshot(app: &AppHandle, reason: &str) -> Result<SnapshotInfo, String> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let settings = SnapshotSettings::load(&app_data_dir);
+    let manager = postgres_manager(app);
+    let app_version = app.package_info().version.to_string();
+    let reason = reason.to_string();
+
+    tokio::task::spawn_blocking(move || {
+        create_snapshot_blocking(
+            &app_data_dir,
+            manager.as_deref(),
+            &reason,
+            &app_version,
+            settings.max_bytes,
+        )
+    })
+    .await
+    .map_err(|e| format!("Task panicked: {}", e))?
+}
+
+/// Take a safety snapshot before a risky operation, if enabled
+///
+/// Callers should abort the operation if this fails.
+pub async fn snapshot_before(app: &AppHandle, operation: &str) -> Result<Option<String>, String> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    if !SnapshotSettings::load(&app_data_dir).before_risky_operations {
+        return Ok(None);
+    }
+    let info = create_snapshot(app, &format!("before-{}", operation))
+        .await
+        .map_err(|e| format!("Safety snapshot failed: {}", e))?;
+    Ok(Some(info.id))
+}
+
+/// Scheduler entry point: take a periodic snapshot
+pub async fn run_scheduled_snapshot(app: &AppHandle) -> Result<(), String> {
+    create_snapshot(app, "scheduled").await.map(|_| ())
+}
+
+/// Register or remove the periodic snapshot job
+pub fn apply_settings(app: &AppHandle, settings: &SnapshotSettings) {
+    let scheduler = app.state::<Scheduler>();
+    if !settings.periodic {
+        scheduler.remove_job(SNAPSHOT_JOB_ID);
+        return;
+    }
+    scheduler.upsert_job(
+        ScheduledJob {
+            id: SNAPSHOT_JOB_ID.to_string(),
+            name: "Data snapshot".to_string(),
+            schedule: Schedule::Interval {
+                every_secs: u64::from(settings.interval_hours) * 3600,
+            },
+            skip_on_battery: true,
+            wait_for_idle: true,
+            jitter_secs: 300,
+            action: JobAction::DataSnapshot,
+        },
+        Local::now(),
+    );
+}
+
+/// Load settings, schedule snapshots, and snapshot after an app upgrade
+pub fn start(app: &AppHandle) {
+    let Ok(app_data_dir) = app.path().app_data_dir() else {
+        return;
+    };
+    let settings = SnapshotSettings::load(&app_data_dir);
+    apply_settings(app, &settings);
+
+    let version = app.package_info().version.to_string();
+    let mut state: SnapshotState =
+        load_json(&SnapshotState::path(&app_data_dir)).unwrap_or_default();
+    let upgraded = state
+        .last_app_version
+        .as_deref()
+        .is_some_and(|last| last != version);
+    state.last_app_version = Some(version.clone());
+    if let Err(e) = save_json_atomic(&SnapshotState::path(&app_data_dir), &state) {
+        tracing::warn!("Failed to save snapshot state: {}", e);
+    }
+
+    if upgraded && settings.before_risky_operations {
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = create_snapshot(&app, &format!("upgrade-to-{}", version)).await {
+                tracing::warn!("Upgrade snapshot failed: {}", e);
+            }
+        });
+    }
+}
+
+// ============================================================
+// Commands
+// ============================================================
+
+/// Get the snapshot settings
+#[tauri::command]
+pub async fn get_snapshot_settings(app: AppHandle) -> Result<SnapshotSettings, AppError> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    Ok(SnapshotSettings::load(&app_data_dir))
+}
+
+/// Update the snapshot settings and reschedule
+#[tauri::command]
+pub async fn set_snapshot_settings(
+    app: AppHandle,
+    settings: SnapshotSettings,
+) -> Result<(), AppError> {
+    settings.validate()?;
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    settings.save(&app_data_dir)?;
+    apply_settings(&app, &settings);
+    Ok(())
+}
+
+/// List snapshots, newest first
+#[tauri::command]
+pub async fn list_snapshots(app: AppHandle) -> Result<Vec<SnapshotInfo>, AppError> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    Ok(list_manifests(&app_data_dir)
+        .iter()
+        .map(SnapshotInfo::from)
+        .collect())
+}
+
+/// Take a snapshot now
+#[tauri::command]
+pub async fn take_snapshot(app: AppHandle) -> Result<SnapshotInfo, AppError> {
+    create_snapshot(&app, "manual")
+        .await
+        .map_err(AppError::from)
+}
+
+/// Restore the app data directory (and database, if included) from a snapshot
+///
+/// Refuses while services are starting or restarting. The current state is
+/// snapshotted first, and the backend is stopped for the restore and started
+/// again even if it fails. Restart the app afterwards so settings are
+/// reloaded.
+#[tauri::command]
+pub async fn restore_snapshot(
+    app: AppHandle,
+    id: String,
+) -> Result<SnapshotRestoreResult, AppError> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let manifest = list_manifests(&app_data_dir)
+        .into_iter()
+        .find(|m| m.id == id)
+        .ok_or_else(|| format!("Snapshot not found: {}", id))?;
+
+    let services = app.state::<ServiceManager>();
+    crate::backup::check_restorable(&services)?;
+    let safety_snapshot = snapshot_before(&app, "snapshot-restore").await?;
+    let manager = postgres_manager(&app);
+
+    services.send(ServiceCommand::StopBackend).await?;
+    let restored = tokio::task::spawn_blocking(move || {
+        let _guard = SNAPSHOT_LOCK.lock().unwrap();
+        let (files_restored, files_removed) = restore_files(&app_data_dir, &manifest)?;
+        let database_restored = match (manifest.has_database, manager) {
+            (true, Some(manager)) => {
+                let dump = snapshots_dir(&app_data_dir)
+                    .join(&manifest.id)
+                    .join(DATABASE_DUMP);
+                restore_database(&manager, &dump)?;
+                true
+            }
+            _ => false,
+        };
+        tracing::info!(
+            "Restored snapshot {} ({} files, {} removed, database: {})",
+            manifest.id,
+            files_restored,
+            files_removed,
+            database_restored
+        );
+        Ok::<_, String>(SnapshotRestoreResult {
+            files_restored,
+            files_removed,
+            database_restored,
+            safety_snapshot: None,
+        })
+    })
+    .await;
+
+    let restarted = services.send(ServiceCommand::RestartBackend).await;
+    if let Err(ref e) = restarted {
+        tracing::error!("Backend failed to start after snapshot restore: {}", e);
+    }
+    let mut result = restored.map_err(|e| format!("Task panicked: {}", e))??;
+    restarted?;
+    result.safety_snapshot = safety_snapshot;
+    Ok(result)
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write(root: &Path, path: &str, contents: &str) {
+        let path = root.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    }
+
+    fn snapshot(root: &Path) -> SnapshotInfo {
+        create_snapshot_blocking(root, None, "test", "1.0.0", u64::MAX).unwrap()
+    }
+
+    #[test]
+    fn test_snapshot_excludes_transient_directories() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        write(root, "settings.json", "{}");
+        write(root, "attachments/ab/abcd", "image");
+        write(root, "logs/app.log", "log");
+        write(root, "postgresql/PG_VERSION", "18");
+
+        let info = snapshot(root);
+        assert_eq!(info.file_count, 2);
+        assert_eq!(info.logical_bytes, 7);
+        assert!(!info.has_database);
+
+        let files = snapshots_dir(root).join(&info.id).join("files");
+        assert!(files.join("attachments/ab/abcd").exists());
+        assert!(!files.join("logs").exists());
+        // A second snapshot doesn't include the first
+        assert_eq!(snapshot(root).file_count, 2);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_unchanged_files_are_hard_linked() {
+        use std::os::unix::fs::MetadataExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        write(root, "big.bin", &"x".repeat(10_000));
+        write(root, "settings.json", "{}");
+
+        let first = snapshot(root);
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        write(root, "settings.json", "{\"changed\":true}");
+        let second = snapshot(root);
+
+        let path = |id: &str, file: &str| snapshots_dir(root).join(id).join("files").join(file);
+        let a = std::fs::metadata(path(&first.id, "big.bin")).unwrap();
+        let b = std::fs::metadata(path(&second.id, "big.bin")).unwrap();
+        assert_eq!(a.ino(), b.ino());
+        assert_eq!(
+            std::fs::read_to_string(path(&second.id, "settings.json")).unwrap(),
+            "{\"changed\":true}"
+        );
+        // The shared file is only counted once
+        assert!(disk_usage(&snapshots_dir(root)) < 20_000);
+    }
+
+    #[test]
+    fn test_restore_files_matches_snapshot() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        write(root, "settings.json", "original");
+        write(root, "logs/app.log", "log");
+        let info = snapshot(root);
+
+        write(root, "settings.json", "modified");
+        write(root, "new/file.txt", "new");
+        let manifest = list_manifests(root)
+            .into_iter()
+            .find(|m| m.id == info.id)
+            .unwrap();
+        let (restored, removed) = restore_files(root, &manifest).unwrap();
+
+        assert_eq!((restored, removed), (1, 1));
+        assert_eq!(
+            std::fs::read_to_string(root.join("settings.json")).unwrap(),
+            "original"
+        );
+        assert!(!root.join("new/file.txt").exists());
+        // Excluded directories are left alone
+        assert!(root.join("logs/app.log").exists());
+        // Editing the restored file doesn't change the snapshot
+        write(root, "settings.json", "edited");
+        let stored = snapshots_dir(root)
+            .join(&info.id)
+            .join("files/settings.json");
+        assert_eq!(std::fs::read_to_string(stored).unwrap(), "original");
+    }
+
+    #[test]
+    fn test_restore_keeps_live_instance_state() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        write(root, "settings.json", "original");
+        write(root, "instance.lock", "1234");
+        write(root, "credential-accounts.json", "[\"old\"]");
+        let info = snapshot(root);
+        assert_eq!(info.file_count, 1);
+
+        // As if recorded by a version that didn't exclude them yet
+        let mut manifest = list_manifests(root)
+            .into_iter()
+            .find(|m| m.id == info.id)
+            .unwrap();
+        for path in ["instance.lock", "credential-accounts.json"] {
+            write(
+                &snapshots_dir(root).join(&info.id).join("files"),
+                path,
+                "stale",
+            );
+            manifest.files.push(SnapshotFile {
+                path: path.to_string(),
+                size: 5,
+                modified_ms: 0,
+            });
+        }
+        write(root, "credential-accounts.json", "[\"new\"]");
+
+        assert_eq!(restore_files(root, &manifest).unwrap(), (1, 0));
+        assert_eq!(
+            std::fs::read_to_string(root.join("instance.lock")).unwrap(),
+            "1234"
+        );
+        assert_eq!(
+            std::fs::read_to_string(root.join("credential-accounts.json")).unwrap(),
+            "[\"new\"]"
+        );
+    }
+
+    #[test]
+    fn test_prune_to_budget_keeps_protected() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        write(root, "data.bin", &"x".repeat(5_000));
+        let first = snapshot(root);
+        write(root, "data.bin", &"y".repeat(5_001));
+        let second = snapshot(root);
+
+        prune_to_budget(root, 1, &second.id);
+        let remaining: Vec<String> = list_manifests(root).into_iter().map(|m| m.id).collect();
+        assert_eq!(remaining, vec![second.id]);
+        assert!(!snapshots_dir(root).join(first.id).exists());
+    }
+
+    #[test]
+    fn test_settings_validation() {
+        assert!(SnapshotSettings::default().validate().is_ok());
+        let invalid = SnapshotSettings {
+            interval_hours: 0,
+            ..Default::default()
+        };
+        assert!(invalid.validate().is_err());
+    }
+}
@@ -0,0 +1,249 @@
+//! Native splash window shown while services start.
+//!
+//! This module provides:
+//! - A small undecorated window opened first thing at launch, loading the
+//!   static `splash.html` rather than the app bundle
+//! - Startup events mapped to a status line and progress, pushed to the
+//!   splash page as they're emitted
+//! - Revealing the main window, which starts hidden, and closing the splash
+//!   once `AllServicesReady` fires, startup fails, or `REVEAL_TIMEOUT` passes
+//!
+//! The splash page has no IPC access; updates are sent with `eval`, and the
+//! latest one is sent again when the page finishes loading.
+
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::time::Duration;
+use tauri::webview::PageLoadEvent;
+use tauri::{AppHandle, Manager, Runtime, WebviewUrl, WebviewWindowBuilder};
+
+use crate::startup::StartupEvent;
+
+/// Label of the splash window
+const LABEL: &str = "splash";
+
+/// Longest the main window stays hidden behind the splash
+const REVEAL_TIMEOUT: Duration = Duration::from_secs(180);
+
+/// What the splash shows
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SplashStatus {
+    pub message: String,
+    /// Overall progress; None leaves the bar where it was
+    pub percent: Option<u8>,
+    pub error: bool,
+}
+
+impl SplashStatus {
+    fn progress(message: impl Into<String>, percent: u8) -> Self {
+        Self {
+            message: message.into(),
+            percent: Some(percent),
+            error: false,
+        }
+    }
+}
+
+/// Latest status, kept in Tauri state while the splash is open
+#[derive(Default)]
+pub struct Splash {
+    status: Mutex<Option<SplashStatus>>,
+}
+
+/// Splash status for a startup event; None for events after startup
+fn status_for(event: &StartupEvent) -> Option<SplashStatus> {
+    Some(match event {
+        StartupEvent::PostgresStarting { .. } => SplashStatus::progress("Starting database…", 10),
+        StartupEvent::DatabaseUpgrading {
+            to_version,
+            percent,
+            ..
+        } => SplashStatus::progress(
+            format!("Upgrading database to PostgreSQL {}…", to_version),
+            10 + (u16::from(*percent) * 3 / 10) as u8,
+        ),
+        StartupEvent::DatabaseUpgraded { .. } => SplashStatus::progress("Database upgraded", 40),
+        StartupEvent::PostgresReady { .. } => SplashStatus::progress("Database ready", 45),
+        StartupEvent::PortConflict { port, service } => SplashStatus {
+            message: format!("Port {} is in use, moving {} to another…", port, service),
+            percent: None,
+            error: false,
+        },
+        StartupEvent::RetryingStartup {
+            service,
+            attempt,
+            max_attempts,
+            ..
+        } => SplashStatus {
+            message: format!("Retrying {} ({}/{})…", service, attempt, max_attempts),
+            percent: None,
+            error: false,
+        },
+        StartupEvent::BackendStarting { .. } => SplashStatus::progress("Starting backend…", 55),
+        // Creeps towards 90% while the health check keeps waiting
+        StartupEvent::BackendWaiting { attempt, .. } => {
+            SplashStatus::progress("Waiting for the backend…", 60 + (*attempt).min(30) as u8)
+        }
+        StartupEvent::BackendReady { .. } => SplashStatus::progress("Backend ready", 95),
+        StartupEvent::AllServicesReady { .. } => SplashStatus::progress("Ready", 100),
+        StartupEvent::PostgresFailed { error, .. }
+        | StartupEvent::BackendFailed { error, .. }
+        | StartupEvent::StartupFailed { error } => SplashStatus {
+            message: error.clone(),
+            percent: None,
+            error: true,
+        },
+        StartupEvent::BackendCrashed { .. } | StartupEvent::BackendRestarted { .. } => return None,
+    })
+}
+
+/// Whether startup is over and the main window should be shown
+fn ends_startup(event: &StartupEvent) -> bool {
+    matches!(
+        event,
+        StartupEvent::AllServicesReady { .. } | StartupEvent::StartupFailed { .. }
+    )
+}
+
+fn push<R: Runtime>(window: &tauri::WebviewWindow<R>, status: &SplashStatus) {
+    let Ok(json) = serde_json::to_string(status) else {
+        return;
+    };
+    let _ = window.eval(format!(
+        "window.splashUpdate && window.splashUpdate({})",
+        json
+    ));
+}
+
+/// Show the main window and close the splash
+pub fn reveal<R: Runtime>(app: &AppHandle<R>) {
+    if let Some(main) = app.get_webview_window("main") {
+        let _ = main.show();
+        let _ = main.set_focus();
+    }
+    if let Some(splash) = app.get_webview_window(LABEL) {
+        let _ = splash.close();
+    }
+}
+
+/// Pass a startup event on to the splash, revealing the app when startup ends
+pub fn update<R: Runtime>(app: &AppHandle<R>, event: &StartupEvent) {
+    let Some(splash) = app.get_webview_window(LABEL) else {
+        return;
+    };
+    if let Some(status) = status_for(event) {
+        push(&splash, &status);
+        if let Some(state) = app.try_state::<Splash>() {
+            *state.status.lock() = Some(status);
+        }
+    }
+    if ends_startup(event) {
+        reveal(app);
+    }
+}
+
+/// Open the splash window; the main window is revealed directly if it can't
+pub fn open(app: &AppHandle) {
+    app.manage(Splash::default());
+    let built = WebviewWindowBuilder::new(app, LABEL, WebviewUrl::App("splash.html".into()))
+        .title(app.package_info().name.clone())
+        .inner_size(420.0, 260.0)
+        .resizable(false)
+        .decorations(false)
+        .center()
+        .always_on_top(true)
+        .skip_taskbar(true)
+        .focused(true)
+        .on_page_load(|window, payload| {
+            if payload.event() != PageLoadEvent::Finished {
+                return;
+            }
+            let status = window.state::<Splash>().status.lock().clone();
+            if let Some(status) = status {
+                push(&window, &status);
+            }
+        })
+        .build();
+    if let Err(e) = built {
+        tracing::warn!("Failed to open the splash window: {}", e);
+        reveal(app);
+        return;
+    }
+
+    // Never leave the app hidden behind a splash that missed its event
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(REVEAL_TIMEOUT).await;
+        if app.get_webview_window(LABEL).is_some() {
+            tracing::warn!("Startup is taking long, showing the app anyway");
+            reveal(&app);
+        }
+    });
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::UpgradeStep;
+
+    #[test]
+    fn test_status_for_progresses() {
+        let events = [
+            StartupEvent::PostgresStarting { port: 5433 },
+            StartupEvent::DatabaseUpgrading {
+                from_version: 17,
+                to_version: 18,
+                step: UpgradeStep::Upgrading,
+                percent: 50,
+            },
+            StartupEvent::PostgresReady {
+                port: 5433,
+                duration_ms: 900,
+            },
+            StartupEvent::BackendStarting { port: 5001 },
+            StartupEvent::BackendWaiting {
+                port: 5001,
+                attempt: 100,
+                elapsed_ms: 20_000,
+            },
+            StartupEvent::BackendReady {
+                port: 5001,
+                duration_ms: 3000,
+            },
+            StartupEvent::AllServicesReady {
+                total_duration_ms: 4000,
+            },
+        ];
+        let percents: Vec<u8> = events
+            .iter()
+            .map(|event| status_for(event).unwrap().percent.unwrap())
+            .collect();
+        assert_eq!(percents, vec![10, 25, 45, 55, 90, 95, 100]);
+    }
+
+    #[test]
+    fn test_failures_and_end_of_startup() {
+        let failed = StartupEvent::StartupFailed {
+            error: "PostgreSQL didn't start".to_string(),
+        };
+        let status = status_for(&failed).unwrap();
+        assert!(status.error);
+        assert_eq!(status.message, "PostgreSQL didn't start");
+        assert!(ends_startup(&failed));
+
+        // A backend failure may still be retried
+        assert!(!ends_startup(&StartupEvent::BackendFailed {
+            error: "exited".to_string(),
+            port: 5001,
+        }));
+        assert!(status_for(&StartupEvent::BackendRestarted {
+            port: 5001,
+            attempt: 1
+        })
+        .is_none());
+    }
+}
@@ -5,10 +5,18 @@
 //! - Configurable timeouts
 //! - Status event emission to frontend
 //! - Startup metrics collection
+//! - Startup history with percentile statistics across recent boots
 
 use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
 use std::time::{Duration, Instant};
-use tauri::{AppHandle, Emitter};
+use tauri::{AppHandle, Runtime};
+
+use crate::config::load_json;
+use crate::database::{UpgradeMethod, UpgradeStep};
+
+/// Boots kept in the startup history
+pub const STARTUP_HISTORY_LIMIT: usize = 30;
 
 /// Startup status events emitted to the frontend
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -20,6 +28,20 @@ pub enum StartupEvent {
     PostgresReady { port: u16, duration_ms: u64 },
     /// PostgreSQL failed to start
     PostgresFailed { error: String, port: u16 },
+    /// The data directory is being upgraded to a new PostgreSQL major version
+    DatabaseUpgrading {
+        from_version: u32,
+        to_version: u32,
+        step: UpgradeStep,
+        percent: u8,
+    },
+    /// The data directory was upgraded
+    DatabaseUpgraded {
+        from_version: u32,
+        to_version: u32,
+        method: UpgradeMethod,
+        duration_ms: u64,
+    },
     /// Backend is starting
     BackendStarting { port: u16 },
     /// Backend is ready
@@ -39,13 +61,47 @@ pub enum StartupEvent {
         max_attempts: u32,
         delay_ms: u64,
     },
+    /// Still waiting for the backend health check to pass
+    BackendWaiting {
+        port: u16,
+        attempt: u32,
+        elapsed_ms: u64,
+    },
+    /// The backend exited without being asked to
+    BackendCrashed {
+        exit_code: Option<i32>,
+        /// Crashes within the crash-loop window, including this one
+        recent_crashes: u32,
+        /// Milliseconds until the restart, or None when giving up
+        restart_in_ms: Option<u64>,
+    },
+    /// The backend is running again after a crash
+    BackendRestarted { port: u16, attempt: u32 },
 }
 
 impl StartupEvent {
+    /// Whether this is a state transition rather than a progress update
+    pub fn is_critical(&self) -> bool {
+        !matches!(
+            self,
+            StartupEvent::RetryingStartup { .. } | StartupEvent::BackendWaiting { .. }
+        )
+    }
+
     /// Emit this event to the frontend
-    pub fn emit(&self, app: &AppHandle) {
-        if let Err(e) = app.emit("startup-event", self) {
-            log::warn!("Failed to emit startup event: {}", e);
+    ///
+    /// Progress updates are coalesced so only the latest is sent per flush;
+    /// transitions go out immediately. The splash window, if still open,
+    /// gets every event, and each is added to the startup trace.
+    pub fn emit<R: Runtime>(&self, app: &AppHandle<R>) {
+        crate::splash::update(app, self);
+        crate::startup_trace::record(app, self);
+        match self {
+            _ if self.is_critical() => crate::events::emit_critical(app, "startup-event", self),
+            StartupEvent::RetryingStartup { service, .. } => {
+                crate::events::emit_coalesced(app, "startup-event", service.clone(), self)
+            }
+            _ => crate::events::emit_coalesced(app, "startup-event", "backend-waiting", self),
         }
     }
 }
@@ -63,6 +119,18 @@ pub struct StartupConfig {
     pub max_attempts: u32,
     /// Overall timeout for service startup (seconds)
     pub timeout_secs: u64,
+    /// Health-check interval right after a (re)start (milliseconds)
+    pub health_fast_interval_ms: u64,
+    /// How long after a (re)start checks stay at the fast interval (seconds)
+    pub health_fast_window_secs: u64,
+    /// Longest interval while the backend is starting or failing (milliseconds)
+    pub health_max_interval_ms: u64,
+    /// Interval once the backend is up and healthy (seconds)
+    pub health_steady_interval_secs: u64,
+    /// Random spread applied to each interval, as a fraction (0.2 = ±20%)
+    pub health_jitter: f64,
+    /// How long to wait for the backend's first successful health check (seconds)
+    pub health_max_wait_secs: u64,
 }
 
 impl Default for StartupConfig {
@@ -73,10 +141,58 @@ impl Default for StartupConfig {
             backoff_multiplier: 2.0,
             max_attempts: 10,
             timeout_secs: 120,
+            health_fast_interval_ms: 250,
+            health_fast_window_secs: 10,
+            health_max_interval_ms: 2000,
+            health_steady_interval_secs: 30,
+            health_jitter: 0.2,
+            // Longer timeout for first start with migrations
+            health_max_wait_secs: 120,
         }
     }
 }
 
+impl StartupConfig {
+    /// Delay before the next health check
+    ///
+    /// Checks run at the fast interval for a short window after a (re)start,
+    /// back off towards `health_max_interval_ms` while the service is
+    /// starting or failing, and relax to the steady interval once healthy.
+    /// `jitter` is in [-1, 1] and spreads checks so several windows or a
+    /// wake from sleep don't all poll at once.
+    pub fn health_interval(
+        &self,
+        since_restart: Duration,
+        consecutive_failures: u32,
+        healthy: bool,
+        jitter: f64,
+    ) -> Duration {
+        let fast = self.health_fast_interval_ms as f64;
+        let base_ms = if since_restart < Duration::from_secs(self.health_fast_window_secs) {
+            fast
+        } else if healthy && consecutive_failures == 0 {
+            (self.health_steady_interval_secs * 1000) as f64
+        } else {
+            (fast
+                * self
+                    .backoff_multiplier
+                    .powi(consecutive_failures.min(16) as i32))
+            .min(self.health_max_interval_ms as f64)
+        };
+        let spread = 1.0 + self.health_jitter * jitter.clamp(-1.0, 1.0);
+        Duration::from_millis((base_ms * spread).max(fast / 2.0) as u64)
+    }
+}
+
+/// Random value in [-1, 1] for `StartupConfig::health_interval`
+pub fn random_jitter() -> f64 {
+    let mut bytes = [0u8; 4];
+    if getrandom::fill(&mut bytes).is_err() {
+        return 0.0;
+    }
+    (u32::from_le_bytes(bytes) as f64 / u32::MAX as f64) * 2.0 - 1.0
+}
+
 /// Backoff strategy for retrying operations
 pub struct ExponentialBackoff {
     config: StartupConfig,
@@ -127,7 +243,7 @@ impl ExponentialBackoff {
 }
 
 /// Metrics collected during startup
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
 pub struct StartupMetrics {
     /// Time to start PostgreSQL (milliseconds)
     pub postgres_startup_ms: Option<u64>,
@@ -147,6 +263,22 @@ pub struct StartupMetrics {
     pub success: bool,
     /// Error message if startup failed
     pub error: Option<String>,
+    /// How the backend was last stopped
+    pub shutdown_mode: Option<ShutdownMode>,
+    /// Time the last backend stop took (milliseconds)
+    pub shutdown_ms: Option<u64>,
+}
+
+/// How a backend process was stopped
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShutdownMode {
+    /// Exited on its own within the grace period after being asked to stop
+    Graceful,
+    /// Killed after the grace period ran out or the stop request failed
+    Forced,
+    /// Had already exited when it was stopped
+    AlreadyExited,
 }
 
 impl StartupMetrics {
@@ -179,6 +311,22 @@ impl StartupMetrics {
         self.success = false;
         self.error = Some(error);
     }
+
+    /// Record how the backend was stopped
+    pub fn mark_shutdown(&mut self, mode: ShutdownMode, duration: Duration) {
+        self.shutdown_mode = Some(mode);
+        self.shutdown_ms = Some(duration.as_millis() as u64);
+    }
+
+    /// Fresh metrics for a new startup, keeping the record of the last
+    /// shutdown since a restart's stop happens just before it
+    pub fn restarted(&self) -> Self {
+        Self {
+            shutdown_mode: self.shutdown_mode,
+            shutdown_ms: self.shutdown_ms,
+            ..Self::default()
+        }
+    }
 }
 
 /// Timer for measuring startup durations
@@ -210,6 +358,156 @@ impl Default for StartupTimer {
     }
 }
 
+/// One service startup, as kept in the history
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BootRecord {
+    /// When startup finished (Unix epoch seconds)
+    pub at: i64,
+    /// App version that booted
+    pub version: String,
+    pub metrics: StartupMetrics,
+}
+
+/// Recent startups, oldest first, persisted in startup-history.json
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StartupHistory {
+    pub boots: Vec<BootRecord>,
+}
+
+impl StartupHistory {
+    pub fn path(app_data_dir: &Path) -> PathBuf {
+        app_data_dir.join("startup-history.json")
+    }
+
+    pub fn load(app_data_dir: &Path) -> Self {
+        load_json(&Self::path(app_data_dir)).unwrap_or_default()
+    }
+
+    pub fn save(&self, app_data_dir: &Path) -> Result<(), String> {
+        crate::journal::write(app_data_dir, "startup-history.json", self)
+    }
+
+    /// Add a boot, dropping the oldest beyond `STARTUP_HISTORY_LIMIT`
+    pub fn record(&mut self, boot: BootRecord) {
+        self.boots.push(boot);
+        let excess = self.boots.len().saturating_sub(STARTUP_HISTORY_LIMIT);
+        self.boots.drain(..excess);
+    }
+
+    /// Summarize the recorded boots
+    pub fn stats(&self) -> StartupStats {
+        let successful: Vec<&BootRecord> =
+            self.boots.iter().filter(|b| b.metrics.success).collect();
+        let durations = |field: fn(&StartupMetrics) -> Option<u64>| -> Vec<u64> {
+            successful
+                .iter()
+                .filter_map(|b| field(&b.metrics))
+                .collect()
+        };
+        let totals = durations(|m| m.total_startup_ms);
+        let postgres = durations(|m| m.postgres_startup_ms);
+        let backend = durations(|m| m.backend_startup_ms);
+
+        let retried = self
+            .boots
+            .iter()
+            .filter(|b| b.metrics.postgres_retry_count + b.metrics.backend_retry_count > 0)
+            .count();
+        let retries: u32 = self
+            .boots
+            .iter()
+            .map(|b| b.metrics.postgres_retry_count + b.metrics.backend_retry_count)
+            .sum();
+        let ratio = |n: f64| {
+            if self.boots.is_empty() {
+                0.0
+            } else {
+                n / self.boots.len() as f64
+            }
+        };
+
+        // Compare the median of the newer half of boots with the older half
+        let half = totals.len() / 2;
+        let trend_change_pct = match (
+            percentile(&totals[..half], 50.0),
+            percentile(&totals[totals.len() - half..], 50.0),
+        ) {
+            (Some(older), Some(newer)) if half >= 2 && older > 0 => {
+                Some((newer as f64 - older as f64) / older as f64 * 100.0)
+            }
+            _ => None,
+        };
+
+        StartupStats {
+            boots: self.boots.len(),
+            failures: self.boots.len() - successful.len(),
+            total_p50_ms: percentile(&totals, 50.0),
+            total_p95_ms: percentile(&totals, 95.0),
+            postgres_p50_ms: percentile(&postgres, 50.0),
+            postgres_p95_ms: percentile(&postgres, 95.0),
+            backend_p50_ms: percentile(&backend, 50.0),
+            backend_p95_ms: percentile(&backend, 95.0),
+            retry_rate: ratio(retried as f64),
+            average_retries: ratio(retries as f64),
+            trend_change_pct,
+            trend: self
+                .boots
+                .iter()
+                .map(|b| BootPoint {
+                    at: b.at,
+                    version: b.version.clone(),
+                    total_ms: b.metrics.total_startup_ms,
+                    success: b.metrics.success,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Startup time of one boot, for charting the trend
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BootPoint {
+    pub at: i64,
+    pub version: String,
+    pub total_ms: Option<u64>,
+    pub success: bool,
+}
+
+/// Startup statistics over the recorded boots
+///
+/// Percentiles cover successful boots only.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StartupStats {
+    pub boots: usize,
+    pub failures: usize,
+    pub total_p50_ms: Option<u64>,
+    pub total_p95_ms: Option<u64>,
+    pub postgres_p50_ms: Option<u64>,
+    pub postgres_p95_ms: Option<u64>,
+    pub backend_p50_ms: Option<u64>,
+    pub backend_p95_ms: Option<u64>,
+    /// Share of boots that needed at least one retry (0.0 to 1.0)
+    pub retry_rate: f64,
+    /// Retries per boot
+    pub average_retries: f64,
+    /// Change in median startup time from the older half of boots to the
+    /// newer half (percent; positive means slower)
+    pub trend_change_pct: Option<f64>,
+    /// Every recorded boot, oldest first
+    pub trend: Vec<BootPoint>,
+}
+
+/// Nearest-rank percentile of unsorted values
+pub fn percentile(values: &[u64], pct: f64) -> Option<u64> {
+    if values.is_empty() {
+        return None;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_unstable();
+    let rank = ((pct / 100.0) * sorted.len() as f64).ceil() as usize;
+    Some(sorted[rank.clamp(1, sorted.len()) - 1])
+}
+
 // ============================================================
 // Unit Tests
 // ============================================================
@@ -236,6 +534,7 @@ mod tests {
             backoff_multiplier: 2.0,
             max_attempts: 5,
             timeout_secs: 60,
+            ..Default::default()
         };
         let mut backoff = ExponentialBackoff::new(config);
 
@@ -301,6 +600,88 @@ mod tests {
         assert_eq!(metrics.error, Some("Connection refused".to_string()));
     }
 
+    #[test]
+    fn test_startup_metrics_keep_shutdown_across_restart() {
+        let mut metrics = StartupMetrics::new();
+        metrics.mark_failed("Connection refused".to_string());
+        metrics.mark_shutdown(ShutdownMode::Forced, Duration::from_millis(10_250));
+
+        let restarted = metrics.restarted();
+        assert_eq!(restarted.error, None);
+        assert_eq!(restarted.shutdown_mode, Some(ShutdownMode::Forced));
+        assert_eq!(restarted.shutdown_ms, Some(10_250));
+        assert_eq!(
+            serde_json::to_value(ShutdownMode::AlreadyExited).unwrap(),
+            "already_exited"
+        );
+    }
+
+    fn boot(at: i64, total_ms: Option<u64>, retries: u32) -> BootRecord {
+        BootRecord {
+            at,
+            version: "2.0.0".to_string(),
+            metrics: StartupMetrics {
+                total_startup_ms: total_ms,
+                postgres_startup_ms: total_ms.map(|t| t / 2),
+                backend_startup_ms: total_ms.map(|t| t / 2),
+                postgres_retry_count: retries,
+                success: total_ms.is_some(),
+                ..Default::default()
+            },
+        }
+    }
+
+    #[test]
+    fn test_percentile() {
+        assert_eq!(percentile(&[], 50.0), None);
+        assert_eq!(percentile(&[7], 95.0), Some(7));
+        let values: Vec<u64> = (1..=100).rev().collect();
+        assert_eq!(percentile(&values, 50.0), Some(50));
+        assert_eq!(percentile(&values, 95.0), Some(95));
+        assert_eq!(percentile(&values, 100.0), Some(100));
+    }
+
+    #[test]
+    fn test_startup_history_is_bounded_and_persisted() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let mut history = StartupHistory::load(temp_dir.path());
+        for i in 0..STARTUP_HISTORY_LIMIT as i64 + 5 {
+            history.record(boot(i, Some(1000), 0));
+        }
+        assert_eq!(history.boots.len(), STARTUP_HISTORY_LIMIT);
+        assert_eq!(history.boots[0].at, 5);
+
+        history.save(temp_dir.path()).unwrap();
+        assert_eq!(StartupHistory::load(temp_dir.path()), history);
+    }
+
+    #[test]
+    fn test_startup_stats() {
+        let mut history = StartupHistory::default();
+        assert_eq!(history.stats().total_p50_ms, None);
+
+        for (i, total) in [1000, 1100, 900, 1000, 2000, 2100, 1900, 2000]
+            .iter()
+            .enumerate()
+        {
+            history.record(boot(i as i64, Some(*total), u32::from(i % 4 == 0)));
+        }
+        history.record(boot(9, None, 3));
+
+        let stats = history.stats();
+        assert_eq!(stats.boots, 9);
+        assert_eq!(stats.failures, 1);
+        assert_eq!(stats.total_p50_ms, Some(1100));
+        assert_eq!(stats.total_p95_ms, Some(2100));
+        assert_eq!(stats.backend_p50_ms, Some(550));
+        assert!((stats.retry_rate - 3.0 / 9.0).abs() < 1e-9);
+        assert!((stats.average_retries - 5.0 / 9.0).abs() < 1e-9);
+        // Median went from 1000ms to 2000ms
+        assert_eq!(stats.trend_change_pct, Some(100.0));
+        assert_eq!(stats.trend.len(), 9);
+        assert!(!stats.trend[8].success);
+    }
+
     #[test]
     fn test_startup_timer() {
         let timer = StartupTimer::new();
@@ -318,4 +699,58 @@ mod tests {
         assert!(json.contains("PostgresReady"));
         assert!(json.contains("5433"));
     }
+
+    #[test]
+    fn test_progress_events_are_not_critical() {
+        assert!(StartupEvent::BackendStarting { port: 5001 }.is_critical());
+        assert!(!StartupEvent::BackendWaiting {
+            port: 5001,
+            attempt: 3,
+            elapsed_ms: 900,
+        }
+        .is_critical());
+    }
+
+    #[test]
+    fn test_health_interval_phases() {
+        let config = StartupConfig::default();
+        let just_started = Duration::from_secs(1);
+        let settled = Duration::from_secs(60);
+
+        // Fast right after a (re)start, even when failing
+        assert_eq!(
+            config.health_interval(just_started, 3, false, 0.0),
+            Duration::from_millis(250)
+        );
+        // Backs off while failing, capped
+        assert_eq!(
+            config.health_interval(settled, 1, false, 0.0),
+            Duration::from_millis(500)
+        );
+        assert_eq!(
+            config.health_interval(settled, 10, false, 0.0),
+            Duration::from_millis(2000)
+        );
+        // Steady once healthy
+        assert_eq!(
+            config.health_interval(settled, 0, true, 0.0),
+            Duration::from_secs(30)
+        );
+        // Jitter spreads by at most the configured fraction
+        assert_eq!(
+            config.health_interval(settled, 0, true, 1.0),
+            Duration::from_secs(36)
+        );
+        assert_eq!(
+            config.health_interval(settled, 0, true, -1.0),
+            Duration::from_secs(24)
+        );
+    }
+
+    #[test]
+    fn test_random_jitter_range() {
+        for _ in 0..100 {
+            assert!((-1.0..=1.0).contains(&random_jitter()));
+        }
+    }
 }
@@ -0,0 +1,393 @@
+//! Operating system and network dependencies of service startup.
+//!
+//! This module provides:
+//! - `PortProbe`, `HealthProbe` and `ProcessSpawner`, with the real
+//!   implementations; health checks go through the shared backend client
+//! - `StartupDeps`, held in `AppState`, which the startup path and the
+//!   health monitor go through instead of binding ports, spawning processes
+//!   or calling the backend directly
+//! - `claim_port`, `spawn` and `wait_for_health`, the startup steps built
+//!   on them
+//!
+//! Tests swap in fakes to drive port conflicts, spawn failures and slow or
+//! failing health checks deterministically.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::process::{Child, Command};
+
+use crate::config::PortRange;
+use crate::error::AppError;
+use crate::port_utils;
+use crate::startup::{self, StartupConfig};
+
+/// Alternative ports tried after a conflict
+const PORT_ATTEMPTS: u16 = 10;
+
+/// Timeout for each health request
+pub const HEALTH_REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// Checks whether local ports can be bound
+pub trait PortProbe: Send + Sync {
+    fn is_available(&self, port: u16) -> BoxFuture<'_, bool>;
+
+    /// Lowest available port among `attempts` ports from `start`
+    fn find_available(&self, start: u16, attempts: u16) -> BoxFuture<'_, Option<u16>> {
+        Box::pin(async move {
+            for port in (0..attempts).map_while(|offset| start.checked_add(offset)) {
+                if self.is_available(port).await {
+                    return Some(port);
+                }
+            }
+            None
+        })
+    }
+}
+
+/// Checks whether the backend on a port answers its health endpoint
+pub trait HealthProbe: Send + Sync {
+    /// Ok when healthy; otherwise why not
+    fn check(&self, port: u16) -> BoxFuture<'_, Result<(), String>>;
+}
+
+/// Starts child processes
+pub trait ProcessSpawner: Send + Sync {
+    fn spawn(&self, command: &mut Command) -> std::io::Result<Child>;
+}
+
+/// Ports on this machine
+pub struct SystemPorts;
+
+impl PortProbe for SystemPorts {
+    fn is_available(&self, port: u16) -> BoxFuture<'_, bool> {
+        Box::pin(port_utils::is_port_available_async(port))
+    }
+
+    fn find_available(&self, start: u16, attempts: u16) -> BoxFuture<'_, Option<u16>> {
+        Box::pin(port_utils::find_available_port_async(start, attempts))
+    }
+}
+
+/// Health checks over HTTP, through the shared backend client
+pub struct HttpHealth {
+    client: reqwest::Client,
+}
+
+impl HttpHealth {
+    pub fn new(client: reqwest::Client) -> Self {
+        Self { client }
+    }
+}
+
+impl HealthProbe for HttpHealth {
+    fn check(&self, port: u16) -> BoxFuture<'_, Result<(), String>> {
+        Box::pin(async move {
+            let response = self
+                .client
+                .get(format!("http://localhost:{}/api/health", port))
+                .timeout(HEALTH_REQUEST_TIMEOUT)
+                .send()
+                .await
+                .map_err(|e| e.to_string())?;
+            if !response.status().is_success() {
+                return Err(format!("Health check returned {}", response.status()));
+            }
+            Ok(())
+        })
+    }
+}
+
+/// Health probe in place until the HTTP clients exist; services never
+/// start before then
+struct NoClient;
+
+impl HealthProbe for NoClient {
+    fn check(&self, _port: u16) -> BoxFuture<'_, Result<(), String>> {
+        Box::pin(async { Err("HTTP client not initialized".to_string()) })
+    }
+}
+
+/// Real process spawning
+pub struct SystemSpawner;
+
+impl ProcessSpawner for SystemSpawner {
+    fn spawn(&self, command: &mut Command) -> std::io::Result<Child> {
+        command.spawn()
+    }
+}
+
+/// Everything service startup needs from outside the process
+#[derive(Clone)]
+pub struct StartupDeps {
+    pub ports: Arc<dyn PortProbe>,
+    pub health: Arc<dyn HealthProbe>,
+    pub spawner: Arc<dyn ProcessSpawner>,
+}
+
+impl Default for StartupDeps {
+    fn default() -> Self {
+        Self {
+            ports: Arc::new(SystemPorts),
+            health: Arc::new(NoClient),
+            spawner: Arc::new(SystemSpawner),
+        }
+    }
+}
+
+impl StartupDeps {
+    /// The real implementations, checking health with the shared backend client
+    pub fn system(backend_client: reqwest::Client) -> Self {
+        Self {
+            health: Arc::new(HttpHealth::new(backend_client)),
+            ..Self::default()
+        }
+    }
+}
+
+/// Claim `port`, or the first free one in `range` if it's taken
+///
+/// Without a range the next few ports after `port` are tried.
+/// `on_conflict` runs when the preferred port is in use.
+pub async fn claim_port(
+    ports: &dyn PortProbe,
+    port: u16,
+    range: Option<PortRange>,
+    service: &str,
+    on_conflict: impl FnOnce(),
+) -> Result<u16, AppError> {
+    if ports.is_available(port).await {
+        return Ok(port);
+    }
+    tracing::warn!("Port {} is in use, searching for alternative...", port);
+    on_conflict();
+
+    let range = range.unwrap_or(PortRange {
+        start: port.saturating_add(1),
+        end: port.saturating_add(PORT_ATTEMPTS),
+    });
+    match ports.find_available(range.start, range.count()).await {
+        Some(found) => {
+            tracing::info!("Found alternative {} port: {}", service, found);
+            Ok(found)
+        }
+        None => Err(AppError::Conflict(format!(
+            "Port {} is in use and no alternatives available in range {}-{}",
+            port, range.start, range.end
+        ))),
+    }
+}
+
+/// Spawn a service process
+pub fn spawn(spawner: &dyn ProcessSpawner, command: &mut Command) -> Result<Child, AppError> {
+    spawner
+        .spawn(command)
+        .map_err(|e| AppError::Backend(format!("Failed to spawn backend: {}", e)))
+}
+
+/// Poll the backend's health until it answers or `health_max_wait_secs`
+/// passes, returning how long it took
+///
+/// `on_attempt` is called before each check with the attempt number and
+/// the time waited so far.
+pub async fn wait_for_health(
+    health: &dyn HealthProbe,
+    port: u16,
+    config: &StartupConfig,
+    mut on_attempt: impl FnMut(u32, Duration),
+) -> Result<Duration, AppError> {
+    let start = Instant::now();
+    let max_duration = Duration::from_secs(config.health_max_wait_secs);
+    let mut attempt = 0;
+
+    loop {
+        attempt += 1;
+        on_attempt(attempt, start.elapsed());
+        match health.check(port).await {
+            Ok(()) => return Ok(start.elapsed()),
+            Err(e) => tracing::debug!("Backend not ready yet: {}", e),
+        }
+        if start.elapsed() >= max_duration {
+            break;
+        }
+
+        // Fast at first, then backing off while the backend runs migrations
+        let interval =
+            config.health_interval(start.elapsed(), attempt, false, startup::random_jitter());
+        tokio::time::sleep(interval).await;
+    }
+
+    Err(AppError::Backend(format!(
+        "Backend failed to start within {} seconds",
+        config.health_max_wait_secs
+    )))
+}
+
+// ============================================================
+// Test Fakes
+// ============================================================
+
+/// Fakes shared by these tests and the startup tests in lib.rs
+#[cfg(test)]
+pub(crate) mod fakes {
+    use super::*;
+    use std::collections::HashSet;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    pub(crate) struct FakePorts {
+        pub busy: HashSet<u16>,
+    }
+
+    impl PortProbe for FakePorts {
+        fn is_available(&self, port: u16) -> BoxFuture<'_, bool> {
+            let available = !self.busy.contains(&port);
+            Box::pin(async move { available })
+        }
+    }
+
+    /// Every port is taken
+    pub(crate) struct BusyPorts;
+
+    impl PortProbe for BusyPorts {
+        fn is_available(&self, _port: u16) -> BoxFuture<'_, bool> {
+            Box::pin(async { false })
+        }
+    }
+
+    /// Unhealthy for the first `slow_checks` checks, then healthy
+    pub(crate) struct FakeHealth {
+        slow_checks: u32,
+        checks: AtomicU32,
+    }
+
+    impl FakeHealth {
+        pub fn new(slow_checks: u32) -> Self {
+            Self {
+                slow_checks,
+                checks: AtomicU32::new(0),
+            }
+        }
+
+        pub fn checks(&self) -> u32 {
+            self.checks.load(Ordering::SeqCst)
+        }
+    }
+
+    impl HealthProbe for FakeHealth {
+        fn check(&self, _port: u16) -> BoxFuture<'_, Result<(), String>> {
+            let check = self.checks.fetch_add(1, Ordering::SeqCst);
+            Box::pin(async move {
+                if check < self.slow_checks {
+                    Err("connection refused".to_string())
+                } else {
+                    Ok(())
+                }
+            })
+        }
+    }
+
+    pub(crate) struct FailingSpawner;
+
+    impl ProcessSpawner for FailingSpawner {
+        fn spawn(&self, _command: &mut Command) -> std::io::Result<Child> {
+            Err(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                "no such file",
+            ))
+        }
+    }
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::fakes::*;
+    use super::*;
+    use std::collections::HashSet;
+
+    fn fast_config(max_wait_secs: u64) -> StartupConfig {
+        StartupConfig {
+            health_fast_interval_ms: 1,
+            health_jitter: 0.0,
+            health_max_wait_secs: max_wait_secs,
+            ..StartupConfig::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_claim_port() {
+        let ports = FakePorts {
+            busy: HashSet::from([5001, 5002]),
+        };
+        assert_eq!(
+            claim_port(&ports, 5000, None, "Backend", || panic!("no conflict"))
+                .await
+                .unwrap(),
+            5000
+        );
+
+        let mut conflicts = 0;
+        let port = claim_port(&ports, 5001, None, "Backend", || conflicts += 1)
+            .await
+            .unwrap();
+        assert_eq!(port, 5003);
+        assert_eq!(conflicts, 1);
+
+        // A configured range is searched instead of the next ports
+        let range = PortRange {
+            start: 6000,
+            end: 6010,
+        };
+        let port = claim_port(&ports, 5001, Some(range), "Backend", || {})
+            .await
+            .unwrap();
+        assert_eq!(port, 6000);
+    }
+
+    #[tokio::test]
+    async fn test_claim_port_exhausted() {
+        let ports = FakePorts {
+            busy: (5433..=5443).collect(),
+        };
+        let err = claim_port(&ports, 5433, None, "PostgreSQL", || {})
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::Conflict(_)));
+    }
+
+    #[test]
+    fn test_spawn_failure() {
+        let mut command = Command::new("secondbrain-api");
+        let err = spawn(&FailingSpawner, &mut command).unwrap_err();
+        assert!(matches!(err, AppError::Backend(ref m) if m.contains("no such file")));
+    }
+
+    #[tokio::test]
+    async fn test_slow_health() {
+        let health = FakeHealth::new(3);
+        let mut attempts = Vec::new();
+        wait_for_health(&health, 5001, &fast_config(30), |attempt, _| {
+            attempts.push(attempt)
+        })
+        .await
+        .unwrap();
+        assert_eq!(attempts, vec![1, 2, 3, 4]);
+    }
+
+    #[tokio::test]
+    async fn test_health_timeout() {
+        let health = FakeHealth::new(u32::MAX);
+        let err = wait_for_health(&health, 5001, &fast_config(0), |_, _| {})
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::Backend(_)));
+        // Checked once even with no time to wait
+        assert_eq!(health.checks(), 1);
+    }
+}
@@ -0,0 +1,626 @@
+//! Startup and health timelines, exported as traces.
+//!
+//! This module provides:
+//! - A timeline of each startup: every startup event and backend health
+//!   change, timed from the first event, with the last `TRACE_LIMIT`
+//!   startups kept in startup-trace.json
+//! - Spans derived from matching events: the whole startup, PostgreSQL, a
+//!   database upgrade, each backend start and each retry delay
+//! - `export_startup_trace`, which writes them as Chrome trace event JSON,
+//!   opened by chrome://tracing, Perfetto and speedscope
+//!
+//! `BackendWaiting` progress isn't kept; the backend span covers the wait.
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::config::{load_json, save_json_atomic};
+use crate::error::AppError;
+use crate::startup::StartupEvent;
+
+/// Startups kept in startup-trace.json
+const TRACE_LIMIT: usize = 5;
+
+/// Most entries kept per startup, so health changes over a long session
+/// don't grow the file without bound
+const MAX_ENTRIES: usize = 500;
+
+/// Persisted timelines in the app data directory
+const FILE: &str = "startup-trace.json";
+
+/// Something that happened during a startup
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Mark {
+    Startup {
+        event: StartupEvent,
+    },
+    Health {
+        healthy: bool,
+        error: Option<String>,
+    },
+}
+
+/// A mark and when it happened
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimelineEntry {
+    /// Time since the startup's first event (milliseconds)
+    pub at_ms: u64,
+    pub mark: Mark,
+}
+
+/// One startup, from its first event on
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StartupTimeline {
+    /// When the first event happened (Unix epoch milliseconds)
+    pub started_at: i64,
+    pub version: String,
+    pub entries: Vec<TimelineEntry>,
+}
+
+impl StartupTimeline {
+    fn first_event(&self) -> Option<&StartupEvent> {
+        self.entries.iter().find_map(|entry| match &entry.mark {
+            Mark::Startup { event } => Some(event),
+            Mark::Health { .. } => None,
+        })
+    }
+
+    /// Whether the startup has ended
+    fn is_finished(&self) -> bool {
+        let first = self.first_event();
+        self.entries.iter().any(|entry| match &entry.mark {
+            Mark::Startup { event } => ends_startup(first, event),
+            Mark::Health { .. } => false,
+        })
+    }
+}
+
+/// Whether `event` ends a startup that began with `first`: services are
+/// ready or gave up, or a backend started on its own is ready
+fn ends_startup(first: Option<&StartupEvent>, event: &StartupEvent) -> bool {
+    match event {
+        StartupEvent::AllServicesReady { .. } | StartupEvent::StartupFailed { .. } => true,
+        StartupEvent::BackendReady { .. } => {
+            matches!(first, Some(StartupEvent::BackendStarting { .. }))
+        }
+        _ => false,
+    }
+}
+
+/// Recent startups, oldest first
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StartupTraces {
+    pub startups: Vec<StartupTimeline>,
+}
+
+impl StartupTraces {
+    fn path(app_data_dir: &Path) -> PathBuf {
+        app_data_dir.join(FILE)
+    }
+
+    pub fn load(app_data_dir: &Path) -> Self {
+        load_json(&Self::path(app_data_dir)).unwrap_or_default()
+    }
+
+    fn save(&self, app_data_dir: &Path) -> Result<(), String> {
+        save_json_atomic(&Self::path(app_data_dir), self)
+    }
+
+    /// Add or replace a startup, dropping the oldest beyond `TRACE_LIMIT`
+    fn upsert(&mut self, timeline: StartupTimeline) {
+        match self
+            .startups
+            .iter_mut()
+            .find(|t| t.started_at == timeline.started_at)
+        {
+            Some(existing) => *existing = timeline,
+            None => self.startups.push(timeline),
+        }
+        let excess = self.startups.len().saturating_sub(TRACE_LIMIT);
+        self.startups.drain(..excess);
+    }
+}
+
+/// The startup being recorded, kept in Tauri state
+#[derive(Default)]
+pub struct Recorder {
+    current: Mutex<Option<(Instant, StartupTimeline)>>,
+}
+
+/// Whether an event begins a new startup once the last one has finished
+fn begins_startup(event: &StartupEvent) -> bool {
+    matches!(
+        event,
+        StartupEvent::PostgresStarting { .. } | StartupEvent::BackendStarting { .. }
+    )
+}
+
+pub fn init(app: &AppHandle) {
+    app.manage(Recorder::default());
+}
+
+fn append<R: Runtime>(app: &AppHandle<R>, mark: Mark, may_begin: bool) {
+    let Some(recorder) = app.try_state::<Recorder>() else {
+        return;
+    };
+    let timeline = {
+        let mut current = recorder.current.lock();
+        let stale = match current.as_ref() {
+            Some((_, timeline)) => timeline.is_finished(),
+            None => true,
+        };
+        if stale && may_begin {
+            *current = Some((
+                Instant::now(),
+                StartupTimeline {
+                    started_at: chrono::Utc::now().timestamp_millis(),
+                    version: app.package_info().version.to_string(),
+                    entries: Vec::new(),
+                },
+            ));
+        }
+        let Some((started, timeline)) = current.as_mut() else {
+            return;
+        };
+        if timeline.entries.len() >= MAX_ENTRIES {
+            return;
+        }
+        timeline.entries.push(TimelineEntry {
+            at_ms: started.elapsed().as_millis() as u64,
+            mark,
+        });
+        timeline.clone()
+    };
+
+    let Ok(app_data_dir) = app.path().app_data_dir() else {
+        return;
+    };
+    let mut traces = StartupTraces::load(&app_data_dir);
+    traces.upsert(timeline);
+    if let Err(e) = traces.save(&app_data_dir) {
+        tracing::warn!("Failed to write {}: {}", FILE, e);
+    }
+}
+
+/// Add a startup event to the current startup's timeline
+pub fn record<R: Runtime>(app: &AppHandle<R>, event: &StartupEvent) {
+    if matches!(event, StartupEvent::BackendWaiting { .. }) {
+        return;
+    }
+    let may_begin = begins_startup(event);
+    append(
+        app,
+        Mark::Startup {
+            event: event.clone(),
+        },
+        may_begin,
+    );
+}
+
+/// Add a backend health change to the current startup's timeline
+pub fn record_health(app: &AppHandle, healthy: bool, error: Option<String>) {
+    append(app, Mark::Health { healthy, error }, false);
+}
+
+/// Lane of the trace a span or mark is drawn in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Lane {
+    Startup = 1,
+    Postgres = 2,
+    Backend = 3,
+    Health = 4,
+}
+
+impl Lane {
+    const ALL: [Lane; 4] = [Lane::Startup, Lane::Postgres, Lane::Backend, Lane::Health];
+
+    fn name(self) -> &'static str {
+        match self {
+            Lane::Startup => "Startup",
+            Lane::Postgres => "PostgreSQL",
+            Lane::Backend => "Backend",
+            Lane::Health => "Health",
+        }
+    }
+}
+
+/// A stretch of time in a startup
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Span {
+    name: &'static str,
+    lane: Lane,
+    start_ms: u64,
+    duration_ms: u64,
+    /// False when the startup ended or the timeline stopped before it did
+    finished: bool,
+}
+
+/// A span that has started, by name
+type OpenSpan = (&'static str, Lane, u64);
+
+fn is_open(open: &[OpenSpan], name: &str) -> bool {
+    open.iter().any(|(n, _, _)| *n == name)
+}
+
+/// End the open span called `name`, if there is one
+fn close(spans: &mut Vec<Span>, open: &mut Vec<OpenSpan>, name: &str, at: u64) {
+    if let Some(index) = open.iter().position(|(n, _, _)| *n == name) {
+        let (name, lane, start_ms) = open.remove(index);
+        spans.push(Span {
+            name,
+            lane,
+            start_ms,
+            duration_ms: at.saturating_sub(start_ms),
+            finished: true,
+        });
+    }
+}
+
+/// Spans derived from a timeline's matching events
+fn spans(timeline: &StartupTimeline) -> Vec<Span> {
+    let mut spans = Vec::new();
+    let end_ms = timeline.entries.last().map_or(0, |entry| entry.at_ms);
+    let first = timeline.first_event();
+    let mut open: Vec<OpenSpan> = Vec::new();
+
+    if let Some(first) = timeline.entries.first() {
+        open.push(("startup", Lane::Startup, first.at_ms));
+    }
+    for entry in &timeline.entries {
+        let Mark::Startup { event } = &entry.mark else {
+            continue;
+        };
+        let at = entry.at_ms;
+        if ends_startup(first, event) {
+            close(&mut spans, &mut open, "startup", at);
+        }
+        match event {
+            StartupEvent::PostgresStarting { .. } if !is_open(&open, "postgres") => {
+                open.push(("postgres", Lane::Postgres, at));
+            }
+            StartupEvent::PostgresReady { .. } | StartupEvent::PostgresFailed { .. } => {
+                close(&mut spans, &mut open, "postgres", at);
+            }
+            StartupEvent::DatabaseUpgrading { .. } if !is_open(&open, "database_upgrade") => {
+                open.push(("database_upgrade", Lane::Postgres, at));
+            }
+            StartupEvent::DatabaseUpgraded { .. } => {
+                close(&mut spans, &mut open, "database_upgrade", at);
+            }
+            StartupEvent::BackendStarting { .. } if !is_open(&open, "backend") => {
+                open.push(("backend", Lane::Backend, at));
+            }
+            StartupEvent::BackendReady { .. } | StartupEvent::BackendFailed { .. } => {
+                close(&mut spans, &mut open, "backend", at);
+            }
+            StartupEvent::RetryingStartup { delay_ms, .. } => spans.push(Span {
+                name: "retry_delay",
+                lane: Lane::Startup,
+                start_ms: at,
+                duration_ms: *delay_ms,
+                finished: true,
+            }),
+            _ => {}
+        }
+    }
+    // Whatever is still open ran at least until the last entry
+    spans.extend(open.into_iter().map(|(name, lane, start_ms)| Span {
+        name,
+        lane,
+        start_ms,
+        duration_ms: end_ms.saturating_sub(start_ms),
+        finished: false,
+    }));
+    spans.sort_by_key(|span| (span.start_ms, std::cmp::Reverse(span.duration_ms)));
+    spans
+}
+
+/// Name, lane and details of a mark, for its instant event
+fn describe(mark: &Mark) -> (String, Lane, Value) {
+    match mark {
+        Mark::Startup { event } => {
+            let value = serde_json::to_value(event).unwrap_or(Value::Null);
+            let name = value["type"].as_str().unwrap_or("StartupEvent").to_string();
+            let lane = match event {
+                StartupEvent::PostgresStarting { .. }
+                | StartupEvent::PostgresReady { .. }
+                | StartupEvent::PostgresFailed { .. }
+                | StartupEvent::DatabaseUpgrading { .. }
+                | StartupEvent::DatabaseUpgraded { .. } => Lane::Postgres,
+                StartupEvent::BackendStarting { .. }
+                | StartupEvent::BackendReady { .. }
+                | StartupEvent::BackendFailed { .. }
+                | StartupEvent::BackendWaiting { .. }
+                | StartupEvent::BackendCrashed { .. }
+                | StartupEvent::BackendRestarted { .. } => Lane::Backend,
+                _ => Lane::Startup,
+            };
+            (name, lane, value["data"].clone())
+        }
+        Mark::Health { healthy, error } => (
+            if *healthy { "Healthy" } else { "Unhealthy" }.to_string(),
+            Lane::Health,
+            json!({ "error": error }),
+        ),
+    }
+}
+
+/// Chrome trace event JSON for the timelines, one process per startup
+fn chrome_trace(timelines: &[StartupTimeline]) -> Value {
+    let mut events = Vec::new();
+    for (index, timeline) in timelines.iter().enumerate() {
+        let pid = index + 1;
+        let started = chrono::DateTime::from_timestamp_millis(timeline.started_at)
+            .map(|at| at.to_rfc3339())
+            .unwrap_or_default();
+        events.push(json!({
+            "ph": "M", "name": "process_name", "pid": pid,
+            "args": { "name": format!("Startup {} (v{})", started, timeline.version) },
+        }));
+        events.push(json!({
+            "ph": "M", "name": "process_sort_index", "pid": pid,
+            "args": { "sort_index": pid },
+        }));
+        for lane in Lane::ALL {
+            events.push(json!({
+                "ph": "M", "name": "thread_name", "pid": pid, "tid": lane as u8,
+                "args": { "name": lane.name() },
+            }));
+        }
+        for span in spans(timeline) {
+            events.push(json!({
+                "ph": "X", "name": span.name, "cat": "startup",
+                "pid": pid, "tid": span.lane as u8,
+                "ts": span.start_ms * 1000, "dur": span.duration_ms * 1000,
+                "args": { "finished": span.finished },
+            }));
+        }
+        for entry in &timeline.entries {
+            let (name, lane, args) = describe(&entry.mark);
+            events.push(json!({
+                "ph": "i", "s": "t", "name": name, "cat": "event",
+                "pid": pid, "tid": lane as u8,
+                "ts": entry.at_ms * 1000, "args": args,
+            }));
+        }
+    }
+    json!({
+        "traceEvents": events,
+        "displayTimeUnit": "ms",
+        "otherData": { "startups": timelines.len() },
+    })
+}
+
+/// Summary of an exported trace
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StartupTraceExport {
+    pub path: String,
+    pub startups: usize,
+    pub events: usize,
+}
+
+/// Destination with a `.json` extension; its folder must exist
+fn trace_path(destination: &str) -> Result<PathBuf, AppError> {
+    let path = PathBuf::from(destination);
+    if !path.parent().is_some_and(Path::is_dir) {
+        return Err(AppError::NotFound(format!(
+            "Folder doesn't exist: {}",
+            path.display()
+        )));
+    }
+    let is_json = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+    Ok(if is_json {
+        path
+    } else {
+        path.with_extension("json")
+    })
+}
+
+// ============================================================
+// Commands
+// ============================================================
+
+/// Write the recent startup timelines as a trace for chrome://tracing,
+/// Perfetto or speedscope
+#[tauri::command]
+pub async fn export_startup_trace(
+    app: AppHandle,
+    destination: String,
+) -> Result<StartupTraceExport, AppError> {
+    let path = trace_path(&destination)?;
+    let traces = StartupTraces::load(&app.path().app_data_dir()?);
+    if traces.startups.is_empty() {
+        return Err(AppError::NotFound(
+            "No startups have been recorded yet".to_string(),
+        ));
+    }
+    let trace = chrome_trace(&traces.startups);
+    let events = trace["traceEvents"].as_array().map_or(0, Vec::len);
+    std::fs::write(&path, serde_json::to_vec(&trace)?)?;
+    tracing::info!(
+        "Exported {} startups as a trace to {:?}",
+        traces.startups.len(),
+        path
+    );
+    Ok(StartupTraceExport {
+        path: path.to_string_lossy().to_string(),
+        startups: traces.startups.len(),
+        events,
+    })
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(at_ms: u64, event: StartupEvent) -> TimelineEntry {
+        TimelineEntry {
+            at_ms,
+            mark: Mark::Startup { event },
+        }
+    }
+
+    fn timeline() -> StartupTimeline {
+        StartupTimeline {
+            started_at: 1_760_000_000_000,
+            version: "1.0.0".to_string(),
+            entries: vec![
+                entry(0, StartupEvent::PostgresStarting { port: 5433 }),
+                entry(
+                    1200,
+                    StartupEvent::PostgresReady {
+                        port: 5433,
+                        duration_ms: 1200,
+                    },
+                ),
+                entry(1300, StartupEvent::BackendStarting { port: 5001 }),
+                entry(
+                    2000,
+                    StartupEvent::BackendFailed {
+                        error: "exited".to_string(),
+                        port: 5001,
+                    },
+                ),
+                entry(
+                    2000,
+                    StartupEvent::RetryingStartup {
+                        service: "backend".to_string(),
+                        attempt: 1,
+                        max_attempts: 3,
+                        delay_ms: 500,
+                    },
+                ),
+                entry(2500, StartupEvent::BackendStarting { port: 5001 }),
+                entry(
+                    6500,
+                    StartupEvent::BackendReady {
+                        port: 5001,
+                        duration_ms: 4000,
+                    },
+                ),
+                entry(
+                    6600,
+                    StartupEvent::AllServicesReady {
+                        total_duration_ms: 6600,
+                    },
+                ),
+                TimelineEntry {
+                    at_ms: 9000,
+                    mark: Mark::Health {
+                        healthy: false,
+                        error: Some("timeout".to_string()),
+                    },
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_spans_pair_events() {
+        let spans: Vec<(&str, u64, u64)> = spans(&timeline())
+            .iter()
+            .map(|span| (span.name, span.start_ms, span.duration_ms))
+            .collect();
+        assert_eq!(
+            spans,
+            vec![
+                ("startup", 0, 6600),
+                ("postgres", 0, 1200),
+                ("backend", 1300, 700),
+                ("retry_delay", 2000, 500),
+                ("backend", 2500, 4000),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unfinished_spans_run_to_last_entry() {
+        let mut timeline = timeline();
+        timeline.entries.truncate(6);
+        let spans = spans(&timeline);
+        let backend = spans.iter().rfind(|span| span.name == "backend").unwrap();
+        assert_eq!((backend.start_ms, backend.duration_ms), (2500, 0));
+        assert!(!backend.finished);
+        assert!(!timeline.is_finished());
+    }
+
+    #[test]
+    fn test_backend_restart_is_its_own_startup() {
+        let restart = StartupTimeline {
+            started_at: 0,
+            version: "1.0.0".to_string(),
+            entries: vec![
+                entry(0, StartupEvent::BackendStarting { port: 5001 }),
+                entry(
+                    3000,
+                    StartupEvent::BackendReady {
+                        port: 5001,
+                        duration_ms: 3000,
+                    },
+                ),
+            ],
+        };
+        assert!(restart.is_finished());
+        let spans = spans(&restart);
+        assert_eq!(spans[0].name, "startup");
+        assert_eq!(spans[0].duration_ms, 3000);
+        assert!(spans.iter().all(|span| span.finished));
+
+        // Within a full startup, a ready backend doesn't end it
+        let mut full = timeline();
+        full.entries.truncate(7);
+        assert!(!full.is_finished());
+    }
+
+    #[test]
+    fn test_chrome_trace_events() {
+        let trace = chrome_trace(&[timeline()]);
+        let events = trace["traceEvents"].as_array().unwrap();
+        let complete: Vec<&Value> = events.iter().filter(|e| e["ph"] == "X").collect();
+        assert_eq!(complete.len(), 5);
+        assert_eq!(complete[0]["name"], "startup");
+        assert_eq!(complete[0]["dur"], 6_600_000);
+
+        let ready = events.iter().find(|e| e["name"] == "BackendReady").unwrap();
+        assert_eq!(ready["ph"], "i");
+        assert_eq!(ready["ts"], 6_500_000);
+        assert_eq!(ready["tid"], Lane::Backend as u8);
+        assert_eq!(ready["args"]["port"], 5001);
+
+        let health = events.iter().find(|e| e["name"] == "Unhealthy").unwrap();
+        assert_eq!(health["tid"], Lane::Health as u8);
+    }
+
+    #[test]
+    fn test_traces_keep_recent_startups() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut traces = StartupTraces::load(dir.path());
+        for i in 0..TRACE_LIMIT as i64 + 2 {
+            let mut timeline = timeline();
+            timeline.started_at = i;
+            traces.upsert(timeline);
+        }
+        // Updating the current startup replaces it
+        let mut latest = timeline();
+        latest.started_at = TRACE_LIMIT as i64 + 1;
+        latest.entries.truncate(1);
+        traces.upsert(latest);
+        traces.save(dir.path()).unwrap();
+
+        let traces = StartupTraces::load(dir.path());
+        assert_eq!(traces.startups.len(), TRACE_LIMIT);
+        assert_eq!(traces.startups[0].started_at, 2);
+        assert_eq!(traces.startups.last().unwrap().entries.len(), 1);
+    }
+}
@@ -0,0 +1,286 @@
+//! Binary streams to the webview over Tauri channels.
+//!
+//! This module provides:
+//! - `ByteStream`, sending raw chunks over an `ipc::Channel` instead of
+//!   building one large JSON string
+//! - Backpressure: at most `WINDOW` chunks are in flight until the frontend
+//!   acknowledges them with `ack_stream`
+//! - `stream_file` for files produced by exports, and cancellation with
+//!   `cancel_stream`
+//!
+//! A stream is identified by its channel id. Each chunk arrives as an
+//! `ArrayBuffer`; an empty chunk marks the end of the stream.
+
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::ipc::{Channel, InvokeResponseBody};
+use tauri::{AppHandle, Manager};
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+use tokio::sync::watch;
+
+use crate::error::AppError;
+
+/// Chunks sent ahead of the frontend's acknowledgements
+const WINDOW: u64 = 8;
+
+/// Size of each chunk
+pub const CHUNK_SIZE: usize = 256 * 1024;
+
+/// How long to wait for an acknowledgement before giving up
+const ACK_TIMEOUT: Duration = Duration::from_secs(30);
+
+struct StreamControl {
+    /// Chunks the frontend has acknowledged
+    acked: watch::Sender<u64>,
+    cancelled: AtomicBool,
+}
+
+/// Open streams and the files they may read, kept in Tauri state
+#[derive(Default)]
+pub struct StreamRegistry {
+    streams: Mutex<HashMap<u32, Arc<StreamControl>>>,
+    /// Files and folders written by exports this session
+    readable: Mutex<Vec<PathBuf>>,
+}
+
+impl StreamRegistry {
+    /// Allow `stream_file` to read `path`, or anything under it if a folder
+    pub fn allow_read(&self, path: &Path) {
+        if let Ok(path) = path.canonicalize() {
+            let mut readable = self.readable.lock();
+            if !readable.contains(&path) {
+                readable.push(path);
+            }
+        }
+    }
+
+    fn is_readable(&self, path: &Path) -> bool {
+        self.readable
+            .lock()
+            .iter()
+            .any(|allowed| path.starts_with(allowed))
+    }
+}
+
+/// Totals reported when a stream completes
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct StreamSummary {
+    pub stream_id: u32,
+    pub bytes: u64,
+    pub chunks: u64,
+}
+
+/// Sending half of a stream
+pub struct ByteStream {
+    app: AppHandle,
+    channel: Channel,
+    control: Arc<StreamControl>,
+    summary: StreamSummary,
+}
+
+impl ByteStream {
+    pub fn open(app: &AppHandle, channel: Channel) -> Self {
+        let (acked, _) = watch::channel(0);
+        let control = Arc::new(StreamControl {
+            acked,
+            cancelled: AtomicBool::new(false),
+        });
+        let stream_id = channel.id();
+        app.state::<StreamRegistry>()
+            .streams
+            .lock()
+            .insert(stream_id, control.clone());
+        Self {
+            app: app.clone(),
+            channel,
+            control,
+            summary: StreamSummary {
+                stream_id,
+                ..StreamSummary::default()
+            },
+        }
+    }
+
+    /// Send one chunk, waiting while the frontend is `WINDOW` chunks behind
+    pub async fn send(&mut self, bytes: Vec<u8>) -> Result<(), AppError> {
+        if bytes.is_empty() {
+            return Ok(());
+        }
+        let mut acked = self.control.acked.subscribe();
+        let sent = self.summary.chunks;
+        let wait = acked.wait_for(|acked| {
+            sent.saturating_sub(*acked) < WINDOW || self.control.cancelled.load(Ordering::SeqCst)
+        });
+        match tokio::time::timeout(ACK_TIMEOUT, wait).await {
+            Ok(Ok(_)) => {}
+            Ok(Err(_)) => return Err(AppError::Cancelled("Stream closed".to_string())),
+            Err(_) => {
+                return Err(AppError::Cancelled(
+                    "Stream stalled waiting for the frontend".to_string(),
+                ))
+            }
+        }
+        if self.control.cancelled.load(Ordering::SeqCst) {
+            return Err(AppError::Cancelled("Stream cancelled".to_string()));
+        }
+        let len = bytes.len() as u64;
+        self.channel
+            .send(InvokeResponseBody::Raw(bytes))
+            .map_err(|e| AppError::Internal(format!("Failed to send stream chunk: {}", e)))?;
+        self.summary.chunks += 1;
+        self.summary.bytes += len;
+        Ok(())
+    }
+
+    /// Send the end marker and return the totals
+    pub fn finish(self) -> Result<StreamSummary, AppError> {
+        self.channel
+            .send(InvokeResponseBody::Raw(Vec::new()))
+            .map_err(|e| AppError::Internal(format!("Failed to end stream: {}", e)))?;
+        Ok(self.summary.clone())
+    }
+}
+
+impl Drop for ByteStream {
+    fn drop(&mut self) {
+        self.app
+            .state::<StreamRegistry>()
+            .streams
+            .lock()
+            .remove(&self.summary.stream_id);
+    }
+}
+
+/// Send a file from `offset` in chunks
+pub async fn send_file(stream: &mut ByteStream, path: &Path, offset: u64) -> Result<(), AppError> {
+    let mut file = tokio::fs::File::open(path).await?;
+    if offset > 0 {
+        file.seek(std::io::SeekFrom::Start(offset)).await?;
+    }
+    loop {
+        let mut buffer = vec![0u8; CHUNK_SIZE];
+        let read = file.read(&mut buffer).await?;
+        if read == 0 {
+            return Ok(());
+        }
+        buffer.truncate(read);
+        stream.send(buffer).await?;
+    }
+}
+
+/// Split text into chunks on line boundaries where possible
+pub fn text_chunks(text: &str) -> Vec<Vec<u8>> {
+    let mut chunks = Vec::new();
+    let mut current = Vec::with_capacity(CHUNK_SIZE.min(text.len()));
+    for line in text.split_inclusive('\n') {
+        if !current.is_empty() && current.len() + line.len() > CHUNK_SIZE {
+            chunks.push(std::mem::take(&mut current));
+        }
+        current.extend_from_slice(line.as_bytes());
+    }
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+/// Acknowledge chunks received on a stream, letting more be sent
+#[tauri::command]
+pub async fn ack_stream(app: AppHandle, stream_id: u32, received: u64) -> Result<(), AppError> {
+    let control = app
+        .state::<StreamRegistry>()
+        .streams
+        .lock()
+        .get(&stream_id)
+        .cloned();
+    if let Some(control) = control {
+        control.acked.send_if_modified(|acked| {
+            let advanced = received > *acked;
+            if advanced {
+                *acked = received;
+            }
+            advanced
+        });
+    }
+    Ok(())
+}
+
+/// Stop a stream; the producing command returns a cancelled error
+#[tauri::command]
+pub async fn cancel_stream(app: AppHandle, stream_id: u32) -> Result<(), AppError> {
+    let control = app
+        .state::<StreamRegistry>()
+        .streams
+        .lock()
+        .get(&stream_id)
+        .cloned();
+    if let Some(control) = control {
+        control.cancelled.store(true, Ordering::SeqCst);
+        control.acked.send_modify(|_| {});
+    }
+    Ok(())
+}
+
+/// Stream a file written by an export to the webview
+#[tauri::command]
+pub async fn stream_file(
+    app: AppHandle,
+    path: String,
+    offset: Option<u64>,
+    on_chunk: Channel,
+) -> Result<StreamSummary, AppError> {
+    let path = Path::new(&path).canonicalize()?;
+    if !app.state::<StreamRegistry>().is_readable(&path) {
+        return Err(AppError::Permission(format!(
+            "{} was not produced by an export",
+            path.display()
+        )));
+    }
+    let mut stream = ByteStream::open(&app, on_chunk);
+    send_file(&mut stream, &path, offset.unwrap_or(0)).await?;
+    stream.finish()
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_text_chunks_split_on_lines() {
+        assert!(text_chunks("").is_empty());
+        assert_eq!(text_chunks("a\nb\n"), vec![b"a\nb\n".to_vec()]);
+
+        let line = format!("{}\n", "x".repeat(CHUNK_SIZE / 2));
+        let text = line.repeat(3);
+        let chunks = text_chunks(&text);
+        assert_eq!(chunks.len(), 3);
+        assert!(chunks.iter().all(|c| c.ends_with(b"\n")));
+        assert_eq!(chunks.concat(), text.into_bytes());
+    }
+
+    #[test]
+    fn test_readable_paths() {
+        let temp_dir = TempDir::new().unwrap();
+        let export_dir = temp_dir.path().join("export");
+        std::fs::create_dir_all(export_dir.join("Work")).unwrap();
+        std::fs::write(export_dir.join("Work/a.md"), "a").unwrap();
+        std::fs::write(temp_dir.path().join("secret.txt"), "s").unwrap();
+
+        let registry = StreamRegistry::default();
+        registry.allow_read(&export_dir);
+        let inside = export_dir.join("Work/a.md").canonicalize().unwrap();
+        let outside = temp_dir.path().join("secret.txt").canonicalize().unwrap();
+        assert!(registry.is_readable(&inside));
+        assert!(!registry.is_readable(&outside));
+    }
+}
@@ -0,0 +1,169 @@
+//! Token counting for prompt budgets and ingestion chunk sizing.
+//!
+//! This module provides:
+//! - Exact token counts for OpenAI models via tiktoken
+//! - Approximate counts for Anthropic, Gemini, and local models
+//! - Provider detection from model identifiers
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::AppError;
+
+/// Provider family inferred from a model identifier
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TokenizerFamily {
+    OpenAI,
+    Anthropic,
+    Gemini,
+    Other,
+}
+
+impl TokenizerFamily {
+    /// Infer the provider family from a model name
+    pub fn from_model(model: &str) -> Self {
+        let model = model.to_ascii_lowercase();
+
+        if model.starts_with("gpt-")
+            || model.starts_with("o1")
+            || model.starts_with("o3")
+            || model.starts_with("o4")
+            || model.starts_with("text-embedding-3")
+            || model.starts_with("text-embedding-ada")
+            || model.starts_with("chatgpt-")
+        {
+            TokenizerFamily::OpenAI
+        } else if model.starts_with("claude") {
+            TokenizerFamily::Anthropic
+        } else if model.starts_with("gemini") || model.starts_with("text-embedding-00") {
+            TokenizerFamily::Gemini
+        } else {
+            TokenizerFamily::Other
+        }
+    }
+}
+
+/// Result of a token count
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenCount {
+    /// Number of tokens in the text
+    pub tokens: usize,
+    /// Model the count was computed for
+    pub model: String,
+    /// Provider family used for counting
+    pub family: TokenizerFamily,
+    /// Whether the count comes from the model's own tokenizer
+    pub exact: bool,
+}
+
+/// Anthropic tokenizers produce slightly more tokens than cl100k for English text
+const ANTHROPIC_CL100K_RATIO: f64 = 1.1;
+
+/// Gemini averages roughly four characters per token
+const GEMINI_CHARS_PER_TOKEN: f64 = 4.0;
+
+/// Count tokens in `text` for the given model
+pub fn count_tokens_for_model(text: &str, model: &str) -> TokenCount {
+    let family = TokenizerFamily::from_model(model);
+
+    let (tokens, exact) = match family {
+        TokenizerFamily::OpenAI => match tiktoken_rs::bpe_for_model(model) {
+            Ok(bpe) => (bpe.encode_with_special_tokens(text).len(), true),
+            Err(_) => (
+                tiktoken_rs::o200k_base_singleton()
+                    .encode_with_special_tokens(text)
+                    .len(),
+                false,
+            ),
+        },
+        TokenizerFamily::Anthropic => {
+            let base = tiktoken_rs::cl100k_base_singleton()
+                .encode_with_special_tokens(text)
+                .len();
+            (
+                ((base as f64) * ANTHROPIC_CL100K_RATIO).ceil() as usize,
+                false,
+            )
+        }
+        TokenizerFamily::Gemini => (
+            ((text.chars().count() as f64) / GEMINI_CHARS_PER_TOKEN).ceil() as usize,
+            false,
+        ),
+        TokenizerFamily::Other => (
+            tiktoken_rs::cl100k_base_singleton()
+                .encode_with_special_tokens(text)
+                .len(),
+            false,
+        ),
+    };
+
+    TokenCount {
+        tokens,
+        model: model.to_string(),
+        family,
+        exact,
+    }
+}
+
+/// Count tokens for a model without a backend round trip
+#[tauri::command]
+pub async fn count_tokens(text: String, model: String) -> Result<TokenCount, AppError> {
+    tokio::task::spawn_blocking(move || count_tokens_for_model(&text, &model))
+        .await
+        .map_err(AppError::from)
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_family_from_model() {
+        assert_eq!(
+            TokenizerFamily::from_model("gpt-4o"),
+            TokenizerFamily::OpenAI
+        );
+        assert_eq!(
+            TokenizerFamily::from_model("claude-3-5-sonnet"),
+            TokenizerFamily::Anthropic
+        );
+        assert_eq!(
+            TokenizerFamily::from_model("gemini-1.5-pro"),
+            TokenizerFamily::Gemini
+        );
+        assert_eq!(
+            TokenizerFamily::from_model("llama3"),
+            TokenizerFamily::Other
+        );
+    }
+
+    #[test]
+    fn test_openai_count_is_exact() {
+        let count = count_tokens_for_model("Hello world", "gpt-4o");
+        assert!(count.exact);
+        assert_eq!(count.tokens, 2);
+    }
+
+    #[test]
+    fn test_anthropic_count_is_approximate() {
+        let count = count_tokens_for_model("Hello world", "claude-3-opus");
+        assert!(!count.exact);
+        assert!(count.tokens >= 2);
+    }
+
+    #[test]
+    fn test_gemini_count_uses_char_heuristic() {
+        let count = count_tokens_for_model("abcdefgh", "gemini-pro");
+        assert_eq!(count.tokens, 2);
+    }
+
+    #[test]
+    fn test_empty_text() {
+        let count = count_tokens_for_model("", "gpt-4o");
+        assert_eq!(count.tokens, 0);
+    }
+}
@@ -0,0 +1,396 @@
+//! Trash retention and purging of soft-deleted data.
+//!
+//! This module provides:
+//! - A daily job that permanently deletes items trashed longer than the
+//!   retention period
+//! - Trash statistics per item type
+//! - Emptying the whole trash, guarded by a short-lived confirmation token
+//!
+//! The backend only soft-deletes notes and conversations, so purging runs
+//! SQL directly against the embedded database. Note images and versions,
+//! and conversation messages, are removed by their foreign key cascades.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+
+use crate::backup::postgres_manager;
+use crate::config::{load_json, save_json_atomic};
+use crate::error::{database_not_running, AppError};
+use crate::scheduler::{JobAction, Schedule, ScheduledJob, Scheduler, TRASH_PURGE_JOB_ID};
+
+/// How long a confirmation token from `get_trash_stats` stays valid
+const CONFIRMATION_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// Trash retention settings
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrashSettings {
+    /// Purge expired items daily
+    pub auto_purge: bool,
+    /// Days an item stays in the trash before it can be purged
+    pub retention_days: u32,
+}
+
+impl Default for TrashSettings {
+    fn default() -> Self {
+        Self {
+            auto_purge: true,
+            retention_days: 30,
+        }
+    }
+}
+
+impl TrashSettings {
+    fn path(app_data_dir: &Path) -> PathBuf {
+        app_data_dir.join("trash-retention.json")
+    }
+
+    /// Load settings, returning defaults if missing or invalid
+    pub fn load(app_data_dir: &Path) -> Self {
+        load_json(&Self::path(app_data_dir)).unwrap_or_default()
+    }
+
+    /// Save settings atomically
+    pub fn save(&self, app_data_dir: &Path) -> Result<(), String> {
+        save_json_atomic(&Self::path(app_data_dir), self)
+    }
+
+    /// Validate settings
+    pub fn validate(&self) -> Result<(), String> {
+        if !(1..=3650).contains(&self.retention_days) {
+            return Err("Retention must be between 1 and 3650 days".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Trash contents of one item type
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrashCategory {
+    pub count: u64,
+    /// Items older than the retention period
+    pub expired: u64,
+    /// Deletion time of the oldest item (RFC 3339)
+    pub oldest_deleted_at: Option<String>,
+}
+
+/// Trash statistics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashStats {
+    pub notes: TrashCategory,
+    pub conversations: TrashCategory,
+    pub retention_days: u32,
+    /// Pass to `empty_trash_now` to confirm; valid for five minutes
+    pub confirmation_token: String,
+}
+
+/// Items permanently deleted by a purge
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PurgeReport {
+    pub notes: u64,
+    pub conversations: u64,
+}
+
+/// Managed state for trash operations
+#[derive(Default)]
+pub struct TrashManager {
+    /// Token issued with the last stats, and when
+    token: Mutex<Option<(String, Instant)>>,
+}
+
+impl TrashManager {
+    fn issue_token(&self) -> Result<String, String> {
+        let mut bytes = [0u8; 16];
+        getrandom::fill(&mut bytes).map_err(|e| format!("Failed to generate token: {}", e))?;
+        let token: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+        *self.token.lock().unwrap() = Some((token.clone(), Instant::now()));
+        Ok(token)
+    }
+
+    /// Check and consume a token; each token confirms one operation
+    fn consume_token(&self, token: &str) -> Result<(), String> {
+        match self.token.lock().unwrap().take() {
+            Some((expected, issued)) if expected == token => {
+                if issued.elapsed() > CONFIRMATION_TTL {
+                    Err("Confirmation expired, review the trash again".to_string())
+                } else {
+                    Ok(())
+                }
+            }
+            _ => Err("Invalid confirmation token".to_string()),
+        }
+    }
+}
+
+/// SQL condition for items trashed more than `days` ago, or any trashed item
+///
+/// Rows soft-deleted before `deleted_at` existed fall back to `updated_at`.
+fn expired_condition(days: Option<u32>) -> String {
+    match days {
+        Some(days) => format!(
+            "is_deleted AND COALESCE(deleted_at, updated_at) < now() - make_interval(days => {})",
+            days
+        ),
+        None => "is_deleted".to_string(),
+    }
+}
+
+fn stats_sql(retention_days: u32) -> String {
+    let expired = expired_condition(Some(retention_days));
+    let category = |table: &str| {
+        format!(
+            "SELECT '{table}', count(*), count(*) FILTER (WHERE {expired}), \
+             to_char(min(deleted_at) AT TIME ZONE 'UTC', 'YYYY-MM-DD\"T\"HH24:MI:SS\"Z\"') \
+             FROM {table} WHERE is_deleted"
+        )
+    };
+    format!(
+        "{} UNION ALL {}",
+        category("notes"),
+        category("chat_conversations")
+    )
+}
+
+fn purge_sql(older_than_days: Option<u32>) -> String {
+    let condition = expired_condition(older_than_days);
+    format!(
+        "WITH purged AS (SELECT id FROM notes WHERE {condition}), \
+         embeddings AS (DELETE FROM note_embeddings WHERE note_id IN (SELECT id FROM purged)), \
+         notes_deleted AS (DELETE FROM notes WHERE id IN (SELECT id FROM purged) RETURNING 1), \
+         conversations_deleted AS (DELETE FROM chat_conversations WHERE {condition} RETURNING 1) \
+         SELECT (SELECT count(*) FROM notes_deleted), (SELECT count(*) FROM conversations_deleted)"
+    )
+}
+
+fn parse_category(line: &str) -> Result<(String, TrashCategory), String> {
+    let fields: Vec<&str> = line.split('|').collect();
+    let [table, count, expired, oldest] = fields[..] else {
+        return Err(format!("Unexpected trash stats row: {}", line));
+    };
+    let number = |s: &str| {
+        s.parse::<u64>()
+            .map_err(|_| format!("Unexpected trash stats row: {}", line))
+    };
+    let category = TrashCategory {
+        count: number(count)?,
+        expired: number(expired)?,
+        oldest_deleted_at: (!oldest.is_empty()).then(|| oldest.to_string()),
+    };
+    Ok((table.to_string(), category))
+}
+
+fn parse_stats(output: &str) -> Result<(TrashCategory, TrashCategory), String> {
+    let mut notes = None;
+    let mut conversations = None;
+    for line in output.lines().filter(|l| !l.trim().is_empty()) {
+        match parse_category(line)? {
+            (table, category) if table == "notes" => notes = Some(category),
+            (table, category) if table == "chat_conversations" => conversations = Some(category),
+            (table, _) => return Err(format!("Unexpected trash table: {}", table)),
+        }
+    }
+    notes
+        .zip(conversations)
+        .ok_or_else(|| "Incomplete trash stats".to_string())
+}
+
+fn parse_purge(output: &str) -> Result<PurgeReport, String> {
+    let line = output.trim();
+    let (notes, conversations) = line
+        .split_once('|')
+        .ok_or_else(|| format!("Unexpected purge result: {}", line))?;
+    let number = |s: &str| {
+        s.parse::<u64>()
+            .map_err(|_| format!("Unexpected purge result: {}", line))
+    };
+    Ok(PurgeReport {
+        notes: number(notes)?,
+        conversations: number(conversations)?,
+    })
+}
+
+async fn purge(app: &AppHandle, older_than_days: Option<u32>) -> Result<PurgeReport, String> {
+    let manager = postgres_manager(app).ok_or_else(|| "Database is not running".to_string())?;
+    let report = tokio::task::spawn_blocking(move || {
+        parse_purge(&manager.run_sql(&purge_sql(older_than_days))?)
+    })
+    .await
+    .map_err(|e| format!("Task panicked: {}", e))??;
+
+    tracing::info!(
+        "Purged {} note(s) and {} conversation(s) from the trash",
+        report.notes,
+        report.conversations
+    );
+    Ok(report)
+}
+
+/// Scheduler entry point: purge items past the retention period
+pub async fn purge_expired(app: &AppHandle) -> Result<(), String> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let settings = TrashSettings::load(&app_data_dir);
+    purge(app, Some(settings.retention_days)).await.map(|_| ())
+}
+
+/// Register or remove the purge job to match settings
+pub fn apply_settings(app: &AppHandle, settings: &TrashSettings) {
+    let scheduler = app.state::<Scheduler>();
+    if !settings.auto_purge {
+        scheduler.remove_job(TRASH_PURGE_JOB_ID);
+        return;
+    }
+    scheduler.upsert_job(
+        ScheduledJob {
+            id: TRASH_PURGE_JOB_ID.to_string(),
+            name: "Trash retention purge".to_string(),
+            schedule: Schedule::Daily {
+                hour: 3,
+                minute: 30,
+            },
+            skip_on_battery: true,
+            wait_for_idle: true,
+            jitter_secs: 600,
+            action: JobAction::PurgeTrash,
+        },
+        chrono::Local::now(),
+    );
+}
+
+/// Load persisted settings and register the purge job if enabled
+pub fn start(app: &AppHandle) {
+    if let Ok(app_data_dir) = app.path().app_data_dir() {
+        apply_settings(app, &TrashSettings::load(&app_data_dir));
+    }
+}
+
+// ============================================================
+// Commands
+// ============================================================
+
+/// Get the trash retention settings
+#[tauri::command]
+pub async fn get_trash_settings(app: AppHandle) -> Result<TrashSettings, AppError> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    Ok(TrashSettings::load(&app_data_dir))
+}
+
+/// Update the trash retention settings
+#[tauri::command]
+pub async fn set_trash_settings(app: AppHandle, settings: TrashSettings) -> Result<(), AppError> {
+    settings.validate()?;
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    settings.save(&app_data_dir)?;
+    apply_settings(&app, &settings);
+    Ok(())
+}
+
+/// Get trash statistics and a confirmation token for `empty_trash_now`
+#[tauri::command]
+pub async fn get_trash_stats(app: AppHandle) -> Result<TrashStats, AppError> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let retention_days = TrashSettings::load(&app_data_dir).retention_days;
+    let manager = postgres_manager(&app).ok_or_else(database_not_running)?;
+
+    let (notes, conversations) = tokio::task::spawn_blocking(move || {
+        parse_stats(&manager.run_sql(&stats_sql(retention_days))?)
+    })
+    .await
+    .map_err(|e| format!("Task panicked: {}", e))??;
+
+    Ok(TrashStats {
+        notes,
+        conversations,
+        retention_days,
+        confirmation_token: app.state::<TrashManager>().issue_token()?,
+    })
+}
+
+/// Permanently delete everything in the trash
+///
+/// Requires the token from a recent `get_trash_stats` call, so the user has
+/// seen what will be deleted.
+#[tauri::command]
+pub async fn empty_trash_now(
+    app: AppHandle,
+    confirmation_token: String,
+) -> Result<PurgeReport, AppError> {
+    app.state::<TrashManager>()
+        .consume_token(&confirmation_token)?;
+    purge(&app, None).await.map_err(AppError::from)
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_confirmation_token_is_single_use() {
+        let manager = TrashManager::default();
+        assert!(manager.consume_token("anything").is_err());
+
+        let token = manager.issue_token().unwrap();
+        assert!(manager.consume_token("wrong").is_err());
+        // A wrong guess invalidates the token
+        assert!(manager.consume_token(&token).is_err());
+
+        let token = manager.issue_token().unwrap();
+        assert!(manager.consume_token(&token).is_ok());
+        assert!(manager.consume_token(&token).is_err());
+    }
+
+    #[test]
+    fn test_confirmation_token_expires() {
+        let manager = TrashManager::default();
+        *manager.token.lock().unwrap() = Some((
+            "t".to_string(),
+            Instant::now() - CONFIRMATION_TTL - Duration::from_secs(1),
+        ));
+        assert!(manager.consume_token("t").unwrap_err().contains("expired"));
+    }
+
+    #[test]
+    fn test_purge_sql_respects_retention() {
+        let sql = purge_sql(Some(30));
+        assert!(sql.contains("make_interval(days => 30)"));
+        assert!(sql.contains("DELETE FROM note_embeddings"));
+        assert!(!purge_sql(None).contains("make_interval"));
+    }
+
+    #[test]
+    fn test_parse_results() {
+        let (notes, conversations) =
+            parse_stats("chat_conversations|0|0|\nnotes|12|3|2024-01-02T03:04:05Z\n").unwrap();
+        assert_eq!(notes.count, 12);
+        assert_eq!(notes.expired, 3);
+        assert_eq!(
+            notes.oldest_deleted_at.as_deref(),
+            Some("2024-01-02T03:04:05Z")
+        );
+        assert_eq!(conversations, TrashCategory::default());
+        assert!(parse_stats("garbage").is_err());
+
+        assert_eq!(
+            parse_purge("4|1\n").unwrap(),
+            PurgeReport {
+                notes: 4,
+                conversations: 1
+            }
+        );
+    }
+
+    #[test]
+    fn test_settings_validation() {
+        assert!(TrashSettings::default().validate().is_ok());
+        let settings = TrashSettings {
+            retention_days: 0,
+            ..Default::default()
+        };
+        assert!(settings.validate().is_err());
+    }
+}
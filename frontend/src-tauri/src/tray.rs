@@ -0,0 +1,950 @@
+//! System tray icon and menu.
+//!
+//! This module provides:
+//! - Deferred tray construction, once the main window is on screen, so
+//!   building the menu never delays the first paint or service startup
+//! - A cache of decoded icon images
+//! - A service status line, the last backup time and a recent notes submenu
+//!   that are updated in place, touching only the section whose content
+//!   changed
+//! - A tooltip, and optionally a title beside the icon, with live activity
+//!   such as "Backend restarting…" or "3 jobs queued"
+//! - A badge counting notices routed to the tray, cleared when the main
+//!   window is focused
+//! - A monochrome icon that's a template on macOS and matches the light or
+//!   dark taskbar elsewhere, or the colored icon, saved in tray.json
+//! - A Launch at Login checkbox, kept in step with `login_item`
+//!
+//! Updates that arrive before the tray exists are kept and applied when it
+//! is built.
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::image::Image;
+use tauri::menu::{
+    CheckMenuItem, IsMenuItem, Menu, MenuEvent, MenuItem, PredefinedMenuItem, Submenu,
+};
+use tauri::tray::TrayIconBuilder;
+use tauri::{AppHandle, Emitter, Manager, Runtime, Wry};
+
+use crate::config::{load_json, save_json_atomic, ServiceConfig};
+use crate::error::AppError;
+use crate::health::HealthMonitor;
+use crate::scheduler::{JobStatus, Scheduler};
+use crate::services::{ServiceCommand, ServiceManager, ServicePhase, ServiceState};
+use crate::AppState;
+
+/// Tray icon, relative to the resource directory; its shape is also used
+/// for the monochrome variants
+const TRAY_ICON: &str = "icons/tray/tray-icon.png";
+
+/// Status line when nothing needs attention
+const IDLE_STATUS: &str = "Services running";
+
+/// How often job activity is refreshed
+const JOBS_POLL: Duration = Duration::from_secs(5);
+
+/// Longest the tray waits for the main window before being built anyway
+const WINDOW_WAIT: Duration = Duration::from_secs(3);
+
+/// How often the window's visibility is checked while waiting
+const WINDOW_POLL: Duration = Duration::from_millis(50);
+
+/// Most recent notes listed in the tray
+const MAX_RECENT_NOTES: usize = 8;
+
+/// Menu ID prefix for recent note items
+const RECENT_NOTE_PREFIX: &str = "recent_note:";
+
+/// A note listed in the tray's recent notes submenu
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecentNote {
+    pub id: String,
+    pub title: String,
+}
+
+/// Which tray icon to show
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrayIconStyle {
+    /// A template on macOS; elsewhere a single-color glyph matching the
+    /// taskbar theme
+    #[default]
+    Monochrome,
+    /// The app's colors on every platform
+    Color,
+}
+
+/// Tray appearance settings
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TraySettings {
+    pub icon_style: TrayIconStyle,
+    /// Show activity beside the icon, on platforms with tray titles
+    pub show_status_title: bool,
+}
+
+impl TraySettings {
+    fn path(app_data_dir: &Path) -> PathBuf {
+        app_data_dir.join("tray.json")
+    }
+
+    /// Load settings, returning defaults if missing or invalid
+    pub fn load(app_data_dir: &Path) -> Self {
+        load_json(&Self::path(app_data_dir)).unwrap_or_default()
+    }
+
+    /// Save settings atomically
+    pub fn save(&self, app_data_dir: &Path) -> Result<(), String> {
+        save_json_atomic(&Self::path(app_data_dir), self)
+    }
+}
+
+/// Dynamic parts of the tray
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct TrayContent {
+    status: String,
+    tooltip: String,
+    /// Text beside the icon; empty hides it
+    title: String,
+    /// Notices since the main window was last focused
+    badge: usize,
+    recent: Vec<RecentNote>,
+    last_backup: String,
+    /// Focus session countdown, while one runs
+    focus: Option<String>,
+    launch_at_login: bool,
+}
+
+/// Menu items that change after the tray is built
+struct TrayHandles {
+    status: MenuItem<Wry>,
+    last_backup: MenuItem<Wry>,
+    recent: Submenu<Wry>,
+    launch_at_login: CheckMenuItem<Wry>,
+    /// Content currently shown
+    shown: TrayContent,
+}
+
+/// Tray state kept in Tauri state
+#[derive(Default)]
+pub struct TrayState {
+    icons: Mutex<HashMap<String, Image<'static>>>,
+    settings: Mutex<TraySettings>,
+    /// Latest content, shown once the tray exists
+    content: Mutex<TrayContent>,
+    handles: Mutex<Option<TrayHandles>>,
+}
+
+/// Load an icon from the resource directory, decoding each file only once
+pub fn cached_icon(app: &AppHandle, relative: &str) -> Option<Image<'static>> {
+    let state = app.state::<TrayState>();
+    if let Some(icon) = state.icons.lock().get(relative) {
+        return Some(icon.clone());
+    }
+    let path = app.path().resource_dir().ok()?.join(relative);
+    if !path.exists() {
+        return None;
+    }
+    match Image::from_path(&path) {
+        Ok(icon) => {
+            tracing::debug!("Loaded icon {:?}", path);
+            state
+                .icons
+                .lock()
+                .insert(relative.to_string(), icon.clone());
+            Some(icon)
+        }
+        Err(e) => {
+            tracing::warn!("Failed to load icon {:?}: {}", path, e);
+            None
+        }
+    }
+}
+
+/// Tray icon in the given style, and whether it's a template
+fn tray_icon(app: &AppHandle, style: TrayIconStyle) -> Option<(Image<'static>, bool)> {
+    let icon = cached_icon(app, TRAY_ICON).or_else(|| app.default_window_icon().cloned())?;
+    if style == TrayIconStyle::Color {
+        return Some((icon, false));
+    }
+    if cfg!(target_os = "macos") {
+        return Some((icon, true));
+    }
+
+    // Without template rendering, draw the shape in whichever color stands
+    // out against the taskbar
+    let light = taskbar_is_dark(app);
+    let key = format!("{}#{}", TRAY_ICON, if light { "light" } else { "dark" });
+    let state = app.state::<TrayState>();
+    let mut icons = state.icons.lock();
+    let glyph = icons.entry(key).or_insert_with(|| {
+        Image::new_owned(glyph_rgba(icon.rgba(), light), icon.width(), icon.height())
+    });
+    Some((glyph.clone(), false))
+}
+
+/// Recolor every pixel white or black, keeping the icon's shape
+fn glyph_rgba(rgba: &[u8], light: bool) -> Vec<u8> {
+    let value = if light { u8::MAX } else { 0 };
+    rgba.chunks_exact(4)
+        .flat_map(|pixel| [value, value, value, pixel[3]])
+        .collect()
+}
+
+/// Whether the taskbar or panel holding the tray is dark
+fn taskbar_is_dark(app: &AppHandle) -> bool {
+    // Windows themes the taskbar separately from apps, and it's dark by
+    // default even when apps are light
+    #[cfg(windows)]
+    {
+        let _ = app;
+        std::process::Command::new("reg")
+            .args([
+                "query",
+                r"HKCU\Software\Microsoft\Windows\CurrentVersion\Themes\Personalize",
+                "/v",
+                "SystemUsesLightTheme",
+            ])
+            .output()
+            .map_or(true, |output| {
+                !String::from_utf8_lossy(&output.stdout).contains("0x1")
+            })
+    }
+
+    #[cfg(not(windows))]
+    {
+        app.get_webview_window("main")
+            .and_then(|window| window.theme().ok())
+            .map_or(false, |theme| theme == tauri::Theme::Dark)
+    }
+}
+
+/// Show the icon for the current settings and theme
+fn apply_icon(app: &AppHandle) {
+    let Some(tray) = app.tray_by_id("main") else {
+        return;
+    };
+    let style = app.state::<TrayState>().settings.lock().icon_style;
+    let Some((icon, template)) = tray_icon(app, style) else {
+        return;
+    };
+    if let Err(e) = tray
+        .set_icon(Some(icon))
+        .and_then(|()| tray.set_icon_as_template(template))
+    {
+        tracing::warn!("Failed to update tray icon: {}", e);
+    }
+}
+
+/// Switch icon variants when the system theme changes
+pub fn on_theme_changed(app: &AppHandle) {
+    if app.try_state::<TrayState>().is_some() {
+        apply_icon(app);
+    }
+}
+
+/// Status line for the current service and health state
+fn status_label(state: &ServiceState, healthy: bool) -> String {
+    match state.busy {
+        Some(ServiceCommand::RestartBackend) => return "Backend restarting…".to_string(),
+        Some(ServiceCommand::RestartDatabase) => return "Database restarting…".to_string(),
+        _ => {}
+    }
+    let status = match (state.postgres, state.backend) {
+        (ServicePhase::Failed, _) => "Database failed to start",
+        (_, ServicePhase::Failed) => "Backend stopped unexpectedly",
+        (ServicePhase::Stopping, _) | (_, ServicePhase::Stopping) => "Stopping services…",
+        (ServicePhase::Running, ServicePhase::Running) if healthy => IDLE_STATUS,
+        (ServicePhase::Running, ServicePhase::Running) => "Backend not responding",
+        (ServicePhase::Stopped, ServicePhase::Stopped) => "Services stopped",
+        _ => "Starting services…",
+    };
+    status.to_string()
+}
+
+/// Summary of scheduled jobs running or held back, if there are any
+fn jobs_label(jobs: &[JobStatus]) -> Option<String> {
+    let running = jobs.iter().filter(|job| job.running).count();
+    let queued = jobs
+        .iter()
+        .filter(|job| !job.running && job.deferred_reason.is_some())
+        .count();
+    let noun = |count: usize| if count == 1 { "job" } else { "jobs" };
+    match (running, queued) {
+        (0, 0) => None,
+        (running, 0) => Some(format!("{} {} running", running, noun(running))),
+        (0, queued) => Some(format!("{} {} queued", queued, noun(queued))),
+        (running, queued) => Some(format!(
+            "{} {} running, {} queued",
+            running,
+            noun(running),
+            queued
+        )),
+    }
+}
+
+/// Tooltip and title for the status line, job activity, badge and focus
+/// countdown
+fn activity_text(
+    status: &str,
+    jobs: Option<&str>,
+    badge: usize,
+    focus: Option<&str>,
+    show_title: bool,
+) -> (String, String) {
+    let notices = (badge > 0).then(|| {
+        format!(
+            "{} new {}",
+            badge,
+            if badge == 1 { "notice" } else { "notices" }
+        )
+    });
+    let mut tooltip = vec![status];
+    tooltip.extend(jobs);
+    tooltip.extend(notices.as_deref());
+    tooltip.extend(focus);
+
+    // The badge and countdown show even without the status title
+    let mut title = Vec::new();
+    title.extend(focus);
+    let badge_text = format!("● {}", badge);
+    if badge > 0 {
+        title.push(badge_text.as_str());
+    }
+    if show_title {
+        if status != IDLE_STATUS {
+            title.push(status);
+        }
+        title.extend(jobs);
+    }
+    (
+        format!("Second Brain — {}", tooltip.join(" · ")),
+        title.join(" · "),
+    )
+}
+
+/// Menu line for the last local backup (Unix epoch seconds)
+fn backup_label(last_backup_at: Option<u64>) -> String {
+    last_backup_at
+        .and_then(|at| chrono::DateTime::from_timestamp(at as i64, 0))
+        .map(|at| {
+            format!(
+                "Last Backup: {}",
+                at.with_timezone(&chrono::Local).format("%b %-d, %H:%M")
+            )
+        })
+        .unwrap_or_else(|| "No Backups Yet".to_string())
+}
+
+fn label_for_note(note: &RecentNote) -> String {
+    let title = note.title.trim();
+    if title.is_empty() {
+        "Untitled".to_string()
+    } else if title.chars().count() > 40 {
+        format!("{}…", title.chars().take(39).collect::<String>())
+    } else {
+        title.to_string()
+    }
+}
+
+fn fill_recent(submenu: &Submenu<Wry>, notes: &[RecentNote]) -> tauri::Result<()> {
+    let app = submenu.app_handle();
+    for item in submenu.items()? {
+        submenu.remove(&item)?;
+    }
+    if notes.is_empty() {
+        let none = MenuItem::with_id(app, "recent_none", "No Recent Notes", false, None::<&str>)?;
+        submenu.append(&none)?;
+    }
+    for note in notes {
+        let id = format!("{}{}", RECENT_NOTE_PREFIX, note.id);
+        let item = MenuItem::with_id(app, id, label_for_note(note), true, None::<&str>)?;
+        submenu.append(&item)?;
+    }
+    Ok(())
+}
+
+/// Show the latest content, updating only sections that changed
+fn refresh<R: Runtime>(app: &AppHandle<R>) {
+    let state = app.state::<TrayState>();
+    let content = state.content.lock().clone();
+    let mut handles = state.handles.lock();
+    let Some(handles) = handles.as_mut() else {
+        return;
+    };
+    if handles.shown.status != content.status {
+        if let Err(e) = handles.status.set_text(&content.status) {
+            tracing::warn!("Failed to update tray status: {}", e);
+        }
+    }
+    if handles.shown.last_backup != content.last_backup {
+        if let Err(e) = handles.last_backup.set_text(&content.last_backup) {
+            tracing::warn!("Failed to update last backup in tray: {}", e);
+        }
+    }
+    if handles.shown.tooltip != content.tooltip || handles.shown.title != content.title {
+        if let Some(tray) = app.tray_by_id("main") {
+            let title = (!content.title.is_empty()).then_some(content.title.as_str());
+            if let Err(e) = tray
+                .set_tooltip(Some(&content.tooltip))
+                .and_then(|()| tray.set_title(title))
+            {
+                tracing::warn!("Failed to update tray tooltip: {}", e);
+            }
+        }
+    }
+    if handles.shown.recent != content.recent {
+        if let Err(e) = fill_recent(&handles.recent, &content.recent) {
+            tracing::warn!("Failed to update recent notes in tray: {}", e);
+        }
+    }
+    if handles.shown.launch_at_login != content.launch_at_login {
+        if let Err(e) = handles.launch_at_login.set_checked(content.launch_at_login) {
+            tracing::warn!("Failed to update Launch at Login in tray: {}", e);
+        }
+    }
+    handles.shown = content;
+}
+
+/// Recompute the status line and activity from the service, health and
+/// job state
+pub fn refresh_status<R: Runtime>(app: &AppHandle<R>) {
+    let (Some(services), Some(state)) = (
+        app.try_state::<ServiceManager>(),
+        app.try_state::<TrayState>(),
+    ) else {
+        return;
+    };
+    let healthy = app
+        .try_state::<HealthMonitor>()
+        .map_or(true, |monitor| monitor.is_healthy());
+    let status = status_label(&services.subscribe().borrow(), healthy);
+    let jobs = app
+        .try_state::<Scheduler>()
+        .and_then(|scheduler| jobs_label(&scheduler.list()));
+    let show_title = state.settings.lock().show_status_title;
+    {
+        let mut content = state.content.lock();
+        let (tooltip, title) = activity_text(
+            &status,
+            jobs.as_deref(),
+            content.badge,
+            content.focus.as_deref(),
+            show_title,
+        );
+        content.status = status;
+        content.tooltip = tooltip;
+        content.title = title;
+    }
+    refresh(app);
+}
+
+/// Show the time of the last local backup
+pub fn set_last_backup(app: &AppHandle, last_backup_at: Option<u64>) {
+    let Some(state) = app.try_state::<TrayState>() else {
+        return;
+    };
+    state.content.lock().last_backup = backup_label(last_backup_at);
+    refresh(app);
+}
+
+/// Check or uncheck Launch at Login
+pub fn set_launch_at_login(app: &AppHandle, enabled: bool) {
+    let Some(state) = app.try_state::<TrayState>() else {
+        return;
+    };
+    state.content.lock().launch_at_login = enabled;
+    refresh(app);
+}
+
+/// Toggle launching at login from the checkbox, which has already flipped
+fn toggle_launch_at_login(app: &AppHandle) {
+    let enabled = !app.state::<TrayState>().content.lock().launch_at_login;
+    let app = app.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        if let Err(e) = crate::login_item::set(&app, enabled) {
+            tracing::warn!("Failed to change Launch at Login: {}", e);
+            // Put the checkbox back to match the setting
+            let state = app.state::<TrayState>();
+            let checked = state.content.lock().launch_at_login;
+            if let Some(handles) = state.handles.lock().as_ref() {
+                let _ = handles.launch_at_login.set_checked(checked);
+            }
+        }
+    });
+}
+
+/// Show a focus session's countdown beside the icon, or clear it with None
+pub fn set_focus_countdown(app: &AppHandle, countdown: Option<String>) {
+    let Some(state) = app.try_state::<TrayState>() else {
+        return;
+    };
+    state.content.lock().focus = countdown;
+    refresh_status(app);
+}
+
+/// Count a notice on the tray badge
+pub fn add_badge<R: Runtime>(app: &AppHandle<R>) {
+    let Some(state) = app.try_state::<TrayState>() else {
+        return;
+    };
+    let badge = {
+        let mut content = state.content.lock();
+        content.badge += 1;
+        content.badge
+    };
+    set_window_badge(app, Some(badge as i64));
+    refresh_status(app);
+}
+
+/// Clear the tray badge, once the user is looking at the app
+pub fn clear_badge(app: &AppHandle) {
+    let Some(state) = app.try_state::<TrayState>() else {
+        return;
+    };
+    if std::mem::take(&mut state.content.lock().badge) == 0 {
+        return;
+    }
+    set_window_badge(app, None);
+    refresh_status(app);
+}
+
+/// Mirror the badge on the dock or taskbar entry, where supported
+fn set_window_badge<R: Runtime>(app: &AppHandle<R>, count: Option<i64>) {
+    if let Some(window) = app.get_webview_window("main") {
+        if let Err(e) = window.set_badge_count(count) {
+            tracing::debug!("Window badge not updated: {}", e);
+        }
+    }
+}
+
+fn show_main_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+fn on_menu_event(app: &AppHandle, event: MenuEvent) {
+    let id = event.id.as_ref();
+    if let Some(note_id) = id.strip_prefix(RECENT_NOTE_PREFIX) {
+        show_main_window(app);
+        let _ = app.emit("open-note", note_id);
+        return;
+    }
+    match id {
+        "show" => show_main_window(app),
+        "hide" => {
+            if let Some(window) = app.get_webview_window("main") {
+                let _ = window.hide();
+            }
+        }
+        "tray_new_note" => {
+            show_main_window(app);
+            let _ = app.emit("create-new-note", ());
+        }
+        "tray_new_chat" => {
+            show_main_window(app);
+            let _ = app.emit("create-new-chat", ());
+        }
+        "settings" => {
+            show_main_window(app);
+            let _ = app.emit("navigate-to-settings", ());
+        }
+        "tray_lock" => crate::app_lock::lock_now(app),
+        "launch_at_login" => toggle_launch_at_login(app),
+        "tray_reload_window" => {
+            if let Err(e) = crate::webview_watchdog::reload(app, "main") {
+                tracing::warn!("Failed to reload window: {}", e);
+            }
+        }
+        "copy_api_url" => {
+            let port = *app.state::<AppState>().backend_port.read();
+            let url = format!("http://localhost:{}/api", port);
+            let _ = app.emit("copy-to-clipboard", url);
+        }
+        "restart_all" | "restart_database" => {
+            app.state::<ServiceManager>()
+                .dispatch(ServiceCommand::RestartDatabase);
+        }
+        "restart_backend" => {
+            app.state::<ServiceManager>()
+                .dispatch(ServiceCommand::RestartBackend);
+        }
+        "open_logs" => {
+            if let Ok(app_data_dir) = app.path().app_data_dir() {
+                let log_path = app_data_dir.join("logs");
+                let _ = std::fs::create_dir_all(&log_path);
+                crate::open_folder(&log_path);
+            }
+        }
+        "open_data" => {
+            if let Ok(app_data_dir) = app.path().app_data_dir() {
+                crate::open_folder(&app_data_dir);
+            }
+        }
+        "quit" => {
+            app.state::<ServiceManager>().shutdown_blocking(app);
+            app.exit(0);
+        }
+        _ => {}
+    }
+}
+
+fn build_menu(app: &AppHandle, content: &TrayContent) -> tauri::Result<(Menu<Wry>, TrayHandles)> {
+    let status = MenuItem::with_id(app, "status", &content.status, false, None::<&str>)?;
+    let last_backup = MenuItem::with_id(
+        app,
+        "last_backup",
+        &content.last_backup,
+        false,
+        None::<&str>,
+    )?;
+
+    // Window controls
+    let show = MenuItem::with_id(app, "show", "Show Second Brain", true, None::<&str>)?;
+    let hide = MenuItem::with_id(app, "hide", "Hide", true, None::<&str>)?;
+
+    // Quick actions
+    let new_note = MenuItem::with_id(app, "tray_new_note", "New Note", true, None::<&str>)?;
+    let new_chat = MenuItem::with_id(app, "tray_new_chat", "New Chat", true, None::<&str>)?;
+    let recent = Submenu::with_id(app, "recent_notes", "Recent Notes", true)?;
+    fill_recent(&recent, &content.recent)?;
+
+    // Settings and info
+    let settings = MenuItem::with_id(app, "settings", "Settings...", true, None::<&str>)?;
+    let launch_at_login = CheckMenuItem::with_id(
+        app,
+        "launch_at_login",
+        "Launch at Login",
+        true,
+        content.launch_at_login,
+        None::<&str>,
+    )?;
+    let copy_api_url = MenuItem::with_id(app, "copy_api_url", "Copy API URL", true, None::<&str>)?;
+    let lock = MenuItem::with_id(app, "tray_lock", "Lock", true, None::<&str>)?;
+    let reload_window = MenuItem::with_id(
+        app,
+        "tray_reload_window",
+        "Reload Window",
+        true,
+        None::<&str>,
+    )?;
+
+    // Service controls submenu
+    let restart_all = MenuItem::with_id(
+        app,
+        "restart_all",
+        "Restart All Services",
+        true,
+        None::<&str>,
+    )?;
+    let restart_backend_item = MenuItem::with_id(
+        app,
+        "restart_backend",
+        "Restart Backend Only",
+        true,
+        None::<&str>,
+    )?;
+    let restart_db_item = MenuItem::with_id(
+        app,
+        "restart_database",
+        "Restart Database Only",
+        true,
+        None::<&str>,
+    )?;
+    let services_submenu = Submenu::with_items(
+        app,
+        "Services",
+        true,
+        &[&restart_all, &restart_backend_item, &restart_db_item],
+    )?;
+
+    // Folders
+    let open_logs = MenuItem::with_id(app, "open_logs", "Open Logs Folder", true, None::<&str>)?;
+    let open_data = MenuItem::with_id(app, "open_data", "Open Data Folder", true, None::<&str>)?;
+
+    // Quit
+    let quit = MenuItem::with_id(app, "quit", "Quit Second Brain", true, None::<&str>)?;
+
+    let separators = (0..5)
+        .map(|_| PredefinedMenuItem::separator(app))
+        .collect::<tauri::Result<Vec<_>>>()?;
+
+    let items: [&dyn IsMenuItem<Wry>; 21] = [
+        &status,
+        &last_backup,
+        &separators[0],
+        &show,
+        &hide,
+        &separators[1],
+        &new_note,
+        &new_chat,
+        &recent,
+        &separators[2],
+        &settings,
+        &launch_at_login,
+        &copy_api_url,
+        &lock,
+        &reload_window,
+        &separators[3],
+        &services_submenu,
+        &open_logs,
+        &open_data,
+        &separators[4],
+        &quit,
+    ];
+    let menu = Menu::with_items(app, &items)?;
+    Ok((
+        menu,
+        TrayHandles {
+            status,
+            last_backup,
+            recent,
+            launch_at_login,
+            shown: content.clone(),
+        },
+    ))
+}
+
+fn build(app: &AppHandle) -> tauri::Result<()> {
+    let state = app.state::<TrayState>();
+    let content = state.content.lock().clone();
+    let (menu, handles) = build_menu(app, &content)?;
+
+    let style = state.settings.lock().icon_style;
+    let mut builder = TrayIconBuilder::with_id("main");
+    if let Some((icon, template)) = tray_icon(app, style) {
+        builder = builder.icon(icon).icon_as_template(template);
+    }
+    if !content.tooltip.is_empty() {
+        builder = builder.tooltip(&content.tooltip);
+    }
+    if !content.title.is_empty() {
+        builder = builder.title(&content.title);
+    }
+    builder
+        .menu(&menu)
+        .show_menu_on_left_click(true)
+        .on_menu_event(on_menu_event)
+        .build(app)?;
+
+    *state.handles.lock() = Some(handles);
+    // Content may have changed while the menu was being built
+    refresh(app);
+    Ok(())
+}
+
+async fn wait_for_main_window(app: &AppHandle) {
+    let started = std::time::Instant::now();
+    while started.elapsed() < WINDOW_WAIT {
+        let visible = app
+            .get_webview_window("main")
+            .and_then(|window| window.is_visible().ok())
+            .unwrap_or(false);
+        if visible {
+            return;
+        }
+        tokio::time::sleep(WINDOW_POLL).await;
+    }
+}
+
+/// Build the tray once the main window is shown, then keep its status current
+pub fn start(app: &AppHandle) {
+    let app_data_dir = app.path().app_data_dir().ok();
+    let settings = app_data_dir
+        .as_deref()
+        .map(TraySettings::load)
+        .unwrap_or_default();
+    let last_backup_at = app_data_dir
+        .as_deref()
+        .and_then(|dir| ServiceConfig::load(dir).last_backup_at);
+    app.manage(TrayState {
+        settings: Mutex::new(settings),
+        content: Mutex::new(TrayContent {
+            last_backup: backup_label(last_backup_at),
+            launch_at_login: crate::login_item::is_enabled(app),
+            ..TrayContent::default()
+        }),
+        ..TrayState::default()
+    });
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        wait_for_main_window(&app).await;
+        let builder = app.clone();
+        let scheduled = app.run_on_main_thread(move || {
+            if let Err(e) = build(&builder) {
+                tracing::error!("Failed to create tray: {}", e);
+            }
+        });
+        if let Err(e) = scheduled {
+            tracing::error!("Failed to schedule tray creation: {}", e);
+        }
+
+        let mut services = app.state::<ServiceManager>().subscribe();
+        let mut jobs_poll = tokio::time::interval(JOBS_POLL);
+        loop {
+            refresh_status(&app);
+            tokio::select! {
+                changed = services.changed() => {
+                    if changed.is_err() {
+                        break;
+                    }
+                }
+                _ = jobs_poll.tick() => {}
+            }
+        }
+    });
+}
+
+/// Get the tray appearance settings
+#[tauri::command]
+pub async fn get_tray_settings(app: AppHandle) -> Result<TraySettings, AppError> {
+    let app_data_dir = app.path().app_data_dir()?;
+    Ok(TraySettings::load(&app_data_dir))
+}
+
+/// Update the tray appearance settings and apply them
+#[tauri::command]
+pub async fn set_tray_settings(app: AppHandle, settings: TraySettings) -> Result<(), AppError> {
+    let app_data_dir = app.path().app_data_dir()?;
+    settings.save(&app_data_dir)?;
+    // No tray in headless mode; the settings apply on the next launch
+    if let Some(state) = app.try_state::<TrayState>() {
+        *state.settings.lock() = settings;
+        apply_icon(&app);
+        refresh_status(&app);
+    }
+    Ok(())
+}
+
+/// Replace the notes listed under Recent Notes in the tray
+#[tauri::command]
+pub async fn set_tray_recent_notes(app: AppHandle, notes: Vec<RecentNote>) -> Result<(), AppError> {
+    let state = app.state::<TrayState>();
+    {
+        let mut content = state.content.lock();
+        content.recent = notes.into_iter().take(MAX_RECENT_NOTES).collect();
+    }
+    refresh(&app);
+    Ok(())
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(postgres: ServicePhase, backend: ServicePhase) -> ServiceState {
+        ServiceState {
+            postgres,
+            backend,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_status_label() {
+        use ServicePhase::*;
+        assert_eq!(
+            status_label(&state(Running, Running), true),
+            "Services running"
+        );
+        assert_eq!(
+            status_label(&state(Running, Running), false),
+            "Backend not responding"
+        );
+        assert_eq!(
+            status_label(&state(Running, Starting), true),
+            "Starting services…"
+        );
+        assert_eq!(
+            status_label(&state(Failed, Stopped), true),
+            "Database failed to start"
+        );
+        assert_eq!(
+            status_label(&state(Running, Failed), true),
+            "Backend stopped unexpectedly"
+        );
+        assert_eq!(
+            status_label(&state(Stopped, Stopped), true),
+            "Services stopped"
+        );
+    }
+
+    #[test]
+    fn test_status_label_while_restarting() {
+        use ServicePhase::*;
+        let mut restarting = state(Running, Stopping);
+        restarting.busy = Some(ServiceCommand::RestartBackend);
+        assert_eq!(status_label(&restarting, false), "Backend restarting…");
+    }
+
+    #[test]
+    fn test_activity_text() {
+        let (tooltip, title) = activity_text(IDLE_STATUS, None, 0, None, true);
+        assert_eq!(tooltip, "Second Brain — Services running");
+        assert_eq!(title, "");
+
+        let (tooltip, title) =
+            activity_text("Backend restarting…", Some("3 jobs queued"), 0, None, true);
+        assert_eq!(
+            tooltip,
+            "Second Brain — Backend restarting… · 3 jobs queued"
+        );
+        assert_eq!(title, "Backend restarting… · 3 jobs queued");
+
+        let (_, title) = activity_text("Backend restarting…", None, 0, None, false);
+        assert_eq!(title, "");
+
+        let (tooltip, title) = activity_text(IDLE_STATUS, None, 2, None, false);
+        assert_eq!(tooltip, "Second Brain — Services running · 2 new notices");
+        assert_eq!(title, "● 2");
+
+        let (tooltip, title) = activity_text(IDLE_STATUS, None, 1, Some("Focus 24:59"), false);
+        assert_eq!(
+            tooltip,
+            "Second Brain — Services running · 1 new notice · Focus 24:59"
+        );
+        assert_eq!(title, "Focus 24:59 · ● 1");
+    }
+
+    #[test]
+    fn test_glyph_rgba() {
+        let rgba = [54, 105, 61, 255, 10, 20, 30, 0, 54, 105, 61, 128];
+        assert_eq!(
+            glyph_rgba(&rgba, true),
+            vec![255, 255, 255, 255, 255, 255, 255, 0, 255, 255, 255, 128]
+        );
+        assert_eq!(
+            glyph_rgba(&rgba, false),
+            vec![0, 0, 0, 255, 0, 0, 0, 0, 0, 0, 0, 128]
+        );
+    }
+
+    #[test]
+    fn test_backup_label() {
+        assert_eq!(backup_label(None), "No Backups Yet");
+        assert!(backup_label(Some(1_760_000_000)).starts_with("Last Backup: "));
+    }
+
+    #[test]
+    fn test_label_for_note() {
+        let note = |title: &str| RecentNote {
+            id: "1".to_string(),
+            title: title.to_string(),
+        };
+        assert_eq!(label_for_note(&note("  ")), "Untitled");
+        assert_eq!(label_for_note(&note("Weekly review")), "Weekly review");
+        let long = label_for_note(&note(&"x".repeat(60)));
+        assert_eq!(long.chars().count(), 40);
+        assert!(long.ends_with('…'));
+    }
+}
@@ -0,0 +1,682 @@
+//! Remote access to the backend through a tunnel.
+//!
+//! This module provides:
+//! - Tunnel providers: Tailscale Serve (tailnet only), Tailscale Funnel,
+//!   Cloudflare quick tunnels and named Cloudflare tunnels
+//! - A supervisor that runs the provider's CLI, reads the public URL from its
+//!   output and restarts it if it exits or the backend moves to another port
+//! - Commands to configure, start and stop the tunnel and read its status
+//!
+//! The tunnel forwards to the backend API, which still requires signing in;
+//! Tailscale Serve additionally limits access to devices on the tailnet.
+//! A named Cloudflare tunnel's token is kept in the keychain and handed to
+//! `cloudflared` through its environment, never on the command line.
+
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::process::Command;
+use tokio::sync::mpsc;
+
+use crate::config::{load_json, save_json_atomic};
+use crate::error::AppError;
+use crate::keychain;
+use crate::services::{ServiceManager, ServicePhase};
+use crate::AppState;
+
+/// Keychain account holding a named Cloudflare tunnel's token
+const CLOUDFLARE_TOKEN_ACCOUNT: &str = "cloudflare-tunnel-token";
+
+/// Consecutive failed launches before giving up
+const MAX_ATTEMPTS: u32 = 3;
+
+/// Delay before relaunching, multiplied by the attempt number
+const RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// A run this long resets the failure count
+const STABLE_RUN: Duration = Duration::from_secs(60);
+
+/// Where CLIs live when the app was started without a login shell's PATH
+const EXTRA_BIN_DIRS: &[&str] = &[
+    "/opt/homebrew/bin",
+    "/usr/local/bin",
+    "/usr/bin",
+    "/Applications/Tailscale.app/Contents/MacOS",
+];
+
+/// How the tunnel is provided
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TunnelProvider {
+    /// Reachable only from devices on the same tailnet
+    #[default]
+    TailscaleServe,
+    /// Public URL on the tailnet's ts.net domain
+    TailscaleFunnel,
+    /// Throwaway public trycloudflare.com URL, no account needed
+    CloudflareQuick,
+    /// Tunnel set up in the Cloudflare dashboard, run with its token
+    CloudflareNamed,
+}
+
+impl TunnelProvider {
+    fn program(self) -> &'static str {
+        match self {
+            TunnelProvider::TailscaleServe | TunnelProvider::TailscaleFunnel => "tailscale",
+            TunnelProvider::CloudflareQuick | TunnelProvider::CloudflareNamed => "cloudflared",
+        }
+    }
+
+    /// Arguments that forward to the backend in the foreground
+    fn args(self, port: u16) -> Vec<String> {
+        let target = format!("http://localhost:{}", port);
+        match self {
+            TunnelProvider::TailscaleServe => vec!["serve".to_string(), target],
+            TunnelProvider::TailscaleFunnel => vec!["funnel".to_string(), target],
+            TunnelProvider::CloudflareQuick => vec![
+                "tunnel".to_string(),
+                "--no-autoupdate".to_string(),
+                "--url".to_string(),
+                target,
+            ],
+            // Named tunnels route to the backend through their dashboard config
+            TunnelProvider::CloudflareNamed => vec![
+                "tunnel".to_string(),
+                "--no-autoupdate".to_string(),
+                "run".to_string(),
+            ],
+        }
+    }
+
+    /// Whether anyone on the internet can reach the URL
+    pub fn is_public(self) -> bool {
+        self != TunnelProvider::TailscaleServe
+    }
+
+    /// Public URL announced in a line of the CLI's output
+    fn parse_url(self, line: &str) -> Option<String> {
+        let suffix = match self {
+            TunnelProvider::TailscaleServe | TunnelProvider::TailscaleFunnel => ".ts.net",
+            TunnelProvider::CloudflareQuick => ".trycloudflare.com",
+            // The hostname is configured, not announced
+            TunnelProvider::CloudflareNamed => return None,
+        };
+        line.split(|c: char| c.is_whitespace() || c == '|')
+            .filter_map(|word| word.strip_prefix("https://"))
+            .map(|rest| rest.trim_end_matches('/'))
+            .find(|host| host.ends_with(suffix) && !host.starts_with("api."))
+            .map(|host| format!("https://{}", host))
+    }
+
+    /// Output showing a named tunnel is connected
+    fn is_connected_line(self, line: &str) -> bool {
+        self == TunnelProvider::CloudflareNamed && line.contains("Registered tunnel connection")
+    }
+}
+
+/// Tunnel settings
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TunnelSettings {
+    /// Start the tunnel with the app
+    pub enabled: bool,
+    pub provider: TunnelProvider,
+    /// Hostname routed to a named Cloudflare tunnel
+    pub hostname: Option<String>,
+    /// Path to the provider's CLI; found on PATH when unset
+    pub binary_path: Option<String>,
+}
+
+impl TunnelSettings {
+    fn path(app_data_dir: &Path) -> PathBuf {
+        app_data_dir.join("tunnel.json")
+    }
+
+    pub fn load(app_data_dir: &Path) -> Self {
+        load_json(&Self::path(app_data_dir)).unwrap_or_default()
+    }
+
+    pub fn save(&self, app_data_dir: &Path) -> Result<(), String> {
+        save_json_atomic(&Self::path(app_data_dir), self)
+    }
+
+    fn validate(&self) -> Result<(), AppError> {
+        if self.provider == TunnelProvider::CloudflareNamed {
+            let hostname = self.hostname.as_deref().map(str::trim).unwrap_or_default();
+            let valid = hostname.contains('.')
+                && hostname
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-');
+            if !valid {
+                return Err(AppError::InvalidInput(
+                    "A hostname such as brain.example.com is required for a named tunnel"
+                        .to_string(),
+                ));
+            }
+        }
+        if let Some(path) = self.binary_path.as_deref() {
+            if !Path::new(path).is_absolute() {
+                return Err(AppError::InvalidInput(
+                    "The CLI path must be absolute".to_string(),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// URL to show before the CLI announces one
+    fn configured_url(&self) -> Option<String> {
+        match self.provider {
+            TunnelProvider::CloudflareNamed => self
+                .hostname
+                .as_deref()
+                .map(|host| format!("https://{}", host.trim())),
+            _ => None,
+        }
+    }
+}
+
+/// Tunnel lifecycle
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TunnelPhase {
+    #[default]
+    Stopped,
+    Starting,
+    Connected,
+    Failed,
+}
+
+/// Tunnel status, emitted as `tunnel-status` on every change
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct TunnelStatus {
+    pub phase: TunnelPhase,
+    pub provider: Option<TunnelProvider>,
+    pub public: bool,
+    pub url: Option<String>,
+    /// Backend port being forwarded
+    pub port: Option<u16>,
+    pub connected_at: Option<String>,
+    pub error: Option<String>,
+}
+
+struct Running {
+    task: tauri::async_runtime::JoinHandle<()>,
+    port: u16,
+}
+
+/// Running tunnel, kept in Tauri state
+#[derive(Default)]
+pub struct TunnelManager {
+    running: Mutex<Option<Running>>,
+    status: RwLock<TunnelStatus>,
+}
+
+impl TunnelManager {
+    fn set_status(&self, app: &AppHandle, update: impl FnOnce(&mut TunnelStatus)) {
+        let status = {
+            let mut status = self.status.write();
+            update(&mut status);
+            status.clone()
+        };
+        crate::events::emit_critical(app, "tunnel-status", &status);
+    }
+}
+
+/// Find a CLI on PATH or in the usual install locations
+fn find_program(name: &str) -> Option<PathBuf> {
+    let file = if cfg!(windows) {
+        format!("{}.exe", name)
+    } else {
+        name.to_string()
+    };
+    let path_dirs = std::env::var_os("PATH")
+        .map(|paths| std::env::split_paths(&paths).collect::<Vec<_>>())
+        .unwrap_or_default();
+    path_dirs
+        .into_iter()
+        .chain(EXTRA_BIN_DIRS.iter().map(PathBuf::from))
+        .map(|dir| dir.join(&file))
+        .find(|candidate| candidate.is_file())
+}
+
+fn program_path(settings: &TunnelSettings) -> Result<PathBuf, AppError> {
+    if let Some(path) = settings.binary_path.as_deref() {
+        return Ok(PathBuf::from(path));
+    }
+    let name = settings.provider.program();
+    find_program(name).ok_or_else(|| {
+        AppError::NotFound(format!(
+            "{} was not found; install it or set its path in tunnel settings",
+            name
+        ))
+    })
+}
+
+/// Run the CLI once, returning why it stopped
+async fn run_once(
+    app: &AppHandle,
+    settings: &TunnelSettings,
+    program: &Path,
+    token: Option<&str>,
+    port: u16,
+) -> AppError {
+    let provider = settings.provider;
+    let mut command = Command::new(program);
+    command
+        .args(provider.args(port))
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    if let Some(token) = token {
+        command.env("TUNNEL_TOKEN", token);
+    }
+    let mut child = match command.spawn() {
+        Ok(child) => child,
+        Err(e) => {
+            return AppError::Internal(format!("Failed to start {}: {}", program.display(), e))
+        }
+    };
+
+    // Both streams carry useful output depending on the CLI
+    let (lines_tx, mut lines) = mpsc::channel::<String>(64);
+    for stream in [
+        child
+            .stdout
+            .take()
+            .map(|s| Box::new(s) as Box<dyn tokio::io::AsyncRead + Send + Unpin>),
+        child
+            .stderr
+            .take()
+            .map(|s| Box::new(s) as Box<dyn tokio::io::AsyncRead + Send + Unpin>),
+    ]
+    .into_iter()
+    .flatten()
+    {
+        let tx = lines_tx.clone();
+        tauri::async_runtime::spawn(async move {
+            let _reader = crate::resource_monitor::track_reader();
+            let mut reader = BufReader::new(stream).lines();
+            while let Ok(Some(line)) = reader.next_line().await {
+                if tx.send(line).await.is_err() {
+                    break;
+                }
+            }
+        });
+    }
+    drop(lines_tx);
+
+    let manager = app.state::<TunnelManager>();
+    let mut last_line = String::new();
+    while let Some(line) = lines.recv().await {
+        let line = crate::secrets::redact_env_vars(&line);
+        tracing::debug!("[tunnel] {}", line);
+        let url = provider.parse_url(&line).or_else(|| {
+            provider
+                .is_connected_line(&line)
+                .then(|| settings.configured_url())
+                .flatten()
+        });
+        if let Some(url) = url {
+            let changed = {
+                let status = manager.status.read();
+                status.url.as_deref() != Some(url.as_str())
+                    || status.phase != TunnelPhase::Connected
+            };
+            if changed {
+                tracing::info!("Tunnel connected at {}", url);
+                manager.set_status(app, |status| {
+                    status.phase = TunnelPhase::Connected;
+                    status.url = Some(url);
+                    status.connected_at = Some(chrono::Utc::now().to_rfc3339());
+                    status.error = None;
+                });
+            }
+        }
+        if !line.trim().is_empty() {
+            last_line = line;
+        }
+    }
+
+    let exit = match child.wait().await {
+        Ok(exit) => exit.to_string(),
+        Err(e) => e.to_string(),
+    };
+    let detail = if last_line.is_empty() {
+        String::new()
+    } else {
+        format!(": {}", last_line.trim())
+    };
+    AppError::Network(format!(
+        "{} exited ({}){}",
+        provider.program(),
+        exit,
+        detail
+    ))
+}
+
+/// Keep the tunnel up, relaunching after unexpected exits
+async fn supervise(app: AppHandle, settings: TunnelSettings, port: u16) {
+    let manager = app.state::<TunnelManager>();
+    let prepared = program_path(&settings).and_then(|program| {
+        let token = if settings.provider == TunnelProvider::CloudflareNamed {
+            let app_data_dir = app.path().app_data_dir()?;
+            Some(
+                keychain::get_secret(&app_data_dir, CLOUDFLARE_TOKEN_ACCOUNT)?.ok_or_else(
+                    || AppError::NotReady("No Cloudflare tunnel token has been saved".to_string()),
+                )?,
+            )
+        } else {
+            None
+        };
+        Ok((program, token))
+    });
+    let (program, token) = match prepared {
+        Ok(prepared) => prepared,
+        Err(e) => {
+            manager.set_status(&app, |status| {
+                status.phase = TunnelPhase::Failed;
+                status.error = Some(e.to_string());
+            });
+            return;
+        }
+    };
+
+    let mut attempt = 0;
+    loop {
+        let started = std::time::Instant::now();
+        let error = run_once(&app, &settings, &program, token.as_deref(), port).await;
+        attempt = if started.elapsed() >= STABLE_RUN {
+            1
+        } else {
+            attempt + 1
+        };
+        tracing::warn!("Tunnel stopped (attempt {}): {}", attempt, error);
+        let giving_up = attempt >= MAX_ATTEMPTS;
+        manager.set_status(&app, |status| {
+            status.phase = if giving_up {
+                TunnelPhase::Failed
+            } else {
+                TunnelPhase::Starting
+            };
+            status.url = None;
+            status.connected_at = None;
+            status.error = Some(error.to_string());
+        });
+        if giving_up {
+            return;
+        }
+        tokio::time::sleep(RETRY_DELAY * attempt).await;
+    }
+}
+
+/// Stop the tunnel if one is running
+fn stop(app: &AppHandle) {
+    let manager = app.state::<TunnelManager>();
+    if let Some(running) = manager.running.lock().take() {
+        // Dropping the child inside the task kills the CLI
+        running.task.abort();
+        tracing::info!("Tunnel stopped");
+    }
+    manager.set_status(app, |status| *status = TunnelStatus::default());
+}
+
+/// Start the tunnel to the backend's current port, replacing any running one
+fn launch(app: &AppHandle, settings: &TunnelSettings) {
+    stop(app);
+    let port = *app.state::<AppState>().backend_port.read();
+    let manager = app.state::<TunnelManager>();
+    manager.set_status(app, |status| {
+        *status = TunnelStatus {
+            phase: TunnelPhase::Starting,
+            provider: Some(settings.provider),
+            public: settings.provider.is_public(),
+            port: Some(port),
+            ..TunnelStatus::default()
+        };
+    });
+    tracing::info!(
+        "Starting {:?} tunnel to backend port {}",
+        settings.provider,
+        port
+    );
+    let task = tauri::async_runtime::spawn(supervise(app.clone(), settings.clone(), port));
+    *manager.running.lock() = Some(Running { task, port });
+}
+
+/// Manage tunnel state, start it if enabled and follow backend port changes
+pub fn start(app: &AppHandle) {
+    app.manage(TunnelManager::default());
+    if let Ok(app_data_dir) = app.path().app_data_dir() {
+        let settings = TunnelSettings::load(&app_data_dir);
+        if settings.enabled {
+            launch(app, &settings);
+        }
+    }
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut services = app.state::<ServiceManager>().subscribe();
+        while services.changed().await.is_ok() {
+            if services.borrow_and_update().backend != ServicePhase::Running {
+                continue;
+            }
+            let port = *app.state::<AppState>().backend_port.read();
+            let moved = app
+                .state::<TunnelManager>()
+                .running
+                .lock()
+                .as_ref()
+                .is_some_and(|running| running.port != port);
+            if moved {
+                tracing::info!("Backend moved to port {}; restarting tunnel", port);
+                if let Ok(app_data_dir) = app.path().app_data_dir() {
+                    launch(&app, &TunnelSettings::load(&app_data_dir));
+                }
+            }
+        }
+    });
+}
+
+// ============================================================
+// Commands
+// ============================================================
+
+/// Tunnel settings and whether a named tunnel token is saved
+#[derive(Debug, Clone, Serialize)]
+pub struct TunnelConfig {
+    pub settings: TunnelSettings,
+    pub has_token: bool,
+    /// Whether the provider's CLI was found
+    pub cli_found: bool,
+}
+
+fn config(app_data_dir: &Path, settings: TunnelSettings) -> Result<TunnelConfig, AppError> {
+    Ok(TunnelConfig {
+        has_token: keychain::get_secret(app_data_dir, CLOUDFLARE_TOKEN_ACCOUNT)?.is_some(),
+        cli_found: program_path(&settings).is_ok_and(|path| path.is_file()),
+        settings,
+    })
+}
+
+/// Get tunnel settings
+#[tauri::command]
+pub async fn get_tunnel_settings(app: AppHandle) -> Result<TunnelConfig, AppError> {
+    let app_data_dir = app.path().app_data_dir()?;
+    config(&app_data_dir, TunnelSettings::load(&app_data_dir))
+}
+
+/// Save tunnel settings and, for a named tunnel, its token
+///
+/// A running tunnel is restarted with the new settings.
+#[tauri::command]
+pub async fn set_tunnel_settings(
+    app: AppHandle,
+    settings: TunnelSettings,
+    token: Option<String>,
+) -> Result<TunnelConfig, AppError> {
+    settings.validate()?;
+    let app_data_dir = app.path().app_data_dir()?;
+    if let Some(token) = token.as_deref().map(str::trim).filter(|t| !t.is_empty()) {
+        keychain::set_secret(&app_data_dir, CLOUDFLARE_TOKEN_ACCOUNT, token)?;
+    }
+    settings.save(&app_data_dir)?;
+    if app.state::<TunnelManager>().running.lock().is_some() {
+        launch(&app, &settings);
+    }
+    config(&app_data_dir, settings)
+}
+
+/// Start the tunnel with the saved settings
+#[tauri::command]
+pub async fn start_tunnel(app: AppHandle) -> Result<TunnelStatus, AppError> {
+    let app_data_dir = app.path().app_data_dir()?;
+    let settings = TunnelSettings::load(&app_data_dir);
+    settings.validate()?;
+    program_path(&settings)?;
+    launch(&app, &settings);
+    Ok(app.state::<TunnelManager>().status.read().clone())
+}
+
+/// Stop the tunnel
+#[tauri::command]
+pub async fn stop_tunnel(app: AppHandle) -> Result<TunnelStatus, AppError> {
+    stop(&app);
+    Ok(app.state::<TunnelManager>().status.read().clone())
+}
+
+/// Current tunnel status and URL
+#[tauri::command]
+pub async fn get_tunnel_status(app: AppHandle) -> Result<TunnelStatus, AppError> {
+    Ok(app.state::<TunnelManager>().status.read().clone())
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_args() {
+        assert_eq!(
+            TunnelProvider::TailscaleServe.args(5001),
+            vec!["serve", "http://localhost:5001"]
+        );
+        assert_eq!(
+            TunnelProvider::CloudflareQuick.args(5001),
+            vec![
+                "tunnel",
+                "--no-autoupdate",
+                "--url",
+                "http://localhost:5001"
+            ]
+        );
+        // The token travels in the environment, not argv
+        assert!(!TunnelProvider::CloudflareNamed
+            .args(5001)
+            .iter()
+            .any(|arg| arg.contains("token")));
+    }
+
+    #[test]
+    fn test_parse_url() {
+        assert_eq!(
+            TunnelProvider::CloudflareQuick.parse_url(
+                "2025-01-01T00:00:00Z INF |  https://calm-river-dune.trycloudflare.com  |"
+            ),
+            Some("https://calm-river-dune.trycloudflare.com".to_string())
+        );
+        assert_eq!(
+            TunnelProvider::CloudflareQuick
+                .parse_url("INF Requesting new quick Tunnel on https://api.trycloudflare.com..."),
+            None
+        );
+        assert_eq!(
+            TunnelProvider::TailscaleFunnel.parse_url("https://laptop.tail1234.ts.net/"),
+            Some("https://laptop.tail1234.ts.net".to_string())
+        );
+        assert_eq!(
+            TunnelProvider::TailscaleServe.parse_url("|-- proxy http://localhost:5001"),
+            None
+        );
+        assert_eq!(
+            TunnelProvider::CloudflareNamed.parse_url("https://brain.example.com"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_named_tunnel_connects_on_registration() {
+        let provider = TunnelProvider::CloudflareNamed;
+        assert!(provider
+            .is_connected_line("INF Registered tunnel connection connIndex=0 location=ams01"));
+        assert!(!TunnelProvider::CloudflareQuick
+            .is_connected_line("INF Registered tunnel connection connIndex=0"));
+    }
+
+    #[test]
+    fn test_is_public() {
+        assert!(!TunnelProvider::TailscaleServe.is_public());
+        assert!(TunnelProvider::TailscaleFunnel.is_public());
+        assert!(TunnelProvider::CloudflareQuick.is_public());
+    }
+
+    #[test]
+    fn test_validate() {
+        let named = |hostname: Option<&str>| TunnelSettings {
+            provider: TunnelProvider::CloudflareNamed,
+            hostname: hostname.map(str::to_string),
+            ..TunnelSettings::default()
+        };
+        assert!(TunnelSettings::default().validate().is_ok());
+        assert!(named(Some("brain.example.com")).validate().is_ok());
+        assert!(named(None).validate().is_err());
+        assert!(named(Some("https://brain.example.com")).validate().is_err());
+        assert_eq!(
+            named(Some("brain.example.com")).configured_url().as_deref(),
+            Some("https://brain.example.com")
+        );
+
+        let relative = TunnelSettings {
+            binary_path: Some("bin/tailscale".to_string()),
+            ..TunnelSettings::default()
+        };
+        assert!(relative.validate().is_err());
+    }
+
+    #[test]
+    fn test_settings_round_trip() {
+        let dir = TempDir::new().unwrap();
+        assert_eq!(TunnelSettings::load(dir.path()), TunnelSettings::default());
+
+        let settings = TunnelSettings {
+            enabled: true,
+            provider: TunnelProvider::CloudflareQuick,
+            hostname: None,
+            binary_path: Some("/usr/local/bin/cloudflared".to_string()),
+        };
+        settings.save(dir.path()).unwrap();
+        assert_eq!(TunnelSettings::load(dir.path()), settings);
+    }
+
+    #[test]
+    fn test_program_path_prefers_configured() {
+        let settings = TunnelSettings {
+            binary_path: Some("/opt/tools/cloudflared".to_string()),
+            ..TunnelSettings::default()
+        };
+        assert_eq!(
+            program_path(&settings).unwrap(),
+            PathBuf::from("/opt/tools/cloudflared")
+        );
+    }
+}
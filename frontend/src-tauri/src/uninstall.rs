@@ -0,0 +1,314 @@
+//! Removing everything the app leaves on the machine.
+//!
+//! This module provides:
+//! - `uninstall_cleanup`, which stops services, optionally writes a final
+//!   encrypted backup, then removes the data, log and cache directories,
+//!   launch-at-login entries, protocol and native messaging registrations,
+//!   and keychain items, and quits
+//! - A report of what was removed and what couldn't be, so leftovers can be
+//!   cleaned up by hand
+//!
+//! Only the current profile is cleaned up. Directories are removed only
+//! when they're named after the app's identifier, so a misconfigured path
+//! can never take anything else with it.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+use crate::backup::BackupManifest;
+use crate::error::AppError;
+use crate::services::{ServiceCommand, ServiceManager};
+
+/// Delay before quitting, so the report reaches the webview
+const EXIT_DELAY: Duration = Duration::from_millis(500);
+
+/// What to clean up
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct UninstallOptions {
+    /// Write an encrypted backup to the configured folder first, and stop
+    /// if that fails
+    pub final_backup: bool,
+    /// Keep the data directory and keychain items, removing only
+    /// registrations, logs and caches
+    pub keep_data: bool,
+}
+
+/// Outcome of a cleanup
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct UninstallReport {
+    /// The final backup, if one was requested
+    pub backup: Option<BackupManifest>,
+    /// Everything removed
+    pub removed: Vec<String>,
+    /// Everything that couldn't be removed, with the reason
+    pub failed: Vec<String>,
+}
+
+impl UninstallReport {
+    /// Record a step; `Ok(false)` means there was nothing to remove
+    fn record(&mut self, what: impl Into<String>, result: Result<bool, String>) {
+        let what = what.into();
+        match result {
+            Ok(true) => self.removed.push(what),
+            Ok(false) => {}
+            Err(e) => {
+                tracing::warn!("Couldn't remove {}: {}", what, e);
+                self.failed.push(format!("{}: {}", what, e));
+            }
+        }
+    }
+}
+
+/// Whether a path is named after the app, and so safe to delete
+fn is_app_owned(path: &Path, identifier: &str) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.starts_with(identifier))
+}
+
+fn remove_dir(path: &Path) -> Result<bool, String> {
+    match std::fs::remove_dir_all(path) {
+        Ok(()) => Ok(true),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+fn remove_file(path: &Path) -> Result<bool, String> {
+    match std::fs::remove_file(path) {
+        Ok(()) => Ok(true),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Directories the app writes to, data directory last
+fn app_dirs(app: &AppHandle, identifier: &str, keep_data: bool) -> Vec<PathBuf> {
+    let resolver = app.path();
+    let mut dirs: Vec<PathBuf> = [resolver.app_log_dir(), resolver.app_cache_dir()]
+        .into_iter()
+        .flatten()
+        .collect();
+
+    #[cfg(target_os = "macos")]
+    if let Ok(home) = resolver.home_dir() {
+        let library = home.join("Library");
+        dirs.push(library.join("WebKit").join(identifier));
+        dirs.push(library.join("HTTPStorages").join(identifier));
+        dirs.push(
+            library
+                .join("Saved Application State")
+                .join(format!("{}.savedState", identifier)),
+        );
+    }
+
+    if !keep_data {
+        // The local data directory holds the webview's storage on Windows
+        dirs.extend(
+            [
+                resolver.app_local_data_dir(),
+                resolver.app_config_dir(),
+                resolver.app_data_dir(),
+            ]
+            .into_iter()
+            .flatten(),
+        );
+    }
+
+    let mut unique: Vec<PathBuf> = Vec::new();
+    for dir in dirs {
+        if is_app_owned(&dir, identifier) && !unique.contains(&dir) {
+            unique.push(dir);
+        }
+    }
+    // Remove nested directories before the ones holding them
+    unique.sort_by_key(|dir| std::cmp::Reverse(dir.components().count()));
+    unique
+}
+
+/// Login items under the names an autostart entry would use
+fn remove_login_items(app: &AppHandle, identifier: &str, report: &mut UninstallReport) {
+    let name = app.package_info().name.clone();
+    let Ok(home) = app.path().home_dir() else {
+        return;
+    };
+
+    #[cfg(target_os = "macos")]
+    for label in [identifier.to_string(), name.clone()] {
+        let path = home
+            .join("Library/LaunchAgents")
+            .join(format!("{}.plist", label));
+        report.record(path.to_string_lossy(), remove_file(&path));
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        let _ = identifier;
+        let path = home
+            .join(".config/autostart")
+            .join(format!("{}.desktop", name));
+        report.record(path.to_string_lossy(), remove_file(&path));
+    }
+
+    #[cfg(windows)]
+    {
+        let _ = (identifier, home);
+        let key = r"HKCU\Software\Microsoft\Windows\CurrentVersion\Run";
+        report.record(
+            format!(r"{}\{}", key, name),
+            delete_registry(&["delete", key, "/v", &name, "/f"]),
+        );
+    }
+}
+
+/// The `secondbrain://` handler registered for the current user
+fn remove_protocol_handler(report: &mut UninstallReport) {
+    #[cfg(windows)]
+    {
+        let key = r"HKCU\Software\Classes\secondbrain";
+        report.record(key, delete_registry(&["delete", key, "/f"]));
+    }
+
+    // macOS and Linux drop the handler along with the app bundle or package
+    #[cfg(not(windows))]
+    let _ = report;
+}
+
+/// Run `reg delete`; a missing key or value isn't an error
+#[cfg(windows)]
+fn delete_registry(args: &[&str]) -> Result<bool, String> {
+    let query: Vec<&str> = std::iter::once("query")
+        .chain(args[1..].iter().copied().filter(|arg| *arg != "/f"))
+        .collect();
+    let exists = std::process::Command::new("reg")
+        .args(&query)
+        .output()
+        .map(|output| output.status.success())
+        .unwrap_or(false);
+    if !exists {
+        return Ok(false);
+    }
+    let output = std::process::Command::new("reg")
+        .args(args)
+        .output()
+        .map_err(|e| e.to_string())?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    Ok(true)
+}
+
+/// Stop services, back up if asked, then remove everything
+async fn cleanup(app: &AppHandle, options: &UninstallOptions) -> Result<UninstallReport, AppError> {
+    let app_data_dir = app.path().app_data_dir()?;
+    let identifier = app.config().identifier.clone();
+    let mut report = UninstallReport::default();
+
+    if options.final_backup {
+        tracing::info!("Writing a final backup before cleanup");
+        let manifest =
+            crate::backup::run_encrypted_backup(&crate::jobs::JobContext::untracked(app))
+                .await
+                .map_err(|e| {
+                    AppError::Io(format!("Final backup failed, nothing was removed: {}", e))
+                })?;
+        report.backup = Some(manifest);
+    }
+
+    if let Some(services) = app.try_state::<ServiceManager>() {
+        if let Err(e) = services.send(ServiceCommand::Shutdown).await {
+            tracing::warn!("Service shutdown failed: {}", e);
+            crate::stop_services(app);
+        }
+    }
+
+    tracing::info!(keep_data = options.keep_data, "Removing app files");
+    let app = app.clone();
+    let options = options.clone();
+    tokio::task::spawn_blocking(move || {
+        remove_login_items(&app, &identifier, &mut report);
+        remove_protocol_handler(&mut report);
+        report.record(
+            "Browser extension registrations",
+            crate::native_messaging::uninstall_all(&app_data_dir).map_err(String::from),
+        );
+        if !options.keep_data {
+            report.record(
+                "Keychain items",
+                crate::keychain::delete_all(&app_data_dir).map(|count| count > 0),
+            );
+        }
+        for dir in app_dirs(&app, &identifier, options.keep_data) {
+            report.record(dir.to_string_lossy(), remove_dir(&dir));
+        }
+        report
+    })
+    .await
+    .map_err(AppError::from)
+}
+
+// ============================================================
+// Commands
+// ============================================================
+
+/// Remove the app's data, registrations and credentials, then quit
+#[tauri::command]
+pub async fn uninstall_cleanup(
+    app: AppHandle,
+    options: UninstallOptions,
+) -> Result<UninstallReport, AppError> {
+    let report = cleanup(&app, &options).await?;
+    tracing::info!(
+        removed = report.removed.len(),
+        failed = report.failed.len(),
+        "Cleanup finished, quitting"
+    );
+
+    let handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(EXIT_DELAY).await;
+        handle.exit(0);
+    });
+    Ok(report)
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_is_app_owned() {
+        let id = "com.secondbrain.desktop";
+        assert!(is_app_owned(Path::new("/data/com.secondbrain.desktop"), id));
+        assert!(is_app_owned(
+            Path::new("/data/com.secondbrain.desktop.savedState"),
+            id
+        ));
+        assert!(!is_app_owned(Path::new("/home/me"), id));
+        assert!(!is_app_owned(Path::new("/"), id));
+    }
+
+    #[test]
+    fn test_report_records_outcomes() {
+        let dir = TempDir::new().unwrap();
+        let target = dir.path().join("com.secondbrain.desktop");
+        std::fs::create_dir_all(target.join("logs")).unwrap();
+
+        let mut report = UninstallReport::default();
+        report.record("data", remove_dir(&target));
+        report.record("again", remove_dir(&target));
+        report.record("login item", Err("denied".to_string()));
+
+        assert!(!target.exists());
+        assert_eq!(report.removed, vec!["data"]);
+        assert_eq!(report.failed, vec!["login item: denied"]);
+    }
+}
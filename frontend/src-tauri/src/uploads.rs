@@ -0,0 +1,534 @@
+//! Streaming uploads of large files to the backend.
+//!
+//! This module provides:
+//! - Multipart uploads streamed from disk in chunks, so the webview never
+//!   holds multi-gigabyte files in memory
+//! - `upload-progress`, `upload-completed` and `upload-failed` events
+//! - Automatic retries with backoff on connection errors and 5xx responses
+//! - Failed uploads kept on disk so they can be resumed after a restart
+//! - Cancellation of running uploads
+//!
+//! The backend's upload endpoints accept whole multipart files and have no
+//! byte-range support, so a resumed upload streams the file again from the
+//! start; it is only resumed if the file is unchanged since it was queued.
+
+use futures_util::stream::{self, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::io::AsyncReadExt;
+
+use crate::config::{load_json, save_json_atomic};
+use crate::error::AppError;
+use crate::AppState;
+
+/// Bytes read from disk per chunk
+const CHUNK_SIZE: usize = 256 * 1024;
+
+/// Attempts per upload before it is marked failed
+const MAX_ATTEMPTS: u32 = 3;
+
+/// Minimum time between progress events
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+/// An upload, persisted until it completes or is cancelled
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UploadSession {
+    pub id: String,
+    pub path: PathBuf,
+    /// Backend path relative to `/api`, including any query string
+    pub target: String,
+    /// Multipart form field holding the file
+    pub field: String,
+    pub content_type: String,
+    pub size: u64,
+    /// Modification time when queued, used to detect changed files on resume
+    pub modified_ms: u64,
+    pub attempts: u32,
+    pub last_error: Option<String>,
+}
+
+/// Progress event payload
+#[derive(Debug, Clone, Serialize)]
+struct UploadProgress<'a> {
+    id: &'a str,
+    sent: u64,
+    total: u64,
+}
+
+/// Running uploads and their cancellation flags
+#[derive(Default)]
+pub struct UploadManager {
+    running: Mutex<HashMap<String, Arc<AtomicBool>>>,
+}
+
+fn sessions_path(app_data_dir: &Path) -> PathBuf {
+    app_data_dir.join("uploads.json")
+}
+
+fn load_sessions(app_data_dir: &Path) -> Vec<UploadSession> {
+    load_json(&sessions_path(app_data_dir)).unwrap_or_default()
+}
+
+/// Insert or replace a session, or remove it when `session` is None
+fn update_sessions(app_data_dir: &Path, id: &str, session: Option<&UploadSession>) {
+    let mut sessions = load_sessions(app_data_dir);
+    sessions.retain(|s| s.id != id);
+    if let Some(session) = session {
+        sessions.push(session.clone());
+    }
+    if let Err(e) = save_json_atomic(&sessions_path(app_data_dir), &sessions) {
+        tracing::warn!("Failed to save upload sessions: {}", e);
+    }
+}
+
+fn modified_ms(metadata: &std::fs::Metadata) -> u64 {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+/// Content type from the file extension
+fn guess_content_type(path: &Path) -> &'static str {
+    let extension = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    match extension.as_str() {
+        "pdf" => "application/pdf",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "mp3" => "audio/mpeg",
+        "wav" => "audio/wav",
+        "m4a" => "audio/mp4",
+        "mp4" => "video/mp4",
+        "mov" => "video/quicktime",
+        "txt" => "text/plain",
+        "md" => "text/markdown",
+        "csv" => "text/csv",
+        "json" => "application/json",
+        "zip" => "application/zip",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Multipart framing around the file contents
+struct Multipart {
+    boundary: String,
+    head: Vec<u8>,
+    tail: Vec<u8>,
+}
+
+impl Multipart {
+    fn new(field: &str, file_name: &str, content_type: &str) -> Self {
+        let mut random = [0u8; 12];
+        let _ = getrandom::fill(&mut random);
+        let boundary = format!(
+            "----SecondBrainUpload{}",
+            random
+                .iter()
+                .map(|b| format!("{:02x}", b))
+                .collect::<String>()
+        );
+        let clean = |s: &str| s.replace(['"', '\r', '\n'], "_");
+        let head = format!(
+            "--{}\r\nContent-Disposition: form-data; name=\"{}\"; filename=\"{}\"\r\n\
+             Content-Type: {}\r\n\r\n",
+            boundary,
+            clean(field),
+            clean(file_name),
+            content_type
+        )
+        .into_bytes();
+        let tail = format!("\r\n--{}--\r\n", boundary).into_bytes();
+        Self {
+            boundary,
+            head,
+            tail,
+        }
+    }
+
+    fn content_length(&self, file_size: u64) -> u64 {
+        self.head.len() as u64 + file_size + self.tail.len() as u64
+    }
+}
+
+/// Whether a failed attempt is worth retrying
+fn is_retryable(status: Option<reqwest::StatusCode>) -> bool {
+    match status {
+        None => true,
+        Some(status) => {
+            status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS
+        }
+    }
+}
+
+/// Stream one attempt, returning the backend response or the error and status
+async fn send_once(
+    app: &AppHandle,
+    session: &UploadSession,
+    cancelled: &Arc<AtomicBool>,
+) -> Result<serde_json::Value, (String, Option<reqwest::StatusCode>)> {
+    let (port, auth) = {
+        let state = app.state::<AppState>();
+        let port = *state.backend_port.read();
+        let auth = state.backend_auth.read().clone();
+        (port, auth)
+    };
+    let file = tokio::fs::File::open(&session.path)
+        .await
+        .map_err(|e| (format!("Failed to open file: {}", e), None))?;
+    let file_name = session
+        .path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "upload".to_string());
+    let multipart = Multipart::new(&session.field, &file_name, &session.content_type);
+    let content_length = multipart.content_length(session.size);
+
+    let progress_app = app.clone();
+    let id = session.id.clone();
+    let total = session.size;
+    let cancelled = cancelled.clone();
+    let chunks = stream::unfold(
+        (file, 0u64, Instant::now() - PROGRESS_INTERVAL),
+        move |(mut file, sent, last_emit)| {
+            let app = progress_app.clone();
+            let id = id.clone();
+            let cancelled = cancelled.clone();
+            async move {
+                if cancelled.load(Ordering::SeqCst) {
+                    return Some((
+                        Err(std::io::Error::new(
+                            std::io::ErrorKind::Interrupted,
+                            "Upload cancelled",
+                        )),
+                        (file, sent, last_emit),
+                    ));
+                }
+                // Never send more than the declared length, even if the file grew
+                let remaining = total.saturating_sub(sent).min(CHUNK_SIZE as u64) as usize;
+                if remaining == 0 {
+                    return None;
+                }
+                let mut buf = vec![0u8; remaining];
+                match file.read(&mut buf).await {
+                    Ok(0) => None,
+                    Ok(n) => {
+                        buf.truncate(n);
+                        let sent = sent + n as u64;
+                        let mut last_emit = last_emit;
+                        if last_emit.elapsed() >= PROGRESS_INTERVAL || sent == total {
+                            let _ = app.emit(
+                                "upload-progress",
+                                UploadProgress {
+                                    id: &id,
+                                    sent,
+                                    total,
+                                },
+                            );
+                            last_emit = Instant::now();
+                        }
+                        Some((Ok(buf), (file, sent, last_emit)))
+                    }
+                    Err(e) => Some((Err(e), (file, sent, last_emit))),
+                }
+            }
+        },
+    );
+    let Multipart {
+        boundary,
+        head,
+        tail,
+    } = multipart;
+    let body = stream::once(async move { Ok::<_, std::io::Error>(head) })
+        .chain(chunks)
+        .chain(stream::once(async move { Ok(tail) }));
+
+    // No overall timeout; a large upload may take a long time
+    let mut request = crate::http::backend(app)
+        .post(format!("http://localhost:{}/api{}", port, session.target))
+        .header(
+            "Content-Type",
+            format!("multipart/form-data; boundary={}", boundary),
+        )
+        .header("Content-Length", content_length)
+        .body(reqwest::Body::wrap_stream(body));
+    if let Some(auth) = auth {
+        request = request.header("Authorization", auth);
+    }
+
+    let response = request
+        .send()
+        .await
+        .map_err(|e| (format!("Upload failed: {}", e), None))?;
+    let status = response.status();
+    let text = response.text().await.unwrap_or_default();
+    let value = serde_json::from_str(&text).unwrap_or(serde_json::Value::String(text));
+    if !status.is_success() {
+        return Err((
+            format!("Backend returned {}: {}", status, value),
+            Some(status),
+        ));
+    }
+    Ok(value)
+}
+
+/// Run an upload to completion, retrying transient failures
+async fn run_upload(app: AppHandle, mut session: UploadSession, cancelled: Arc<AtomicBool>) {
+    let Ok(app_data_dir) = app.path().app_data_dir() else {
+        return;
+    };
+    update_sessions(&app_data_dir, &session.id, Some(&session));
+
+    let outcome = loop {
+        session.attempts += 1;
+        match send_once(&app, &session, &cancelled).await {
+            Ok(response) => break Ok(response),
+            Err((error, status)) => {
+                let retry = !cancelled.load(Ordering::SeqCst)
+                    && is_retryable(status)
+                    && session.attempts % MAX_ATTEMPTS != 0;
+                tracing::warn!(
+                    "Upload {} attempt {} failed: {}",
+                    session.id,
+                    session.attempts,
+                    error
+                );
+                if !retry {
+                    break Err((error, is_retryable(status)));
+                }
+                let backoff = 2u64.pow(session.attempts % MAX_ATTEMPTS);
+                tokio::time::sleep(Duration::from_secs(backoff)).await;
+            }
+        }
+    };
+
+    app.state::<UploadManager>()
+        .running
+        .lock()
+        .unwrap()
+        .remove(&session.id);
+
+    match outcome {
+        Ok(response) => {
+            update_sessions(&app_data_dir, &session.id, None);
+            tracing::info!("Uploaded {:?} ({} bytes)", session.path, session.size);
+            let _ = app.emit(
+                "upload-completed",
+                serde_json::json!({ "id": session.id, "response": response }),
+            );
+        }
+        Err((error, retryable)) => {
+            let cancelled = cancelled.load(Ordering::SeqCst);
+            if cancelled {
+                update_sessions(&app_data_dir, &session.id, None);
+            } else {
+                session.last_error = Some(error.clone());
+                update_sessions(&app_data_dir, &session.id, Some(&session));
+            }
+            let _ = app.emit(
+                "upload-failed",
+                serde_json::json!({
+                    "id": session.id,
+                    "error": error,
+                    "cancelled": cancelled,
+                    "resumable": retryable && !cancelled,
+                }),
+            );
+        }
+    }
+}
+
+fn spawn_upload(app: &AppHandle, session: UploadSession) -> Result<(), String> {
+    let cancelled = Arc::new(AtomicBool::new(false));
+    {
+        let manager = app.state::<UploadManager>();
+        let mut running = manager.running.lock().unwrap();
+        if running.contains_key(&session.id) {
+            return Err("Upload is already running".to_string());
+        }
+        running.insert(session.id.clone(), cancelled.clone());
+    }
+    tauri::async_runtime::spawn(run_upload(app.clone(), session, cancelled));
+    Ok(())
+}
+
+/// Start uploading a file to a backend multipart endpoint
+///
+/// Returns the upload ID immediately; progress and the result arrive as
+/// `upload-progress`, `upload-completed` and `upload-failed` events.
+#[tauri::command]
+pub async fn start_upload(
+    app: AppHandle,
+    path: String,
+    target: String,
+    field: Option<String>,
+    content_type: Option<String>,
+) -> Result<String, AppError> {
+    if !target.starts_with('/') {
+        return Err(AppError::InvalidInput(
+            "Upload target must be a backend path starting with '/'".to_string(),
+        ));
+    }
+    let path = PathBuf::from(path);
+    let metadata =
+        std::fs::metadata(&path).map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+    if !metadata.is_file() {
+        return Err(AppError::InvalidInput(format!(
+            "Not a file: {}",
+            path.display()
+        )));
+    }
+
+    let mut random = [0u8; 8];
+    getrandom::fill(&mut random).map_err(|e| format!("Failed to generate upload ID: {}", e))?;
+    let session = UploadSession {
+        id: random.iter().map(|b| format!("{:02x}", b)).collect(),
+        content_type: content_type.unwrap_or_else(|| guess_content_type(&path).to_string()),
+        path,
+        target,
+        field: field.unwrap_or_else(|| "file".to_string()),
+        size: metadata.len(),
+        modified_ms: modified_ms(&metadata),
+        attempts: 0,
+        last_error: None,
+    };
+    let id = session.id.clone();
+    spawn_upload(&app, session)?;
+    Ok(id)
+}
+
+/// Retry a failed upload, provided the file hasn't changed
+#[tauri::command]
+pub async fn resume_upload(app: AppHandle, id: String) -> Result<(), AppError> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let session = load_sessions(&app_data_dir)
+        .into_iter()
+        .find(|s| s.id == id)
+        .ok_or_else(|| format!("Upload not found: {}", id))?;
+
+    let metadata = std::fs::metadata(&session.path)
+        .map_err(|e| format!("Cannot read {}: {}", session.path.display(), e))?;
+    if metadata.len() != session.size || modified_ms(&metadata) != session.modified_ms {
+        return Err(AppError::Conflict(
+            "The file has changed since the upload started; start a new upload".to_string(),
+        ));
+    }
+    spawn_upload(&app, session).map_err(AppError::from)
+}
+
+/// Cancel a running upload, or discard a failed one
+#[tauri::command]
+pub async fn cancel_upload(app: AppHandle, id: String) -> Result<(), AppError> {
+    if let Some(flag) = app
+        .state::<UploadManager>()
+        .running
+        .lock()
+        .unwrap()
+        .get(&id)
+    {
+        flag.store(true, Ordering::SeqCst);
+        return Ok(());
+    }
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    update_sessions(&app_data_dir, &id, None);
+    Ok(())
+}
+
+/// List uploads that are running or can be resumed
+#[tauri::command]
+pub async fn list_uploads(app: AppHandle) -> Result<Vec<UploadSession>, AppError> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    Ok(load_sessions(&app_data_dir))
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn session(id: &str) -> UploadSession {
+        UploadSession {
+            id: id.to_string(),
+            path: PathBuf::from("/tmp/video.mp4"),
+            target: "/gemini/files/upload".to_string(),
+            field: "file".to_string(),
+            content_type: "video/mp4".to_string(),
+            size: 10,
+            modified_ms: 1,
+            attempts: 0,
+            last_error: None,
+        }
+    }
+
+    #[test]
+    fn test_multipart_framing() {
+        let multipart = Multipart::new("file", "my \"clip\".mp4", "video/mp4");
+        let head = String::from_utf8(multipart.head.clone()).unwrap();
+        assert!(head.starts_with(&format!("--{}\r\n", multipart.boundary)));
+        assert!(head.contains("name=\"file\"; filename=\"my _clip_.mp4\""));
+        assert!(head.ends_with("Content-Type: video/mp4\r\n\r\n"));
+        assert_eq!(
+            multipart.tail,
+            format!("\r\n--{}--\r\n", multipart.boundary).into_bytes()
+        );
+        assert_eq!(
+            multipart.content_length(100),
+            (multipart.head.len() + 100 + multipart.tail.len()) as u64
+        );
+    }
+
+    #[test]
+    fn test_guess_content_type() {
+        assert_eq!(guess_content_type(Path::new("a/b.PDF")), "application/pdf");
+        assert_eq!(guess_content_type(Path::new("clip.mov")), "video/quicktime");
+        assert_eq!(
+            guess_content_type(Path::new("archive.bin")),
+            "application/octet-stream"
+        );
+        assert_eq!(
+            guess_content_type(Path::new("noext")),
+            "application/octet-stream"
+        );
+    }
+
+    #[test]
+    fn test_is_retryable() {
+        assert!(is_retryable(None));
+        assert!(is_retryable(Some(reqwest::StatusCode::BAD_GATEWAY)));
+        assert!(is_retryable(Some(reqwest::StatusCode::TOO_MANY_REQUESTS)));
+        assert!(!is_retryable(Some(reqwest::StatusCode::BAD_REQUEST)));
+        assert!(!is_retryable(Some(reqwest::StatusCode::PAYLOAD_TOO_LARGE)));
+    }
+
+    #[test]
+    fn test_session_persistence() {
+        let temp_dir = TempDir::new().unwrap();
+        update_sessions(temp_dir.path(), "a", Some(&session("a")));
+        update_sessions(temp_dir.path(), "b", Some(&session("b")));
+
+        let mut updated = session("a");
+        updated.attempts = 3;
+        update_sessions(temp_dir.path(), "a", Some(&updated));
+        let sessions = load_sessions(temp_dir.path());
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions.iter().find(|s| s.id == "a").unwrap().attempts, 3);
+
+        update_sessions(temp_dir.path(), "b", None);
+        assert_eq!(load_sessions(temp_dir.path()).len(), 1);
+    }
+}
@@ -0,0 +1,655 @@
+//! Per-provider AI usage and cost tracking.
+//!
+//! This module provides:
+//! - Recording of token and request counts from AI responses passing
+//!   through the proxy, grouped by day and provider in usage.json
+//! - Estimated cost from a per-model price table (USD per million tokens)
+//! - A monthly budget with a `usage-budget-alert` event when spending
+//!   crosses the warning percentage and again when it exceeds the budget
+//! - `get_usage_stats(period)` and settings commands for the UI
+//!
+//! Costs are estimates: prices change and the table only knows common
+//! models. Requests for unknown models are counted but not priced, and
+//! cache hits are never recorded since they cost nothing.
+
+use chrono::{Datelike, NaiveDate};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+
+use crate::config::{load_json, save_json_atomic};
+use crate::error::AppError;
+use crate::notifications::{Notice, Severity};
+use crate::tokens::TokenizerFamily;
+
+/// Days of usage kept on disk
+const RETENTION_DAYS: i64 = 400;
+
+/// Input and output prices in USD per million tokens, matched by model
+/// prefix with the longest prefix winning
+const PRICES: &[(&str, f64, f64)] = &[
+    ("gpt-4o-mini", 0.15, 0.60),
+    ("gpt-4o", 2.50, 10.00),
+    ("gpt-4.1-nano", 0.10, 0.40),
+    ("gpt-4.1-mini", 0.40, 1.60),
+    ("gpt-4.1", 2.00, 8.00),
+    ("gpt-5-nano", 0.05, 0.40),
+    ("gpt-5-mini", 0.25, 2.00),
+    ("gpt-5", 1.25, 10.00),
+    ("o3-mini", 1.10, 4.40),
+    ("o4-mini", 1.10, 4.40),
+    ("o3", 2.00, 8.00),
+    ("text-embedding-3-small", 0.02, 0.0),
+    ("text-embedding-3-large", 0.13, 0.0),
+    ("claude-opus-4", 15.00, 75.00),
+    ("claude-sonnet-4", 3.00, 15.00),
+    ("claude-3-7-sonnet", 3.00, 15.00),
+    ("claude-3-5-sonnet", 3.00, 15.00),
+    ("claude-haiku-4", 1.00, 5.00),
+    ("claude-3-5-haiku", 0.80, 4.00),
+    ("gemini-2.5-pro", 1.25, 10.00),
+    ("gemini-2.5-flash-lite", 0.10, 0.40),
+    ("gemini-2.5-flash", 0.30, 2.50),
+    ("gemini-2.0-flash", 0.10, 0.40),
+    ("text-embedding-004", 0.0, 0.0),
+    ("grok-4", 3.00, 15.00),
+    ("grok-3-mini", 0.30, 0.50),
+    ("grok-3", 3.00, 15.00),
+];
+
+/// Providers that run on the user's machine and never cost anything
+const LOCAL_PROVIDERS: &[&str] = &["ollama", "local"];
+
+/// Budget settings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UsageSettings {
+    /// Monthly spending limit in USD; no alerts without one
+    pub monthly_budget_usd: Option<f64>,
+    /// Percentage of the budget at which to warn
+    pub alert_percent: u8,
+}
+
+impl Default for UsageSettings {
+    fn default() -> Self {
+        Self {
+            monthly_budget_usd: None,
+            alert_percent: 80,
+        }
+    }
+}
+
+impl UsageSettings {
+    fn path(app_data_dir: &Path) -> PathBuf {
+        app_data_dir.join("usage-settings.json")
+    }
+
+    /// Load settings, returning defaults if missing or invalid
+    pub fn load(app_data_dir: &Path) -> Self {
+        load_json(&Self::path(app_data_dir)).unwrap_or_default()
+    }
+
+    /// Save settings atomically
+    pub fn save(&self, app_data_dir: &Path) -> Result<(), String> {
+        save_json_atomic(&Self::path(app_data_dir), self)
+    }
+
+    /// Validate settings
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(budget) = self.monthly_budget_usd {
+            if !budget.is_finite() || budget <= 0.0 {
+                return Err("The monthly budget must be a positive amount".to_string());
+            }
+        }
+        if !(1..=100).contains(&self.alert_percent) {
+            return Err("The alert percentage must be between 1 and 100".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Totals for one provider over some span of days
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UsageTotals {
+    pub requests: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub estimated_cost_usd: f64,
+    /// Requests for models missing from the price table
+    pub unpriced_requests: u64,
+}
+
+impl UsageTotals {
+    fn add(&mut self, other: &UsageTotals) {
+        self.requests += other.requests;
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+        self.estimated_cost_usd += other.estimated_cost_usd;
+        self.unpriced_requests += other.unpriced_requests;
+    }
+}
+
+/// Usage reported by one AI response
+#[derive(Debug, Clone, PartialEq)]
+pub struct UsageSample {
+    pub provider: String,
+    pub model: String,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+}
+
+impl UsageSample {
+    /// Read usage from a backend AI response, falling back to the request
+    /// body for the provider and model
+    ///
+    /// Returns None for responses that report no token usage.
+    pub fn from_exchange(request: Option<&Value>, response: &Value) -> Option<Self> {
+        let field = |name: &str| {
+            response
+                .get(name)
+                .or_else(|| request.and_then(|body| body.get(name)))
+                .and_then(Value::as_str)
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+        };
+        let tokens = |object: &Value, names: &[&str]| {
+            names
+                .iter()
+                .find_map(|name| object.get(*name).and_then(Value::as_u64))
+        };
+
+        let (input_tokens, output_tokens) = match response.get("usage") {
+            Some(usage) if usage.is_object() => (
+                tokens(usage, &["inputTokens", "input_tokens", "prompt_tokens"]).unwrap_or(0),
+                tokens(
+                    usage,
+                    &["outputTokens", "output_tokens", "completion_tokens"],
+                )
+                .unwrap_or(0),
+            ),
+            // Only a combined count; attribute it all to input
+            _ => (
+                tokens(response, &["tokensUsed", "tokens_used"]).unwrap_or(0),
+                0,
+            ),
+        };
+        if input_tokens + output_tokens == 0 {
+            return None;
+        }
+
+        let model = field("model").unwrap_or("unknown").to_string();
+        let provider = field("provider")
+            .map(str::to_ascii_lowercase)
+            .unwrap_or_else(|| provider_for_model(&model).to_string());
+        Some(Self {
+            provider,
+            model,
+            input_tokens,
+            output_tokens,
+        })
+    }
+
+    /// Estimated cost in USD, or None for models without a known price
+    fn cost_usd(&self) -> Option<f64> {
+        if LOCAL_PROVIDERS.contains(&self.provider.as_str()) {
+            return Some(0.0);
+        }
+        let (input, output) = price_for_model(&self.model)?;
+        Some((self.input_tokens as f64 * input + self.output_tokens as f64 * output) / 1e6)
+    }
+
+    fn totals(&self) -> UsageTotals {
+        let cost = self.cost_usd();
+        UsageTotals {
+            requests: 1,
+            input_tokens: self.input_tokens,
+            output_tokens: self.output_tokens,
+            estimated_cost_usd: cost.unwrap_or(0.0),
+            unpriced_requests: u64::from(cost.is_none()),
+        }
+    }
+}
+
+/// Provider name for a model when the response doesn't say
+fn provider_for_model(model: &str) -> &'static str {
+    match TokenizerFamily::from_model(model) {
+        TokenizerFamily::OpenAI => "openai",
+        TokenizerFamily::Anthropic => "anthropic",
+        TokenizerFamily::Gemini => "gemini",
+        TokenizerFamily::Other if model.to_ascii_lowercase().starts_with("grok") => "xai",
+        TokenizerFamily::Other => "other",
+    }
+}
+
+/// Input and output price per million tokens for a model
+fn price_for_model(model: &str) -> Option<(f64, f64)> {
+    let model = model.to_ascii_lowercase();
+    // Providers prefix some model names, e.g. "models/gemini-2.5-pro"
+    let model = model.rsplit('/').next().unwrap_or(&model);
+    PRICES
+        .iter()
+        .filter(|(prefix, _, _)| model.starts_with(prefix))
+        .max_by_key(|(prefix, _, _)| prefix.len())
+        .map(|(_, input, output)| (*input, *output))
+}
+
+/// Span of days covered by `get_usage_stats`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UsagePeriod {
+    Today,
+    /// The last seven days, including today
+    Week,
+    /// The current calendar month
+    Month,
+    /// The current calendar year
+    Year,
+    All,
+}
+
+impl UsagePeriod {
+    /// First day of the period, or None for all recorded usage
+    fn since(self, today: NaiveDate) -> Option<NaiveDate> {
+        match self {
+            UsagePeriod::Today => Some(today),
+            UsagePeriod::Week => Some(today - chrono::Duration::days(6)),
+            UsagePeriod::Month => today.with_day(1),
+            UsagePeriod::Year => NaiveDate::from_ymd_opt(today.year(), 1, 1),
+            UsagePeriod::All => None,
+        }
+    }
+}
+
+/// Recorded usage, by day then provider
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct UsageStore {
+    days: BTreeMap<NaiveDate, BTreeMap<String, UsageTotals>>,
+    /// Highest budget percentage already alerted on, by month ("2026-03")
+    alerted: BTreeMap<String, u8>,
+}
+
+impl UsageStore {
+    fn path(app_data_dir: &Path) -> PathBuf {
+        app_data_dir.join("usage.json")
+    }
+
+    fn load(app_data_dir: &Path) -> Self {
+        load_json(&Self::path(app_data_dir)).unwrap_or_default()
+    }
+
+    fn save(&self, app_data_dir: &Path) -> Result<(), String> {
+        save_json_atomic(&Self::path(app_data_dir), self)
+    }
+
+    fn record(&mut self, day: NaiveDate, sample: &UsageSample) {
+        self.days
+            .entry(day)
+            .or_default()
+            .entry(sample.provider.clone())
+            .or_default()
+            .add(&sample.totals());
+
+        let cutoff = day - chrono::Duration::days(RETENTION_DAYS);
+        self.days.retain(|kept, _| *kept > cutoff);
+        let oldest_month = month_key(cutoff);
+        self.alerted.retain(|month, _| *month >= oldest_month);
+    }
+
+    /// Totals by provider from `since` on
+    fn totals(&self, since: Option<NaiveDate>) -> BTreeMap<String, UsageTotals> {
+        let mut totals: BTreeMap<String, UsageTotals> = BTreeMap::new();
+        let days = match since {
+            Some(since) => self.days.range(since..),
+            None => self.days.range(..),
+        };
+        for providers in days.map(|(_, providers)| providers) {
+            for (provider, usage) in providers {
+                totals.entry(provider.clone()).or_default().add(usage);
+            }
+        }
+        totals
+    }
+
+    fn month_cost(&self, today: NaiveDate) -> f64 {
+        self.totals(UsagePeriod::Month.since(today))
+            .values()
+            .map(|usage| usage.estimated_cost_usd)
+            .sum()
+    }
+
+    /// Budget percentage newly crossed this month, if an alert is due
+    fn alert_due(&mut self, today: NaiveDate, settings: &UsageSettings) -> Option<u8> {
+        let budget = settings.monthly_budget_usd?;
+        let spent = self.month_cost(today);
+        let level = if spent >= budget {
+            100
+        } else if spent >= budget * f64::from(settings.alert_percent) / 100.0 {
+            settings.alert_percent
+        } else {
+            return None;
+        };
+        let alerted = self.alerted.entry(month_key(today)).or_insert(0);
+        if level <= *alerted {
+            return None;
+        }
+        *alerted = level;
+        Some(level)
+    }
+}
+
+fn month_key(day: NaiveDate) -> String {
+    day.format("%Y-%m").to_string()
+}
+
+/// Spending against the monthly budget
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BudgetStatus {
+    pub month: String,
+    pub budget_usd: f64,
+    pub spent_usd: f64,
+    pub percent_used: f64,
+    pub exceeded: bool,
+}
+
+impl BudgetStatus {
+    fn new(today: NaiveDate, budget_usd: f64, spent_usd: f64) -> Self {
+        Self {
+            month: month_key(today),
+            budget_usd,
+            spent_usd,
+            percent_used: spent_usd / budget_usd * 100.0,
+            exceeded: spent_usd >= budget_usd,
+        }
+    }
+}
+
+/// Usage of one provider
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProviderUsage {
+    pub provider: String,
+    #[serde(flatten)]
+    pub usage: UsageTotals,
+}
+
+/// Everything `get_usage_stats` reports
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct UsageStats {
+    pub period: UsagePeriod,
+    /// First day covered, or None for all recorded usage
+    pub since: Option<NaiveDate>,
+    /// Providers by estimated cost, most expensive first
+    pub providers: Vec<ProviderUsage>,
+    pub total: UsageTotals,
+    pub budget: Option<BudgetStatus>,
+}
+
+/// Usage store, kept in Tauri state and loaded on first use
+#[derive(Default)]
+pub struct UsageTracker {
+    store: Mutex<Option<UsageStore>>,
+}
+
+impl UsageTracker {
+    fn with_store<T>(&self, app_data_dir: &Path, f: impl FnOnce(&mut UsageStore) -> T) -> T {
+        let mut store = self.store.lock();
+        f(store.get_or_insert_with(|| UsageStore::load(app_data_dir)))
+    }
+}
+
+fn today() -> NaiveDate {
+    chrono::Local::now().date_naive()
+}
+
+/// Record usage from a proxied AI response, alerting if it takes spending
+/// over a budget threshold
+pub fn record(app: &AppHandle, request: Option<&Value>, response: &Value) {
+    let Some(sample) = UsageSample::from_exchange(request, response) else {
+        return;
+    };
+    let Ok(app_data_dir) = app.path().app_data_dir() else {
+        return;
+    };
+    let settings = UsageSettings::load(&app_data_dir);
+    let today = today();
+
+    let alert = app
+        .state::<UsageTracker>()
+        .with_store(&app_data_dir, |store| {
+            store.record(today, &sample);
+            let alert = store
+                .alert_due(today, &settings)
+                .zip(settings.monthly_budget_usd)
+                .map(|(_, budget)| BudgetStatus::new(today, budget, store.month_cost(today)));
+            if let Err(e) = store.save(&app_data_dir) {
+                tracing::warn!("Failed to save AI usage: {}", e);
+            }
+            alert
+        });
+
+    if let Some(status) = alert {
+        tracing::warn!(
+            spent_usd = status.spent_usd,
+            budget_usd = status.budget_usd,
+            "AI spending crossed a budget threshold"
+        );
+        crate::events::emit_critical(app, "usage-budget-alert", &status);
+        let (severity, title) = if status.exceeded {
+            (Severity::Error, "AI budget exceeded")
+        } else {
+            (Severity::Warning, "AI budget almost used")
+        };
+        crate::notifications::notify(
+            app,
+            Notice::new(
+                "usage",
+                severity,
+                title,
+                format!(
+                    "An estimated ${:.2} of your ${:.2} monthly budget is spent.",
+                    status.spent_usd, status.budget_usd
+                ),
+            ),
+        );
+    }
+}
+
+// ============================================================
+// Commands
+// ============================================================
+
+/// Get AI usage and estimated cost by provider for a period
+#[tauri::command]
+pub async fn get_usage_stats(app: AppHandle, period: UsagePeriod) -> Result<UsageStats, AppError> {
+    let app_data_dir = app.path().app_data_dir()?;
+    let settings = UsageSettings::load(&app_data_dir);
+    let today = today();
+    let since = period.since(today);
+
+    let (by_provider, month_cost) = app
+        .state::<UsageTracker>()
+        .with_store(&app_data_dir, |store| {
+            (store.totals(since), store.month_cost(today))
+        });
+
+    let mut total = UsageTotals::default();
+    let mut providers: Vec<ProviderUsage> = by_provider
+        .into_iter()
+        .map(|(provider, usage)| {
+            total.add(&usage);
+            ProviderUsage { provider, usage }
+        })
+        .collect();
+    providers.sort_by(|a, b| {
+        b.usage
+            .estimated_cost_usd
+            .total_cmp(&a.usage.estimated_cost_usd)
+            .then_with(|| b.usage.requests.cmp(&a.usage.requests))
+    });
+
+    Ok(UsageStats {
+        period,
+        since,
+        providers,
+        total,
+        budget: settings
+            .monthly_budget_usd
+            .map(|budget| BudgetStatus::new(today, budget, month_cost)),
+    })
+}
+
+/// Get the monthly budget settings
+#[tauri::command]
+pub async fn get_usage_settings(app: AppHandle) -> Result<UsageSettings, AppError> {
+    let app_data_dir = app.path().app_data_dir()?;
+    Ok(UsageSettings::load(&app_data_dir))
+}
+
+/// Update the monthly budget settings
+#[tauri::command]
+pub async fn set_usage_settings(app: AppHandle, settings: UsageSettings) -> Result<(), AppError> {
+    settings.validate()?;
+    let app_data_dir = app.path().app_data_dir()?;
+    settings.save(&app_data_dir)?;
+    Ok(())
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    fn day(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn sample(provider: &str, model: &str, input: u64, output: u64) -> UsageSample {
+        UsageSample {
+            provider: provider.to_string(),
+            model: model.to_string(),
+            input_tokens: input,
+            output_tokens: output,
+        }
+    }
+
+    #[test]
+    fn test_sample_from_exchange() {
+        let response = json!({
+            "content": "Hi",
+            "model": "claude-sonnet-4-20250514",
+            "provider": "Anthropic",
+            "usage": { "inputTokens": 1200, "outputTokens": 300 }
+        });
+        assert_eq!(
+            UsageSample::from_exchange(None, &response),
+            Some(sample("anthropic", "claude-sonnet-4-20250514", 1200, 300))
+        );
+
+        // Provider and model from the request, combined count only
+        let request = json!({ "model": "gpt-4o-mini", "prompt": "..." });
+        let response = json!({ "content": "Hi", "tokensUsed": 50 });
+        assert_eq!(
+            UsageSample::from_exchange(Some(&request), &response),
+            Some(sample("openai", "gpt-4o-mini", 50, 0))
+        );
+
+        // Not an AI response
+        assert_eq!(
+            UsageSample::from_exchange(None, &json!({ "id": "note-1" })),
+            None
+        );
+    }
+
+    #[test]
+    fn test_cost() {
+        let cost = sample("openai", "gpt-4o-mini-2024-07-18", 1_000_000, 1_000_000)
+            .cost_usd()
+            .unwrap();
+        assert!((cost - 0.75).abs() < 1e-9);
+        assert_eq!(
+            price_for_model("models/gemini-2.5-flash-lite"),
+            Some((0.10, 0.40))
+        );
+        assert_eq!(sample("ollama", "llama3.2", 500, 500).cost_usd(), Some(0.0));
+        assert_eq!(sample("other", "mystery-1", 500, 500).cost_usd(), None);
+    }
+
+    #[test]
+    fn test_totals_by_period() {
+        let mut store = UsageStore::default();
+        store.record(day(2026, 2, 27), &sample("openai", "gpt-4o", 1000, 0));
+        store.record(day(2026, 3, 2), &sample("openai", "gpt-4o", 2000, 0));
+        store.record(day(2026, 3, 2), &sample("other", "mystery-1", 10, 10));
+
+        let today = day(2026, 3, 2);
+        let month = store.totals(UsagePeriod::Month.since(today));
+        assert_eq!(month["openai"].requests, 1);
+        assert_eq!(month["openai"].input_tokens, 2000);
+        assert_eq!(month["other"].unpriced_requests, 1);
+
+        let week = store.totals(UsagePeriod::Week.since(today));
+        assert_eq!(week["openai"].requests, 2);
+
+        // Old days are dropped
+        store.record(day(2027, 6, 1), &sample("openai", "gpt-4o", 1, 0));
+        assert_eq!(store.totals(None)["openai"].requests, 1);
+    }
+
+    #[test]
+    fn test_budget_alerts() {
+        let settings = UsageSettings {
+            monthly_budget_usd: Some(10.0),
+            alert_percent: 80,
+        };
+        let today = day(2026, 3, 15);
+        let mut store = UsageStore::default();
+
+        // $2.50 per million input tokens for gpt-4o
+        store.record(today, &sample("openai", "gpt-4o", 2_000_000, 0));
+        assert_eq!(store.alert_due(today, &settings), None);
+
+        store.record(today, &sample("openai", "gpt-4o", 1_500_000, 0));
+        assert_eq!(store.alert_due(today, &settings), Some(80));
+        assert_eq!(store.alert_due(today, &settings), None);
+
+        store.record(today, &sample("openai", "gpt-4o", 1_000_000, 0));
+        assert_eq!(store.alert_due(today, &settings), Some(100));
+        assert_eq!(store.alert_due(today, &settings), None);
+
+        // A new month starts over
+        let next = day(2026, 4, 1);
+        store.record(next, &sample("openai", "gpt-4o", 3_500_000, 0));
+        assert_eq!(store.alert_due(next, &settings), Some(80));
+    }
+
+    #[test]
+    fn test_store_roundtrip_and_settings() {
+        let dir = TempDir::new().unwrap();
+        let mut store = UsageStore::default();
+        store.record(day(2026, 3, 2), &sample("gemini", "gemini-2.5-pro", 10, 20));
+        store.save(dir.path()).unwrap();
+        let loaded = UsageStore::load(dir.path());
+        assert_eq!(loaded.totals(None)["gemini"].output_tokens, 20);
+
+        assert!(UsageSettings::default().validate().is_ok());
+        let negative = UsageSettings {
+            monthly_budget_usd: Some(-5.0),
+            ..UsageSettings::default()
+        };
+        assert!(negative.validate().is_err());
+        let zero_percent = UsageSettings {
+            alert_percent: 0,
+            ..UsageSettings::default()
+        };
+        assert!(zero_percent.validate().is_err());
+    }
+}
@@ -0,0 +1,543 @@
+//! Voice setup preflight.
+//!
+//! This module provides:
+//! - Microphone permission status, requested through the system prompt when
+//!   the user hasn't been asked yet
+//! - Enumeration of audio input devices
+//! - Authenticated pings to the configured speech-to-text and text-to-speech
+//!   providers
+//! - `check_voice_setup`, which combines these into a readiness report so
+//!   problems show up before the user starts recording
+//! - `list_audio_devices` and `set_audio_device`, which keep the preferred
+//!   input across launches and tell a running recorder to switch to it
+//!
+//! Permission and devices come from AVFoundation on macOS; elsewhere the
+//! webview's own prompt and device list apply, so only providers are checked
+//! and a selected device is stored by the webview's id as given.
+//!
+//! The recorder runs in the webview, which matches the selection by id and
+//! then by name (AVFoundation and webview ids differ) and handles the device
+//! disappearing mid-recording.
+
+use futures_util::future::join_all;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::config::{load_json, save_json_atomic};
+use crate::error::AppError;
+use crate::osascript::{run_jxa, PermissionStatus};
+use crate::Secrets;
+
+/// Timeout for each provider ping
+const PING_TIMEOUT: Duration = Duration::from_secs(8);
+
+/// AVFoundation bridge: `status`, `request` or `devices`
+const MICROPHONE_SCRIPT: &str = r#"
+ObjC.import('AVFoundation');
+ObjC.import('Foundation');
+
+function run(argv) {
+    const mode = argv[0];
+    const audio = $.AVMediaTypeAudio;
+
+    if (mode === 'request') {
+        let done = false;
+        $.AVCaptureDevice.requestAccessForMediaTypeCompletionHandler(audio, function (granted) { done = true; });
+        const deadline = Date.now() + 120000;
+        while (!done && Date.now() < deadline) {
+            $.NSRunLoop.currentRunLoop.runUntilDate($.NSDate.dateWithTimeIntervalSinceNow(0.2));
+        }
+    }
+
+    if (mode === 'devices') {
+        const fallback = $.AVCaptureDevice.defaultDeviceWithMediaType(audio);
+        const defaultId = fallback.isNil() ? null : ObjC.unwrap(fallback.uniqueID);
+        const devices = $.AVCaptureDevice.devicesWithMediaType(audio);
+        const out = [];
+        for (let i = 0; i < devices.count; i++) {
+            const d = devices.objectAtIndex(i);
+            const id = ObjC.unwrap(d.uniqueID);
+            out.push({ id: id, name: ObjC.unwrap(d.localizedName), is_default: id === defaultId });
+        }
+        return JSON.stringify(out);
+    }
+
+    return JSON.stringify({ status: $.AVCaptureDevice.authorizationStatusForMediaType(audio) });
+}
+"#;
+
+/// An audio input device
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AudioInputDevice {
+    pub id: String,
+    pub name: String,
+    /// The system's default input
+    pub is_default: bool,
+}
+
+/// The input the user picked
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SelectedAudioDevice {
+    pub id: String,
+    /// Device name, used to find the device where ids differ
+    pub name: Option<String>,
+}
+
+/// Preferred audio input, kept across launches
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AudioDeviceSettings {
+    /// The system default is used when None
+    pub input: Option<SelectedAudioDevice>,
+}
+
+impl AudioDeviceSettings {
+    fn path(app_data_dir: &Path) -> PathBuf {
+        app_data_dir.join("audio-device.json")
+    }
+
+    /// Load the settings, returning defaults if missing or invalid
+    pub fn load(app_data_dir: &Path) -> Self {
+        load_json(&Self::path(app_data_dir)).unwrap_or_default()
+    }
+
+    /// Save the settings atomically
+    pub fn save(&self, app_data_dir: &Path) -> Result<(), String> {
+        save_json_atomic(&Self::path(app_data_dir), self)
+    }
+}
+
+/// Input devices and the one recordings use
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AudioDevices {
+    /// Empty where devices can't be listed natively
+    pub devices: Vec<AudioInputDevice>,
+    pub selected: Option<SelectedAudioDevice>,
+}
+
+/// What a provider is used for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VoiceRole {
+    SpeechToText,
+    TextToSpeech,
+}
+
+/// Result of pinging a provider
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProviderStatus {
+    NotConfigured,
+    Ready,
+    /// The provider rejected the API key
+    Unauthorized,
+    Unreachable,
+}
+
+/// A voice provider's readiness
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ProviderCheck {
+    pub provider: &'static str,
+    pub role: VoiceRole,
+    pub status: ProviderStatus,
+    pub latency_ms: Option<u64>,
+    pub error: Option<String>,
+}
+
+/// Readiness report for voice features
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct VoiceSetup {
+    /// Whether recording and both directions of speech should work
+    pub ready: bool,
+    pub microphone: PermissionStatus,
+    /// Input devices, empty where they can't be listed natively
+    pub input_devices: Vec<AudioInputDevice>,
+    pub providers: Vec<ProviderCheck>,
+    /// What to fix, in the order it blocks the user
+    pub problems: Vec<String>,
+}
+
+/// A provider, its role, and how to authenticate a cheap request to it
+struct Provider {
+    name: &'static str,
+    role: VoiceRole,
+    url: &'static str,
+    key: Option<String>,
+    auth: fn(reqwest::RequestBuilder, &str) -> reqwest::RequestBuilder,
+}
+
+fn providers(secrets: &Secrets) -> Vec<Provider> {
+    let key = |value: &Option<String>| {
+        value
+            .as_deref()
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .map(str::to_string)
+    };
+    vec![
+        Provider {
+            name: "deepgram",
+            role: VoiceRole::SpeechToText,
+            url: "https://api.deepgram.com/v1/projects",
+            key: key(&secrets.deepgram_api_key),
+            auth: |request, key| request.header("Authorization", format!("Token {}", key)),
+        },
+        Provider {
+            name: "elevenlabs",
+            role: VoiceRole::TextToSpeech,
+            url: "https://api.elevenlabs.io/v1/models",
+            key: key(&secrets.elevenlabs_api_key),
+            auth: |request, key| request.header("xi-api-key", key),
+        },
+        Provider {
+            name: "openai-tts",
+            role: VoiceRole::TextToSpeech,
+            url: "https://api.openai.com/v1/models",
+            key: key(&secrets.openai_tts_api_key),
+            auth: |request, key| request.bearer_auth(key),
+        },
+    ]
+}
+
+async fn ping(client: &reqwest::Client, provider: Provider) -> ProviderCheck {
+    let mut check = ProviderCheck {
+        provider: provider.name,
+        role: provider.role,
+        status: ProviderStatus::NotConfigured,
+        latency_ms: None,
+        error: None,
+    };
+    let Some(key) = provider.key else {
+        return check;
+    };
+
+    let started = Instant::now();
+    let request = client.get(provider.url).timeout(PING_TIMEOUT);
+    match (provider.auth)(request, &key).send().await {
+        Ok(response) => {
+            check.latency_ms = Some(started.elapsed().as_millis() as u64);
+            let status = response.status();
+            check.status = if status.is_success() {
+                ProviderStatus::Ready
+            } else if status == reqwest::StatusCode::UNAUTHORIZED
+                || status == reqwest::StatusCode::FORBIDDEN
+            {
+                ProviderStatus::Unauthorized
+            } else {
+                ProviderStatus::Unreachable
+            };
+            if !status.is_success() {
+                check.error = Some(format!("{} returned {}", provider.name, status));
+            }
+        }
+        Err(e) => {
+            check.status = ProviderStatus::Unreachable;
+            check.error = Some(e.to_string());
+        }
+    }
+    check
+}
+
+#[derive(Deserialize)]
+struct RawStatus {
+    status: i64,
+}
+
+fn parse_status(json: &str) -> Result<PermissionStatus, String> {
+    let raw: RawStatus =
+        serde_json::from_str(json).map_err(|e| format!("Invalid AVFoundation output: {}", e))?;
+    Ok(PermissionStatus::from_code(raw.status))
+}
+
+/// Microphone permission, asking the user first if they haven't been asked
+fn microphone_permission() -> PermissionStatus {
+    if !cfg!(target_os = "macos") {
+        return PermissionStatus::Unavailable;
+    }
+    let status = run_jxa(MICROPHONE_SCRIPT, &["status"])
+        .and_then(|out| parse_status(&out))
+        .unwrap_or(PermissionStatus::Unavailable);
+    if status != PermissionStatus::NotDetermined {
+        return status;
+    }
+    tracing::info!("Requesting microphone access");
+    run_jxa(MICROPHONE_SCRIPT, &["request"])
+        .and_then(|out| parse_status(&out))
+        .unwrap_or(PermissionStatus::Unavailable)
+}
+
+/// Audio input devices, empty where they can't be listed natively
+pub fn input_devices() -> Vec<AudioInputDevice> {
+    if !cfg!(target_os = "macos") {
+        return Vec::new();
+    }
+    match run_jxa(MICROPHONE_SCRIPT, &["devices"])
+        .and_then(|out| serde_json::from_str(&out).map_err(|e| e.to_string()))
+    {
+        Ok(devices) => devices,
+        Err(e) => {
+            tracing::warn!("Failed to list audio input devices: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+/// The selection for `id`, looked up in `devices` when they're listed
+fn select_device(
+    id: &str,
+    native_devices: bool,
+    devices: &[AudioInputDevice],
+) -> Result<SelectedAudioDevice, AppError> {
+    if !native_devices {
+        return Ok(SelectedAudioDevice {
+            id: id.to_string(),
+            name: None,
+        });
+    }
+    devices
+        .iter()
+        .find(|device| device.id == id)
+        .map(|device| SelectedAudioDevice {
+            id: device.id.clone(),
+            name: Some(device.name.clone()),
+        })
+        .ok_or_else(|| AppError::NotFound(format!("No audio input device {}", id)))
+}
+
+/// Problems blocking voice use, given what was found
+fn problems(
+    microphone: PermissionStatus,
+    native_devices: bool,
+    devices: &[AudioInputDevice],
+    providers: &[ProviderCheck],
+) -> Vec<String> {
+    let mut problems = Vec::new();
+    match microphone {
+        PermissionStatus::Denied | PermissionStatus::Restricted => problems.push(
+            "Microphone access is off; allow Second Brain in System Settings > Privacy & \
+             Security > Microphone"
+                .to_string(),
+        ),
+        PermissionStatus::NotDetermined => {
+            problems.push("Microphone access hasn't been granted yet".to_string())
+        }
+        _ => {}
+    }
+    if native_devices && devices.is_empty() {
+        problems.push("No microphone is connected".to_string());
+    }
+
+    for role in [VoiceRole::SpeechToText, VoiceRole::TextToSpeech] {
+        let candidates: Vec<&ProviderCheck> = providers.iter().filter(|p| p.role == role).collect();
+        if candidates.iter().any(|p| p.status == ProviderStatus::Ready) {
+            continue;
+        }
+        let what = match role {
+            VoiceRole::SpeechToText => "speech-to-text",
+            VoiceRole::TextToSpeech => "text-to-speech",
+        };
+        let failing: Vec<String> = candidates
+            .iter()
+            .filter(|p| p.status != ProviderStatus::NotConfigured)
+            .map(|p| format!("{} ({:?})", p.provider, p.status).to_lowercase())
+            .collect();
+        if failing.is_empty() {
+            problems.push(format!("No {} provider is configured", what));
+        } else {
+            problems.push(format!(
+                "No {} provider is working: {}",
+                what,
+                failing.join(", ")
+            ));
+        }
+    }
+    problems
+}
+
+// ============================================================
+// Commands
+// ============================================================
+
+/// Check the microphone and voice providers before recording
+///
+/// Shows the system's microphone prompt if the user hasn't been asked yet.
+#[tauri::command]
+pub async fn check_voice_setup(app: AppHandle) -> Result<VoiceSetup, AppError> {
+    let app_data_dir = app.path().app_data_dir()?;
+    let secrets = crate::load_secrets_async(app_data_dir).await;
+    let client = crate::http::external(&app);
+
+    let native = tokio::task::spawn_blocking(|| (microphone_permission(), input_devices()));
+    let pings = join_all(
+        providers(&secrets)
+            .into_iter()
+            .map(|provider| ping(&client, provider)),
+    );
+    let (native, providers) = tokio::join!(native, pings);
+    let (microphone, input_devices) = native?;
+
+    let problems = problems(
+        microphone,
+        cfg!(target_os = "macos"),
+        &input_devices,
+        &providers,
+    );
+    Ok(VoiceSetup {
+        ready: problems.is_empty(),
+        microphone,
+        input_devices,
+        providers,
+        problems,
+    })
+}
+
+/// List audio input devices and the selected one
+#[tauri::command]
+pub async fn list_audio_devices(app: AppHandle) -> Result<AudioDevices, AppError> {
+    let app_data_dir = app.path().app_data_dir()?;
+    let devices = tokio::task::spawn_blocking(input_devices).await?;
+    Ok(AudioDevices {
+        devices,
+        selected: AudioDeviceSettings::load(&app_data_dir).input,
+    })
+}
+
+/// Select the input device for recordings, or the system default with None
+///
+/// Emits `audio-device-changed` so a recording in progress switches to it.
+#[tauri::command]
+pub async fn set_audio_device(
+    app: AppHandle,
+    id: Option<String>,
+) -> Result<Option<SelectedAudioDevice>, AppError> {
+    let app_data_dir = app.path().app_data_dir()?;
+    let input = match id.as_deref().map(str::trim).filter(|id| !id.is_empty()) {
+        Some(id) => {
+            let devices = tokio::task::spawn_blocking(input_devices).await?;
+            Some(select_device(id, cfg!(target_os = "macos"), &devices)?)
+        }
+        None => None,
+    };
+    let settings = AudioDeviceSettings { input };
+    settings.save(&app_data_dir)?;
+    tracing::info!(device = ?settings.input, "Audio input device selected");
+    let _ = app.emit("audio-device-changed", &settings.input);
+    Ok(settings.input)
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(provider: &'static str, role: VoiceRole, status: ProviderStatus) -> ProviderCheck {
+        ProviderCheck {
+            provider,
+            role,
+            status,
+            latency_ms: None,
+            error: None,
+        }
+    }
+
+    #[test]
+    fn test_problems() {
+        let device = AudioInputDevice {
+            id: "BuiltInMicrophoneDevice".to_string(),
+            name: "MacBook Pro Microphone".to_string(),
+            is_default: true,
+        };
+        let providers = vec![
+            check("deepgram", VoiceRole::SpeechToText, ProviderStatus::Ready),
+            check(
+                "elevenlabs",
+                VoiceRole::TextToSpeech,
+                ProviderStatus::Unauthorized,
+            ),
+            check("openai-tts", VoiceRole::TextToSpeech, ProviderStatus::Ready),
+        ];
+        assert!(problems(
+            PermissionStatus::Authorized,
+            true,
+            &[device.clone()],
+            &providers
+        )
+        .is_empty());
+
+        let providers = vec![
+            check(
+                "deepgram",
+                VoiceRole::SpeechToText,
+                ProviderStatus::NotConfigured,
+            ),
+            check(
+                "elevenlabs",
+                VoiceRole::TextToSpeech,
+                ProviderStatus::Unauthorized,
+            ),
+            check(
+                "openai-tts",
+                VoiceRole::TextToSpeech,
+                ProviderStatus::NotConfigured,
+            ),
+        ];
+        let found = problems(PermissionStatus::Denied, true, &[], &providers);
+        assert_eq!(found.len(), 4);
+        assert!(found[0].starts_with("Microphone access is off"));
+        assert_eq!(found[1], "No microphone is connected");
+        assert_eq!(found[2], "No speech-to-text provider is configured");
+        assert_eq!(
+            found[3],
+            "No text-to-speech provider is working: elevenlabs (unauthorized)"
+        );
+
+        // Without native device listing, an empty list isn't a problem
+        assert_eq!(
+            problems(PermissionStatus::Unavailable, false, &[], &providers).len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_providers_from_secrets() {
+        let secrets = Secrets {
+            deepgram_api_key: Some("dg-key".to_string()),
+            elevenlabs_api_key: Some("  ".to_string()),
+            ..Secrets::default()
+        };
+        let configured: Vec<&str> = providers(&secrets)
+            .iter()
+            .filter(|p| p.key.is_some())
+            .map(|p| p.name)
+            .collect();
+        assert_eq!(configured, vec!["deepgram"]);
+        assert_eq!(
+            parse_status(r#"{"status":2}"#),
+            Ok(PermissionStatus::Denied)
+        );
+    }
+
+    #[test]
+    fn test_select_device() {
+        let devices = vec![AudioInputDevice {
+            id: "AppleUSBAudioEngine:Jabra".to_string(),
+            name: "Jabra Evolve2".to_string(),
+            is_default: false,
+        }];
+        assert_eq!(
+            select_device("AppleUSBAudioEngine:Jabra", true, &devices).unwrap(),
+            SelectedAudioDevice {
+                id: "AppleUSBAudioEngine:Jabra".to_string(),
+                name: Some("Jabra Evolve2".to_string()),
+            }
+        );
+        assert!(select_device("unplugged", true, &devices).is_err());
+
+        // Webview ids can't be checked natively and are stored as given
+        assert_eq!(select_device("f3a9", false, &[]).unwrap().name, None);
+    }
+}
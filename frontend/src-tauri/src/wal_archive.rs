@@ -0,0 +1,835 @@
+//! Opt-in WAL archiving and point-in-time restore.
+//!
+//! This module provides:
+//! - Settings kept in wal-archive.json; while enabled, PostgreSQL copies each
+//!   completed WAL segment into the app data dir's `wal-archive/wal` folder
+//! - Base backups taken with `pg_basebackup` when archiving is turned on and
+//!   every `base_backup_days` after, keeping the newest `keep_base_backups`
+//! - Hourly maintenance compressing archived segments and removing those
+//!   older than the oldest kept base backup
+//! - `restore_to_timestamp`, rewinding the database to any moment covered by
+//!   the archive, e.g. just before an accidental bulk delete of notes
+//!
+//! Nightly dumps only restore whole days; the archive makes every change
+//! since the oldest kept base backup recoverable. A restore keeps the data
+//! directory it replaced in `wal-archive/before-restore` until the next one.
+
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+use tauri::{AppHandle, Manager};
+
+use crate::backup::postgres_manager;
+use crate::config::{load_json, save_json_atomic};
+use crate::database::PostgresManager;
+use crate::diagnostics::dir_size;
+use crate::error::{database_not_running, AppError};
+use crate::scheduler::{JobAction, Schedule, ScheduledJob, Scheduler, WAL_ARCHIVE_JOB_ID};
+use crate::services::{ServiceCommand, ServiceManager, ServicePhase};
+
+/// Folder under the app data dir holding the archive
+const ARCHIVE_DIR: &str = "wal-archive";
+
+/// Archived segments are compressed once untouched this long, so a copy
+/// still being written by `archive_command` is left alone
+const COMPRESS_AFTER: Duration = Duration::from_secs(60);
+
+/// Longest to wait for the current WAL segment to be archived before a restore
+const ARCHIVE_WAIT: Duration = Duration::from_secs(30);
+
+/// How often maintenance runs
+const MAINTENANCE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Only one base backup, maintenance pass or restore runs at a time
+static ARCHIVE_LOCK: Mutex<()> = Mutex::new(());
+
+/// WAL archiving settings
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WalArchiveSettings {
+    /// Archive WAL; takes effect when the database restarts
+    pub enabled: bool,
+    /// Days between base backups
+    pub base_backup_days: u32,
+    /// Base backups to keep; the archive reaches back to the oldest one
+    pub keep_base_backups: usize,
+}
+
+impl Default for WalArchiveSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            base_backup_days: 7,
+            keep_base_backups: 2,
+        }
+    }
+}
+
+impl WalArchiveSettings {
+    fn path(app_data_dir: &Path) -> PathBuf {
+        app_data_dir.join("wal-archive.json")
+    }
+
+    /// Load settings, returning defaults if missing or invalid
+    pub fn load(app_data_dir: &Path) -> Self {
+        load_json(&Self::path(app_data_dir)).unwrap_or_default()
+    }
+
+    /// Save settings atomically
+    pub fn save(&self, app_data_dir: &Path) -> Result<(), String> {
+        save_json_atomic(&Self::path(app_data_dir), self)
+    }
+
+    /// Validate settings
+    pub fn validate(&self) -> Result<(), String> {
+        if !(1..=90).contains(&self.base_backup_days) {
+            return Err("Base backups must be taken every 1 to 90 days".to_string());
+        }
+        if self.keep_base_backups == 0 {
+            return Err("At least one base backup must be kept".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// A base backup the archive can be replayed onto
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BaseBackup {
+    /// Folder name under `wal-archive/base`, e.g. `base-20250101-120000`
+    pub id: String,
+    pub started_at: DateTime<Utc>,
+    /// Earliest time the database can be restored to from this backup
+    pub finished_at: DateTime<Utc>,
+    /// First WAL segment replay needs
+    pub start_segment: String,
+    pub size_bytes: u64,
+}
+
+/// What the archive currently covers
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WalArchiveStatus {
+    pub enabled: bool,
+    /// Oldest first
+    pub base_backups: Vec<BaseBackup>,
+    pub earliest_restore_point: Option<DateTime<Utc>>,
+    /// When the newest segment was archived
+    pub latest_restore_point: Option<DateTime<Utc>>,
+    pub archived_segments: usize,
+    /// Disk used by base backups and segments
+    pub size_bytes: u64,
+}
+
+/// Result of `restore_to_timestamp`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PointInTimeRestore {
+    pub target: DateTime<Utc>,
+    /// Base backup the archive was replayed onto
+    pub base_backup: String,
+    /// Where the replaced data directory was kept
+    pub previous_data_dir: PathBuf,
+}
+
+fn archive_root(app_data_dir: &Path) -> PathBuf {
+    app_data_dir.join(ARCHIVE_DIR)
+}
+
+fn wal_dir(app_data_dir: &Path) -> PathBuf {
+    archive_root(app_data_dir).join("wal")
+}
+
+fn base_dir(app_data_dir: &Path) -> PathBuf {
+    archive_root(app_data_dir).join("base")
+}
+
+fn restore_dir(app_data_dir: &Path) -> PathBuf {
+    archive_root(app_data_dir).join("restore")
+}
+
+fn previous_data_dir(app_data_dir: &Path) -> PathBuf {
+    archive_root(app_data_dir).join("before-restore")
+}
+
+/// Folder PostgreSQL archives into, or None when archiving is off
+pub(crate) fn enabled_wal_dir(app_data_dir: &Path) -> Option<PathBuf> {
+    WalArchiveSettings::load(app_data_dir)
+        .enabled
+        .then(|| wal_dir(app_data_dir))
+}
+
+/// Whether a file name is a WAL segment (timeline, log and segment in hex)
+fn is_segment(name: &str) -> bool {
+    name.len() == 24 && name.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Position of an archived segment or backup history file in the WAL,
+/// ignoring its timeline so segments from before and after a restore compare
+fn segment_position(name: &str) -> Option<&str> {
+    let segment = name.get(..24).filter(|s| is_segment(s))?;
+    Some(&segment[8..])
+}
+
+/// First segment a base backup needs, from its backup_label
+fn parse_start_segment(label: &str) -> Option<String> {
+    let line = label
+        .lines()
+        .find(|line| line.starts_with("START WAL LOCATION:"))?;
+    let file = line.split("(file ").nth(1)?.trim_end_matches(')').trim();
+    is_segment(file).then(|| file.to_string())
+}
+
+/// Parse an RFC 3339 restore target, which can't be in the future
+fn parse_target(timestamp: &str, now: DateTime<Utc>) -> Result<DateTime<Utc>, String> {
+    let target = DateTime::parse_from_rfc3339(timestamp)
+        .map_err(|e| format!("Invalid timestamp '{}': {}", timestamp, e))?
+        .with_timezone(&Utc);
+    if target > now {
+        return Err("Can't restore to a time in the future".to_string());
+    }
+    Ok(target)
+}
+
+/// Target in the form `recovery_target_time` takes
+fn postgres_time(target: DateTime<Utc>) -> String {
+    target.format("%Y-%m-%d %H:%M:%S%.6f+00").to_string()
+}
+
+/// Newest base backup finished by `target`
+fn pick_base_backup(backups: &[BaseBackup], target: DateTime<Utc>) -> Result<BaseBackup, String> {
+    backups
+        .iter()
+        .rev()
+        .find(|backup| backup.finished_at <= target)
+        .cloned()
+        .ok_or_else(|| match backups.first() {
+            Some(oldest) => format!(
+                "The archive only reaches back to {}",
+                oldest.finished_at.to_rfc3339()
+            ),
+            None => "No base backup has been taken yet".to_string(),
+        })
+}
+
+/// Base backups on disk, oldest first
+fn list_base_backups(app_data_dir: &Path) -> Vec<BaseBackup> {
+    let dir = base_dir(app_data_dir);
+    let Ok(entries) = fs::read_dir(&dir) else {
+        return Vec::new();
+    };
+    let mut backups: Vec<BaseBackup> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|path| load_json::<BaseBackup>(&path))
+        .filter(|backup| dir.join(&backup.id).is_dir())
+        .collect();
+    backups.sort_by_key(|backup| backup.finished_at);
+    backups
+}
+
+/// Whether a new base backup is due
+fn base_backup_due(backups: &[BaseBackup], days: u32, now: DateTime<Utc>) -> bool {
+    match backups.last() {
+        Some(newest) => now - newest.finished_at >= ChronoDuration::days(days.into()),
+        None => true,
+    }
+}
+
+/// Take a base backup of the running server into the archive
+fn take_base_backup(manager: &PostgresManager, app_data_dir: &Path) -> Result<BaseBackup, String> {
+    let dir = base_dir(app_data_dir);
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {:?}: {}", dir, e))?;
+    let started_at = Utc::now();
+    let id = format!("base-{}", started_at.format("%Y%m%d-%H%M%S"));
+    let dest = dir.join(&id);
+
+    tracing::info!("Taking WAL archive base backup {}", id);
+    manager.base_backup(&dest)?;
+    let label = fs::read_to_string(dest.join("backup_label"))
+        .map_err(|e| format!("Failed to read backup_label: {}", e))?;
+    let start_segment = parse_start_segment(&label)
+        .ok_or_else(|| "backup_label has no start WAL location".to_string())?;
+
+    let backup = BaseBackup {
+        id: id.clone(),
+        started_at,
+        finished_at: Utc::now(),
+        start_segment,
+        size_bytes: dir_size(&dest),
+    };
+    save_json_atomic(&dir.join(format!("{}.json", id)), &backup)?;
+    Ok(backup)
+}
+
+/// Delete the oldest base backups beyond `keep`; returns those left
+fn prune_base_backups(
+    app_data_dir: &Path,
+    mut backups: Vec<BaseBackup>,
+    keep: usize,
+) -> Vec<BaseBackup> {
+    let dir = base_dir(app_data_dir);
+    let excess = backups.len().saturating_sub(keep);
+    for backup in backups.drain(..excess) {
+        tracing::info!("Removing WAL archive base backup {}", backup.id);
+        if let Err(e) = fs::remove_dir_all(dir.join(&backup.id)) {
+            tracing::warn!("Failed to remove base backup {}: {}", backup.id, e);
+            continue;
+        }
+        let _ = fs::remove_file(dir.join(format!("{}.json", backup.id)));
+    }
+    backups
+}
+
+/// Delete segments that come before `start_segment`; timeline history files
+/// are always kept. Returns the number deleted.
+fn prune_segments(wal_dir: &Path, start_segment: &str) -> usize {
+    let Some(start) = segment_position(start_segment) else {
+        return 0;
+    };
+    let Ok(entries) = fs::read_dir(wal_dir) else {
+        return 0;
+    };
+    let mut removed = 0;
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.contains(".history") {
+            continue;
+        }
+        if segment_position(&name).is_some_and(|position| position < start)
+            && fs::remove_file(entry.path()).is_ok()
+        {
+            removed += 1;
+        }
+    }
+    removed
+}
+
+/// Gzip archived files untouched for `min_age`; returns the number compressed
+fn compress_segments(wal_dir: &Path, min_age: Duration) -> Result<usize, String> {
+    let Ok(entries) = fs::read_dir(wal_dir) else {
+        return Ok(0);
+    };
+    let now = SystemTime::now();
+    let mut compressed = 0;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.ends_with(".gz") || name.ends_with(".tmp") || !path.is_file() {
+            continue;
+        }
+        let settled = entry
+            .metadata()
+            .and_then(|meta| meta.modified())
+            .is_ok_and(|modified| now.duration_since(modified).unwrap_or_default() >= min_age);
+        if !settled {
+            continue;
+        }
+
+        let target = wal_dir.join(format!("{}.gz", name));
+        let partial = wal_dir.join(format!("{}.gz.tmp", name));
+        let write = || -> std::io::Result<()> {
+            let mut input = BufReader::new(File::open(&path)?);
+            let mut encoder = GzEncoder::new(
+                BufWriter::new(File::create(&partial)?),
+                Compression::default(),
+            );
+            std::io::copy(&mut input, &mut encoder)?;
+            encoder.finish()?.into_inner()?.sync_all()?;
+            fs::rename(&partial, &target)?;
+            fs::remove_file(&path)
+        };
+        if let Err(e) = write() {
+            let _ = fs::remove_file(&partial);
+            return Err(format!("Failed to compress {}: {}", name, e));
+        }
+        compressed += 1;
+    }
+    Ok(compressed)
+}
+
+/// Copy the segments replay from `start_segment` needs into `staging`,
+/// decompressed, along with every timeline history file
+fn stage_segments(wal_dir: &Path, staging: &Path, start_segment: &str) -> Result<usize, String> {
+    let start = segment_position(start_segment)
+        .ok_or_else(|| format!("Invalid start segment {}", start_segment))?;
+    if staging.exists() {
+        fs::remove_dir_all(staging).map_err(|e| format!("Failed to clear {:?}: {}", staging, e))?;
+    }
+    fs::create_dir_all(staging).map_err(|e| format!("Failed to create {:?}: {}", staging, e))?;
+
+    let entries =
+        fs::read_dir(wal_dir).map_err(|e| format!("Failed to read the WAL archive: {}", e))?;
+    let mut staged = 0;
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let plain = name.strip_suffix(".gz").unwrap_or(&name);
+        let needed = plain.ends_with(".history")
+            || segment_position(plain).is_some_and(|position| position >= start);
+        if !needed || name.ends_with(".tmp") {
+            continue;
+        }
+        let copy = || -> std::io::Result<()> {
+            let mut output = File::create(staging.join(plain))?;
+            if name.ends_with(".gz") {
+                let mut decoder = GzDecoder::new(BufReader::new(File::open(entry.path())?));
+                std::io::copy(&mut decoder, &mut output)?;
+            } else {
+                std::io::copy(&mut File::open(entry.path())?, &mut output)?;
+            }
+            Ok(())
+        };
+        copy().map_err(|e| format!("Failed to stage {}: {}", name, e))?;
+        staged += 1;
+    }
+    Ok(staged)
+}
+
+/// Switch to a new WAL segment and wait until the finished one is archived,
+/// so everything written so far can be replayed
+fn archive_current_wal(manager: &PostgresManager) -> Result<(), String> {
+    let segment = manager.run_sql("SELECT pg_walfile_name(pg_switch_wal())")?;
+    let segment = segment.trim().to_string();
+    let position = segment_position(&segment)
+        .ok_or_else(|| format!("Unexpected WAL file name '{}'", segment))?
+        .to_string();
+    let started = Instant::now();
+    loop {
+        let archived =
+            manager.run_sql("SELECT coalesce(last_archived_wal, '') FROM pg_stat_archiver")?;
+        if segment_position(archived.trim()).is_some_and(|archived| archived >= position.as_str()) {
+            return Ok(());
+        }
+        if started.elapsed() > ARCHIVE_WAIT {
+            return Err(format!(
+                "WAL segment {} wasn't archived within {:?}",
+                segment, ARCHIVE_WAIT
+            ));
+        }
+        std::thread::sleep(Duration::from_millis(500));
+    }
+}
+
+/// Compress new segments, take a base backup when due, and drop what the
+/// kept base backups no longer need
+fn maintain(
+    manager: &PostgresManager,
+    app_data_dir: &Path,
+    settings: &WalArchiveSettings,
+) -> Result<(), String> {
+    let _guard = ARCHIVE_LOCK.lock().unwrap();
+    let wal = wal_dir(app_data_dir);
+    let compressed = compress_segments(&wal, COMPRESS_AFTER)?;
+
+    let mut backups = list_base_backups(app_data_dir);
+    if base_backup_due(&backups, settings.base_backup_days, Utc::now()) {
+        backups.push(take_base_backup(manager, app_data_dir)?);
+    }
+    let backups = prune_base_backups(app_data_dir, backups, settings.keep_base_backups);
+    let removed = backups
+        .first()
+        .map_or(0, |oldest| prune_segments(&wal, &oldest.start_segment));
+    tracing::debug!(
+        "WAL archive maintenance: {} compressed, {} removed",
+        compressed,
+        removed
+    );
+    Ok(())
+}
+
+/// Stop the server and replay the archive onto `base` up to `target`
+fn recover(
+    manager: &PostgresManager,
+    app_data_dir: &Path,
+    base: &BaseBackup,
+    target: DateTime<Utc>,
+) -> Result<(), String> {
+    let _guard = ARCHIVE_LOCK.lock().unwrap();
+    manager.stop()?;
+    let staging = restore_dir(app_data_dir);
+    let staged = stage_segments(&wal_dir(app_data_dir), &staging, &base.start_segment)?;
+    tracing::info!(
+        "Restoring to {} from base backup {} with {} archived files",
+        target.to_rfc3339(),
+        base.id,
+        staged
+    );
+    let result = manager.recover_to(
+        &base_dir(app_data_dir).join(&base.id),
+        &staging,
+        &postgres_time(target),
+        &previous_data_dir(app_data_dir),
+    );
+    let _ = fs::remove_dir_all(&staging);
+    result
+}
+
+fn status(app_data_dir: &Path) -> WalArchiveStatus {
+    let settings = WalArchiveSettings::load(app_data_dir);
+    let base_backups = list_base_backups(app_data_dir);
+    let segments: Vec<fs::Metadata> = fs::read_dir(wal_dir(app_data_dir))
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter_map(|e| e.metadata().ok())
+                .collect()
+        })
+        .unwrap_or_default();
+    let latest_restore_point = segments
+        .iter()
+        .filter_map(|meta| meta.modified().ok())
+        .max()
+        .map(DateTime::<Utc>::from);
+    WalArchiveStatus {
+        enabled: settings.enabled,
+        earliest_restore_point: base_backups.first().map(|backup| backup.finished_at),
+        latest_restore_point: latest_restore_point.filter(|_| !base_backups.is_empty()),
+        archived_segments: segments.len(),
+        size_bytes: dir_size(&archive_root(app_data_dir)),
+        base_backups,
+    }
+}
+
+/// Take a base backup now
+async fn base_backup_now(app: &AppHandle) -> Result<BaseBackup, String> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let manager = postgres_manager(app).ok_or_else(|| "Database is not running".to_string())?;
+    tokio::task::spawn_blocking(move || {
+        let _guard = ARCHIVE_LOCK.lock().unwrap();
+        take_base_backup(&manager, &app_data_dir)
+    })
+    .await
+    .map_err(|e| format!("Task panicked: {}", e))?
+}
+
+/// Run archive maintenance if archiving is on
+pub async fn run_maintenance(app: &AppHandle) -> Result<(), String> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let settings = WalArchiveSettings::load(&app_data_dir);
+    if !settings.enabled {
+        return Ok(());
+    }
+    let manager = postgres_manager(app).ok_or_else(|| "Database is not running".to_string())?;
+    tokio::task::spawn_blocking(move || maintain(&manager, &app_data_dir, &settings))
+        .await
+        .map_err(|e| format!("Task panicked: {}", e))?
+}
+
+/// Register or remove the maintenance job
+pub fn apply_schedule(app: &AppHandle, settings: &WalArchiveSettings) {
+    let scheduler = app.state::<Scheduler>();
+    scheduler.remove_job(WAL_ARCHIVE_JOB_ID);
+
+    if settings.enabled {
+        scheduler.upsert_job(
+            ScheduledJob {
+                id: WAL_ARCHIVE_JOB_ID.to_string(),
+                name: "WAL archive maintenance".to_string(),
+                schedule: Schedule::Interval {
+                    every_secs: MAINTENANCE_INTERVAL.as_secs(),
+                },
+                skip_on_battery: true,
+                wait_for_idle: false,
+                jitter_secs: 60,
+                action: JobAction::WalArchiveMaintenance,
+            },
+            chrono::Local::now(),
+        );
+    }
+}
+
+/// Load persisted settings and schedule maintenance
+pub fn start(app: &AppHandle) {
+    if let Ok(app_data_dir) = app.path().app_data_dir() {
+        apply_schedule(app, &WalArchiveSettings::load(&app_data_dir));
+    }
+}
+
+// ============================================================
+// Commands
+// ============================================================
+
+/// Get the WAL archiving settings
+#[tauri::command]
+pub async fn get_wal_archive_settings(app: AppHandle) -> Result<WalArchiveSettings, AppError> {
+    let app_data_dir = app.path().app_data_dir()?;
+    Ok(WalArchiveSettings::load(&app_data_dir))
+}
+
+/// Update the WAL archiving settings
+///
+/// Turning archiving on or off restarts the database; turning it on also
+/// takes the first base backup. An existing archive is kept when archiving
+/// is turned off.
+#[tauri::command]
+pub async fn set_wal_archive_settings(
+    app: AppHandle,
+    settings: WalArchiveSettings,
+) -> Result<WalArchiveStatus, AppError> {
+    settings.validate().map_err(AppError::InvalidInput)?;
+    let app_data_dir = app.path().app_data_dir()?;
+    let toggled = WalArchiveSettings::load(&app_data_dir).enabled != settings.enabled;
+    let services = app.state::<ServiceManager>();
+    if toggled && services.state().busy.is_some() {
+        return Err(AppError::Conflict(
+            "Services are starting or restarting; try again once they are running".to_string(),
+        ));
+    }
+
+    settings.save(&app_data_dir)?;
+    apply_schedule(&app, &settings);
+    if toggled {
+        // archive_mode only changes when the server restarts
+        services.send(ServiceCommand::RestartDatabase).await?;
+        if settings.enabled {
+            base_backup_now(&app).await.map_err(AppError::Database)?;
+        }
+    }
+    Ok(status(&app_data_dir))
+}
+
+/// Get what the WAL archive covers
+#[tauri::command]
+pub async fn get_wal_archive_status(app: AppHandle) -> Result<WalArchiveStatus, AppError> {
+    let app_data_dir = app.path().app_data_dir()?;
+    Ok(tokio::task::spawn_blocking(move || status(&app_data_dir)).await?)
+}
+
+/// Rewind the database to `timestamp` (RFC 3339) by replaying the WAL
+/// archive onto the newest base backup taken before it
+///
+/// The backend is stopped for the restore and the services are restarted
+/// even if it fails. Changes made after `timestamp` are lost, but the
+/// replaced data directory is kept until the next restore.
+#[tauri::command]
+pub async fn restore_to_timestamp(
+    app: AppHandle,
+    timestamp: String,
+) -> Result<PointInTimeRestore, AppError> {
+    let target = parse_target(&timestamp, Utc::now()).map_err(AppError::InvalidInput)?;
+    let app_data_dir = app.path().app_data_dir()?;
+    if !WalArchiveSettings::load(&app_data_dir).enabled {
+        return Err(AppError::InvalidInput("WAL archiving is off".to_string()));
+    }
+    let base = pick_base_backup(&list_base_backups(&app_data_dir), target)
+        .map_err(AppError::InvalidInput)?;
+
+    let services = app.state::<ServiceManager>();
+    let state = services.state();
+    if state.busy.is_some() || state.postgres != ServicePhase::Running {
+        return Err(AppError::Conflict(
+            "The database must be running to restore; try again once it is".to_string(),
+        ));
+    }
+    let manager = postgres_manager(&app).ok_or_else(database_not_running)?;
+
+    let archiver = manager.clone();
+    tokio::task::spawn_blocking(move || archive_current_wal(&archiver))
+        .await?
+        .map_err(AppError::Database)?;
+    services.send(ServiceCommand::StopBackend).await?;
+
+    let (dir, replayed) = (app_data_dir.clone(), base.clone());
+    let recovered =
+        tokio::task::spawn_blocking(move || recover(&manager, &dir, &replayed, target)).await;
+
+    let restarted = services.send(ServiceCommand::RestartDatabase).await;
+    if let Err(ref e) = restarted {
+        tracing::error!(
+            "Services failed to start after point-in-time restore: {}",
+            e
+        );
+    }
+    recovered?.map_err(AppError::Database)?;
+    restarted?;
+
+    // Recovery starts a new timeline; later restores replay from a backup on it
+    if let Err(e) = base_backup_now(&app).await {
+        tracing::warn!("Failed to take a base backup after restoring: {}", e);
+    }
+    tracing::info!(
+        "Restored the database to {} from {}",
+        target.to_rfc3339(),
+        base.id
+    );
+    Ok(PointInTimeRestore {
+        target,
+        base_backup: base.id,
+        previous_data_dir: previous_data_dir(&app_data_dir),
+    })
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    const LABEL: &str = "START WAL LOCATION: 0/2000028 (file 000000010000000000000002)\n\
+                         CHECKPOINT LOCATION: 0/2000060\n\
+                         BACKUP METHOD: streamed\n";
+
+    fn backup(id: &str, finished_at: DateTime<Utc>, start_segment: &str) -> BaseBackup {
+        BaseBackup {
+            id: id.to_string(),
+            started_at: finished_at - ChronoDuration::minutes(1),
+            finished_at,
+            start_segment: start_segment.to_string(),
+            size_bytes: 0,
+        }
+    }
+
+    #[test]
+    fn test_validate_settings() {
+        assert!(WalArchiveSettings::default().validate().is_ok());
+        let mut settings = WalArchiveSettings {
+            base_backup_days: 0,
+            ..Default::default()
+        };
+        assert!(settings.validate().is_err());
+        settings.base_backup_days = 7;
+        settings.keep_base_backups = 0;
+        assert!(settings.validate().is_err());
+    }
+
+    #[test]
+    fn test_parse_start_segment() {
+        assert_eq!(
+            parse_start_segment(LABEL).as_deref(),
+            Some("000000010000000000000002")
+        );
+        assert_eq!(parse_start_segment("BACKUP METHOD: streamed\n"), None);
+    }
+
+    #[test]
+    fn test_segment_position() {
+        assert_eq!(
+            segment_position("000000020000000000000003"),
+            Some("0000000000000003")
+        );
+        assert_eq!(
+            segment_position("000000010000000000000002.00000028.backup"),
+            Some("0000000000000002")
+        );
+        assert_eq!(segment_position("00000002.history"), None);
+    }
+
+    #[test]
+    fn test_parse_target() {
+        let now = Utc::now();
+        let past = (now - ChronoDuration::hours(1)).to_rfc3339();
+        assert!(parse_target(&past, now).is_ok());
+        let future = (now + ChronoDuration::hours(1)).to_rfc3339();
+        assert!(parse_target(&future, now).is_err());
+        assert!(parse_target("yesterday", now).is_err());
+    }
+
+    #[test]
+    fn test_pick_base_backup() {
+        let now = Utc::now();
+        let backups = vec![
+            backup(
+                "a",
+                now - ChronoDuration::days(8),
+                "000000010000000000000002",
+            ),
+            backup(
+                "b",
+                now - ChronoDuration::days(1),
+                "000000010000000000000009",
+            ),
+        ];
+        let target = now - ChronoDuration::days(2);
+        assert_eq!(pick_base_backup(&backups, target).unwrap().id, "a");
+        assert_eq!(pick_base_backup(&backups, now).unwrap().id, "b");
+        assert!(pick_base_backup(&backups, now - ChronoDuration::days(9)).is_err());
+        assert!(pick_base_backup(&[], now).is_err());
+    }
+
+    #[test]
+    fn test_base_backup_due() {
+        let now = Utc::now();
+        assert!(base_backup_due(&[], 7, now));
+        let recent = [backup("a", now - ChronoDuration::days(3), "")];
+        assert!(!base_backup_due(&recent, 7, now));
+        assert!(base_backup_due(&recent, 2, now));
+    }
+
+    #[test]
+    fn test_prune_segments_keeps_history() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in [
+            "000000010000000000000001.gz",
+            "000000010000000000000002",
+            "000000010000000000000003.gz",
+            "00000002.history",
+        ] {
+            fs::write(dir.path().join(name), "wal").unwrap();
+        }
+        assert_eq!(prune_segments(dir.path(), "000000010000000000000002"), 1);
+        assert!(!dir.path().join("000000010000000000000001.gz").exists());
+        assert!(dir.path().join("000000010000000000000002").exists());
+        assert!(dir.path().join("00000002.history").exists());
+    }
+
+    #[test]
+    fn test_compress_then_stage() {
+        let dir = tempfile::tempdir().unwrap();
+        let wal = dir.path().join("wal");
+        fs::create_dir_all(&wal).unwrap();
+        fs::write(wal.join("000000010000000000000001"), "one").unwrap();
+        fs::write(wal.join("000000010000000000000002"), "two").unwrap();
+        fs::write(wal.join("00000002.history"), "history").unwrap();
+
+        // Nothing is old enough yet
+        assert_eq!(
+            compress_segments(&wal, Duration::from_secs(3600)).unwrap(),
+            0
+        );
+        assert_eq!(compress_segments(&wal, Duration::ZERO).unwrap(), 3);
+        assert!(wal.join("000000010000000000000002.gz").exists());
+        assert!(!wal.join("000000010000000000000002").exists());
+
+        let staging = dir.path().join("restore");
+        let staged = stage_segments(&wal, &staging, "000000010000000000000002").unwrap();
+        assert_eq!(staged, 2);
+        let mut contents = String::new();
+        File::open(staging.join("000000010000000000000002"))
+            .unwrap()
+            .read_to_string(&mut contents)
+            .unwrap();
+        assert_eq!(contents, "two");
+        assert!(staging.join("00000002.history").exists());
+        assert!(!staging.join("000000010000000000000001").exists());
+    }
+
+    #[test]
+    fn test_prune_base_backups() {
+        let dir = tempfile::tempdir().unwrap();
+        let now = Utc::now();
+        let backups: Vec<BaseBackup> = ["a", "b", "c"]
+            .iter()
+            .enumerate()
+            .map(|(i, id)| {
+                let b = backup(id, now - ChronoDuration::days(3 - i as i64), "");
+                fs::create_dir_all(base_dir(dir.path()).join(id)).unwrap();
+                save_json_atomic(&base_dir(dir.path()).join(format!("{}.json", id)), &b).unwrap();
+                b
+            })
+            .collect();
+        assert_eq!(list_base_backups(dir.path()), backups);
+
+        let kept = prune_base_backups(dir.path(), backups, 2);
+        assert_eq!(kept.len(), 2);
+        assert_eq!(kept[0].id, "b");
+        assert!(!base_dir(dir.path()).join("a").exists());
+        assert_eq!(list_base_backups(dir.path()), kept);
+    }
+}
@@ -0,0 +1,261 @@
+//! Detecting and recovering from a hung webview.
+//!
+//! This module provides:
+//! - A heartbeat the frontend sends every few seconds per window; a visible
+//!   window whose heartbeat stops for `HANG_TIMEOUT` is reported as hung
+//! - A notice (routed by `notifications`) and a `webview-unresponsive`
+//!   event when a window hangs, and `webview-recovered` when its heartbeat
+//!   returns
+//! - Recovery without restarting services: reloading the page, or
+//!   destroying the window and building it again from the app config
+//!   (reload is also in the tray menu)
+//!
+//! Windows are only watched once they have sent a first heartbeat, and hidden
+//! or minimized windows are skipped since webviews throttle their timers.
+
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::error::AppError;
+use crate::notifications::{Notice, Severity};
+
+/// Missing heartbeats for this long mark a window as hung
+const HANG_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// How often heartbeats are checked
+const CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy)]
+struct Heartbeat {
+    last: Instant,
+    hung: bool,
+}
+
+/// Heartbeats per window label, kept in Tauri state
+#[derive(Default)]
+pub struct WebviewWatchdog {
+    heartbeats: Mutex<HashMap<String, Heartbeat>>,
+    /// Set while a window is being rebuilt, so its destruction isn't
+    /// mistaken for the app closing
+    recreating: AtomicBool,
+}
+
+impl WebviewWatchdog {
+    /// Record a heartbeat; returns true if the window had been hung
+    fn beat(&self, label: &str, now: Instant) -> bool {
+        let mut heartbeats = self.heartbeats.lock();
+        let previous = heartbeats.insert(
+            label.to_string(),
+            Heartbeat {
+                last: now,
+                hung: false,
+            },
+        );
+        previous.is_some_and(|heartbeat| heartbeat.hung)
+    }
+
+    /// Windows that just went quiet for too long, marking them hung
+    fn newly_hung(&self, now: Instant, is_watched: impl Fn(&str) -> bool) -> Vec<String> {
+        let mut heartbeats = self.heartbeats.lock();
+        let mut hung = Vec::new();
+        for (label, heartbeat) in heartbeats.iter_mut() {
+            if heartbeat.hung || now.duration_since(heartbeat.last) < HANG_TIMEOUT {
+                continue;
+            }
+            if !is_watched(label) {
+                // Don't count time spent hidden against the window
+                heartbeat.last = now;
+                continue;
+            }
+            heartbeat.hung = true;
+            hung.push(label.clone());
+        }
+        hung
+    }
+
+    fn forget(&self, label: &str) {
+        self.heartbeats.lock().remove(label);
+    }
+}
+
+/// Whether a window destroyed just now is being rebuilt by `recreate_window`
+/// rather than closed; clears the flag
+pub fn take_recreating(app: &AppHandle) -> bool {
+    app.try_state::<WebviewWatchdog>()
+        .is_some_and(|watchdog| watchdog.recreating.swap(false, Ordering::SeqCst))
+}
+
+/// Payload of `webview-unresponsive` and `webview-recovered`
+#[derive(Debug, Clone, Serialize)]
+struct WindowEvent<'a> {
+    label: &'a str,
+}
+
+fn is_watched(app: &AppHandle, label: &str) -> bool {
+    app.get_webview_window(label).is_some_and(|window| {
+        window.is_visible().unwrap_or(false) && !window.is_minimized().unwrap_or(false)
+    })
+}
+
+fn report_hang(app: &AppHandle, label: &str) {
+    tracing::warn!(
+        "Window {} has not responded for {}s",
+        label,
+        HANG_TIMEOUT.as_secs()
+    );
+    let _ = app.emit("webview-unresponsive", WindowEvent { label });
+    crate::notifications::notify(
+        app,
+        Notice::new(
+            "webview",
+            Severity::Error,
+            "Second Brain isn't responding",
+            "Choose Reload Window from the tray menu to recover. Your notes are safe.",
+        ),
+    );
+}
+
+/// Watch heartbeats for as long as the app runs
+pub fn start(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+            let hung = app
+                .state::<WebviewWatchdog>()
+                .newly_hung(Instant::now(), |label| is_watched(&app, label));
+            for label in hung {
+                report_hang(&app, &label);
+            }
+        }
+    });
+}
+
+/// Stop watching a closed window
+pub fn on_destroyed(app: &AppHandle, label: &str) {
+    if let Some(watchdog) = app.try_state::<WebviewWatchdog>() {
+        watchdog.forget(label);
+    }
+}
+
+/// Reload a window's page
+pub fn reload(app: &AppHandle, label: &str) -> Result<(), AppError> {
+    let window = app
+        .get_webview_window(label)
+        .ok_or_else(|| AppError::NotFound(format!("No window named {}", label)))?;
+    tracing::info!("Reloading window {}", label);
+    // A native reload works even when the page's script is stuck
+    window.reload()?;
+    app.state::<WebviewWatchdog>().forget(label);
+    Ok(())
+}
+
+/// Destroy the main window and build it again from the app config
+pub fn recreate(app: &AppHandle) -> Result<(), AppError> {
+    let config = app
+        .config()
+        .app
+        .windows
+        .iter()
+        .find(|window| window.label == "main")
+        .cloned()
+        .ok_or_else(|| AppError::NotFound("No main window in the app config".to_string()))?;
+
+    let watchdog = app.state::<WebviewWatchdog>();
+    tracing::info!("Recreating the main window");
+    if let Some(window) = app.get_webview_window("main") {
+        // Cleared by the window's `Destroyed` event
+        watchdog.recreating.store(true, Ordering::SeqCst);
+        if let Err(e) = window.destroy() {
+            watchdog.recreating.store(false, Ordering::SeqCst);
+            return Err(e.into());
+        }
+    }
+    watchdog.forget("main");
+    let window = tauri::WebviewWindowBuilder::from_config(app, &config)?.build()?;
+    window.set_focus()?;
+    Ok(())
+}
+
+// ============================================================
+// Commands
+// ============================================================
+
+/// Heartbeat from a window's frontend
+#[tauri::command]
+pub async fn webview_heartbeat(
+    app: AppHandle,
+    window: tauri::WebviewWindow,
+) -> Result<(), AppError> {
+    let label = window.label();
+    if app.state::<WebviewWatchdog>().beat(label, Instant::now()) {
+        tracing::info!("Window {} is responding again", label);
+        let _ = app.emit("webview-recovered", WindowEvent { label });
+    }
+    Ok(())
+}
+
+/// Reload a window's page, the main window by default
+#[tauri::command]
+pub async fn reload_webview(app: AppHandle, label: Option<String>) -> Result<(), AppError> {
+    reload(&app, label.as_deref().unwrap_or("main"))
+}
+
+/// Rebuild the main window without restarting services
+#[tauri::command]
+pub async fn recreate_window(app: AppHandle) -> Result<(), AppError> {
+    recreate(&app)
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hang_detection() {
+        let watchdog = WebviewWatchdog::default();
+        let start = Instant::now();
+
+        // Unknown windows are never reported
+        assert!(watchdog
+            .newly_hung(start + HANG_TIMEOUT * 2, |_| true)
+            .is_empty());
+
+        assert!(!watchdog.beat("main", start));
+        assert!(watchdog
+            .newly_hung(start + HANG_TIMEOUT / 2, |_| true)
+            .is_empty());
+
+        let late = start + HANG_TIMEOUT + Duration::from_secs(1);
+        assert_eq!(watchdog.newly_hung(late, |_| true), vec!["main"]);
+        // Reported once per hang
+        assert!(watchdog.newly_hung(late, |_| true).is_empty());
+
+        // The next heartbeat reports recovery
+        assert!(watchdog.beat("main", late));
+        assert!(!watchdog.beat("main", late));
+    }
+
+    #[test]
+    fn test_hidden_windows_are_not_hung() {
+        let watchdog = WebviewWatchdog::default();
+        let start = Instant::now();
+        watchdog.beat("main", start);
+
+        let late = start + HANG_TIMEOUT * 2;
+        assert!(watchdog.newly_hung(late, |_| false).is_empty());
+        // Time hidden doesn't count once the window is shown again
+        assert!(watchdog
+            .newly_hung(late + HANG_TIMEOUT / 2, |_| true)
+            .is_empty());
+    }
+}
@@ -0,0 +1,419 @@
+//! Secondary windows and restoring them across launches.
+//!
+//! This module provides:
+//! - Pop-out windows showing any app route, and a small always-on-top
+//!   floating chat
+//! - A record of which of them are open and the route each shows, saved to
+//!   `session-state.json` as they open, navigate and close
+//! - Reopening the recorded windows on the next launch once services are
+//!   running, unless turned off in the session settings
+//! - `clear_session_state` to forget the recorded windows
+//!
+//! Closing a window removes it from the record, except while the app is
+//! quitting: once exit is requested or the main window is gone, the windows
+//! still open are kept so they come back next time.
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use tauri::{AppHandle, Manager, WebviewUrl, WebviewWindowBuilder};
+
+use crate::config::{load_json, save_json_atomic};
+use crate::error::AppError;
+use crate::services::{ServiceManager, ServicePhase};
+
+/// Longest route accepted for a window
+const MAX_ROUTE_LEN: usize = 2048;
+
+/// Session restore settings
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SessionSettings {
+    /// Reopen the windows that were open when the app last quit
+    pub restore_windows: bool,
+}
+
+impl Default for SessionSettings {
+    fn default() -> Self {
+        Self {
+            restore_windows: true,
+        }
+    }
+}
+
+impl SessionSettings {
+    fn path(app_data_dir: &Path) -> PathBuf {
+        app_data_dir.join("session-settings.json")
+    }
+
+    /// Load settings, returning defaults if missing or invalid
+    pub fn load(app_data_dir: &Path) -> Self {
+        load_json(&Self::path(app_data_dir)).unwrap_or_default()
+    }
+
+    /// Save settings atomically
+    pub fn save(&self, app_data_dir: &Path) -> Result<(), String> {
+        save_json_atomic(&Self::path(app_data_dir), self)
+    }
+}
+
+/// Kind of secondary window
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WindowKind {
+    Popout,
+    FloatingChat,
+}
+
+impl WindowKind {
+    fn label_prefix(self) -> &'static str {
+        match self {
+            Self::Popout => "popout-",
+            Self::FloatingChat => "floating-chat-",
+        }
+    }
+}
+
+/// A secondary window as recorded
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedWindow {
+    pub label: String,
+    pub kind: WindowKind,
+    /// Route shown, e.g. `/notes`
+    pub route: String,
+}
+
+/// Recorded secondary windows
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SessionState {
+    pub windows: Vec<SavedWindow>,
+}
+
+impl SessionState {
+    fn path(app_data_dir: &Path) -> PathBuf {
+        app_data_dir.join("session-state.json")
+    }
+
+    pub fn load(app_data_dir: &Path) -> Self {
+        load_json(&Self::path(app_data_dir)).unwrap_or_default()
+    }
+
+    pub fn save(&self, app_data_dir: &Path) -> Result<(), String> {
+        save_json_atomic(&Self::path(app_data_dir), self)
+    }
+
+    /// Update a window's route; returns false for windows not recorded
+    fn navigate(&mut self, label: &str, route: &str) -> bool {
+        match self.windows.iter_mut().find(|window| window.label == label) {
+            Some(window) if window.route != route => {
+                window.route = route.to_string();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Forget a window; returns false if it wasn't recorded
+    fn remove(&mut self, label: &str) -> bool {
+        let before = self.windows.len();
+        self.windows.retain(|window| window.label != label);
+        self.windows.len() != before
+    }
+}
+
+/// Open secondary windows, kept in Tauri state
+#[derive(Default)]
+pub struct WindowSession {
+    next: AtomicU64,
+    state: Mutex<SessionState>,
+    /// Set once the app starts quitting, so closing windows stay recorded
+    quitting: AtomicBool,
+}
+
+/// Check a route is an in-app path
+fn validate_route(route: &str) -> Result<(), AppError> {
+    if !route.starts_with('/')
+        || route.starts_with("//")
+        || route.len() > MAX_ROUTE_LEN
+        || route.contains("://")
+        || route.chars().any(|c| c.is_whitespace() || c.is_control())
+    {
+        return Err(AppError::InvalidInput(format!("Invalid route: {}", route)));
+    }
+    Ok(())
+}
+
+/// URL of a route, matching the frontend's router
+fn route_url(route: &str) -> String {
+    // The production build uses hash routing, the dev server browser routing
+    if cfg!(debug_assertions) {
+        route.trim_start_matches('/').to_string()
+    } else {
+        format!("index.html#{}", route)
+    }
+}
+
+fn save(app: &AppHandle, state: SessionState) {
+    let Ok(app_data_dir) = app.path().app_data_dir() else {
+        return;
+    };
+    if let Err(e) = state.save(&app_data_dir) {
+        tracing::warn!("Failed to save session state: {}", e);
+    }
+}
+
+/// Open a secondary window on a route and record it
+fn open(app: &AppHandle, kind: WindowKind, route: &str) -> Result<String, AppError> {
+    validate_route(route)?;
+    let session = app.state::<WindowSession>();
+    let label = format!(
+        "{}{}",
+        kind.label_prefix(),
+        session.next.fetch_add(1, Ordering::Relaxed)
+    );
+    let builder = WebviewWindowBuilder::new(app, &label, WebviewUrl::App(route_url(route).into()));
+    let builder = match kind {
+        WindowKind::Popout => builder
+            .title("Second Brain")
+            .inner_size(960.0, 720.0)
+            .min_inner_size(480.0, 360.0),
+        WindowKind::FloatingChat => builder
+            .title("Chat — Second Brain")
+            .inner_size(420.0, 640.0)
+            .min_inner_size(320.0, 400.0)
+            .always_on_top(true),
+    };
+    builder.build()?;
+
+    let state = {
+        let mut state = session.state.lock();
+        state.windows.push(SavedWindow {
+            label: label.clone(),
+            kind,
+            route: route.to_string(),
+        });
+        state.clone()
+    };
+    save(app, state);
+    tracing::info!("Opened {:?} window {} on {}", kind, label, route);
+    Ok(label)
+}
+
+/// Stop removing closed windows from the record, since the app is quitting
+pub fn freeze(app: &AppHandle) {
+    if let Some(session) = app.try_state::<WindowSession>() {
+        session.quitting.store(true, Ordering::SeqCst);
+    }
+}
+
+/// Forget a secondary window closed by the user
+pub fn on_destroyed(app: &AppHandle, label: &str) {
+    let Some(session) = app.try_state::<WindowSession>() else {
+        return;
+    };
+    if session.quitting.load(Ordering::SeqCst) {
+        return;
+    }
+    let state = {
+        let mut state = session.state.lock();
+        if !state.remove(label) {
+            return;
+        }
+        state.clone()
+    };
+    save(app, state);
+}
+
+/// Reopen the windows recorded last session
+fn restore(app: &AppHandle, saved: Vec<SavedWindow>) {
+    if saved.is_empty() {
+        return;
+    }
+    tracing::info!("Restoring {} window(s) from the last session", saved.len());
+    for window in saved {
+        if let Err(e) = open(app, window.kind, &window.route) {
+            tracing::warn!("Failed to restore window on {}: {}", window.route, e);
+        }
+    }
+}
+
+/// Load the recorded windows and reopen them once services are running
+pub fn start(app: &AppHandle) {
+    let app_data_dir = app.path().app_data_dir().ok();
+    let saved = app_data_dir
+        .as_deref()
+        .map(SessionState::load)
+        .unwrap_or_default();
+    let restore_windows = app_data_dir
+        .as_deref()
+        .map_or(true, |dir| SessionSettings::load(dir).restore_windows);
+    // Restored windows are recorded again as they open, under new labels
+    if !restore_windows || saved.windows.is_empty() {
+        if !saved.windows.is_empty() {
+            save(app, SessionState::default());
+        }
+        return;
+    }
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut services = app.state::<ServiceManager>().subscribe();
+        if services
+            .wait_for(|state| state.backend == ServicePhase::Running)
+            .await
+            .is_err()
+        {
+            return;
+        }
+        restore(&app, saved.windows);
+    });
+}
+
+// ============================================================
+// Commands
+// ============================================================
+
+/// Open a route in its own window
+#[tauri::command]
+pub async fn open_popout_window(app: AppHandle, route: String) -> Result<String, AppError> {
+    open(&app, WindowKind::Popout, &route)
+}
+
+/// Open the chat in a small window that stays on top
+#[tauri::command]
+pub async fn open_floating_chat(app: AppHandle) -> Result<String, AppError> {
+    open(&app, WindowKind::FloatingChat, "/chat")
+}
+
+/// Record the route a secondary window navigated to
+#[tauri::command]
+pub async fn report_window_route(
+    app: AppHandle,
+    window: tauri::WebviewWindow,
+    route: String,
+) -> Result<(), AppError> {
+    validate_route(&route)?;
+    let session = app.state::<WindowSession>();
+    let state = {
+        let mut state = session.state.lock();
+        if !state.navigate(window.label(), &route) {
+            return Ok(());
+        }
+        state.clone()
+    };
+    save(&app, state);
+    Ok(())
+}
+
+/// Get the session restore settings
+#[tauri::command]
+pub async fn get_session_settings(app: AppHandle) -> Result<SessionSettings, AppError> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    Ok(SessionSettings::load(&app_data_dir))
+}
+
+/// Update the session restore settings
+#[tauri::command]
+pub async fn set_session_settings(
+    app: AppHandle,
+    settings: SessionSettings,
+) -> Result<(), AppError> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    settings.save(&app_data_dir)?;
+    Ok(())
+}
+
+/// Forget the recorded windows; those open now won't reopen next launch
+#[tauri::command]
+pub async fn clear_session_state(app: AppHandle) -> Result<(), AppError> {
+    *app.state::<WindowSession>().state.lock() = SessionState::default();
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    SessionState::default().save(&app_data_dir)?;
+    Ok(())
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn saved(label: &str, route: &str) -> SavedWindow {
+        SavedWindow {
+            label: label.to_string(),
+            kind: WindowKind::Popout,
+            route: route.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_validate_route() {
+        for route in ["/", "/notes", "/chat?tab=recent", "/settings/ai"] {
+            assert!(validate_route(route).is_ok(), "{}", route);
+        }
+        for route in [
+            "",
+            "notes",
+            "//evil.example.com",
+            "/redirect?to=https://example.com",
+            "/notes\n",
+            "/a b",
+        ] {
+            assert!(validate_route(route).is_err(), "{:?}", route);
+        }
+        assert!(validate_route(&format!("/{}", "a".repeat(MAX_ROUTE_LEN))).is_err());
+    }
+
+    #[test]
+    fn test_route_url() {
+        assert!(route_url("/notes").ends_with("notes"));
+        assert!(!route_url("/notes").contains("//"));
+    }
+
+    #[test]
+    fn test_navigate_and_remove() {
+        let mut state = SessionState {
+            windows: vec![saved("popout-0", "/notes")],
+        };
+        assert!(state.navigate("popout-0", "/chat"));
+        assert_eq!(state.windows[0].route, "/chat");
+        assert!(!state.navigate("popout-0", "/chat"));
+        // Windows that aren't recorded, like the main window, are ignored
+        assert!(!state.navigate("main", "/notes"));
+
+        assert!(!state.remove("main"));
+        assert!(state.remove("popout-0"));
+        assert!(state.windows.is_empty());
+    }
+
+    #[test]
+    fn test_state_round_trip() {
+        let dir = TempDir::new().unwrap();
+        assert_eq!(SessionState::load(dir.path()), SessionState::default());
+        assert!(SessionSettings::load(dir.path()).restore_windows);
+
+        let state = SessionState {
+            windows: vec![
+                saved("popout-0", "/notes"),
+                SavedWindow {
+                    label: "floating-chat-1".to_string(),
+                    kind: WindowKind::FloatingChat,
+                    route: "/chat".to_string(),
+                },
+            ],
+        };
+        state.save(dir.path()).unwrap();
+        assert_eq!(SessionState::load(dir.path()), state);
+
+        let settings = SessionSettings {
+            restore_windows: false,
+        };
+        settings.save(dir.path()).unwrap();
+        assert_eq!(SessionSettings::load(dir.path()), settings);
+    }
+}
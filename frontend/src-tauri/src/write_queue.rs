@@ -0,0 +1,391 @@
+//! Offline write queue for requests proxied to the backend.
+//!
+//! This module provides:
+//! - A persistent queue (write-queue.json) capturing webview writes made
+//!   while the backend is restarting or down
+//! - In-order replay once the backend is running again
+//! - `write-queued`, `write-replayed` and `note-created` events, plus
+//!   commands to inspect, replay and discard queued writes
+//!
+//! Once anything is queued, later writes are queued behind it so an old edit
+//! can never be applied over a newer one. Authorization headers are not
+//! stored; replays use the auth registered with `set_backend_auth`.
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::Notify;
+
+use crate::config::load_json;
+use crate::error::AppError;
+use crate::journal;
+use crate::services::{ServiceManager, ServicePhase};
+use crate::AppState;
+
+/// Queue file in the app data directory
+const QUEUE_FILE: &str = "write-queue.json";
+
+/// Most writes kept; further writes fail instead of growing the queue
+const MAX_QUEUED: usize = 1000;
+
+/// A write waiting for the backend
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QueuedWrite {
+    pub id: String,
+    pub method: String,
+    /// Path relative to `/api`
+    pub path: String,
+    pub body: Option<serde_json::Value>,
+    pub headers: HashMap<String, String>,
+    /// When the write was captured (Unix epoch seconds)
+    pub queued_at: i64,
+}
+
+/// Result of queuing a write, returned to the webview in place of a response
+#[derive(Debug, Clone, Serialize)]
+pub struct QueuedResponse {
+    pub queued: bool,
+    pub id: String,
+}
+
+/// Event payload emitted after a queued write is replayed
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplayedEvent {
+    pub write: QueuedWrite,
+    pub success: bool,
+    pub response: Option<serde_json::Value>,
+    /// Why the backend rejected the write; it is dropped from the queue
+    pub error: Option<AppError>,
+}
+
+/// Queue kept in Tauri state
+#[derive(Default)]
+pub struct WriteQueue {
+    writes: Mutex<Vec<QueuedWrite>>,
+    app_data_dir: Mutex<Option<PathBuf>>,
+    replay: Notify,
+}
+
+impl WriteQueue {
+    fn load(app_data_dir: &Path) -> Self {
+        let writes: Vec<QueuedWrite> =
+            load_json(&app_data_dir.join(QUEUE_FILE)).unwrap_or_default();
+        Self {
+            writes: Mutex::new(writes),
+            app_data_dir: Mutex::new(Some(app_data_dir.to_path_buf())),
+            replay: Notify::new(),
+        }
+    }
+
+    fn save(&self, writes: &[QueuedWrite]) {
+        if let Some(ref app_data_dir) = *self.app_data_dir.lock() {
+            if let Err(e) = journal::write(app_data_dir, QUEUE_FILE, &writes) {
+                tracing::warn!("Failed to save write queue: {}", e);
+            }
+        }
+    }
+
+    fn push(&self, write: QueuedWrite) -> Result<(), AppError> {
+        let mut writes = self.writes.lock();
+        if writes.len() >= MAX_QUEUED {
+            return Err(AppError::NotReady(format!(
+                "Backend is unavailable and {} writes are already queued",
+                MAX_QUEUED
+            )));
+        }
+        writes.push(write);
+        self.save(&writes);
+        Ok(())
+    }
+
+    fn front(&self) -> Option<QueuedWrite> {
+        self.writes.lock().first().cloned()
+    }
+
+    fn remove(&self, id: &str) -> bool {
+        let mut writes = self.writes.lock();
+        let before = writes.len();
+        writes.retain(|w| w.id != id);
+        let removed = writes.len() != before;
+        if removed {
+            self.save(&writes);
+        }
+        removed
+    }
+
+    fn is_empty(&self) -> bool {
+        self.writes.lock().is_empty()
+    }
+
+    fn list(&self) -> Vec<QueuedWrite> {
+        self.writes.lock().clone()
+    }
+}
+
+/// Whether a proxied request is a write that should survive a backend outage
+pub fn is_queueable(method: &str, path: &str) -> bool {
+    let write = matches!(
+        method.to_ascii_uppercase().as_str(),
+        "POST" | "PUT" | "PATCH" | "DELETE"
+    );
+    write && (path == "/notes" || path.starts_with("/notes/") || path.starts_with("/notes?"))
+}
+
+/// Whether an error means the backend couldn't be reached at all
+fn is_offline(error: &AppError) -> bool {
+    matches!(error, AppError::NotReady(_) | AppError::Network(_))
+}
+
+fn new_id() -> String {
+    let mut random = [0u8; 8];
+    let _ = getrandom::fill(&mut random);
+    random.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Emit `note-created` when a write created a note
+fn announce_created(app: &AppHandle, method: &str, path: &str, response: &serde_json::Value) {
+    if method.eq_ignore_ascii_case("POST") && path == "/notes" {
+        let _ = app.emit("note-created", response);
+    }
+}
+
+/// Send a write, queuing it instead when the backend is unavailable
+pub async fn send_or_queue(
+    app: &AppHandle,
+    method: &str,
+    path: &str,
+    body: Option<serde_json::Value>,
+    headers: Option<HashMap<String, String>>,
+) -> Result<serde_json::Value, AppError> {
+    let queue = app.state::<WriteQueue>();
+    let ready = *app.state::<AppState>().is_backend_ready.read();
+
+    if ready && queue.is_empty() {
+        match crate::proxy::send_backend_request(app, method, path, body.as_ref(), headers.clone())
+            .await
+        {
+            Err(e) if is_offline(&e) => {
+                tracing::info!("Backend unavailable, queuing {} {}: {}", method, path, e);
+            }
+            Ok(response) => {
+                announce_created(app, method, path, &response);
+                return Ok(response);
+            }
+            result => return result,
+        }
+    }
+
+    let write = QueuedWrite {
+        id: new_id(),
+        method: method.to_ascii_uppercase(),
+        path: path.to_string(),
+        body,
+        headers: headers
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|(name, _)| !name.eq_ignore_ascii_case("authorization"))
+            .collect(),
+        queued_at: chrono::Utc::now().timestamp(),
+    };
+    queue.push(write.clone())?;
+    tracing::info!("Queued {} {} for replay", write.method, write.path);
+    let _ = app.emit("write-queued", &write);
+    // The backend may already be back; try straight away
+    queue.replay.notify_one();
+
+    Ok(serde_json::to_value(QueuedResponse {
+        queued: true,
+        id: write.id,
+    })?)
+}
+
+/// Replay queued writes in order until the queue is empty or the backend
+/// is unreachable; returns how many were sent
+async fn replay(app: &AppHandle) -> usize {
+    let mut sent = 0;
+    while let Some(write) = app.state::<WriteQueue>().front() {
+        if !*app.state::<AppState>().is_backend_ready.read() {
+            break;
+        }
+        let result = crate::proxy::send_backend_request(
+            app,
+            &write.method,
+            &write.path,
+            write.body.as_ref(),
+            Some(write.headers.clone()),
+        )
+        .await;
+        if let Err(ref e) = result {
+            if is_offline(e) {
+                tracing::info!("Backend unavailable, pausing write replay: {}", e);
+                break;
+            }
+            tracing::warn!(
+                "Backend rejected queued {} {}: {}",
+                write.method,
+                write.path,
+                e
+            );
+        }
+
+        app.state::<WriteQueue>().remove(&write.id);
+        sent += 1;
+        let (response, error) = match result {
+            Ok(response) => {
+                announce_created(app, &write.method, &write.path, &response);
+                (Some(response), None)
+            }
+            Err(e) => (None, Some(e)),
+        };
+        let _ = app.emit(
+            "write-replayed",
+            ReplayedEvent {
+                success: error.is_none(),
+                write,
+                response,
+                error,
+            },
+        );
+    }
+    if sent > 0 {
+        tracing::info!("Replayed {} queued writes", sent);
+    }
+    sent
+}
+
+/// Load the queue and replay it whenever the backend comes back
+pub fn start(app: &AppHandle) {
+    let queue = match app.path().app_data_dir() {
+        Ok(app_data_dir) => WriteQueue::load(&app_data_dir),
+        Err(e) => {
+            tracing::warn!("Write queue won't persist: {}", e);
+            WriteQueue::default()
+        }
+    };
+    app.manage(queue);
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut services = app.state::<ServiceManager>().subscribe();
+        let queue = app.state::<WriteQueue>();
+        loop {
+            tokio::select! {
+                _ = queue.replay.notified() => {}
+                changed = services.changed() => {
+                    if changed.is_err() {
+                        break;
+                    }
+                    if services.borrow().backend != ServicePhase::Running {
+                        continue;
+                    }
+                }
+            }
+            replay(&app).await;
+        }
+    });
+}
+
+/// Send what's queued while the backend is still up, before it's stopped;
+/// whatever can't be sent stays queued for the next launch
+pub(crate) async fn flush(app: &AppHandle) {
+    if app.try_state::<WriteQueue>().is_none() {
+        return;
+    }
+    let sent = replay(app).await;
+    if sent > 0 {
+        tracing::info!("Flushed {} queued writes before shutdown", sent);
+    }
+}
+
+/// Replay queued writes as soon as possible, e.g. when health returns
+pub fn replay_soon(app: &AppHandle) {
+    if let Some(queue) = app.try_state::<WriteQueue>() {
+        queue.replay.notify_one();
+    }
+}
+
+/// List writes waiting for the backend
+#[tauri::command]
+pub async fn list_queued_writes(app: AppHandle) -> Result<Vec<QueuedWrite>, AppError> {
+    Ok(app.state::<WriteQueue>().list())
+}
+
+/// Replay queued writes now; returns how many were sent
+#[tauri::command]
+pub async fn replay_queued_writes(app: AppHandle) -> Result<usize, AppError> {
+    if !*app.state::<AppState>().is_backend_ready.read() {
+        return Err(AppError::NotReady("Backend is not ready".to_string()));
+    }
+    Ok(replay(&app).await)
+}
+
+/// Drop a queued write without sending it
+#[tauri::command]
+pub async fn discard_queued_write(app: AppHandle, id: String) -> Result<(), AppError> {
+    if app.state::<WriteQueue>().remove(&id) {
+        Ok(())
+    } else {
+        Err(AppError::NotFound(format!("No queued write '{}'", id)))
+    }
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write(id: &str) -> QueuedWrite {
+        QueuedWrite {
+            id: id.to_string(),
+            method: "POST".to_string(),
+            path: "/notes".to_string(),
+            body: Some(serde_json::json!({ "title": id })),
+            headers: HashMap::new(),
+            queued_at: 1_700_000_000,
+        }
+    }
+
+    #[test]
+    fn test_is_queueable() {
+        assert!(is_queueable("post", "/notes"));
+        assert!(is_queueable("PUT", "/notes/abc"));
+        assert!(is_queueable("DELETE", "/notes/abc"));
+        assert!(!is_queueable("GET", "/notes"));
+        assert!(!is_queueable("POST", "/ai/chat"));
+        assert!(!is_queueable("POST", "/notesearch"));
+    }
+
+    #[test]
+    fn test_queue_persists_in_order() {
+        let temp_dir = TempDir::new().unwrap();
+        let queue = WriteQueue::load(temp_dir.path());
+        queue.push(write("a")).unwrap();
+        queue.push(write("b")).unwrap();
+
+        let reloaded = WriteQueue::load(temp_dir.path());
+        assert_eq!(reloaded.list(), vec![write("a"), write("b")]);
+        assert_eq!(reloaded.front().unwrap().id, "a");
+
+        assert!(reloaded.remove("a"));
+        assert!(!reloaded.remove("a"));
+        assert_eq!(WriteQueue::load(temp_dir.path()).list(), vec![write("b")]);
+    }
+
+    #[test]
+    fn test_queue_is_bounded() {
+        let queue = WriteQueue::default();
+        for i in 0..MAX_QUEUED {
+            queue.push(write(&i.to_string())).unwrap();
+        }
+        assert_eq!(
+            queue.push(write("overflow")).unwrap_err().kind(),
+            "not_ready"
+        );
+    }
+}
@@ -0,0 +1,291 @@
+//! Per-window zoom.
+//!
+//! This module provides:
+//! - Zoom In, Zoom Out and Actual Size for the focused window, from the View
+//!   menu, stepping through the same levels as browsers
+//! - `set_zoom` and `get_zoom` for the frontend, and a `zoom-changed` event
+//! - Each window's level saved in zoom.json and applied whenever its page
+//!   loads
+//!
+//! Numbered windows such as `popout-3` share the level saved for their kind
+//! (`popout`), since their numbers aren't kept across launches.
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use tauri::webview::{PageLoadEvent, PageLoadPayload};
+use tauri::{AppHandle, Emitter, Manager, Webview};
+
+use crate::config::{load_json, save_json_atomic};
+use crate::error::AppError;
+
+/// Zoom levels the menu steps through
+const ZOOM_LEVELS: [f64; 13] = [
+    0.5, 0.67, 0.75, 0.8, 0.9, 1.0, 1.1, 1.25, 1.5, 1.75, 2.0, 2.5, 3.0,
+];
+
+const MIN_ZOOM: f64 = 0.5;
+const MAX_ZOOM: f64 = 3.0;
+
+/// Saved zoom levels by window kind
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ZoomSettings {
+    pub levels: BTreeMap<String, f64>,
+}
+
+impl ZoomSettings {
+    fn path(app_data_dir: &Path) -> PathBuf {
+        app_data_dir.join("zoom.json")
+    }
+
+    /// Load settings, returning defaults if missing or invalid
+    pub fn load(app_data_dir: &Path) -> Self {
+        load_json(&Self::path(app_data_dir)).unwrap_or_default()
+    }
+
+    /// Save settings atomically
+    pub fn save(&self, app_data_dir: &Path) -> Result<(), String> {
+        save_json_atomic(&Self::path(app_data_dir), self)
+    }
+
+    /// Level for a window, 1.0 unless saved
+    fn level(&self, label: &str) -> f64 {
+        self.levels
+            .get(window_key(label))
+            .copied()
+            .filter(|factor| validate_factor(*factor).is_ok())
+            .unwrap_or(1.0)
+    }
+
+    /// Record a window's level; actual size isn't stored
+    fn set_level(&mut self, label: &str, factor: f64) {
+        let key = window_key(label).to_string();
+        if factor == 1.0 {
+            self.levels.remove(&key);
+        } else {
+            self.levels.insert(key, factor);
+        }
+    }
+}
+
+/// Zoom levels, kept in Tauri state
+#[derive(Default)]
+pub struct Zoom {
+    settings: Mutex<ZoomSettings>,
+}
+
+/// Payload of `zoom-changed`
+#[derive(Debug, Clone, Serialize)]
+struct ZoomChanged<'a> {
+    label: &'a str,
+    factor: f64,
+}
+
+/// Settings key for a window: its label without a trailing number
+fn window_key(label: &str) -> &str {
+    match label.rsplit_once('-') {
+        Some((kind, number))
+            if !kind.is_empty()
+                && !number.is_empty()
+                && number.chars().all(|c| c.is_ascii_digit()) =>
+        {
+            kind
+        }
+        _ => label,
+    }
+}
+
+fn validate_factor(factor: f64) -> Result<f64, AppError> {
+    if !factor.is_finite() || !(MIN_ZOOM..=MAX_ZOOM).contains(&factor) {
+        return Err(AppError::InvalidInput(format!(
+            "Zoom must be between {} and {}",
+            MIN_ZOOM, MAX_ZOOM
+        )));
+    }
+    // Two decimals is as fine as anyone zooms
+    Ok((factor * 100.0).round() / 100.0)
+}
+
+/// The next level up or down from `current`
+fn step(current: f64, zoom_in: bool) -> f64 {
+    const EPSILON: f64 = 0.001;
+    if zoom_in {
+        ZOOM_LEVELS
+            .iter()
+            .copied()
+            .find(|level| *level > current + EPSILON)
+            .unwrap_or(MAX_ZOOM)
+    } else {
+        ZOOM_LEVELS
+            .iter()
+            .rev()
+            .copied()
+            .find(|level| *level < current - EPSILON)
+            .unwrap_or(MIN_ZOOM)
+    }
+}
+
+/// Zoom a window and save its level
+fn set(app: &AppHandle, label: &str, factor: f64) -> Result<f64, AppError> {
+    let factor = validate_factor(factor)?;
+    let webview = app
+        .get_webview(label)
+        .ok_or_else(|| AppError::NotFound(format!("No window named {}", label)))?;
+    webview.set_zoom(factor)?;
+
+    let settings = {
+        let mut settings = app.state::<Zoom>().settings.lock();
+        settings.set_level(label, factor);
+        settings.clone()
+    };
+    let app_data_dir = app.path().app_data_dir()?;
+    if let Err(e) = settings.save(&app_data_dir) {
+        tracing::warn!("Failed to save zoom levels: {}", e);
+    }
+    let _ = app.emit("zoom-changed", ZoomChanged { label, factor });
+    Ok(factor)
+}
+
+fn level(app: &AppHandle, label: &str) -> f64 {
+    app.state::<Zoom>().settings.lock().level(label)
+}
+
+/// Apply a window's saved level once its page has loaded
+pub fn on_page_load<R: tauri::Runtime>(webview: &Webview<R>, payload: &PageLoadPayload<'_>) {
+    if payload.event() != PageLoadEvent::Finished {
+        return;
+    }
+    let Some(zoom) = webview.try_state::<Zoom>() else {
+        return;
+    };
+    let factor = zoom.settings.lock().level(webview.label());
+    if factor != 1.0 {
+        if let Err(e) = webview.set_zoom(factor) {
+            tracing::warn!("Failed to zoom {}: {}", webview.label(), e);
+        }
+    }
+}
+
+/// Handle the View menu's zoom items for the focused window
+pub fn on_menu(app: &AppHandle, id: &str) {
+    let Some(window) = app
+        .webview_windows()
+        .into_values()
+        .find(|window| window.is_focused().unwrap_or(false))
+        .or_else(|| app.get_webview_window("main"))
+    else {
+        return;
+    };
+    let label = window.label();
+    let current = level(app, label);
+    let factor = match id {
+        "zoom_in" => step(current, true),
+        "zoom_out" => step(current, false),
+        _ => 1.0,
+    };
+    if let Err(e) = set(app, label, factor) {
+        tracing::warn!("Failed to zoom {}: {}", label, e);
+    }
+}
+
+/// Load saved zoom levels and apply them to windows already open
+pub fn start(app: &AppHandle) {
+    let Ok(app_data_dir) = app.path().app_data_dir() else {
+        return;
+    };
+    let settings = ZoomSettings::load(&app_data_dir);
+    for (label, webview) in app.webviews() {
+        let factor = settings.level(&label);
+        if factor != 1.0 {
+            let _ = webview.set_zoom(factor);
+        }
+    }
+    *app.state::<Zoom>().settings.lock() = settings;
+}
+
+// ============================================================
+// Commands
+// ============================================================
+
+/// Zoom a window, the calling one by default; returns the level applied
+#[tauri::command]
+pub async fn set_zoom(
+    app: AppHandle,
+    webview: Webview,
+    window: Option<String>,
+    factor: f64,
+) -> Result<f64, AppError> {
+    let label = window.unwrap_or_else(|| webview.label().to_string());
+    set(&app, &label, factor)
+}
+
+/// Get a window's zoom level, the calling one by default
+#[tauri::command]
+pub async fn get_zoom(
+    app: AppHandle,
+    webview: Webview,
+    window: Option<String>,
+) -> Result<f64, AppError> {
+    let label = window.unwrap_or_else(|| webview.label().to_string());
+    Ok(level(&app, &label))
+}
+
+// ============================================================
+// Unit Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_window_key() {
+        assert_eq!(window_key("main"), "main");
+        assert_eq!(window_key("popout-3"), "popout");
+        assert_eq!(window_key("floating-chat-12"), "floating-chat");
+        assert_eq!(window_key("floating-chat"), "floating-chat");
+        assert_eq!(window_key("-3"), "-3");
+    }
+
+    #[test]
+    fn test_step() {
+        assert_eq!(step(1.0, true), 1.1);
+        assert_eq!(step(1.0, false), 0.9);
+        // Levels set by hand snap to the next one in the direction
+        assert_eq!(step(1.33, true), 1.5);
+        assert_eq!(step(1.33, false), 1.25);
+        assert_eq!(step(MAX_ZOOM, true), MAX_ZOOM);
+        assert_eq!(step(MIN_ZOOM, false), MIN_ZOOM);
+    }
+
+    #[test]
+    fn test_validate_factor() {
+        assert_eq!(validate_factor(1.234).unwrap(), 1.23);
+        assert!(validate_factor(0.1).is_err());
+        assert!(validate_factor(4.0).is_err());
+        assert!(validate_factor(f64::NAN).is_err());
+    }
+
+    #[test]
+    fn test_levels_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let mut settings = ZoomSettings::load(dir.path());
+        assert_eq!(settings.level("main"), 1.0);
+
+        settings.set_level("main", 1.25);
+        settings.set_level("popout-0", 0.9);
+        settings.save(dir.path()).unwrap();
+
+        let loaded = ZoomSettings::load(dir.path());
+        assert_eq!(loaded, settings);
+        assert_eq!(loaded.level("main"), 1.25);
+        // Another pop-out gets the pop-out level
+        assert_eq!(loaded.level("popout-7"), 0.9);
+
+        settings.set_level("main", 1.0);
+        assert!(!settings.levels.contains_key("main"));
+    }
+}
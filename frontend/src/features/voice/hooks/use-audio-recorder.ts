@@ -9,12 +9,34 @@ import {
   requestMicrophoneAccess,
   float32ToInt16,
   int16ToArrayBuffer,
+  listAudioInputs,
+  resolveAudioInput,
 } from '../../../services/voice-audio.service';
+import { isTauri } from '../../../lib/native-notifications';
+import { listAudioDevices, onAudioDeviceChanged } from '../../../lib/tauri-bridge';
+import type { AudioDeviceChangeEvent, PreferredAudioDevice } from '../types/voice-types';
 
 export interface UseAudioRecorderOptions {
   sampleRate?: number;
   onAudioData?: (data: ArrayBuffer) => void;
   onError?: (error: string) => void;
+  /** Called when the microphone is switched or lost mid-recording */
+  onDeviceChange?: (event: AudioDeviceChangeEvent) => void;
+}
+
+/**
+ * The input device selected in settings, if any
+ */
+async function loadPreferredDevice(): Promise<PreferredAudioDevice | null> {
+  if (!isTauri()) {
+    return null;
+  }
+  try {
+    return (await listAudioDevices()).selected;
+  } catch (err) {
+    console.warn('Failed to load the selected microphone', err);
+    return null;
+  }
 }
 
 export interface UseAudioRecorderReturn {
@@ -30,7 +52,7 @@ export interface UseAudioRecorderReturn {
 }
 
 export function useAudioRecorder(options: UseAudioRecorderOptions = {}): UseAudioRecorderReturn {
-  const { sampleRate = 16000, onAudioData, onError } = options;
+  const { sampleRate = 16000, onAudioData, onError, onDeviceChange } = options;
 
   const [isRecording, setIsRecording] = useState(false);
   const [isPaused, setIsPaused] = useState(false);
@@ -41,12 +63,33 @@ export function useAudioRecorder(options: UseAudioRecorderOptions = {}): UseAudi
   const streamRef = useRef<MediaStream | null>(null);
   const onAudioDataRef = useRef(onAudioData);
   const onErrorRef = useRef(onError);
+  const onDeviceChangeRef = useRef(onDeviceChange);
 
   // Keep callbacks up to date
   useEffect(() => {
     onAudioDataRef.current = onAudioData;
     onErrorRef.current = onError;
-  }, [onAudioData, onError]);
+    onDeviceChangeRef.current = onDeviceChange;
+  }, [onAudioData, onError, onDeviceChange]);
+
+  // Switch a running recording when another microphone is selected
+  useEffect(() => {
+    let unlisten: (() => void) | undefined;
+    let cancelled = false;
+    void onAudioDeviceChanged((device) => {
+      void recorderRef.current?.setPreferredDevice(device);
+    }).then((fn) => {
+      if (cancelled) {
+        fn();
+      } else {
+        unlisten = fn;
+      }
+    });
+    return () => {
+      cancelled = true;
+      unlisten?.();
+    };
+  }, []);
 
   // Request microphone permission
   const requestPermission = useCallback(async (): Promise<boolean> => {
@@ -73,15 +116,27 @@ export function useAudioRecorder(options: UseAudioRecorderOptions = {}): UseAudi
     try {
       setError(null);
 
-      // Use existing stream or get a new one
-      const stream = existingStream ?? await requestMicrophoneAccess(sampleRate);
+      // Use existing stream or get a new one from the selected microphone
+      const preferredDevice = await loadPreferredDevice();
+      const stream = existingStream ?? await requestMicrophoneAccess(
+        sampleRate,
+        resolveAudioInput(await listAudioInputs(), preferredDevice)
+      );
       streamRef.current = stream;
       setHasPermission(true);
 
       // Create and configure recorder
-      const recorder = new AudioRecorder({ sampleRate });
+      const recorder = new AudioRecorder({ sampleRate, preferredDevice });
       recorderRef.current = recorder;
 
+      // Pause instead of recording silence when the microphone goes away
+      recorder.onDeviceChange((event) => {
+        if (event.type === 'paused') {
+          setIsPaused(true);
+        }
+        onDeviceChangeRef.current?.(event);
+      });
+
       // Set up audio data callback
       recorder.onData((float32Data) => {
         if (onAudioDataRef.current) {
@@ -237,6 +237,24 @@ export interface AudioRecorderOptions {
   sampleRate?: number;
   channelCount?: number;
   mimeType?: string;
+  /** Input to record from; the system default when not set */
+  preferredDevice?: PreferredAudioDevice | null;
+}
+
+export interface PreferredAudioDevice {
+  id: string;
+  /** Matched against device labels when the id isn't the webview's */
+  name?: string | null;
+}
+
+/**
+ * Raised when the recording input changes under the recorder: `switched`
+ * when it moved to another device, `paused` when no device was left
+ */
+export interface AudioDeviceChangeEvent {
+  type: 'switched' | 'paused';
+  deviceLabel: string | null;
+  reason?: string;
 }
 
 export interface AudioPlayerOptions {
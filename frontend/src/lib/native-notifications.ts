@@ -1,3 +1,4 @@
+import { invoke } from '@tauri-apps/api/core';
 import { isPermissionGranted, requestPermission, sendNotification } from '@tauri-apps/plugin-notification';
 
 /**
@@ -59,10 +60,28 @@ export async function notify(title: string, body: string): Promise<void> {
   }
 }
 
+/**
+ * Whether a focus session is holding back the app's own notifications
+ */
+async function heldByFocusSession(): Promise<boolean> {
+  if (!isTauri()) {
+    return false;
+  }
+  try {
+    const session = await invoke<{ blocked_sources: string[] } | null>('get_focus_session');
+    return session?.blocked_sources.includes('app') ?? false;
+  } catch {
+    return false;
+  }
+}
+
 /**
  * Notification for AI response complete
  */
 export async function notifyAIResponseComplete(conversationTitle: string): Promise<void> {
+  if (await heldByFocusSession()) {
+    return;
+  }
   await notify('AI Response Ready', `Response received in "${conversationTitle}"`);
 }
 
@@ -70,6 +89,9 @@ export async function notifyAIResponseComplete(conversationTitle: string): Promi
  * Notification for indexing complete
  */
 export async function notifyIndexingComplete(noteCount: number): Promise<void> {
+  if (await heldByFocusSession()) {
+    return;
+  }
   await notify('Indexing Complete', `Successfully indexed ${noteCount} notes`);
 }
 
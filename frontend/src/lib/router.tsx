@@ -26,6 +26,7 @@ import { queryClient } from './query-client';
 import { noteKeys, conversationKeys, statsKeys } from './query-keys';
 import { notesService, chatService, statsService } from '../services';
 import { CACHE } from './constants';
+import { reportWindowRoute } from './tauri-bridge';
 
 // Check if we're running in Tauri production mode
 // In development, Tauri uses the Vite dev server which supports browser routing
@@ -63,6 +64,12 @@ const GitHubPage = lazy(() => import('../pages/GitHubPage').then(m => ({ default
 const VoiceAgentPage = lazy(() => import('../pages/VoiceAgentPage').then(m => ({ default: m.VoiceAgentPage })));
 const VoiceAgentSkeleton = lazy(() => import('../features/voice/components/VoiceAgentSkeleton').then(m => ({ default: m.VoiceAgentSkeleton })));
 
+// Lazy load the print view - only the desktop app's print windows open it
+const PrintNotePage = lazy(() => import('../pages/PrintNotePage').then(m => ({ default: m.PrintNotePage })));
+
+// Lazy load the slides view - only the desktop app's presentation window opens it
+const PresentationPage = lazy(() => import('../pages/PresentationPage').then(m => ({ default: m.PresentationPage })));
+
 // Route definitions (shared between browser and hash routers)
 const routes = [
   {
@@ -284,6 +291,29 @@ const routes = [
       </ProtectedRoute>
     ),
   },
+  {
+    // No app layout: the page is printed as-is
+    path: '/print/notes/:id',
+    element: (
+      <ProtectedRoute>
+        <Suspense fallback={null}>
+          <PrintNotePage />
+        </Suspense>
+      </ProtectedRoute>
+    ),
+  },
+  {
+    path: '/present/notes/:id',
+    element: (
+      <ProtectedRoute>
+        <ErrorBoundary>
+          <Suspense fallback={<PageLoader />}>
+            <PresentationPage />
+          </Suspense>
+        </ErrorBoundary>
+      </ProtectedRoute>
+    ),
+  },
   {
     path: '*',
     element: <NotFoundPage />,
@@ -295,3 +325,10 @@ const routes = [
 export const router = isTauriProduction
   ? createHashRouter(routes)
   : createBrowserRouter(routes);
+
+// Let the desktop app reopen pop-out windows on the route they last showed
+if ('__TAURI_INTERNALS__' in window) {
+  router.subscribe(({ location }) => {
+    void reportWindowRoute(`${location.pathname}${location.search}`);
+  });
+}
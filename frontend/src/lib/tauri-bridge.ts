@@ -1,8 +1,406 @@
-import { invoke } from '@tauri-apps/api/core';
+import { Channel, convertFileSrc, invoke } from '@tauri-apps/api/core';
 import { listen } from '@tauri-apps/api/event';
 import { isTauri } from './native-notifications';
 import { loggers } from '../utils/logger';
 
+/**
+ * Error returned by Tauri commands
+ */
+export interface AppError {
+  kind:
+    | 'not_found'
+    | 'invalid_input'
+    | 'not_ready'
+    | 'database'
+    | 'backend'
+    | 'network'
+    | 'permission'
+    | 'io'
+    | 'conflict'
+    | 'cancelled'
+    | 'internal';
+  message: string;
+  remediation: string | null;
+}
+
+/**
+ * Check whether a rejected invoke carries a structured AppError
+ */
+export function isAppError(error: unknown): error is AppError {
+  return (
+    typeof error === 'object' &&
+    error !== null &&
+    'kind' in error &&
+    'message' in error
+  );
+}
+
+/**
+ * Result of one call in an invokeBatch request
+ */
+export type BatchResult<T = unknown> = { ok: T } | { error: AppError };
+
+/**
+ * Run several read-only commands in one IPC round trip
+ * Results are keyed by the id given to each call
+ */
+export async function invokeBatch(
+  calls: { id: string; command: string }[]
+): Promise<Record<string, BatchResult>> {
+  return await invoke<Record<string, BatchResult>>('invoke_batch', { calls });
+}
+
+/**
+ * Totals reported when a byte stream completes
+ */
+export interface StreamSummary {
+  stream_id: number;
+  bytes: number;
+  chunks: number;
+}
+
+/**
+ * Invoke a streaming command, passing each chunk to onChunk
+ * Chunks are acknowledged as they arrive so the shell can send more;
+ * an empty chunk marks the end of the stream
+ */
+export async function invokeStream(
+  command: string,
+  args: Record<string, unknown>,
+  onChunk: (chunk: Uint8Array) => void
+): Promise<StreamSummary> {
+  const channel = new Channel<ArrayBuffer>();
+  let received = 0;
+  channel.onmessage = (data) => {
+    const chunk = new Uint8Array(data);
+    if (chunk.length === 0) {
+      return;
+    }
+    onChunk(chunk);
+    received += 1;
+    void invoke('ack_stream', { streamId: channel.id, received });
+  };
+  return await invoke<StreamSummary>(command, { ...args, onChunk: channel });
+}
+
+/**
+ * Result of a log read; `reset` means the text replaces what was shown
+ * rather than being appended to it
+ */
+export interface LogRead extends StreamSummary {
+  path: string | null;
+  offset: number;
+  reset: boolean;
+}
+
+/**
+ * Read the newest log file as text
+ * With a consumer name, later calls only return lines written since the last one
+ */
+export async function getRecentLogs(
+  maxLines = 100,
+  consumer?: string
+): Promise<{ text: string; read: LogRead }> {
+  const decoder = new TextDecoder();
+  let text = '';
+  const read = (await invokeStream('get_recent_logs', { maxLines, consumer }, (chunk) => {
+    text += decoder.decode(chunk, { stream: true });
+  })) as LogRead;
+  return { text: text + decoder.decode(), read };
+}
+
+/**
+ * URL the webview can load an attachment from directly (img, audio, video)
+ * The optional file name sets the content type when the file's own bytes
+ * don't identify it
+ */
+export function attachmentUrl(hash: string, name?: string): string {
+  const url = convertFileSrc(hash, 'sb-asset');
+  return name ? `${url}?name=${encodeURIComponent(name)}` : url;
+}
+
+/**
+ * Long-running operations available through start_job
+ */
+export type JobRequest =
+  | {
+      kind: 'export';
+      destination: string;
+      format?: 'md' | 'json' | 'pdf';
+      filter?: Record<string, unknown>;
+    }
+  | { kind: 'backup' }
+  | { kind: 'local_backup' }
+  | { kind: 'restore_backup'; path: string }
+  | { kind: 'apple_import'; source: 'apple_notes' | 'reminders' }
+  | { kind: 'model_download'; url: string; file_name: string; sha256?: string };
+
+/**
+ * Payload of job-progress events; result or error is set once the job ends
+ */
+export interface JobProgress {
+  id: string;
+  kind: JobRequest['kind'];
+  state: 'running' | 'completed' | 'failed' | 'cancelled';
+  stage: string;
+  done: number;
+  total: number | null;
+  result: unknown;
+  error: AppError | null;
+}
+
+/**
+ * Start a job and follow its progress until it ends
+ * Returns the job id (for cancelJob) and a promise of its final progress
+ */
+export async function startJob(
+  request: JobRequest,
+  onProgress?: (progress: JobProgress) => void
+): Promise<{ id: string; done: Promise<JobProgress> }> {
+  const events: JobProgress[] = [];
+  let jobId: string | null = null;
+  let finish: (progress: JobProgress) => void = () => {};
+  const done = new Promise<JobProgress>((resolve) => {
+    finish = resolve;
+  });
+  const handle = (progress: JobProgress) => {
+    onProgress?.(progress);
+    if (progress.state !== 'running') {
+      unlisten();
+      finish(progress);
+    }
+  };
+  // Listen first so no event is missed; buffer until the id is known
+  const unlisten = await listen<JobProgress>('job-progress', (event) => {
+    if (jobId === null) {
+      events.push(event.payload);
+    } else if (event.payload.id === jobId) {
+      handle(event.payload);
+    }
+  });
+  try {
+    jobId = await invoke<string>('start_job', { request });
+  } catch (error) {
+    unlisten();
+    throw error;
+  }
+  events.filter((progress) => progress.id === jobId).forEach(handle);
+  return { id: jobId, done };
+}
+
+/**
+ * Ask a running job to stop; its final progress reports state 'cancelled'
+ */
+export async function cancelJob(id: string): Promise<void> {
+  await invoke('cancel_job', { id });
+}
+
+/**
+ * Save a note as a PDF with the app's formatting
+ * Returns the file written, with a .pdf extension added if missing
+ */
+export async function exportNotePdf(noteId: string, path: string): Promise<string> {
+  return await invoke<string>('export_note_pdf', { noteId, path });
+}
+
+export interface DiagnosticBundle {
+  path: string;
+  size_bytes: number;
+  entries: { name: string; size_bytes: number }[];
+}
+
+/**
+ * Save the diagnostic report, recent logs and service config as one zip to
+ * attach to bug reports; secrets are redacted
+ * Returns the bundle written, with a .zip extension added if missing
+ */
+export async function exportDiagnostics(destination: string): Promise<DiagnosticBundle> {
+  return await invoke<DiagnosticBundle>('export_diagnostics', { destination });
+}
+
+export interface StartupTraceExport {
+  path: string;
+  startups: number;
+  events: number;
+}
+
+/**
+ * Save the recent startup and health timelines as Chrome trace JSON, for
+ * chrome://tracing, Perfetto or speedscope
+ * Returns the trace written, with a .json extension added if missing
+ */
+export async function exportStartupTrace(destination: string): Promise<StartupTraceExport> {
+  return await invoke<StartupTraceExport>('export_startup_trace', { destination });
+}
+
+export interface PreviewSummary {
+  previewed: number;
+  /** Files of a type that isn't previewed */
+  skipped: number;
+  /** Files that couldn't be previewed, with the reason */
+  failed: string[];
+}
+
+/**
+ * Generate Quick Look thumbnails and text previews for an exported file or
+ * folder (macOS only)
+ */
+export async function generateExportPreviews(path: string): Promise<PreviewSummary> {
+  return await invoke<PreviewSummary>('generate_export_previews', { path });
+}
+
+/**
+ * Open a note's print preview with the native print dialog
+ */
+export async function printNote(noteId: string): Promise<void> {
+  await invoke('print_note', { noteId });
+}
+
+/**
+ * Tell the app a print window's note has rendered, or why it couldn't load
+ */
+export async function notifyPrintPageReady(error: string | null = null): Promise<void> {
+  if (!isTauri()) {
+    return;
+  }
+
+  try {
+    await invoke('print_page_ready', { error });
+  } catch (e) {
+    loggers.tauri.warn('Failed to report print page ready:', e);
+  }
+}
+
+/**
+ * Presentation window state, also sent as the presentation-changed event
+ */
+export interface PresentationStatus {
+  active: boolean;
+  note_id: string | null;
+  keeping_awake: boolean;
+}
+
+/**
+ * Present a note as full-screen slides, keeping the display awake
+ */
+export async function enterPresentationMode(noteId: string): Promise<PresentationStatus> {
+  return await invoke<PresentationStatus>('enter_presentation_mode', { noteId });
+}
+
+/**
+ * End the presentation and bring the main window back
+ */
+export async function exitPresentationMode(): Promise<void> {
+  if (!isTauri()) {
+    return;
+  }
+
+  await invoke('exit_presentation_mode');
+}
+
+/**
+ * Open a route in its own window, reopened on the next launch until closed
+ * Returns the new window's label
+ */
+export async function openPopoutWindow(route: string): Promise<string> {
+  return await invoke<string>('open_popout_window', { route });
+}
+
+/**
+ * Open the chat in a small window that stays on top
+ */
+export async function openFloatingChat(): Promise<string> {
+  return await invoke<string>('open_floating_chat');
+}
+
+/**
+ * Tell the app which route this window shows, so a restored window opens there
+ */
+export async function reportWindowRoute(route: string): Promise<void> {
+  if (!isTauri()) {
+    return;
+  }
+
+  try {
+    await invoke('report_window_route', { route });
+  } catch (e) {
+    loggers.tauri.warn('Failed to report window route:', e);
+  }
+}
+
+/**
+ * Zoom a window (this one by default), saved for its next launch
+ * Returns the zoom level applied
+ */
+export async function setZoom(factor: number, window?: string): Promise<number> {
+  return await invoke<number>('set_zoom', { window, factor });
+}
+
+/**
+ * Get a window's zoom level (this one by default)
+ */
+export async function getZoom(window?: string): Promise<number> {
+  return await invoke<number>('get_zoom', { window });
+}
+
+export interface UninstallReport {
+  backup: unknown | null;
+  removed: string[];
+  failed: string[];
+}
+
+/**
+ * Remove the app's data, registrations and credentials, then quit
+ * With keepData, only registrations, logs and caches are removed
+ */
+export async function uninstallCleanup(
+  options: { finalBackup?: boolean; keepData?: boolean } = {}
+): Promise<UninstallReport> {
+  return await invoke<UninstallReport>('uninstall_cleanup', {
+    options: { final_backup: options.finalBackup ?? false, keep_data: options.keepData ?? false },
+  });
+}
+
+export type UsagePeriod = 'today' | 'week' | 'month' | 'year' | 'all';
+
+export interface UsageTotals {
+  requests: number;
+  input_tokens: number;
+  output_tokens: number;
+  estimated_cost_usd: number;
+  unpriced_requests: number;
+}
+
+export interface UsageStats {
+  period: UsagePeriod;
+  since: string | null;
+  providers: (UsageTotals & { provider: string })[];
+  total: UsageTotals;
+  budget: {
+    month: string;
+    budget_usd: number;
+    spent_usd: number;
+    percent_used: number;
+    exceeded: boolean;
+  } | null;
+}
+
+/**
+ * Get AI token usage and estimated cost by provider for a period
+ */
+export async function getUsageStats(period: UsagePeriod = 'month'): Promise<UsageStats> {
+  return await invoke<UsageStats>('get_usage_stats', { period });
+}
+
+/**
+ * Set the monthly AI budget (null for none) and the percentage to warn at
+ */
+export async function setUsageBudget(monthlyBudgetUsd: number | null, alertPercent = 80): Promise<void> {
+  await invoke('set_usage_settings', {
+    settings: { monthly_budget_usd: monthlyBudgetUsd, alert_percent: alertPercent },
+  });
+}
+
 /**
  * Wait for Tauri to be available (it may take a moment for Tauri to inject internals)
  * This handles the race condition where React renders before Tauri is fully initialized
@@ -36,6 +434,54 @@ export async function getBackendUrl(): Promise<string> {
   }
 }
 
+/**
+ * Backend API move after a port conflict
+ */
+export interface ApiUrlChanged {
+  old_url: string;
+  new_url: string;
+  old_port: number;
+  new_port: number;
+}
+
+/**
+ * Listen for the backend API moving to another port
+ */
+export async function onApiUrlChanged(
+  callback: (change: ApiUrlChanged) => void
+): Promise<() => void> {
+  if (!isTauri()) {
+    return () => { /* no-op */ };
+  }
+
+  return await listen<ApiUrlChanged>('api-url-changed', (e) => { callback(e.payload); });
+}
+
+export type ShutdownStep = 'flush_queue' | 'stop_backend' | 'stop_postgres' | 'port_sweep';
+
+export interface ShutdownProgress {
+  step: ShutdownStep;
+  status: 'started' | 'done' | 'timed_out';
+  /** 1-based position of the step in the plan */
+  index: number;
+  total: number;
+  elapsed_ms: number;
+  budget_ms: number;
+}
+
+/**
+ * Listen for progress while services shut down on quit
+ */
+export async function onShutdownProgress(
+  callback: (progress: ShutdownProgress) => void
+): Promise<() => void> {
+  if (!isTauri()) {
+    return () => { /* no-op */ };
+  }
+
+  return await listen<ShutdownProgress>('shutdown-progress', (e) => { callback(e.payload); });
+}
+
 /**
  * Check if the backend is ready
  */
@@ -69,6 +515,22 @@ export async function restartBackend(): Promise<void> {
   }
 }
 
+/**
+ * Replace the backend's JWT secret and restart the backend with it; existing
+ * sessions stop working, so listeners of onJwtSecretRotated should sign in again
+ */
+export async function rotateJwtSecret(): Promise<void> {
+  await invoke('rotate_jwt_secret');
+}
+
+export async function onJwtSecretRotated(callback: () => void): Promise<() => void> {
+  if (!isTauri()) {
+    return () => { /* no-op */ };
+  }
+
+  return await listen('jwt-secret-rotated', () => { callback(); });
+}
+
 /**
  * Open the data directory in Finder
  */
@@ -232,6 +694,420 @@ export async function getSecretsPath(): Promise<string> {
   }
 }
 
+export interface LocalBackup {
+  id: string;
+  path: string;
+  created_at: string;
+  size_bytes: number;
+}
+
+/**
+ * Dump the database into the app's local backup folder
+ * Use startJob({ kind: 'local_backup' }) to follow its progress
+ */
+export async function createBackup(): Promise<LocalBackup> {
+  return await invoke<LocalBackup>('create_backup');
+}
+
+/**
+ * List local backups, newest first
+ */
+export async function listBackups(): Promise<LocalBackup[]> {
+  if (!isTauri()) {
+    return [];
+  }
+  return await invoke<LocalBackup[]>('list_backups');
+}
+
+/**
+ * Restore a local backup, replacing the database's contents
+ * Returns the backup taken of the previous contents; use
+ * startJob({ kind: 'restore_backup', path }) to follow its progress
+ */
+export async function restoreBackup(path: string): Promise<LocalBackup> {
+  return await invoke<LocalBackup>('restore_backup', { path });
+}
+
+/**
+ * Delete a local backup
+ */
+export async function deleteBackup(id: string): Promise<void> {
+  await invoke('delete_backup', { id });
+}
+
+export interface BackupSchedule {
+  /** Daily or weekly (weekday 0 = Monday), in local time; null disables */
+  schedule:
+    | { type: 'daily'; hour: number; minute: number }
+    | { type: 'weekly'; weekday: number; hour: number; minute: number }
+    | null;
+  /** Scheduled backups to keep; manual and pre-restore ones are never deleted */
+  keep: number;
+}
+
+/**
+ * Get the automatic local backup schedule
+ */
+export async function getBackupSchedule(): Promise<BackupSchedule> {
+  return await invoke<BackupSchedule>('get_backup_schedule');
+}
+
+/**
+ * Update the automatic local backup schedule
+ */
+export async function setBackupSchedule(schedule: BackupSchedule): Promise<void> {
+  await invoke('set_backup_schedule', { schedule });
+}
+
+export interface WalArchiveSettings {
+  /** Archive WAL; turning it on or off restarts the database */
+  enabled: boolean;
+  base_backup_days: number;
+  /** The archive reaches back to the oldest kept base backup */
+  keep_base_backups: number;
+}
+
+export interface WalBaseBackup {
+  id: string;
+  started_at: string;
+  finished_at: string;
+  start_segment: string;
+  size_bytes: number;
+}
+
+export interface WalArchiveStatus {
+  enabled: boolean;
+  /** Oldest first */
+  base_backups: WalBaseBackup[];
+  earliest_restore_point: string | null;
+  latest_restore_point: string | null;
+  archived_segments: number;
+  size_bytes: number;
+}
+
+export interface PointInTimeRestore {
+  target: string;
+  base_backup: string;
+  /** Where the replaced data directory was kept */
+  previous_data_dir: string;
+}
+
+/**
+ * Get the WAL archiving settings
+ */
+export async function getWalArchiveSettings(): Promise<WalArchiveSettings> {
+  return await invoke<WalArchiveSettings>('get_wal_archive_settings');
+}
+
+/**
+ * Update the WAL archiving settings; turning archiving on takes the first
+ * base backup
+ */
+export async function setWalArchiveSettings(settings: WalArchiveSettings): Promise<WalArchiveStatus> {
+  return await invoke<WalArchiveStatus>('set_wal_archive_settings', { settings });
+}
+
+/**
+ * Get the range of times the WAL archive can restore to
+ */
+export async function getWalArchiveStatus(): Promise<WalArchiveStatus> {
+  return await invoke<WalArchiveStatus>('get_wal_archive_status');
+}
+
+/**
+ * Rewind the database to an RFC 3339 timestamp, losing later changes
+ */
+export async function restoreToTimestamp(timestamp: string): Promise<PointInTimeRestore> {
+  return await invoke<PointInTimeRestore>('restore_to_timestamp', { timestamp });
+}
+
+export interface CorruptConfig {
+  file: 'secrets.json' | 'service-config.json';
+  quarantined_as: string | null;
+  error: string;
+  recovered_fields: string[];
+}
+
+/**
+ * Save what can be recovered from the newest quarantined copy of a corrupt
+ * config file; restart the backend afterwards for secrets to take effect
+ */
+export async function recoverConfig(file: CorruptConfig['file']): Promise<CorruptConfig> {
+  return await invoke<CorruptConfig>('recover_config', { file });
+}
+
+export interface PortRange {
+  start: number;
+  end: number;
+}
+
+export type BackendLogLevel =
+  | 'trace'
+  | 'debug'
+  | 'information'
+  | 'warning'
+  | 'error'
+  | 'critical';
+
+/**
+ * Service settings stored in service-config.json
+ */
+export interface ServiceSettings {
+  /** Ports the backend may use; null tries the next few after the last one */
+  backend_ports: PortRange | null;
+  /** Ports PostgreSQL may use; null tries the next few after the last one */
+  postgres_ports: PortRange | null;
+  health_check_timeout_secs: number;
+  health_check_interval_secs: number;
+  backend_log_level: BackendLogLevel;
+  postgres_memory: {
+    shared_buffers_mb: number;
+    work_mem_mb: number;
+    maintenance_work_mem_mb: number;
+    effective_cache_size_mb: number;
+    max_connections: number;
+  };
+  auto_restart: {
+    enabled: boolean;
+    max_crashes: number;
+    window_secs: number;
+  };
+  /** Relaunch the app on its own when services fail and can't recover */
+  auto_relaunch: boolean;
+  shutdown_grace_secs: number;
+  launch_at_login: boolean;
+  /** CORS origins the backend allows besides the webview's own */
+  allowed_origins: string[];
+}
+
+export async function getSettings(): Promise<ServiceSettings> {
+  return await invoke<ServiceSettings>('get_settings');
+}
+
+/**
+ * Validate and save service settings; ports, the log level and PostgreSQL
+ * memory apply the next time services start
+ */
+export async function updateSettings(settings: ServiceSettings): Promise<ServiceSettings> {
+  return await invoke<ServiceSettings>('update_settings', { settings });
+}
+
+/**
+ * Replace the extra CORS origins the backend allows, e.g.
+ * `http://192.168.1.20:5173`; restarts a running backend and returns the
+ * effective list
+ */
+export async function setAllowedOrigins(origins: string[]): Promise<string[]> {
+  return await invoke<string[]>('set_allowed_origins', { origins });
+}
+
+/**
+ * Whether the app starts when the user logs in
+ */
+export async function getLaunchAtLogin(): Promise<boolean> {
+  if (!isTauri()) {
+    return false;
+  }
+
+  return await invoke<boolean>('get_launch_at_login');
+}
+
+export async function setLaunchAtLogin(enabled: boolean): Promise<boolean> {
+  return await invoke<boolean>('set_launch_at_login', { enabled });
+}
+
+export interface RelaunchOffer {
+  /** Error that left the services failed */
+  reason: string | null;
+  /** Relaunches still allowed; 0 means relaunching was tried and didn't help */
+  remaining: number;
+  /** Set when the app relaunches on its own after this long */
+  auto_relaunch_in_ms: number | null;
+}
+
+/**
+ * Listen for offers to relaunch after services fail and can't recover
+ */
+export async function onRelaunchOffered(
+  callback: (offer: RelaunchOffer) => void
+): Promise<() => void> {
+  if (!isTauri()) {
+    return () => { /* no-op */ };
+  }
+
+  return await listen<RelaunchOffer>('relaunch-offered', (e) => { callback(e.payload); });
+}
+
+/**
+ * Relaunch the app, shutting services down first
+ */
+export async function relaunchApp(): Promise<void> {
+  await invoke('relaunch_app');
+}
+
+export interface PineconeIndexStats {
+  total_vector_count: number;
+  /** Fraction of capacity in use (pod indexes only) */
+  index_fullness: number;
+  namespaces: Record<string, unknown>;
+}
+
+export interface PineconeIndexStatus {
+  index_name: string;
+  exists: boolean;
+  ready: boolean;
+  state: string | null;
+  dimension: number | null;
+  metric: string | null;
+  stats: PineconeIndexStats | null;
+  /** Why the backend can't use the index as it is */
+  problems: string[];
+}
+
+/**
+ * Check the configured Pinecone index and report its stats
+ */
+export async function checkPineconeIndex(): Promise<PineconeIndexStatus> {
+  return await invoke<PineconeIndexStatus>('check_pinecone_index');
+}
+
+/**
+ * Create the configured Pinecone index if it doesn't exist
+ */
+export async function createPineconeIndex(): Promise<PineconeIndexStatus> {
+  return await invoke<PineconeIndexStatus>('create_pinecone_index');
+}
+
+export interface GitHubTokenCheck {
+  login: string | null;
+  /** Scopes granted; null for fine-grained tokens */
+  scopes: string[] | null;
+  missing_scopes: string[];
+  rate_limit: { limit: number; remaining: number; reset_at: number } | null;
+  /** Unix epoch seconds; null if the token doesn't expire */
+  expires_at: number | null;
+  expiring_soon: boolean;
+  warnings: string[];
+  checked_at: number;
+}
+
+/**
+ * Check the configured GitHub token's scopes, rate limit and expiry
+ */
+export async function checkGitHubToken(): Promise<GitHubTokenCheck> {
+  return await invoke<GitHubTokenCheck>('check_github_token');
+}
+
+export interface GitRootStatus {
+  path: string;
+  exists: boolean;
+  is_dir: boolean;
+  repository_count: number;
+  /** Why the backend can't serve repositories from this root */
+  problem: string | null;
+}
+
+export interface GitRepository {
+  name: string;
+  path: string;
+  /** Root the repository was found under */
+  root: string;
+}
+
+/**
+ * Check git repository roots; pass a comma-separated value to check it
+ * before saving, or nothing to check the configured roots
+ */
+export async function validateGitRoots(roots?: string): Promise<GitRootStatus[]> {
+  return await invoke<GitRootStatus[]>('validate_git_roots', { roots: roots ?? null });
+}
+
+/**
+ * List git repositories under the configured roots
+ */
+export async function listRepositories(): Promise<GitRepository[]> {
+  if (!isTauri()) {
+    return [];
+  }
+  return await invoke<GitRepository[]>('list_repositories');
+}
+
+export interface AudioInputDevice {
+  id: string;
+  name: string;
+  is_default: boolean;
+}
+
+export interface VoiceProviderCheck {
+  provider: 'deepgram' | 'elevenlabs' | 'openai-tts';
+  role: 'speech_to_text' | 'text_to_speech';
+  status: 'not_configured' | 'ready' | 'unauthorized' | 'unreachable';
+  latency_ms: number | null;
+  error: string | null;
+}
+
+export interface VoiceSetup {
+  ready: boolean;
+  microphone:
+    | 'not_determined'
+    | 'restricted'
+    | 'denied'
+    | 'authorized'
+    | 'write_only'
+    | 'unavailable';
+  /** Empty where devices can't be listed natively; use the webview's list */
+  input_devices: AudioInputDevice[];
+  providers: VoiceProviderCheck[];
+  problems: string[];
+}
+
+/**
+ * Check microphone access, input devices and voice providers before
+ * recording; shows the microphone prompt if the user hasn't been asked
+ */
+export async function checkVoiceSetup(): Promise<VoiceSetup> {
+  return await invoke<VoiceSetup>('check_voice_setup');
+}
+
+export interface SelectedAudioDevice {
+  id: string;
+  /** Device name, used to find the device where ids differ */
+  name: string | null;
+}
+
+export interface AudioDevices {
+  /** Empty where devices can't be listed natively; use the webview's list */
+  devices: AudioInputDevice[];
+  selected: SelectedAudioDevice | null;
+}
+
+/**
+ * List audio input devices and the one recordings use
+ */
+export async function listAudioDevices(): Promise<AudioDevices> {
+  return await invoke<AudioDevices>('list_audio_devices');
+}
+
+/**
+ * Select the input device for recordings, or the system default with null
+ */
+export async function setAudioDevice(id: string | null): Promise<SelectedAudioDevice | null> {
+  return await invoke<SelectedAudioDevice | null>('set_audio_device', { id });
+}
+
+/**
+ * Listen for the selected input device changing
+ */
+export async function onAudioDeviceChanged(
+  callback: (device: SelectedAudioDevice | null) => void
+): Promise<() => void> {
+  if (!isTauri()) {
+    return () => { /* no-op */ };
+  }
+
+  return await listen<SelectedAudioDevice | null>('audio-device-changed', (e) => { callback(e.payload); });
+}
+
 /**
  * Listen for navigation events from the tray menu
  */
@@ -298,6 +1174,223 @@ export async function onOpenReportIssue(callback: () => void): Promise<() => voi
   return await listen('open-report-issue', () => { callback(); });
 }
 
+/**
+ * Replace the notes listed under Recent Notes in the tray menu
+ */
+export async function setTrayRecentNotes(notes: { id: string; title: string }[]): Promise<void> {
+  if (!isTauri()) {
+    return;
+  }
+
+  try {
+    await invoke('set_tray_recent_notes', { notes });
+  } catch (e) {
+    loggers.tauri.warn('Failed to update tray recent notes:', e);
+  }
+}
+
+export interface TraySettings {
+  icon_style: 'monochrome' | 'color';
+  show_status_title: boolean;
+}
+
+/**
+ * Get the tray icon style and whether activity is shown beside the icon
+ */
+export async function getTraySettings(): Promise<TraySettings> {
+  return await invoke<TraySettings>('get_tray_settings');
+}
+
+/**
+ * Update the tray icon style and status title, applied immediately
+ */
+export async function setTraySettings(settings: TraySettings): Promise<void> {
+  await invoke('set_tray_settings', { settings });
+}
+
+export type NotificationSeverity = 'info' | 'warning' | 'error' | 'critical';
+export type NotificationRoute = 'system' | 'tray_badge' | 'log';
+
+export interface NotificationRule {
+  source?: string | null;
+  min_severity?: NotificationSeverity | null;
+  quiet_hours?: boolean | null;
+  focused?: boolean | null;
+  route: NotificationRoute;
+}
+
+export interface NotificationRules {
+  rules: NotificationRule[];
+  quiet_hours: { start: string; end: string } | null;
+  default_route: NotificationRoute;
+}
+
+/**
+ * Get the rules deciding which shell notices become system notifications
+ */
+export async function getNotificationRules(): Promise<NotificationRules> {
+  return await invoke<NotificationRules>('get_notification_rules');
+}
+
+/**
+ * Replace the notification rules; the first matching rule decides the route
+ */
+export async function setNotificationRules(rules: NotificationRules): Promise<void> {
+  await invoke('set_notification_rules', { rules });
+}
+
+export interface FocusSettings {
+  /** Notice sources held back during a session; "app" is the webview's own */
+  blocked_sources: string[];
+  /** Shortcut run at the start of a session, e.g. one turning on a Focus */
+  start_shortcut: string | null;
+  end_shortcut: string | null;
+}
+
+export interface ActiveFocusSession {
+  id: string;
+  note_id: string | null;
+  /** Unix epoch seconds */
+  started_at: number;
+  ends_at: number;
+  blocked_sources: string[];
+  held_notices: number;
+}
+
+export interface FocusSession {
+  id: string;
+  note_id: string | null;
+  started_at: number;
+  ended_at: number;
+  planned_minutes: number;
+  focused_seconds: number;
+  /** Whether it ran its full length rather than being ended early */
+  completed: boolean;
+  held_notices: number;
+  /** Whether the backend recorded it */
+  logged: boolean;
+}
+
+/**
+ * Start a focus session, optionally on a note
+ */
+export async function startFocusSession(
+  durationMinutes: number,
+  noteId?: string
+): Promise<ActiveFocusSession> {
+  return await invoke<ActiveFocusSession>('start_focus_session', { durationMinutes, noteId });
+}
+
+/**
+ * End the running focus session early
+ */
+export async function endFocusSession(): Promise<FocusSession> {
+  return await invoke<FocusSession>('end_focus_session');
+}
+
+/**
+ * Get the running focus session, if any
+ */
+export async function getFocusSession(): Promise<ActiveFocusSession | null> {
+  return await invoke<ActiveFocusSession | null>('get_focus_session');
+}
+
+/**
+ * List finished focus sessions, most recent first
+ */
+export async function listFocusSessions(): Promise<FocusSession[]> {
+  return await invoke<FocusSession[]>('list_focus_sessions');
+}
+
+/**
+ * Get the focus session settings
+ */
+export async function getFocusSettings(): Promise<FocusSettings> {
+  return await invoke<FocusSettings>('get_focus_settings');
+}
+
+/**
+ * Update the focus session settings
+ */
+export async function setFocusSettings(settings: FocusSettings): Promise<void> {
+  await invoke('set_focus_settings', { settings });
+}
+
+/**
+ * Listen for a focus session ending, on time or early
+ */
+export async function onFocusSessionEnded(
+  callback: (session: FocusSession) => void
+): Promise<() => void> {
+  if (!isTauri()) {
+    return () => { /* no-op */ };
+  }
+
+  return await listen<FocusSession>('focus-session-ended', (e) => { callback(e.payload); });
+}
+
+export interface Reminder {
+  id: string;
+  note_id: string;
+  /** Unix epoch seconds */
+  at: number;
+  message: string;
+  created_at: number;
+  /** Fired and waiting to be snoozed or completed */
+  fired: boolean;
+  snooze_count: number;
+}
+
+/**
+ * Schedule a reminder on a note; `at` is an ISO 8601 date and time with an offset
+ */
+export async function scheduleReminder(noteId: string, at: string, message: string): Promise<Reminder> {
+  return await invoke<Reminder>('schedule_reminder', { noteId, at, message });
+}
+
+/**
+ * List reminders, soonest first, including fired ones not yet completed
+ */
+export async function listReminders(): Promise<Reminder[]> {
+  return await invoke<Reminder[]>('list_reminders');
+}
+
+export async function snoozeReminder(id: string, minutes: number): Promise<Reminder> {
+  return await invoke<Reminder>('snooze_reminder', { id, minutes });
+}
+
+export async function completeReminder(id: string): Promise<void> {
+  await invoke('complete_reminder', { id });
+}
+
+export async function cancelReminder(id: string): Promise<void> {
+  await invoke('cancel_reminder', { id });
+}
+
+/**
+ * Listen for reminders coming due, to offer snooze and complete
+ */
+export async function onReminderDue(
+  callback: (reminder: Reminder) => void
+): Promise<() => void> {
+  if (!isTauri()) {
+    return () => { /* no-op */ };
+  }
+
+  return await listen<Reminder>('reminder-due', (e) => { callback(e.payload); });
+}
+
+/**
+ * Listen for a note picked from the tray's Recent Notes menu
+ */
+export async function onOpenNote(callback: (noteId: string) => void): Promise<() => void> {
+  if (!isTauri()) {
+    return () => { /* no-op */ };
+  }
+
+  return await listen<string>('open-note', (e) => { callback(e.payload); });
+}
+
 /**
  * Listen for Tauri events with type-safe event names
  */
@@ -309,7 +1402,8 @@ export type TauriEvent =
   | 'create-new-note'
   | 'create-new-chat'
   | 'open-documentation'
-  | 'open-report-issue';
+  | 'open-report-issue'
+  | 'open-note';
 
 export async function onTauriEvent(
   event: TauriEvent,
@@ -0,0 +1,109 @@
+import { useCallback, useEffect, useMemo, useState } from 'react';
+import { useParams } from 'react-router-dom';
+import ReactMarkdown from 'react-markdown';
+import remarkGfm from 'remark-gfm';
+import { notesService } from '../services';
+import { exitPresentationMode } from '../lib/tauri-bridge';
+import type { Note } from '../types/notes';
+
+/**
+ * Split a note into slides on horizontal rules (---) outside code blocks
+ */
+export function splitSlides(content: string): string[] {
+  const slides: string[][] = [[]];
+  let inCode = false;
+  for (const line of content.split('\n')) {
+    if (line.trimStart().startsWith('```')) {
+      inCode = !inCode;
+    }
+    if (!inCode && /^\s*---+\s*$/.test(line)) {
+      slides.push([]);
+    } else {
+      slides[slides.length - 1].push(line);
+    }
+  }
+  return slides.map((lines) => lines.join('\n').trim()).filter((slide) => slide.length > 0);
+}
+
+/**
+ * A note shown as full-screen slides, opened by the desktop app's
+ * presentation window. Arrow keys, space and clicks move between slides;
+ * Escape ends the presentation.
+ */
+export function PresentationPage() {
+  const { id } = useParams<{ id: string }>();
+  const [note, setNote] = useState<Note | null>(null);
+  const [error, setError] = useState<string | null>(null);
+  const [index, setIndex] = useState(0);
+
+  useEffect(() => {
+    if (!id) return;
+    notesService
+      .getById(id)
+      .then(setNote)
+      .catch((e: unknown) => { setError(e instanceof Error ? e.message : String(e)); });
+  }, [id]);
+
+  const slides = useMemo(
+    () => (note ? [`# ${note.title}`, ...splitSlides(note.content)] : []),
+    [note]
+  );
+
+  const go = useCallback(
+    (step: number) => { setIndex((current) => Math.min(Math.max(current + step, 0), slides.length - 1)); },
+    [slides.length]
+  );
+
+  useEffect(() => {
+    const onKey = (event: KeyboardEvent) => {
+      switch (event.key) {
+        case 'ArrowRight':
+        case 'ArrowDown':
+        case 'PageDown':
+        case ' ':
+          go(1);
+          break;
+        case 'ArrowLeft':
+        case 'ArrowUp':
+        case 'PageUp':
+          go(-1);
+          break;
+        case 'Home':
+          setIndex(0);
+          break;
+        case 'End':
+          setIndex(slides.length - 1);
+          break;
+        case 'Escape':
+          void exitPresentationMode();
+          break;
+        default:
+          return;
+      }
+      event.preventDefault();
+    };
+    window.addEventListener('keydown', onKey);
+    return () => { window.removeEventListener('keydown', onKey); };
+  }, [go, slides.length]);
+
+  return (
+    <div
+      className="h-screen w-screen flex flex-col items-center justify-center select-none"
+      style={{ background: 'var(--background)', color: 'var(--text-primary)', cursor: 'pointer' }}
+      onClick={() => { go(1); }}
+    >
+      {error ? (
+        <p>Couldn't load this note: {error}</p>
+      ) : (
+        <article className="prose max-w-4xl w-full px-16" style={{ fontSize: '1.75rem' }}>
+          <ReactMarkdown remarkPlugins={[remarkGfm]}>{slides[index] ?? ''}</ReactMarkdown>
+        </article>
+      )}
+      {slides.length > 0 && (
+        <div className="fixed bottom-6 right-8 text-sm opacity-60">
+          {index + 1} / {slides.length}
+        </div>
+      )}
+    </div>
+  );
+}
@@ -0,0 +1,53 @@
+import { useEffect, useState } from 'react';
+import { useParams } from 'react-router-dom';
+import ReactMarkdown from 'react-markdown';
+import remarkGfm from 'remark-gfm';
+import { notesService } from '../services';
+import { notifyPrintPageReady } from '../lib/tauri-bridge';
+import type { Note } from '../types/notes';
+
+/**
+ * Print-optimized view of one note, opened by the desktop app in its own
+ * window for PDF export and printing
+ */
+export function PrintNotePage() {
+  const { id } = useParams<{ id: string }>();
+  const [note, setNote] = useState<Note | null>(null);
+  const [error, setError] = useState<string | null>(null);
+
+  useEffect(() => {
+    if (!id) return;
+    notesService
+      .getById(id)
+      .then(setNote)
+      .catch((e: unknown) => { setError(e instanceof Error ? e.message : String(e)); });
+  }, [id]);
+
+  useEffect(() => {
+    if (!note && !error) return;
+    // Wait a frame so the note is laid out before the app prints it
+    const frame = requestAnimationFrame(() => { void notifyPrintPageReady(error); });
+    return () => { cancelAnimationFrame(frame); };
+  }, [note, error]);
+
+  if (error) {
+    return <p style={{ padding: '2rem', color: '#b91c1c' }}>Couldn't load this note: {error}</p>;
+  }
+  if (!note) {
+    return null;
+  }
+
+  return (
+    <article
+      className="prose max-w-none"
+      style={{ background: '#fff', color: '#111', padding: '2rem', minHeight: '100vh' }}
+    >
+      <h1>{note.title}</h1>
+      <p style={{ color: '#6b7280', fontSize: '0.875rem' }}>
+        {new Date(note.updatedAt).toLocaleString()}
+        {note.tags.length > 0 && ` · ${note.tags.map((tag) => `#${tag}`).join(' ')}`}
+      </p>
+      <ReactMarkdown remarkPlugins={[remarkGfm]}>{note.content}</ReactMarkdown>
+    </article>
+  );
+}
@@ -1,4 +1,10 @@
-import type { AudioRecorderOptions, AudioPlayerOptions, VADCallbacks } from '../features/voice/types/voice-types';
+import type {
+  AudioRecorderOptions,
+  AudioPlayerOptions,
+  VADCallbacks,
+  PreferredAudioDevice,
+  AudioDeviceChangeEvent,
+} from '../features/voice/types/voice-types';
 
 // ============================================================================
 // Microphone Access
@@ -7,19 +13,33 @@ import type { AudioRecorderOptions, AudioPlayerOptions, VADCallbacks } from '../
 /**
  * Request access to the user's microphone
  * @param sampleRate - Sample rate for audio capture (default: 16000, Grok Voice uses 24000)
+ * @param deviceId - Webview device id to record from; falls back to the default
+ * device if it's gone
  */
-export async function requestMicrophoneAccess(sampleRate = 16000): Promise<MediaStream> {
+export async function requestMicrophoneAccess(sampleRate = 16000, deviceId?: string): Promise<MediaStream> {
+  const constraints = (exactDevice?: string): MediaStreamConstraints => ({
+    audio: {
+      channelCount: 1,
+      sampleRate,
+      echoCancellation: true,
+      noiseSuppression: true,
+      autoGainControl: true,
+      ...(exactDevice ? { deviceId: { exact: exactDevice } } : {}),
+    },
+  });
+
   try {
-    const stream = await navigator.mediaDevices.getUserMedia({
-      audio: {
-        channelCount: 1,
-        sampleRate,
-        echoCancellation: true,
-        noiseSuppression: true,
-        autoGainControl: true,
-      },
-    });
-    return stream;
+    try {
+      return await navigator.mediaDevices.getUserMedia(constraints(deviceId));
+    } catch (error) {
+      const deviceGone = error instanceof DOMException &&
+        (error.name === 'OverconstrainedError' || error.name === 'NotFoundError');
+      if (!deviceId || !deviceGone) {
+        throw error;
+      }
+      console.warn('Selected microphone unavailable, using the default device');
+      return await navigator.mediaDevices.getUserMedia(constraints());
+    }
   } catch (error) {
     if (error instanceof DOMException) {
       if (error.name === 'NotAllowedError') {
@@ -32,6 +52,31 @@ export async function requestMicrophoneAccess(sampleRate = 16000): Promise<Media
   }
 }
 
+/**
+ * List the webview's audio inputs; labels are empty until microphone access
+ * has been granted
+ */
+export async function listAudioInputs(): Promise<MediaDeviceInfo[]> {
+  const devices = await navigator.mediaDevices.enumerateDevices();
+  return devices.filter(device => device.kind === 'audioinput');
+}
+
+/**
+ * Find the webview device id for a preferred device, by id and then by name
+ * (native ids differ from the webview's)
+ */
+export function resolveAudioInput(
+  inputs: MediaDeviceInfo[],
+  preferred: PreferredAudioDevice | null | undefined
+): string | undefined {
+  if (!preferred) {
+    return undefined;
+  }
+  const match = inputs.find(input => input.deviceId === preferred.id) ??
+    inputs.find(input => !!preferred.name && input.label === preferred.name);
+  return match?.deviceId;
+}
+
 // ============================================================================
 // Audio Recorder using AudioWorklet
 // ============================================================================
@@ -42,8 +87,11 @@ export class AudioRecorder {
   private workletNode: AudioWorkletNode | null = null;
   private sourceNode: MediaStreamAudioSourceNode | null = null;
   private onDataCallback: ((data: Float32Array) => void) | null = null;
+  private onDeviceChangeCallback: ((event: AudioDeviceChangeEvent) => void) | null = null;
   private isRecording = false;
-  private options: Required<AudioRecorderOptions>;
+  private isSwitching = false;
+  private options: Required<Omit<AudioRecorderOptions, 'preferredDevice'>>;
+  private preferredDevice: PreferredAudioDevice | null;
 
   constructor(options: AudioRecorderOptions = {}) {
     this.options = {
@@ -51,6 +99,7 @@ export class AudioRecorder {
       channelCount: options.channelCount ?? 1,
       mimeType: options.mimeType ?? 'audio/webm',
     };
+    this.preferredDevice = options.preferredDevice ?? null;
   }
 
   async start(stream: MediaStream): Promise<void> {
@@ -59,6 +108,7 @@ export class AudioRecorder {
     }
 
     this.stream = stream;
+    this.watchDevices();
     this.audioContext = new AudioContext({
       sampleRate: this.options.sampleRate,
     });
@@ -122,6 +172,7 @@ export class AudioRecorder {
 
   stop(): void {
     this.isRecording = false;
+    this.unwatchDevices();
 
     if (this.sourceNode) {
       this.sourceNode.disconnect();
@@ -163,9 +214,110 @@ export class AudioRecorder {
     this.onDataCallback = callback;
   }
 
+  /**
+   * Called when the input device changes mid-recording
+   */
+  onDeviceChange(callback: (event: AudioDeviceChangeEvent) => void): void {
+    this.onDeviceChangeCallback = callback;
+  }
+
+  /**
+   * Change the preferred input; a running recording switches to it
+   */
+  async setPreferredDevice(device: PreferredAudioDevice | null): Promise<void> {
+    this.preferredDevice = device;
+    if (this.stream) {
+      await this.switchDevice();
+    }
+  }
+
   get recording(): boolean {
     return this.isRecording;
   }
+
+  private get currentTrack(): MediaStreamTrack | undefined {
+    return this.stream?.getAudioTracks()[0];
+  }
+
+  private readonly handleTrackEnded = (): void => {
+    void this.handleDevicesChanged();
+  };
+
+  private readonly handleDevicesChanged = async (): Promise<void> => {
+    const track = this.currentTrack;
+    if (!track || this.isSwitching) {
+      return;
+    }
+    const inputs = await listAudioInputs();
+    const currentId = track.getSettings().deviceId;
+    const currentGone = track.readyState === 'ended' ||
+      !inputs.some(input => input.deviceId === currentId);
+    // Move back to the preferred device when it's plugged in again
+    const preferredId = resolveAudioInput(inputs, this.preferredDevice);
+    if (currentGone || (preferredId && preferredId !== currentId)) {
+      await this.switchDevice();
+    }
+  };
+
+  private watchDevices(): void {
+    this.currentTrack?.addEventListener('ended', this.handleTrackEnded);
+    navigator.mediaDevices.addEventListener('devicechange', this.handleDevicesChanged);
+  }
+
+  private unwatchDevices(): void {
+    this.currentTrack?.removeEventListener('ended', this.handleTrackEnded);
+    navigator.mediaDevices.removeEventListener('devicechange', this.handleDevicesChanged);
+  }
+
+  /**
+   * Reconnect the recording to the preferred device, or the default one,
+   * pausing if no input is left rather than recording silence
+   */
+  private async switchDevice(): Promise<void> {
+    const processor = this.workletNode ??
+      (this as unknown as Record<string, AudioNode | undefined>).scriptProcessor;
+    if (!this.audioContext || !processor || this.isSwitching) {
+      return;
+    }
+
+    this.isSwitching = true;
+    const wasRecording = this.isRecording;
+    try {
+      const inputs = await listAudioInputs();
+      const deviceId = resolveAudioInput(inputs, this.preferredDevice);
+      const stream = await requestMicrophoneAccess(this.options.sampleRate, deviceId);
+      if (!this.audioContext) {
+        // Stopped while the new device was opening
+        stream.getTracks().forEach(track => track.stop());
+        return;
+      }
+
+      this.unwatchDevices();
+      this.sourceNode?.disconnect();
+      this.stream?.getTracks().forEach(track => track.stop());
+
+      this.stream = stream;
+      this.sourceNode = this.audioContext.createMediaStreamSource(stream);
+      this.sourceNode.connect(processor);
+      this.watchDevices();
+      this.isRecording = wasRecording;
+
+      this.onDeviceChangeCallback?.({
+        type: 'switched',
+        deviceLabel: this.currentTrack?.label || null,
+      });
+    } catch (error) {
+      console.warn('No microphone available, pausing recording', error);
+      this.isRecording = false;
+      this.onDeviceChangeCallback?.({
+        type: 'paused',
+        deviceLabel: null,
+        reason: error instanceof Error ? error.message : 'No microphone available',
+      });
+    } finally {
+      this.isSwitching = false;
+    }
+  }
 }
 
 /**
@@ -7,7 +7,14 @@ set -e
 
 SCRIPT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)"
 PROJECT_DIR="$(dirname "$SCRIPT_DIR")"
-RESOURCE_DIR="$PROJECT_DIR/frontend/src-tauri/resources/postgresql"
+
+# The app looks for a build matching its OS and architecture first
+# (e.g. postgresql/macos-aarch64), so one bundle can hold both
+ARCH="$(uname -m)"
+if [ "$ARCH" = "arm64" ]; then
+    ARCH="aarch64"
+fi
+RESOURCE_DIR="$PROJECT_DIR/frontend/src-tauri/resources/postgresql/macos-$ARCH"
 
 echo "🐘 Bundling PostgreSQL for Second Brain..."
 echo ""
@@ -59,6 +66,10 @@ BINARIES=(
     "pg_ctl"
     "pg_isready"
     "psql"
+    "pg_dump"
+    "pg_restore"
+    "pg_dumpall"
+    "pg_upgrade"
     "createdb"
     "dropdb"
 )